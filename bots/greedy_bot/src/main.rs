@@ -1,8 +1,8 @@
 use std::collections::BTreeSet;
 
 use clap::Parser;
-use gomori::{Board, Card, CardToPlay, CardsSet, Color, Field, PlayTurnResponse, Rank};
-use gomori_bot_utils::Bot;
+use gomori::{Board, Card, CardToPlay, CardsSet, Color, Field, Hand, PlayTurnResponse, Rank};
+use gomori_bot_utils::{Bot, GamePhase, GameSetup};
 use rand::rngs::StdRng;
 use rand::{seq::SliceRandom, SeedableRng};
 
@@ -80,22 +80,23 @@ impl GreedyBot {
 }
 
 impl Bot for GreedyBot {
-    fn new_game(&mut self, _color: Color) {}
+    fn new_game(&mut self, _color: Color, _setup: GameSetup) {}
 
-    fn play_first_turn(&mut self, cards: [Card; 5]) -> Card {
-        *cards.choose(&mut self.rng).unwrap()
+    fn play_first_turn(&mut self, cards: Hand) -> Card {
+        *Vec::from(cards).choose(&mut self.rng).unwrap()
     }
 
     fn play_turn(
         &mut self,
-        cards: [Card; 5],
+        cards: Hand,
         fields: Vec<Field>,
         _cards_won_by_opponent: CardsSet,
+        phase: GamePhase,
     ) -> PlayTurnResponse {
         let mut cards_to_play = vec![];
 
-        let mut board = Board::new(&fields);
-        let mut remaining_cards: BTreeSet<Card> = BTreeSet::from(cards);
+        let mut board = Board::new_with_rules(&fields, phase.rules);
+        let mut remaining_cards: BTreeSet<Card> = BTreeSet::from_iter(cards);
 
         while let Some(card_to_play) = self.best_card_placement(&board, &remaining_cards) {
             cards_to_play.push(card_to_play);
@@ -106,6 +107,10 @@ impl Bot for GreedyBot {
             }
             board = plan.execute();
         }
-        PlayTurnResponse(cards_to_play)
+        PlayTurnResponse::play_or_skip(
+            &Board::new_with_rules(&fields, phase.rules),
+            cards.cards(),
+            cards_to_play,
+        )
     }
 }
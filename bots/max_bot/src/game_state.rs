@@ -1,5 +1,6 @@
 use gomori::{
-    BitBoard, BitBoardIter, Board, CalculatedEffects, Card, CardToPlay, CardsSet, Field, Rank,
+    BitBoard, BitBoardIter, Board, CalculatedEffects, Card, CardToPlay, CardsSet, Field, Hand,
+    Rank, Rules,
 };
 
 #[derive(Clone, Debug)]
@@ -10,10 +11,10 @@ pub struct GameState {
 }
 
 impl GameState {
-    pub fn initial(cards: [Card; 5], fields: Vec<Field>) -> Self {
+    pub fn initial(cards: Hand, fields: Vec<Field>, rules: Rules) -> Self {
         Self {
-            cards: CardsSet::from_iter(cards),
-            board: Board::new(&fields),
+            cards: cards.cards(),
+            board: Board::new_with_rules(&fields, rules),
             score_delta: 0,
         }
     }
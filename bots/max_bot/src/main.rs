@@ -1,5 +1,5 @@
-use gomori::{Card, CardToPlay, CardsSet, Color, Field, PlayTurnResponse, Rank};
-use gomori_bot_utils::Bot;
+use gomori::{Card, CardToPlay, CardsSet, Color, Field, Hand, PlayTurnResponse, Rank};
+use gomori_bot_utils::{Bot, GamePhase, GameSetup};
 
 use clap::Parser;
 use max_bot::GameState;
@@ -41,9 +41,9 @@ fn initialize_logging(level: LevelFilter) {
 }
 
 impl Bot for DFSBot {
-    fn new_game(&mut self, _color: Color) {}
+    fn new_game(&mut self, _color: Color, _setup: GameSetup) {}
 
-    fn play_first_turn(&mut self, cards: [Card; 5]) -> Card {
+    fn play_first_turn(&mut self, cards: Hand) -> Card {
         // Don't waste a "special" card on the first move
         for card in cards {
             match card.rank {
@@ -53,13 +53,19 @@ impl Bot for DFSBot {
                 }
             }
         }
-        cards[0]
+        cards.iter().next().unwrap()
     }
 
-    fn play_turn(&mut self, cards: [Card; 5], fields: Vec<Field>, _: CardsSet) -> PlayTurnResponse {
-        let root = GameState::initial(cards, fields);
+    fn play_turn(
+        &mut self,
+        cards: Hand,
+        fields: Vec<Field>,
+        _: CardsSet,
+        phase: GamePhase,
+    ) -> PlayTurnResponse {
+        let root = GameState::initial(cards, fields, phase.rules);
         let cards_to_play = search_unroll(&root);
-        PlayTurnResponse(cards_to_play)
+        PlayTurnResponse::play_or_skip(&root.board, root.cards, cards_to_play)
     }
 }
 
@@ -133,5 +139,18 @@ fn search_unroll(state0: &GameState) -> Vec<CardToPlay> {
             }
         }
     }
-    best_actions.into_iter().filter_map(|x| x).collect()
+    best_actions.into_iter().flatten().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use gomori::sample_player_view;
+    use gomori_bot_utils::assert_color_symmetric;
+
+    use super::*;
+
+    #[test]
+    fn plays_the_same_with_colors_swapped() {
+        assert_color_symmetric(&mut DFSBot {}, &sample_player_view());
+    }
 }
@@ -0,0 +1,49 @@
+use gomori::{Card, Color, Hand, PlayTurnResponse, PlayerView, Rank};
+use gomori_bot_utils::{Bot, CardCounter, CardCountingWrapper, GameSetup, HasCardCounter};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+use crate::{Ismcts, IsmctsConfig};
+
+/// The bot that chooses its turns with [`Ismcts`], based on the cards it has counted.
+pub struct MctsBot {
+    ismcts: Ismcts,
+    counter: CardCounter,
+    rng: StdRng,
+}
+
+impl MctsBot {
+    /// The bot, wrapped so that it counts cards.
+    pub fn new(config: IsmctsConfig, seed: u64) -> CardCountingWrapper<Self> {
+        CardCountingWrapper::new(MctsBot {
+            ismcts: Ismcts::new(config),
+            counter: CardCounter::default(),
+            rng: StdRng::seed_from_u64(seed),
+        })
+    }
+}
+
+impl HasCardCounter for MctsBot {
+    fn get_counter(&mut self) -> &mut CardCounter {
+        &mut self.counter
+    }
+}
+
+impl Bot for MctsBot {
+    fn new_game(&mut self, _color: Color, _setup: GameSetup) {}
+
+    fn play_first_turn(&mut self, cards: Hand) -> Card {
+        // There is nothing to search yet, so just keep the face cards and aces
+        cards
+            .iter()
+            .min_by_key(|card| match card.rank {
+                Rank::Jack | Rank::Queen | Rank::King | Rank::Ace => 1,
+                _ => 0,
+            })
+            .unwrap()
+    }
+
+    fn play_turn_view(&mut self, view: PlayerView) -> PlayTurnResponse {
+        self.ismcts.choose_turn(&view, &self.counter, &mut self.rng)
+    }
+}
@@ -0,0 +1,273 @@
+use gomori::{determinize, CardCounter, PlayTurnResponse, PlayerView, TurnValidator};
+use gomori_search::{GameStateTrait, GomoriState, SearchMove};
+use rand::seq::{IteratorRandom, SliceRandom};
+use rand::Rng;
+
+/// Settings for [`Ismcts`].
+#[derive(Clone, Copy, Debug)]
+pub struct IsmctsConfig {
+    /// How many determinized games are simulated per turn.
+    pub iterations: usize,
+    /// The exploration constant of the UCB formula. Higher values try out more moves,
+    /// lower values focus on the moves that look best so far.
+    pub exploration: f64,
+}
+
+impl Default for IsmctsConfig {
+    fn default() -> Self {
+        Self {
+            iterations: 2000,
+            exploration: 0.4,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+struct Node {
+    /// The move that leads to this node from its parent, `None` for the root.
+    mv: Option<SearchMove>,
+    /// The player who made the move.
+    player: usize,
+    parent: Option<usize>,
+    children: Vec<usize>,
+    visits: u32,
+    /// In how many iterations the node's move was legal when its parent was visited.
+    availability: u32,
+    /// The sum of the results for `player`, with 1 for a win and 0.5 for a tie.
+    total_reward: f64,
+}
+
+/// Single-observer information set Monte Carlo tree search.
+///
+/// Each iteration samples the hidden cards with [`determinize()`], descends the tree
+/// through the moves that are legal in that sample, and finishes the game with moves
+/// that greedily win cards. Since the samples differ, so do the legal moves, which the UCB formula accounts
+/// for by counting how often each move was available.
+///
+/// The moves are the single cards of a turn, see [`SearchMove`], so the tree shares the
+/// statistics of turns that start with the same cards.
+///
+/// In games with more than two players, the simulated games are played between the player
+/// and the opponent who plays next.
+pub struct Ismcts {
+    config: IsmctsConfig,
+    nodes: Vec<Node>,
+}
+
+impl Ismcts {
+    pub fn new(config: IsmctsConfig) -> Self {
+        Self {
+            config,
+            nodes: Vec::new(),
+        }
+    }
+
+    /// Chooses the cards to play in a turn, given everything the player has observed.
+    pub fn choose_turn(
+        &mut self,
+        view: &PlayerView,
+        counter: &CardCounter,
+        rng: &mut impl Rng,
+    ) -> PlayTurnResponse {
+        self.nodes = vec![Node {
+            mv: None,
+            player: 1,
+            parent: None,
+            children: Vec::new(),
+            visits: 0,
+            availability: 0,
+            total_reward: 0.0,
+        }];
+        for _ in 0..self.config.iterations {
+            let mut state = determinize(view, counter, rng);
+            // The search only knows two players, so with more, it plays against the next one
+            state.opponents.truncate(1);
+            self.iterate(GomoriState::new(state, GomoriState::card_difference), rng);
+        }
+
+        // Follow the most visited moves until the turn ends. They are legal in every
+        // determinization, since a turn only depends on the player's own hand.
+        let mut validator = TurnValidator::new(view.board.clone(), view.hand.cards());
+        let mut cards_to_play = Vec::new();
+        let mut node = 0;
+        while let Some(&child) = self.nodes[node]
+            .children
+            .iter()
+            .max_by_key(|&&child| self.nodes[child].visits)
+        {
+            match self.nodes[child].mv {
+                Some(SearchMove::Play(ctp)) => {
+                    validator
+                        .play(ctp)
+                        .expect("The tree only contains legal moves");
+                    cards_to_play.push(ctp);
+                }
+                _ => break,
+            }
+            node = child;
+        }
+        // If the tree doesn't reach the end of the turn, finish it randomly
+        while !validator.can_finish() {
+            let ctp = validator
+                .playable_cards()
+                .into_iter()
+                .flat_map(|card| validator.board().legal_plays(card))
+                .choose(rng)
+                .expect("A playable card has a legal play");
+            validator.play(ctp).unwrap();
+            cards_to_play.push(ctp);
+        }
+        PlayTurnResponse::play_or_skip(&view.board, view.hand.cards(), cards_to_play)
+    }
+
+    fn iterate(&mut self, mut state: GomoriState, rng: &mut impl Rng) {
+        // Selection and expansion
+        let mut node = 0;
+        while !state.is_terminal() {
+            let moves = state.legal_moves();
+            let mut untried = moves.clone();
+            for child_idx in 0..self.nodes[node].children.len() {
+                let child = self.nodes[node].children[child_idx];
+                let child_mv = self.nodes[child].mv.unwrap();
+                if moves.contains(&child_mv) {
+                    self.nodes[child].availability += 1;
+                    untried.retain(|&mv| mv != child_mv);
+                }
+            }
+            if let Some(&mv) = untried.choose(rng) {
+                let child = self.nodes.len();
+                self.nodes.push(Node {
+                    mv: Some(mv),
+                    player: state.side_to_move(),
+                    parent: Some(node),
+                    children: Vec::new(),
+                    visits: 0,
+                    availability: 1,
+                    total_reward: 0.0,
+                });
+                self.nodes[node].children.push(child);
+                state = state.apply(mv);
+                node = child;
+                break;
+            }
+            let child = self.select_child(node, &moves);
+            state = state.apply(self.nodes[child].mv.unwrap());
+            node = child;
+        }
+
+        // Simulation
+        while !state.is_terminal() {
+            let mv = playout_move(&state, rng);
+            state = state.apply(mv);
+        }
+
+        // Backpropagation
+        let cards_won = [0, 1].map(|idx| state.player(idx).cards_won.len());
+        let mut current = Some(node);
+        while let Some(idx) = current {
+            let node = &mut self.nodes[idx];
+            let player = node.player;
+            node.visits += 1;
+            node.total_reward += match cards_won[player].cmp(&cards_won[1 - player]) {
+                std::cmp::Ordering::Greater => 1.0,
+                std::cmp::Ordering::Equal => 0.5,
+                std::cmp::Ordering::Less => 0.0,
+            };
+            current = node.parent;
+        }
+    }
+
+    // The child with the best upper confidence bound, among those whose move is legal
+    fn select_child(&self, node: usize, moves: &[SearchMove]) -> usize {
+        let mut best = None;
+        let mut best_ucb = f64::NEG_INFINITY;
+        for &child in &self.nodes[node].children {
+            let Node {
+                mv,
+                visits,
+                availability,
+                total_reward,
+                ..
+            } = self.nodes[child];
+            if !moves.contains(&mv.unwrap()) {
+                continue;
+            }
+            let visits = f64::from(visits.max(1));
+            let ucb = total_reward / visits
+                + self.config.exploration * (f64::from(availability).ln() / visits).sqrt();
+            if ucb > best_ucb {
+                best_ucb = ucb;
+                best = Some(child);
+            }
+        }
+        best.expect("All legal moves have been tried")
+    }
+}
+
+// Random moves rarely win cards, which makes most playouts end in a tie. Greedily
+// winning as many cards as possible is a better guess at how the game will go.
+fn playout_move(state: &GomoriState, rng: &mut impl Rng) -> SearchMove {
+    let mut best_moves = Vec::new();
+    let mut most_cards_won = 0;
+    for mv in state.legal_moves() {
+        let cards_won = match mv {
+            SearchMove::Play(ctp) => state
+                .board()
+                .calculate(ctp)
+                .map_or(0, |effects| effects.cards_won.len()),
+            SearchMove::EndTurn => 0,
+        };
+        if cards_won > most_cards_won {
+            most_cards_won = cards_won;
+            best_moves.clear();
+        }
+        if cards_won == most_cards_won {
+            best_moves.push(mv);
+        }
+    }
+    *best_moves
+        .choose(rng)
+        .expect("Non-terminal states have legal moves")
+}
+
+#[cfg(test)]
+mod tests {
+    use gomori::{execute_turn, random_game_in_progress, sample_player_view, CardsSet};
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    use super::*;
+
+    #[test]
+    fn chosen_turns_are_legal() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut ismcts = Ismcts::new(IsmctsConfig {
+            iterations: 50,
+            ..Default::default()
+        });
+        for _ in 0..10 {
+            let game = random_game_in_progress(&mut rng);
+            let color = game.player.hand.iter().next().unwrap().color();
+            // Everything the player has seen, as a CardCountingWrapper would have counted it
+            let on_board = game
+                .board
+                .iter()
+                .fold(CardsSet::new(), |cards, (_, _, field)| {
+                    cards | field.all_cards()
+                });
+            let mut counter = CardCounter::new(color);
+            counter.draw_pile = CardsSet::from_iter(game.player.draw_pile.clone());
+            counter.cards_won_self = game.player.cards_won;
+            counter.cards_won_opponent = game.opponent.cards_won;
+            counter.available_cards_opponent &= !on_board & !game.opponent.cards_won;
+
+            let view = PlayerView {
+                board: game.board.clone(),
+                hand: game.player.hand,
+                ..sample_player_view()
+            };
+            let turn = ismcts.choose_turn(&view, &counter, &mut rng);
+            execute_turn(&game.player, &game.board, turn).unwrap();
+        }
+    }
+}
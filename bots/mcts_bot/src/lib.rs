@@ -0,0 +1,4 @@
+mod bot;
+mod ismcts;
+pub use bot::*;
+pub use ismcts::*;
@@ -0,0 +1,29 @@
+use clap::Parser;
+use gomori_bot_utils::Bot;
+use mcts_bot::{IsmctsConfig, MctsBot};
+
+#[derive(Parser)]
+struct Args {
+    /// How many games to simulate per turn
+    #[arg(short, long, default_value_t = IsmctsConfig::default().iterations)]
+    iterations: usize,
+
+    /// The exploration constant, higher values try out more moves
+    #[arg(short, long, default_value_t = IsmctsConfig::default().exploration)]
+    exploration: f64,
+
+    /// RNG seed
+    #[arg(long)]
+    seed: Option<u64>,
+}
+
+fn main() -> anyhow::Result<()> {
+    let args = Args::parse();
+    let seed = args.seed.unwrap_or_else(rand::random);
+    let config = IsmctsConfig {
+        iterations: args.iterations,
+        exploration: args.exploration,
+    };
+
+    MctsBot::new(config, seed).run()
+}
@@ -0,0 +1,51 @@
+use gomori::{Board, Card, CardsSet, Color, Field, Hand, PlayTurnResponse};
+use gomori_bot_utils::{Bot, GamePhase, GameSetup};
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+
+use crate::{choose_turn, QModel};
+
+/// The bot that plays the cards that the model rates highest, see [`choose_turn()`].
+pub struct NnBot {
+    model: QModel,
+    temperature: f32,
+    rng: StdRng,
+}
+
+impl NnBot {
+    pub fn new(model: QModel, temperature: f32, seed: u64) -> Self {
+        Self {
+            model,
+            temperature,
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+}
+
+impl Bot for NnBot {
+    fn new_game(&mut self, _color: Color, _setup: GameSetup) {}
+
+    fn play_first_turn(&mut self, cards: Hand) -> Card {
+        // There is no board to rate the cards on yet
+        *Vec::from(cards).choose(&mut self.rng).unwrap()
+    }
+
+    fn play_turn(
+        &mut self,
+        cards: Hand,
+        fields: Vec<Field>,
+        _: CardsSet,
+        phase: GamePhase,
+    ) -> PlayTurnResponse {
+        let board = Board::new_with_rules(&fields, phase.rules);
+        choose_turn(
+            &board,
+            cards.cards(),
+            self.temperature,
+            &mut self.rng,
+            |board, cards_to_play| self.model.evaluate(board, cards_to_play),
+        )
+        .expect("Could not evaluate the cards with the model")
+    }
+}
@@ -0,0 +1,6 @@
+mod bot;
+mod model;
+mod policy;
+pub use bot::*;
+pub use model::*;
+pub use policy::*;
@@ -0,0 +1,27 @@
+use std::path::PathBuf;
+
+use clap::Parser;
+use gomori_bot_utils::Bot;
+use nn_bot::{NnBot, QModel};
+
+#[derive(Parser)]
+struct Args {
+    /// The ONNX model that rates the cards, see the docs of `QModel` for its inputs and output
+    #[arg(short, long)]
+    model: PathBuf,
+
+    /// Zero to always play the best card, higher values sample more of the other cards
+    #[arg(short, long, default_value_t = 0.0)]
+    temperature: f32,
+
+    /// RNG seed
+    #[arg(long)]
+    seed: Option<u64>,
+}
+
+fn main() -> anyhow::Result<()> {
+    let args = Args::parse();
+    let seed = args.seed.unwrap_or_else(rand::random);
+
+    NnBot::new(QModel::load(&args.model)?, args.temperature, seed).run()
+}
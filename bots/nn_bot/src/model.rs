@@ -0,0 +1,169 @@
+use std::path::Path;
+
+use anyhow::Context;
+use gomori::{encode_board, encode_card_to_play, Board, CardToPlay, BOARD_SIZE, NUM_CHANNELS};
+use tract_onnx::prelude::*;
+
+/// An ONNX model that rates cards to play, trained on the data written by the judge's
+/// `export_training_data` tool.
+///
+/// The model must have two inputs, in this order:
+///
+/// * `boards`: `float32` of shape `(N, NUM_CHANNELS, BOARD_SIZE, BOARD_SIZE)`, see
+///   [`gomori::encode_board()`]
+/// * `cards_to_play`: `float32` of shape `(N, 5)`, see [`gomori::encode_card_to_play()`]
+///
+/// Its output must be `N` values of type `float32`, one for each card, with the outcome that
+/// the model expects for the player who plays it: 1 for a win, 0 for a tie and -1 for a loss.
+/// The batch size `N` should be symbolic, since all cards that can be played next are rated
+/// at once.
+pub struct QModel {
+    plan: TypedRunnableModel<TypedModel>,
+}
+
+impl QModel {
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let inner = || -> anyhow::Result<Self> { Self::new(onnx().model_for_path(path)?) };
+        inner().with_context(|| format!("Could not load model '{}'", path.display()))
+    }
+
+    fn new(model: InferenceModel) -> anyhow::Result<Self> {
+        let plan = model.into_optimized()?.into_runnable()?;
+        Ok(Self { plan })
+    }
+
+    /// Rates each of the cards, which must be legal to play on the board.
+    pub fn evaluate(
+        &self,
+        board: &Board,
+        cards_to_play: &[CardToPlay],
+    ) -> anyhow::Result<Vec<f32>> {
+        let n = cards_to_play.len();
+        let size = BOARD_SIZE as usize;
+        let encoded_board = encode_board(board);
+        let boards: Vec<f32> = std::iter::repeat_n(encoded_board, n).flatten().collect();
+        let encoded_cards: Vec<f32> = cards_to_play
+            .iter()
+            .flat_map(|&ctp| encode_card_to_play(board, ctp))
+            .map(f32::from)
+            .collect();
+        let outputs = self.plan.run(tvec!(
+            Tensor::from_shape(&[n, NUM_CHANNELS, size, size], &boards)?.into(),
+            Tensor::from_shape(&[n, 5], &encoded_cards)?.into(),
+        ))?;
+        let values: Vec<f32> = outputs[0].to_array_view::<f32>()?.iter().copied().collect();
+        anyhow::ensure!(
+            values.len() == n,
+            "The model returned {} values for {} cards",
+            values.len(),
+            n
+        );
+        Ok(values)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use gomori::{card, Field};
+    use tract_onnx::pb::{
+        attribute_proto::AttributeType, tensor_proto::DataType, tensor_shape_proto,
+        tensor_shape_proto::dimension, type_proto, AttributeProto, GraphProto, ModelProto,
+        NodeProto, OperatorSetIdProto, TensorShapeProto, TypeProto, ValueInfoProto,
+    };
+
+    use super::*;
+
+    // A model whose output is the sum of all the values of its inputs
+    fn sum_model() -> ModelProto {
+        let value_info = |name: &str, dims: &[i64]| {
+            let dim = std::iter::once(dimension::Value::DimParam(String::from("N")))
+                .chain(dims.iter().map(|&dim| dimension::Value::DimValue(dim)))
+                .map(|value| tensor_shape_proto::Dimension {
+                    value: Some(value),
+                    ..Default::default()
+                })
+                .collect();
+            ValueInfoProto {
+                name: String::from(name),
+                r#type: Some(TypeProto {
+                    value: Some(type_proto::Value::TensorType(type_proto::Tensor {
+                        elem_type: DataType::Float as i32,
+                        shape: Some(TensorShapeProto { dim }),
+                    })),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            }
+        };
+        let node = |op_type: &str, inputs: &[&str], output: &str, axes: Vec<i64>| {
+            let attribute = if axes.is_empty() {
+                vec![]
+            } else {
+                vec![
+                    AttributeProto {
+                        name: String::from("axes"),
+                        r#type: AttributeType::Ints as i32,
+                        ints: axes,
+                        ..Default::default()
+                    },
+                    AttributeProto {
+                        name: String::from("keepdims"),
+                        r#type: AttributeType::Int as i32,
+                        i: 0,
+                        ..Default::default()
+                    },
+                ]
+            };
+            NodeProto {
+                op_type: String::from(op_type),
+                input: inputs.iter().map(|&input| String::from(input)).collect(),
+                output: vec![String::from(output)],
+                attribute,
+                ..Default::default()
+            }
+        };
+        let size = i64::from(BOARD_SIZE);
+        ModelProto {
+            ir_version: 7,
+            opset_import: vec![OperatorSetIdProto {
+                domain: String::new(),
+                version: 11,
+            }],
+            graph: Some(GraphProto {
+                node: vec![
+                    node("ReduceSum", &["boards"], "board_sums", vec![1, 2, 3]),
+                    node("ReduceSum", &["cards_to_play"], "card_sums", vec![1]),
+                    node("Add", &["board_sums", "card_sums"], "values", vec![]),
+                ],
+                input: vec![
+                    value_info("boards", &[NUM_CHANNELS as i64, size, size]),
+                    value_info("cards_to_play", &[5]),
+                ],
+                output: vec![value_info("values", &[])],
+                ..Default::default()
+            }),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn evaluates_all_cards_at_once() {
+        let model = QModel::new(onnx().model_for_proto_model(&sum_model()).unwrap()).unwrap();
+        let board = Board::new(&[Field {
+            i: 3,
+            j: 3,
+            top_card: Some(card!("7♥")),
+            hidden_cards: Default::default(),
+        }]);
+        let cards_to_play = board.legal_plays(card!("7♠"));
+        let board_sum: f32 = encode_board(&board).iter().sum();
+        let expected: Vec<f32> = cards_to_play
+            .iter()
+            .map(|&ctp| {
+                let card_sum: f32 = encode_card_to_play(&board, ctp).map(f32::from).iter().sum();
+                board_sum + card_sum
+            })
+            .collect();
+        assert_eq!(model.evaluate(&board, &cards_to_play).unwrap(), expected);
+    }
+}
@@ -0,0 +1,97 @@
+use gomori::{Board, CardToPlay, CardsSet, PlayTurnResponse, TurnValidator};
+use rand::distributions::WeightedIndex;
+use rand::prelude::Distribution;
+use rand::Rng;
+
+/// Plays cards until the turn may end, choosing each card by the values that `evaluate`
+/// gives to all cards that could be played next, e.g. [`QModel::evaluate()`](crate::QModel::evaluate).
+///
+/// With a `temperature` of zero, the card with the highest value is played. Otherwise, a card
+/// is sampled with a probability proportional to `exp(value / temperature)`, so that higher
+/// temperatures try out more cards.
+pub fn choose_turn(
+    board: &Board,
+    hand: CardsSet,
+    temperature: f32,
+    rng: &mut impl Rng,
+    mut evaluate: impl FnMut(&Board, &[CardToPlay]) -> anyhow::Result<Vec<f32>>,
+) -> anyhow::Result<PlayTurnResponse> {
+    let mut validator = TurnValidator::new(board.clone(), hand);
+    let mut cards_to_play = Vec::new();
+    while !validator.can_finish() {
+        let candidates: Vec<CardToPlay> = validator
+            .playable_cards()
+            .into_iter()
+            .flat_map(|card| validator.board().legal_plays(card))
+            .collect();
+        let values = evaluate(validator.board(), &candidates)?;
+        let ctp = candidates[select(&values, temperature, rng)?];
+        validator
+            .play(ctp)
+            .expect("Only legal cards are candidates");
+        cards_to_play.push(ctp);
+    }
+    Ok(PlayTurnResponse::play_or_skip(board, hand, cards_to_play))
+}
+
+fn select(values: &[f32], temperature: f32, rng: &mut impl Rng) -> anyhow::Result<usize> {
+    let max = values.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+    anyhow::ensure!(max.is_finite(), "The values {:?} are not finite", values);
+    if temperature <= 0.0 {
+        return Ok(values.iter().position(|&value| value == max).unwrap());
+    }
+    // Subtracting the maximum keeps the weights from overflowing
+    let weights = values
+        .iter()
+        .map(|value| ((value - max) / temperature).exp());
+    Ok(WeightedIndex::new(weights)?.sample(rng))
+}
+
+#[cfg(test)]
+mod tests {
+    use gomori::{card, Field};
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    use super::*;
+
+    #[test]
+    fn plays_the_best_cards_until_the_turn_may_end() {
+        let board = Board::new(&[Field {
+            i: 0,
+            j: 0,
+            top_card: Some(card!("7♥")),
+            hidden_cards: Default::default(),
+        }]);
+        let hand = CardsSet::from_iter([card!("7♠"), card!("2♦"), card!("3♣")]);
+        // Prefer playing the seven on top of the other seven, which is a combo, then the
+        // lowest card
+        let evaluate = |board: &Board, cards_to_play: &[CardToPlay]| {
+            Ok(cards_to_play
+                .iter()
+                .map(|ctp| {
+                    if board.get(ctp.i, ctp.j).is_some() {
+                        100.0
+                    } else {
+                        -(ctp.card.rank as i32 as f32)
+                    }
+                })
+                .collect())
+        };
+        let mut rng = StdRng::seed_from_u64(0);
+        let response = choose_turn(&board, hand, 0.0, &mut rng, evaluate).unwrap();
+        let cards_to_play = response.cards_to_play();
+        let cards: Vec<_> = cards_to_play.iter().map(|ctp| ctp.card).collect();
+        assert_eq!(cards, [card!("7♠"), card!("2♦")]);
+        assert_eq!((cards_to_play[0].i, cards_to_play[0].j), (0, 0));
+
+        // At a very high temperature, every card is played first sometimes
+        let first_cards: CardsSet = (0..100)
+            .map(|_| {
+                let response = choose_turn(&board, hand, 1000.0, &mut rng, evaluate).unwrap();
+                response.cards_to_play()[0].card
+            })
+            .collect();
+        assert_eq!(first_cards, hand);
+    }
+}
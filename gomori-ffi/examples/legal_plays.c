@@ -0,0 +1,23 @@
+// Prints the legal plays of a card on a small board.
+#include <stdio.h>
+
+#include "gomori.h"
+
+int main(void) {
+    GomoriBoard *board = gomori_board_new(
+        "[{\"i\":0,\"j\":0,\"top_card\":{\"suit\":\"♥\",\"rank\":\"7\"},\"hidden_cards\":[]}]");
+    if (board == NULL) {
+        fprintf(stderr, "Error: %s\n", gomori_last_error());
+        return 1;
+    }
+    char *plays = gomori_board_legal_plays(board, "[{\"suit\":\"♠\",\"rank\":\"K\"}]");
+    if (plays == NULL) {
+        fprintf(stderr, "Error: %s\n", gomori_last_error());
+        gomori_board_free(board);
+        return 1;
+    }
+    printf("%s\n", plays);
+    gomori_string_free(plays);
+    gomori_board_free(board);
+    return 0;
+}
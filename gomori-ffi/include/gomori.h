@@ -0,0 +1,63 @@
+/*
+ * C bindings for the gomori library.
+ *
+ * Cards, fields and plays are passed as JSON strings in the same format as in the
+ * protocol. Functions that can fail return NULL, and the error message can then be
+ * retrieved with gomori_last_error().
+ */
+#ifndef GOMORI_H
+#define GOMORI_H
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* An opaque handle to a board. */
+typedef struct GomoriBoard GomoriBoard;
+
+/*
+ * Returns the message of the last error that occurred on this thread, or NULL if there
+ * was none. The string is owned by the library and valid until the next failing call
+ * on this thread.
+ */
+const char *gomori_last_error(void);
+
+/* Creates a board from a JSON array of fields. Must be freed with gomori_board_free(). */
+GomoriBoard *gomori_board_new(const char *fields_json);
+
+/* Frees a board. Does nothing if board is NULL. */
+void gomori_board_free(GomoriBoard *board);
+
+/*
+ * Returns the fields of the board as a JSON array, sorted like in the protocol.
+ * Must be freed with gomori_string_free().
+ */
+char *gomori_board_to_json(const GomoriBoard *board);
+
+/*
+ * Returns a JSON array of all the ways to legally play any of the cards in the JSON
+ * array cards_json. Must be freed with gomori_string_free().
+ */
+char *gomori_board_legal_plays(const GomoriBoard *board, const char *cards_json);
+
+/*
+ * Calculates the effects of playing a card, and returns them as a JSON object with the
+ * keys "cards_won" and "combo". Returns NULL if the card can't be played.
+ * Must be freed with gomori_string_free().
+ */
+char *gomori_board_calculate(const GomoriBoard *board, const char *card_to_play_json);
+
+/*
+ * Returns the new board after playing a card. Returns NULL if the card can't be played.
+ * Must be freed with gomori_board_free().
+ */
+GomoriBoard *gomori_board_play_card(const GomoriBoard *board, const char *card_to_play_json);
+
+/* Frees a string returned by this library. Does nothing if s is NULL. */
+void gomori_string_free(char *s);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* GOMORI_H */
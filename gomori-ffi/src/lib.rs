@@ -0,0 +1,282 @@
+//! C bindings for the gomori library, see `include/gomori.h`.
+//!
+//! Cards, fields and plays are passed as JSON strings in the same format as in the protocol.
+//! Functions that can fail return a null pointer, and the error message can then be retrieved
+//! with [`gomori_last_error()`].
+
+use std::cell::RefCell;
+use std::ffi::{c_char, CStr, CString};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::ptr;
+
+use anyhow::Context;
+use gomori::{Board, Card, CardToPlay, Field};
+use serde::Serialize;
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+/// An opaque handle to a [`Board`].
+pub struct GomoriBoard(Board);
+
+/// The JSON version of [`CalculatedEffects`](gomori::CalculatedEffects).
+#[derive(Serialize)]
+struct CalculatedEffects {
+    cards_won: Vec<Card>,
+    combo: bool,
+}
+
+// Runs the function, and turns errors and panics into a null pointer and a stored error message.
+fn ffi_call<T>(f: impl FnOnce() -> anyhow::Result<*mut T>) -> *mut T {
+    let result = catch_unwind(AssertUnwindSafe(f)).unwrap_or_else(|panic| {
+        let msg = panic
+            .downcast_ref::<String>()
+            .map(String::as_str)
+            .or_else(|| panic.downcast_ref::<&str>().copied())
+            .unwrap_or("unknown panic");
+        Err(anyhow::anyhow!("Panicked: {}", msg))
+    });
+    match result {
+        Ok(ptr) => ptr,
+        Err(err) => {
+            let msg = format!("{:#}", err).replace('\0', " ");
+            LAST_ERROR.with(|last_error| {
+                *last_error.borrow_mut() = Some(CString::new(msg).unwrap());
+            });
+            ptr::null_mut()
+        }
+    }
+}
+
+unsafe fn str_arg<'a>(ptr: *const c_char, name: &str) -> anyhow::Result<&'a str> {
+    anyhow::ensure!(!ptr.is_null(), "{} is null", name);
+    CStr::from_ptr(ptr)
+        .to_str()
+        .with_context(|| format!("{} is not valid UTF-8", name))
+}
+
+unsafe fn json_arg<'a, T: serde::Deserialize<'a>>(
+    ptr: *const c_char,
+    name: &str,
+) -> anyhow::Result<T> {
+    let json = str_arg(ptr, name)?;
+    serde_json::from_str(json).with_context(|| format!("Could not parse {}", name))
+}
+
+unsafe fn board_arg<'a>(board: *const GomoriBoard) -> anyhow::Result<&'a Board> {
+    anyhow::ensure!(!board.is_null(), "board is null");
+    Ok(&(*board).0)
+}
+
+fn json_string<T: Serialize>(value: &T) -> anyhow::Result<*mut c_char> {
+    Ok(CString::new(serde_json::to_string(value)?)?.into_raw())
+}
+
+fn new_board(board: Board) -> *mut GomoriBoard {
+    Box::into_raw(Box::new(GomoriBoard(board)))
+}
+
+/// Returns the message of the last error that occurred on this thread, or null if there was none.
+///
+/// The string is owned by the library and valid until the next failing call on this thread.
+#[no_mangle]
+pub extern "C" fn gomori_last_error() -> *const c_char {
+    LAST_ERROR.with(|last_error| {
+        last_error
+            .borrow()
+            .as_ref()
+            .map_or(ptr::null(), |msg| msg.as_ptr())
+    })
+}
+
+/// Creates a board from a JSON array of fields. Returns null on error.
+///
+/// The board must be freed with [`gomori_board_free()`].
+///
+/// # Safety
+///
+/// `fields_json` must be null or a valid pointer to a null-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn gomori_board_new(fields_json: *const c_char) -> *mut GomoriBoard {
+    ffi_call(|| {
+        let fields: Vec<Field> = json_arg(fields_json, "fields_json")?;
+        Ok(new_board(Board::try_new(&fields)?))
+    })
+}
+
+/// Frees a board. Does nothing if `board` is null.
+///
+/// # Safety
+///
+/// `board` must be null or a board returned by this library that has not been freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn gomori_board_free(board: *mut GomoriBoard) {
+    if !board.is_null() {
+        drop(Box::from_raw(board));
+    }
+}
+
+/// Returns the fields of the board as a JSON array, sorted like in the protocol.
+///
+/// The string must be freed with [`gomori_string_free()`].
+///
+/// # Safety
+///
+/// `board` must be null or a valid board.
+#[no_mangle]
+pub unsafe extern "C" fn gomori_board_to_json(board: *const GomoriBoard) -> *mut c_char {
+    ffi_call(|| json_string(&board_arg(board)?.to_fields_vec()))
+}
+
+/// Returns a JSON array of all the ways to legally play any of the cards in the JSON array
+/// `cards_json`. Returns null on error.
+///
+/// The string must be freed with [`gomori_string_free()`].
+///
+/// # Safety
+///
+/// `board` must be null or a valid board, and `cards_json` must be null or a valid pointer to
+/// a null-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn gomori_board_legal_plays(
+    board: *const GomoriBoard,
+    cards_json: *const c_char,
+) -> *mut c_char {
+    ffi_call(|| {
+        let board = board_arg(board)?;
+        let cards: Vec<Card> = json_arg(cards_json, "cards_json")?;
+        let plays: Vec<CardToPlay> = cards
+            .into_iter()
+            .flat_map(|card| board.legal_plays(card))
+            .collect();
+        json_string(&plays)
+    })
+}
+
+/// Calculates the effects of playing a card, and returns them as a JSON object with the keys
+/// `cards_won` and `combo`. Returns null on error, including when the card can't be played.
+///
+/// The string must be freed with [`gomori_string_free()`].
+///
+/// # Safety
+///
+/// `board` must be null or a valid board, and `card_to_play_json` must be null or a valid
+/// pointer to a null-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn gomori_board_calculate(
+    board: *const GomoriBoard,
+    card_to_play_json: *const c_char,
+) -> *mut c_char {
+    ffi_call(|| {
+        let board = board_arg(board)?;
+        let effects = board.calculate(json_arg(card_to_play_json, "card_to_play_json")?)?;
+        json_string(&CalculatedEffects {
+            cards_won: Vec::from_iter(effects.cards_won),
+            combo: effects.combo,
+        })
+    })
+}
+
+/// Returns the new board after playing a card. Returns null on error, including when the card
+/// can't be played.
+///
+/// The board must be freed with [`gomori_board_free()`].
+///
+/// # Safety
+///
+/// `board` must be null or a valid board, and `card_to_play_json` must be null or a valid
+/// pointer to a null-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn gomori_board_play_card(
+    board: *const GomoriBoard,
+    card_to_play_json: *const c_char,
+) -> *mut GomoriBoard {
+    ffi_call(|| {
+        let board = board_arg(board)?;
+        let ctp = json_arg(card_to_play_json, "card_to_play_json")?;
+        Ok(new_board(board.play_card(ctp)?))
+    })
+}
+
+/// Frees a string returned by this library. Does nothing if `s` is null.
+///
+/// # Safety
+///
+/// `s` must be null or a string returned by this library that has not been freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn gomori_string_free(s: *mut c_char) {
+    if !s.is_null() {
+        drop(CString::from_raw(s));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn c_string(s: &str) -> CString {
+        CString::new(s).unwrap()
+    }
+
+    unsafe fn take_string(s: *mut c_char) -> String {
+        assert!(!s.is_null());
+        let string = CStr::from_ptr(s).to_str().unwrap().to_owned();
+        gomori_string_free(s);
+        string
+    }
+
+    unsafe fn last_error() -> String {
+        CStr::from_ptr(gomori_last_error())
+            .to_str()
+            .unwrap()
+            .to_owned()
+    }
+
+    #[test]
+    fn play_a_card() {
+        unsafe {
+            let fields =
+                c_string(r#"[{"i":0,"j":0,"top_card":{"suit":"♥","rank":"7"},"hidden_cards":[]}]"#);
+            let board = gomori_board_new(fields.as_ptr());
+            assert!(!board.is_null());
+
+            let cards = c_string(r#"[{"suit":"♠","rank":"7"}]"#);
+            let plays = take_string(gomori_board_legal_plays(board, cards.as_ptr()));
+            let plays: Vec<CardToPlay> = serde_json::from_str(&plays).unwrap();
+            // Anywhere in the 7 x 7 area around the 7♥, including on top of it
+            assert_eq!(plays.len(), 49);
+
+            let ctp = c_string(r#"{"card":{"suit":"♠","rank":"7"},"i":0,"j":0}"#);
+            let effects = take_string(gomori_board_calculate(board, ctp.as_ptr()));
+            assert_eq!(effects, r#"{"cards_won":[],"combo":true}"#);
+
+            let new_board = gomori_board_play_card(board, ctp.as_ptr());
+            let fields = take_string(gomori_board_to_json(new_board));
+            assert!(fields.contains(r#""top_card":{"suit":"♠","rank":"7"}"#));
+
+            gomori_board_free(new_board);
+            gomori_board_free(board);
+        }
+    }
+
+    #[test]
+    fn errors_are_reported() {
+        unsafe {
+            assert!(gomori_board_new(ptr::null()).is_null());
+            assert_eq!(last_error(), "fields_json is null");
+
+            let fields = c_string("[]");
+            assert!(gomori_board_new(fields.as_ptr()).is_null());
+            assert_eq!(last_error(), "A board needs at least one field");
+
+            let fields =
+                c_string(r#"[{"i":0,"j":0,"top_card":{"suit":"♥","rank":"7"},"hidden_cards":[]}]"#);
+            let board = gomori_board_new(fields.as_ptr());
+            let ctp = c_string(r#"{"card":{"suit":"♠","rank":"2"},"i":0,"j":0}"#);
+            assert!(gomori_board_calculate(board, ctp.as_ptr()).is_null());
+            assert!(last_error().contains("incompatible card"));
+            gomori_board_free(board);
+        }
+    }
+}
@@ -1,19 +1,59 @@
 from gomori._gomori import *
-from typing import List
+from abc import ABC, abstractmethod
+from typing import List, Optional
 
 import json
+import sys
 
-class Bot:
-	def new_game(self, color: Color):
-		raise NotImplementedError()
+# Make the judge submodule of the extension module importable as gomori.judge
+sys.modules["gomori.judge"] = judge
 
-	def play_first_turn(self, cards: List[Card]) -> Card:
-		raise NotImplementedError()
+class Bot(ABC):
+	"""Base class for bots, to be passed to `run_bot()`."""
 
+	@abstractmethod
+	def new_game(
+		self,
+		color: Color,
+		num_players: int = 2,
+		seat: int = 0,
+		suit: Optional[Suit] = None
+	) -> None:
+		"""Called at the start of every game, with the color of the cards that this bot plays.
+
+		`num_players` is the number of players in the game, and `seat` is the bot's
+		position in the turn order, where 0 is the player who plays the first turn. With
+		more than two players, the bot only plays with the cards of `suit`.
+		Bots may leave out these three parameters, and are then called without them.
+		"""
+
+	@abstractmethod
+	def play_first_turn(self, cards: List[Card], i: int = 0, j: int = 0) -> Card:
+		"""Returns the card to play in the first turn of a game. It will be placed at (i, j),
+		which is (0, 0) unless the judge was told to place it elsewhere.
+		Bots may leave out `i` and `j`, and are then called without them.
+		"""
+
+	@abstractmethod
 	def play_turn(
 		self,
 		cards: List[Card],
 		board: Board,
-		cards_won_by_opponent: CardsSet
+		cards_won_by_opponent: CardsSet,
+		draw_pile_size: int = 0,
+		opponent_draw_pile_size: int = 0,
+		turn_number: int = 0
 	) -> PlayTurnResponse:
-		raise NotImplementedError()
+		"""Returns the cards to play in this turn, in order, or `PlayTurnResponse.skip()`
+		if none of the cards can be played.
+
+		The board follows the rules of the game that the judge plays, which may be a
+		variant of the standard rules.
+
+		`cards_won_by_opponent` contains only the cards won by the opponent in their
+		previous turn, not all cards they won so far in this game.
+
+		`draw_pile_size` and `opponent_draw_pile_size` are the numbers of cards left in
+		the draw piles, and `turn_number` counts the turns of the game, starting at 1.
+		Bots may leave out these three parameters, and are then called without them.
+		"""
@@ -0,0 +1,335 @@
+from typing import Any, Callable, Iterator, List, Optional, Set, Tuple
+
+class IllegalCardPlayed(Exception):
+    """Describes why the card cannot be played."""
+
+class IllegalMove(Exception):
+    """Describes why a move is illegal."""
+
+class Suit:
+    Diamond: Suit
+    Heart: Suit
+    Spade: Suit
+    Club: Suit
+    def color(self) -> Color: ...
+
+class Rank:
+    Two: Rank
+    Three: Rank
+    Four: Rank
+    Five: Rank
+    Six: Rank
+    Seven: Rank
+    Eight: Rank
+    Nine: Rank
+    Ten: Rank
+    Jack: Rank
+    Queen: Rank
+    King: Rank
+    Ace: Rank
+
+class Color:
+    Black: Color
+    Red: Color
+    def suits(self) -> List[Suit]: ...
+
+class Card:
+    rank: Rank
+    suit: Suit
+    def __init__(self, rank: Rank, suit: Suit) -> None: ...
+    def __eq__(self, other: object) -> bool: ...
+    def __ne__(self, other: object) -> bool: ...
+    def __lt__(self, other: Card) -> bool: ...
+    def __le__(self, other: Card) -> bool: ...
+    def __gt__(self, other: Card) -> bool: ...
+    def __ge__(self, other: Card) -> bool: ...
+    def __hash__(self) -> int: ...
+    def color(self) -> Color: ...
+    def can_be_placed_on(self, other: Card) -> bool: ...
+
+class CardsSetIter:
+    def __iter__(self) -> CardsSetIter: ...
+    def __next__(self) -> Card: ...
+
+class CardsSet:
+    def __init__(self, cards: List[Card] = ...) -> None: ...
+    def __bool__(self) -> bool: ...
+    def __len__(self) -> int: ...
+    def __contains__(self, card: Card) -> bool: ...
+    def __iter__(self) -> CardsSetIter: ...
+    def __str__(self) -> str:
+        """The cards separated by spaces, e.g. "7♥ 10♦ Q♠"."""
+    @staticmethod
+    def of_color(color: Color) -> CardsSet: ...
+    def __eq__(self, other: object) -> bool: ...
+    def __ne__(self, other: object) -> bool: ...
+    def __lt__(self, other: CardsSet) -> bool:
+        """Whether this is a proper subset of the other set."""
+    def __le__(self, other: CardsSet) -> bool:
+        """Whether this is a subset of the other set."""
+    def __gt__(self, other: CardsSet) -> bool:
+        """Whether this is a proper superset of the other set."""
+    def __ge__(self, other: CardsSet) -> bool:
+        """Whether this is a superset of the other set."""
+    def __hash__(self) -> int: ...
+    def __and__(self, other: CardsSet) -> CardsSet: ...
+    def __or__(self, other: CardsSet) -> CardsSet: ...
+    def __xor__(self, other: CardsSet) -> CardsSet: ...
+    def __invert__(self) -> CardsSet: ...
+    def __iand__(self, other: CardsSet) -> CardsSet: ...
+    def __ior__(self, other: CardsSet) -> CardsSet: ...
+    def __ixor__(self, other: CardsSet) -> CardsSet: ...
+    @property
+    def is_empty(self) -> bool: ...
+    def insert(self, card: Card) -> None: ...
+    def remove(self, card: Card) -> None: ...
+    def difference(self, other: CardsSet) -> CardsSet: ...
+    def is_subset(self, other: CardsSet) -> bool: ...
+    def is_superset(self, other: CardsSet) -> bool: ...
+    def is_disjoint(self, other: CardsSet) -> bool: ...
+    def iter_suits(self) -> List[Tuple[Suit, CardsSet]]: ...
+
+class Field:
+    i: int
+    j: int
+    top_card: Optional[Card]
+    hidden_cards: Set[Card]
+    def __init__(
+        self, i: int, j: int, top_card: Optional[Card] = None, hidden_cards: Set[Card] = ...
+    ) -> None: ...
+    def __eq__(self, other: object) -> bool: ...
+    def __ne__(self, other: object) -> bool: ...
+    def __hash__(self) -> int: ...
+
+class CompactField:
+    def __init__(self, *, top_card: Optional[Card], hidden_cards: CardsSet = ...) -> None: ...
+    def __bool__(self) -> bool: ...
+    def is_empty(self) -> bool: ...
+    @property
+    def top_card(self) -> Optional[Card]: ...
+    def can_place_card(self, card: Card) -> bool: ...
+    @property
+    def num_hidden_cards(self) -> int: ...
+    def place_card(self, card: Card) -> CompactField:
+        """The field with the card on top and the previous top card hidden. Raises a
+        ValueError if the card is already on the field."""
+    def with_top_card(self, top_card: Optional[Card]) -> CompactField:
+        """The field with the top card replaced, i.e. removed, not hidden. Raises a
+        ValueError if the card is one of the hidden cards."""
+    def without_card(self, card: Card) -> CompactField:
+        """The field without the card. Without its top card, the field has no face-up card."""
+    def turn_face_down(self) -> CompactField: ...
+    @property
+    def hidden_cards(self) -> CardsSet: ...
+    def all_cards(self) -> CardsSet: ...
+    def into_field(self, i: int, j: int) -> Field: ...
+
+class FieldHistory:
+    """The cards on a field in the order they were played, see Board.field_history()."""
+    @property
+    def hidden_before(self) -> CardsSet:
+        """The hidden cards that were on the field before the history started, in unknown order."""
+    @property
+    def stack(self) -> List[Tuple[Card, bool]]:
+        """All other cards from the bottom to the top, and whether they face up."""
+    def top_card(self) -> Optional[Card]: ...
+    def all_cards(self) -> CardsSet: ...
+    def to_compact_field(self) -> CompactField: ...
+
+class CardToPlay:
+    def __init__(
+        self,
+        card: Card,
+        i: int,
+        j: int,
+        target_field_for_king_ability: Optional[Tuple[int, int]] = None,
+    ) -> None: ...
+    def __eq__(self, other: object) -> bool: ...
+    def __ne__(self, other: object) -> bool: ...
+    def __hash__(self) -> int: ...
+
+class PlayTurnResponse:
+    cards_to_play: List[CardToPlay]
+    def __init__(self, cards_to_play: List[CardToPlay]) -> None: ...
+    @staticmethod
+    def skip() -> PlayTurnResponse:
+        """Skips the turn, which is only legal if none of the cards can be played."""
+    def is_skip(self) -> bool: ...
+
+class BoundingBox:
+    i_min: int
+    j_min: int
+    i_max: int
+    j_max: int
+    def __init__(self, *, i_min: int, j_min: int, i_max: int, j_max: int) -> None: ...
+    def size_i(self) -> int: ...
+    def size_j(self) -> int: ...
+    def width(self) -> int:
+        """The number of columns, i.e. the same as size_j()."""
+    def height(self) -> int:
+        """The number of rows, i.e. the same as size_i()."""
+    def area(self) -> int: ...
+    def contains(self, i: int, j: int) -> bool: ...
+    def intersection(self, other: BoundingBox) -> Optional[BoundingBox]: ...
+    def union_cover(self, other: BoundingBox) -> BoundingBox:
+        """The smallest bounding box that covers both bounding boxes."""
+    def iter(self) -> List[Tuple[int, int]]:
+        """All coordinates in the bounding box, row by row."""
+    def iter_coords(self) -> List[Tuple[int, int]]:
+        """Deprecated, use iter() instead."""
+    def update(self, i: int, j: int) -> None: ...
+
+class BitBoard:
+    def contains(self, i: int, j: int) -> bool: ...
+    def insert(self, i: int, j: int) -> BitBoard: ...
+    def remove(self, i: int, j: int) -> BitBoard: ...
+    def is_empty(self) -> bool: ...
+    def difference(self, other: BitBoard) -> BitBoard: ...
+    def lines_going_through_point(self, point_i: int, point_j: int) -> BitBoard: ...
+    def __str__(self) -> str:
+        """The coordinates separated by spaces, e.g. "(0, 1) (2, -1)"."""
+    def __len__(self) -> int: ...
+    def __bool__(self) -> bool: ...
+    def __and__(self, other: BitBoard) -> BitBoard: ...
+    def __or__(self, other: BitBoard) -> BitBoard: ...
+    def __xor__(self, other: BitBoard) -> BitBoard: ...
+    def __iand__(self, other: BitBoard) -> BitBoard: ...
+    def __ior__(self, other: BitBoard) -> BitBoard: ...
+    def __ixor__(self, other: BitBoard) -> BitBoard: ...
+    def to_matrix_and_offset(self) -> Tuple[List[List[bool]], Tuple[int, int]]:
+        """The 9 x 9 area around the bitboard's center, and the coordinate of its top left corner."""
+
+class CalculatedEffects:
+    @property
+    def cards_won(self) -> CardsSet: ...
+    @property
+    def combo(self) -> bool: ...
+    def execute(self) -> Board: ...
+    def flipped_fields(self) -> BitBoard:
+        """The fields whose face-up top card is turned face-down by the played face card."""
+    def won_fields(self) -> BitBoard:
+        """The fields that are cleared because their cards are won."""
+
+class Board:
+    def __init__(self, fields: List[Field]) -> None: ...
+    @staticmethod
+    def from_fields_list(fields: List[Tuple[int, int, CompactField]]) -> Board: ...
+    def to_fields(self) -> List[Tuple[int, int, CompactField]]: ...
+    def to_fields_vec(self) -> List[Field]:
+        """The fields, sorted by i first, then j."""
+    def to_numpy(self, dtype: str = "uint8") -> Any:
+        """The board as a NumPy array of shape (19, 7, 7) and dtype "uint8" or "float32", with
+        the top left corner of the bounding box at (3, 3): the suit of the top card (4), its
+        rank (13), the number of hidden cards (1) and the playable area (1). Needs NumPy."""
+    def __len__(self) -> int:
+        """The number of fields with at least one card on them."""
+    def __str__(self) -> str:
+        """The top cards of the board as a grid, see visualize()."""
+    def calculate(self, card_to_play: CardToPlay) -> CalculatedEffects: ...
+    def calculate_all(self, card: Card) -> List[Tuple[CardToPlay, CalculatedEffects]]:
+        """The effects of each of the legal_plays() of the card."""
+    def score_if_played(self, card_to_play: CardToPlay) -> int: ...
+    def play_card(self, card_to_play: CardToPlay) -> Board: ...
+    def bbox(self) -> BoundingBox: ...
+    def playable_area(self) -> BoundingBox: ...
+    def diamonds(self) -> BitBoard: ...
+    def hearts(self) -> BitBoard: ...
+    def spades(self) -> BitBoard: ...
+    def clubs(self) -> BitBoard: ...
+    def suit_counts(self) -> List[int]:
+        """The number of visible cards of each suit, in the order diamonds, hearts, spades, clubs."""
+    def num_cards(self) -> int:
+        """The number of cards on the board, face up or down."""
+    def num_face_down_fields(self) -> int:
+        """The number of fields without a face-up top card."""
+    def occupied(self) -> BitBoard:
+        """The fields with at least one card on them."""
+    def face_down_only(self) -> BitBoard:
+        """The fields without a face-up top card."""
+    def stacks_with_at_least(self, n: int) -> BitBoard:
+        """The fields with at least n cards on them, face up or down."""
+    def valid_king_targets(self, i: int, j: int) -> BitBoard:
+        """The fields that a king played on top of the card at (i, j) may flip."""
+    def orthogonal_neighbors(self, i: int, j: int) -> BitBoard:
+        """The in-bounds fields next to (i, j) horizontally and vertically, which a jack flips."""
+    def diagonal_neighbors(self, i: int, j: int) -> BitBoard:
+        """The in-bounds fields diagonally next to (i, j), which a queen flips."""
+    def possible_to_play_card(self, card: Card) -> bool: ...
+    def must_skip(self, hand: CardsSet) -> bool:
+        """Whether none of the cards can be played, so the turn must be skipped."""
+    def is_locked(self, hands: List[CardsSet]) -> bool:
+        """Whether every player must skip with these hands, which ends the game."""
+    def open_placements_count(self) -> int:
+        """The number of coordinates without a card where a card can be played."""
+    def locations_for_card(self, card: Card) -> BitBoard: ...
+    def empty_playable_locations(self) -> BitBoard:
+        """The coordinates in the playable area without a card on them."""
+    def line_threats(self, suit: Suit) -> List[Tuple[BitBoard, Tuple[int, int]]]:
+        """The places where a card of the suit would complete a line, with that line's cards."""
+    def combo_locations_for_card(self, card: Card) -> BitBoard: ...
+    def locations_for_card_with_policy(
+        self,
+        card: Card,
+        *,
+        combo_only: bool = False,
+        avoid_own_suit_flips: bool = False,
+        mask: Optional[BitBoard] = None,
+    ) -> BitBoard:
+        """Like locations_for_card(), but only combos, placements of jacks and queens that
+        don't flip cards of their own suit, or placements inside the mask, if requested."""
+    def legal_plays(self, card: Card) -> List[CardToPlay]:
+        """All the ways to legally play the card, including every target of a king's ability."""
+    def with_history(self) -> Board:
+        """A copy of the board that records the cards played on it, see history()."""
+    def history(self) -> Optional[List[CardToPlay]]:
+        """The cards played since with_history() was called, or None if they are not recorded."""
+    def field_history(self, i: int, j: int) -> Optional[FieldHistory]:
+        """The cards on the field in the order they were played, or None if the history is not recorded."""
+    def get(self, i: int, j: int) -> Optional[CompactField]: ...
+    def is_in_bounds(self, i: int, j: int) -> bool: ...
+    def zobrist_key(self) -> int:
+        """A hash of the cards on the board, which doesn't depend on the order of the fields."""
+
+def visualize(board: Board) -> str:
+    """Draws the top cards of the board as a grid, with the coordinates of the first row
+    and column. Face-down cards are drawn as 🂠."""
+
+def encode_board(board: Board) -> List[float]:
+    """Encodes the board as 19 planes of 4 x 4 values, relative to its bounding box, in
+    channel-major order: the suit of the top card (4), its rank (13), the number of hidden
+    cards (1) and whether there is a field (1)."""
+
+def encode_card_to_play(board: Board, card_to_play: CardToPlay) -> List[int]:
+    """Encodes a card played on the board as [card, i, j, target_i, target_j], with the
+    card as 4 * rank + suit and the coordinates relative to the board's bounding box."""
+
+class FinishedTurn:
+    """The result of a turn, which rank_turns() passes to the evaluator."""
+    @property
+    def board(self) -> Board:
+        """The board after all cards were played."""
+    @property
+    def remaining_hand(self) -> CardsSet:
+        """The cards from the hand that were not played."""
+    @property
+    def cards_won(self) -> CardsSet:
+        """The cards won over the whole turn."""
+
+def rank_turns(
+    board: Board,
+    cards: List[Card],
+    evaluator: Optional[Callable[[FinishedTurn], float]] = None,
+) -> List[Tuple[PlayTurnResponse, float]]:
+    """Rates the legal turns with the cards on the board, best first. The evaluator rates
+    the result of a turn, higher is better, and defaults to the number of cards won.
+    There are at most 10000 turns, and turns that lead to the same board are only
+    included once."""
+
+def run_bot(bot: object) -> None:
+    """Runs the bot, i.e. answers requests from the judge until the judge is done, which is
+    when it says bye or closes the connection.
+
+    Exceptions raised by the bot's methods are propagated to the caller, as are errors in
+    the communication with the judge, as a RuntimeError. The GIL is released while waiting
+    for the judge, and signal handlers run, so that Ctrl+C raises a KeyboardInterrupt.
+    """
@@ -0,0 +1,22 @@
+from typing import Optional, Tuple
+
+from gomori import Bot
+
+class MatchScore:
+    """The results of a match, with one entry per bot in the order they were passed to `run_match()`."""
+
+    wins: Tuple[int, int]
+    illegal_moves: Tuple[int, int]
+    ties: int
+
+def run_match(
+    bot_a: Bot,
+    bot_b: Bot,
+    num_games: int = 100,
+    seed: Optional[int] = None,
+    stop_on_illegal_move: bool = False,
+) -> MatchScore:
+    """Plays a series of games between two bots, in this process.
+
+    Exceptions raised by the bots' methods are propagated to the caller.
+    """
@@ -1,69 +1,294 @@
-use gomori::{Board, Card, CardsSet, Color, Field, PlayTurnResponse};
-use gomori_bot_utils::Bot;
-use pyo3::{pyfunction, types::PyDict, Py, PyObject, Python};
+use std::sync::mpsc::{sync_channel, Receiver, RecvTimeoutError};
+use std::sync::Mutex;
+use std::time::Duration;
 
-struct PythonBot {
+use gomori::{
+    Board, Card, CardsSet, Color, Deck, Field, Hand, Okay, PlayTurnResponse, Request, Rules,
+};
+use gomori_bot_utils::{protocol_from_env, BotConnection, GamePhase, GameSetup};
+use pyo3::exceptions::{PyRuntimeError, PyTypeError};
+use pyo3::types::PyDict;
+use pyo3::{pyfunction, FromPyObject, Py, PyAny, PyErr, PyObject, PyResult, Python};
+
+/// The methods that a Python bot object needs to have.
+const BOT_METHODS: [&str; 3] = ["new_game", "play_first_turn", "play_turn"];
+
+/// How often to run Python's signal handlers while waiting for the next request.
+const SIGNAL_CHECK_INTERVAL: Duration = Duration::from_millis(100);
+
+/// The arguments of `play_turn` about the game phase, which bots that were written before
+/// they existed don't accept.
+const GAME_PHASE_ARGS: [&str; 3] = ["draw_pile_size", "opponent_draw_pile_size", "turn_number"];
+
+/// The arguments of `new_game` about the players, which bots that were written before
+/// they existed don't accept.
+const GAME_SETUP_ARGS: [&str; 3] = ["num_players", "seat", "suit"];
+
+/// The arguments of `play_first_turn` about where the card will be placed, which bots that
+/// were written before they existed don't accept.
+const FIRST_TURN_ARGS: [&str; 2] = ["i", "j"];
+
+/// Wraps a Python object that implements the methods of `gomori.Bot`.
+pub(crate) struct PythonBot {
     bot: PyObject,
+    /// Whether `new_game` accepts the `GAME_SETUP_ARGS`.
+    accepts_game_setup: bool,
+    /// Whether `play_first_turn` accepts the `FIRST_TURN_ARGS`.
+    accepts_first_turn_location: bool,
+    /// Whether `play_turn` accepts the `GAME_PHASE_ARGS`.
+    accepts_game_phase: bool,
 }
 
-// TODO: Re-evaluate this whole design
-impl gomori_bot_utils::Bot for PythonBot {
-    fn new_game(&mut self, color: Color) {
-        Python::with_gil(|py| {
-            let kwargs = PyDict::new(py);
-            kwargs
-                .set_item("color", Py::new(py, color).unwrap())
-                .unwrap();
-            self.bot
-                .call_method(py, "new_game", (), Some(kwargs))
-                .expect("Call to new_game() failed");
+impl PythonBot {
+    /// Checks that the object has all the methods of a bot.
+    pub(crate) fn new(py: Python, bot: PyObject) -> PyResult<Self> {
+        for method in BOT_METHODS {
+            if !bot.as_ref(py).hasattr(method)? {
+                return Err(PyTypeError::new_err(format!(
+                    "The bot object of type '{}' has no method '{}'. Bots should subclass gomori.Bot.",
+                    bot.as_ref(py).get_type().name()?,
+                    method
+                )));
+            }
+        }
+        let accepts_game_setup = accepts_args(
+            py,
+            bot.getattr(py, "new_game")?.as_ref(py),
+            &GAME_SETUP_ARGS,
+        )?;
+        let accepts_first_turn_location = accepts_args(
+            py,
+            bot.getattr(py, "play_first_turn")?.as_ref(py),
+            &FIRST_TURN_ARGS,
+        )?;
+        let accepts_game_phase = accepts_args(
+            py,
+            bot.getattr(py, "play_turn")?.as_ref(py),
+            &GAME_PHASE_ARGS,
+        )?;
+        Ok(Self {
+            bot,
+            accepts_game_setup,
+            accepts_first_turn_location,
+            accepts_game_phase,
         })
     }
 
-    fn play_first_turn(&mut self, cards: [Card; 5]) -> Card {
-        Python::with_gil(|py| {
-            let kwargs = PyDict::new(py);
-            kwargs
-                .set_item("cards", cards.map(|card| Py::new(py, card).unwrap()))
-                .unwrap();
-            self.bot
-                .call_method(py, "play_first_turn", (), Some(kwargs))
-                .expect("Call to play_first_turn() failed")
-                .extract(py)
-                .expect("play_first_turn() returned wrong type")
-        })
+    pub(crate) fn new_game(&mut self, py: Python, color: Color, setup: GameSetup) -> PyResult<()> {
+        let kwargs = PyDict::new(py);
+        kwargs.set_item("color", Py::new(py, color)?)?;
+        if self.accepts_game_setup {
+            let suit = match setup.deck {
+                Deck::Color(_) => None,
+                Deck::Suit(suit) => Some(Py::new(py, suit)?),
+            };
+            kwargs.set_item("num_players", setup.num_players)?;
+            kwargs.set_item("seat", setup.seat)?;
+            kwargs.set_item("suit", suit)?;
+        }
+        self.bot.call_method(py, "new_game", (), Some(kwargs))?;
+        Ok(())
     }
 
-    fn play_turn(
+    pub(crate) fn play_first_turn(
         &mut self,
-        cards: [Card; 5],
+        py: Python,
+        cards: Hand,
+        i: i8,
+        j: i8,
+    ) -> PyResult<Card> {
+        let kwargs = PyDict::new(py);
+        kwargs.set_item("cards", cards_to_py(py, cards)?)?;
+        if self.accepts_first_turn_location {
+            kwargs.set_item("i", i)?;
+            kwargs.set_item("j", j)?;
+        }
+        let result = self
+            .bot
+            .call_method(py, "play_first_turn", (), Some(kwargs))?;
+        extract_result(py, result, "play_first_turn", "Card")
+    }
+
+    pub(crate) fn play_turn(
+        &mut self,
+        py: Python,
+        cards: Hand,
         fields: Vec<Field>,
         cards_won_by_opponent: CardsSet,
-    ) -> PlayTurnResponse {
-        Python::with_gil(|py| {
-            let kwargs = PyDict::new(py);
-            kwargs
-                .set_item("cards", cards.map(|card| Py::new(py, card).unwrap()))
-                .unwrap();
-            kwargs
-                .set_item("board", Py::new(py, Board::new(&fields)).unwrap())
-                .unwrap();
-            kwargs
-                .set_item(
-                    "cards_won_by_opponent",
-                    Py::new(py, cards_won_by_opponent).unwrap(),
-                )
-                .unwrap();
-            self.bot
-                .call_method(py, "play_turn", (), Some(kwargs))
-                .expect("Call to play_turn() failed")
-                .extract(py)
-                .expect("play_turn() returned wrong type")
-        })
+        phase: GamePhase,
+    ) -> PyResult<PlayTurnResponse> {
+        let kwargs = PyDict::new(py);
+        kwargs.set_item("cards", cards_to_py(py, cards)?)?;
+        let board = Board::new_with_rules(&fields, phase.rules);
+        kwargs.set_item("board", Py::new(py, board)?)?;
+        kwargs.set_item("cards_won_by_opponent", Py::new(py, cards_won_by_opponent)?)?;
+        if self.accepts_game_phase {
+            let GamePhase {
+                draw_pile_size,
+                opponent_draw_pile_size,
+                turn_number,
+                ..
+            } = phase;
+            let values = [draw_pile_size, opponent_draw_pile_size, turn_number];
+            for (name, value) in GAME_PHASE_ARGS.into_iter().zip(values) {
+                kwargs.set_item(name, value)?;
+            }
+        }
+        let result = self.bot.call_method(py, "play_turn", (), Some(kwargs))?;
+        extract_result(py, result, "play_turn", "PlayTurnResponse")
+    }
+}
+
+// Whether the method has parameters for all of the given names, or takes **kwargs.
+fn accepts_args(py: Python, method: &PyAny, names: &[&str]) -> PyResult<bool> {
+    let parameters = py
+        .import("inspect")?
+        .call_method1("signature", (method,))?
+        .getattr("parameters")?;
+    for parameter in parameters.call_method0("values")?.iter()? {
+        let parameter = parameter?;
+        if parameter
+            .getattr("kind")?
+            .eq(parameter.getattr("VAR_KEYWORD")?)?
+        {
+            return Ok(true);
+        }
+    }
+    for &name in names {
+        if !parameters.contains(name)? {
+            return Ok(false);
+        }
     }
+    Ok(true)
 }
 
+fn cards_to_py(py: Python, cards: Hand) -> PyResult<Vec<Py<Card>>> {
+    cards.into_iter().map(|card| Py::new(py, card)).collect()
+}
+
+// Converts the return value of a bot method, with an error message that names the method.
+fn extract_result<'a, T: FromPyObject<'a>>(
+    py: Python<'a>,
+    result: PyObject,
+    method: &str,
+    expected_type: &str,
+) -> PyResult<T> {
+    let result = result.into_ref(py);
+    result.extract().map_err(|_| {
+        let type_name = result
+            .get_type()
+            .name()
+            .unwrap_or("<unknown type>")
+            .to_owned();
+        PyTypeError::new_err(format!(
+            "{}() must return a {}, but returned a value of type '{}'",
+            method, expected_type, type_name
+        ))
+    })
+}
+
+// Turns a communication error into a Python exception.
+fn to_py_err(err: anyhow::Error) -> PyErr {
+    PyRuntimeError::new_err(format!("{:#}", err))
+}
+
+type ReceivedRequest = anyhow::Result<Option<Request>>;
+
+/// Receives the requests on another thread, since reading stdin can't be interrupted.
+struct RequestReceiver {
+    // Only to make a reference to it sendable to the thread that waits without the GIL
+    receiver: Mutex<Receiver<ReceivedRequest>>,
+}
+
+impl RequestReceiver {
+    /// Receives requests until the judge says bye or closes the connection.
+    fn spawn(mut connection: BotConnection) -> Self {
+        let (sender, receiver) = sync_channel(0);
+        std::thread::spawn(move || loop {
+            let received = connection.receive();
+            let done =
+                !matches!(received, Ok(Some(ref req)) if !matches!(req, Request::Bye { .. }));
+            if sender.send(received).is_err() || done {
+                break;
+            }
+        });
+        Self {
+            receiver: Mutex::new(receiver),
+        }
+    }
+
+    /// Waits for the next request without holding the GIL, so that other Python threads
+    /// can run, and runs the signal handlers regularly, so that e.g. Ctrl+C raises a
+    /// `KeyboardInterrupt`.
+    fn next(&self, py: Python) -> PyResult<Option<Request>> {
+        loop {
+            let received = py.allow_threads(|| {
+                let receiver = self.receiver.lock().unwrap();
+                receiver.recv_timeout(SIGNAL_CHECK_INTERVAL)
+            });
+            match received {
+                Ok(received) => return received.map_err(to_py_err),
+                Err(RecvTimeoutError::Timeout) => py.check_signals()?,
+                // The thread only stops after the last request
+                Err(RecvTimeoutError::Disconnected) => return Ok(None),
+            }
+        }
+    }
+}
+
+/// Runs the bot, i.e. answers requests from the judge until the judge is done, which is
+/// when it says bye or closes the connection.
+///
+/// Exceptions raised by the bot's methods are propagated to the caller, as are errors in
+/// the communication with the judge, as a `RuntimeError`. The GIL is released while
+/// waiting for the judge, and signal handlers run, so that Ctrl+C raises a
+/// `KeyboardInterrupt`.
 #[pyfunction]
-pub fn run_bot(bot: PyObject) {
-    PythonBot { bot }.run().unwrap()
+pub fn run_bot(py: Python, bot: PyObject) -> PyResult<()> {
+    let mut bot = PythonBot::new(py, bot)?;
+    let protocol = protocol_from_env().map_err(to_py_err)?;
+    let receiver = RequestReceiver::spawn(BotConnection::with_protocol(protocol));
+    let mut connection = BotConnection::with_protocol(protocol);
+    let mut rules = Rules::STANDARD;
+    while let Some(req) = receiver.next(py)? {
+        match req {
+            Request::NewGame {
+                color,
+                num_players,
+                seat,
+                suit,
+                rules: game_rules,
+                ..
+            } => {
+                rules = game_rules;
+                let setup = GameSetup::new(color, num_players, seat, suit, rules);
+                bot.new_game(py, color, setup)?;
+                connection.send(&Okay()).map_err(to_py_err)?;
+            }
+            Request::PlayFirstTurn { cards, i, j } => {
+                let card = bot.play_first_turn(py, cards, i, j)?;
+                connection.send(&card).map_err(to_py_err)?;
+            }
+            Request::PlayTurn {
+                cards,
+                fields,
+                cards_won_by_opponent,
+                draw_pile_size,
+                opponent_draw_pile_size,
+                turn_number,
+            } => {
+                let cards_won_by_opponent = CardsSet::from_iter(cards_won_by_opponent);
+                let phase = GamePhase {
+                    draw_pile_size,
+                    opponent_draw_pile_size,
+                    turn_number,
+                    rules,
+                };
+                let response = bot.play_turn(py, cards, fields, cards_won_by_opponent, phase)?;
+                connection.send(&response).map_err(to_py_err)?;
+            }
+            Request::Bye { .. } => break,
+        }
+    }
+    Ok(())
 }
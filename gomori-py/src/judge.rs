@@ -0,0 +1,133 @@
+use gomori::{CardsSet, Okay, Request, Rules};
+use gomori_bot_utils::{GamePhase, GameSetup};
+use judge::{play_matchup, Connection, MatchConfig, Player};
+use pyo3::exceptions::PyRuntimeError;
+use pyo3::{pyclass, pyfunction, pymethods, PyErr, PyObject, PyResult, Python};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+use crate::bot::PythonBot;
+
+/// Lets the judge talk to a Python bot object in-process, instead of to a bot process.
+struct PythonBotConnection {
+    bot: PythonBot,
+    /// The rules of the current game.
+    rules: Rules,
+}
+
+impl Connection for PythonBotConnection {
+    fn exchange(&mut self, request: &str) -> anyhow::Result<String> {
+        let request: Request = serde_json::from_str(request)?;
+        Python::with_gil(|py| {
+            let response = match request {
+                Request::NewGame {
+                    color,
+                    num_players,
+                    seat,
+                    suit,
+                    rules,
+                    ..
+                } => {
+                    self.rules = rules;
+                    let setup = GameSetup::new(color, num_players, seat, suit, rules);
+                    self.bot.new_game(py, color, setup)?;
+                    serde_json::to_string(&Okay())?
+                }
+                Request::PlayFirstTurn { cards, i, j } => {
+                    serde_json::to_string(&self.bot.play_first_turn(py, cards, i, j)?)?
+                }
+                Request::PlayTurn {
+                    cards,
+                    fields,
+                    cards_won_by_opponent,
+                    draw_pile_size,
+                    opponent_draw_pile_size,
+                    turn_number,
+                } => {
+                    let cards_won_by_opponent = CardsSet::from_iter(cards_won_by_opponent);
+                    let phase = GamePhase {
+                        draw_pile_size,
+                        opponent_draw_pile_size,
+                        turn_number,
+                        rules: self.rules,
+                    };
+                    let response =
+                        self.bot
+                            .play_turn(py, cards, fields, cards_won_by_opponent, phase)?;
+                    serde_json::to_string(&response)?
+                }
+                Request::Bye { .. } => String::new(),
+            };
+            Ok(response)
+        })
+    }
+}
+
+/// The results of a match, from the perspective of both bots.
+///
+/// The tuples contain one entry per bot, in the order in which they were passed to `run_match()`.
+#[pyclass(get_all)]
+#[derive(Clone, Debug)]
+pub struct MatchScore {
+    /// Games won by each bot, including games won through an illegal move of the other bot.
+    wins: (usize, usize),
+    /// Games lost by each bot due to an illegal move.
+    illegal_moves: (usize, usize),
+    ties: usize,
+}
+
+#[pymethods]
+impl MatchScore {
+    fn __repr__(&self) -> String {
+        format!(
+            "MatchScore(wins={:?}, illegal_moves={:?}, ties={})",
+            self.wins, self.illegal_moves, self.ties
+        )
+    }
+}
+
+fn python_player(py: Python, bot: PyObject) -> PyResult<Player> {
+    let name = bot.as_ref(py).get_type().name()?.to_owned();
+    let bot = PythonBot::new(py, bot)?;
+    Ok(Player::with_connection(
+        name,
+        Box::new(PythonBotConnection {
+            bot,
+            rules: Rules::STANDARD,
+        }),
+    ))
+}
+
+/// Plays a series of games between two bots, in this process.
+///
+/// Exceptions raised by the bots' methods are propagated to the caller.
+#[pyfunction]
+#[pyo3(signature = (bot_a, bot_b, num_games = 100, seed = None, stop_on_illegal_move = false))]
+pub fn run_match(
+    py: Python,
+    bot_a: PyObject,
+    bot_b: PyObject,
+    num_games: usize,
+    seed: Option<u64>,
+    stop_on_illegal_move: bool,
+) -> PyResult<MatchScore> {
+    let mut players = [python_player(py, bot_a)?, python_player(py, bot_b)?];
+    let mut rng = StdRng::seed_from_u64(seed.unwrap_or_else(rand::random));
+    let config = MatchConfig {
+        num_games,
+        stop_on_illegal_move,
+        ..Default::default()
+    };
+    let score =
+        play_matchup(&mut rng, &mut players, &config, &mut None, &mut |_| {}).map_err(|err| {
+            match err.downcast::<PyErr>() {
+                Ok(py_err) => py_err,
+                Err(err) => PyRuntimeError::new_err(format!("{:#}", err)),
+            }
+        })?;
+    Ok(MatchScore {
+        wins: (score.wins[0], score.wins[1]),
+        illegal_moves: (score.illegal_moves[0], score.illegal_moves[1]),
+        ties: score.ties,
+    })
+}
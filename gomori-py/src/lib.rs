@@ -1,6 +1,60 @@
+use ::gomori::advisor;
 use pyo3::prelude::*;
 
 mod bot;
+mod judge;
+
+/// Draws the top cards of the board as a grid, with its coordinates.
+#[pyfunction]
+fn visualize(board: ::gomori::Board) -> String {
+    ::gomori::visualize_top_cards(&board.to_fields_vec())
+}
+
+/// Encodes the board as a flat list of numbers, see the Rust function of the same name.
+#[pyfunction]
+fn encode_board(board: ::gomori::Board) -> Vec<f32> {
+    ::gomori::encode_board(&board).to_vec()
+}
+
+/// Encodes a card played on the board, see the Rust function of the same name.
+#[pyfunction]
+fn encode_card_to_play(board: ::gomori::Board, card_to_play: ::gomori::CardToPlay) -> [i8; 5] {
+    ::gomori::encode_card_to_play(&board, card_to_play)
+}
+
+/// Rates the legal turns with the cards on the board, best first, see the Rust function
+/// `advisor::rank_turns()`. Without an evaluator, a turn is rated by the number of cards won.
+#[pyfunction]
+#[pyo3(signature = (board, cards, evaluator = None))]
+fn rank_turns(
+    py: Python,
+    board: ::gomori::Board,
+    cards: Vec<::gomori::Card>,
+    evaluator: Option<PyObject>,
+) -> PyResult<Vec<(::gomori::PlayTurnResponse, f64)>> {
+    let hand = ::gomori::CardsSet::from_iter(cards);
+    let Some(evaluator) = evaluator else {
+        return Ok(advisor::rank_turns(&board, hand, advisor::material));
+    };
+    // The first exception of the evaluator, after which it's not called anymore
+    let mut error = None;
+    let ranked = advisor::rank_turns(&board, hand, |turn| {
+        if error.is_some() {
+            return 0.0;
+        }
+        let rating = evaluator
+            .call1(py, (turn.clone(),))
+            .and_then(|rating| rating.extract(py));
+        rating.unwrap_or_else(|err| {
+            error = Some(err);
+            0.0
+        })
+    });
+    match error {
+        Some(err) => Err(err),
+        None => Ok(ranked),
+    }
+}
 
 /// A Python module implemented in Rust.
 #[pymodule]
@@ -23,10 +77,21 @@ fn gomori(py: Python, m: &PyModule) -> PyResult<()> {
     m.add_class::<::gomori::Color>()?;
     m.add_class::<::gomori::CompactField>()?;
     m.add_class::<::gomori::Field>()?;
+    m.add_class::<::gomori::FieldHistory>()?;
+    m.add_class::<::gomori::FinishedTurn>()?;
     m.add_class::<::gomori::PlayTurnResponse>()?;
     m.add_class::<::gomori::PyCalculatedEffects>()?;
     m.add_class::<::gomori::Rank>()?;
     m.add_class::<::gomori::Suit>()?;
     m.add_function(wrap_pyfunction!(bot::run_bot, m)?)?;
+    m.add_function(wrap_pyfunction!(visualize, m)?)?;
+    m.add_function(wrap_pyfunction!(encode_board, m)?)?;
+    m.add_function(wrap_pyfunction!(encode_card_to_play, m)?)?;
+    m.add_function(wrap_pyfunction!(rank_turns, m)?)?;
+
+    let judge_module = PyModule::new(py, "judge")?;
+    judge_module.add_class::<judge::MatchScore>()?;
+    judge_module.add_function(wrap_pyfunction!(judge::run_match, judge_module)?)?;
+    m.add_submodule(judge_module)?;
     Ok(())
 }
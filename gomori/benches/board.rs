@@ -0,0 +1,165 @@
+//! Benchmarks for the core [`Board`] operations, across boards with different numbers of fields.
+//!
+//! Run with `cargo bench -p gomori`.
+
+use std::collections::BTreeSet;
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use gomori::{Board, BoardArena, Card, CardToPlay, Field, BLACK_CARDS, RED_CARDS};
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+
+const NUM_FIELDS: [usize; 5] = [1, 4, 8, 12, 16];
+
+/// Creates a board with the given number of fields within a 4 x 4 area, and a card that is not on it.
+fn random_board(num_fields: usize, rng: &mut StdRng) -> (Board, Card) {
+    let mut coordinates: Vec<(i8, i8)> = (0..4).flat_map(|i| (0..4).map(move |j| (i, j))).collect();
+    coordinates.shuffle(rng);
+    let mut cards: Vec<Card> = RED_CARDS
+        .iter()
+        .chain(BLACK_CARDS.iter())
+        .copied()
+        .collect();
+    cards.shuffle(rng);
+    let card = cards.pop().unwrap();
+    let fields: Vec<Field> = coordinates[..num_fields]
+        .iter()
+        .map(|&(i, j)| {
+            // Give some fields hidden cards too
+            let num_hidden_cards = usize::from(cards.len().is_multiple_of(3));
+            let hidden_cards = BTreeSet::from_iter(cards.drain(..num_hidden_cards));
+            Field {
+                i,
+                j,
+                top_card: cards.pop(),
+                hidden_cards,
+            }
+        })
+        .collect();
+    (Board::new(&fields), card)
+}
+
+fn all_plays(board: &Board, card: Card) -> Vec<CardToPlay> {
+    board
+        .locations_for_card(card)
+        .into_iter()
+        .map(|(i, j)| CardToPlay {
+            card,
+            i,
+            j,
+            target_field_for_king_ability: Some((i, j)),
+        })
+        .collect()
+}
+
+fn bench_board(c: &mut Criterion) {
+    let mut rng = StdRng::seed_from_u64(0);
+    let boards: Vec<(usize, Board, Card)> = NUM_FIELDS
+        .iter()
+        .map(|&num_fields| {
+            let (board, card) = random_board(num_fields, &mut rng);
+            (num_fields, board, card)
+        })
+        .collect();
+
+    let mut group = c.benchmark_group("calculate_all_locations");
+    for (num_fields, board, card) in &boards {
+        let plays = all_plays(board, *card);
+        group.bench_with_input(
+            BenchmarkId::from_parameter(num_fields),
+            &plays,
+            |b, plays| {
+                b.iter(|| {
+                    plays
+                        .iter()
+                        .filter_map(|&ctp| board.calculate(ctp).ok())
+                        .map(|effects| effects.cards_won.len())
+                        .sum::<u32>()
+                })
+            },
+        );
+    }
+    group.finish();
+
+    let mut group = c.benchmark_group("calculate_all");
+    for (num_fields, board, card) in &boards {
+        group.bench_with_input(BenchmarkId::from_parameter(num_fields), card, |b, &card| {
+            b.iter(|| {
+                board
+                    .calculate_all(card)
+                    .map(|(_, effects)| effects.cards_won.len())
+                    .sum::<u32>()
+            })
+        });
+    }
+    group.finish();
+
+    let mut group = c.benchmark_group("score_if_played_all_locations");
+    for (num_fields, board, card) in &boards {
+        let plays = all_plays(board, *card);
+        group.bench_with_input(
+            BenchmarkId::from_parameter(num_fields),
+            &plays,
+            |b, plays| {
+                b.iter(|| {
+                    plays
+                        .iter()
+                        .filter_map(|&ctp| board.score_if_played(ctp).ok())
+                        .sum::<u32>()
+                })
+            },
+        );
+    }
+    group.finish();
+
+    let mut group = c.benchmark_group("play_card");
+    for (num_fields, board, card) in &boards {
+        let ctp = all_plays(board, *card)[0];
+        group.bench_with_input(BenchmarkId::from_parameter(num_fields), &ctp, |b, &ctp| {
+            b.iter(|| board.play_card(ctp))
+        });
+    }
+    group.finish();
+
+    let mut group = c.benchmark_group("play_card_with_arena");
+    for (num_fields, board, card) in &boards {
+        let ctp = all_plays(board, *card)[0];
+        let mut arena = BoardArena::new();
+        group.bench_with_input(BenchmarkId::from_parameter(num_fields), &ctp, |b, &ctp| {
+            b.iter(|| {
+                let new_board = arena.execute(board.calculate(ctp).unwrap());
+                arena.recycle(new_board);
+            })
+        });
+    }
+    group.finish();
+
+    let mut group = c.benchmark_group("locations_for_card");
+    for (num_fields, board, card) in &boards {
+        group.bench_with_input(BenchmarkId::from_parameter(num_fields), card, |b, &card| {
+            b.iter(|| board.locations_for_card(card))
+        });
+    }
+    group.finish();
+
+    let mut group = c.benchmark_group("get");
+    for (num_fields, board, _) in &boards {
+        group.bench_with_input(
+            BenchmarkId::from_parameter(num_fields),
+            board,
+            |b, board| {
+                b.iter(|| {
+                    (0..4)
+                        .flat_map(|i| (0..4).map(move |j| (i, j)))
+                        .filter(|&(i, j)| board.get(i, j).is_some())
+                        .count()
+                })
+            },
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_board);
+criterion_main!(benches);
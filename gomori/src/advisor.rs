@@ -0,0 +1,172 @@
+//! Suggests turns to a player, e.g. to show hints to a person who is learning the game.
+//!
+//! [`rank_turns()`] tries out the legal turns and rates what the board looks like
+//! afterwards with an evaluator, such as the default [`material()`].
+
+use alloc::collections::BTreeSet;
+use core::cmp::Reverse;
+
+use crate::prelude::*;
+use crate::{
+    Board, CardToPlay, CardsSet, CompactField, FinishedTurn, PlayTurnResponse, TurnState,
+    TurnValidator,
+};
+
+/// The most turns that [`rank_turns()`] tries out.
+///
+/// Long combos can be played in so many ways that trying them all would take too long.
+pub const MAX_TURNS: usize = 10_000;
+
+// What is left of a turn after some cards have been played. Turns that get to the same
+// position can only continue in the same ways.
+type Position = (Vec<(i8, i8, CompactField)>, u64, bool);
+
+/// Returns the legal turns with this hand on this board, with their rating by the
+/// evaluator, best first.
+///
+/// The evaluator gets the result of the turn, and returns a higher number for a better
+/// turn. If none of the cards can be played, the only turn is to skip.
+///
+/// Of the turns that lead to the same board, e.g. by playing the same cards in a different
+/// order, only the first one is included. Even so, there are sometimes more turns than
+/// [`MAX_TURNS`], and then only that many are tried. The plays that win the most cards are
+/// tried first, so that the turns left out are unlikely to be the best ones. Turns with the
+/// same rating are in the order in which they were tried.
+///
+/// ```
+/// use gomori::advisor::{material, rank_turns};
+/// use gomori::{card, Board, CardsSet, Field};
+/// let board = Board::new(&[
+///     Field { i: 0, j: 0, top_card: Some(card!("2♠")), hidden_cards: Default::default() },
+///     Field { i: 0, j: 1, top_card: Some(card!("3♠")), hidden_cards: Default::default() },
+///     Field { i: 0, j: 2, top_card: Some(card!("4♠")), hidden_cards: Default::default() },
+/// ]);
+/// let hand = CardsSet::from_iter([card!("5♠"), card!("7♦")]);
+/// let turns = rank_turns(&board, hand, material);
+/// // Completing the line of spades wins its other three cards
+/// let (best_turn, rating) = &turns[0];
+/// assert_eq!(best_turn.cards_to_play()[0].card, card!("5♠"));
+/// assert_eq!(*rating, 3.0);
+/// ```
+pub fn rank_turns(
+    board: &Board,
+    hand: CardsSet,
+    mut evaluate: impl FnMut(&FinishedTurn) -> f64,
+) -> Vec<(PlayTurnResponse, f64)> {
+    let mut turns = Vec::new();
+    collect_turns(
+        TurnValidator::new(board.clone(), hand),
+        &mut Vec::new(),
+        &mut BTreeSet::new(),
+        &mut turns,
+    );
+    let mut ranked: Vec<_> = turns
+        .into_iter()
+        .map(|(cards_to_play, turn)| {
+            let rating = evaluate(&turn);
+            (
+                PlayTurnResponse::play_or_skip(board, hand, cards_to_play),
+                rating,
+            )
+        })
+        .collect();
+    // Stable, so that ties keep their order
+    ranked.sort_by(|(_, a), (_, b)| b.total_cmp(a));
+    ranked
+}
+
+/// The default evaluator for [`rank_turns()`]: the number of cards won in the turn.
+pub fn material(turn: &FinishedTurn) -> f64 {
+    f64::from(turn.cards_won.len())
+}
+
+// Adds every way to finish the turn from where the validator is to `turns`, with the cards
+// played in it, except from positions that were already seen, and until there are
+// MAX_TURNS of them
+fn collect_turns(
+    validator: TurnValidator,
+    cards_to_play: &mut Vec<CardToPlay>,
+    seen: &mut BTreeSet<Position>,
+    turns: &mut Vec<(Vec<CardToPlay>, FinishedTurn)>,
+) {
+    let mut fields = validator.board().fields.clone();
+    fields.sort_unstable();
+    let in_combo = validator.state() == TurnState::InCombo;
+    if turns.len() >= MAX_TURNS || !seen.insert((fields, validator.remaining_hand().bits, in_combo))
+    {
+        return;
+    }
+    // If the turn may end here, no more cards can be played
+    if validator.can_finish() {
+        let turn = validator.finish().expect("The turn can finish");
+        turns.push((cards_to_play.clone(), turn));
+        return;
+    }
+    let mut plays: Vec<_> = validator
+        .playable_cards()
+        .into_iter()
+        .flat_map(|card| validator.board().calculate_all(card))
+        .map(|(ctp, effects)| (ctp, effects.cards_won.len(), effects.combo))
+        .collect();
+    plays.sort_by_key(|&(_, num_cards_won, combo)| Reverse((num_cards_won, combo)));
+    for (ctp, _, _) in plays {
+        let mut next = validator.clone();
+        next.play(ctp).expect("Legal plays can be played");
+        cards_to_play.push(ctp);
+        collect_turns(next, cards_to_play, seen, turns);
+        cards_to_play.pop();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    use super::*;
+    use crate::{card, execute_turn, field, random_game_in_progress};
+
+    #[test]
+    fn combos_are_followed_to_the_end() {
+        // The 9♠ on the 9♦ completes the line of spades, which wins the three other spades,
+        // and is a combo, after which the 6♦ must be played too
+        let board = Board::new(&[
+            field(0, 0, card!("2♠")),
+            field(0, 1, card!("3♠")),
+            field(0, 2, card!("4♠")),
+            field(0, 3, card!("9♦")),
+            field(1, 0, card!("K♣")),
+        ]);
+        let hand = CardsSet::from_iter([card!("9♠"), card!("6♦")]);
+        let turns = rank_turns(&board, hand, material);
+        let (best_turn, rating) = &turns[0];
+        assert_eq!(*rating, 3.0);
+        let cards: Vec<_> = best_turn
+            .cards_to_play()
+            .iter()
+            .map(|ctp| ctp.card)
+            .collect();
+        assert_eq!(cards, [card!("9♠"), card!("6♦")]);
+        assert!(turns.iter().all(|(turn, _)| !turn.is_skip()));
+
+        // A custom evaluator that prefers keeping cards in the hand
+        let turns = rank_turns(&board, hand, |turn| f64::from(turn.remaining_hand.len()));
+        assert_eq!(turns[0].0.cards_to_play().len(), 1);
+        assert_eq!(turns[0].1, 1.0);
+    }
+
+    #[test]
+    fn ranked_turns_are_legal_and_rated_by_their_result() {
+        let mut rng = StdRng::seed_from_u64(0);
+        for _ in 0..10 {
+            let game = random_game_in_progress(&mut rng);
+            let turns = rank_turns(&game.board, game.player.hand.cards(), material);
+            assert!(!turns.is_empty() && turns.len() <= MAX_TURNS);
+            assert!(turns.windows(2).all(|pair| pair[0].1 >= pair[1].1));
+            for (turn, rating) in turns {
+                let result = execute_turn(&game.player, &game.board, turn).unwrap();
+                assert_eq!(f64::from(result.cards_won_this_turn.len()), rating);
+            }
+        }
+    }
+}
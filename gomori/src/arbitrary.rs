@@ -1,6 +1,8 @@
 use std::collections::{BTreeMap, BTreeSet};
 
-use crate::{Card, CardToPlay, Field, Rank, Suit};
+use quickcheck::Arbitrary;
+
+use crate::{Card, CardToPlay, Field};
 
 #[derive(Clone, Debug)]
 pub struct PlayCardInput {
@@ -9,7 +11,7 @@ pub struct PlayCardInput {
     pub card_to_play: CardToPlay,
 }
 
-impl quickcheck::Arbitrary for PlayCardInput {
+impl Arbitrary for PlayCardInput {
     fn arbitrary(g: &mut quickcheck::Gen) -> Self {
         let mut already_played_cards = BTreeSet::<Card>::arbitrary(g);
 
@@ -71,43 +73,3 @@ impl quickcheck::Arbitrary for PlayCardInput {
         }
     }
 }
-
-#[cfg(test)]
-impl quickcheck::Arbitrary for Suit {
-    fn arbitrary(g: &mut quickcheck::Gen) -> Self {
-        *g.choose(&[Suit::Diamond, Suit::Heart, Suit::Spade, Suit::Club])
-            .unwrap()
-    }
-}
-
-#[cfg(test)]
-impl quickcheck::Arbitrary for Rank {
-    fn arbitrary(g: &mut quickcheck::Gen) -> Self {
-        *g.choose(&[
-            Rank::Two,
-            Rank::Three,
-            Rank::Four,
-            Rank::Five,
-            Rank::Six,
-            Rank::Seven,
-            Rank::Eight,
-            Rank::Nine,
-            Rank::Ten,
-            Rank::Jack,
-            Rank::Queen,
-            Rank::King,
-            Rank::Ace,
-        ])
-        .unwrap()
-    }
-}
-
-#[cfg(test)]
-impl quickcheck::Arbitrary for Card {
-    fn arbitrary(g: &mut quickcheck::Gen) -> Self {
-        Self {
-            rank: Rank::arbitrary(g),
-            suit: Suit::arbitrary(g),
-        }
-    }
-}
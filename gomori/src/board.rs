@@ -1,22 +1,57 @@
+mod arena;
 mod bbox;
 mod bitboard;
+mod canonical;
+mod color_swap;
 mod compact_field;
-
-use std::ops::Deref;
-
+mod field_history;
+// Needs the floating point logarithm, which is not in core
+#[cfg(feature = "std")]
+mod information;
+#[cfg(feature = "rayon")]
+mod parallel;
+mod placement_policy;
+mod transform;
+
+use alloc::sync::Arc;
+use core::hash::{Hash, Hasher};
+use core::ops::Deref;
+
+pub use arena::*;
 pub use bbox::*;
 pub use bitboard::*;
+pub use canonical::*;
+pub use color_swap::*;
 pub use compact_field::*;
-
-use crate::{Card, CardToPlay, CardsSet, Field, IllegalCardPlayed, Rank, Suit};
+pub use field_history::*;
+#[cfg(feature = "std")]
+pub use information::*;
+pub use placement_policy::*;
+pub use transform::*;
+
+use crate::prelude::*;
+use crate::{
+    Card, CardToPlay, CardsSet, Explanation, Field, IllegalCardPlayed, InvalidBoard, Line,
+    LineDirection, MoveExplanation, Rank, Rules, Suit,
+};
 
 pub const BOARD_SIZE: i8 = 4;
 
+/// The largest [`board_size`](Rules::board_size) of a variant of the game.
+///
+/// Every board of this size, and every card that can be played next to it, fits into the
+/// area of a [`BitBoard`].
+pub const MAX_BOARD_SIZE: i8 = 5;
+
 /// Represents a board with at least one card on it.
 ///
 /// The idea is that a list of [`Field`]s is used in the communication between judge and bots,
 /// but is then converted into this type for performing the actual tasks like e.g. [determining
 /// whether a card can be played](Board::possible_to_play_card).
+///
+/// Boards are equal if they have the same rules and the same cards on the same fields,
+/// regardless of the order of their fields (which is unspecified) and of their
+/// [history](Board::history). Equal boards have the same hash.
 //
 // Because after the first move, there is at least one card on it,
 // the minimum and maximum coordinates always exist.
@@ -28,7 +63,13 @@ pub struct Board {
     /// The `bbox` and `bitboards` fields are derived from this list.
     ///
     /// Note: This is not guaranteed to be sorted.
-    fields: Vec<(i8, i8, CompactField)>,
+    pub(crate) fields: Vec<(i8, i8, CompactField)>,
+    /// Positional index into `fields`, for looking up a field by its coordinates in O(1).
+    ///
+    /// Covers the same 9 x 9 area as the bitboards (see `field_index_position()`), with
+    /// one entry per coordinate. An entry is the position of the field in `fields` plus one,
+    /// or zero if there is no field at that coordinate.
+    index: [u8; AREA_LEN],
     /// The center coordinate for all bitboards produced by this board.
     /// Using a consistent center coordinate enables binary operations like bitwise or.
     /// See also the bitboard docs.
@@ -37,15 +78,27 @@ pub struct Board {
     bbox: BoundingBox,
     /// All the diamond/heart/spade/club cards on the board.
     bitboards: [BitBoard; 4],
+    /// The number of cards on the board, face up or down.
+    num_cards: u32,
+    /// The number of fields without a top card.
+    num_face_down_fields: u32,
+    /// The rules for playing cards on this board.
+    rules: Rules,
+    /// The cards played on this board so far, if enabled with [`Board::with_history()`].
+    pub(crate) history: Option<Vec<CardToPlay>>,
+    /// The fields at the time [`Board::with_history()`] was called, from which the
+    /// [`field_history()`](Board::field_history) is replayed. `Some` iff `history` is.
+    pub(crate) history_start: Option<Arc<[(i8, i8, CompactField)]>>,
 }
 
 #[derive(Clone)]
-struct Diff {
+pub(crate) struct Diff {
     flipped: BitBoard,
     won: BitBoard,
     new_card: Card,
     new_card_i: i8,
     new_card_j: i8,
+    target_field_for_king_ability: Option<(i8, i8)>,
 }
 
 /// The effects that playing a card would have.
@@ -53,8 +106,8 @@ struct Diff {
 /// Returned by [`Board::calculate()`].
 pub struct CalculatedEffects<'a> {
     /// This struct ties together the board and its diff, to prevent any possible mixups
-    board: &'a Board,
-    diff: Diff,
+    pub(crate) board: &'a Board,
+    pub(crate) diff: Diff,
     /// The cards that were won as a result of playing this card
     pub cards_won: CardsSet,
     /// Should another card be played?
@@ -65,42 +118,167 @@ pub struct CalculatedEffects<'a> {
 impl Board {
     /// Creates a new board from a list of [`Field`]s.
     ///
-    /// Panics if the fields are (obviously) invalid, e.g. if it is larger than 4 x 4.
+    /// The fields don't need to be sorted. Panics if the fields are invalid, see
+    /// [`try_new()`](Board::try_new).
     pub fn new(fields: &[Field]) -> Self {
-        Self::from_fields_list(
-            fields
-                .iter()
-                .map(|f| (f.i, f.j, CompactField::from(f)))
-                .collect(),
-        )
+        Self::try_new(fields).unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    /// Creates a new board from a list of [`Field`]s, if they are valid.
+    ///
+    /// Valid means that there is at least one field, no field is empty, no two fields have
+    /// the same coordinates or a card in common, and all fields fit in a [`BOARD_SIZE`] x
+    /// [`BOARD_SIZE`] area.
+    /// The fields don't need to be sorted.
+    pub fn try_new(fields: &[Field]) -> Result<Self, InvalidBoard> {
+        Self::try_from_fields_list(to_fields_list(fields))
+    }
+
+    /// Creates a new board for a variant of the game.
+    ///
+    /// Panics if the rules or fields are invalid, see [`try_new_with_rules()`](Board::try_new_with_rules).
+    pub fn new_with_rules(fields: &[Field], rules: Rules) -> Self {
+        Self::try_new_with_rules(fields, rules).unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    /// Creates a new board for a variant of the game, if the rules and fields are valid.
+    ///
+    /// The fields must be valid as for [`try_new()`](Board::try_new), except that they
+    /// need to fit in the board size of the rules.
+    pub fn try_new_with_rules(fields: &[Field], rules: Rules) -> Result<Self, InvalidBoard> {
+        rules.validate().map_err(InvalidBoard::InvalidRules)?;
+        let fields = to_fields_list(fields);
+        check_fields_list(&fields, rules.board_size)?;
+        Ok(Self::from_valid_fields_list(fields, rules))
+    }
+
+    /// Like [`new()`](Board::new), but the fields are only validated in debug builds.
+    ///
+    /// For trusted inputs on hot paths, e.g. the fields of a deserialized [`Request`](crate::Request),
+    /// which are validated during deserialization. Invalid fields lead to an unspecified (but safe)
+    /// board or a panic.
+    pub fn new_unchecked(fields: &[Field]) -> Self {
+        let fields = to_fields_list(fields);
+        debug_assert_eq!(check_fields_list(&fields, BOARD_SIZE), Ok(()));
+        Self::from_valid_fields_list(fields, Rules::STANDARD)
     }
 
     /// Creates a new board from a list of [`CompactField`]s.
     ///
-    /// Panics if the fields are (obviously) invalid, e.g. if it is larger than 4 x 4.
+    /// Panics if the fields are invalid, see [`try_new()`](Board::try_new).
     pub fn from_fields_list(fields: Vec<(i8, i8, CompactField)>) -> Self {
-        assert!(!fields.is_empty());
+        Self::try_from_fields_list(fields).unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    /// Creates a new board from a list of [`CompactField`]s, if they are valid.
+    ///
+    /// See [`try_new()`](Board::try_new) for what is valid.
+    pub fn try_from_fields_list(fields: Vec<(i8, i8, CompactField)>) -> Result<Self, InvalidBoard> {
+        check_fields_list(&fields, BOARD_SIZE)?;
+        Ok(Self::from_valid_fields_list(fields, Rules::STANDARD))
+    }
+
+    fn from_valid_fields_list(fields: Vec<(i8, i8, CompactField)>, rules: Rules) -> Self {
         let bitboards_center = (fields[0].0, fields[0].1);
+        Self::from_valid_fields_list_centered_at(fields, bitboards_center, rules)
+    }
+
+    // The center must be within MAX_BOARD_SIZE - 1 fields of every field, in both directions
+    pub(crate) fn from_valid_fields_list_centered_at(
+        fields: Vec<(i8, i8, CompactField)>,
+        bitboards_center: (i8, i8),
+        rules: Rules,
+    ) -> Self {
         let mut bbox = BoundingBox::singleton(fields[0].0, fields[0].1);
         let mut bitboards = [BitBoard::empty_board_centered_at(bitboards_center); 4];
+        let mut num_cards = 0;
+        let mut num_face_down_fields = 0;
 
         for field in &fields {
-            debug_assert!(field.2.top_card().is_some() || !field.2.hidden_cards().is_empty());
             bbox.update(field.0, field.1);
+            num_cards += field.2.all_cards().len();
             if let Some(Card { suit, .. }) = field.2.top_card() {
                 bitboards[suit as usize] = bitboards[suit as usize].insert(field.0, field.1);
+            } else {
+                num_face_down_fields += 1;
             }
         }
 
-        assert!(bbox.size_i() <= BOARD_SIZE as u8);
-        assert!(bbox.size_j() <= BOARD_SIZE as u8);
+        // Since the board fits in a 5 x 5 area, every field is representable in the index.
+        let mut index = [0; AREA_LEN];
+        for (idx, &(i, j, _)) in fields.iter().enumerate().rev() {
+            let pos = field_index_position(bitboards_center, i, j).unwrap();
+            index[pos] = idx as u8 + 1;
+        }
 
         Self {
             fields,
+            index,
             bitboards_center,
             bbox,
             bitboards,
+            num_cards,
+            num_face_down_fields,
+            rules,
+            history: None,
+            history_start: None,
+        }
+    }
+
+    /// The rules that this board follows.
+    pub fn rules(&self) -> Rules {
+        self.rules
+    }
+
+    /// Starts recording every card that is played on this board, see [`history()`](Board::history).
+    ///
+    /// The boards returned by [`CalculatedEffects::execute()`] keep recording. Cards played
+    /// before this was called are not known.
+    pub fn with_history(mut self) -> Self {
+        if self.history.is_none() {
+            self.history = Some(Vec::new());
+            self.history_start = Some(self.fields.as_slice().into());
+        }
+        self
+    }
+
+    /// The cards played on this board since [`with_history()`](Board::with_history) was
+    /// called, in the order they were played. `None` if the history is not recorded.
+    pub fn history(&self) -> Option<&[CardToPlay]> {
+        self.history.as_deref()
+    }
+
+    /// The cards on the field at the given coordinate, in the order they were stacked, and
+    /// which of them face up. `None` if the history is not recorded.
+    ///
+    /// Cards that were on the field before [`with_history()`](Board::with_history) was
+    /// called are known only as a set, except for their top card. The field has no cards if
+    /// there are none at the coordinate. This replays the history, so it takes time
+    /// proportional to its length.
+    pub fn field_history(&self, i: i8, j: i8) -> Option<FieldHistory> {
+        let (history, start) = (self.history.as_ref()?, self.history_start.as_ref()?);
+        let mut field_history = start
+            .iter()
+            .find(|&&(fi, fj, _)| (fi, fj) == (i, j))
+            .map(|&(_, _, field)| FieldHistory::from(field))
+            .unwrap_or_default();
+        let mut board = Board::from_valid_fields_list(start.to_vec(), self.rules);
+        for &card_to_play in history {
+            let effects = board
+                .calculate(card_to_play)
+                .expect("The history contains an illegal card");
+            if (card_to_play.i, card_to_play.j) == (i, j) {
+                field_history.place_card(card_to_play.card);
+            }
+            if effects.diff.flipped.contains(i, j) {
+                field_history.turn_face_down();
+            }
+            if effects.diff.won.contains(i, j) {
+                field_history = FieldHistory::default();
+            }
+            board = effects.execute();
         }
+        Some(field_history)
     }
 
     /// Calculate playing a card and return the effects that this would have.
@@ -118,59 +296,75 @@ impl Board {
         &self,
         card_to_play: CardToPlay,
     ) -> Result<CalculatedEffects<'_>, IllegalCardPlayed> {
-        let CardToPlay { i, j, card, .. } = card_to_play;
-
-        if !self.is_in_bounds(i, j) {
-            return Err(IllegalCardPlayed::OutOfBounds);
-        }
-
-        let existing_field: Option<CompactField> = self.get(i, j);
-
-        // Check whether there is already a card on that field on which
-        // the new card cannot be placed.
-        if let Some(incompatible_card) = existing_field
-            .and_then(|f| f.top_card())
-            .filter(|&c| !card.can_be_placed_on(c))
-        {
-            return Err(IllegalCardPlayed::IncompatibleCard {
-                existing_card: incompatible_card,
-            });
-        }
-
-        // Since a field only exists when there's a card on it, existence of the
-        // field means that this is a combo.
-        let combo = existing_field.is_some();
-
-        let flipped = if combo {
-            // Activate the face card's abilities
-            self.fields_to_flip(card_to_play)?
-        } else {
-            BitBoard::empty_board_centered_at(self.bitboards_center)
-        };
+        let (flipped, won, combo) = self.flipped_and_won(card_to_play)?;
+        Ok(self.effects(card_to_play, flipped, won, combo))
+    }
 
-        let won: BitBoard = {
-            // A bitboard representation of all cards of the same suit as the newly
-            // placed card. If there is a line of 4 cards, it must be cards of this
-            // suit.
-            let cards_of_same_suit = self.bitboards[card.suit as usize]
-                .insert(i, j)
-                .difference(flipped);
-            cards_of_same_suit
-                .lines_going_through_point(i, j)
-                .remove(i, j)
-        };
+    /// Calculates the effects of all the ways to legally play the given card, i.e. of each
+    /// of its [`legal_plays()`](Board::legal_plays).
+    ///
+    /// This is faster than calling [`calculate()`](Board::calculate) for every location of
+    /// the card, since the checks whether the card can be placed there are done for all
+    /// locations at once.
+    pub fn calculate_all(
+        &self,
+        card: Card,
+    ) -> impl Iterator<Item = (CardToPlay, CalculatedEffects<'_>)> + '_ {
+        let king_ability = card.rank == Rank::King && self.rules.king_ability;
+        self.locations_for_card(card)
+            .into_iter()
+            .flat_map(move |(i, j)| {
+                let combo = self.get(i, j).is_some();
+                // For a king played on top of another card, every target of its ability is
+                // a separate play. The king itself is a possible target too.
+                let with_targets = king_ability && combo;
+                let targets = with_targets
+                    .then(|| self.valid_king_targets(i, j))
+                    .into_iter()
+                    .flatten()
+                    .map(Some);
+                let no_target = (!with_targets).then_some(None);
+                no_target.into_iter().chain(targets).map(move |target| {
+                    let ctp = CardToPlay {
+                        card,
+                        i,
+                        j,
+                        target_field_for_king_ability: target,
+                    };
+                    (ctp, combo)
+                })
+            })
+            .filter_map(move |(ctp, combo)| {
+                let (flipped, won) = self.flipped_and_won_at_location(ctp, combo).ok()?;
+                Some((ctp, self.effects(ctp, flipped, won, combo)))
+            })
+    }
 
-        let cards_won = {
-            let mut set = CardsSet::new();
+    // Internal helper that collects the effects of a play from its flipped and won fields
+    fn effects(
+        &self,
+        card_to_play: CardToPlay,
+        flipped: BitBoard,
+        won: BitBoard,
+        combo: bool,
+    ) -> CalculatedEffects<'_> {
+        let CardToPlay {
+            i,
+            j,
+            card,
+            target_field_for_king_ability,
+        } = card_to_play;
+        let mut cards_won = CardsSet::new();
+        // Most plays don't win anything
+        if !won.is_empty() {
             for &(i, j, field) in &self.fields {
                 if won.contains(i, j) {
-                    set |= field.all_cards();
+                    cards_won |= field.all_cards();
                 }
             }
-            set
-        };
+        }
 
-        Ok(CalculatedEffects {
+        CalculatedEffects {
             board: self,
             diff: Diff {
                 flipped,
@@ -178,10 +372,46 @@ impl Board {
                 new_card: card,
                 new_card_i: i,
                 new_card_j: j,
+                target_field_for_king_ability,
             },
             cards_won,
             combo,
-        })
+        }
+    }
+
+    /// Describes what playing a card would do, or why it is illegal, e.g. for teaching the rules.
+    ///
+    /// This is a shortcut for [`calculate()`](Board::calculate) followed by
+    /// [`CalculatedEffects::explain()`].
+    pub fn explain(&self, card_to_play: CardToPlay) -> MoveExplanation {
+        match self.calculate(card_to_play) {
+            Ok(effects) => MoveExplanation::Legal(effects.explain()),
+            Err(reason) => MoveExplanation::Illegal {
+                card: card_to_play.card,
+                i: card_to_play.i,
+                j: card_to_play.j,
+                reason,
+            },
+        }
+    }
+
+    /// The number of cards that would be won by playing a card.
+    ///
+    /// This is equal to the length of [`cards_won`](CalculatedEffects::cards_won) in the result of
+    /// [`calculate()`](Board::calculate), but faster, since it skips preparing the changes to the board.
+    /// Whether the card would start a combo can be checked with `board.get(i, j).is_some()`.
+    pub fn score_if_played(&self, card_to_play: CardToPlay) -> Result<u32, IllegalCardPlayed> {
+        let (_, won, _) = self.flipped_and_won(card_to_play)?;
+        if won.is_empty() {
+            return Ok(0);
+        }
+        let mut score = 0;
+        for &(i, j, field) in &self.fields {
+            if won.contains(i, j) {
+                score += field.all_cards().len();
+            }
+        }
+        Ok(score)
     }
 
     /// Shorthand for [`calculate()`](Board::calculate) immediately followed by [`execute()`](CalculatedEffects::execute).
@@ -191,7 +421,8 @@ impl Board {
 
     /// The smallest area enclosing the cards currently on the board.
     ///
-    /// This is always smaller than or equal to [`BOARD_SIZE`] x [`BOARD_SIZE`].
+    /// This is always smaller than or equal to [`board_size`](Rules::board_size) x
+    /// [`board_size`](Rules::board_size) of the board's rules.
     ///
     /// See [`Self::playable_area()`] for the area where cards may be placed.
     pub fn bbox(&self) -> BoundingBox {
@@ -205,13 +436,14 @@ impl Board {
     ///
     /// Note that this area can be bigger than [`BOARD_SIZE`] x [`BOARD_SIZE`],
     /// e.g. if there's only a single card on the board so far, the area
-    /// will be the 7 x 7 area centered on that card.
+    /// will be the 7 x 7 area centered on that card with the standard rules.
     pub fn playable_area(&self) -> BoundingBox {
+        let board_size = self.rules.board_size;
         BoundingBox {
-            i_min: self.bbox.i_max - BOARD_SIZE + 1,
-            j_min: self.bbox.j_max - BOARD_SIZE + 1,
-            i_max: self.bbox.i_min + BOARD_SIZE - 1,
-            j_max: self.bbox.j_min + BOARD_SIZE - 1,
+            i_min: self.bbox.i_max - board_size + 1,
+            j_min: self.bbox.j_max - board_size + 1,
+            i_max: self.bbox.i_min + board_size - 1,
+            j_max: self.bbox.j_min + board_size - 1,
         }
     }
 
@@ -235,21 +467,136 @@ impl Board {
         self.bitboards[Suit::Club as usize]
     }
 
+    /// The number of visible cards of each suit, indexed by `Suit as usize`.
+    pub fn suit_counts(&self) -> [u32; 4] {
+        self.bitboards.map(|bb| bb.num_entries())
+    }
+
+    /// The number of cards on the board, face up or down.
+    pub fn num_cards(&self) -> u32 {
+        self.num_cards
+    }
+
+    /// The number of fields whose cards all face down, i.e. the size of
+    /// [`face_down_only()`](Board::face_down_only).
+    pub fn num_face_down_fields(&self) -> u32 {
+        self.num_face_down_fields
+    }
+
+    /// The fields with at least one card on them.
+    pub fn occupied(&self) -> BitBoard {
+        self.fields_where(|_| true)
+    }
+
+    /// The fields whose cards all face down, i.e. that have no top card.
+    pub fn face_down_only(&self) -> BitBoard {
+        self.fields_where(|field| field.top_card().is_none())
+    }
+
+    /// The fields with at least `n` cards on them, face up or down.
+    ///
+    /// These are the cards that are won when the field is part of a completed line.
+    pub fn stacks_with_at_least(&self, n: u32) -> BitBoard {
+        self.fields_where(|field| field.all_cards().len() >= n)
+    }
+
+    /// The fields that the ability of a king played on top of the card at `(i, j)` may flip:
+    /// those with a face-up top card, and `(i, j)` itself.
+    ///
+    /// Empty if there is no card at `(i, j)`, or kings have no ability in this board's
+    /// [`Rules`], since the king wouldn't flip anything then.
+    pub fn valid_king_targets(&self, i: i8, j: i8) -> BitBoard {
+        if !self.rules.king_ability || self.get(i, j).is_none() {
+            return BitBoard::empty_board_centered_at(self.bitboards_center);
+        }
+        self.fields_where(|field| field.top_card().is_some())
+            .insert(i, j)
+    }
+
+    /// The fields above, below, left and right of `(i, j)` that are in bounds, whether or not
+    /// they have cards on them.
+    ///
+    /// These are the fields that a jack played on `(i, j)` flips.
+    pub fn orthogonal_neighbors(&self, i: i8, j: i8) -> BitBoard {
+        self.neighbors_in_bounds(i, j, [(-1, 0), (1, 0), (0, -1), (0, 1)])
+    }
+
+    /// The fields diagonally next to `(i, j)` that are in bounds, whether or not they have
+    /// cards on them.
+    ///
+    /// These are the fields that a queen played on `(i, j)` flips.
+    pub fn diagonal_neighbors(&self, i: i8, j: i8) -> BitBoard {
+        self.neighbors_in_bounds(i, j, [(-1, -1), (-1, 1), (1, -1), (1, 1)])
+    }
+
+    // The fields at these offsets from (i, j) that are in bounds
+    fn neighbors_in_bounds(&self, i: i8, j: i8, offsets: [(i8, i8); 4]) -> BitBoard {
+        let mut bitboard = BitBoard::empty_board_centered_at(self.bitboards_center);
+        for (di, dj) in offsets {
+            let (Some(ni), Some(nj)) = (i.checked_add(di), j.checked_add(dj)) else {
+                continue;
+            };
+            if self.is_in_bounds(ni, nj) {
+                bitboard = bitboard.insert(ni, nj);
+            }
+        }
+        bitboard
+    }
+
+    // The fields for which the predicate holds, as a bitboard like those of the suits
+    fn fields_where(&self, predicate: impl Fn(CompactField) -> bool) -> BitBoard {
+        let mut bitboard = BitBoard::empty_board_centered_at(self.bitboards_center);
+        for &(i, j, field) in &self.fields {
+            if predicate(field) {
+                bitboard = bitboard.insert(i, j);
+            }
+        }
+        bitboard
+    }
+
     /// Is it possible to play this card anywhere?
     ///
     /// This is a bit more efficient than checking [`Self::locations_for_card()`].
     pub fn possible_to_play_card(&self, card: Card) -> bool {
-        if self.fields.len() < 16 {
+        let board_size = self.rules.board_size as usize;
+        if self.fields.len() < board_size * board_size {
             return true;
         }
-        for (_, _, field) in &self.fields {
-            if field.can_place_card(card) {
+        for &(_, _, field) in &self.fields {
+            if self.can_place_card_on(field, card) {
                 return true;
             }
         }
         false
     }
 
+    /// Whether a player with this hand must skip their turn, i.e. none of the cards can
+    /// be played anywhere.
+    ///
+    /// Only then is skipping the turn, see [`TurnAction::Skip`](crate::TurnAction::Skip), legal.
+    pub fn must_skip(&self, hand: CardsSet) -> bool {
+        !hand
+            .into_iter()
+            .any(|card| self.possible_to_play_card(card))
+    }
+
+    /// Whether every player, with these hands, must skip their turn.
+    ///
+    /// Since skipping doesn't change the board or the hands, the game then ends. This can
+    /// only happen once the board has no [open placements](Self::open_placements_count) left.
+    pub fn is_locked(&self, hands: &[CardsSet]) -> bool {
+        hands.iter().all(|&hand| self.must_skip(hand))
+    }
+
+    /// The number of coordinates without a card on them where a card can be played, see
+    /// [`empty_playable_locations()`](Self::empty_playable_locations).
+    ///
+    /// Once this is zero, the board fills the whole [`board_size`](Rules::board_size) x
+    /// [`board_size`](Rules::board_size) area, and cards can only be played on top of others.
+    pub fn open_placements_count(&self) -> u32 {
+        self.empty_playable_locations().num_entries()
+    }
+
     /// Returns all the coordinates that are valid places to play the given card.
     pub fn locations_for_card(&self, card: Card) -> BitBoard {
         // Create a BitBoard with 1 in every location where any card could be played
@@ -264,39 +611,132 @@ impl Board {
             .insert_area(i_min, j_min, i_max, j_max);
 
         for &(i, j, field) in &self.fields {
-            if !field.can_place_card(card) {
+            if !self.can_place_card_on(field, card) {
                 bitboard = bitboard.remove(i, j);
             }
         }
         bitboard
     }
 
+    /// Like [`locations_for_card()`](Self::locations_for_card), but only the locations that
+    /// the policy allows.
+    ///
+    /// This is much cheaper than calculating every placement to filter them afterwards.
+    pub fn locations_for_card_with_policy(&self, card: Card, policy: PlacementPolicy) -> BitBoard {
+        let mut locations = if policy.combo_only {
+            self.combo_locations_for_card(card)
+        } else {
+            self.locations_for_card(card)
+        };
+        if let Some(mask) = policy.mask {
+            for (i, j) in locations {
+                if !mask.contains(i, j) {
+                    locations = locations.remove(i, j);
+                }
+            }
+        }
+        let flips =
+            matches!(card.rank, Rank::Jack | Rank::Queen) && self.rules.has_ability(card.rank);
+        if policy.avoid_own_suit_flips && flips {
+            let own_suit = self.bitboards[card.suit as usize];
+            for (i, j) in locations {
+                // Abilities are only activated on top of another card
+                if self.get(i, j).is_none() {
+                    continue;
+                }
+                let neighbors = if card.rank == Rank::Jack {
+                    self.orthogonal_neighbors(i, j)
+                } else {
+                    self.diagonal_neighbors(i, j)
+                };
+                if !(neighbors & own_suit).is_empty() {
+                    locations = locations.remove(i, j);
+                }
+            }
+        }
+        locations
+    }
+
+    /// Returns the coordinates in the playable area that have no card on them yet.
+    ///
+    /// Any card can be played there, and it never starts a combo.
+    pub fn empty_playable_locations(&self) -> BitBoard {
+        let BoundingBox {
+            i_min,
+            j_min,
+            i_max,
+            j_max,
+        } = self.playable_area();
+        let mut bitboard = BitBoard::empty_board_centered_at(self.bitboards_center)
+            .insert_area(i_min, j_min, i_max, j_max);
+        for &(i, j, _) in &self.fields {
+            bitboard = bitboard.remove(i, j);
+        }
+        bitboard
+    }
+
     /// Returns all the coordinates that already have a card on them and are valid places to play the given card.
     pub fn combo_locations_for_card(&self, card: Card) -> BitBoard {
         let mut bitboard = BitBoard::empty_board_centered_at(self.bitboards_center);
         for &(i, j, field) in &self.fields {
-            if field.can_place_card(card) {
+            if self.can_place_card_on(field, card) {
                 bitboard = bitboard.insert(i, j);
             }
         }
         bitboard
     }
 
+    /// Finds the places where a card of this suit would complete a line, e.g. to keep
+    /// the opponent from winning cards there.
+    ///
+    /// For each such place, the cards of the line that it would complete are returned as a
+    /// `BitBoard`, along with the coordinates of the place. These are the lines of three
+    /// (or, generally, [`line_length`](Rules::line_length) minus one) visible cards of the
+    /// suit. The place can be empty or have another card on it, but whether a particular
+    /// card can actually be played there is not checked, see
+    /// [`locations_for_card()`](Self::locations_for_card). Also, face cards that are
+    /// played on top of another card might flip some of the line's cards with their ability.
+    pub fn line_threats(&self, suit: Suit) -> impl Iterator<Item = (BitBoard, (i8, i8))> + '_ {
+        let cards_of_suit = self.bitboards[suit as usize];
+        self.playable_area()
+            .iter()
+            .filter(move |&(i, j)| !cards_of_suit.contains(i, j))
+            .filter_map(move |(i, j)| {
+                let lines = self.lines_through(cards_of_suit.insert(i, j), i, j);
+                (!lines.is_empty()).then(|| (lines.remove(i, j), (i, j)))
+            })
+    }
+
+    /// Returns all the ways to legally play the given card.
+    ///
+    /// For a king that is played on top of another card, every possible target for its
+    /// ability is a separate play.
+    pub fn legal_plays(&self, card: Card) -> Vec<CardToPlay> {
+        self.calculate_all(card).map(|(ctp, _)| ctp).collect()
+    }
+
     /// Returns a [`CompactField`] if there are any cards at the given coordinate.
     pub fn get(&self, i: i8, j: i8) -> Option<CompactField> {
-        for &(i_field, j_field, compact_field) in &self.fields {
-            if i_field == i && j_field == j {
-                return Some(compact_field);
-            }
-        }
-        None
+        let pos = field_index_position(self.bitboards_center, i, j)?;
+        let idx = self.index[pos].checked_sub(1)?;
+        Some(self.fields[idx as usize].2)
     }
 
     pub fn is_in_bounds(&self, i: i8, j: i8) -> bool {
-        (i.checked_sub(self.bbox.i_min).map(|diff| diff < BOARD_SIZE)).unwrap_or(false)
-            && (self.bbox.i_max.checked_sub(i).map(|diff| diff < BOARD_SIZE)).unwrap_or(false)
-            && (j.checked_sub(self.bbox.j_min).map(|diff| diff < BOARD_SIZE)).unwrap_or(false)
-            && (self.bbox.j_max.checked_sub(j).map(|diff| diff < BOARD_SIZE)).unwrap_or(false)
+        let board_size = self.rules.board_size;
+        (i.checked_sub(self.bbox.i_min).map(|diff| diff < board_size)).unwrap_or(false)
+            && (self.bbox.i_max.checked_sub(i).map(|diff| diff < board_size)).unwrap_or(false)
+            && (j.checked_sub(self.bbox.j_min).map(|diff| diff < board_size)).unwrap_or(false)
+            && (self.bbox.j_max.checked_sub(j).map(|diff| diff < board_size)).unwrap_or(false)
+    }
+
+    // Like CompactField::can_place_card(), but with this board's rules
+    fn can_place_card_on(&self, field: CompactField, card: Card) -> bool {
+        if let Some(c) = field.top_card() {
+            self.rules.can_be_placed_on(card, c)
+        } else {
+            true
+        }
     }
 
     pub fn to_fields_vec(&self) -> Vec<Field> {
@@ -320,6 +760,94 @@ impl Board {
         fields_vec
     }
 
+    /// The Zobrist key of the cards on the board, which [`Hash`] is based on.
+    ///
+    /// It is the XOR of a pseudorandom number for every card, which depends on the card, its
+    /// coordinates and whether it is face up. So it doesn't depend on the order of the
+    /// fields or on how the board was reached, and neither on the rules.
+    pub fn zobrist_key(&self) -> u64 {
+        let mut key = 0;
+        for &(i, j, field) in &self.fields {
+            let location = u64::from(i as u8) << 16 | u64::from(j as u8) << 8;
+            let top_card = field.top_card().map(|card| (card, 1));
+            let hidden_cards = field.hidden_cards().into_iter().map(|card| (card, 0));
+            for (card, face_up) in top_card.into_iter().chain(hidden_cards) {
+                key ^= split_mix(location | u64::from(card.index()) << 1 | face_up);
+            }
+        }
+        key
+    }
+
+    // Internal helper that checks whether the card can be played, and computes which fields
+    // are flipped and which are won by playing it, and whether it's a combo.
+    fn flipped_and_won(
+        &self,
+        card_to_play: CardToPlay,
+    ) -> Result<(BitBoard, BitBoard, bool), IllegalCardPlayed> {
+        let CardToPlay { i, j, card, .. } = card_to_play;
+
+        if !self.is_in_bounds(i, j) {
+            return Err(IllegalCardPlayed::OutOfBounds);
+        }
+
+        let existing_field: Option<CompactField> = self.get(i, j);
+
+        // Check whether there is already a card on that field on which
+        // the new card cannot be placed.
+        if let Some(incompatible_card) = existing_field
+            .and_then(|f| f.top_card())
+            .filter(|&c| !self.rules.can_be_placed_on(card, c))
+        {
+            return Err(IllegalCardPlayed::IncompatibleCard {
+                existing_card: incompatible_card,
+            });
+        }
+
+        // Since a field only exists when there's a card on it, existence of the
+        // field means that this is a combo.
+        let combo = existing_field.is_some();
+        let (flipped, won) = self.flipped_and_won_at_location(card_to_play, combo)?;
+        Ok((flipped, won, combo))
+    }
+
+    // Like flipped_and_won(), but for a card that is known to fit at its location
+    fn flipped_and_won_at_location(
+        &self,
+        card_to_play: CardToPlay,
+        combo: bool,
+    ) -> Result<(BitBoard, BitBoard), IllegalCardPlayed> {
+        let CardToPlay { i, j, card, .. } = card_to_play;
+        let flipped = if combo && self.rules.has_ability(card.rank) {
+            // Activate the face card's abilities
+            self.fields_to_flip(card_to_play)?
+        } else {
+            BitBoard::empty_board_centered_at(self.bitboards_center)
+        };
+
+        let won: BitBoard = {
+            // A bitboard representation of all cards of the same suit as the newly
+            // placed card. If there is a line of 4 cards, it must be cards of this
+            // suit.
+            let cards_of_same_suit = self.bitboards[card.suit as usize]
+                .insert(i, j)
+                .difference(flipped);
+            self.lines_through(cards_of_same_suit, i, j).remove(i, j)
+        };
+
+        Ok((flipped, won))
+    }
+
+    // The lines of cards of the same suit that go through the point, which must be one of
+    // the cards
+    fn lines_through(&self, cards_of_same_suit: BitBoard, i: i8, j: i8) -> BitBoard {
+        if self.rules.line_length == 4 && self.rules.board_size == 4 {
+            // Since the board is 4 x 4, lines of 4 are always consecutive
+            cards_of_same_suit.lines_going_through_point(i, j)
+        } else {
+            cards_of_same_suit.lines_of_length_going_through_point(i, j, self.rules.line_length)
+        }
+    }
+
     // Internal helper function to compute fields where the top cards are flipped face-down.
     //
     // Note: The result also contains empty fields and fields
@@ -327,37 +855,22 @@ impl Board {
         let (card_i, card_j) = (card_to_play.i, card_to_play.j);
         let mut flipped = BitBoard::empty_board_centered_at(self.bitboards_center);
         match card_to_play.card.rank {
-            Rank::Jack => {
-                for (i, j) in [
-                    (card_i - 1, card_j),
-                    (card_i + 1, card_j),
-                    (card_i, card_j - 1),
-                    (card_i, card_j + 1),
-                ] {
-                    if self.is_in_bounds(i, j) {
-                        flipped = flipped.insert(i, j);
-                    }
-                }
-            }
-            Rank::Queen => {
-                for (i, j) in [
-                    (card_i - 1, card_j - 1),
-                    (card_i - 1, card_j + 1),
-                    (card_i + 1, card_j - 1),
-                    (card_i + 1, card_j + 1),
-                ] {
-                    if self.is_in_bounds(i, j) {
-                        flipped = flipped.insert(i, j);
-                    }
-                }
-            }
+            Rank::Jack => flipped = self.orthogonal_neighbors(card_i, card_j),
+            Rank::Queen => flipped = self.diagonal_neighbors(card_i, card_j),
             Rank::King => {
                 let (tgt_i, tgt_j) = card_to_play
                     .target_field_for_king_ability
                     .ok_or(IllegalCardPlayed::NoTargetForKingAbility)?;
+                if !self.is_in_bounds(tgt_i, tgt_j) {
+                    return Err(IllegalCardPlayed::TargetForKingAbilityIsOutOfBounds {
+                        tgt_i,
+                        tgt_j,
+                    });
+                }
                 let field = self
                     .get(tgt_i, tgt_j)
-                    .ok_or(IllegalCardPlayed::TargetForKingAbilityDoesNotExist { tgt_i, tgt_j })?;
+                    .ok_or(IllegalCardPlayed::TargetForKingAbilityIsEmpty { tgt_i, tgt_j })?;
+                // The king's own field is face up once the king is on it
                 if field.top_card().is_none() && (card_i, card_j) != (tgt_i, tgt_j) {
                     return Err(IllegalCardPlayed::TargetForKingAbilityIsFaceDown { tgt_i, tgt_j });
                 }
@@ -369,6 +882,88 @@ impl Board {
     }
 }
 
+// Converts fields to the representation stored in a board, keeping their order.
+fn to_fields_list(fields: &[Field]) -> Vec<(i8, i8, CompactField)> {
+    fields
+        .iter()
+        .map(|f| (f.i, f.j, CompactField::from(f)))
+        .collect()
+}
+
+pub(crate) fn check_fields_list(
+    fields: &[(i8, i8, CompactField)],
+    board_size: i8,
+) -> Result<(), InvalidBoard> {
+    let Some(&(first_i, first_j, _)) = fields.first() else {
+        return Err(InvalidBoard::NoFields);
+    };
+    let mut bbox = BoundingBox::singleton(first_i, first_j);
+    let mut cards = CardsSet::new();
+    for &(i, j, field) in fields {
+        if field.is_empty() {
+            return Err(InvalidBoard::EmptyField { i, j });
+        }
+        if let Some(card) = (cards & field.all_cards()).into_iter().next() {
+            return Err(InvalidBoard::DuplicateCard { card });
+        }
+        cards |= field.all_cards();
+        bbox.update(i, j);
+    }
+    if bbox.size_i() > board_size as u8 || bbox.size_j() > board_size as u8 {
+        return Err(InvalidBoard::TooLarge { board_size });
+    }
+    // Now all fields are representable in a positional index
+    let mut seen = [false; AREA_LEN];
+    for &(i, j, _) in fields {
+        let pos = field_index_position((first_i, first_j), i, j).unwrap();
+        if core::mem::replace(&mut seen[pos], true) {
+            return Err(InvalidBoard::DuplicateField { i, j });
+        }
+    }
+    Ok(())
+}
+
+// Internal helper that maps a coordinate to its position in the index of a board,
+// if it lies within the 9 x 9 area centered at `bitboards_center`.
+fn field_index_position(bitboards_center: (i8, i8), i: i8, j: i8) -> Option<usize> {
+    let i_local = i.checked_sub(bitboards_center.0 - (MAX_BOARD_SIZE - 1))?;
+    let j_local = j.checked_sub(bitboards_center.1 - (MAX_BOARD_SIZE - 1))?;
+    if (0..AREA_SIZE).contains(&i_local) && (0..AREA_SIZE).contains(&j_local) {
+        Some(i_local as usize * AREA_SIZE as usize + j_local as usize)
+    } else {
+        None
+    }
+}
+
+impl PartialEq for Board {
+    fn eq(&self, other: &Self) -> bool {
+        self.rules == other.rules
+            && self.fields.len() == other.fields.len()
+            && self
+                .fields
+                .iter()
+                .all(|&(i, j, field)| other.get(i, j) == Some(field))
+    }
+}
+
+impl Eq for Board {}
+
+impl Hash for Board {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.rules.hash(state);
+        self.zobrist_key().hash(state);
+    }
+}
+
+// The random numbers of the Zobrist key are computed with the SplitMix64 finalizer instead
+// of a table, so that the coordinates of a field don't need to be bounded
+fn split_mix(x: u64) -> u64 {
+    let mut z = x.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
 impl Deref for Board {
     type Target = [(i8, i8, CompactField)];
 
@@ -382,24 +977,123 @@ impl<'a> CalculatedEffects<'a> {
     pub fn execute(self) -> Board {
         self.diff.apply(self.board)
     }
+
+    /// Like [`execute()`](Self::execute), but overwrites `target` with the new board,
+    /// reusing its allocations.
+    ///
+    /// This avoids allocating for every board in a search, see also [`BoardArena`].
+    pub fn execute_into(self, target: &mut Board) {
+        let fields = core::mem::take(&mut target.fields);
+        let history = target.history.take().unwrap_or_default();
+        *target = self.diff.apply_with_buffers(self.board, fields, history);
+    }
+
+    /// The fields whose top card is turned face-down by the ability of the played face card.
+    ///
+    /// Unlike the neighbors that a jack or queen affects, this only contains fields with a
+    /// face-up card on them, including possibly the field the card is played on.
+    pub fn flipped_fields(&self) -> BitBoard {
+        self.diff.flipped_fields(self.board)
+    }
+
+    /// The fields that are cleared because their cards are won.
+    pub fn won_fields(&self) -> BitBoard {
+        self.diff.won
+    }
+
+    /// Describes the reasons for these effects, e.g. which cards are flipped and which lines are completed.
+    pub fn explain(&self) -> Explanation {
+        let Diff {
+            flipped,
+            won,
+            new_card,
+            new_card_i,
+            new_card_j,
+            ..
+        } = self.diff;
+        let existing_field = self.board.get(new_card_i, new_card_j);
+        let top_card_after_play = |i, j| {
+            if (i, j) == (new_card_i, new_card_j) {
+                Some(new_card)
+            } else {
+                self.board.get(i, j).and_then(|field| field.top_card())
+            }
+        };
+        let flipped = flipped
+            .into_iter()
+            .filter_map(|(i, j)| top_card_after_play(i, j).map(|card| (i, j, card)))
+            .collect();
+        let mut lines: Vec<Line> = Vec::new();
+        for (i, j) in won {
+            if let Some(direction) = LineDirection::between((new_card_i, new_card_j), (i, j)) {
+                match lines.iter_mut().find(|line| line.direction == direction) {
+                    Some(line) => line.fields.push((i, j)),
+                    None => lines.push(Line {
+                        direction,
+                        suit: new_card.suit,
+                        fields: vec![(new_card_i, new_card_j), (i, j)],
+                    }),
+                }
+            }
+        }
+        for line in &mut lines {
+            line.fields.sort();
+        }
+        Explanation {
+            card: new_card,
+            i: new_card_i,
+            j: new_card_j,
+            placed_on: existing_field.and_then(|field| field.top_card()),
+            combo: self.combo,
+            ability: Some(new_card.rank)
+                .filter(|&rank| self.combo && self.board.rules.has_ability(rank)),
+            flipped,
+            lines,
+            cards_won: self.cards_won,
+        }
+    }
 }
 
 impl Diff {
+    fn flipped_fields(&self, board: &Board) -> BitBoard {
+        let face_up = board.bitboards.iter().fold(
+            BitBoard::empty_board_centered_at(board.bitboards_center)
+                .insert(self.new_card_i, self.new_card_j),
+            |acc, &bb| acc | bb,
+        );
+        self.flipped & face_up
+    }
+
     fn apply(self, board: &Board) -> Board {
-        let mut new_fields = Vec::with_capacity(board.fields.len() + 1);
+        let new_fields = Vec::with_capacity(board.fields.len() + 1);
+        self.apply_with_buffers(board, new_fields, Vec::new())
+    }
+
+    // Like apply(), but the new board's fields and history are stored in the given Vecs,
+    // whose contents are discarded
+    pub(crate) fn apply_with_buffers(
+        self,
+        board: &Board,
+        mut new_fields: Vec<(i8, i8, CompactField)>,
+        mut history_buffer: Vec<CardToPlay>,
+    ) -> Board {
+        new_fields.clear();
         let mut bbox = BoundingBox::singleton(self.new_card_i, self.new_card_j);
         let bitboards_center = (self.new_card_i, self.new_card_j);
         let mut bitboards = [BitBoard::empty_board_centered_at(bitboards_center); 4];
+        let mut index = [0; AREA_LEN];
         let mut field_for_new_card_already_exists = false;
+        let mut num_cards = 0;
+        let mut num_face_down_fields = 0;
 
         // Copy over the fields while applying changes and updating derived
-        // data (bbox and bitboards)
+        // data (bbox, bitboards and counts)
         for &(i, j, mut field) in board.fields.iter() {
             if self.won.contains(i, j) {
                 continue;
             }
             if (i, j) == (self.new_card_i, self.new_card_j) {
-                field = field.place_card(self.new_card);
+                field = field.place_card_unchecked(self.new_card);
                 field_for_new_card_already_exists = true;
             }
             if self.flipped.contains(i, j) {
@@ -408,39 +1102,70 @@ impl Diff {
             new_fields.push((i, j, field));
 
             // Update derived data
+            let pos = field_index_position(bitboards_center, i, j)
+                .expect("Field outside of the area around the new card");
+            index[pos] = new_fields.len() as u8;
             bbox.update(i, j);
+            num_cards += field.num_hidden_cards();
             if let Some(Card { suit, .. }) = field.top_card() {
                 bitboards[suit as usize] = bitboards[suit as usize].insert(i, j);
+                num_cards += 1;
+            } else {
+                num_face_down_fields += 1;
             }
         }
 
         // Handle the new card, if it was not placed on a preexisting field
         if !field_for_new_card_already_exists {
             let mut new_field = CompactField::new().place_card(self.new_card);
+            num_cards += 1;
             if self.flipped.contains(self.new_card_i, self.new_card_j) {
                 new_field = new_field.turn_face_down();
+                num_face_down_fields += 1;
             } else {
                 bitboards[self.new_card.suit as usize] =
                     bitboards[self.new_card.suit as usize].insert(self.new_card_i, self.new_card_j);
             }
             new_fields.push((self.new_card_i, self.new_card_j, new_field));
+            // The new card is at the center of the index
+            index[AREA_LEN / 2] = new_fields.len() as u8;
         }
 
+        let history = board.history.as_ref().map(|history| {
+            history_buffer.clear();
+            history_buffer.extend_from_slice(history);
+            history_buffer.push(CardToPlay {
+                card: self.new_card,
+                i: self.new_card_i,
+                j: self.new_card_j,
+                target_field_for_king_ability: self.target_field_for_king_ability,
+            });
+            history_buffer
+        });
+
         Board {
             bitboards_center,
             fields: new_fields,
+            index,
             bbox,
             bitboards,
+            num_cards,
+            num_face_down_fields,
+            rules: board.rules,
+            history,
+            history_start: board.history_start.clone(),
         }
     }
 }
 
 #[cfg(feature = "python")]
 mod python {
-    use pyo3::{pyclass, pymethods, Py};
+    use pyo3::exceptions::PyValueError;
+    use pyo3::types::PyByteArray;
+    use pyo3::{pyclass, pymethods, Py, PyAny, PyResult, Python};
 
     use super::*;
-    use crate::{BoundingBox, CardToPlay, CompactField, IllegalCardPlayed};
+    use crate::{BoundingBox, CardToPlay, CompactField, FieldHistory, IllegalCardPlayed};
 
     #[pyclass]
     pub struct CalculatedEffects {
@@ -457,8 +1182,14 @@ mod python {
     #[pymethods]
     impl Board {
         #[new]
-        fn py_from_fields_list(fields: Vec<(i8, i8, CompactField)>) -> Self {
-            Self::from_fields_list(fields)
+        fn py_new(fields: Vec<Field>) -> Result<Self, InvalidBoard> {
+            Self::try_new(&fields)
+        }
+
+        #[staticmethod]
+        #[pyo3(name = "from_fields_list")]
+        fn py_from_fields_list(fields: Vec<(i8, i8, CompactField)>) -> Result<Self, InvalidBoard> {
+            Self::try_from_fields_list(fields)
         }
 
         #[pyo3(name = "to_fields")]
@@ -466,6 +1197,49 @@ mod python {
             self.fields.clone()
         }
 
+        #[pyo3(name = "to_fields_vec")]
+        fn py_to_fields_vec(&self) -> Vec<Field> {
+            self.to_fields_vec()
+        }
+
+        /// The planes of `encode_board_planes()` as a NumPy array, which is built from a
+        /// buffer without converting each value to a Python object.
+        #[pyo3(name = "to_numpy")]
+        #[pyo3(signature = (dtype = "uint8"))]
+        fn py_to_numpy<'py>(&self, py: Python<'py>, dtype: &str) -> PyResult<&'py PyAny> {
+            if self.rules.board_size > crate::BOARD_SIZE {
+                return Err(PyValueError::new_err(format!(
+                    "Boards of size {} can't be encoded, only those up to {}",
+                    self.rules.board_size,
+                    crate::BOARD_SIZE
+                )));
+            }
+            let planes = crate::encode_board_planes(self);
+            let values = planes.iter().flatten().flatten();
+            let bytes: Vec<u8> = match dtype {
+                "uint8" => values.copied().collect(),
+                "float32" => values.flat_map(|&v| f32::from(v).to_ne_bytes()).collect(),
+                _ => {
+                    return Err(PyValueError::new_err(format!(
+                        "Unsupported dtype '{}', must be 'uint8' or 'float32'",
+                        dtype
+                    )))
+                }
+            };
+            let shape = (crate::NUM_PLANES, crate::PLANE_SIZE, crate::PLANE_SIZE);
+            py.import("numpy")?
+                .call_method1("frombuffer", (PyByteArray::new(py, &bytes), dtype))?
+                .call_method1("reshape", (shape,))
+        }
+
+        fn __len__(&self) -> usize {
+            self.len()
+        }
+
+        fn __str__(&self) -> String {
+            crate::visualize_top_cards(&self.to_fields_vec())
+        }
+
         #[pyo3(name = "calculate")]
         pub(crate) fn py_calculate(
             slf: Py<Self>,
@@ -484,6 +1258,33 @@ mod python {
             })
         }
 
+        #[pyo3(name = "calculate_all")]
+        fn py_calculate_all(slf: Py<Self>, card: Card) -> Vec<(CardToPlay, CalculatedEffects)> {
+            let plays: Vec<_> = pyo3::Python::with_gil(|py| {
+                slf.borrow(py)
+                    .calculate_all(card)
+                    .map(|(ctp, calc)| (ctp, calc.diff, calc.cards_won, calc.combo))
+                    .collect()
+            });
+            plays
+                .into_iter()
+                .map(|(ctp, diff, cards_won, combo)| {
+                    let effects = CalculatedEffects {
+                        board: slf.clone(),
+                        diff,
+                        cards_won,
+                        combo,
+                    };
+                    (ctp, effects)
+                })
+                .collect()
+        }
+
+        #[pyo3(name = "score_if_played")]
+        fn py_score_if_played(&self, card_to_play: CardToPlay) -> Result<u32, IllegalCardPlayed> {
+            self.score_if_played(card_to_play)
+        }
+
         #[pyo3(name = "play_card")]
         fn py_play_card(&self, card_to_play: CardToPlay) -> Result<Board, IllegalCardPlayed> {
             self.play_card(card_to_play)
@@ -519,21 +1320,128 @@ mod python {
             self.clubs()
         }
 
+        #[pyo3(name = "suit_counts")]
+        fn py_suit_counts(&self) -> [u32; 4] {
+            self.suit_counts()
+        }
+
+        #[pyo3(name = "num_cards")]
+        fn py_num_cards(&self) -> u32 {
+            self.num_cards()
+        }
+
+        #[pyo3(name = "num_face_down_fields")]
+        fn py_num_face_down_fields(&self) -> u32 {
+            self.num_face_down_fields()
+        }
+
+        #[pyo3(name = "occupied")]
+        fn py_occupied(&self) -> BitBoard {
+            self.occupied()
+        }
+
+        #[pyo3(name = "face_down_only")]
+        fn py_face_down_only(&self) -> BitBoard {
+            self.face_down_only()
+        }
+
+        #[pyo3(name = "stacks_with_at_least")]
+        fn py_stacks_with_at_least(&self, n: u32) -> BitBoard {
+            self.stacks_with_at_least(n)
+        }
+
+        #[pyo3(name = "valid_king_targets")]
+        fn py_valid_king_targets(&self, i: i8, j: i8) -> BitBoard {
+            self.valid_king_targets(i, j)
+        }
+
+        #[pyo3(name = "orthogonal_neighbors")]
+        fn py_orthogonal_neighbors(&self, i: i8, j: i8) -> BitBoard {
+            self.orthogonal_neighbors(i, j)
+        }
+
+        #[pyo3(name = "diagonal_neighbors")]
+        fn py_diagonal_neighbors(&self, i: i8, j: i8) -> BitBoard {
+            self.diagonal_neighbors(i, j)
+        }
+
         #[pyo3(name = "possible_to_play_card")]
         fn py_possible_to_play_card(&self, card: Card) -> bool {
             self.possible_to_play_card(card)
         }
 
+        #[pyo3(name = "must_skip")]
+        fn py_must_skip(&self, hand: CardsSet) -> bool {
+            self.must_skip(hand)
+        }
+
+        #[pyo3(name = "is_locked")]
+        fn py_is_locked(&self, hands: Vec<CardsSet>) -> bool {
+            self.is_locked(&hands)
+        }
+
+        #[pyo3(name = "open_placements_count")]
+        fn py_open_placements_count(&self) -> u32 {
+            self.open_placements_count()
+        }
+
         #[pyo3(name = "locations_for_card")]
         fn py_locations_for_card(&self, card: Card) -> BitBoard {
             self.locations_for_card(card)
         }
 
+        #[pyo3(name = "empty_playable_locations")]
+        fn py_empty_playable_locations(&self) -> BitBoard {
+            self.empty_playable_locations()
+        }
+
+        #[pyo3(name = "line_threats")]
+        fn py_line_threats(&self, suit: Suit) -> Vec<(BitBoard, (i8, i8))> {
+            self.line_threats(suit).collect()
+        }
+
         #[pyo3(name = "combo_locations_for_card")]
         fn py_combo_locations_for_card(&self, card: Card) -> BitBoard {
             self.combo_locations_for_card(card)
         }
 
+        #[pyo3(name = "locations_for_card_with_policy")]
+        #[pyo3(signature = (card, *, combo_only=false, avoid_own_suit_flips=false, mask=None))]
+        fn py_locations_for_card_with_policy(
+            &self,
+            card: Card,
+            combo_only: bool,
+            avoid_own_suit_flips: bool,
+            mask: Option<BitBoard>,
+        ) -> BitBoard {
+            let policy = PlacementPolicy {
+                combo_only,
+                avoid_own_suit_flips,
+                mask,
+            };
+            self.locations_for_card_with_policy(card, policy)
+        }
+
+        #[pyo3(name = "legal_plays")]
+        fn py_legal_plays(&self, card: Card) -> Vec<CardToPlay> {
+            self.legal_plays(card)
+        }
+
+        #[pyo3(name = "with_history")]
+        fn py_with_history(&self) -> Board {
+            self.clone().with_history()
+        }
+
+        #[pyo3(name = "history")]
+        fn py_history(&self) -> Option<Vec<CardToPlay>> {
+            self.history.clone()
+        }
+
+        #[pyo3(name = "field_history")]
+        fn py_field_history(&self, i: i8, j: i8) -> Option<FieldHistory> {
+            self.field_history(i, j)
+        }
+
         #[pyo3(name = "get")]
         fn py_get(&self, i: i8, j: i8) -> Option<CompactField> {
             self.get(i, j)
@@ -543,6 +1451,11 @@ mod python {
         fn py_is_in_bounds(&self, i: i8, j: i8) -> bool {
             self.is_in_bounds(i, j)
         }
+
+        #[pyo3(name = "zobrist_key")]
+        fn py_zobrist_key(&self) -> u64 {
+            self.zobrist_key()
+        }
     }
 
     #[pymethods]
@@ -550,6 +1463,14 @@ mod python {
         fn execute(&self) -> Board {
             pyo3::Python::with_gil(|py| self.diff.clone().apply(&self.board.borrow(py)))
         }
+
+        fn flipped_fields(&self) -> BitBoard {
+            pyo3::Python::with_gil(|py| self.diff.flipped_fields(&self.board.borrow(py)))
+        }
+
+        fn won_fields(&self) -> BitBoard {
+            self.diff.won
+        }
     }
 }
 
@@ -559,12 +1480,310 @@ pub use python::CalculatedEffects as PyCalculatedEffects;
 
 #[cfg(test)]
 mod tests {
-    use std::collections::BTreeSet;
+    use alloc::collections::BTreeSet;
 
     use quickcheck::quickcheck;
 
     use super::*;
-    use crate::{arbitrary::PlayCardInput, card, CardToPlay};
+    use crate::{arbitrary::PlayCardInput, card, ctp, field, CardToPlay, GameInProgress};
+
+    #[test]
+    fn invalid_boards_are_rejected() {
+        // Unlike the usual test fields, these may be empty
+        let any_field = |i, j, top_card| Field {
+            i,
+            j,
+            top_card,
+            hidden_cards: BTreeSet::new(),
+        };
+        let card = Some(card!("2♥"));
+        assert_eq!(Board::try_new(&[]).err(), Some(InvalidBoard::NoFields));
+        assert_eq!(
+            Board::try_new(&[any_field(0, 0, card), any_field(1, 0, None)]).err(),
+            Some(InvalidBoard::EmptyField { i: 1, j: 0 })
+        );
+        let other_card = Some(card!("3♥"));
+        assert_eq!(
+            Board::try_new(&[
+                any_field(0, 0, card),
+                any_field(1, 0, other_card),
+                any_field(0, 0, Some(card!("4♥")))
+            ])
+            .err(),
+            Some(InvalidBoard::DuplicateField { i: 0, j: 0 })
+        );
+        assert_eq!(
+            Board::try_new(&[
+                any_field(0, 0, card),
+                any_field(1, 0, other_card),
+                Field {
+                    i: 0,
+                    j: 1,
+                    top_card: None,
+                    hidden_cards: BTreeSet::from([card!("4♥"), card!("3♥")]),
+                },
+            ])
+            .err(),
+            Some(InvalidBoard::DuplicateCard {
+                card: card!("3♥")
+            })
+        );
+        assert_eq!(
+            Board::try_new(&[any_field(0, 0, card), any_field(-4, 2, other_card)]).err(),
+            Some(InvalidBoard::TooLarge { board_size: 4 })
+        );
+        assert!(Board::try_new(&[any_field(1, 0, card), any_field(-2, 3, other_card)]).is_ok());
+    }
+
+    #[test]
+    fn equality_ignores_field_order() {
+        use std::collections::hash_map::DefaultHasher;
+
+        let hash = |board: &Board| {
+            let mut hasher = DefaultHasher::new();
+            board.hash(&mut hasher);
+            hasher.finish()
+        };
+        let mut fields = vec![
+            field(0, 0, card!("7♥")),
+            field(0, 1, card!("8♥")),
+            field(1, 1, card!("9♣")),
+        ];
+        let board = Board::new(&fields);
+        fields.reverse();
+        let reversed = Board::new(&fields).with_history();
+        assert_eq!(board, reversed);
+        assert_eq!(hash(&board), hash(&reversed));
+
+        let ctp = CardToPlay {
+            card: card!("9♦"),
+            i: 1,
+            j: 1,
+            target_field_for_king_ability: None,
+        };
+        let after_play = board.play_card(ctp).unwrap();
+        assert_ne!(board, after_play);
+        // The same board, built by playing the card
+        let mut fields = after_play.to_fields_vec();
+        fields.reverse();
+        assert_eq!(Board::new(&fields), after_play);
+        assert_eq!(hash(&Board::new(&fields)), hash(&after_play));
+    }
+
+    #[test]
+    fn zobrist_keys_only_depend_on_the_cards() {
+        let one_card = Board::new(&[field(0, 0, card!("7♥"))]);
+        let two_cards = Board::new(&[field(0, 0, card!("7♥")), field(0, 1, card!("8♥"))]);
+        let played = one_card
+            .clone()
+            .with_history()
+            .calculate(ctp(card!("8♥"), 0, 1))
+            .unwrap()
+            .execute();
+        assert_eq!(played.zobrist_key(), two_cards.zobrist_key());
+        let moved = Board::new(&[field(0, 0, card!("7♥")), field(1, 0, card!("8♥"))]);
+        assert_ne!(moved.zobrist_key(), two_cards.zobrist_key());
+        assert_ne!(one_card.zobrist_key(), two_cards.zobrist_key());
+        let variant = Board::new_with_rules(
+            &[field(0, 0, card!("7♥"))],
+            Rules {
+                jack_ability: false,
+                ..Rules::STANDARD
+            },
+        );
+        assert_eq!(variant.zobrist_key(), one_card.zobrist_key());
+        assert_ne!(variant, one_card);
+    }
+
+    // A full board of red number cards
+    fn red_number_cards() -> Vec<Field> {
+        [Suit::Heart, Suit::Diamond]
+            .into_iter()
+            .flat_map(|suit| Rank::ALL[..8].iter().map(move |&rank| Card { suit, rank }))
+            .enumerate()
+            .map(|(idx, card)| Field {
+                i: idx as i8 / 4,
+                j: idx as i8 % 4,
+                top_card: Some(card),
+                hidden_cards: BTreeSet::new(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn must_skip_only_if_no_card_can_be_played() {
+        let board = Board::new(&red_number_cards());
+        let hand = CardsSet::from_iter([card!("T♠"), card!("T♣"), card!("J♠")]);
+        assert!(board.must_skip(hand));
+        assert!(board.must_skip(CardsSet::new()));
+        // Cards of the same rank, face cards of the same suit and aces can be played on top
+        assert!(!board.must_skip(hand.insert(card!("2♠"))));
+        assert!(!board.must_skip(hand.insert(card!("J♥"))));
+        assert!(!board.must_skip(hand.insert(card!("A♣"))));
+    }
+
+    #[test]
+    fn locked_only_if_no_player_can_play() {
+        let fields = red_number_cards();
+        let board = Board::new(&fields);
+        let hand = CardsSet::from_iter([card!("T♠"), card!("T♣"), card!("J♠")]);
+        assert_eq!(board.open_placements_count(), 0);
+        assert!(board.is_locked(&[hand, CardsSet::from_iter([card!("Q♣")])]));
+        assert!(!board.is_locked(&[hand, hand.insert(card!("9♠"))]));
+        let board = Board::new(&fields[..1]);
+        assert_eq!(board.open_placements_count(), 7 * 7 - 1);
+        assert!(!board.is_locked(&[hand]));
+    }
+
+    #[test]
+    fn core_types_are_send_and_sync() {
+        // Bots that search on several threads share and send these between them
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<Board>();
+        assert_send_sync::<CalculatedEffects<'_>>();
+        assert_send_sync::<BoardArena>();
+        assert_send_sync::<crate::PlayerState>();
+        assert_send_sync::<crate::CardCounter>();
+    }
+
+    // Three fields, with one, two and two cards on them
+    fn stacks() -> Board {
+        Board::new(&[
+            Field {
+                i: 0,
+                j: 0,
+                top_card: None,
+                hidden_cards: BTreeSet::from([card!("7♥"), card!("2♠")]),
+            },
+            Field {
+                i: 0,
+                j: 1,
+                top_card: Some(card!("3♠")),
+                hidden_cards: BTreeSet::from([card!("4♦")]),
+            },
+            Field {
+                i: 1,
+                j: 0,
+                top_card: Some(card!("5♦")),
+                hidden_cards: BTreeSet::new(),
+            },
+        ])
+    }
+
+    #[test]
+    fn bitboards_of_stacks() {
+        let board = stacks();
+        let coords = |bitboard: BitBoard| bitboard.into_iter().collect::<Vec<_>>();
+        assert_eq!(coords(board.occupied()), [(0, 0), (0, 1), (1, 0)]);
+        assert_eq!(coords(board.face_down_only()), [(0, 0)]);
+        assert_eq!(board.stacks_with_at_least(1), board.occupied());
+        assert_eq!(coords(board.stacks_with_at_least(2)), [(0, 0), (0, 1)]);
+        assert!(board.stacks_with_at_least(3).is_empty());
+    }
+
+    #[test]
+    fn cards_of_stacks_are_counted() {
+        let board = stacks();
+        assert_eq!(board.num_cards(), 5);
+        assert_eq!(board.num_face_down_fields(), 1);
+        // Only the face-up cards count, i.e. 5♦ and 3♠
+        assert_eq!(board.suit_counts(), [1, 0, 1, 0]);
+    }
+
+    #[test]
+    fn king_targets_are_validated() {
+        let board = Board::new(&[
+            Field {
+                i: 0,
+                j: 0,
+                top_card: Some(card!("7♠")),
+                hidden_cards: BTreeSet::new(),
+            },
+            Field {
+                i: 0,
+                j: 1,
+                top_card: None,
+                hidden_cards: BTreeSet::from([card!("2♦")]),
+            },
+            Field {
+                i: 1,
+                j: 0,
+                top_card: Some(card!("3♥")),
+                hidden_cards: BTreeSet::new(),
+            },
+        ]);
+        let king = |i: i8, j: i8, target: Option<(i8, i8)>| {
+            board
+                .calculate(CardToPlay {
+                    card: card!("K♠"),
+                    i,
+                    j,
+                    target_field_for_king_ability: target,
+                })
+                .err()
+        };
+        assert_eq!(
+            king(0, 0, None),
+            Some(IllegalCardPlayed::NoTargetForKingAbility)
+        );
+        assert_eq!(
+            king(0, 0, Some((0, 4))),
+            Some(IllegalCardPlayed::TargetForKingAbilityIsOutOfBounds { tgt_i: 0, tgt_j: 4 })
+        );
+        assert_eq!(
+            king(0, 0, Some((1, 1))),
+            Some(IllegalCardPlayed::TargetForKingAbilityIsEmpty { tgt_i: 1, tgt_j: 1 })
+        );
+        assert_eq!(
+            king(0, 0, Some((0, 1))),
+            Some(IllegalCardPlayed::TargetForKingAbilityIsFaceDown { tgt_i: 0, tgt_j: 1 })
+        );
+        assert_eq!(king(0, 0, Some((0, 0))), None);
+        assert_eq!(king(0, 0, Some((1, 0))), None);
+        // The king may flip itself, even on a field that was face down
+        assert_eq!(king(0, 1, Some((0, 1))), None);
+
+        let coords = |bitboard: BitBoard| bitboard.into_iter().collect::<Vec<_>>();
+        assert_eq!(coords(board.valid_king_targets(0, 0)), [(0, 0), (1, 0)]);
+        assert_eq!(
+            coords(board.valid_king_targets(0, 1)),
+            [(0, 0), (0, 1), (1, 0)]
+        );
+        assert!(board.valid_king_targets(1, 1).is_empty());
+        let targets: Vec<_> = board
+            .legal_plays(card!("K♠"))
+            .into_iter()
+            .filter(|ctp| (ctp.i, ctp.j) == (0, 0))
+            .filter_map(|ctp| ctp.target_field_for_king_ability)
+            .collect();
+        assert_eq!(targets, [(0, 0), (1, 0)]);
+    }
+
+    #[test]
+    fn neighbors_are_clipped_to_bounds() {
+        let fields: Vec<Field> = [(0, 0, card!("2♠")), (0, 3, card!("3♠"))]
+            .into_iter()
+            .map(|(i, j, card)| Field {
+                i,
+                j,
+                top_card: Some(card),
+                hidden_cards: BTreeSet::new(),
+            })
+            .collect();
+        let board = Board::new(&fields);
+        let coords = |bitboard: BitBoard| bitboard.into_iter().collect::<Vec<_>>();
+        // The board is already four fields wide, so j must stay between 0 and 3
+        assert_eq!(
+            coords(board.orthogonal_neighbors(0, 3)),
+            [(-1, 3), (0, 2), (1, 3)]
+        );
+        assert_eq!(coords(board.diagonal_neighbors(0, 0)), [(-1, 1), (1, 1)]);
+        assert_eq!(
+            coords(board.orthogonal_neighbors(1, 1)),
+            [(0, 1), (1, 0), (1, 2), (2, 1)]
+        );
+        assert!(board.diagonal_neighbors(0, 10).is_empty());
+    }
 
     quickcheck! {
         fn possible_locations_fn(input: PlayCardInput) -> bool {
@@ -580,6 +1799,295 @@ mod tests {
             }
             more_than_zero_locations == board.possible_to_play_card(input.card_to_play.card)
         }
+
+        fn legal_plays_contains_every_legal_play(input: PlayCardInput) -> bool {
+            let board = Board::new(&input.fields);
+            let mut ctp = input.card_to_play;
+            // A target is only part of the play for kings played on top of another card
+            if ctp.card.rank != Rank::King || board.get(ctp.i, ctp.j).is_none() {
+                ctp.target_field_for_king_ability = None;
+            }
+            let plays = board.legal_plays(ctp.card);
+            plays.contains(&ctp) == board.calculate(ctp).is_ok()
+                && plays.is_empty() != board.possible_to_play_card(input.card_to_play.card)
+        }
+
+        fn execute_into_agrees_with_execute(input: PlayCardInput) -> bool {
+            let board = Board::new(&input.fields);
+            let Ok(expected) = board.play_card(input.card_to_play) else {
+                return true;
+            };
+            // Overwrite a board that has more fields and a different center
+            let mut target = expected.clone();
+            for ctp in board.legal_plays(input.card_to_play.card).into_iter().rev() {
+                target = target.play_card(ctp).unwrap_or(target);
+            }
+            board.calculate(input.card_to_play).unwrap().execute_into(&mut target);
+            target.to_fields_vec() == expected.to_fields_vec()
+                && target.locations_for_card(input.card_to_play.card)
+                    == expected.locations_for_card(input.card_to_play.card)
+        }
+
+        fn calculate_all_agrees_with_calculate(input: PlayCardInput) -> bool {
+            let board = Board::new(&input.fields);
+            let agrees = board.calculate_all(input.card_to_play.card).all(|(ctp, effects)| {
+                let expected = board.calculate(ctp).unwrap();
+                effects.cards_won == expected.cards_won
+                    && effects.combo == expected.combo
+                    && effects.flipped_fields() == expected.flipped_fields()
+                    && effects.won_fields() == expected.won_fields()
+            });
+            agrees
+        }
+
+        fn calculate_all_finds_every_play_that_calculate_accepts(input: PlayCardInput) -> bool {
+            let board = Board::new(&input.fields);
+            let card = input.card_to_play.card;
+            let summary = |effects: &CalculatedEffects| {
+                (effects.cards_won, effects.combo, effects.flipped_fields(), effects.won_fields())
+            };
+            let mut expected = Vec::new();
+            for (i, j) in board.playable_area().iter() {
+                // Kings played on top of another card need a target, which may be any card
+                let targets: Vec<_> = if card.rank == Rank::King && board.get(i, j).is_some() {
+                    board.occupied().into_iter().map(Some).collect()
+                } else {
+                    vec![None]
+                };
+                for target_field_for_king_ability in targets {
+                    let ctp = CardToPlay { card, i, j, target_field_for_king_ability };
+                    if let Ok(effects) = board.calculate(ctp) {
+                        expected.push((ctp, summary(&effects)));
+                    }
+                }
+            }
+            let mut all: Vec<_> = board
+                .calculate_all(card)
+                .map(|(ctp, effects)| (ctp, summary(&effects)))
+                .collect();
+            expected.sort_by_key(|&(ctp, _)| ctp);
+            all.sort_by_key(|&(ctp, _)| ctp);
+            all == expected
+        }
+
+        fn placement_policy_agrees_with_calculate(
+            input: PlayCardInput,
+            combo_only: bool,
+            avoid_own_suit_flips: bool,
+            masked: bool
+        ) -> bool {
+            let board = Board::new(&input.fields);
+            let CardToPlay { card, i, j, .. } = input.card_to_play;
+            let mask = board.orthogonal_neighbors(i, j) | board.diagonal_neighbors(i, j);
+            let policy = PlacementPolicy {
+                combo_only,
+                avoid_own_suit_flips,
+                mask: masked.then_some(mask),
+            };
+            let own_suit = board.bitboards[card.suit as usize];
+            let expected: Vec<_> = board.locations_for_card(card).into_iter().filter(|&(i, j)| {
+                let combo = board.get(i, j).is_some();
+                let target_field_for_king_ability = combo.then_some((i, j));
+                let ctp = CardToPlay { card, i, j, target_field_for_king_ability };
+                let effects = board.calculate(ctp).unwrap();
+                let flips_own_suit = matches!(card.rank, Rank::Jack | Rank::Queen)
+                    && !(effects.flipped_fields() & own_suit).is_empty();
+                (!combo_only || effects.combo)
+                    && (!masked || mask.contains(i, j))
+                    && !(avoid_own_suit_flips && flips_own_suit)
+            }).collect();
+            Vec::from_iter(board.locations_for_card_with_policy(card, policy)) == expected
+        }
+
+        fn counts_are_maintained_when_playing(game: GameInProgress) -> bool {
+            let counts = |board: &Board| (board.num_cards(), board.num_face_down_fields(), board.suit_counts());
+            let recomputed = Board::new(&game.board.to_fields_vec());
+            let fields = game.board.to_fields_vec();
+            let mut suit_counts = [0; 4];
+            for card in fields.iter().filter_map(|field| field.top_card) {
+                suit_counts[card.suit as usize] += 1;
+            }
+            let expected = (
+                fields.iter().map(|field| field.hidden_cards.len() as u32 + u32::from(field.top_card.is_some())).sum(),
+                fields.iter().filter(|field| field.top_card.is_none()).count() as u32,
+                suit_counts,
+            );
+            counts(&game.board) == expected && counts(&recomputed) == expected
+        }
+
+        fn score_agrees_with_calculate(input: PlayCardInput) -> bool {
+            let board = Board::new(&input.fields);
+            let score = board.score_if_played(input.card_to_play);
+            match board.calculate(input.card_to_play) {
+                Ok(effects) => score == Ok(effects.cards_won.len()),
+                Err(err) => score == Err(err),
+            }
+        }
+
+        fn get_agrees_with_fields(input: PlayCardInput) -> bool {
+            let board = Board::new(&input.fields);
+            let after_play = board.play_card(input.card_to_play);
+            let boards = [Some(board), after_play.ok()];
+            boards.into_iter().flatten().all(|board| {
+                let playable_area = board.playable_area();
+                (playable_area.i_min - 1..=playable_area.i_max + 1).all(|i| {
+                    (playable_area.j_min - 1..=playable_area.j_max + 1).all(|j| {
+                        let expected = board
+                            .iter()
+                            .find(|&&(i_field, j_field, _)| (i_field, j_field) == (i, j))
+                            .map(|&(_, _, field)| field);
+                        board.get(i, j) == expected
+                    })
+                })
+            })
+        }
+    }
+
+    #[test]
+    fn empty_playable_locations_are_the_playable_area_without_fields() {
+        let board = Board::new(&[
+            Field {
+                i: 0,
+                j: 0,
+                top_card: Some(card!("7♥")),
+                hidden_cards: BTreeSet::new(),
+            },
+            Field {
+                i: 2,
+                j: 1,
+                top_card: None,
+                hidden_cards: BTreeSet::from([card!("8♥")]),
+            },
+        ]);
+        let area = board.playable_area();
+        assert_eq!(area.iter().count(), 5 * 6);
+        let expected: Vec<(i8, i8)> = area
+            .iter()
+            .filter(|&(i, j)| board.get(i, j).is_none())
+            .collect();
+        let mut actual: Vec<(i8, i8)> = board.empty_playable_locations().into_iter().collect();
+        actual.sort();
+        assert_eq!(actual, expected);
+        assert_eq!(actual.len(), 5 * 6 - 2);
+    }
+
+    #[test]
+    fn line_threats_complete_lines_of_three() {
+        // Three clubs in a row, and one on the diagonal
+        let board = Board::new(&[
+            field(0, 0, card!("2♣")),
+            field(0, 1, card!("3♣")),
+            field(0, 2, card!("4♣")),
+            field(1, 0, card!("5♥")),
+            field(1, 1, card!("6♣")),
+        ]);
+        type Line = Vec<(i8, i8)>;
+        let mut threats: Vec<(Line, (i8, i8))> = board
+            .line_threats(Suit::Club)
+            .map(|(line, place)| (line.into_iter().collect(), place))
+            .collect();
+        threats.sort();
+        assert_eq!(
+            threats,
+            [
+                (vec![(0, 0), (0, 1), (0, 2)], (0, -1)),
+                (vec![(0, 0), (0, 1), (0, 2)], (0, 3)),
+            ]
+        );
+        // The place can also be covered by a card of another suit, and the other end of
+        // the line is out of bounds then
+        let board = board
+            .play_card(CardToPlay {
+                card: card!("7♥"),
+                i: 0,
+                j: 3,
+                target_field_for_king_ability: None,
+            })
+            .unwrap();
+        let places: Vec<(i8, i8)> = board.line_threats(Suit::Club).map(|(_, p)| p).collect();
+        assert_eq!(places, [(0, 3)]);
+        // Two out of three in a row don't make a threat yet, and neither do other suits
+        assert_eq!(board.line_threats(Suit::Heart).count(), 0);
+
+        let board = Board::new_with_rules(
+            &[field(0, 0, card!("2♣")), field(0, 1, card!("3♣"))],
+            Rules {
+                line_length: 3,
+                ..Rules::STANDARD
+            },
+        );
+        let places: Vec<(i8, i8)> = board.line_threats(Suit::Club).map(|(_, p)| p).collect();
+        assert_eq!(places, [(0, -1), (0, 2)]);
+    }
+
+    #[test]
+    fn history_records_played_cards() {
+        let board = Board::new(&[Field {
+            i: 0,
+            j: 0,
+            top_card: Some(card!("7♥")),
+            hidden_cards: BTreeSet::new(),
+        }]);
+        let plays = [ctp(card!("7♠"), 0, 0), ctp(card!("3♦"), 1, 0)];
+        assert_eq!(board.play_card(plays[0]).unwrap().history(), None);
+
+        let mut board = board.with_history();
+        assert_eq!(board.history(), Some(&[][..]));
+        for play in plays {
+            board = board.play_card(play).unwrap();
+        }
+        assert_eq!(board.history(), Some(&plays[..]));
+        // Calculating without executing doesn't change the history
+        board.calculate(ctp(card!("3♣"), 1, 0)).unwrap();
+        assert_eq!(board.history(), Some(&plays[..]));
+    }
+
+    #[test]
+    fn field_history_records_stacking_order() {
+        let board = Board::new(&[
+            Field {
+                i: 0,
+                j: 0,
+                top_card: Some(card!("7♥")),
+                hidden_cards: BTreeSet::from([card!("2♠")]),
+            },
+            Field {
+                i: 1,
+                j: 0,
+                top_card: Some(card!("5♦")),
+                hidden_cards: BTreeSet::new(),
+            },
+        ]);
+        assert_eq!(board.field_history(0, 0), None);
+
+        let mut board = board.with_history();
+        // The jack's ability turns the 7♠ face down, so that the 4♣ can be placed on it
+        let plays = [
+            ctp(card!("7♠"), 0, 0),
+            ctp(card!("J♦"), 1, 0),
+            ctp(card!("4♣"), 0, 0),
+        ];
+        for play in plays {
+            board = board.play_card(play).unwrap();
+        }
+        let field_history = board.field_history(0, 0).unwrap();
+        assert_eq!(
+            field_history,
+            FieldHistory {
+                hidden_before: CardsSet::from_iter([card!("2♠")]),
+                stack: vec![
+                    (card!("7♥"), true),
+                    (card!("7♠"), false),
+                    (card!("4♣"), true)
+                ],
+            }
+        );
+        assert_eq!(CompactField::from(&field_history), board.get(0, 0).unwrap());
+        assert_eq!(
+            board.field_history(1, 0).unwrap().stack,
+            [(card!("5♦"), true), (card!("J♦"), true)]
+        );
+        assert_eq!(board.field_history(2, 2), Some(FieldHistory::default()));
     }
 
     #[test]
@@ -665,5 +2173,126 @@ mod tests {
             .unwrap();
         assert!(plan.diff.flipped.is_empty());
         assert!(!plan.diff.won.is_empty());
+        let explanation = plan.explain();
+        assert_eq!(explanation.placed_on, Some(card!("A♠")));
+        assert!(explanation.combo);
+        assert_eq!(explanation.ability, None);
+        assert_eq!(
+            explanation.lines,
+            vec![Line {
+                direction: LineDirection::AntiDiagonal,
+                suit: Suit::Diamond,
+                fields: vec![(-1, 0), (0, -1), (1, -2), (2, -3)],
+            }]
+        );
+    }
+
+    #[test]
+    fn explain_flips() {
+        let board = Board::new(&[
+            Field {
+                i: 0,
+                j: 0,
+                top_card: Some(card!("7♥")),
+                hidden_cards: BTreeSet::new(),
+            },
+            Field {
+                i: 0,
+                j: 1,
+                top_card: Some(card!("3♠")),
+                hidden_cards: BTreeSet::new(),
+            },
+            Field {
+                i: 1,
+                j: 0,
+                top_card: Some(card!("2♣")),
+                hidden_cards: BTreeSet::new(),
+            },
+        ]);
+        let explanation = board
+            .calculate(CardToPlay {
+                i: 0,
+                j: 0,
+                card: card!("J♥"),
+                target_field_for_king_ability: None,
+            })
+            .unwrap()
+            .explain();
+        assert_eq!(explanation.placed_on, Some(card!("7♥")));
+        assert!(explanation.combo);
+        // Empty fields next to the jack are not listed
+        assert_eq!(
+            explanation.flipped,
+            vec![(0, 1, card!("3♠")), (1, 0, card!("2♣"))]
+        );
+        assert_eq!(explanation.ability, Some(Rank::Jack));
+        assert!(explanation.lines.is_empty());
+        assert_eq!(
+            explanation.to_string(),
+            "🂻 at (0, 0) on top of 🂷 is a combo, its ability flips 🂣 at (0, 1) and 🃒 at (1, 0), no cards won"
+        );
+    }
+
+    /// The fields that a play flips or clears are available without executing it.
+    #[test]
+    fn flipped_and_won_fields() {
+        let board = Board::new(&[
+            field(0, 0, card!("7♥")),
+            field(0, 1, card!("3♠")),
+            field(1, 0, card!("2♣")),
+        ]);
+        let effects = board.calculate(ctp(card!("J♥"), 0, 0)).unwrap();
+        // Empty fields next to the jack are not flipped
+        assert_eq!(
+            effects.flipped_fields().into_iter().collect::<Vec<_>>(),
+            vec![(0, 1), (1, 0)]
+        );
+        assert_eq!(effects.flipped_fields() & board.spades(), board.spades());
+        assert!(effects.won_fields().is_empty());
+
+        let board = Board::new(&[
+            field(-1, 0, card!("4♦")),
+            field(-1, -1, card!("5♦")),
+            field(-1, -2, card!("6♦")),
+            field(-1, -3, card!("A♠")),
+        ]);
+        let effects = board.calculate(ctp(card!("A♦"), -1, -3)).unwrap();
+        assert!(effects.flipped_fields().is_empty());
+        assert_eq!(effects.won_fields(), board.diamonds());
+    }
+
+    #[test]
+    fn explain_illegal_move() {
+        let board = Board::new(&[Field {
+            i: 0,
+            j: 0,
+            top_card: Some(card!("7♥")),
+            hidden_cards: BTreeSet::new(),
+        }]);
+        let ctp = CardToPlay {
+            i: 0,
+            j: 0,
+            card: card!("3♠"),
+            target_field_for_king_ability: None,
+        };
+        let explanation = board.explain(ctp);
+        assert_eq!(
+            explanation,
+            MoveExplanation::Illegal {
+                card: card!("3♠"),
+                i: 0,
+                j: 0,
+                reason: IllegalCardPlayed::IncompatibleCard {
+                    existing_card: card!("7♥")
+                },
+            }
+        );
+        assert!(explanation
+            .to_string()
+            .starts_with("🂣 at (0, 0) is illegal: "));
+        assert!(matches!(
+            board.explain(CardToPlay { j: 1, ..ctp }),
+            MoveExplanation::Legal(Explanation { combo: false, .. })
+        ));
     }
 }
@@ -0,0 +1,57 @@
+use crate::prelude::*;
+use crate::{Board, CalculatedEffects, CardToPlay, CompactField};
+
+/// A pool of boards whose allocations are reused.
+///
+/// Search algorithms create and drop a board for every node, which means allocating and
+/// freeing its list of fields. Instead, boards that are no longer needed can be given back
+/// with [`recycle()`](BoardArena::recycle), and their allocations are then used by the boards
+/// created with [`execute()`](BoardArena::execute).
+///
+/// ```
+/// use gomori::{card, Board, BoardArena, CardToPlay, Field};
+/// let board = Board::new(&[Field {
+///     i: 0,
+///     j: 0,
+///     top_card: Some(card!("7♠")),
+///     hidden_cards: Default::default(),
+/// }]);
+/// let mut arena = BoardArena::new();
+/// for ctp in board.legal_plays(card!("7♦")) {
+///     let child = arena.execute(board.calculate(ctp).unwrap());
+///     // ... search the child
+///     arena.recycle(child);
+/// }
+/// ```
+#[derive(Debug, Default)]
+pub struct BoardArena {
+    /// The lists of fields of the recycled boards.
+    fields: Vec<Vec<(i8, i8, CompactField)>>,
+    /// The histories of the recycled boards that had one.
+    histories: Vec<Vec<CardToPlay>>,
+}
+
+impl BoardArena {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Like [`CalculatedEffects::execute()`], but reuses a recycled board if there is one.
+    pub fn execute(&mut self, effects: CalculatedEffects<'_>) -> Board {
+        match self.fields.pop() {
+            Some(fields) => {
+                let history = self.histories.pop().unwrap_or_default();
+                effects
+                    .diff
+                    .apply_with_buffers(effects.board, fields, history)
+            }
+            None => effects.execute(),
+        }
+    }
+
+    /// Gives back a board that is no longer needed, so that its allocations can be reused.
+    pub fn recycle(&mut self, board: Board) {
+        self.fields.push(board.fields);
+        self.histories.extend(board.history);
+    }
+}
@@ -4,7 +4,7 @@
 /// half-open range, it's possible for a point with `i == i_max`
 /// to be contained in the area.
 #[cfg_attr(feature = "python", pyo3::pyclass(get_all, set_all))]
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub struct BoundingBox {
     pub i_min: i8,
     pub j_min: i8,
@@ -24,6 +24,21 @@ impl BoundingBox {
         (self.j_max - self.j_min) as u8 + 1
     }
 
+    /// The number of columns, i.e. the same as [`size_j()`](Self::size_j).
+    pub fn width(&self) -> u8 {
+        self.size_j()
+    }
+
+    /// The number of rows, i.e. the same as [`size_i()`](Self::size_i).
+    pub fn height(&self) -> u8 {
+        self.size_i()
+    }
+
+    /// The number of coordinates in the bounding box.
+    pub fn area(&self) -> u16 {
+        self.width() as u16 * self.height() as u16
+    }
+
     pub fn contains(&self, i: i8, j: i8) -> bool {
         i >= self.i_min && j >= self.j_min && i <= self.i_max && j <= self.j_max
     }
@@ -54,6 +69,48 @@ impl BoundingBox {
         })
     }
 
+    /// The coordinates that are in both bounding boxes, or `None` if there are none.
+    pub fn intersection(&self, other: &BoundingBox) -> Option<BoundingBox> {
+        let intersection = Self {
+            i_min: self.i_min.max(other.i_min),
+            j_min: self.j_min.max(other.j_min),
+            i_max: self.i_max.min(other.i_max),
+            j_max: self.j_max.min(other.j_max),
+        };
+        (intersection.i_min <= intersection.i_max && intersection.j_min <= intersection.j_max)
+            .then_some(intersection)
+    }
+
+    /// The smallest bounding box that covers both bounding boxes.
+    ///
+    /// This can contain coordinates that are in neither of them, so it's not a union in the
+    /// sense of sets.
+    pub fn union_cover(&self, other: &BoundingBox) -> BoundingBox {
+        Self {
+            i_min: self.i_min.min(other.i_min),
+            j_min: self.j_min.min(other.j_min),
+            i_max: self.i_max.max(other.i_max),
+            j_max: self.j_max.max(other.j_max),
+        }
+    }
+
+    /// All coordinates in the bounding box, row by row.
+    pub fn iter(&self) -> impl Iterator<Item = (i8, i8)> {
+        let Self {
+            i_min,
+            j_min,
+            i_max,
+            j_max,
+        } = *self;
+        (i_min..=i_max).flat_map(move |i| (j_min..=j_max).map(move |j| (i, j)))
+    }
+
+    /// Same as [`iter()`](Self::iter).
+    #[deprecated(note = "Use iter() instead")]
+    pub fn iter_coords(&self) -> impl Iterator<Item = (i8, i8)> {
+        self.iter()
+    }
+
     /// Expands the bounding box to cover point `(i, j)`.
     pub fn update(&mut self, i: i8, j: i8) {
         self.i_min = self.i_min.min(i);
@@ -65,7 +122,8 @@ impl BoundingBox {
 
 #[cfg(feature = "python")]
 mod python {
-    use pyo3::pymethods;
+    use pyo3::exceptions::PyDeprecationWarning;
+    use pyo3::{pymethods, PyErr, PyResult, Python};
 
     use super::*;
     #[pymethods]
@@ -91,14 +149,105 @@ mod python {
             self.size_j()
         }
 
+        #[pyo3(name = "width")]
+        fn py_width(&self) -> u8 {
+            self.width()
+        }
+
+        #[pyo3(name = "height")]
+        fn py_height(&self) -> u8 {
+            self.height()
+        }
+
+        #[pyo3(name = "area")]
+        fn py_area(&self) -> u16 {
+            self.area()
+        }
+
         #[pyo3(name = "contains")]
         fn py_contains(&self, i: i8, j: i8) -> bool {
             self.contains(i, j)
         }
 
+        #[pyo3(name = "intersection")]
+        fn py_intersection(&self, other: BoundingBox) -> Option<BoundingBox> {
+            self.intersection(&other)
+        }
+
+        #[pyo3(name = "union_cover")]
+        fn py_union_cover(&self, other: BoundingBox) -> BoundingBox {
+            self.union_cover(&other)
+        }
+
+        #[pyo3(name = "iter")]
+        fn py_iter(&self) -> Vec<(i8, i8)> {
+            self.iter().collect()
+        }
+
+        #[pyo3(name = "iter_coords")]
+        fn py_iter_coords(&self, py: Python) -> PyResult<Vec<(i8, i8)>> {
+            let category = py.get_type::<PyDeprecationWarning>();
+            PyErr::warn(py, category, "Use iter() instead", 1)?;
+            Ok(self.iter().collect())
+        }
+
         #[pyo3(name = "update")]
         fn py_update(&mut self, i: i8, j: i8) {
             self.update(i, j)
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sizes_are_inclusive() {
+        let bbox = BoundingBox {
+            i_min: -1,
+            j_min: 2,
+            i_max: 0,
+            j_max: 4,
+        };
+        assert_eq!((bbox.width(), bbox.height(), bbox.area()), (3, 2, 6));
+        assert_eq!(bbox.iter().count(), 6);
+        assert_eq!(BoundingBox::singleton(3, 3).area(), 1);
+    }
+
+    #[test]
+    fn intersection_and_union_cover() {
+        let a = BoundingBox {
+            i_min: 0,
+            j_min: 0,
+            i_max: 2,
+            j_max: 2,
+        };
+        let b = BoundingBox {
+            i_min: 2,
+            j_min: 1,
+            i_max: 3,
+            j_max: 5,
+        };
+        let intersection = a.intersection(&b).unwrap();
+        assert_eq!(intersection.iter().collect::<Vec<_>>(), [(2, 1), (2, 2)]);
+        assert!(intersection
+            .iter()
+            .all(|(i, j)| a.contains(i, j) && b.contains(i, j)));
+        assert_eq!(
+            a.union_cover(&b),
+            BoundingBox {
+                i_min: 0,
+                j_min: 0,
+                i_max: 3,
+                j_max: 5,
+            }
+        );
+        // Since the ranges are inclusive, the corner is shared, but the next diagonal isn't
+        assert_eq!(a.intersection(&BoundingBox::singleton(3, 3)), None);
+        assert_eq!(
+            a.intersection(&BoundingBox::singleton(2, 2)),
+            Some(BoundingBox::singleton(2, 2))
+        );
+    }
+}
@@ -1,12 +1,22 @@
-use std::{
-    fmt::{self, Debug},
+use core::{
+    fmt::{self, Debug, Display},
     iter::FusedIterator,
 };
 
-const I_SHIFT: u8 = 49 + 7;
-const J_SHIFT: u8 = 49;
-const BOARD_MASK: u64 = 0x1ffffffffffff;
-const OFFSET_MASK: u64 = 0x7ffe000000000000;
+use crate::prelude::*;
+use crate::MAX_BOARD_SIZE;
+
+/// The width and height of the area that a `BitBoard` covers.
+pub(crate) const AREA_SIZE: i8 = 2 * MAX_BOARD_SIZE - 1;
+/// The number of fields in the area that a `BitBoard` covers.
+pub(crate) const AREA_LEN: usize = AREA_SIZE as usize * AREA_SIZE as usize;
+// How far the area extends from its center in each direction
+const RADIUS: i8 = MAX_BOARD_SIZE - 1;
+
+const I_SHIFT: u8 = AREA_LEN as u8 + 7;
+const J_SHIFT: u8 = AREA_LEN as u8;
+const BOARD_MASK: u128 = (1 << AREA_LEN) - 1;
+const OFFSET_MASK: u128 = 0x3fff << AREA_LEN;
 
 /// A compact board representation that stores only a single
 /// bit per field, equivalent to a set of coordinates.
@@ -15,8 +25,8 @@ const OFFSET_MASK: u64 = 0x7ffe000000000000;
 /// [which fields on the board have a visible diamond card on them](crate::Board::diamonds).
 /// So a `BitBoard` is kind of like a "view" into a particular `Board`.
 ///
-/// Two `BitBoard`s can be combined with operations like [`&`](std::ops::BitAnd),
-/// [`|`](std::ops::BitOr`), or [`difference()`](BitBoard::difference).
+/// Two `BitBoard`s can be combined with operations like [`&`](core::ops::BitAnd),
+/// [`|`](core::ops::BitOr`), or [`difference()`](BitBoard::difference).
 /// It's important that when two `BitBoard`s are combined, they must both originate[^note] from the same `Board`.
 /// The reason is that the area represented by `BitBoard`s from different `Board`s may not be identical, so some
 /// contents of one board may be unrepresentable in the other `BitBoard`.
@@ -26,41 +36,48 @@ const OFFSET_MASK: u64 = 0x7ffe000000000000;
 ///     or that X is the result of combining/processing `BitBoard`s that originate from X.
 ///
 /// It can be converted back into a list of coordinate pairs by
-/// means of its [`IntoIterator`] instance.
+/// means of its [`IntoIterator`] instance. The coordinates are yielded in row-major
+/// order, i.e. sorted by `i` first, then by `j`, and can be iterated in reverse.
 ///
 /// # Note on immutability
 ///
 /// This is an immutable type, so its "mutating" methods return a
-/// new value instead of really mutating in-place (except for `std::ops::BitXxxAssign` trait methods).
+/// new value instead of really mutating in-place (except for `core::ops::BitXxxAssign` trait methods).
 /// It is also [`Copy`], so a value is not consumed by methods with `self` receiver.
 ///
 /// # Implementation
 ///
-/// Internally, a `BitBoard` is (1) an `(offset_i, offset_j)` coordinate pair and (2) a 49-bit-bitset. The bitset encodes an 7 x 7 area with one bit per field, like so:
+/// Internally, a `BitBoard` is (1) an `(offset_i, offset_j)` coordinate pair and (2) an 81-bit-bitset. The bitset encodes a 9 x 9 area with one bit per field, like so:
 ///
 ///
 /// ```text
-/// 1 0 1 1 0 0 1
-/// 1 1 1 0 1 0 1
-/// 0 1 1 0 0 1 0
-/// 0 0 1 1 1 1 0
-/// 0 1 1 0 1 0 1
-/// 0 0 0 1 0 1 1
-/// 0 0 0 1 0 0 1
+/// 1 0 1 1 0 0 1 0 0
+/// 1 1 1 0 1 0 1 0 1
+/// 0 1 1 0 0 1 0 0 0
+/// 0 0 1 1 1 1 0 1 1
+/// 0 1 1 0 1 0 1 0 0
+/// 0 0 0 1 0 1 1 0 1
+/// 0 0 0 1 0 0 1 1 0
+/// 1 0 0 0 0 0 0 0 1
+/// 0 1 0 0 1 0 0 0 0
 /// ```
 ///
-/// The offset is added to every local `(i, j)` coordinate in that field (the local `i` and `j` both range from `0` to `6`) to obtain the true `(i, j)` coordinate.
+/// The offset is added to every local `(i, j)` coordinate in that field (the local `i` and `j` both range from `0` to `8`) to obtain the true `(i, j)` coordinate.
 ///
-/// Every valid board would fit in a 4 x 4 area, so why 7 x 7? One reason is that with a 7 x 7 board,
+/// Every valid board would fit in a 5 x 5 area (see [`MAX_BOARD_SIZE`](crate::MAX_BOARD_SIZE)), so why 9 x 9? One reason is that with a 9 x 9 board,
 /// we can be sure that not only the board itself can be represented, but also the next card, as long as it is in the board's [playable area](crate::Board::playable_area).
+///
+/// Two `BitBoard`s are only equal if they also have the same offset, like the `BitBoard`s
+/// of the same [`Board`](crate::Board). Comparing `BitBoard`s from different boards that
+/// contain the same coordinates is therefore not meaningful.
 #[cfg_attr(feature = "python", pyo3::pyclass)]
-#[derive(Clone, Copy, PartialEq, Eq)]
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
 pub struct BitBoard {
-    /// The low 49 bits are the board itself (7x7)
+    /// The low 81 bits are the board itself (9x9)
     /// The next highest 7 bits are the j offset.
     /// The next highest 7 bits are the i offset.
-    /// The uppermost bit indicates whether the author of this
-    /// code is cool as fuck (it is set to 0 if true).
+    /// The remaining uppermost bits indicate whether the author of this
+    /// code is cool as fuck (they are set to 0 if true).
     ///
     /// How can we store a i8 in 7 bits? Well, the actual range
     /// of card coordinates is much lower than the range of an
@@ -74,24 +91,26 @@ pub struct BitBoard {
     /// and adding it back when reading.
     ///
     /// How do (i, j) coordinates map to bits in the board?
-    /// (i, j) is represented as the bit number  (i * 7 + j), counted from
+    /// (i, j) is represented as the bit number  (i * 9 + j), counted from
     /// the least significant bit. So if you lay out a number like
-    /// 0b1100000000000011111111111111111111111111111111111 in blocks of 7
-    /// from least significant to most significant bit
+    /// 0b110000000000000000000000000111111111111111111111111111111111111111111111111111111
+    /// in blocks of 9 from least significant to most significant bit
     /// (which is also what the Debug impl does) like so:
     ///
     /// ```text
-    /// 1 1 1 1 1 1 1
-    /// 1 1 1 1 1 1 1
-    /// 1 1 1 1 1 1 1
-    /// 1 1 1 1 1 1 1
-    /// 1 1 1 1 1 1 1
-    /// 0 0 0 0 0 0 0
-    /// 0 0 0 0 0 1 1
+    /// 1 1 1 1 1 1 1 1 1
+    /// 1 1 1 1 1 1 1 1 1
+    /// 1 1 1 1 1 1 1 1 1
+    /// 1 1 1 1 1 1 1 1 1
+    /// 1 1 1 1 1 1 1 1 1
+    /// 1 1 1 1 1 1 1 1 1
+    /// 0 0 0 0 0 0 0 0 0
+    /// 0 0 0 0 0 0 0 0 0
+    /// 0 0 0 0 0 0 0 1 1
     /// ```
     /// then this 2D array effectively has a coordinate system that has i going from the
-    /// top (0) to the bottom (6), and j going from the left (0) to the right (6).
-    bits: u64,
+    /// top (0) to the bottom (8), and j going from the left (0) to the right (8).
+    bits: u128,
 }
 
 // !!!!!! NOTE: Keep in sync with pymethods impl block !!!!!!
@@ -104,11 +123,11 @@ impl BitBoard {
         debug_assert!(j <= 52);
         // This makes use of a really nice property:
         // When we place the first coordinate in the center of
-        // the 7x7 area that is modeled, then no matter where the
-        // remaining cards are, if there is a 4x4 bbox enclosing
-        // all the cards, it will fit within the 7x7 area.
-        let offset_i = i - 3;
-        let offset_j = j - 3;
+        // the 9x9 area that is modeled, then no matter where the
+        // remaining cards are, if there is a 5x5 bbox enclosing
+        // all the cards, it will fit within the 9x9 area.
+        let offset_i = i - RADIUS;
+        let offset_j = j - RADIUS;
         Self {
             bits: encode_offset(offset_i, offset_j),
         }
@@ -127,23 +146,22 @@ impl BitBoard {
         } else {
             return false;
         };
-        if i_local >= 7 || j_local >= 7 {
+        if !(0..AREA_SIZE).contains(&i_local) || !(0..AREA_SIZE).contains(&j_local) {
             return false;
         }
-        let idx = i_local * 7 + j_local;
-        self.bits & (1u64 << idx) != 0
+        self.bits & (1 << local_idx(i_local, j_local)) != 0
     }
 
     /// Sets the bit for the specified coordinate to `true`.
     ///
     /// This function must only be used with coordinates in the underlying board's [`playable_area`](crate::Board::playable_area).
-    /// Other coordinates may exceed the 7x7 area stored in the `BitBoard`, and that will cause a panic in debug mode.
+    /// Other coordinates may exceed the 9x9 area stored in the `BitBoard`, and that will cause a panic in debug mode.
     /// In release mode, no checks are performed, it will just cause invalid data.
     #[must_use]
     pub fn insert(self, i: i8, j: i8) -> Self {
         let idx = self.arr_idx(i, j);
         Self {
-            bits: self.bits | (1u64 << idx),
+            bits: self.bits | (1 << idx),
         }
     }
 
@@ -154,7 +172,7 @@ impl BitBoard {
         let (max_local_i, max_local_j) = self.local_coords(i_max, j_max);
         for i in min_local_i..=max_local_i {
             for j in min_local_j..=max_local_j {
-                bits |= 1u64 << (i * 7 + j);
+                bits |= 1 << local_idx(i as i8, j as i8);
             }
         }
         Self { bits }
@@ -167,26 +185,14 @@ impl BitBoard {
     pub fn remove(self, i: i8, j: i8) -> Self {
         let idx = self.arr_idx(i, j);
         Self {
-            bits: self.bits & !(1u64 << idx),
+            bits: self.bits & !(1 << idx),
         }
     }
 
     /// Returns all lines on the field consisting of at least three points.
     ///
-    /// An exception are the diagonals in the far four corners of the bitboard, for example:
-    ///
-    /// ```text
-    /// 0 0 0 0 0 0 0
-    /// 0 0 0 0 0 0 0
-    /// 0 0 0 0 0 0 0
-    /// 0 0 0 1 0 0 0
-    /// 0 0 0 0 0 0 1
-    /// 0 0 0 0 0 1 0
-    /// 0 0 0 0 1 0 0
-    /// ```
-    ///
-    /// They are not included, since the fourth field for those diagonals is outside the
-    /// playable area of the board.
+    /// A line is a whole row, column or diagonal of the bitboard's area, so its points
+    /// don't need to be next to each other.
     #[must_use]
     pub fn threes_in_a_row(self) -> ThreesInARowIter {
         ThreesInARowIter {
@@ -219,7 +225,9 @@ impl BitBoard {
     /// Any lines that are found are returned in a new `BitBoard`. The result is therefore
     /// a subset of the input.
     ///
-    /// Only valid for point coordinates in the range `[-52, 52]`.
+    /// Only valid for point coordinates in the range `[-52, 52]`, and for bitboards of
+    /// boards that fit in a 4 x 4 area, where four points in a row are always consecutive.
+    /// For larger boards, see [`lines_of_length_going_through_point()`](Self::lines_of_length_going_through_point).
     #[must_use]
     pub fn lines_going_through_point(self, point_i: i8, point_j: i8) -> BitBoard {
         debug_assert!(point_i >= -52);
@@ -228,16 +236,18 @@ impl BitBoard {
         debug_assert!(point_j <= 52);
 
         let (offset_i, offset_j) = self.offset();
-        let delta = (point_i - offset_i - 3, point_j - offset_j - 3);
+        let delta = (point_i - offset_i - RADIUS, point_j - offset_j - RADIUS);
 
         let mut line_bits = 0;
-        // These patterns are lines on the 7x7 board - horizontal, vertical, and two diagonal.
-        for pattern in [
-            0xfe00000u64,
-            0x204081020408u64,
-            0x1010101010101u64,
-            0x41041041040u64,
-        ] {
+        // These patterns are lines of 7 fields through the center of the area - horizontal,
+        // vertical, and two diagonal.
+        const PATTERNS: [u128; 4] = [
+            line_through_center((0, 1), 3),
+            line_through_center((1, 0), 3),
+            line_through_center((1, 1), 3),
+            line_through_center((1, -1), 3),
+        ];
+        for pattern in PATTERNS {
             let pattern_intersect = self.bits & shift_2d_lossy(pattern, delta);
             debug_assert!(pattern_intersect.count_ones() <= 4);
             if pattern_intersect.count_ones() == 4 {
@@ -249,28 +259,86 @@ impl BitBoard {
         }
     }
 
+    /// Like [`lines_going_through_point()`](Self::lines_going_through_point), but for lines
+    /// of at least `min_length` consecutive points, e.g. for variants of the game with a
+    /// different [`line_length`](crate::Rules::line_length).
+    #[must_use]
+    pub fn lines_of_length_going_through_point(
+        self,
+        point_i: i8,
+        point_j: i8,
+        min_length: u8,
+    ) -> BitBoard {
+        let mut result = Self {
+            bits: self.bits & OFFSET_MASK,
+        };
+        if !self.contains(point_i, point_j) {
+            return result;
+        }
+        for (di, dj) in [(0, 1), (1, 0), (1, 1), (1, -1)] {
+            // The number of consecutive points before and after the given point
+            let run = |sign: i8| {
+                (1..AREA_SIZE)
+                    .take_while(|&k| {
+                        self.contains(point_i + sign * k * di, point_j + sign * k * dj)
+                    })
+                    .count() as i8
+            };
+            let (before, after) = (run(-1), run(1));
+            if (before + 1 + after) as u8 >= min_length {
+                for k in -before..=after {
+                    result = result.insert(point_i + k * di, point_j + k * dj);
+                }
+            }
+        }
+        result
+    }
+
     fn local_coords(self, i: i8, j: i8) -> (u8, u8) {
         let (offset_i, offset_j) = self.offset();
         debug_assert!(i >= offset_i);
         debug_assert!(j >= offset_j);
-        debug_assert!(i - offset_i < 7);
-        debug_assert!(j - offset_j < 7);
+        debug_assert!(i - offset_i < AREA_SIZE);
+        debug_assert!(j - offset_j < AREA_SIZE);
         let i_local = (i - offset_i) as u8;
         let j_local = (j - offset_j) as u8;
         (i_local, j_local)
     }
 
-    fn arr_idx(self, i: i8, j: i8) -> u8 {
+    fn arr_idx(self, i: i8, j: i8) -> u32 {
         let (i_local, j_local) = self.local_coords(i, j);
-        i_local * 7 + j_local
+        local_idx(i_local as i8, j_local as i8)
     }
 
-    fn offset(self) -> (i8, i8) {
+    pub(crate) fn offset(self) -> (i8, i8) {
         decode_offset(self.bits)
     }
+
+    /// The coordinate at the center of the area, like the one that the bitboard was created with.
+    pub(crate) fn center(self) -> (i8, i8) {
+        let (offset_i, offset_j) = self.offset();
+        (offset_i + RADIUS, offset_j + RADIUS)
+    }
+}
+
+// The bit number of a local coordinate
+const fn local_idx(i_local: i8, j_local: i8) -> u32 {
+    (i_local * AREA_SIZE + j_local) as u32
+}
+
+// The fields at distances up to `reach` from the center of the area in the direction
+// (di, dj) and in the opposite direction, including the center
+const fn line_through_center((di, dj): (i8, i8), reach: i8) -> u128 {
+    let mut bits = 0;
+    let mut k = -reach;
+    while k <= reach {
+        bits |= 1 << local_idx(RADIUS + k * di, RADIUS + k * dj);
+        k += 1;
+    }
+    bits
 }
 
-fn decode_offset(bits: u64) -> (i8, i8) {
+fn decode_offset(bits: u128) -> (i8, i8) {
     // The highest bit of i_compressed is garbage and needs
     // to be replaced with the second-highest bit.
     let offset_i_compressed = 0b01111111i8 & (bits >> I_SHIFT) as i8;
@@ -280,61 +348,55 @@ fn decode_offset(bits: u64) -> (i8, i8) {
     (offset_i, offset_j)
 }
 
-fn encode_offset(offset_i: i8, offset_j: i8) -> u64 {
-    let offset_i_bits = u64::from(offset_i as u8 & 0b01111111u8) << I_SHIFT;
-    let offset_j_bits = u64::from(offset_j as u8 & 0b01111111u8) << J_SHIFT;
+fn encode_offset(offset_i: i8, offset_j: i8) -> u128 {
+    let offset_i_bits = u128::from(offset_i as u8 & 0b01111111u8) << I_SHIFT;
+    let offset_j_bits = u128::from(offset_j as u8 & 0b01111111u8) << J_SHIFT;
     offset_i_bits | offset_j_bits
 }
 
+// The number of distinct shifts along one axis, from -AREA_SIZE to AREA_SIZE. Shifting
+// further is the same as shifting by AREA_SIZE.
+const NUM_SHIFTS: usize = 2 * AREA_SIZE as usize + 1;
+
+// For every shift along the i axis (or the j axis, if `along_i` is false), a mask for the
+// bits that do not get "shifted out" by moving all points by that much.
+const fn shift_masks(along_i: bool) -> [u128; NUM_SHIFTS] {
+    let mut masks = [0; NUM_SHIFTS];
+    let mut shift_idx = 0;
+    while shift_idx < NUM_SHIFTS {
+        let delta = shift_idx as i8 - AREA_SIZE;
+        let mut i = 0;
+        while i < AREA_SIZE {
+            let mut j = 0;
+            while j < AREA_SIZE {
+                let moved = if along_i { i + delta } else { j + delta };
+                if 0 <= moved && moved < AREA_SIZE {
+                    masks[shift_idx] |= 1 << local_idx(i, j);
+                }
+                j += 1;
+            }
+            i += 1;
+        }
+        shift_idx += 1;
+    }
+    masks
+}
+
 // A 2D shift can be implemented as a mask + a bitshift.
 // If we only did a bitshift without masking, then we'd get artifacts from bits wrapping around.
-fn shift_2d_lossy(bits: u64, (delta_i, delta_j): (i8, i8)) -> u64 {
-    // A mask for the bits that do not get "shifted out" by moving all points by delta_i along the i axis.
-    static SHIFT_MASK_I: [u64; 15] = [
-        0b0000000000000000000000000000000000000000000000000,
-        0b1111111000000000000000000000000000000000000000000,
-        0b1111111111111100000000000000000000000000000000000,
-        0b1111111111111111111110000000000000000000000000000,
-        0b1111111111111111111111111111000000000000000000000,
-        0b1111111111111111111111111111111111100000000000000,
-        0b1111111111111111111111111111111111111111110000000,
-        0b1111111111111111111111111111111111111111111111111,
-        0b0000000111111111111111111111111111111111111111111,
-        0b0000000000000011111111111111111111111111111111111,
-        0b0000000000000000000001111111111111111111111111111,
-        0b0000000000000000000000000000111111111111111111111,
-        0b0000000000000000000000000000000000011111111111111,
-        0b0000000000000000000000000000000000000000001111111,
-        0b0000000000000000000000000000000000000000000000000,
-    ];
-
-    static SHIFT_MASK_J: [u64; 15] = [
-        0b0000000000000000000000000000000000000000000000000,
-        0b1000000100000010000001000000100000010000001000000,
-        0b1100000110000011000001100000110000011000001100000,
-        0b1110000111000011100001110000111000011100001110000,
-        0b1111000111100011110001111000111100011110001111000,
-        0b1111100111110011111001111100111110011111001111100,
-        0b1111110111111011111101111110111111011111101111110,
-        0b1111111111111111111111111111111111111111111111111,
-        0b0111111011111101111110111111011111101111110111111,
-        0b0011111001111100111110011111001111100111110011111,
-        0b0001111000111100011110001111000111100011110001111,
-        0b0000111000011100001110000111000011100001110000111,
-        0b0000011000001100000110000011000001100000110000011,
-        0b0000001000000100000010000001000000100000010000001,
-        0b0000000000000000000000000000000000000000000000000,
-    ];
+fn shift_2d_lossy(bits: u128, (delta_i, delta_j): (i8, i8)) -> u128 {
+    static SHIFT_MASK_I: [u128; NUM_SHIFTS] = shift_masks(true);
+    static SHIFT_MASK_J: [u128; NUM_SHIFTS] = shift_masks(false);
 
     // Larger values will get clamped to the ends, where all bits will be masked out.
-    let mask_i = SHIFT_MASK_I[(delta_i + 7).clamp(0, 14) as usize];
-    let mask_j = SHIFT_MASK_J[(delta_j + 7).clamp(0, 14) as usize];
+    let mask_i = SHIFT_MASK_I[(delta_i.saturating_add(AREA_SIZE)).clamp(0, 2 * AREA_SIZE) as usize];
+    let mask_j = SHIFT_MASK_J[(delta_j.saturating_add(AREA_SIZE)).clamp(0, 2 * AREA_SIZE) as usize];
     let valid_bits = bits & mask_i & mask_j;
-    let shift_by = delta_i * 7 + delta_j;
+    let shift_by = i32::from(delta_i) * i32::from(AREA_SIZE) + i32::from(delta_j);
     if shift_by > 0 {
-        valid_bits << shift_by.min(63)
+        valid_bits << shift_by.min(127)
     } else {
-        valid_bits >> shift_by.abs().min(63)
+        valid_bits >> shift_by.abs().min(127)
     }
 }
 
@@ -353,35 +415,53 @@ pub enum LineOrientation {
 /// Iterator returned by [`BitBoard::threes_in_a_row()`].
 pub struct ThreesInARowIter {
     bitboard: BitBoard,
-    // Bitset of the local i coordinates whose rows have at least 3 bits set
+    // The index of the current row, column and diagonals
     n: i8,
     orientation: LineOrientation,
 }
 
+// The number of diagonals in each direction that have at least three fields
+const NUM_DIAGONALS: i8 = 2 * AREA_SIZE - 5;
+// The lines through the center of the area, which are shifted to get the others
+const ROW: u128 = line_through_center((0, 1), RADIUS);
+const COLUMN: u128 = line_through_center((1, 0), RADIUS);
+const DIAGONAL: u128 = line_through_center((1, 1), RADIUS);
+const ANTIDIAGONAL: u128 = line_through_center((1, -1), RADIUS);
+
 impl Iterator for ThreesInARowIter {
     type Item = (LineOrientation, BitBoard);
 
     fn next(&mut self) -> Option<Self::Item> {
-        while self.n < 7 {
+        while self.n < NUM_DIAGONALS {
             let current_orientation = self.orientation;
+            // There are fewer rows and columns than diagonals
+            let is_row = self.n < AREA_SIZE;
             let mask = match current_orientation {
                 LineOrientation::IRow => {
-                    let mask = 0b1111111u64 << (self.n * 7);
+                    let mask = if is_row {
+                        shift_2d_lossy(ROW, (self.n - RADIUS, 0))
+                    } else {
+                        0
+                    };
                     (self.orientation, self.n) = (LineOrientation::JRow, self.n);
                     mask
                 }
                 LineOrientation::JRow => {
-                    let mask = 0b1000000100000010000001000000100000010000001u64 << self.n;
+                    let mask = if is_row {
+                        shift_2d_lossy(COLUMN, (0, self.n - RADIUS))
+                    } else {
+                        0
+                    };
                     (self.orientation, self.n) = (LineOrientation::Diagonal, self.n);
                     mask
                 }
                 LineOrientation::Diagonal => {
-                    let mask = shift_2d_lossy(0x1010101010101u64, (3 - self.n, 0));
+                    let mask = shift_2d_lossy(DIAGONAL, (NUM_DIAGONALS / 2 - self.n, 0));
                     (self.orientation, self.n) = (LineOrientation::Antidiagonal, self.n);
                     mask
                 }
                 LineOrientation::Antidiagonal => {
-                    let mask = shift_2d_lossy(0x41041041040u64, (self.n - 3, 0));
+                    let mask = shift_2d_lossy(ANTIDIAGONAL, (self.n - NUM_DIAGONALS / 2, 0));
                     (self.orientation, self.n) = (LineOrientation::IRow, self.n + 1);
                     mask
                 }
@@ -400,7 +480,7 @@ impl Iterator for ThreesInARowIter {
 
 impl FusedIterator for ThreesInARowIter {}
 
-impl std::ops::BitAnd for BitBoard {
+impl core::ops::BitAnd for BitBoard {
     type Output = Self;
 
     fn bitand(self, rhs: Self) -> Self::Output {
@@ -411,7 +491,7 @@ impl std::ops::BitAnd for BitBoard {
     }
 }
 
-impl std::ops::BitOr for BitBoard {
+impl core::ops::BitOr for BitBoard {
     type Output = Self;
 
     fn bitor(self, rhs: Self) -> Self::Output {
@@ -422,7 +502,7 @@ impl std::ops::BitOr for BitBoard {
     }
 }
 
-impl std::ops::BitXor for BitBoard {
+impl core::ops::BitXor for BitBoard {
     type Output = Self;
 
     fn bitxor(self, rhs: Self) -> Self::Output {
@@ -433,21 +513,21 @@ impl std::ops::BitXor for BitBoard {
     }
 }
 
-impl std::ops::BitAndAssign for BitBoard {
+impl core::ops::BitAndAssign for BitBoard {
     fn bitand_assign(&mut self, rhs: Self) {
         debug_assert_eq!(self.bits & OFFSET_MASK, rhs.bits & OFFSET_MASK);
         self.bits &= rhs.bits;
     }
 }
 
-impl std::ops::BitOrAssign for BitBoard {
+impl core::ops::BitOrAssign for BitBoard {
     fn bitor_assign(&mut self, rhs: Self) {
         debug_assert_eq!(self.bits & OFFSET_MASK, rhs.bits & OFFSET_MASK);
         self.bits |= rhs.bits;
     }
 }
 
-impl std::ops::BitXorAssign for BitBoard {
+impl core::ops::BitXorAssign for BitBoard {
     fn bitxor_assign(&mut self, rhs: Self) {
         debug_assert_eq!(self.bits & OFFSET_MASK, rhs.bits & OFFSET_MASK);
         self.bits ^= rhs.bits & BOARD_MASK;
@@ -460,15 +540,29 @@ impl Debug for BitBoard {
     }
 }
 
+/// The coordinates in the bitboard, in the order of [`BitBoardIter`], e.g. `(0, 1) (2, -1)`.
+/// An empty bitboard is an empty string.
+impl Display for BitBoard {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (idx, (i, j)) in self.into_iter().enumerate() {
+            if idx > 0 {
+                f.write_str(" ")?;
+            }
+            write!(f, "({}, {})", i, j)?;
+        }
+        Ok(())
+    }
+}
+
 // Prints the bitset as a 2D array, least significant bit first,
 // such that the local coordinate (0, 0) is in the top left corner,
 // and i is the vertical and j the horizontal coordinate.
-fn print_bits(bits: u64) -> String {
-    let digits = format!("{:049b}", bits & BOARD_MASK);
-    let mut s = String::with_capacity(49 * 2);
+fn print_bits(bits: u128) -> String {
+    let digits = format!("{:0width$b}", bits & BOARD_MASK, width = AREA_LEN);
+    let mut s = String::with_capacity(AREA_LEN * 2);
     for (idx, c) in digits.chars().rev().enumerate() {
         s.push(c);
-        if idx % 7 == 6 {
+        if idx % AREA_SIZE as usize == AREA_SIZE as usize - 1 {
             s.push('\n');
         } else {
             s.push(' ');
@@ -478,6 +572,10 @@ fn print_bits(bits: u64) -> String {
 }
 
 /// Iterator produced by [`BitBoard::into_iter()`].
+///
+/// Yields the coordinates sorted by `i` first, then by `j`, which is the order of the
+/// bits in the internal layout. This order is guaranteed, so it's safe to rely on it
+/// e.g. for deterministic move orderings.
 pub struct BitBoardIter {
     bitboard: BitBoard,
 }
@@ -499,12 +597,12 @@ impl Iterator for BitBoardIter {
         if self.bitboard.is_empty() {
             None
         } else {
-            // This cast is safe, as the max value for trailing_zeros is 64
+            // This cast is safe, as the board is not empty, so trailing_zeros is at most 80
             let idx: i8 = self.bitboard.bits.trailing_zeros() as i8;
             let (offset_i, offset_j) = self.bitboard.offset();
             // Clear the flag corresponding to this coordinate
-            self.bitboard.bits ^= 1u64 << idx;
-            Some((offset_i + idx / 7, offset_j + idx % 7))
+            self.bitboard.bits ^= 1 << idx;
+            Some((offset_i + idx / AREA_SIZE, offset_j + idx % AREA_SIZE))
         }
     }
 
@@ -514,6 +612,20 @@ impl Iterator for BitBoardIter {
     }
 }
 
+impl DoubleEndedIterator for BitBoardIter {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.bitboard.is_empty() {
+            None
+        } else {
+            // The highest set bit of the board, which is at most bit 80
+            let idx: i8 = 127 - (self.bitboard.bits & BOARD_MASK).leading_zeros() as i8;
+            let (offset_i, offset_j) = self.bitboard.offset();
+            self.bitboard.bits ^= 1 << idx;
+            Some((offset_i + idx / AREA_SIZE, offset_j + idx % AREA_SIZE))
+        }
+    }
+}
+
 impl ExactSizeIterator for BitBoardIter {
     fn len(&self) -> usize {
         self.bitboard.num_entries() as usize
@@ -560,6 +672,10 @@ mod python {
             self.lines_going_through_point(point_i, point_j)
         }
 
+        fn __str__(&self) -> String {
+            self.to_string()
+        }
+
         fn __len__(&self) -> usize {
             self.num_entries() as usize
         }
@@ -592,11 +708,12 @@ mod python {
             *self ^= other
         }
         // Python exclusive
-        fn to_matrix_and_offset(&self) -> ([[bool; 7]; 7], (i8, i8)) {
-            let arr = std::array::from_fn(|i_local| {
-                std::array::from_fn(|j_local| {
-                    let idx = i_local * 7 + j_local;
-                    self.bits & (1u64 << idx) != 0
+        fn to_matrix_and_offset(
+            &self,
+        ) -> ([[bool; AREA_SIZE as usize]; AREA_SIZE as usize], (i8, i8)) {
+            let arr = core::array::from_fn(|i_local| {
+                core::array::from_fn(|j_local| {
+                    self.bits & (1 << local_idx(i_local as i8, j_local as i8)) != 0
                 })
             });
             (arr, self.offset())
@@ -610,15 +727,51 @@ mod tests {
 
     use super::*;
 
+    #[test]
+    fn display() {
+        let bb = BitBoard::empty_board_centered_at((0, 0))
+            .insert(2, -1)
+            .insert(0, 1);
+        assert_eq!(bb.to_string(), "(0, 1) (2, -1)");
+        assert_eq!(BitBoard::empty_board_centered_at((0, 0)).to_string(), "");
+    }
+
     quickcheck! {
         fn offset_compression(i: i8, j: i8) -> bool {
             // Restrict i and j to the range [-52, 52]
             let i = i % 53;
             let j = j % 53;
-            BitBoard::empty_board_centered_at((i, j)).offset() == (i - 3, j - 3)
+            BitBoard::empty_board_centered_at((i, j)).offset() == (i - 4, j - 4)
         }
     }
 
+    #[test]
+    fn iteration_order() {
+        let bb = BitBoard::empty_board_centered_at((0, 0))
+            .insert(1, -2)
+            .insert(-3, 3)
+            .insert(0, 0)
+            .insert(-3, -3)
+            .insert(3, 3);
+        let expected = vec![(-3, -3), (-3, 3), (0, 0), (1, -2), (3, 3)];
+        assert_eq!(bb.into_iter().collect::<Vec<_>>(), expected);
+        let reversed: Vec<_> = bb.into_iter().rev().collect();
+        assert_eq!(reversed, expected.iter().rev().copied().collect::<Vec<_>>());
+        let mut iter = bb.into_iter();
+        assert_eq!(iter.next_back(), Some((3, 3)));
+        assert_eq!(iter.next(), Some((-3, -3)));
+        assert_eq!(iter.len(), 3);
+    }
+
+    #[test]
+    fn contains_outside_of_area() {
+        let bb = BitBoard::empty_board_centered_at((0, 0)).insert(-4, 4);
+        assert!(bb.contains(-4, 4));
+        assert!(!bb.contains(-5, 4));
+        assert!(!bb.contains(-4, 5));
+        assert!(!bb.contains(i8::MIN, i8::MAX));
+    }
+
     #[test]
     fn shift_far() {
         let bb = BitBoard::empty_board_centered_at((12, 30))
@@ -646,6 +799,12 @@ mod tests {
             Vec::from_iter(bb.lines_going_through_point(9, 9)),
             Vec::new()
         );
+        // Unlike above, the point at (8, 11) doesn't count, since it's not connected to the others
+        assert!(bb.lines_of_length_going_through_point(11, 11, 4).is_empty());
+        assert_eq!(
+            Vec::from_iter(bb.lines_of_length_going_through_point(12, 11, 3)),
+            vec![(11, 11), (12, 11), (13, 11)]
+        );
     }
 
     #[test]
@@ -678,5 +837,14 @@ mod tests {
             Vec::from_iter(bb_4.threes_in_a_row()),
             vec![(LineOrientation::Diagonal, bb_4)]
         );
+        // The shortest diagonals, in the corners of the area
+        let bb_5 = BitBoard::empty_board_centered_at((-20, -10))
+            .insert(-16, -12)
+            .insert(-17, -13)
+            .insert(-18, -14);
+        assert_eq!(
+            Vec::from_iter(bb_5.threes_in_a_row()),
+            vec![(LineOrientation::Diagonal, bb_5)]
+        );
     }
 }
@@ -0,0 +1,128 @@
+use serde::{Deserialize, Serialize};
+
+use crate::prelude::*;
+use crate::{Board, CompactField, Field, InvalidBoard, Symmetry, Transform};
+
+/// A board in a normal form, which is the same for all boards that are equal
+/// up to translation, rotation and reflection.
+///
+/// The fields are translated so that the smallest coordinates are zero, and out
+/// of the eight symmetric variants, the one with the smallest list of fields is used.
+/// This is useful as a key for looking up positions, e.g. in an opening book.
+///
+/// Serialized as a list of [`Field`]s. When deserializing, the fields are
+/// canonicalized again, so they don't need to be in canonical form.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(into = "Vec<Field>", try_from = "Vec<Field>")]
+pub struct CanonicalBoard {
+    /// Sorted by coordinates, without empty fields.
+    fields: Vec<(i8, i8, CompactField)>,
+}
+
+impl CanonicalBoard {
+    /// Creates a board from the canonical fields.
+    pub fn to_board(&self) -> Board {
+        Board::from_fields_list(self.fields.clone())
+    }
+}
+
+impl From<CanonicalBoard> for Vec<Field> {
+    fn from(board: CanonicalBoard) -> Self {
+        board
+            .fields
+            .into_iter()
+            .map(|(i, j, field)| field.into_field(i, j))
+            .collect()
+    }
+}
+
+impl TryFrom<Vec<Field>> for CanonicalBoard {
+    type Error = InvalidBoard;
+
+    fn try_from(fields: Vec<Field>) -> Result<Self, Self::Error> {
+        Ok(Board::try_new(&fields)?.canonicalize().0)
+    }
+}
+
+impl Board {
+    /// Computes the [`CanonicalBoard`] for this board, and the transform that maps
+    /// this board's coordinates to the canonical coordinates.
+    pub fn canonicalize(&self) -> (CanonicalBoard, Transform) {
+        let mut best: Option<(CanonicalBoard, Transform)> = None;
+        for symmetry in Symmetry::ALL {
+            let mut fields: Vec<_> = self
+                .fields
+                .iter()
+                .filter(|(_, _, field)| !field.is_empty())
+                .map(|&(i, j, field)| {
+                    let (i, j) = symmetry.apply(i, j);
+                    (i, j, field)
+                })
+                .collect();
+            // There is always at least one non-empty field on a board
+            let i_min = fields.iter().map(|f| f.0).min().unwrap();
+            let j_min = fields.iter().map(|f| f.1).min().unwrap();
+            for field in &mut fields {
+                field.0 -= i_min;
+                field.1 -= j_min;
+            }
+            fields.sort();
+            let candidate = CanonicalBoard { fields };
+            let is_smaller = match &best {
+                Some((board, _)) => candidate < *board,
+                None => true,
+            };
+            if is_smaller {
+                let transform = Transform {
+                    symmetry,
+                    offset: (-i_min, -j_min),
+                };
+                best = Some((candidate, transform));
+            }
+        }
+        best.unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{card, field, CardToPlay};
+
+    #[test]
+    fn symmetric_boards_have_the_same_canonical_form() {
+        let board = Board::new(&[
+            field(0, 0, card!("7♠")),
+            field(0, 1, card!("8♥")),
+            field(1, 1, card!("2♣")),
+        ]);
+        let (canonical, transform) = board.canonicalize();
+        for symmetry in Symmetry::ALL {
+            let other = Transform {
+                symmetry,
+                offset: (5, 0),
+            };
+            let (other_canonical, other_transform) = other.apply(&board).canonicalize();
+            assert_eq!(other_canonical, canonical);
+            // Both transforms lead to the same canonical coordinates
+            assert_eq!(
+                other_transform.apply(&other.apply(&(1, 1))),
+                transform.apply(&(1, 1))
+            );
+        }
+    }
+
+    #[test]
+    fn card_to_play_round_trip() {
+        let board = Board::new(&[field(3, -2, card!("7♠")), field(4, -2, card!("8♥"))]);
+        let (_, transform) = board.canonicalize();
+        let ctp = CardToPlay {
+            card: card!("K♥"),
+            i: 4,
+            j: -2,
+            target_field_for_king_ability: Some((3, -2)),
+        };
+        let canonical_ctp = transform.apply(&ctp);
+        assert_eq!(transform.inverse().apply(&canonical_ctp), ctp);
+    }
+}
@@ -0,0 +1,192 @@
+use crate::prelude::*;
+use crate::{
+    Board, Card, CardToPlay, CardsSet, Color, CompactField, Field, Hand, PlayerView, Suit,
+};
+
+/// Something with cards whose colors can be swapped, i.e. red cards become black and the
+/// other way around.
+///
+/// Diamonds are swapped with spades, and hearts with clubs. The rules of the game don't
+/// change under this, so a bot without a color bias should play the color-swapped turn
+/// in the color-swapped position. Swapping twice gives back the original.
+///
+/// ```
+/// use gomori::{card, CardToPlay, SwapColors};
+/// let ctp = CardToPlay { card: card!("K♥"), i: 0, j: 1, target_field_for_king_ability: Some((1, 1)) };
+/// let swapped = ctp.swap_colors();
+/// assert_eq!(swapped.card, card!("K♣"));
+/// assert_eq!(swapped.swap_colors(), ctp);
+/// ```
+pub trait SwapColors {
+    fn swap_colors(&self) -> Self;
+}
+
+impl SwapColors for Suit {
+    fn swap_colors(&self) -> Self {
+        match self {
+            Suit::Diamond => Suit::Spade,
+            Suit::Heart => Suit::Club,
+            Suit::Spade => Suit::Diamond,
+            Suit::Club => Suit::Heart,
+        }
+    }
+}
+
+impl SwapColors for Color {
+    fn swap_colors(&self) -> Self {
+        match self {
+            Color::Black => Color::Red,
+            Color::Red => Color::Black,
+        }
+    }
+}
+
+impl SwapColors for Card {
+    fn swap_colors(&self) -> Self {
+        Card {
+            suit: self.suit.swap_colors(),
+            rank: self.rank,
+        }
+    }
+}
+
+// The suit is in the low two bits of a card's index, and swapping the colors flips the
+// higher one of them, so each pair of bits is swapped with its neighboring pair.
+const LOWER_PAIRS: u64 = 0x0003_3333_3333_3333;
+
+impl SwapColors for CardsSet {
+    fn swap_colors(&self) -> Self {
+        CardsSet {
+            bits: (self.bits & LOWER_PAIRS) << 2 | (self.bits >> 2) & LOWER_PAIRS,
+        }
+    }
+}
+
+impl SwapColors for Hand {
+    fn swap_colors(&self) -> Self {
+        Hand::try_from(self.cards().swap_colors()).expect("Swapping keeps the number of cards")
+    }
+}
+
+impl SwapColors for CompactField {
+    fn swap_colors(&self) -> Self {
+        let mut field = CompactField::new();
+        for card in self.hidden_cards() {
+            field = field.place_card(card.swap_colors());
+        }
+        field = field.turn_face_down();
+        match self.top_card() {
+            Some(card) => field.place_card(card.swap_colors()),
+            None => field,
+        }
+    }
+}
+
+impl SwapColors for Field {
+    fn swap_colors(&self) -> Self {
+        Field {
+            i: self.i,
+            j: self.j,
+            top_card: self.top_card.map(|card| card.swap_colors()),
+            hidden_cards: self.hidden_cards.iter().map(Card::swap_colors).collect(),
+        }
+    }
+}
+
+/// Swaps the card, but keeps the coordinates and the target of the king's ability.
+impl SwapColors for CardToPlay {
+    fn swap_colors(&self) -> Self {
+        CardToPlay {
+            card: self.card.swap_colors(),
+            ..*self
+        }
+    }
+}
+
+impl SwapColors for Board {
+    fn swap_colors(&self) -> Self {
+        let swap_fields = |fields: &[(i8, i8, CompactField)]| {
+            fields
+                .iter()
+                .map(|&(i, j, field)| (i, j, field.swap_colors()))
+                .collect::<Vec<_>>()
+        };
+        let mut board = Board::from_valid_fields_list(swap_fields(&self.fields), self.rules);
+        board.history = self
+            .history
+            .as_ref()
+            .map(|history| history.iter().map(CardToPlay::swap_colors).collect());
+        board.history_start = self
+            .history_start
+            .as_ref()
+            .map(|start| swap_fields(start).into());
+        board
+    }
+}
+
+impl SwapColors for PlayerView {
+    fn swap_colors(&self) -> Self {
+        PlayerView {
+            board: self.board.swap_colors(),
+            hand: self.hand.swap_colors(),
+            cards_won: self.cards_won.swap_colors(),
+            cards_won_by_opponents: self.cards_won_by_opponents.swap_colors(),
+            new_cards_won_by_opponents: self.new_cards_won_by_opponents.swap_colors(),
+            draw_pile_size: self.draw_pile_size,
+            opponent_draw_pile_size: self.opponent_draw_pile_size,
+            turn_number: self.turn_number,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use quickcheck::quickcheck;
+
+    use super::*;
+    use crate::arbitrary::PlayCardInput;
+    use crate::{card, GameInProgress, IllegalCardPlayed, BLACK_CARDS_SET, RED_CARDS_SET};
+
+    #[test]
+    fn cards_sets_are_swapped_card_by_card() {
+        assert_eq!(RED_CARDS_SET.swap_colors(), BLACK_CARDS_SET);
+        assert_eq!(BLACK_CARDS_SET.swap_colors(), RED_CARDS_SET);
+        let set = CardsSet::from_iter([card!("2♦"), card!("T♥"), card!("A♣")]);
+        assert_eq!(
+            set.swap_colors(),
+            CardsSet::from_iter([card!("2♠"), card!("T♣"), card!("A♥")])
+        );
+    }
+
+    quickcheck! {
+        /// Playing a swapped card on a swapped board has the swapped effects.
+        fn rules_are_invariant(input: PlayCardInput) -> bool {
+            let board = Board::new(&input.fields);
+            let swapped_board = board.swap_colors();
+            let ctp = input.card_to_play;
+            match (board.calculate(ctp), swapped_board.calculate(ctp.swap_colors())) {
+                (Ok(effects), Ok(swapped_effects)) => {
+                    swapped_effects.cards_won == effects.cards_won.swap_colors()
+                        && swapped_effects.combo == effects.combo
+                        && swapped_effects.execute().to_fields_vec()
+                            == effects.execute().swap_colors().to_fields_vec()
+                }
+                (
+                    Err(IllegalCardPlayed::IncompatibleCard { existing_card }),
+                    Err(IllegalCardPlayed::IncompatibleCard { existing_card: swapped_card }),
+                ) => swapped_card == existing_card.swap_colors(),
+                (Err(err), Err(swapped_err)) => err == swapped_err,
+                _ => false,
+            }
+        }
+
+        fn swapping_twice_is_the_identity(game: GameInProgress) -> bool {
+            let board = game.board.swap_colors();
+            let fields: Vec<Field> = game.board.to_fields_vec().iter().map(Field::swap_colors).collect();
+            board.to_fields_vec() == fields
+                && board.suit_counts()[Suit::Heart as usize] == game.board.suit_counts()[Suit::Club as usize]
+                && board.swap_colors() == game.board
+                && game.player.hand.swap_colors().swap_colors() == game.player.hand
+        }
+    }
+}
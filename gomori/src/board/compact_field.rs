@@ -17,7 +17,7 @@ const CLEAR_TOP_CARD_MASK: u64 = !(TOP_CARD_INDICATOR_BIT | TOP_CARD_MASK);
 /// new value instead of really mutating in-place. It is also [`Copy`],
 /// so a value is not consumed by methods with `self` receiver.
 #[cfg_attr(feature = "python", pyo3::pyclass)]
-#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub struct CompactField {
     /// The low 52 bits are a bitset of the hidden cards.
     /// The next highest 6 bits are the index of the face-up card, if any.
@@ -55,13 +55,58 @@ impl CompactField {
         }
     }
 
-    /// Place a new card on this field, which will be the new face-up card
+    /// Place a new card on this field, which will be the new face-up card. The previous top
+    /// card, if any, becomes a hidden card.
+    ///
+    /// Whether the card may be placed here by the rules is not checked, see
+    /// [`can_place_card()`](Self::can_place_card). Panics if the card is already on this field.
+    #[must_use] // Because users might expect this to be a mutating method
+    pub fn place_card(self, card: Card) -> Self {
+        assert_not_on_field(self, card);
+        self.place_card_unchecked(card)
+    }
+
+    // Like place_card(), for playing on a board, whose cards are already known to be unique
+    pub(crate) fn place_card_unchecked(self, card: Card) -> Self {
+        self.turn_face_down().with_top_card_unchecked(card)
+    }
+
+    /// Replaces the face-up card, without turning the current one face down, i.e. the
+    /// current top card is removed from the field. With `None`, the field only has hidden
+    /// cards afterwards.
+    ///
+    /// Panics if the card is one of the hidden cards.
+    #[must_use] // Because users might expect this to be a mutating method
+    pub fn with_top_card(self, top_card: Option<Card>) -> Self {
+        let bits = self.bits & CLEAR_TOP_CARD_MASK;
+        match top_card {
+            Some(card) => {
+                assert_not_on_field(Self { bits }, card);
+                Self { bits }.with_top_card_unchecked(card)
+            }
+            None => Self { bits },
+        }
+    }
+
+    /// Removes the card from this field, e.g. to undo placing it.
+    ///
+    /// If it's the top card, the field has no face-up card afterwards, since the order of
+    /// the hidden cards is not known. If the card is not on this field, it is unchanged.
     #[must_use] // Because users might expect this to be a mutating method
-    pub(crate) fn place_card(self, card: Card) -> Self {
-        let card_idx = card.to_index();
-        let Self { bits } = self.turn_face_down();
+    pub fn without_card(self, card: Card) -> Self {
+        if self.top_card() == Some(card) {
+            self.with_top_card(None)
+        } else {
+            Self {
+                bits: self.bits & !(1u64 << card.to_index()),
+            }
+        }
+    }
+
+    // Sets the top card, which must not be set yet
+    fn with_top_card_unchecked(self, card: Card) -> Self {
         Self {
-            bits: bits | TOP_CARD_INDICATOR_BIT | (u64::from(card_idx) << 52),
+            bits: self.bits | TOP_CARD_INDICATOR_BIT | (u64::from(card.to_index()) << 52),
         }
     }
 
@@ -110,6 +155,15 @@ impl CompactField {
     }
 }
 
+// Every card exists only once, so it can't be on the field twice
+fn assert_not_on_field(field: CompactField, card: Card) {
+    assert!(
+        !field.all_cards().contains(card),
+        "{} is already on the field",
+        card
+    );
+}
+
 impl Default for CompactField {
     fn default() -> Self {
         Self::new()
@@ -135,24 +189,33 @@ impl From<&Field> for CompactField {
 
 #[cfg(feature = "python")]
 mod python {
-    use pyo3::pymethods;
+    use pyo3::exceptions::PyValueError;
+    use pyo3::{pymethods, PyResult};
 
     use super::*;
     use crate::{Card, Field};
 
+    // Raises a ValueError instead of the panic of the Rust methods
+    fn check_not_on_field(field: CompactField, card: Card) -> PyResult<()> {
+        if field.all_cards().contains(card) {
+            Err(PyValueError::new_err(format!(
+                "{} is already on the field",
+                card
+            )))
+        } else {
+            Ok(())
+        }
+    }
+
     #[pymethods]
     impl CompactField {
         #[new]
         #[pyo3(signature = (*, top_card, hidden_cards = CardsSet::new()))]
-        fn py_new(top_card: Option<Card>, hidden_cards: CardsSet) -> Self {
-            let field = Self {
+        fn py_new(top_card: Option<Card>, hidden_cards: CardsSet) -> PyResult<Self> {
+            Self {
                 bits: hidden_cards.bits,
-            };
-            if let Some(c) = top_card {
-                field.place_card(c)
-            } else {
-                field
             }
+            .py_with_top_card(top_card)
         }
 
         fn __repr__(&self) -> String {
@@ -194,6 +257,25 @@ mod python {
             self.num_hidden_cards()
         }
 
+        #[pyo3(name = "place_card")]
+        fn py_place_card(&self, card: Card) -> PyResult<Self> {
+            check_not_on_field(*self, card)?;
+            Ok(self.place_card(card))
+        }
+
+        #[pyo3(name = "with_top_card")]
+        fn py_with_top_card(&self, top_card: Option<Card>) -> PyResult<Self> {
+            if let Some(card) = top_card {
+                check_not_on_field(self.with_top_card(None), card)?;
+            }
+            Ok(self.with_top_card(top_card))
+        }
+
+        #[pyo3(name = "without_card")]
+        fn py_without_card(&self, card: Card) -> Self {
+            self.without_card(card)
+        }
+
         #[pyo3(name = "turn_face_down")]
         fn py_turn_face_down(&self) -> Self {
             self.turn_face_down()
@@ -256,4 +338,40 @@ mod test {
         );
         assert_eq!(field.top_card(), Some(CARD_3));
     }
+
+    #[test]
+    fn replace_and_remove_cards() {
+        let field = CompactField::new().place_card(CARD_1).place_card(CARD_2);
+        let replaced = field.with_top_card(Some(CARD_3));
+        assert_eq!(replaced.top_card(), Some(CARD_3));
+        assert_eq!(replaced.hidden_cards(), CardsSet::from_iter([CARD_1]));
+        assert_eq!(
+            field.with_top_card(None).all_cards(),
+            CardsSet::from_iter([CARD_1])
+        );
+
+        // Removing the top card leaves no face-up card
+        let removed = field.without_card(CARD_2);
+        assert_eq!(removed.top_card(), None);
+        assert_eq!(removed.hidden_cards(), CardsSet::from_iter([CARD_1]));
+        let removed = field.without_card(CARD_1);
+        assert_eq!(removed.top_card(), Some(CARD_2));
+        assert_eq!(removed.num_hidden_cards(), 0);
+        assert!(removed.without_card(CARD_2).is_empty());
+        assert_eq!(field.without_card(CARD_3), field);
+    }
+
+    #[test]
+    #[should_panic(expected = "is already on the field")]
+    fn cards_cannot_be_placed_twice() {
+        let field = CompactField::new().place_card(CARD_1).place_card(CARD_2);
+        let _ = field.place_card(CARD_1);
+    }
+
+    #[test]
+    #[should_panic(expected = "is already on the field")]
+    fn hidden_cards_cannot_be_the_top_card() {
+        let field = CompactField::new().place_card(CARD_1).place_card(CARD_2);
+        let _ = field.with_top_card(Some(CARD_1));
+    }
 }
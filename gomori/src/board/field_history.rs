@@ -0,0 +1,105 @@
+use crate::prelude::*;
+use crate::{Card, CardsSet, CompactField};
+
+/// The cards on a single field, in the order they were played, see [`Board::field_history()`](crate::Board::field_history).
+///
+/// Unlike a [`CompactField`], this knows which cards lie on top of which, and which of the
+/// covered cards face up. It can be converted into a `CompactField`, which is all that
+/// matters for the rules.
+#[cfg_attr(feature = "python", pyo3::pyclass(get_all))]
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct FieldHistory {
+    /// The hidden cards that were already on the field when the history started, whose
+    /// order is unknown. They lie below all cards in `stack`.
+    pub hidden_before: CardsSet,
+    /// All other cards, from the bottom to the top, and whether they face up.
+    pub stack: Vec<(Card, bool)>,
+}
+
+impl FieldHistory {
+    /// The uppermost card, if it faces up.
+    pub fn top_card(&self) -> Option<Card> {
+        self.stack
+            .last()
+            .and_then(|&(card, face_up)| face_up.then_some(card))
+    }
+
+    /// All cards on the field.
+    pub fn all_cards(&self) -> CardsSet {
+        self.stack
+            .iter()
+            .fold(self.hidden_before, |cards, &(card, _)| cards.insert(card))
+    }
+
+    /// Puts a card on top, facing up.
+    pub(crate) fn place_card(&mut self, card: Card) {
+        self.stack.push((card, true));
+    }
+
+    /// Turns the uppermost card face down.
+    pub(crate) fn turn_face_down(&mut self) {
+        if let Some((_, face_up)) = self.stack.last_mut() {
+            *face_up = false;
+        }
+    }
+}
+
+/// A field whose history is unknown, i.e. all cards except for the top card are
+/// [`hidden_before`](FieldHistory::hidden_before).
+impl From<CompactField> for FieldHistory {
+    fn from(field: CompactField) -> Self {
+        Self {
+            hidden_before: field.hidden_cards(),
+            stack: field
+                .top_card()
+                .map(|card| (card, true))
+                .into_iter()
+                .collect(),
+        }
+    }
+}
+
+impl From<&FieldHistory> for CompactField {
+    fn from(history: &FieldHistory) -> Self {
+        let top_card = history.top_card();
+        let mut field = CompactField::new();
+        for card in history.all_cards() {
+            if Some(card) != top_card {
+                field = field.place_card(card).turn_face_down();
+            }
+        }
+        match top_card {
+            Some(card) => field.place_card(card),
+            None => field,
+        }
+    }
+}
+
+#[cfg(feature = "python")]
+mod python {
+    use pyo3::pymethods;
+
+    use super::*;
+
+    #[pymethods]
+    impl FieldHistory {
+        #[pyo3(name = "top_card")]
+        fn py_top_card(&self) -> Option<Card> {
+            self.top_card()
+        }
+
+        #[pyo3(name = "all_cards")]
+        fn py_all_cards(&self) -> CardsSet {
+            self.all_cards()
+        }
+
+        #[pyo3(name = "to_compact_field")]
+        fn py_to_compact_field(&self) -> CompactField {
+            CompactField::from(self)
+        }
+
+        fn __repr__(&self) -> String {
+            format!("{:?}", self)
+        }
+    }
+}
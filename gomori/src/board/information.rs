@@ -0,0 +1,131 @@
+use super::Board;
+use crate::{CardCounter, CardsSet, Hand};
+
+/// How much a player doesn't know about the game, see [`Board::hidden_information()`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct InfoMetrics {
+    /// The number of cards on the board that don't face up, i.e. all but the top cards.
+    pub num_hidden_cards: u32,
+    /// The cards whose location is unknown to the player: those that the opponent still
+    /// has in its hand or draw pile.
+    pub unknown_cards: CardsSet,
+    /// For each field whose cards all face down, its coordinates and the cards that may
+    /// have been its top card before it was flipped.
+    ///
+    /// The board doesn't record which of the field's cards that was, so these are all of
+    /// them.
+    pub face_down_candidates: Vec<((i8, i8), CardsSet)>,
+    /// The entropy in bits of which of the unknown cards are in the opponent's hand, if
+    /// every hand is equally likely, as in [`determinize()`](crate::determinize).
+    pub opponent_hand_entropy: f64,
+    /// The entropy in bits of the top cards of the face-down fields, i.e. the sum of the
+    /// binary logarithms of the numbers of candidates.
+    pub face_down_entropy: f64,
+}
+
+impl InfoMetrics {
+    /// The sum of both entropies.
+    pub fn total_entropy(&self) -> f64 {
+        self.opponent_hand_entropy + self.face_down_entropy
+    }
+}
+
+impl Board {
+    /// Computes how many cards on this board are hidden, which cards the player doesn't
+    /// know the location of, and estimates of the entropy of both, for a player with the
+    /// given card counter.
+    ///
+    /// The hidden cards themselves are known, since the requests contain them, but not
+    /// which of them a face-down field's top card was. Flipping the opponent's stacks face
+    /// down or winning its cards changes these, so they can be used to decide whether
+    /// denying information is worth it.
+    pub fn hidden_information(&self, counter: &CardCounter) -> InfoMetrics {
+        let mut on_board = CardsSet::new();
+        let mut num_face_up_cards = 0;
+        let mut face_down_candidates = Vec::new();
+        for &(i, j, field) in self.iter() {
+            on_board |= field.all_cards();
+            match field.top_card() {
+                Some(_) => num_face_up_cards += 1,
+                None => face_down_candidates.push(((i, j), field.hidden_cards())),
+            }
+        }
+        let unknown_cards = counter
+            .available_cards_opponent
+            .difference(on_board | counter.cards_won_self | counter.cards_won_opponent);
+        let hand_size = unknown_cards.len().min(Hand::MAX_SIZE as u32);
+        let face_down_entropy = face_down_candidates
+            .iter()
+            .map(|(_, candidates)| f64::from(candidates.len()).log2())
+            .sum();
+        InfoMetrics {
+            num_hidden_cards: self.num_cards() - num_face_up_cards,
+            unknown_cards,
+            face_down_candidates,
+            opponent_hand_entropy: log2_binomial(unknown_cards.len(), hand_size),
+            face_down_entropy,
+        }
+    }
+}
+
+// The binary logarithm of n choose k, for k <= n
+fn log2_binomial(n: u32, k: u32) -> f64 {
+    (0..k)
+        .map(|i| (f64::from(n - i) / f64::from(i + 1)).log2())
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::collections::BTreeSet;
+
+    use super::*;
+    use crate::{card, field, Color, Field};
+
+    #[test]
+    fn binomials() {
+        assert_eq!(log2_binomial(5, 0), 0.0);
+        assert_eq!(log2_binomial(5, 5), 0.0);
+        assert!((log2_binomial(4, 2) - 6f64.log2()).abs() < 1e-9);
+        assert!((log2_binomial(26, 5) - 65780f64.log2()).abs() < 1e-9);
+    }
+
+    #[test]
+    fn hidden_cards_and_candidates() {
+        // We are red, so the opponent has played the black cards
+        let board = Board::new(&[
+            Field {
+                hidden_cards: BTreeSet::from([card!("2♦")]),
+                ..field(0, 0, card!("2♣"))
+            },
+            Field {
+                i: 0,
+                j: 1,
+                top_card: None,
+                hidden_cards: BTreeSet::from([card!("3♠"), card!("3♥")]),
+            },
+            field(0, 2, card!("4♣")),
+        ]);
+        let mut counter = CardCounter::new(Color::Red);
+        counter.available_cards_opponent = counter
+            .available_cards_opponent
+            .difference(CardsSet::from_iter([card!("2♣"), card!("3♠"), card!("4♣")]));
+        counter.cards_won_opponent = CardsSet::from_iter([card!("5♠")]);
+        let metrics = board.hidden_information(&counter);
+        assert_eq!(metrics.num_hidden_cards, 3);
+        assert_eq!(metrics.unknown_cards.len(), 22);
+        assert!(!metrics.unknown_cards.contains(card!("5♠")));
+        assert_eq!(
+            metrics.face_down_candidates,
+            vec![((0, 1), CardsSet::from_iter([card!("3♠"), card!("3♥")]))]
+        );
+        assert_eq!(metrics.face_down_entropy, 1.0);
+        assert!((metrics.opponent_hand_entropy - log2_binomial(22, 5)).abs() < 1e-9);
+        assert_eq!(metrics.total_entropy(), metrics.opponent_hand_entropy + 1.0);
+
+        // Late in the game, the opponent's last cards are all in its hand
+        counter.available_cards_opponent = CardsSet::from_iter([card!("K♠"), card!("K♣")]);
+        let metrics = board.hidden_information(&counter);
+        assert_eq!(metrics.opponent_hand_entropy, 0.0);
+    }
+}
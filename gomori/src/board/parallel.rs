@@ -0,0 +1,68 @@
+use core::cmp::Reverse;
+
+use rayon::prelude::*;
+
+use super::{Board, CalculatedEffects};
+use crate::{Card, CardToPlay};
+
+impl Board {
+    /// Evaluates every legal play of the given cards on rayon's thread pool, and returns the
+    /// play with the highest value, together with that value.
+    ///
+    /// The plays are the [`legal_plays()`](Board::legal_plays) of each card, and `eval_fn`
+    /// gets their [effects](Board::calculate). Of several plays with the same value, the first
+    /// one in that order is returned, so the result doesn't depend on the number of threads.
+    /// Returns `None` if none of the cards can be played.
+    pub fn par_best_play<T, F>(
+        &self,
+        hand: impl IntoIterator<Item = Card>,
+        eval_fn: F,
+    ) -> Option<(CardToPlay, T)>
+    where
+        T: Ord + Send,
+        F: Fn(CardToPlay, &CalculatedEffects<'_>) -> T + Sync,
+    {
+        let plays: Vec<CardToPlay> = hand
+            .into_iter()
+            .flat_map(|card| self.legal_plays(card))
+            .collect();
+        plays
+            .into_par_iter()
+            .enumerate()
+            .map(|(idx, ctp)| {
+                let effects = self.calculate(ctp).expect("Legal plays can be calculated");
+                (eval_fn(ctp, &effects), Reverse(idx), ctp)
+            })
+            .max_by(|(value_a, idx_a, _), (value_b, idx_b, _)| {
+                value_a.cmp(value_b).then(idx_a.cmp(idx_b))
+            })
+            .map(|(value, _, ctp)| (ctp, value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use quickcheck::quickcheck;
+
+    use super::*;
+    use crate::GameInProgress;
+
+    quickcheck! {
+        fn par_best_play_agrees_with_sequential(game: GameInProgress) -> bool {
+            let eval = |_: CardToPlay, effects: &CalculatedEffects<'_>| {
+                (effects.cards_won.len(), effects.combo)
+            };
+            let expected = game.player.hand.iter().flat_map(|card| game.board.calculate_all(card)).fold(
+                None,
+                |best: Option<(CardToPlay, (u32, bool))>, (ctp, effects)| {
+                    let value = eval(ctp, &effects);
+                    match best {
+                        Some((_, best_value)) if best_value >= value => best,
+                        _ => Some((ctp, value)),
+                    }
+                },
+            );
+            game.board.par_best_play(game.player.hand.iter(), eval) == expected
+        }
+    }
+}
@@ -0,0 +1,16 @@
+use super::BitBoard;
+
+/// Which placements [`Board::locations_for_card_with_policy()`](super::Board::locations_for_card_with_policy)
+/// returns, so that bots can skip the ones they aren't interested in without calculating them.
+///
+/// The default allows every placement, like [`Board::locations_for_card()`](super::Board::locations_for_card).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PlacementPolicy {
+    /// Only placements on top of another card, which continue the turn with a combo.
+    pub combo_only: bool,
+    /// Skip placements of jacks and queens on top of another card where their ability would
+    /// flip a face-up card of their own suit, which could otherwise be part of a line.
+    pub avoid_own_suit_flips: bool,
+    /// Only placements inside this area.
+    pub mask: Option<BitBoard>,
+}
@@ -0,0 +1,332 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{BitBoard, Board, CardToPlay, Field};
+
+/// One of the eight symmetries of a square, i.e. a rotation or a reflection.
+///
+/// The rules of the game don't change under these (lines, and the neighbors flipped by
+/// jacks and queens, are mapped onto each other), so boards that only differ by a
+/// symmetry and a translation are equivalent.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Symmetry {
+    Identity,
+    /// Maps `(i, j)` to `(j, -i)`.
+    Rotate90,
+    /// Maps `(i, j)` to `(-i, -j)`.
+    Rotate180,
+    /// Maps `(i, j)` to `(-j, i)`.
+    Rotate270,
+    /// Maps `(i, j)` to `(-i, j)`.
+    ReflectI,
+    /// Maps `(i, j)` to `(i, -j)`.
+    ReflectJ,
+    /// Maps `(i, j)` to `(j, i)`.
+    Transpose,
+    /// Maps `(i, j)` to `(-j, -i)`.
+    AntiTranspose,
+}
+
+impl Symmetry {
+    pub const ALL: [Symmetry; 8] = [
+        Symmetry::Identity,
+        Symmetry::Rotate90,
+        Symmetry::Rotate180,
+        Symmetry::Rotate270,
+        Symmetry::ReflectI,
+        Symmetry::ReflectJ,
+        Symmetry::Transpose,
+        Symmetry::AntiTranspose,
+    ];
+
+    pub fn apply(self, i: i8, j: i8) -> (i8, i8) {
+        match self {
+            Symmetry::Identity => (i, j),
+            Symmetry::Rotate90 => (j, -i),
+            Symmetry::Rotate180 => (-i, -j),
+            Symmetry::Rotate270 => (-j, i),
+            Symmetry::ReflectI => (-i, j),
+            Symmetry::ReflectJ => (i, -j),
+            Symmetry::Transpose => (j, i),
+            Symmetry::AntiTranspose => (-j, -i),
+        }
+    }
+
+    /// The symmetry that undoes this one.
+    pub fn inverse(self) -> Self {
+        match self {
+            Symmetry::Rotate90 => Symmetry::Rotate270,
+            Symmetry::Rotate270 => Symmetry::Rotate90,
+            // All others are their own inverse
+            other => other,
+        }
+    }
+
+    /// The symmetry that is equivalent to first applying `self`, then `other`.
+    pub fn then(self, other: Symmetry) -> Self {
+        // A symmetry is determined by where it maps the two unit vectors
+        let (i1, j1) = self.apply(1, 0);
+        let (i2, j2) = self.apply(0, 1);
+        let expected = (other.apply(i1, j1), other.apply(i2, j2));
+        Symmetry::ALL
+            .into_iter()
+            .find(|s| (s.apply(1, 0), s.apply(0, 1)) == expected)
+            .expect("Symmetries are closed under composition")
+    }
+}
+
+/// A [`Symmetry`] followed by a translation by `offset`.
+///
+/// Use [`apply()`](Transform::apply) to map anything that is [`Transformable`], e.g. a
+/// [`Board`] or a [`CardToPlay`], and [`inverse()`](Transform::inverse) to map it back.
+///
+/// ```
+/// use gomori::{card, CardToPlay, Symmetry, Transform};
+/// let transform = Transform {
+///     symmetry: Symmetry::Rotate90,
+///     offset: (2, 0),
+/// };
+/// assert_eq!(transform.apply(&(1, 1)), (3, -1));
+/// let ctp = CardToPlay { card: card!("K♥"), i: 0, j: 1, target_field_for_king_ability: Some((1, 1)) };
+/// let transformed = transform.apply(&ctp);
+/// assert_eq!(transformed.target_field_for_king_ability, Some((3, -1)));
+/// assert_eq!(transform.inverse().apply(&transformed), ctp);
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Transform {
+    pub symmetry: Symmetry,
+    pub offset: (i8, i8),
+}
+
+impl Transform {
+    pub const IDENTITY: Transform = Transform {
+        symmetry: Symmetry::Identity,
+        offset: (0, 0),
+    };
+
+    pub fn translation(di: i8, dj: i8) -> Self {
+        Self {
+            symmetry: Symmetry::Identity,
+            offset: (di, dj),
+        }
+    }
+
+    pub fn apply<T: Transformable>(&self, value: &T) -> T {
+        value.transformed_by(self)
+    }
+
+    /// The transform that undoes this one.
+    pub fn inverse(&self) -> Self {
+        let symmetry = self.symmetry.inverse();
+        Self {
+            symmetry,
+            offset: symmetry.apply(-self.offset.0, -self.offset.1),
+        }
+    }
+
+    /// The transform that is equivalent to first applying `self`, then `other`.
+    pub fn then(&self, other: &Transform) -> Self {
+        Self {
+            symmetry: self.symmetry.then(other.symmetry),
+            offset: other.apply(&self.offset),
+        }
+    }
+}
+
+/// Something with coordinates that a [`Transform`] can be applied to.
+pub trait Transformable {
+    /// Prefer calling [`Transform::apply()`], which reads more naturally.
+    fn transformed_by(&self, transform: &Transform) -> Self;
+}
+
+/// A coordinate pair `(i, j)`.
+impl Transformable for (i8, i8) {
+    fn transformed_by(&self, transform: &Transform) -> Self {
+        let (i, j) = transform.symmetry.apply(self.0, self.1);
+        (i + transform.offset.0, j + transform.offset.1)
+    }
+}
+
+impl Transformable for Field {
+    fn transformed_by(&self, transform: &Transform) -> Self {
+        let (i, j) = transform.apply(&(self.i, self.j));
+        Field {
+            i,
+            j,
+            top_card: self.top_card,
+            hidden_cards: self.hidden_cards.clone(),
+        }
+    }
+}
+
+/// Transforms both the coordinates and the target of the king's ability, if any.
+impl Transformable for CardToPlay {
+    fn transformed_by(&self, transform: &Transform) -> Self {
+        let (i, j) = transform.apply(&(self.i, self.j));
+        CardToPlay {
+            card: self.card,
+            i,
+            j,
+            target_field_for_king_ability: self
+                .target_field_for_king_ability
+                .map(|target| transform.apply(&target)),
+        }
+    }
+}
+
+impl Transformable for Board {
+    fn transformed_by(&self, transform: &Transform) -> Self {
+        // The bitboards are centered on the transformed center, like those of the BitBoard
+        // impl below
+        let fields = self
+            .fields
+            .iter()
+            .map(|&(i, j, field)| {
+                let (i, j) = transform.apply(&(i, j));
+                (i, j, field)
+            })
+            .collect();
+        let center = transform.apply(&self.bitboards_center);
+        let mut board = Board::from_valid_fields_list_centered_at(fields, center, self.rules);
+        board.history = self.history.as_ref().map(|history| {
+            history
+                .iter()
+                .map(|ctp| ctp.transformed_by(transform))
+                .collect()
+        });
+        board.history_start = self.history_start.as_ref().map(|start| {
+            start
+                .iter()
+                .map(|&(i, j, field)| {
+                    let (i, j) = transform.apply(&(i, j));
+                    (i, j, field)
+                })
+                .collect()
+        });
+        board
+    }
+}
+
+/// The result can be combined with the `BitBoard`s of the transformed [`Board`] that
+/// this `BitBoard` originates from.
+impl Transformable for BitBoard {
+    fn transformed_by(&self, transform: &Transform) -> Self {
+        let center = transform.apply(&self.center());
+        self.into_iter()
+            .fold(BitBoard::empty_board_centered_at(center), |bb, (i, j)| {
+                let (i, j) = transform.apply(&(i, j));
+                bb.insert(i, j)
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{card, field};
+
+    fn all_transforms() -> impl Iterator<Item = Transform> {
+        Symmetry::ALL.into_iter().map(|symmetry| Transform {
+            symmetry,
+            offset: (3, -2),
+        })
+    }
+
+    #[test]
+    fn inverse_and_composition() {
+        for transform in all_transforms() {
+            assert_eq!(
+                transform.inverse().apply(&transform.apply(&(2, -5))),
+                (2, -5)
+            );
+            assert_eq!(transform.then(&transform.inverse()), Transform::IDENTITY);
+            for other in all_transforms() {
+                let composed = transform.then(&other);
+                assert_eq!(
+                    composed.apply(&(1, 4)),
+                    other.apply(&transform.apply(&(1, 4)))
+                );
+            }
+        }
+    }
+
+    /// Playing a transformed card on a transformed board has the same effects.
+    #[test]
+    fn rules_are_invariant() {
+        let board = Board::new(&[
+            field(0, 0, card!("7♥")),
+            field(0, 1, card!("3♠")),
+            field(1, 1, card!("9♥")),
+            field(2, 2, card!("T♥")),
+            field(3, 3, card!("4♥")),
+            field(1, 0, card!("2♣")),
+        ]);
+        let ctp = CardToPlay {
+            card: card!("J♥"),
+            i: 0,
+            j: 0,
+            target_field_for_king_ability: None,
+        };
+        let effects = board.calculate(ctp).unwrap();
+        assert!(!effects.cards_won.is_empty());
+        let board_after = board.calculate(ctp).unwrap().execute();
+        for transform in all_transforms() {
+            let transformed_board = transform.apply(&board);
+            assert_eq!(transformed_board.hearts(), transform.apply(&board.hearts()));
+            let transformed_effects = transformed_board.calculate(transform.apply(&ctp)).unwrap();
+            assert_eq!(transformed_effects.cards_won, effects.cards_won);
+            assert_eq!(transformed_effects.combo, effects.combo);
+            let fields: Vec<_> = board_after
+                .to_fields_vec()
+                .iter()
+                .map(|f| transform.apply(f))
+                .collect();
+            assert_eq!(
+                Board::new(&fields).to_fields_vec(),
+                transformed_effects.execute().to_fields_vec()
+            );
+        }
+    }
+
+    /// Boards that were played on are centered on the last card played, not on their first
+    /// field, and their transformed bitboards still fit together.
+    #[test]
+    fn played_boards_keep_their_center() {
+        let mut board = Board::new(&[field(0, 0, card!("7♥"))]);
+        for ctp in [
+            CardToPlay {
+                card: card!("8♥"),
+                i: 0,
+                j: 1,
+                target_field_for_king_ability: None,
+            },
+            CardToPlay {
+                card: card!("3♠"),
+                i: 1,
+                j: 2,
+                target_field_for_king_ability: None,
+            },
+            CardToPlay {
+                card: card!("2♣"),
+                i: 2,
+                j: 3,
+                target_field_for_king_ability: None,
+            },
+        ] {
+            board = board.play_card(ctp).unwrap();
+        }
+        let bitboard = board.hearts();
+        for transform in all_transforms() {
+            let transformed_board = transform.apply(&board);
+            let transformed_bitboard = transform.apply(&bitboard);
+            assert_eq!(transformed_board.hearts(), transformed_bitboard);
+            assert_eq!(
+                transformed_board.hearts() | transformed_board.spades(),
+                transformed_bitboard | transform.apply(&board.spades())
+            );
+            for &(i, j, field) in board.iter() {
+                let (ti, tj) = transform.apply(&(i, j));
+                assert_eq!(transformed_board.get(ti, tj), Some(field));
+            }
+        }
+    }
+}
@@ -0,0 +1,154 @@
+//! Serde formats for [`Card`]s and [`CardsSet`]s other than the one of the protocol, for
+//! use with `#[serde(with = "...")]`.
+//!
+//! The protocol writes cards with the Unicode suit symbols, like `{"suit":"♥","rank":"10"}`.
+//! Training data and compact recordings are smaller with indices, and config files are
+//! easier to write with ASCII:
+//!
+//! ```
+//! use gomori::{card, Card, CardsSet};
+//! use serde::{Deserialize, Serialize};
+//!
+//! #[derive(Serialize, Deserialize)]
+//! struct Config {
+//!     #[serde(with = "gomori::card::as_ascii")]
+//!     favorite: Card,
+//!     #[serde(with = "gomori::card::set_as_indices")]
+//!     avoided: CardsSet,
+//! }
+//!
+//! let config = Config {
+//!     favorite: card!("T♥"),
+//!     avoided: CardsSet::from_iter([card!("2♦"), card!("A♣")]),
+//! };
+//! let json = serde_json::to_string(&config).unwrap();
+//! assert_eq!(json, r#"{"favorite":"TH","avoided":[0,51]}"#);
+//! ```
+
+use serde::de::Error;
+use serde::{Deserialize, Deserializer, Serializer};
+
+use crate::prelude::*;
+use crate::{Card, CardsSet};
+
+/// A card as its [index](Card::index), e.g. `33` for the ten of hearts.
+pub mod as_index {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(card: &Card, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u8(card.index())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Card, D::Error> {
+        card_from_index(u8::deserialize(deserializer)?)
+    }
+}
+
+/// A card as [two ASCII characters](Card::to_ascii), e.g. `"TH"` for the ten of hearts.
+///
+/// Deserializing also accepts the Unicode suit symbols and lowercase suit letters, like the
+/// [`FromStr`](core::str::FromStr) instance of [`Card`].
+pub mod as_ascii {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(card: &Card, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&card.to_ascii())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Card, D::Error> {
+        card_from_str(&String::deserialize(deserializer)?)
+    }
+}
+
+/// A set of cards as a list of their [indices](Card::index), in ascending order.
+pub mod set_as_indices {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(cards: &CardsSet, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(cards.into_iter().map(Card::index))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<CardsSet, D::Error> {
+        Vec::<u8>::deserialize(deserializer)?
+            .into_iter()
+            .map(card_from_index)
+            .collect()
+    }
+}
+
+/// A set of cards as a list of [two ASCII characters](Card::to_ascii) per card, e.g.
+/// `["TH", "AC"]`.
+pub mod set_as_ascii {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(cards: &CardsSet, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(cards.into_iter().map(|card| card.to_ascii()))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<CardsSet, D::Error> {
+        Vec::<String>::deserialize(deserializer)?
+            .iter()
+            .map(|text| card_from_str(text))
+            .collect()
+    }
+}
+
+fn card_from_index<E: Error>(index: u8) -> Result<Card, E> {
+    Card::try_from_index(index).ok_or_else(|| {
+        E::custom(format!(
+            "Invalid card index {}, must be less than 52",
+            index
+        ))
+    })
+}
+
+fn card_from_str<E: Error>(text: &str) -> Result<Card, E> {
+    text.parse()
+        .map_err(|err| E::custom(format!("Invalid card '{}': {}", text, err)))
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Serialize;
+
+    use super::*;
+    use crate::card;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Formats {
+        #[serde(with = "as_index")]
+        index: Card,
+        #[serde(with = "as_ascii")]
+        ascii: Card,
+        #[serde(with = "set_as_indices")]
+        indices: CardsSet,
+        #[serde(with = "set_as_ascii")]
+        ascii_set: CardsSet,
+    }
+
+    #[test]
+    fn formats_round_trip() {
+        let cards = CardsSet::from_iter([card!("2♦"), card!("T♥"), card!("A♣")]);
+        let formats = Formats {
+            index: card!("T♥"),
+            ascii: card!("Q♠"),
+            indices: cards,
+            ascii_set: cards,
+        };
+        let json = serde_json::to_string(&formats).unwrap();
+        assert_eq!(
+            json,
+            r#"{"index":33,"ascii":"QS","indices":[0,33,51],"ascii_set":["2D","TH","AC"]}"#
+        );
+        assert_eq!(serde_json::from_str::<Formats>(&json).unwrap(), formats);
+
+        let lowercase = r#"{"index":33,"ascii":"qs","indices":[],"ascii_set":["Th"]}"#;
+        let err = serde_json::from_str::<Formats>(lowercase).unwrap_err();
+        assert!(err.to_string().contains("Invalid card 'qs'"));
+        let invalid_index = r#"{"index":52,"ascii":"QS","indices":[],"ascii_set":[]}"#;
+        assert!(serde_json::from_str::<Formats>(invalid_index).is_err());
+        let unicode = r#"{"index":33,"ascii":"Q♠","indices":[],"ascii_set":["Th"]}"#;
+        let formats = serde_json::from_str::<Formats>(unicode).unwrap();
+        assert_eq!(formats.ascii_set, CardsSet::from_iter([card!("T♥")]));
+    }
+}
@@ -0,0 +1,130 @@
+use crate::{Board, CardToPlay, CardsSet, Color, Deck, IllegalCardPlayed, Rules};
+
+/// Information about the cards in the game, derived from
+/// observing all played cards.
+///
+/// `gomori_bot_utils` can update this automatically, through its `HasCardCounter`
+/// trait and `CardCountingWrapper`.
+#[derive(Clone, Copy, Debug)]
+pub struct CardCounter {
+    /// Cards in our draw pile.
+    pub draw_pile: CardsSet,
+    /// Cards in the opponent's draw pile + hand.
+    /// We don't have any information to distinguish the two.
+    ///
+    /// With more than one opponent, these are the cards of all opponents, and in a
+    /// game with three players, also those of the suit that nobody plays.
+    pub available_cards_opponent: CardsSet,
+    /// Cards won by us.
+    pub cards_won_self: CardsSet,
+    /// Cards won by our opponent, or by any of the opponents.
+    pub cards_won_opponent: CardsSet,
+    /// The number of other players in the game.
+    pub num_opponents: usize,
+}
+
+impl CardCounter {
+    /// The state at the start of a two-player game, before any cards are known.
+    pub fn new(color: Color) -> Self {
+        Self::for_deck(Deck::Color(color), 2)
+    }
+
+    /// The state at the start of a game with the given number of players, in which we
+    /// play with `deck`, before any cards are known.
+    pub fn for_deck(deck: Deck, num_players: usize) -> Self {
+        Self::for_deck_with_rules(deck, num_players, Rules::STANDARD)
+    }
+
+    /// Like [`for_deck()`](Self::for_deck), but in a variant of the game, where only the
+    /// cards of [`Rules::cards()`] are dealt.
+    pub fn for_deck_with_rules(deck: Deck, num_players: usize, rules: Rules) -> Self {
+        let draw_pile = CardsSet::from_iter(deck.cards_with_rules(rules));
+        Self {
+            draw_pile,
+            available_cards_opponent: rules.cards().difference(draw_pile),
+            cards_won_self: CardsSet::new(),
+            cards_won_opponent: CardsSet::new(),
+            num_opponents: num_players - 1,
+        }
+    }
+
+    /// Updates the counter with the cards that the opponent played in a turn, starting from
+    /// `board_before`.
+    ///
+    /// The played cards are no longer available to the opponent, and the cards won with
+    /// them are added to the opponent's won cards. Returns the board after the turn, which
+    /// shows which cards were turned face down where. If one of the cards can't be played,
+    /// the counter is not changed.
+    pub fn observe_opponent_turn(
+        &mut self,
+        board_before: &Board,
+        turn: &[CardToPlay],
+    ) -> Result<Board, IllegalCardPlayed> {
+        let mut board = board_before.clone();
+        let mut cards_played = CardsSet::new();
+        let mut cards_won = CardsSet::new();
+        for &ctp in turn {
+            let effects = board.calculate(ctp)?;
+            cards_played = cards_played.insert(ctp.card);
+            cards_won |= effects.cards_won;
+            board = effects.execute();
+        }
+        self.available_cards_opponent = self
+            .available_cards_opponent
+            .difference(cards_played | cards_won);
+        self.cards_won_opponent |= cards_won;
+        Ok(board)
+    }
+}
+
+impl Default for CardCounter {
+    fn default() -> Self {
+        CardCounter {
+            draw_pile: CardsSet::new(),
+            available_cards_opponent: CardsSet::new(),
+            cards_won_self: CardsSet::new(),
+            cards_won_opponent: CardsSet::new(),
+            num_opponents: 1,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{card, ctp, field, BLACK_CARDS_SET, RED_CARDS_SET};
+
+    #[test]
+    fn observe_opponent_turn_removes_played_cards() {
+        // We are red, so the opponent plays black cards
+        let board = Board::new(&[
+            field(0, 0, card!("2♣")),
+            field(0, 1, card!("3♣")),
+            field(0, 2, card!("4♣")),
+            field(1, 1, card!("5♥")),
+        ]);
+        let mut counter = CardCounter::new(Color::Red);
+        // A combo on the heart, then the line of clubs is completed
+        let turn = [ctp(card!("5♠"), 1, 1), ctp(card!("6♣"), 0, 3)];
+        // A king needs a target for its ability
+        let illegal = [ctp(card!("5♠"), 1, 1), ctp(card!("K♣"), 0, 0)];
+        assert!(counter.observe_opponent_turn(&board, &illegal).is_err());
+        assert_eq!(counter.available_cards_opponent, BLACK_CARDS_SET);
+
+        let board_after = counter.observe_opponent_turn(&board, &turn).unwrap();
+        let field = board_after.get(1, 1).unwrap();
+        assert_eq!(field.top_card(), Some(card!("5♠")));
+        assert_eq!(field.hidden_cards(), CardsSet::from_iter([card!("5♥")]));
+        assert!(counter.cards_won_opponent.contains(card!("2♣")));
+        let played = CardsSet::from_iter([card!("5♠"), card!("6♣")]);
+        assert!(counter
+            .available_cards_opponent
+            .is_disjoint(played | counter.cards_won_opponent));
+        assert_eq!(
+            counter.available_cards_opponent.len(),
+            BLACK_CARDS_SET.len() - 5
+        );
+        assert_eq!(counter.draw_pile, RED_CARDS_SET);
+        assert_eq!(counter.cards_won_self, CardsSet::new());
+    }
+}
@@ -1,18 +1,21 @@
-use std::str::FromStr;
+use core::str::FromStr;
 
 use serde::{Deserialize, Serialize};
 
+use crate::prelude::*;
+use crate::Color;
+
 /// A playing card in a standard 52-card game.
-#[cfg_attr(feature = "python", pyo3::pyclass(get_all, set_all))]
-#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[cfg_attr(feature = "python", pyo3::pyclass(module = "gomori", get_all, set_all))]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 pub struct Card {
     pub suit: Suit,
     pub rank: Rank,
 }
 
 /// The suit of a [card](Card).
-#[cfg_attr(feature = "python", pyo3::pyclass)]
-#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[cfg_attr(feature = "python", pyo3::pyclass(module = "gomori"))]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 #[repr(u8)]
 pub enum Suit {
     #[serde(rename = "♦")]
@@ -26,8 +29,8 @@ pub enum Suit {
 }
 
 /// The rank of a [card](Card).
-#[cfg_attr(feature = "python", pyo3::pyclass)]
-#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[cfg_attr(feature = "python", pyo3::pyclass(module = "gomori"))]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 #[repr(u8)]
 pub enum Rank {
     #[serde(rename = "2")]
@@ -58,14 +61,83 @@ pub enum Rank {
     Ace,
 }
 
-impl std::fmt::Display for Card {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl core::fmt::Display for Card {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         write!(f, "{}", self.unicode_char())
     }
 }
 
+impl Suit {
+    /// Whether this is one of the red or the black suits.
+    pub fn color(self) -> Color {
+        match self {
+            Suit::Diamond | Suit::Heart => Color::Red,
+            Suit::Spade | Suit::Club => Color::Black,
+        }
+    }
+}
+
+/// The suit symbol, as in the JSON protocol.
+impl core::fmt::Display for Suit {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let symbol = match self {
+            Suit::Diamond => "♦",
+            Suit::Heart => "♥",
+            Suit::Spade => "♠",
+            Suit::Club => "♣",
+        };
+        f.write_str(symbol)
+    }
+}
+
+impl Rank {
+    /// All ranks, from two to ace.
+    pub const ALL: [Rank; 13] = [
+        Rank::Two,
+        Rank::Three,
+        Rank::Four,
+        Rank::Five,
+        Rank::Six,
+        Rank::Seven,
+        Rank::Eight,
+        Rank::Nine,
+        Rank::Ten,
+        Rank::Jack,
+        Rank::Queen,
+        Rank::King,
+        Rank::Ace,
+    ];
+}
+
+/// The rank as in the JSON protocol, i.e. `10` and not `T` for tens.
+impl core::fmt::Display for Rank {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let symbol = match self {
+            Rank::Two => "2",
+            Rank::Three => "3",
+            Rank::Four => "4",
+            Rank::Five => "5",
+            Rank::Six => "6",
+            Rank::Seven => "7",
+            Rank::Eight => "8",
+            Rank::Nine => "9",
+            Rank::Ten => "10",
+            Rank::Jack => "J",
+            Rank::Queen => "Q",
+            Rank::King => "K",
+            Rank::Ace => "A",
+        };
+        f.write_str(symbol)
+    }
+}
+
 // !!!!!! NOTE: Keep in sync with pymethods impl block !!!!!!
 impl Card {
+    /// The color of the card's suit.
+    pub fn color(&self) -> Color {
+        self.suit.color()
+    }
+
     pub fn can_be_placed_on(&self, other: Card) -> bool {
         self.rank == other.rank
             || match self.rank {
@@ -103,6 +175,36 @@ impl Card {
         char::from_u32(codepoint).unwrap()
     }
 
+    /// The card's index, a number less than 52 that orders the cards by rank, then suit.
+    ///
+    /// This is how cards are encoded for machine learning, see
+    /// [`encode_card_to_play()`](crate::encode_card_to_play), and in the
+    /// [`card::as_index`](crate::card::as_index) serde format.
+    pub fn index(self) -> u8 {
+        self.to_index()
+    }
+
+    /// The card with this [index](Card::index), if it is less than 52.
+    pub fn try_from_index(index: u8) -> Option<Self> {
+        (index < 52).then(|| Self::from_index(index))
+    }
+
+    /// The card as two ASCII characters, e.g. `TH` for the ten of hearts, which
+    /// [`FromStr`] accepts too.
+    pub fn to_ascii(&self) -> String {
+        let rank = match self.rank {
+            Rank::Ten => 'T',
+            rank => rank.to_string().chars().next().unwrap(),
+        };
+        let suit = match self.suit {
+            Suit::Diamond => 'D',
+            Suit::Heart => 'H',
+            Suit::Spade => 'S',
+            Suit::Club => 'C',
+        };
+        format!("{}{}", rank, suit)
+    }
+
     // INTERNAL - maps a card onto its "index", a number less than 52
     #[inline]
     pub(crate) fn to_index(self) -> u8 {
@@ -120,8 +222,8 @@ impl Card {
         // suit are just cast to their underlying repr and back, which is fine.
         unsafe {
             Card {
-                rank: std::mem::transmute::<u8, Rank>(bits >> 2),
-                suit: std::mem::transmute::<u8, Suit>(bits & 3),
+                rank: core::mem::transmute::<u8, Rank>(bits >> 2),
+                suit: core::mem::transmute::<u8, Suit>(bits & 3),
             }
         }
     }
@@ -136,6 +238,21 @@ pub enum CardFromStrErr {
     InvalidSuit,
 }
 
+impl core::error::Error for CardFromStrErr {}
+
+impl core::fmt::Display for CardFromStrErr {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            CardFromStrErr::LessThanTwoChars => write!(f, "A card needs two characters"),
+            CardFromStrErr::MoreThanTwoChars => write!(f, "A card has only two characters"),
+            CardFromStrErr::InvalidRank => write!(f, "Invalid rank, expected one of 2-9TJQKA"),
+            CardFromStrErr::InvalidSuit => {
+                write!(f, "Invalid suit, expected one of ♦♥♠♣ or DHSC")
+            }
+        }
+    }
+}
+
 impl FromStr for Card {
     type Err = CardFromStrErr;
 
@@ -163,10 +280,10 @@ impl FromStr for Card {
             _ => return Err(CardFromStrErr::InvalidRank),
         };
         let suit = match suit_char {
-            '♦' => Suit::Diamond,
-            '♥' => Suit::Heart,
-            '♠' => Suit::Spade,
-            '♣' => Suit::Club,
+            '♦' | 'D' | 'd' => Suit::Diamond,
+            '♥' | 'H' | 'h' => Suit::Heart,
+            '♠' | 'S' | 's' => Suit::Spade,
+            '♣' | 'C' | 'c' => Suit::Club,
             _ => return Err(CardFromStrErr::InvalidSuit),
         };
         Ok(Card { rank, suit })
@@ -176,7 +293,8 @@ impl FromStr for Card {
 /// Shorthand for creating cards from a two-character string.
 ///
 /// The first character is the [rank](Rank) (note: 10 is `T`), the second is
-/// the [suit](Suit) as a unicode character (♦, ♥, ♠, or ♣).
+/// the [suit](Suit) as a unicode character (♦, ♥, ♠, or ♣), or as an ASCII letter
+/// (`D`, `H`, `S` or `C`, also in lowercase).
 ///
 /// This macro is just calling the [`FromStr`] instance of [`Card`].
 /// ```
@@ -185,11 +303,12 @@ impl FromStr for Card {
 ///     card!("T♥"),
 ///     Card { rank: Rank::Ten, suit: Suit::Heart }
 /// );
+/// assert_eq!(card!("TH"), card!("T♥"));
 /// ```
 #[macro_export]
 macro_rules! card {
     ($rs:literal) => {
-        <$crate::Card as std::str::FromStr>::from_str($rs)
+        <$crate::Card as core::str::FromStr>::from_str($rs)
             .expect("Invalid card code given to card! macro")
     };
 }
@@ -413,10 +532,24 @@ pub static BLACK_CARDS: [Card; 26] = [
 
 #[cfg(feature = "python")]
 mod python {
-    use pyo3::pymethods;
+    use core::hash::{Hash, Hasher};
+    use std::collections::hash_map::DefaultHasher;
+
+    use pyo3::pyclass::CompareOp;
+    use pyo3::{pymethods, PyObject, PyResult, Python};
 
     use super::*;
 
+    // Enum variants are pickled as a lookup of the variant on their type.
+    fn reduce_variant(
+        py: Python,
+        ty: &pyo3::types::PyType,
+        variant: String,
+    ) -> PyResult<(PyObject, (PyObject, String))> {
+        let getattr = py.import("builtins")?.getattr("getattr")?;
+        Ok((getattr.into(), (ty.into(), variant)))
+    }
+
     #[pymethods]
     impl Card {
         #[new]
@@ -433,6 +566,26 @@ mod python {
             self.to_string()
         }
 
+        fn __richcmp__(&self, other: &Self, op: CompareOp) -> bool {
+            op.matches(self.cmp(other))
+        }
+
+        fn __hash__(&self) -> u64 {
+            let mut hasher = DefaultHasher::new();
+            self.hash(&mut hasher);
+            hasher.finish()
+        }
+
+        fn __reduce__(&self, py: Python) -> (PyObject, (Rank, Suit)) {
+            (py.get_type::<Card>().into(), (self.rank, self.suit))
+        }
+
+        #[pyo3(name = "color")]
+        fn py_color(&self) -> Color {
+            self.color()
+        }
+
+        #[pyo3(name = "can_be_placed_on")]
         fn py_can_be_placed_on(&self, other: Card) -> bool {
             self.can_be_placed_on(other)
         }
@@ -444,13 +597,21 @@ mod python {
             format!("Suit.{:?}", self)
         }
 
-        fn __str__(&self) -> &'static str {
-            match self {
-                Suit::Diamond => "♦",
-                Suit::Heart => "♥",
-                Suit::Spade => "♠",
-                Suit::Club => "♣",
-            }
+        fn __hash__(&self) -> u64 {
+            *self as u64
+        }
+
+        fn __reduce__(&self, py: Python) -> PyResult<(PyObject, (PyObject, String))> {
+            reduce_variant(py, py.get_type::<Suit>(), format!("{:?}", self))
+        }
+
+        fn __str__(&self) -> String {
+            self.to_string()
+        }
+
+        #[pyo3(name = "color")]
+        fn py_color(&self) -> Color {
+            self.color()
         }
     }
 
@@ -460,22 +621,16 @@ mod python {
             format!("Rank.{:?}", self)
         }
 
-        fn __str__(&self) -> &'static str {
-            match self {
-                Rank::Two => "2",
-                Rank::Three => "3",
-                Rank::Four => "4",
-                Rank::Five => "5",
-                Rank::Six => "6",
-                Rank::Seven => "7",
-                Rank::Eight => "8",
-                Rank::Nine => "9",
-                Rank::Ten => "10",
-                Rank::Jack => "J",
-                Rank::Queen => "Q",
-                Rank::King => "K",
-                Rank::Ace => "A",
-            }
+        fn __hash__(&self) -> u64 {
+            *self as u64
+        }
+
+        fn __reduce__(&self, py: Python) -> PyResult<(PyObject, (PyObject, String))> {
+            reduce_variant(py, py.get_type::<Rank>(), format!("{:?}", self))
+        }
+
+        fn __str__(&self) -> String {
+            self.to_string()
         }
     }
 }
@@ -506,4 +661,14 @@ mod tests {
         assert_eq!(Card::from_index(CARD_2.to_index()), CARD_2);
         assert_eq!(Card::from_index(CARD_3.to_index()), CARD_3);
     }
+
+    #[test]
+    fn ascii_suits_are_parsed() {
+        assert_eq!("2d".parse::<Card>().unwrap(), CARD_1);
+        assert_eq!("AC".parse::<Card>().unwrap(), CARD_2);
+        assert_eq!("Q♥".parse::<Card>().unwrap(), CARD_3);
+        assert_eq!(CARD_3.to_ascii().parse::<Card>().unwrap(), CARD_3);
+        assert!("QX".parse::<Card>().is_err());
+        assert_eq!(Card::try_from_index(52), None);
+    }
 }
@@ -1,10 +1,14 @@
-use std::iter::FusedIterator;
+use core::iter::FusedIterator;
 
-use crate::Card;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::prelude::*;
+use crate::{Card, Color, Suit};
 
 /// A compact set of [`Card`]s.
 ///
-/// Allows intersection/union/xor with other such sets via bitwise ops.
+/// Allows intersection/union/xor with other such sets via bitwise ops, and has
+/// methods for the other common set operations, like [`difference`](Self::difference).
 /// Also implements [`IntoIterator`], so it can be converted into e.g.
 /// a vector with `Vec::from_iter(cards_set)`.
 ///
@@ -21,10 +25,10 @@ use crate::Card;
 /// # Note on immutability
 ///
 /// This is an immutable type, so its "mutating" methods return a
-/// new value instead of really mutating in-place (except for `std::ops::BitXxxAssign` trait methods).
+/// new value instead of really mutating in-place (except for `core::ops::BitXxxAssign` trait methods).
 /// It is also [`Copy`], so a value is not consumed by methods with `self` receiver.
-#[cfg_attr(feature = "python", pyo3::pyclass)]
-#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "python", pyo3::pyclass(module = "gomori"))]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
 pub struct CardsSet {
     // Only the low 52 bits are used.
     pub(crate) bits: u64,
@@ -32,6 +36,9 @@ pub struct CardsSet {
 
 const VALID_BITS: u64 = 0b1111111111111111111111111111111111111111111111111111u64;
 
+// The bits of all diamonds. Shifting them by a suit's index gives that suit's bits.
+const DIAMOND_BITS: u64 = 0x1111111111111;
+
 /// Equal to `CardsSet::from_iter(RED_CARDS)`.
 pub const RED_CARDS_SET: CardsSet = CardsSet {
     bits: 0x3333333333333,
@@ -49,6 +56,14 @@ impl CardsSet {
         Self { bits: 0 }
     }
 
+    /// The 26 cards of this color, i.e. [`RED_CARDS_SET`] or [`BLACK_CARDS_SET`].
+    pub fn of_color(color: Color) -> Self {
+        match color {
+            Color::Black => BLACK_CARDS_SET,
+            Color::Red => RED_CARDS_SET,
+        }
+    }
+
     pub fn len(self) -> u32 {
         self.bits.count_ones()
     }
@@ -74,9 +89,53 @@ impl CardsSet {
             bits: self.bits & !(1u64 << card.to_index()),
         }
     }
+
+    /// The cards in this set that are not in the other set.
+    ///
+    /// Equivalent to `self & !other`.
+    #[must_use]
+    pub fn difference(self, other: CardsSet) -> Self {
+        Self {
+            bits: self.bits & !other.bits,
+        }
+    }
+
+    /// Whether every card in this set is also in the other set.
+    pub fn is_subset(self, other: CardsSet) -> bool {
+        self.bits & !other.bits == 0
+    }
+
+    /// Whether every card in the other set is also in this set.
+    pub fn is_superset(self, other: CardsSet) -> bool {
+        other.is_subset(self)
+    }
+
+    /// Whether the two sets have no card in common.
+    pub fn is_disjoint(self, other: CardsSet) -> bool {
+        self.bits & other.bits == 0
+    }
+
+    /// Splits the set by suit, in the order of [`Suit`]'s variants.
+    ///
+    /// All four suits are returned, even if the set contains no card of that suit.
+    ///
+    /// ```
+    /// use gomori::{card, CardsSet, Suit};
+    /// let set = CardsSet::from_iter([card!("7♥"), card!("2♥"), card!("A♣")]);
+    /// let hearts = set.iter_suits().find(|&(suit, _)| suit == Suit::Heart).unwrap().1;
+    /// assert_eq!(Vec::from_iter(hearts), vec![card!("2♥"), card!("7♥")]);
+    /// ```
+    pub fn iter_suits(self) -> impl Iterator<Item = (Suit, CardsSet)> {
+        [Suit::Diamond, Suit::Heart, Suit::Spade, Suit::Club]
+            .into_iter()
+            .map(move |suit| {
+                let bits = self.bits & (DIAMOND_BITS << suit as u8);
+                (suit, CardsSet { bits })
+            })
+    }
 }
 
-impl std::ops::BitAnd for CardsSet {
+impl core::ops::BitAnd for CardsSet {
     type Output = Self;
 
     fn bitand(self, rhs: Self) -> Self::Output {
@@ -86,7 +145,7 @@ impl std::ops::BitAnd for CardsSet {
     }
 }
 
-impl std::ops::BitOr for CardsSet {
+impl core::ops::BitOr for CardsSet {
     type Output = Self;
 
     fn bitor(self, rhs: Self) -> Self::Output {
@@ -96,7 +155,7 @@ impl std::ops::BitOr for CardsSet {
     }
 }
 
-impl std::ops::BitXor for CardsSet {
+impl core::ops::BitXor for CardsSet {
     type Output = Self;
 
     fn bitxor(self, rhs: Self) -> Self::Output {
@@ -106,25 +165,25 @@ impl std::ops::BitXor for CardsSet {
     }
 }
 
-impl std::ops::BitAndAssign for CardsSet {
+impl core::ops::BitAndAssign for CardsSet {
     fn bitand_assign(&mut self, rhs: Self) {
         self.bits &= rhs.bits;
     }
 }
 
-impl std::ops::BitOrAssign for CardsSet {
+impl core::ops::BitOrAssign for CardsSet {
     fn bitor_assign(&mut self, rhs: Self) {
         self.bits |= rhs.bits;
     }
 }
 
-impl std::ops::BitXorAssign for CardsSet {
+impl core::ops::BitXorAssign for CardsSet {
     fn bitxor_assign(&mut self, rhs: Self) {
         self.bits ^= rhs.bits;
     }
 }
 
-impl std::ops::Not for CardsSet {
+impl core::ops::Not for CardsSet {
     type Output = Self;
 
     fn not(self) -> Self::Output {
@@ -134,6 +193,33 @@ impl std::ops::Not for CardsSet {
     }
 }
 
+/// The cards in order, separated by spaces, with the rank and suit of each card as in the
+/// JSON protocol, e.g. `2♦ 7♥ Q♠`. An empty set is an empty string.
+impl core::fmt::Display for CardsSet {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        for (idx, card) in self.into_iter().enumerate() {
+            if idx > 0 {
+                f.write_str(" ")?;
+            }
+            write!(f, "{}{}", card.rank, card.suit)?;
+        }
+        Ok(())
+    }
+}
+
+/// Serialized as a list of cards, like a `BTreeSet<Card>`.
+impl Serialize for CardsSet {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(*self)
+    }
+}
+
+impl<'de> Deserialize<'de> for CardsSet {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(Self::from_iter(Vec::<Card>::deserialize(deserializer)?))
+    }
+}
+
 impl FromIterator<Card> for CardsSet {
     fn from_iter<T: IntoIterator<Item = Card>>(iter: T) -> Self {
         let mut bits = 0;
@@ -193,10 +279,11 @@ impl FusedIterator for CardsSetIter {}
 
 #[cfg(feature = "python")]
 mod python {
-    use pyo3::pymethods;
+    use pyo3::pyclass::CompareOp;
+    use pyo3::{pymethods, PyObject, Python};
 
     use super::*;
-    use crate::Card;
+    use crate::{Card, Color};
 
     #[pymethods]
     impl CardsSet {
@@ -222,12 +309,44 @@ mod python {
             self.into_iter()
         }
 
+        fn __str__(&self) -> String {
+            self.to_string()
+        }
+
+        #[staticmethod]
+        #[pyo3(name = "of_color")]
+        fn py_of_color(color: Color) -> Self {
+            Self::of_color(color)
+        }
+
         // Needed in other __repr__ functions
         pub(crate) fn __repr__(&self) -> String {
             let card_reprs: Vec<_> = self.into_iter().map(|c| c.__repr__()).collect();
             format!("CardsSet([{}])", card_reprs.join(", "))
         }
 
+        /// Compares like Python sets, i.e. `<=` is the subset relation.
+        fn __richcmp__(&self, other: &Self, op: CompareOp) -> bool {
+            let is_subset = self.is_subset(*other);
+            let is_superset = self.is_superset(*other);
+            match op {
+                CompareOp::Eq => self == other,
+                CompareOp::Ne => self != other,
+                CompareOp::Lt => is_subset && self != other,
+                CompareOp::Le => is_subset,
+                CompareOp::Gt => is_superset && self != other,
+                CompareOp::Ge => is_superset,
+            }
+        }
+
+        fn __hash__(&self) -> u64 {
+            self.bits
+        }
+
+        fn __reduce__(&self, py: Python) -> (PyObject, (Vec<Card>,)) {
+            (py.get_type::<CardsSet>().into(), (Vec::from_iter(*self),))
+        }
+
         fn __and__(&self, other: CardsSet) -> CardsSet {
             *self & other
         }
@@ -271,6 +390,31 @@ mod python {
         fn py_remove(&mut self, card: Card) {
             *self = self.remove(card);
         }
+
+        #[pyo3(name = "difference")]
+        fn py_difference(&self, other: CardsSet) -> CardsSet {
+            self.difference(other)
+        }
+
+        #[pyo3(name = "is_subset")]
+        fn py_is_subset(&self, other: CardsSet) -> bool {
+            self.is_subset(other)
+        }
+
+        #[pyo3(name = "is_superset")]
+        fn py_is_superset(&self, other: CardsSet) -> bool {
+            self.is_superset(other)
+        }
+
+        #[pyo3(name = "is_disjoint")]
+        fn py_is_disjoint(&self, other: CardsSet) -> bool {
+            self.is_disjoint(other)
+        }
+
+        #[pyo3(name = "iter_suits")]
+        fn py_iter_suits(&self) -> Vec<(Suit, CardsSet)> {
+            self.iter_suits().collect()
+        }
     }
 
     #[pymethods]
@@ -288,11 +432,60 @@ mod python {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::{BLACK_CARDS, RED_CARDS};
+    use crate::{card, Deck, Rank, BLACK_CARDS, RED_CARDS};
 
     #[test]
     fn set_constants() {
         assert_eq!(CardsSet::from_iter(RED_CARDS), RED_CARDS_SET);
         assert_eq!(CardsSet::from_iter(BLACK_CARDS), BLACK_CARDS_SET);
     }
+
+    #[test]
+    fn colors() {
+        for (color, cards) in [(Color::Red, RED_CARDS), (Color::Black, BLACK_CARDS)] {
+            assert_eq!(CardsSet::of_color(color), CardsSet::from_iter(cards));
+            // The order of the deck matters for replaying shuffled games
+            assert_eq!(Deck::Color(color).cards(), cards);
+            for card in cards {
+                assert_eq!(card.color(), color);
+                assert!(color.suits().contains(&card.suit));
+            }
+        }
+        assert_eq!(card!("J♦").color(), Color::Red);
+        assert_eq!(Suit::Club.color(), Color::Black);
+    }
+
+    #[test]
+    fn display() {
+        let cards = CardsSet::from_iter([card!("Q♠"), card!("7♥"), card!("T♦")]);
+        assert_eq!(cards.to_string(), "7♥ 10♦ Q♠");
+        assert_eq!(CardsSet::new().to_string(), "");
+    }
+
+    #[test]
+    fn set_operations() {
+        let red_aces = CardsSet::from_iter([card!("A♥"), card!("A♦")]);
+        let aces = red_aces | CardsSet::from_iter([card!("A♠"), card!("A♣")]);
+        assert_eq!(aces.difference(red_aces), aces & BLACK_CARDS_SET);
+        assert_eq!(red_aces.difference(aces), CardsSet::new());
+        assert!(red_aces.is_subset(aces) && red_aces.is_subset(RED_CARDS_SET));
+        assert!(!aces.is_subset(RED_CARDS_SET));
+        assert!(aces.is_superset(red_aces) && aces.is_superset(aces));
+        assert!(red_aces.is_disjoint(BLACK_CARDS_SET));
+        assert!(!aces.is_disjoint(BLACK_CARDS_SET));
+
+        let suits: Vec<(Suit, CardsSet)> = RED_CARDS_SET.iter_suits().collect();
+        assert_eq!(suits.len(), 4);
+        assert_eq!(suits[0].1 | suits[1].1, RED_CARDS_SET);
+        assert!(suits[2].1.is_empty() && suits[3].1.is_empty());
+        for (suit, cards) in aces.iter_suits() {
+            assert_eq!(
+                Vec::from_iter(cards),
+                [Card {
+                    suit,
+                    rank: Rank::Ace
+                }]
+            );
+        }
+    }
 }
@@ -0,0 +1,240 @@
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+use crate::prelude::*;
+use crate::{Board, CardCounter, CardsSet, DrawPile, Hand, PlayerState, PlayerView};
+
+/// A fully specified game state, as seen by one player. Returned by [`determinize()`].
+///
+/// The states can be used with [`execute_turn()`](crate::execute_turn) to simulate the
+/// rest of the game.
+#[derive(Clone, Debug)]
+pub struct DeterminizedState {
+    pub board: Board,
+    /// The player's own state. Only the order of the draw pile is made up.
+    pub me: PlayerState,
+    /// The opponents' states, with hands and draw piles that are made up, in the order
+    /// in which they play after the player. There is one opponent in a two-player game.
+    pub opponents: Vec<PlayerState>,
+}
+
+/// Samples a game state that is consistent with everything the player has observed.
+///
+/// The cards that the opponent may still have, i.e. those of its color that are neither
+/// on the board nor won by anyone, are randomly split into a hand of five cards and a
+/// draw pile. The order of both draw piles is random. Every such state is equally likely,
+/// so this doesn't take into account what the opponent's previous decisions reveal
+/// about its hand, e.g. that it had no playable card when it skipped a turn.
+///
+/// With more than one opponent, i.e. if `counter.num_opponents` is more than one, every
+/// opponent plays with one suit. The suits are assigned to the opponents at random, and in
+/// a game with three players, the suit that nobody plays is one of the suits of which no
+/// card has been seen yet. The cards won by the opponents are all attributed to the first
+/// opponent, since the player can't tell who won them.
+///
+/// The board and the player's hand are those of the `view`. The player's own draw pile
+/// consists of the cards in the draw pile of `counter` that are neither in its hand nor on
+/// the board.
+///
+/// Panics if `counter` leaves fewer than five cards for an opponent, which can't happen
+/// in a game that is still running if the counter is up to date.
+pub fn determinize(
+    view: &PlayerView,
+    counter: &CardCounter,
+    rng: &mut impl Rng,
+) -> DeterminizedState {
+    let PlayerView { board, hand, .. } = view;
+    let hand = *hand;
+    let on_board = board.iter().fold(CardsSet::new(), |cards, (_, _, field)| {
+        cards | field.all_cards()
+    });
+
+    let mut my_draw_pile = Vec::from_iter(counter.draw_pile.difference(hand.cards() | on_board));
+    my_draw_pile.shuffle(rng);
+
+    let opponent_cards = counter
+        .available_cards_opponent
+        .difference(on_board | counter.cards_won_opponent);
+    let opponent_cards_by_player = if counter.num_opponents == 1 {
+        vec![opponent_cards]
+    } else {
+        sample_opponent_suits(
+            opponent_cards,
+            board.rules().cards(),
+            counter.num_opponents,
+            rng,
+        )
+    };
+    let opponents = opponent_cards_by_player
+        .into_iter()
+        .enumerate()
+        .map(|(idx, cards)| {
+            let mut cards = Vec::from_iter(cards);
+            assert!(
+                cards.len() >= Hand::MAX_SIZE,
+                "The opponent must have at least five cards, but only {} are available",
+                cards.len()
+            );
+            cards.shuffle(rng);
+            let hand = cards.split_off(cards.len() - Hand::MAX_SIZE);
+            PlayerState {
+                draw_pile: DrawPile::from_cards(cards),
+                hand: Hand::try_from(hand).unwrap(),
+                cards_won: if idx == 0 {
+                    counter.cards_won_opponent
+                } else {
+                    CardsSet::new()
+                },
+            }
+        })
+        .collect();
+
+    DeterminizedState {
+        board: board.clone(),
+        me: PlayerState {
+            draw_pile: DrawPile::from_cards(my_draw_pile),
+            hand,
+            cards_won: counter.cards_won_self,
+        },
+        opponents,
+    }
+}
+
+// Splits the cards that the opponents may still have by suit, one suit per opponent.
+// `deck` has all cards of the game.
+fn sample_opponent_suits(
+    opponent_cards: CardsSet,
+    deck: CardsSet,
+    num_opponents: usize,
+    rng: &mut impl Rng,
+) -> Vec<CardsSet> {
+    let mut suits: Vec<CardsSet> = opponent_cards
+        .iter_suits()
+        .map(|(_, cards)| cards)
+        .filter(|cards| !cards.is_empty())
+        .collect();
+    if suits.len() > num_opponents {
+        // The suit that nobody plays can't have been seen
+        let full_suits = deck
+            .iter_suits()
+            .map(|(_, cards)| cards)
+            .collect::<Vec<_>>();
+        let unseen: Vec<usize> = (0..suits.len())
+            .filter(|&idx| full_suits.contains(&suits[idx]))
+            .collect();
+        let unused = *unseen
+            .choose(rng)
+            .expect("Only the suits of the opponents have been seen");
+        suits.remove(unused);
+    }
+    suits.shuffle(rng);
+    suits
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    use super::*;
+    use crate::{card, execute_first_turn, sample_player_view, CardToPlay, Color, Deck, Suit};
+    use crate::{BLACK_CARDS_SET, RED_CARDS_SET};
+
+    fn view(board: &Board, hand: Hand) -> PlayerView {
+        PlayerView {
+            board: board.clone(),
+            hand,
+            ..sample_player_view()
+        }
+    }
+
+    /// After the first turn of a real game, the sampled states have the right cards,
+    /// although not necessarily in the right places.
+    #[test]
+    fn sampled_cards_match_real_game() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut me = PlayerState::new(Color::Red, &mut rng);
+        let opponent = PlayerState::new(Color::Black, &mut rng);
+        let opponent_cards =
+            opponent.hand.cards() | CardsSet::from_iter(opponent.draw_pile.clone());
+
+        let mut counter = CardCounter::new(Color::Red);
+        counter.draw_pile &= !me.hand.cards();
+        let first_card = me.hand.iter().next().unwrap();
+        let first_turn = execute_first_turn(&me, first_card).unwrap();
+        me = first_turn.state;
+        let board = first_turn.board;
+        counter.draw_pile &= !me.hand.cards();
+
+        for _ in 0..10 {
+            let state = determinize(&view(&board, me.hand), &counter, &mut rng);
+            assert_eq!(state.me.hand, me.hand);
+            assert_eq!(
+                CardsSet::from_iter(state.me.draw_pile.clone()),
+                CardsSet::from_iter(me.draw_pile.clone())
+            );
+            let [sampled_opponent] = &state.opponents[..] else {
+                panic!("Expected a single opponent");
+            };
+            let sampled_opponent_cards = sampled_opponent.hand.cards()
+                | CardsSet::from_iter(sampled_opponent.draw_pile.clone());
+            assert_eq!(sampled_opponent_cards, opponent_cards);
+            assert_eq!(sampled_opponent.draw_pile.len(), opponent.draw_pile.len());
+        }
+    }
+
+    /// In a game with three players, each opponent gets one of the suits that can still be
+    /// in play, and the suit of which cards are on the board is one of them.
+    #[test]
+    fn opponents_get_one_suit_each() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut me = PlayerState::with_deck(Deck::Suit(Suit::Heart), &mut rng);
+        let mut counter = CardCounter::for_deck(Deck::Suit(Suit::Heart), 3);
+        assert_eq!(counter.num_opponents, 2);
+        assert_eq!(counter.available_cards_opponent.len(), 39);
+        let first_card = me.hand.iter().next().unwrap();
+        let first_turn = execute_first_turn(&me, first_card).unwrap();
+        me = first_turn.state;
+        let board = first_turn.board;
+        counter.draw_pile = CardsSet::from_iter(me.draw_pile.clone());
+        // An opponent played a spade, which is visible on the board
+        let spade = card!("7♠");
+        let board = board
+            .calculate(CardToPlay {
+                card: spade,
+                i: 1,
+                j: 1,
+                target_field_for_king_ability: None,
+            })
+            .unwrap()
+            .execute();
+        counter.available_cards_opponent = counter.available_cards_opponent.remove(spade);
+
+        let mut unused_suits = CardsSet::new();
+        for _ in 0..20 {
+            let state = determinize(&view(&board, me.hand), &counter, &mut rng);
+            assert_eq!(state.opponents.len(), 2);
+            let suits: Vec<CardsSet> = state
+                .opponents
+                .iter()
+                .map(|opponent| {
+                    assert_eq!(opponent.hand.len(), Hand::MAX_SIZE);
+                    opponent.hand.cards() | CardsSet::from_iter(opponent.draw_pile.clone())
+                })
+                .collect();
+            let spades = suits.iter().find(|cards| cards.contains(card!("8♠")));
+            assert_eq!(spades.map(|cards| cards.len()), Some(12));
+            let other = suits.iter().find(|cards| !cards.contains(card!("8♠")));
+            let other = *other.unwrap();
+            assert_eq!(other.len(), 13);
+            assert!(other.is_disjoint(CardsSet::from_iter(Deck::Suit(Suit::Heart).cards())));
+            unused_suits |= (RED_CARDS_SET | BLACK_CARDS_SET)
+                .difference(other | *spades.unwrap() | counter.draw_pile | me.hand.cards())
+                .difference(board.iter().fold(CardsSet::new(), |cards, (_, _, field)| {
+                    cards | field.all_cards()
+                }));
+        }
+        // Both the diamonds and the clubs were sampled as the suit that nobody plays
+        assert_eq!(unused_suits.len(), 26);
+    }
+}
@@ -0,0 +1,188 @@
+//! A fixed-size numeric encoding of boards and moves, e.g. for machine learning.
+//!
+//! Positions are encoded relative to the board's [bounding box](Board::bbox), so that the
+//! same arrangement of cards always has the same encoding, no matter where on the table it
+//! is. Training code and bots that use a trained model must use the same encoding, which is
+//! why it lives here and is also exposed to Python.
+
+use crate::{Board, Card, CardToPlay, BOARD_SIZE};
+
+/// The number of channels of [`encode_board()`]: 4 for the suit of the top card, 13 for its
+/// rank, one for the number of hidden cards and one for whether there is a field at all.
+pub const NUM_CHANNELS: usize = 4 + 13 + 1 + 1;
+
+/// The number of values of [`encode_board()`].
+pub const ENCODED_BOARD_LEN: usize = NUM_CHANNELS * BOARD_SIZE as usize * BOARD_SIZE as usize;
+
+/// Encodes the board as `NUM_CHANNELS` planes of `BOARD_SIZE` x `BOARD_SIZE` values.
+///
+/// The values are in channel-major order, i.e. the value of channel `c` at `(i, j)`, relative
+/// to the top left corner of the bounding box, is at `(c * BOARD_SIZE + i) * BOARD_SIZE + j`.
+/// The channels are:
+///
+/// * 0 to 3: 1 if the top card is a diamond, heart, spade or club, respectively
+/// * 4 to 16: 1 if the top card has the rank two, three, …, king or ace, respectively
+/// * 17: the number of hidden cards
+/// * 18: 1 if there are any cards at this place
+///
+/// Panics if the board is from a variant with a [`board_size`](crate::Rules::board_size)
+/// larger than `BOARD_SIZE`, which doesn't fit into the planes.
+pub fn encode_board(board: &Board) -> [f32; ENCODED_BOARD_LEN] {
+    assert_fits(board);
+    let plane_len = BOARD_SIZE as usize * BOARD_SIZE as usize;
+    let (i_min, j_min) = (board.bbox().i_min, board.bbox().j_min);
+    let mut encoded = [0.0; ENCODED_BOARD_LEN];
+    for &(i, j, field) in board.iter() {
+        let pos = (i - i_min) as usize * BOARD_SIZE as usize + (j - j_min) as usize;
+        if let Some(Card { suit, rank }) = field.top_card() {
+            encoded[suit as usize * plane_len + pos] = 1.0;
+            encoded[(4 + rank as usize) * plane_len + pos] = 1.0;
+        }
+        encoded[17 * plane_len + pos] = field.num_hidden_cards() as f32;
+        encoded[18 * plane_len + pos] = 1.0;
+    }
+    encoded
+}
+
+/// The width and height of the planes of [`encode_board_planes()`].
+pub const PLANE_SIZE: usize = 7;
+
+/// The number of planes of [`encode_board_planes()`]: 4 for the suit of the top card, 13
+/// for its rank, one for the number of hidden cards and one for the playable area.
+pub const NUM_PLANES: usize = 4 + 13 + 1 + 1;
+
+/// Encodes the board as `NUM_PLANES` planes of `PLANE_SIZE` x `PLANE_SIZE` values.
+///
+/// Unlike [`encode_board()`], the planes also cover the places around the cards where a card
+/// may be played. The top left corner of the bounding box is at `(3, 3)`, so that the
+/// playable area always fits, and the same arrangement of cards has the same encoding. The
+/// planes are:
+///
+/// * 0 to 3: 1 if the top card is a diamond, heart, spade or club, respectively
+/// * 4 to 16: 1 if the top card has the rank two, three, …, king or ace, respectively
+/// * 17: the number of hidden cards
+/// * 18: 1 if the place is in the [playable area](Board::playable_area)
+///
+/// Panics for larger boards than `BOARD_SIZE` x `BOARD_SIZE`, like [`encode_board()`].
+pub fn encode_board_planes(board: &Board) -> [[[u8; PLANE_SIZE]; PLANE_SIZE]; NUM_PLANES] {
+    assert_fits(board);
+    let (i_min, j_min) = (board.bbox().i_min, board.bbox().j_min);
+    let pos = |i: i8, j: i8| ((i - i_min + 3) as usize, (j - j_min + 3) as usize);
+    let mut planes = [[[0; PLANE_SIZE]; PLANE_SIZE]; NUM_PLANES];
+    for (i, j) in board.playable_area().iter() {
+        let (row, col) = pos(i, j);
+        planes[18][row][col] = 1;
+    }
+    for &(i, j, field) in board.iter() {
+        let (row, col) = pos(i, j);
+        if let Some(Card { suit, rank }) = field.top_card() {
+            planes[suit as usize][row][col] = 1;
+            planes[4 + rank as usize][row][col] = 1;
+        }
+        planes[17][row][col] = field.num_hidden_cards() as u8;
+    }
+    planes
+}
+
+fn assert_fits(board: &Board) {
+    assert!(
+        board.rules().board_size <= BOARD_SIZE,
+        "Boards of size {} can't be encoded, only those up to {}",
+        board.rules().board_size,
+        BOARD_SIZE
+    );
+}
+
+/// Encodes a card played on the board as `[card, i, j, target_i, target_j]`.
+///
+/// The card is its index `4 * rank + suit`, with the ranks and suits numbered like the
+/// channels of [`encode_board()`]. The coordinates are relative to the top left corner of
+/// the board's bounding box, like in [`encode_board()`], so they can be negative. Cards
+/// without a target for the king's ability have a target of `(-128, -128)`.
+pub fn encode_card_to_play(board: &Board, card_to_play: CardToPlay) -> [i8; 5] {
+    let (i_min, j_min) = (board.bbox().i_min, board.bbox().j_min);
+    let CardToPlay {
+        card,
+        i,
+        j,
+        target_field_for_king_ability,
+    } = card_to_play;
+    let (target_i, target_j) = match target_field_for_king_ability {
+        Some((i, j)) => (i - i_min, j - j_min),
+        None => (i8::MIN, i8::MIN),
+    };
+    [
+        card.to_index() as i8,
+        i - i_min,
+        j - j_min,
+        target_i,
+        target_j,
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::collections::BTreeSet;
+
+    use super::*;
+    use crate::{card, Field};
+
+    // A face-up card, and two face-down cards, with the bounding box at (offset, offset)
+    fn fields(offset: i8) -> Vec<Field> {
+        vec![
+            Field {
+                i: offset,
+                j: offset,
+                top_card: Some(card!("7♥")),
+                hidden_cards: BTreeSet::new(),
+            },
+            Field {
+                i: offset + 1,
+                j: offset + 2,
+                top_card: None,
+                hidden_cards: BTreeSet::from([card!("A♣"), card!("2♣")]),
+            },
+        ]
+    }
+
+    #[test]
+    fn encoding_is_relative_to_the_bounding_box() {
+        let board = Board::new(&fields(0));
+        let encoded = encode_board(&board);
+        assert_eq!(encoded, encode_board(&Board::new(&fields(-5))));
+        // The heart, the seven and the two fields
+        let value = |channel: usize, i: usize, j: usize| encoded[(channel * 4 + i) * 4 + j];
+        assert_eq!(value(1, 0, 0), 1.0);
+        assert_eq!(value(4 + 5, 0, 0), 1.0);
+        assert_eq!(value(17, 1, 2), 2.0);
+        assert_eq!(value(18, 0, 0) + value(18, 1, 2), 2.0);
+        assert_eq!(encoded.iter().sum::<f32>(), 1.0 + 1.0 + 2.0 + 2.0);
+
+        let ctp = CardToPlay {
+            card: card!("K♥"),
+            i: 0,
+            j: 0,
+            target_field_for_king_ability: Some((-1, 0)),
+        };
+        assert_eq!(encode_card_to_play(&board, ctp), [11 * 4 + 1, 0, 0, -1, 0]);
+    }
+
+    #[test]
+    fn planes_include_the_playable_area() {
+        let board = Board::new(&fields(0));
+        let planes = encode_board_planes(&board);
+        assert_eq!(planes, encode_board_planes(&Board::new(&fields(-5))));
+        assert_eq!(planes[1][3][3], 1);
+        assert_eq!(planes[4 + 5][3][3], 1);
+        assert_eq!(planes[17][4][5], 2);
+        // The bounding box is 2 x 3, so the playable area is 6 x 5, from (-2, -1) to (3, 3)
+        let playable = planes[18]
+            .iter()
+            .flatten()
+            .filter(|&&value| value == 1)
+            .count();
+        assert_eq!(playable, 30);
+        assert_eq!((planes[18][1][2], planes[18][6][6]), (1, 1));
+        assert_eq!((planes[18][0][2], planes[18][1][1]), (0, 0));
+    }
+}
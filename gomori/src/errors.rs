@@ -1,19 +1,37 @@
-use crate::Card;
+use crate::prelude::*;
+use crate::{Card, CardToPlay, Rank};
 
 /// The error type for [`Board::calculate()`](crate::Board::calculate), i.e. for playing a single card.
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub enum IllegalCardPlayed {
     OutOfBounds,
-    IncompatibleCard { existing_card: Card },
+    IncompatibleCard {
+        existing_card: Card,
+    },
     NoTargetForKingAbility,
-    TargetForKingAbilityDoesNotExist { tgt_i: i8, tgt_j: i8 },
-    TargetForKingAbilityIsFaceDown { tgt_i: i8, tgt_j: i8 },
+    /// The target of the king's ability is outside of the playing field, so there can't be
+    /// a card there.
+    TargetForKingAbilityIsOutOfBounds {
+        tgt_i: i8,
+        tgt_j: i8,
+    },
+    /// The target of the king's ability is within the playing field, but has no cards on it.
+    TargetForKingAbilityIsEmpty {
+        tgt_i: i8,
+        tgt_j: i8,
+    },
+    /// The target of the king's ability only has face-down cards on it, and isn't the field
+    /// that the king is played on.
+    TargetForKingAbilityIsFaceDown {
+        tgt_i: i8,
+        tgt_j: i8,
+    },
 }
 
-impl std::error::Error for IllegalCardPlayed {}
+impl core::error::Error for IllegalCardPlayed {}
 
-impl std::fmt::Display for IllegalCardPlayed {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl core::fmt::Display for IllegalCardPlayed {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         match self {
             IllegalCardPlayed::OutOfBounds =>
                 write!(f, "Card was played out of the bounds of the playing field"),
@@ -21,10 +39,106 @@ impl std::fmt::Display for IllegalCardPlayed {
                 write!(f, "Card was played on top of an incompatible card, {}", existing_card.unicode_char()),
             IllegalCardPlayed::NoTargetForKingAbility =>
                 write!(f, "A king was played on top of another card, but no target for its ability was specified"),
-            IllegalCardPlayed::TargetForKingAbilityDoesNotExist { tgt_i, tgt_j } =>
-                write!(f, "A king was played on top of another card, but the specified target card for its ability ({}, {}) does not exist", tgt_i, tgt_j),
+            IllegalCardPlayed::TargetForKingAbilityIsOutOfBounds { tgt_i, tgt_j } =>
+                write!(f, "A king was played on top of another card, but the specified target field for its ability ({}, {}) is out of the bounds of the playing field", tgt_i, tgt_j),
+            IllegalCardPlayed::TargetForKingAbilityIsEmpty { tgt_i, tgt_j } =>
+                write!(f, "A king was played on top of another card, but the specified target field for its ability ({}, {}) has no cards on it", tgt_i, tgt_j),
             IllegalCardPlayed::TargetForKingAbilityIsFaceDown { tgt_i, tgt_j } =>
-                write!(f, "A king was played on top of another card, but the specified target card for its ability ({}, {}) is already face-down", tgt_i, tgt_j),
+                write!(f, "A king was played on top of another card, but the specified target field for its ability ({}, {}) has no face-up card, and isn't the king's own field", tgt_i, tgt_j),
+        }
+    }
+}
+
+/// The error type for creating a [`Board`](crate::Board) from invalid fields.
+#[derive(Debug, PartialEq, Eq)]
+pub enum InvalidBoard {
+    NoFields,
+    EmptyField { i: i8, j: i8 },
+    DuplicateField { i: i8, j: i8 },
+    DuplicateCard { card: Card },
+    TooLarge { board_size: i8 },
+    InvalidRules(InvalidRules),
+}
+
+impl core::error::Error for InvalidBoard {}
+
+impl core::fmt::Display for InvalidBoard {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            InvalidBoard::NoFields => write!(f, "A board needs at least one field"),
+            InvalidBoard::EmptyField { i, j } => {
+                write!(f, "The field at ({}, {}) has no cards", i, j)
+            }
+            InvalidBoard::DuplicateField { i, j } => {
+                write!(f, "There is more than one field at ({}, {})", i, j)
+            }
+            InvalidBoard::DuplicateCard { card } => {
+                write!(f, "The card {} is on more than one field", card)
+            }
+            InvalidBoard::TooLarge { board_size } => write!(
+                f,
+                "The fields don't fit in a {} x {} area",
+                board_size, board_size
+            ),
+            InvalidBoard::InvalidRules(err) => write!(f, "Invalid rules: {}", err),
+        }
+    }
+}
+
+/// The error type for [`Rules::validate()`](crate::Rules::validate).
+#[derive(Debug, PartialEq, Eq)]
+pub enum InvalidRules {
+    BoardSizeOutOfRange { board_size: i8 },
+    LineLengthOutOfRange { line_length: u8 },
+    LowestRankTooHigh { lowest_rank: Rank },
+}
+
+impl core::error::Error for InvalidRules {}
+
+impl core::fmt::Display for InvalidRules {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            InvalidRules::BoardSizeOutOfRange { board_size } => write!(
+                f,
+                "The board size must be between 2 and {}, but is {}",
+                crate::MAX_BOARD_SIZE,
+                board_size
+            ),
+            InvalidRules::LineLengthOutOfRange { line_length } => write!(
+                f,
+                "The line length must be between 2 and the board size, but is {}",
+                line_length
+            ),
+            InvalidRules::LowestRankTooHigh { lowest_rank } => write!(
+                f,
+                "The lowest rank of the deck must be at most 10, but is {}",
+                lowest_rank
+            ),
+        }
+    }
+}
+
+/// The error type for creating a [`Hand`](crate::Hand) from invalid cards.
+#[derive(Debug, PartialEq, Eq)]
+pub enum InvalidHand {
+    TooManyCards { num_cards: usize },
+    DuplicateCard { card: Card },
+}
+
+impl core::error::Error for InvalidHand {}
+
+impl core::fmt::Display for InvalidHand {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            InvalidHand::TooManyCards { num_cards } => write!(
+                f,
+                "A hand has at most {} cards, but got {}",
+                crate::Hand::MAX_SIZE,
+                num_cards
+            ),
+            InvalidHand::DuplicateCard { card } => {
+                write!(f, "The card {} is in the hand more than once", card)
+            }
         }
     }
 }
@@ -33,11 +147,17 @@ impl std::fmt::Display for IllegalCardPlayed {
 /// The error type for one turn.
 pub enum IllegalMove {
     PlayedCardNotInHand,
+    /// Played an empty list of cards, although a card could be played.
     PlayedZeroCards,
+    /// Skipped the turn, although a card could be played.
+    SkippedDespitePlayableCard,
     PlayedMoreThanFiveCards,
     IllegalCardPlayed {
         card_idx: usize,
-        card: Card,
+        card_to_play: CardToPlay,
+        /// The top cards of the board before the card was played, as drawn by
+        /// [`visualize_top_cards()`](crate::visualize_top_cards), to reproduce the error.
+        board: String,
         err: IllegalCardPlayed,
     },
     PlayedCardAfterEndOfCombo {
@@ -48,8 +168,8 @@ pub enum IllegalMove {
     },
 }
 
-impl std::error::Error for IllegalMove {
-    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+impl core::error::Error for IllegalMove {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
         match self {
             IllegalMove::IllegalCardPlayed { err, .. } => Some(err),
             _ => None,
@@ -68,24 +188,35 @@ fn ordinal_number(num: usize) -> &'static str {
     }
 }
 
-impl std::fmt::Display for IllegalMove {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl core::fmt::Display for IllegalMove {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         match self {
             IllegalMove::PlayedCardNotInHand => {
                 write!(f, "Tried to play a card that was not in the player's hand")
             }
             IllegalMove::PlayedZeroCards => write!(f, "Tried to play zero cards"),
+            IllegalMove::SkippedDespitePlayableCard => {
+                write!(f, "Tried to skip the turn, but a card could be played")
+            }
             IllegalMove::PlayedMoreThanFiveCards => write!(f, "Tried to play more than five cards"),
             IllegalMove::IllegalCardPlayed {
                 card_idx,
-                card,
-                err: _,
-            } => write!(
-                f,
-                "Error playing the {} card, which was {}",
-                ordinal_number(*card_idx),
-                card
-            ),
+                card_to_play,
+                ..
+            } => {
+                write!(
+                    f,
+                    "Error playing the {} card, which was {} at ({}, {})",
+                    ordinal_number(*card_idx),
+                    card_to_play.card,
+                    card_to_play.i,
+                    card_to_play.j
+                )?;
+                if let Some((i, j)) = card_to_play.target_field_for_king_ability {
+                    write!(f, " with the king's ability targeting ({}, {})", i, j)?;
+                }
+                Ok(())
+            }
             IllegalMove::PlayedCardAfterEndOfCombo { card_idx } => write!(
                 f,
                 "The {} card did not start a combo, but another card was played",
@@ -132,6 +263,12 @@ mod python {
             IllegalMoveException::new_err(err.to_string())
         }
     }
+
+    impl From<InvalidBoard> for PyErr {
+        fn from(err: InvalidBoard) -> PyErr {
+            pyo3::exceptions::PyValueError::new_err(err.to_string())
+        }
+    }
 }
 #[cfg(feature = "python")]
 pub use python::*;
@@ -0,0 +1,137 @@
+use serde::{Deserialize, Serialize};
+
+use crate::prelude::*;
+use crate::{Card, CardsSet, IllegalCardPlayed, Rank, Suit};
+
+/// The direction of a line of four cards.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LineDirection {
+    /// All cards in the line have the same `i` coordinate.
+    SameI,
+    /// All cards in the line have the same `j` coordinate.
+    SameJ,
+    /// Both `i` and `j` increase along the line.
+    Diagonal,
+    /// `i` increases and `j` decreases along the line.
+    AntiDiagonal,
+}
+
+impl LineDirection {
+    /// The direction of the line going through both points, if they are on a line.
+    pub fn between(from: (i8, i8), to: (i8, i8)) -> Option<Self> {
+        let (di, dj) = (to.0 - from.0, to.1 - from.1);
+        match (di, dj) {
+            (0, 0) => None,
+            (0, _) => Some(LineDirection::SameI),
+            (_, 0) => Some(LineDirection::SameJ),
+            _ if di == dj => Some(LineDirection::Diagonal),
+            _ if di == -dj => Some(LineDirection::AntiDiagonal),
+            _ => None,
+        }
+    }
+}
+
+/// A line of four cards of the same suit, which is completed by the played card.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Line {
+    pub direction: LineDirection,
+    pub suit: Suit,
+    /// The coordinates of the cards in the line, including the played card, in ascending order.
+    pub fields: Vec<(i8, i8)>,
+}
+
+impl core::fmt::Display for Line {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let fields: Vec<_> = self
+            .fields
+            .iter()
+            .map(|(i, j)| format!("({}, {})", i, j))
+            .collect();
+        write!(
+            f,
+            "{:?} line of {:?} at {}",
+            self.direction,
+            self.suit,
+            fields.join(" ")
+        )
+    }
+}
+
+/// Describes why playing a card has the effects that it has.
+///
+/// Returned by [`CalculatedEffects::explain()`](crate::CalculatedEffects::explain).
+/// The [`Display`](core::fmt::Display) implementation renders it as a sentence.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Explanation {
+    pub card: Card,
+    pub i: i8,
+    pub j: i8,
+    /// The top card that the played card was placed on, if any, which makes this a combo.
+    ///
+    /// This is `None` for a combo on a field whose top card is face-down.
+    pub placed_on: Option<Card>,
+    /// Whether another card should be played.
+    pub combo: bool,
+    /// The rank of the played face card if its ability was activated, i.e. if it was played
+    /// on top of another card.
+    pub ability: Option<Rank>,
+    /// The cards flipped face-down by the ability of the played face card, with their coordinates.
+    pub flipped: Vec<(i8, i8, Card)>,
+    /// The lines of four cards of the same suit that were completed by the played card.
+    pub lines: Vec<Line>,
+    pub cards_won: CardsSet,
+}
+
+impl core::fmt::Display for Explanation {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{} at ({}, {})", self.card, self.i, self.j)?;
+        match (self.combo, self.placed_on) {
+            (true, Some(card)) => write!(f, " on top of {} is a combo", card)?,
+            (true, None) => write!(f, " on top of a face-down card is a combo")?,
+            (false, _) => write!(f, " is not a combo")?,
+        }
+        if !self.flipped.is_empty() {
+            let flipped: Vec<_> = self
+                .flipped
+                .iter()
+                .map(|(i, j, card)| format!("{} at ({}, {})", card, i, j))
+                .collect();
+            write!(f, ", its ability flips {}", flipped.join(" and "))?;
+        }
+        if !self.lines.is_empty() {
+            let lines: Vec<_> = self.lines.iter().map(|l| l.to_string()).collect();
+            write!(f, ", it completes the {}", lines.join(" and the "))?;
+        }
+        if self.cards_won.is_empty() {
+            write!(f, ", no cards won")
+        } else {
+            let cards: Vec<_> = self.cards_won.into_iter().map(|c| c.to_string()).collect();
+            write!(f, ", wins {}", cards.join(" "))
+        }
+    }
+}
+
+/// Describes what playing a card would do, or why it can't be played.
+///
+/// Returned by [`Board::explain()`](crate::Board::explain).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum MoveExplanation {
+    Legal(Explanation),
+    Illegal {
+        card: Card,
+        i: i8,
+        j: i8,
+        reason: IllegalCardPlayed,
+    },
+}
+
+impl core::fmt::Display for MoveExplanation {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            MoveExplanation::Legal(explanation) => explanation.fmt(f),
+            MoveExplanation::Illegal { card, i, j, reason } => {
+                write!(f, "{} at ({}, {}) is illegal: {}", card, i, j, reason)
+            }
+        }
+    }
+}
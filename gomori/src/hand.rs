@@ -0,0 +1,184 @@
+use serde::{Deserialize, Serialize};
+
+use crate::prelude::*;
+use crate::{Card, CardsSet, CardsSetIter, InvalidHand};
+
+/// The cards in a player's hand, which are at most five different cards.
+///
+/// A hand has five cards at the start of a turn, except at the end of the game, when the
+/// draw pile is empty. In the middle of a turn, the cards that were played so far are missing.
+///
+/// Serialized as a list of cards. When deserializing, more than five cards or duplicate
+/// cards are rejected.
+///
+/// # Note on immutability
+///
+/// Like [`CardsSet`], this is an immutable [`Copy`] type, so its "mutating" methods return a
+/// new value instead of really mutating in-place.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(into = "Vec<Card>", try_from = "Vec<Card>")]
+pub struct Hand {
+    cards: CardsSet,
+}
+
+impl Hand {
+    /// The number of cards in a full hand.
+    pub const MAX_SIZE: usize = 5;
+
+    /// Creates a new, empty hand.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(self) -> usize {
+        self.cards.len() as usize
+    }
+
+    pub fn is_empty(self) -> bool {
+        self.cards.is_empty()
+    }
+
+    /// Whether the hand has [`MAX_SIZE`](Hand::MAX_SIZE) cards.
+    pub fn is_full(self) -> bool {
+        self.len() == Self::MAX_SIZE
+    }
+
+    pub fn contains(self, card: Card) -> bool {
+        self.cards.contains(card)
+    }
+
+    /// Adds a card, e.g. one drawn from the draw pile.
+    pub fn insert(self, card: Card) -> Result<Self, InvalidHand> {
+        if self.contains(card) {
+            Err(InvalidHand::DuplicateCard { card })
+        } else if self.is_full() {
+            Err(InvalidHand::TooManyCards {
+                num_cards: Self::MAX_SIZE + 1,
+            })
+        } else {
+            Ok(Self {
+                cards: self.cards.insert(card),
+            })
+        }
+    }
+
+    /// Removes a card, e.g. one that was played. Does nothing if the card is not in the hand.
+    pub fn remove(self, card: Card) -> Self {
+        Self {
+            cards: self.cards.remove(card),
+        }
+    }
+
+    /// The cards as a set, e.g. to combine them with other sets.
+    pub fn cards(self) -> CardsSet {
+        self.cards
+    }
+
+    /// Iterates over the cards by ascending rank, see [`CardsSetIter`].
+    pub fn iter(self) -> CardsSetIter {
+        self.cards.into_iter()
+    }
+}
+
+impl TryFrom<CardsSet> for Hand {
+    type Error = InvalidHand;
+
+    fn try_from(cards: CardsSet) -> Result<Self, Self::Error> {
+        if cards.len() as usize > Self::MAX_SIZE {
+            return Err(InvalidHand::TooManyCards {
+                num_cards: cards.len() as usize,
+            });
+        }
+        Ok(Self { cards })
+    }
+}
+
+impl TryFrom<&[Card]> for Hand {
+    type Error = InvalidHand;
+
+    fn try_from(cards: &[Card]) -> Result<Self, Self::Error> {
+        if cards.len() > Self::MAX_SIZE {
+            return Err(InvalidHand::TooManyCards {
+                num_cards: cards.len(),
+            });
+        }
+        cards
+            .iter()
+            .try_fold(Self::new(), |hand, &card| hand.insert(card))
+    }
+}
+
+impl TryFrom<Vec<Card>> for Hand {
+    type Error = InvalidHand;
+
+    fn try_from(cards: Vec<Card>) -> Result<Self, Self::Error> {
+        Self::try_from(cards.as_slice())
+    }
+}
+
+impl TryFrom<[Card; 5]> for Hand {
+    type Error = InvalidHand;
+
+    fn try_from(cards: [Card; 5]) -> Result<Self, Self::Error> {
+        Self::try_from(cards.as_slice())
+    }
+}
+
+impl From<Hand> for CardsSet {
+    fn from(hand: Hand) -> Self {
+        hand.cards
+    }
+}
+
+impl From<Hand> for Vec<Card> {
+    fn from(hand: Hand) -> Self {
+        hand.iter().collect()
+    }
+}
+
+impl IntoIterator for Hand {
+    type Item = Card;
+
+    type IntoIter = CardsSetIter;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::card;
+
+    #[test]
+    fn wire_format() {
+        let hand = Hand::try_from([
+            card!("2♦"),
+            card!("3♦"),
+            card!("4♦"),
+            card!("5♦"),
+            card!("6♦"),
+        ])
+        .unwrap();
+        let json = serde_json::to_string(&hand).unwrap();
+        assert_eq!(json, serde_json::to_string(&Vec::from(hand)).unwrap());
+        assert_eq!(serde_json::from_str::<Hand>(&json).unwrap(), hand);
+
+        // A hand can have fewer than five cards, but not more
+        let json = serde_json::to_string(&[card!("2♦"), card!("3♦")]).unwrap();
+        assert_eq!(serde_json::from_str::<Hand>(&json).unwrap().len(), 2);
+        let too_many = [card!("7♥"), card!("2♦")]
+            .into_iter()
+            .chain(hand)
+            .collect::<Vec<_>>();
+        assert!(serde_json::from_str::<Hand>(&serde_json::to_string(&too_many).unwrap()).is_err());
+        let duplicate = serde_json::to_string(&[card!("2♦"), card!("2♦")]).unwrap();
+        assert!(serde_json::from_str::<Hand>(&duplicate).is_err());
+
+        assert_eq!(
+            hand.insert(card!("7♥")),
+            Err(InvalidHand::TooManyCards { num_cards: 6 })
+        );
+    }
+}
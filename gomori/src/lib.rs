@@ -1,5 +1,5 @@
 //! # Overview
-//! A bot will receive a [`Board`] and a set of five [`Card`]s, and outputs a list of [`CardToPlay`].
+//! A bot will receive a [`Board`] and a [`Hand`] of five [`Card`]s, and outputs a list of [`CardToPlay`].
 //! Therefore, to get familiar with the API, it is recommended to start looking at these types.
 //!
 //! # Coordinates
@@ -33,22 +33,71 @@
 //! As a result, these coordinates may be negative, or larger than 4. They are represented
 //! as an `i8`.
 
+//! # Features
+//! - `std` (enabled by default): Without it, the crate is `no_std` and only needs `alloc`,
+//!   e.g. to run a search on an embedded target. [`Board::hidden_information()`] needs it.
+//!   All the other features enable it.
+//! - `python`, `wasm`: Bindings for these languages.
+//! - `rayon`: Parallel versions of some functions on the board.
+//! - `testing`: Helpers for generating random games in tests.
+
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+// The macros of the pyo3 version we depend on trigger some lints of newer toolchains.
+#![cfg_attr(
+    feature = "python",
+    allow(
+        non_local_definitions,
+        unexpected_cfgs,
+        clippy::unnecessary_fallible_conversions,
+        clippy::wrong_self_convention
+    )
+)]
+
+extern crate alloc;
+
 pub use board::*;
+pub use card_counter::*;
 pub use cards::*;
 pub use cards_set::*;
+pub use determinize::*;
+pub use encoding::*;
 pub use errors::*;
+pub use explanation::*;
+pub use hand::*;
 pub use player_state::*;
+pub use player_view::*;
 pub use protocol_types::*;
+pub use rules::Rules;
+#[cfg(any(test, feature = "testing"))]
+pub use testing::*;
 pub use turn::*;
 pub use visualization::*;
+#[cfg(feature = "wasm")]
+pub use wasm::*;
 
+pub mod advisor;
 #[cfg(test)]
 mod arbitrary;
 mod board;
+pub mod card;
+mod card_counter;
 mod cards;
 mod cards_set;
+mod determinize;
+mod encoding;
 mod errors;
+mod explanation;
+mod hand;
+#[cfg(any(test, feature = "testing"))]
+pub mod naive;
 mod player_state;
+mod player_view;
+mod prelude;
 mod protocol_types;
+pub mod rules;
+#[cfg(any(test, feature = "testing"))]
+mod testing;
 mod turn;
 mod visualization;
+#[cfg(feature = "wasm")]
+mod wasm;
@@ -0,0 +1,228 @@
+//! A reference implementation of playing a card, written in the most straightforward way:
+//! the board is a list of fields, and every lookup searches it.
+//!
+//! It's slow, but has none of the bit tricks of [`BitBoard`](crate::BitBoard), so the
+//! property tests below check [`Board::calculate()`](crate::Board::calculate) and
+//! [`BitBoard::lines_going_through_point()`](crate::BitBoard::lines_going_through_point)
+//! against it, and so do the differential tests of whole games. It is only available with
+//! the `testing` feature.
+
+use alloc::collections::BTreeSet;
+
+use crate::{Card, CardToPlay, CardsSet, Field, Rank, BOARD_SIZE};
+
+/// The effects of playing a legal card, like in [`CalculatedEffects`](crate::CalculatedEffects).
+#[derive(Debug, PartialEq, Eq)]
+pub struct NaiveEffects {
+    pub cards_won: CardsSet,
+    pub combo: bool,
+    /// The fields whose top card is flipped face-down.
+    pub flipped: BTreeSet<(i8, i8)>,
+    /// The fields whose cards are won.
+    pub won: BTreeSet<(i8, i8)>,
+    /// The board after playing the card, sorted by `i` and then `j`.
+    pub fields_after: Vec<Field>,
+}
+
+fn field_at(fields: &[Field], i: i8, j: i8) -> Option<&Field> {
+    fields.iter().find(|field| (field.i, field.j) == (i, j))
+}
+
+/// All points that are part of four consecutive `points` in a row, column or diagonal
+/// through `(i, j)`.
+pub fn lines_through(points: &[(i8, i8)], i: i8, j: i8) -> BTreeSet<(i8, i8)> {
+    let mut result = BTreeSet::new();
+    for (di, dj) in [(0, 1), (1, 0), (1, 1), (1, -1)] {
+        for start in -3..=0 {
+            let line: Vec<_> = (start..start + 4)
+                .map(|k| (i + k * di, j + k * dj))
+                .collect();
+            if line.iter().all(|point| points.contains(point)) {
+                result.extend(line);
+            }
+        }
+    }
+    result
+}
+
+/// Plays the card, or returns `None` if that's illegal.
+pub fn play(fields: &[Field], card_to_play: CardToPlay) -> Option<NaiveEffects> {
+    let CardToPlay { card, i, j, .. } = card_to_play;
+
+    // All fields, including the new card's, must fit into the board
+    let coords: Vec<(i8, i8)> = fields.iter().map(|f| (f.i, f.j)).chain([(i, j)]).collect();
+    let size = |coord: fn(&(i8, i8)) -> i8| {
+        let min = coords.iter().map(coord).min().unwrap();
+        let max = coords.iter().map(coord).max().unwrap();
+        max - min + 1
+    };
+    if size(|c| c.0) > BOARD_SIZE || size(|c| c.1) > BOARD_SIZE {
+        return None;
+    }
+
+    let existing = field_at(fields, i, j);
+    if let Some(Some(top_card)) = existing.map(|field| field.top_card) {
+        let compatible = card.rank == top_card.rank
+            || card.rank == Rank::Ace
+            || matches!(card.rank, Rank::Jack | Rank::Queen | Rank::King)
+                && card.suit == top_card.suit;
+        if !compatible {
+            return None;
+        }
+    }
+    let combo = existing.is_some();
+
+    let mut to_flip = Vec::new();
+    if combo {
+        match card.rank {
+            Rank::Jack => to_flip = vec![(i - 1, j), (i + 1, j), (i, j - 1), (i, j + 1)],
+            Rank::Queen => {
+                to_flip = vec![
+                    (i - 1, j - 1),
+                    (i - 1, j + 1),
+                    (i + 1, j - 1),
+                    (i + 1, j + 1),
+                ]
+            }
+            Rank::King => {
+                let (target_i, target_j) = card_to_play.target_field_for_king_ability?;
+                let target = field_at(fields, target_i, target_j)?;
+                if target.top_card.is_none() && (target_i, target_j) != (i, j) {
+                    return None;
+                }
+                to_flip = vec![(target_i, target_j)];
+            }
+            _ => {}
+        }
+    }
+
+    // Place the card, then flip the face-up cards
+    let mut fields_after: Vec<Field> = fields.to_vec();
+    match fields_after.iter_mut().find(|f| (f.i, f.j) == (i, j)) {
+        Some(field) => {
+            field.hidden_cards.extend(field.top_card.replace(card));
+        }
+        None => fields_after.push(Field {
+            i,
+            j,
+            top_card: Some(card),
+            hidden_cards: BTreeSet::new(),
+        }),
+    }
+    let mut flipped = BTreeSet::new();
+    for field in fields_after.iter_mut() {
+        if to_flip.contains(&(field.i, field.j)) {
+            if let Some(top_card) = field.top_card.take() {
+                field.hidden_cards.insert(top_card);
+                flipped.insert((field.i, field.j));
+            }
+        }
+    }
+
+    // Win the other cards of lines of face-up cards of the same suit
+    let same_suit: Vec<(i8, i8)> = fields_after
+        .iter()
+        .filter(|f| f.top_card.map(|c| c.suit) == Some(card.suit))
+        .map(|f| (f.i, f.j))
+        .collect();
+    let mut won = lines_through(&same_suit, i, j);
+    won.remove(&(i, j));
+    let mut cards_won = CardsSet::new();
+    fields_after.retain(|field| {
+        if !won.contains(&(field.i, field.j)) {
+            return true;
+        }
+        let cards: Vec<Card> = field
+            .top_card
+            .into_iter()
+            .chain(field.hidden_cards.iter().copied())
+            .collect();
+        cards_won = cards.into_iter().fold(cards_won, CardsSet::insert);
+        false
+    });
+    fields_after.sort_by_key(|f| (f.i, f.j));
+
+    Some(NaiveEffects {
+        cards_won,
+        combo,
+        flipped,
+        won,
+        fields_after,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use quickcheck::quickcheck;
+
+    use super::*;
+    use crate::arbitrary::PlayCardInput;
+    use crate::{BitBoard, Board, GameInProgress};
+
+    // Whether Board::calculate() agrees with play() about the card, which may be illegal
+    fn agrees_with_naive(board: &Board, ctp: CardToPlay) -> bool {
+        let actual = board.calculate(ctp).ok().map(|effects| {
+            let mut actual = NaiveEffects {
+                cards_won: effects.cards_won,
+                combo: effects.combo,
+                flipped: effects.flipped_fields().into_iter().collect(),
+                won: effects.won_fields().into_iter().collect(),
+                fields_after: Vec::new(),
+            };
+            actual.fields_after = effects.execute().to_fields_vec();
+            actual.fields_after.sort_by_key(|f| (f.i, f.j));
+            actual
+        });
+        actual == play(&board.to_fields_vec(), ctp)
+    }
+
+    quickcheck! {
+        fn calculate_agrees_with_naive(input: PlayCardInput) -> bool {
+            let board = Board::new(&input.fields);
+            let without_target = CardToPlay {
+                target_field_for_king_ability: None,
+                ..input.card_to_play
+            };
+            agrees_with_naive(&board, input.card_to_play) && agrees_with_naive(&board, without_target)
+        }
+
+        // Random boards rarely have lines, but boards of real games often do
+        fn legal_plays_agree_with_naive(game: GameInProgress) -> bool {
+            game.player
+                .hand
+                .iter()
+                .flat_map(|card| game.board.legal_plays(card))
+                .all(|ctp| agrees_with_naive(&game.board, ctp))
+        }
+
+        fn lines_agree_with_naive(corner: (i8, i8), points: u16, point: u8, center: u8) -> bool {
+            // Any points in a 4 x 4 area, like the cards of a game, and a point in that area
+            let (i_min, j_min) = (corner.0 % 48, corner.1 % 48);
+            let area: Vec<(i8, i8)> = (0..16).map(|k| (i_min + k / 4, j_min + k % 4)).collect();
+            let points: Vec<(i8, i8)> = (0..16)
+                .filter(|k| points & (1 << k) != 0)
+                .map(|k| area[k])
+                .collect();
+            let (i, j) = area[point as usize % 16];
+            // The bitboard's 9 x 9 area only needs to contain the 4 x 4 area
+            let center = (i_min + (center % 4) as i8, j_min + (center / 4 % 4) as i8);
+            let bitboard = points
+                .iter()
+                .fold(BitBoard::empty_board_centered_at(center), |bb, &(i, j)| bb.insert(i, j));
+            let actual: BTreeSet<_> = bitboard.lines_going_through_point(i, j).into_iter().collect();
+            actual == lines_through(&points, i, j)
+        }
+    }
+
+    #[test]
+    fn lines_need_four_consecutive_points() {
+        let points = [(0, 0), (0, 1), (0, 2), (0, 3), (1, 1), (2, 2), (3, 3)];
+        assert_eq!(
+            lines_through(&points, 0, 1),
+            BTreeSet::from_iter(points[..4].to_vec())
+        );
+        let diagonal = [(0, 0), (1, 1), (2, 2), (3, 3)];
+        assert_eq!(lines_through(&points, 1, 1), BTreeSet::from(diagonal));
+        assert!(lines_through(&points, 1, 2).is_empty());
+    }
+}
@@ -1,24 +1,188 @@
 use rand::rngs::StdRng;
 use rand::seq::SliceRandom;
+use serde::{Deserialize, Serialize};
 
-use crate::{Card, CardsSet, Color, BLACK_CARDS, RED_CARDS};
+use crate::prelude::*;
+use crate::{Card, CardsSet, Color, Hand, Rules, Suit};
+
+/// The cards that a player plays with.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Deck {
+    /// The 26 cards of one color, in a game with two players.
+    Color(Color),
+    /// The 13 cards of one suit, in a game with three or four players.
+    Suit(Suit),
+}
+
+impl Deck {
+    /// Assigns the decks for a game, in random order.
+    ///
+    /// With two players, one plays the red and the other the black cards. With three or
+    /// four players, every player gets one suit, so with three players, one suit is not
+    /// used. Panics for other numbers of players.
+    pub fn deal(num_players: usize, rng: &mut StdRng) -> Vec<Deck> {
+        match num_players {
+            2 => {
+                let mut colors = [Color::Red, Color::Black];
+                colors.shuffle(rng);
+                colors.into_iter().map(Deck::Color).collect()
+            }
+            3 | 4 => {
+                let mut suits = [Suit::Diamond, Suit::Heart, Suit::Spade, Suit::Club];
+                suits.shuffle(rng);
+                suits[..num_players]
+                    .iter()
+                    .copied()
+                    .map(Deck::Suit)
+                    .collect()
+            }
+            _ => panic!("Games need two to four players, not {}", num_players),
+        }
+    }
+
+    pub fn color(self) -> Color {
+        match self {
+            Deck::Color(color) => color,
+            Deck::Suit(suit) => suit.color(),
+        }
+    }
+
+    /// The cards of the deck, sorted by suit and then by rank.
+    pub fn cards(self) -> Vec<Card> {
+        self.cards_with_rules(Rules::STANDARD)
+    }
+
+    /// The cards of the deck in a variant of the game, i.e. those that are also in
+    /// [`Rules::cards()`], sorted by suit and then by rank.
+    pub fn cards_with_rules(self, rules: Rules) -> Vec<Card> {
+        (CardsSet::of_color(self.color()) & rules.cards())
+            .iter_suits()
+            .filter(|&(suit, _)| match self {
+                Deck::Color(_) => true,
+                Deck::Suit(deck_suit) => suit == deck_suit,
+            })
+            .flat_map(|(_, cards)| cards)
+            .collect()
+    }
+}
+
+/// The cards that a player draws from, in the order in which they will be drawn.
+///
+/// Cards are drawn from the end, so the last card is the next one. Serializes as the list of
+/// cards.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct DrawPile {
+    cards: Vec<Card>,
+}
+
+impl DrawPile {
+    /// The 26 cards of one color, shuffled.
+    pub fn new_shuffled(color: Color, rng: &mut StdRng) -> Self {
+        Self::shuffled(Deck::Color(color), rng)
+    }
+
+    /// The cards of the deck, shuffled.
+    pub fn shuffled(deck: Deck, rng: &mut StdRng) -> Self {
+        Self::shuffled_with_rules(deck, Rules::STANDARD, rng)
+    }
+
+    /// The cards of the deck in a variant of the game, shuffled, see
+    /// [`Deck::cards_with_rules()`].
+    pub fn shuffled_with_rules(deck: Deck, rules: Rules, rng: &mut StdRng) -> Self {
+        let mut cards = deck.cards_with_rules(rules);
+        cards.shuffle(rng);
+        Self { cards }
+    }
+
+    /// A draw pile with the cards in exactly this order, e.g. to replay a game or set up a
+    /// test. The last card is drawn first.
+    pub fn from_cards(cards: Vec<Card>) -> Self {
+        Self { cards }
+    }
+
+    pub fn len(&self) -> usize {
+        self.cards.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cards.is_empty()
+    }
+
+    /// The next `n` cards that will be drawn, or all of them if there are fewer. As in the
+    /// whole pile, the last card of the slice is drawn first.
+    pub fn peek_n(&self, n: usize) -> &[Card] {
+        &self.cards[self.cards.len().saturating_sub(n)..]
+    }
+
+    /// Removes the next card from the pile.
+    pub fn draw(&mut self) -> Option<Card> {
+        self.cards.pop()
+    }
+
+    /// Removes the next `n` cards from the pile, or all of them if there are fewer. They are
+    /// in the same order as in [`peek_n()`](Self::peek_n).
+    pub fn draw_n(&mut self, n: usize) -> Vec<Card> {
+        self.cards.split_off(self.cards.len().saturating_sub(n))
+    }
+
+    /// The cards in the pile, the next card being the last one.
+    pub fn cards(&self) -> &[Card] {
+        &self.cards
+    }
+
+    pub fn iter(&self) -> core::slice::Iter<'_, Card> {
+        self.cards.iter()
+    }
+}
+
+impl IntoIterator for DrawPile {
+    type Item = Card;
+    type IntoIter = alloc::vec::IntoIter<Card>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.cards.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a DrawPile {
+    type Item = &'a Card;
+    type IntoIter = core::slice::Iter<'a, Card>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.cards.iter()
+    }
+}
 
 /// The state for a single player during one game.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct PlayerState {
-    pub draw_pile: Vec<Card>,
-    pub hand: [Card; 5],
+    pub draw_pile: DrawPile,
+    pub hand: Hand,
     pub cards_won: CardsSet,
 }
 
 impl PlayerState {
     pub fn new(color: Color, rng: &mut StdRng) -> Self {
-        let mut draw_pile = Vec::from(match color {
-            Color::Black => &BLACK_CARDS,
-            Color::Red => &RED_CARDS,
-        });
-        draw_pile.shuffle(rng);
-        let hand = draw_pile.split_off(26 - 5).try_into().unwrap();
+        Self::with_draw_pile(DrawPile::new_shuffled(color, rng))
+    }
+
+    /// Shuffles the deck and draws the first hand from it.
+    pub fn with_deck(deck: Deck, rng: &mut StdRng) -> Self {
+        Self::with_draw_pile(DrawPile::shuffled(deck, rng))
+    }
+
+    /// Draws the first hand from an already shuffled draw pile, e.g. to replay a game.
+    ///
+    /// Panics if it has fewer than [`Hand::MAX_SIZE`] cards.
+    pub fn with_draw_pile(mut draw_pile: DrawPile) -> Self {
+        assert!(
+            draw_pile.len() >= Hand::MAX_SIZE,
+            "The draw pile needs at least {} cards, not {}",
+            Hand::MAX_SIZE,
+            draw_pile.len()
+        );
+        let hand = Hand::try_from(draw_pile.draw_n(Hand::MAX_SIZE)).unwrap();
 
         Self {
             draw_pile,
@@ -27,3 +191,47 @@ impl PlayerState {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{card, Rank};
+
+    #[test]
+    fn cards_are_drawn_from_the_end() {
+        let cards = vec![
+            card!("2♥"),
+            card!("3♥"),
+            card!("4♥"),
+            card!("5♥"),
+            card!("6♥"),
+            card!("7♥"),
+            card!("8♥"),
+        ];
+        let draw_pile = DrawPile::from_cards(cards.clone());
+        assert_eq!(draw_pile.peek_n(2), &[card!("7♥"), card!("8♥")]);
+        assert_eq!(draw_pile.peek_n(10), cards.as_slice());
+
+        let mut state = PlayerState::with_draw_pile(draw_pile);
+        assert_eq!(state.hand.cards(), CardsSet::from_iter(cards[2..].to_vec()));
+        assert_eq!(state.draw_pile.len(), 2);
+        assert_eq!(state.draw_pile.draw(), Some(card!("3♥")));
+        assert_eq!(state.draw_pile.draw(), Some(card!("2♥")));
+        assert_eq!(state.draw_pile.draw(), None);
+    }
+
+    #[test]
+    fn variants_can_have_smaller_decks() {
+        let rules = Rules {
+            lowest_rank: Rank::Seven,
+            ..Rules::STANDARD
+        };
+        let cards = Deck::Color(Color::Red).cards_with_rules(rules);
+        assert_eq!(cards.len(), 16);
+        assert!(cards
+            .iter()
+            .all(|card| card.rank >= Rank::Seven && card.suit.color() == Color::Red));
+        assert_eq!(Deck::Suit(Suit::Spade).cards_with_rules(rules).len(), 8);
+        assert_eq!(Deck::Suit(Suit::Spade).cards().len(), 13);
+    }
+}
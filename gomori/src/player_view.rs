@@ -0,0 +1,149 @@
+use crate::{Board, CardToPlay, CardsSet, Hand, IllegalCardPlayed, Request, Rules};
+
+/// Everything that a player knows about the game when it is asked to play a turn.
+///
+/// The [`Request::PlayTurn`] only contains the cards won by the opponents since the
+/// player's last turn, so the won cards need to be accumulated over the turns of a game,
+/// see [`from_request()`](PlayerView::from_request). `gomori_bot_utils` does this
+/// automatically and passes the view to `Bot::play_turn_view()`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PlayerView {
+    pub board: Board,
+    pub hand: Hand,
+    /// The cards won by the player so far in this game.
+    pub cards_won: CardsSet,
+    /// The cards won by all other players so far in this game.
+    pub cards_won_by_opponents: CardsSet,
+    /// The cards won by all other players since the player's last turn, as in the request.
+    pub new_cards_won_by_opponents: CardsSet,
+    /// See [`Request::PlayTurn`].
+    pub draw_pile_size: usize,
+    /// See [`Request::PlayTurn`].
+    pub opponent_draw_pile_size: usize,
+    /// See [`Request::PlayTurn`].
+    pub turn_number: usize,
+}
+
+impl PlayerView {
+    /// The view for a [`Request::PlayTurn`], or `None` for other requests.
+    ///
+    /// The board follows `rules`, which the judge sends in the [`Request::NewGame`].
+    ///
+    /// `cards_won` and `cards_won_by_opponents` are the cards won before the request, e.g.
+    /// the fields of the view of the player's previous turn, with the cards it won in that
+    /// turn (see [`cards_won_with()`](PlayerView::cards_won_with)) added. The cards won by
+    /// the opponents since then are added from the request.
+    pub fn from_request(
+        req: &Request,
+        rules: Rules,
+        cards_won: CardsSet,
+        cards_won_by_opponents: CardsSet,
+    ) -> Option<Self> {
+        match req {
+            Request::PlayTurn {
+                cards,
+                fields,
+                cards_won_by_opponent,
+                draw_pile_size,
+                opponent_draw_pile_size,
+                turn_number,
+            } => {
+                let new_cards_won_by_opponents =
+                    CardsSet::from_iter(cards_won_by_opponent.iter().copied());
+                Some(Self {
+                    board: Board::new_with_rules(fields, rules),
+                    hand: *cards,
+                    cards_won,
+                    cards_won_by_opponents: cards_won_by_opponents | new_cards_won_by_opponents,
+                    new_cards_won_by_opponents,
+                    draw_pile_size: *draw_pile_size,
+                    opponent_draw_pile_size: *opponent_draw_pile_size,
+                    turn_number: *turn_number,
+                })
+            }
+            _ => None,
+        }
+    }
+
+    /// The cards that the player wins by playing these cards, in order, on the board.
+    pub fn cards_won_with(
+        &self,
+        cards_to_play: &[CardToPlay],
+    ) -> Result<CardsSet, IllegalCardPlayed> {
+        let mut board = self.board.clone();
+        let mut cards_won = CardsSet::new();
+        for &ctp in cards_to_play {
+            let effects = board.calculate(ctp)?;
+            cards_won |= effects.cards_won;
+            board = effects.execute();
+        }
+        Ok(cards_won)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::collections::BTreeSet;
+
+    use crate::{card, Field};
+
+    use super::*;
+
+    #[test]
+    fn won_cards_are_accumulated() {
+        let fields: Vec<_> = [(0, 0, "7♥"), (0, 1, "8♥"), (0, 2, "9♥")]
+            .into_iter()
+            .map(|(i, j, card): (i8, i8, &str)| Field {
+                i,
+                j,
+                top_card: Some(card.parse().unwrap()),
+                hidden_cards: BTreeSet::new(),
+            })
+            .collect();
+        let req = Request::PlayTurn {
+            cards: Hand::try_from([card!("T♥"), card!("2♠")].as_slice()).unwrap(),
+            fields,
+            cards_won_by_opponent: BTreeSet::from([card!("A♣")]),
+            draw_pile_size: 10,
+            opponent_draw_pile_size: 11,
+            turn_number: 7,
+        };
+        let view = PlayerView::from_request(
+            &req,
+            Rules::STANDARD,
+            CardsSet::from_iter([card!("3♦")]),
+            CardsSet::from_iter([card!("4♣")]),
+        )
+        .unwrap();
+        assert_eq!(view.cards_won, CardsSet::from_iter([card!("3♦")]));
+        assert_eq!(
+            view.cards_won_by_opponents,
+            CardsSet::from_iter([card!("4♣"), card!("A♣")])
+        );
+        assert_eq!(
+            view.new_cards_won_by_opponents,
+            CardsSet::from_iter([card!("A♣")])
+        );
+        assert_eq!(view.turn_number, 7);
+
+        let ctp = CardToPlay {
+            card: card!("T♥"),
+            i: 0,
+            j: 3,
+            target_field_for_king_ability: None,
+        };
+        assert_eq!(
+            view.cards_won_with(&[ctp]).unwrap(),
+            CardsSet::from_iter([card!("7♥"), card!("8♥"), card!("9♥")])
+        );
+        assert!(PlayerView::from_request(
+            &Request::Bye {
+                match_context: None
+            },
+            Rules::STANDARD,
+            CardsSet::new(),
+            CardsSet::new()
+        )
+        .is_none());
+    }
+}
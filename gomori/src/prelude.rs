@@ -0,0 +1,6 @@
+//! The items of the standard prelude that are in `alloc`, so that each module can import
+//! them with `use crate::prelude::*;` whether or not the crate is `no_std`.
+
+pub(crate) use alloc::string::{String, ToString};
+pub(crate) use alloc::vec::Vec;
+pub(crate) use alloc::{format, vec};
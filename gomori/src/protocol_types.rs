@@ -1,8 +1,12 @@
-use std::collections::BTreeSet;
+use alloc::collections::BTreeSet;
 
-use serde::{Deserialize, Serialize};
+use serde::de::Error;
+use serde::{Deserialize, Deserializer, Serialize};
 
-use crate::Card;
+use crate::prelude::*;
+use crate::{
+    check_fields_list, Board, Card, CardsSet, CompactField, Hand, Rules, Suit, MAX_BOARD_SIZE,
+};
 
 /// Request for a bot to do something.
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -11,32 +15,159 @@ pub enum Request {
     /// Request to reset the bot's state for a new game.
     ///
     /// The response should be an [`Okay`].
-    NewGame { color: Color },
+    NewGame {
+        /// The color of the player's cards.
+        color: Color,
+        /// The number of players in the game.
+        ///
+        /// If missing from the JSON, it is treated as two.
+        #[serde(default = "two_players")]
+        num_players: usize,
+        /// The player's position in the turn order, i.e. 0 for the player who plays the first turn.
+        ///
+        /// If missing from the JSON, it is treated as 0.
+        #[serde(default)]
+        seat: usize,
+        /// With more than two players, every player only gets the cards of a single suit.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        suit: Option<Suit>,
+        /// How the match has gone for the player before this game, if the game is part of
+        /// a match.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        match_context: Option<MatchContext>,
+        /// The rules of the game, which bots should pass to
+        /// [`Board::new_with_rules()`](crate::Board::new_with_rules) for the boards of the game.
+        /// They also say which cards of the player's deck are played with, see
+        /// [`Deck::cards_with_rules()`](crate::Deck::cards_with_rules).
+        ///
+        /// If missing from the JSON, they are [`Rules::STANDARD`].
+        #[serde(default)]
+        rules: Rules,
+    },
     /// Request to play the first turn.
     ///
     /// The response should be a single [`Card`], as it is impossible to have a
-    /// combo in the first turn. The card will be placed at the coordinates `(0, 0)`.
+    /// combo in the first turn. The card will be placed at the coordinates `(i, j)`.
     PlayFirstTurn {
         /// The hand of the player.
-        cards: [Card; 5],
+        cards: Hand,
+        /// Where the card will be placed.
+        ///
+        /// If missing from the JSON, it is treated as 0.
+        #[serde(default)]
+        i: i8,
+        /// See `i`.
+        #[serde(default)]
+        j: i8,
     },
     /// Request to play the next turn.
     ///
     /// The response should be an [`PlayTurnResponse`].
     PlayTurn {
         /// The hand of the player.
-        cards: [Card; 5],
+        cards: Hand,
         /// The board, represented as a list of the fields that are in use,
         /// i.e. have at least one card on them.
         ///
         /// They are sorted by i first, then j (row-major order, if you think
-        /// of i and j as matrix indices).
+        /// of i and j as matrix indices). When deserializing, this is ensured by
+        /// re-sorting, and the fields are checked to form a valid board (see
+        /// [`Board::try_new()`](crate::Board::try_new)) of at most
+        /// [`MAX_BOARD_SIZE`](crate::MAX_BOARD_SIZE) x [`MAX_BOARD_SIZE`](crate::MAX_BOARD_SIZE),
+        /// since the size depends on the rules of the game. In the standard game, they
+        /// can be passed to [`Board::new_unchecked()`](crate::Board::new_unchecked).
+        #[serde(deserialize_with = "deserialize_board_fields")]
         fields: Vec<Field>,
-        /// The cards won by the opponent in the previous turn.
+        /// The cards won by the opponent in their previous turn, i.e. since this
+        /// player's last turn. With more than two players, these are the cards won by all
+        /// other players since this player's last turn.
+        ///
+        /// This is not cumulative: It is empty if the opponent didn't win any cards
+        /// or skipped their turn, and in the first request of a game. Bots that need
+        /// all cards won by the opponent so far have to accumulate them.
+        /// If missing from the JSON, it is treated as empty.
+        #[serde(default)]
         cards_won_by_opponent: BTreeSet<Card>,
+        /// The number of cards left in the player's draw pile.
+        ///
+        /// If missing from the JSON, it is treated as 0.
+        #[serde(default)]
+        draw_pile_size: usize,
+        /// The number of cards left in the opponent's draw pile. With more than two
+        /// players, this is the sum over all other players.
+        ///
+        /// If missing from the JSON, it is treated as 0.
+        #[serde(default)]
+        opponent_draw_pile_size: usize,
+        /// The number of this turn in the game, where the first turn of the game is 1 and
+        /// skipped turns are counted too. In a two-player game, the first request to play a
+        /// turn is for turn 2.
+        ///
+        /// If missing from the JSON, it is treated as 0.
+        #[serde(default)]
+        turn_number: usize,
     },
     /// The bot should shut down.
-    Bye,
+    ///
+    /// No response is expected.
+    Bye {
+        /// The final score of the match, if the game was part of a match, so that the bot
+        /// learns how the last game ended.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        match_context: Option<MatchContext>,
+    },
+}
+
+fn two_players() -> usize {
+    2
+}
+
+/// The score of a match before a game, from the perspective of the player it is sent to.
+///
+/// Sent in the [`Request::NewGame`] request, e.g. for bots that play more riskily when
+/// they are behind.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MatchContext {
+    /// The number of games that the player won.
+    pub wins: usize,
+    /// The number of games that another player won.
+    pub losses: usize,
+    /// The number of games that nobody won.
+    pub ties: usize,
+    /// The number of games in the whole match, if it is known in advance. With a
+    /// sequential probability ratio test, the match may end earlier.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub num_games: Option<usize>,
+}
+
+impl MatchContext {
+    /// The number of games of the match played so far.
+    pub fn games_played(&self) -> usize {
+        self.wins + self.losses + self.ties
+    }
+
+    /// Counts a game that was won by the player with the given index, or by nobody, from
+    /// the perspective of the player with index `player_idx`.
+    pub fn record_game(&mut self, player_idx: usize, winner: Option<usize>) {
+        match winner {
+            Some(winner_idx) if winner_idx == player_idx => self.wins += 1,
+            Some(_) => self.losses += 1,
+            None => self.ties += 1,
+        }
+    }
+}
+
+fn deserialize_board_fields<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Vec<Field>, D::Error> {
+    let mut fields = Vec::<Field>::deserialize(deserializer)?;
+    fields.sort_by_key(|f| (f.i, f.j));
+    let fields_list: Vec<_> = fields
+        .iter()
+        .map(|f| (f.i, f.j, CompactField::from(f)))
+        .collect();
+    check_fields_list(&fields_list, MAX_BOARD_SIZE).map_err(D::Error::custom)?;
+    Ok(fields)
 }
 
 /// Dummy struct for use in bot communication.
@@ -45,6 +176,37 @@ pub enum Request {
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Okay();
 
+/// The encoding of the messages between the judge and a bot.
+///
+/// The judge tells a bot process which one to use through the environment
+/// variable [`Protocol::ENV_VAR`]. If it is not set, JSON is used.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Protocol {
+    /// One JSON message per line.
+    #[default]
+    Json,
+    /// MessagePack messages, each preceded by its length in bytes as a
+    /// 32-bit big-endian integer.
+    ///
+    /// Structs are encoded as maps, like in the JSON protocol.
+    MsgPack,
+}
+
+impl Protocol {
+    /// The environment variable through which the judge tells a bot process
+    /// which protocol to use. Its value is the lowercase name, e.g. `msgpack`.
+    pub const ENV_VAR: &'static str = "GOMORI_PROTOCOL";
+
+    /// The lowercase name, as used in player configs and in [`Protocol::ENV_VAR`].
+    pub fn name(&self) -> &'static str {
+        match self {
+            Protocol::Json => "json",
+            Protocol::MsgPack => "msgpack",
+        }
+    }
+}
+
 /// Black or white.
 #[cfg_attr(feature = "python", pyo3::pyclass)]
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
@@ -56,9 +218,21 @@ pub enum Color {
     Red,
 }
 
+impl Color {
+    /// The two suits of this color, in the order of [`Suit`]'s variants.
+    pub fn suits(self) -> [Suit; 2] {
+        match self {
+            Color::Black => [Suit::Spade, Suit::Club],
+            Color::Red => [Suit::Diamond, Suit::Heart],
+        }
+    }
+}
+
 /// A single field on the board, including coordinates.
-#[cfg_attr(feature = "python", pyo3::pyclass(get_all, set_all))]
-#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+///
+/// Fields are ordered by `i` first, then `j`, like in [`Board::to_fields_vec()`](crate::Board::to_fields_vec).
+#[cfg_attr(feature = "python", pyo3::pyclass(module = "gomori", get_all, set_all))]
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
 pub struct Field {
     /// The first coordinate.
     pub i: i8,
@@ -75,8 +249,8 @@ pub struct Field {
 /// Specifies which card to play, and where.
 ///
 /// Used in a [`PlayTurnResponse`], and in [`calculate()`](crate::Board::calculate).
-#[cfg_attr(feature = "python", pyo3::pyclass)]
-#[derive(Copy, Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "python", pyo3::pyclass(module = "gomori"))]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
 pub struct CardToPlay {
     pub card: Card,
     pub i: i8,
@@ -89,21 +263,162 @@ pub struct CardToPlay {
     pub target_field_for_king_ability: Option<(i8, i8)>,
 }
 
-/// The cards to play in this turn, in order.
+/// What a player does in a turn.
+///
+/// In JSON, skipping is represented as `"skip"`, and playing as the list of cards to play.
+/// For backward compatibility, an empty list is also accepted as a skip.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(from = "TurnActionRepr", into = "TurnActionRepr")]
+pub enum TurnAction {
+    /// Skips the turn, which is only legal if none of the cards in the hand can be played.
+    Skip,
+    /// Plays these cards, in order.
+    Play(Vec<CardToPlay>),
+}
+
+impl TurnAction {
+    /// The cards to play, which are none if the turn is skipped.
+    pub fn cards_to_play(&self) -> &[CardToPlay] {
+        match self {
+            TurnAction::Skip => &[],
+            TurnAction::Play(cards_to_play) => cards_to_play,
+        }
+    }
+
+    /// Whether the turn is skipped, explicitly or with an empty list of cards.
+    pub fn is_skip(&self) -> bool {
+        self.cards_to_play().is_empty()
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+enum TurnActionRepr {
+    Skip(SkipKeyword),
+    Play(Vec<CardToPlay>),
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum SkipKeyword {
+    Skip,
+}
+
+impl From<TurnActionRepr> for TurnAction {
+    fn from(repr: TurnActionRepr) -> Self {
+        match repr {
+            TurnActionRepr::Skip(SkipKeyword::Skip) => TurnAction::Skip,
+            TurnActionRepr::Play(cards_to_play) => TurnAction::Play(cards_to_play),
+        }
+    }
+}
+
+impl From<TurnAction> for TurnActionRepr {
+    fn from(action: TurnAction) -> Self {
+        match action {
+            TurnAction::Skip => TurnActionRepr::Skip(SkipKeyword::Skip),
+            TurnAction::Play(cards_to_play) => TurnActionRepr::Play(cards_to_play),
+        }
+    }
+}
+
+/// The response to a [`Request::PlayTurn`].
 #[cfg_attr(feature = "python", pyo3::pyclass)]
-#[derive(Clone, Debug, Serialize, Deserialize)]
-pub struct PlayTurnResponse(pub Vec<CardToPlay>);
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PlayTurnResponse(pub TurnAction);
+
+impl PlayTurnResponse {
+    pub fn skip() -> Self {
+        Self(TurnAction::Skip)
+    }
+
+    pub fn play(cards_to_play: Vec<CardToPlay>) -> Self {
+        Self(TurnAction::Play(cards_to_play))
+    }
+
+    /// Skips the turn if none of the cards in the hand can be played on the board, and plays
+    /// the cards otherwise.
+    ///
+    /// Whether there are any cards to play doesn't matter, so that a bot that finds no card
+    /// to play although it could play one makes an illegal move, instead of skipping.
+    pub fn play_or_skip(board: &Board, hand: CardsSet, cards_to_play: Vec<CardToPlay>) -> Self {
+        if board.must_skip(hand) {
+            Self::skip()
+        } else {
+            Self::play(cards_to_play)
+        }
+    }
+
+    /// See [`TurnAction::cards_to_play()`].
+    pub fn cards_to_play(&self) -> &[CardToPlay] {
+        self.0.cards_to_play()
+    }
+
+    /// See [`TurnAction::is_skip()`].
+    pub fn is_skip(&self) -> bool {
+        self.0.is_skip()
+    }
+}
 
 #[cfg(feature = "python")]
 mod python {
-    use pyo3::pymethods;
+    use core::hash::{Hash, Hasher};
+    use std::collections::hash_map::DefaultHasher;
+
+    use pyo3::pyclass::CompareOp;
+    use pyo3::{pymethods, IntoPy, PyObject, Python};
 
     use super::*;
 
+    // Supports only equality, since there is no meaningful order.
+    fn richcmp_eq<T: PartialEq>(py: Python, a: &T, b: &T, op: CompareOp) -> PyObject {
+        match op {
+            CompareOp::Eq => (a == b).into_py(py),
+            CompareOp::Ne => (a != b).into_py(py),
+            _ => py.NotImplemented(),
+        }
+    }
+
+    fn hash<T: Hash>(value: &T) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        value.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    #[pymethods]
+    impl Field {
+        #[new]
+        #[pyo3(signature = (i, j, top_card=None, hidden_cards=BTreeSet::new()))]
+        fn py_new(i: i8, j: i8, top_card: Option<Card>, hidden_cards: BTreeSet<Card>) -> Self {
+            Self {
+                i,
+                j,
+                top_card,
+                hidden_cards,
+            }
+        }
+
+        fn __richcmp__(&self, py: Python, other: &Self, op: CompareOp) -> PyObject {
+            richcmp_eq(py, self, other, op)
+        }
+
+        fn __hash__(&self) -> u64 {
+            hash(self)
+        }
+
+        #[allow(clippy::type_complexity)]
+        fn __reduce__(&self, py: Python) -> (PyObject, (i8, i8, Option<Card>, BTreeSet<Card>)) {
+            (
+                py.get_type::<Field>().into(),
+                (self.i, self.j, self.top_card, self.hidden_cards.clone()),
+            )
+        }
+    }
+
     #[pymethods]
     impl CardToPlay {
         #[new]
-        #[pyo3(signature = (*, card, i, j, target_field_for_king_ability=None))]
+        #[pyo3(signature = (card, i, j, target_field_for_king_ability=None))]
         fn py_new(
             card: Card,
             i: i8,
@@ -117,13 +432,270 @@ mod python {
                 target_field_for_king_ability,
             }
         }
+
+        fn __richcmp__(&self, py: Python, other: &Self, op: CompareOp) -> PyObject {
+            richcmp_eq(py, self, other, op)
+        }
+
+        fn __hash__(&self) -> u64 {
+            hash(self)
+        }
+
+        #[allow(clippy::type_complexity)]
+        fn __reduce__(&self, py: Python) -> (PyObject, (Card, i8, i8, Option<(i8, i8)>)) {
+            (
+                py.get_type::<CardToPlay>().into(),
+                (
+                    self.card,
+                    self.i,
+                    self.j,
+                    self.target_field_for_king_ability,
+                ),
+            )
+        }
+    }
+
+    #[pymethods]
+    impl Color {
+        #[pyo3(name = "suits")]
+        fn py_suits(&self) -> [Suit; 2] {
+            self.suits()
+        }
     }
 
     #[pymethods]
     impl PlayTurnResponse {
         #[new]
         fn py_new(cards_to_play: Vec<CardToPlay>) -> Self {
-            Self(cards_to_play)
+            Self::play(cards_to_play)
         }
+
+        #[staticmethod]
+        #[pyo3(name = "skip")]
+        fn py_skip() -> Self {
+            Self::skip()
+        }
+
+        #[getter]
+        #[pyo3(name = "cards_to_play")]
+        fn py_cards_to_play(&self) -> Vec<CardToPlay> {
+            self.cards_to_play().to_vec()
+        }
+
+        #[pyo3(name = "is_skip")]
+        fn py_is_skip(&self) -> bool {
+            self.is_skip()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::card;
+
+    fn play_turn_json(coordinates: &[(i8, i8)]) -> String {
+        let fields = coordinates
+            .iter()
+            .zip([card!("A♠"), card!("2♠"), card!("3♠")])
+            .map(|(&(i, j), card)| Field {
+                i,
+                j,
+                top_card: Some(card),
+                hidden_cards: BTreeSet::new(),
+            })
+            .collect();
+        let request = Request::PlayTurn {
+            cards: Hand::new().insert(card!("2♦")).unwrap(),
+            fields,
+            cards_won_by_opponent: BTreeSet::new(),
+            draw_pile_size: 0,
+            opponent_draw_pile_size: 0,
+            turn_number: 0,
+        };
+        serde_json::to_string(&request).unwrap()
+    }
+
+    #[test]
+    fn new_game_defaults_to_two_players() {
+        let request: Request = serde_json::from_str(r#"{"type":"NewGame","color":"red"}"#).unwrap();
+        assert!(matches!(
+            request,
+            Request::NewGame {
+                color: Color::Red,
+                num_players: 2,
+                seat: 0,
+                suit: None,
+                match_context: None,
+                rules: Rules::STANDARD,
+            }
+        ));
+    }
+
+    #[test]
+    fn new_game_tells_the_rules() {
+        let rules = Rules {
+            line_length: 3,
+            aces_are_wild: false,
+            ..Rules::STANDARD
+        };
+        let request = Request::NewGame {
+            color: Color::Black,
+            num_players: 2,
+            seat: 0,
+            suit: None,
+            match_context: None,
+            rules,
+        };
+        let json = serde_json::to_string(&request).unwrap();
+        let Request::NewGame { rules: sent, .. } = serde_json::from_str(&json).unwrap() else {
+            panic!("Expected a NewGame request");
+        };
+        assert_eq!(sent, rules);
+
+        // Missing rules are those of the standard game, e.g. the deck from before it could
+        // be configured
+        let json = r#"{"board_size":3,"line_length":3,"aces_are_wild":true,"jack_ability":true,"queen_ability":true,"king_ability":true}"#;
+        assert_eq!(
+            serde_json::from_str::<Rules>(json).unwrap(),
+            Rules {
+                board_size: 3,
+                line_length: 3,
+                ..Rules::STANDARD
+            }
+        );
+    }
+
+    #[test]
+    fn match_context_is_optional() {
+        let mut context = MatchContext {
+            num_games: Some(10),
+            ..Default::default()
+        };
+        context.record_game(1, Some(1));
+        context.record_game(1, Some(0));
+        context.record_game(1, None);
+        assert_eq!(context.games_played(), 3);
+        let request = Request::NewGame {
+            color: Color::Black,
+            num_players: 2,
+            seat: 1,
+            suit: None,
+            match_context: Some(context),
+            rules: Rules::STANDARD,
+        };
+        let json = serde_json::to_string(&request).unwrap();
+        assert!(json.contains(r#""match_context":{"wins":1,"losses":1,"ties":1,"num_games":10},"#));
+        let Request::NewGame { match_context, .. } = serde_json::from_str(&json).unwrap() else {
+            panic!("Expected a NewGame request");
+        };
+        assert_eq!(match_context, Some(context));
+    }
+
+    #[test]
+    fn first_card_defaults_to_the_origin() {
+        let request = Request::PlayFirstTurn {
+            cards: Hand::new().insert(card!("2♦")).unwrap(),
+            i: 1,
+            j: -2,
+        };
+        let json = serde_json::to_string(&request).unwrap();
+        assert!(json.ends_with(r#","i":1,"j":-2}"#));
+        let without_location = json.replace(r#","i":1,"j":-2"#, "");
+        assert!(matches!(
+            serde_json::from_str(&without_location).unwrap(),
+            Request::PlayFirstTurn { i: 0, j: 0, .. }
+        ));
+    }
+
+    #[test]
+    fn play_turn_fields_are_sorted_and_validated() {
+        let request = play_turn_json(&[(1, 0), (0, 1), (0, 0)]);
+        let Request::PlayTurn { fields, .. } = serde_json::from_str(&request).unwrap() else {
+            panic!("Expected a PlayTurn request");
+        };
+        let coordinates: Vec<_> = fields.iter().map(|f| (f.i, f.j)).collect();
+        assert_eq!(coordinates, vec![(0, 0), (0, 1), (1, 0)]);
+
+        let request = play_turn_json(&[(0, 1), (0, 0), (0, 1)]);
+        let err = serde_json::from_str::<Request>(&request).unwrap_err();
+        assert!(err.to_string().contains("more than one field at (0, 1)"));
+
+        // The board may be from a variant with a larger board
+        let request = play_turn_json(&[(0, 0), (0, 4)]);
+        assert!(serde_json::from_str::<Request>(&request).is_ok());
+        let request = play_turn_json(&[(0, 0), (0, 5)]);
+        let err = serde_json::from_str::<Request>(&request).unwrap_err();
+        assert!(err.to_string().contains("don't fit in a 5 x 5 area"));
+    }
+
+    #[test]
+    fn game_phase_defaults_to_zero() {
+        let request = play_turn_json(&[(0, 0)]);
+        let suffix = r#","draw_pile_size":0,"opponent_draw_pile_size":0,"turn_number":0}"#;
+        assert!(request.ends_with(suffix));
+        let old_request = request.replace(suffix, "}");
+        assert!(matches!(
+            serde_json::from_str(&old_request).unwrap(),
+            Request::PlayTurn {
+                draw_pile_size: 0,
+                opponent_draw_pile_size: 0,
+                turn_number: 0,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn turn_actions_are_skip_or_a_list_of_cards() {
+        let ctp = CardToPlay {
+            card: card!("2♦"),
+            i: 0,
+            j: 1,
+            target_field_for_king_ability: None,
+        };
+        for (response, json) in [
+            (PlayTurnResponse::skip(), r#""skip""#),
+            (
+                PlayTurnResponse::play(vec![ctp]),
+                format!("[{}]", serde_json::to_string(&ctp).unwrap()).as_str(),
+            ),
+            (PlayTurnResponse::play(vec![]), "[]"),
+        ] {
+            assert_eq!(serde_json::to_string(&response).unwrap(), json);
+            assert_eq!(
+                serde_json::from_str::<PlayTurnResponse>(json).unwrap(),
+                response
+            );
+        }
+        assert!(PlayTurnResponse::play(vec![]).is_skip());
+        assert!(serde_json::from_str::<PlayTurnResponse>(r#""pass""#).is_err());
+    }
+
+    /// A bot that finds no card to play, although it could play one, doesn't skip.
+    #[test]
+    fn play_or_skip_only_skips_if_no_card_can_be_played() {
+        let board = Board::new(&[Field {
+            i: 0,
+            j: 0,
+            top_card: Some(card!("7♥")),
+            hidden_cards: BTreeSet::new(),
+        }]);
+        let hand = CardsSet::from_iter([card!("2♠")]);
+        let response = PlayTurnResponse::play_or_skip(&board, hand, vec![]);
+        assert_eq!(response, PlayTurnResponse::play(vec![]));
+        let state = crate::PlayerState {
+            draw_pile: Default::default(),
+            hand: Hand::try_from([card!("2♠")].as_slice()).unwrap(),
+            cards_won: CardsSet::new(),
+        };
+        assert!(matches!(
+            crate::execute_turn(&state, &board, response),
+            Err(crate::IllegalMove::PlayedZeroCards)
+        ));
+        assert_eq!(
+            PlayTurnResponse::play_or_skip(&board, CardsSet::new(), vec![]),
+            PlayTurnResponse::skip()
+        );
     }
 }
@@ -0,0 +1,526 @@
+//! Variants of the game rules, see [`Rules`], and their description as text.
+
+use alloc::collections::BTreeSet;
+use core::fmt::Write;
+
+use serde::{Deserialize, Serialize};
+
+use crate::prelude::*;
+use crate::{
+    Board, Card, CardToPlay, CardsSet, Field, Hand, InvalidRules, Rank, Suit, BLACK_CARDS_SET,
+    BOARD_SIZE, MAX_BOARD_SIZE, RED_CARDS_SET,
+};
+
+/// The rules of the standard game as text, see [`Rules::describe()`] for variants.
+pub fn describe() -> String {
+    Rules::STANDARD.describe()
+}
+
+/// The parameters of the game rules, for experimenting with variants of the game.
+///
+/// The default is the standard game. A [`Board`](crate::Board) created with
+/// [`Board::new_with_rules()`](crate::Board::new_with_rules) follows these rules, and so do
+/// all boards that result from playing cards on it.
+///
+/// The judge tells bots about the variant in the [`Request::NewGame`](crate::Request::NewGame),
+/// since the fields of the board don't say which rules they follow. Fields that are missing
+/// from the JSON are those of the standard game.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Rules {
+    /// The width and height of the area that all cards must fit into.
+    ///
+    /// [`BOARD_SIZE`] in the standard game, and at most [`MAX_BOARD_SIZE`], e.g. 5 for a
+    /// 5 x 5 board.
+    pub board_size: i8,
+    /// How many cards of the same suit in a row complete a line.
+    ///
+    /// Between 2 and `board_size`.
+    pub line_length: u8,
+    /// Whether an ace may be played on any card, instead of only on other aces.
+    pub aces_are_wild: bool,
+    /// Whether a jack played on another card flips its horizontal and vertical neighbors.
+    pub jack_ability: bool,
+    /// Whether a queen played on another card flips its diagonal neighbors.
+    pub queen_ability: bool,
+    /// Whether a king played on another card flips a card of the player's choice.
+    pub king_ability: bool,
+    /// The lowest rank of the deck, which has all cards of this rank and higher, see
+    /// [`cards()`](Rules::cards).
+    ///
+    /// The standard deck has all 52 cards from two to ace, and e.g. [`Rank::Seven`] leaves
+    /// the 32 cards from seven to ace. At most [`Rank::Ten`], since every player needs
+    /// enough cards for a full hand, even with four players who only get a suit each.
+    pub lowest_rank: Rank,
+}
+
+impl Rules {
+    /// The rules of the standard game.
+    pub const STANDARD: Rules = Rules {
+        board_size: BOARD_SIZE,
+        line_length: BOARD_SIZE as u8,
+        aces_are_wild: true,
+        jack_ability: true,
+        queen_ability: true,
+        king_ability: true,
+        lowest_rank: Rank::Two,
+    };
+
+    pub fn validate(&self) -> Result<(), InvalidRules> {
+        if !(2..=MAX_BOARD_SIZE).contains(&self.board_size) {
+            return Err(InvalidRules::BoardSizeOutOfRange {
+                board_size: self.board_size,
+            });
+        }
+        if !(2..=self.board_size as u8).contains(&self.line_length) {
+            return Err(InvalidRules::LineLengthOutOfRange {
+                line_length: self.line_length,
+            });
+        }
+        if self.lowest_rank > Rank::Ten {
+            return Err(InvalidRules::LowestRankTooHigh {
+                lowest_rank: self.lowest_rank,
+            });
+        }
+        Ok(())
+    }
+
+    /// The cards of the deck, i.e. all cards of the [`lowest_rank`](Rules::lowest_rank)
+    /// and higher.
+    ///
+    /// The players get the cards of their [`Deck`](crate::Deck) that are in it, see
+    /// [`Deck::cards_with_rules()`](crate::Deck::cards_with_rules).
+    pub fn cards(&self) -> CardsSet {
+        (RED_CARDS_SET | BLACK_CARDS_SET)
+            .into_iter()
+            .filter(|card| card.rank >= self.lowest_rank)
+            .collect()
+    }
+
+    /// Whether `card` may be played on top of `other`.
+    ///
+    /// With the standard rules, this is the same as [`Card::can_be_placed_on()`].
+    pub fn can_be_placed_on(&self, card: Card, other: Card) -> bool {
+        if card.rank == Rank::Ace && !self.aces_are_wild {
+            other.rank == Rank::Ace
+        } else {
+            card.can_be_placed_on(other)
+        }
+    }
+
+    /// Whether cards of this rank have an ability when played on top of another card.
+    pub fn has_ability(&self, rank: Rank) -> bool {
+        match rank {
+            Rank::Jack => self.jack_ability,
+            Rank::Queen => self.queen_ability,
+            Rank::King => self.king_ability,
+            _ => false,
+        }
+    }
+
+    /// The rules of the game as text, e.g. to show them to players of a variant.
+    ///
+    /// The text is generated from the rules engine where possible, e.g. the placement table
+    /// from [`can_be_placed_on()`](Rules::can_be_placed_on) and the fields that jacks and
+    /// queens flip from playing them on a board, so that it can't drift from the engine.
+    pub fn describe(&self) -> String {
+        let mut text = String::new();
+        self.write_description(&mut text)
+            .expect("Writing to a String can't fail");
+        text
+    }
+
+    fn write_description(&self, text: &mut String) -> core::fmt::Result {
+        let n = self.board_size;
+        writeln!(text, "Cards")?;
+        writeln!(
+            text,
+            "  The deck has the {} cards from {} to A of each suit. With two players, each \
+             player gets the cards of one color, and otherwise the cards of one suit.",
+            self.cards().len(),
+            self.lowest_rank
+        )?;
+        writeln!(text)?;
+        writeln!(text, "Board")?;
+        writeln!(
+            text,
+            "  All cards on the table must fit into an area of {} x {} fields. A card may be \
+             played on any empty field within it, or on top of another card as listed below.",
+            n, n
+        )?;
+        writeln!(text)?;
+        writeln!(text, "Placement on top of another card")?;
+        for rank in Rank::ALL {
+            writeln!(
+                text,
+                "  {:<2} on {}",
+                rank.to_string(),
+                self.describe_placement(rank)
+            )?;
+        }
+        writeln!(text)?;
+        writeln!(text, "Turns and combos")?;
+        writeln!(
+            text,
+            "  Players have {} cards in their hand. Playing a card on top of another card is \
+             a combo, after which the player must play another card if possible, up to {} \
+             cards in a turn. Playing a card on an empty field ends the turn. A player who \
+             can't play any card skips the turn. After the turn, the hand is refilled from \
+             the draw pile.",
+            Hand::MAX_SIZE,
+            Hand::MAX_SIZE
+        )?;
+        writeln!(text)?;
+        writeln!(text, "Abilities, when played on top of another card")?;
+        let mut any_ability = false;
+        for rank in [Rank::Jack, Rank::Queen] {
+            if self.has_ability(rank) {
+                any_ability = true;
+                writeln!(
+                    text,
+                    "  {} flips the top cards of these fields face down:",
+                    rank
+                )?;
+                *text += &self.flip_diagram(rank);
+            }
+        }
+        if self.has_ability(Rank::King) {
+            any_ability = true;
+            writeln!(text, "  {}", self.describe_king_ability())?;
+        }
+        if !any_ability {
+            writeln!(text, "  None")?;
+        }
+        writeln!(text)?;
+        writeln!(text, "Winning cards")?;
+        let line = if self.line_length == n as u8 {
+            format!("a line of {} cards", self.line_length)
+        } else {
+            format!("a line of {} or more cards", self.line_length)
+        };
+        writeln!(
+            text,
+            "  A card that completes {} of its suit in a row, horizontally, vertically or \
+             diagonally, wins the other cards in the line, including the face-down cards \
+             below them. Cards flipped face down by the same card don't count.",
+            line
+        )?;
+        writeln!(
+            text,
+            "  The game ends when a player can't refill their hand, and whoever won the most \
+             cards wins."
+        )
+    }
+
+    // The cards that a card of this rank may be played on, as text
+    fn describe_placement(&self, rank: Rank) -> String {
+        let card = Card {
+            suit: Suit::Heart,
+            rank,
+        };
+        let on_any_suit: Vec<Rank> = Rank::ALL
+            .into_iter()
+            .filter(|&other| {
+                let other = Card {
+                    suit: Suit::Spade,
+                    rank: other,
+                };
+                self.can_be_placed_on(card, other)
+            })
+            .collect();
+        if on_any_suit.len() == Rank::ALL.len() {
+            return String::from("any card");
+        }
+        let on_same_suit: Vec<Rank> = Rank::ALL
+            .into_iter()
+            .filter(|&other| {
+                let other = Card {
+                    suit: Suit::Heart,
+                    rank: other,
+                };
+                !on_any_suit.contains(&other.rank) && self.can_be_placed_on(card, other)
+            })
+            .collect();
+        let join = |ranks: &[Rank]| {
+            let names: Vec<String> = ranks.iter().map(|rank| rank.to_string()).collect();
+            names.join(" or ")
+        };
+        let mut parts = Vec::new();
+        if !on_any_suit.is_empty() {
+            parts.push(format!("any {}", join(&on_any_suit)));
+        }
+        if on_same_suit.len() + on_any_suit.len() == Rank::ALL.len() {
+            parts.push(String::from("any other card of the same suit"));
+        } else if !on_same_suit.is_empty() {
+            parts.push(format!("a {} of the same suit", join(&on_same_suit)));
+        }
+        if parts.is_empty() {
+            String::from("nothing")
+        } else {
+            parts.join(", or ")
+        }
+    }
+
+    // A 3 x 3 board of spades around a diamond, so that no line is completed. If
+    // `face_down_corner` is true, the card at (0, 0) is face down.
+    fn sample_board(&self, face_down_corner: bool) -> Board {
+        let rules = Rules {
+            board_size: BOARD_SIZE,
+            line_length: BOARD_SIZE as u8,
+            ..*self
+        };
+        let mut other_ranks = Rank::ALL.into_iter();
+        let fields: Vec<Field> = (0..3)
+            .flat_map(|i| (0..3).map(move |j| (i, j)))
+            .map(|(i, j)| {
+                let card = if (i, j) == (1, 1) {
+                    Card {
+                        suit: Suit::Diamond,
+                        rank: Rank::Two,
+                    }
+                } else {
+                    Card {
+                        suit: Suit::Spade,
+                        rank: other_ranks.next().unwrap(),
+                    }
+                };
+                if face_down_corner && (i, j) == (0, 0) {
+                    Field {
+                        i,
+                        j,
+                        top_card: None,
+                        hidden_cards: BTreeSet::from([card]),
+                    }
+                } else {
+                    Field {
+                        i,
+                        j,
+                        top_card: Some(card),
+                        hidden_cards: BTreeSet::new(),
+                    }
+                }
+            })
+            .collect();
+        Board::new_with_rules(&fields, rules)
+    }
+
+    // Plays a king in the middle of the sample board, and describes which fields it may
+    // target
+    fn describe_king_ability(&self) -> String {
+        let board = self.sample_board(true);
+        let may_target = |i, j| {
+            board
+                .calculate(CardToPlay {
+                    card: Card {
+                        suit: Suit::Diamond,
+                        rank: Rank::King,
+                    },
+                    i: 1,
+                    j: 1,
+                    target_field_for_king_ability: Some((i, j)),
+                })
+                .is_ok()
+        };
+        let face_up = if may_target(0, 0) {
+            "face up or down"
+        } else {
+            "face up"
+        };
+        let itself = if may_target(1, 1) {
+            " or the king itself"
+        } else {
+            ""
+        };
+        format!(
+            "K flips the top card of one field of the player's choice face down, which must \
+             be {}{}.",
+            face_up, itself
+        )
+    }
+
+    // Plays a card of this rank in the middle of the sample board, and draws which fields it
+    // flips
+    fn flip_diagram(&self, rank: Rank) -> String {
+        let board = self.sample_board(false);
+        let card_to_play = CardToPlay {
+            card: Card {
+                suit: Suit::Diamond,
+                rank,
+            },
+            i: 1,
+            j: 1,
+            target_field_for_king_ability: None,
+        };
+        let flipped = board
+            .calculate(card_to_play)
+            .expect("The card fits on a card of its suit")
+            .flipped_fields();
+        let mut diagram = String::new();
+        for i in 0..3 {
+            diagram += "   ";
+            for j in 0..3 {
+                let symbol = if (i, j) == (1, 1) {
+                    rank.to_string()
+                } else if flipped.contains(i, j) {
+                    String::from("x")
+                } else {
+                    String::from(".")
+                };
+                diagram += &format!(" {}", symbol);
+            }
+            diagram += "\n";
+        }
+        diagram
+    }
+}
+
+impl Default for Rules {
+    fn default() -> Self {
+        Self::STANDARD
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{card, ctp, field, Board, CardsSet, IllegalCardPlayed, InvalidBoard};
+
+    #[test]
+    fn variant() {
+        let rules = Rules {
+            board_size: 3,
+            line_length: 3,
+            aces_are_wild: false,
+            jack_ability: false,
+            ..Rules::STANDARD
+        };
+        let fields = [
+            field(0, 0, card!("2♥")),
+            field(0, 1, card!("3♥")),
+            field(1, 1, card!("7♠")),
+        ];
+        let board = Board::new_with_rules(&fields, rules);
+        assert_eq!(
+            board.calculate(ctp(card!("4♥"), 0, 3)).err(),
+            Some(IllegalCardPlayed::OutOfBounds)
+        );
+        let effects = board.calculate(ctp(card!("4♥"), 0, 2)).unwrap();
+        assert_eq!(
+            effects.cards_won,
+            CardsSet::from_iter([card!("2♥"), card!("3♥")])
+        );
+        assert_eq!(
+            board.calculate(ctp(card!("A♠"), 1, 1)).err(),
+            Some(IllegalCardPlayed::IncompatibleCard {
+                existing_card: card!("7♠")
+            })
+        );
+        let effects = board.calculate(ctp(card!("J♠"), 1, 1)).unwrap();
+        assert!(effects.combo);
+        assert!(effects.flipped_fields().is_empty());
+        // The resulting board keeps the rules
+        assert_eq!(effects.execute().rules(), rules);
+
+        let too_long = Rules {
+            line_length: 4,
+            ..rules
+        };
+        assert_eq!(
+            Board::try_new_with_rules(&fields, too_long).err(),
+            Some(InvalidBoard::InvalidRules(
+                InvalidRules::LineLengthOutOfRange { line_length: 4 }
+            ))
+        );
+
+        // Every player must have enough cards for a hand
+        let small_deck = Rules {
+            lowest_rank: Rank::Ten,
+            ..rules
+        };
+        assert_eq!(small_deck.validate(), Ok(()));
+        assert_eq!(small_deck.cards().len(), 20);
+        assert_eq!(
+            Rules {
+                lowest_rank: Rank::Jack,
+                ..rules
+            }
+            .validate(),
+            Err(InvalidRules::LowestRankTooHigh {
+                lowest_rank: Rank::Jack
+            })
+        );
+    }
+
+    #[test]
+    fn five_by_five() {
+        let rules = Rules {
+            board_size: 5,
+            ..Rules::STANDARD
+        };
+        let fields = [
+            field(0, 0, card!("2♥")),
+            field(0, 2, card!("4♥")),
+            field(0, 4, card!("6♥")),
+        ];
+        assert_eq!(
+            Board::try_new(&fields).err(),
+            Some(InvalidBoard::TooLarge { board_size: 4 })
+        );
+        let board = Board::new_with_rules(&fields, rules);
+        assert_eq!(
+            board.calculate(ctp(card!("7♥"), 0, 5)).err(),
+            Some(IllegalCardPlayed::OutOfBounds)
+        );
+        // Four cards of the suit in the same row are not a line if they aren't next to
+        // each other
+        let effects = board.calculate(ctp(card!("3♥"), 0, 1)).unwrap();
+        assert!(effects.cards_won.is_empty());
+        let board = effects.execute();
+        let effects = board.calculate(ctp(card!("5♥"), 0, 3)).unwrap();
+        assert_eq!(effects.cards_won.len(), 4);
+
+        // Around the first card, the whole area of the bitboards is playable
+        let first_card = Board::new_with_rules(&fields[..1], rules);
+        assert_eq!(first_card.open_placements_count(), 9 * 9 - 1);
+
+        let too_large = Rules {
+            board_size: 6,
+            ..rules
+        };
+        assert_eq!(
+            too_large.validate(),
+            Err(InvalidRules::BoardSizeOutOfRange { board_size: 6 })
+        );
+    }
+
+    #[test]
+    fn description_follows_the_rules() {
+        let standard = Rules::STANDARD.describe();
+        assert!(standard.contains("The deck has the 52 cards from 2 to A of each suit."));
+        assert!(standard.contains("area of 4 x 4 fields"));
+        assert!(standard.contains("\n  2  on any 2\n"));
+        assert!(standard.contains("\n  J  on any J, or any other card of the same suit\n"));
+        assert!(standard.contains("\n  A  on any card\n"));
+        assert!(standard.contains("\n    . x .\n    x J x\n    . x .\n"));
+        assert!(standard.contains("\n    x . x\n    . Q .\n    x . x\n"));
+        assert!(standard.contains("must be face up or the king itself."));
+        assert_eq!(describe(), standard);
+        assert!(standard.contains("completes a line of 4 cards of its suit"));
+
+        let variant = Rules {
+            board_size: 3,
+            line_length: 2,
+            aces_are_wild: false,
+            jack_ability: false,
+            queen_ability: false,
+            king_ability: false,
+            lowest_rank: Rank::Seven,
+        }
+        .describe();
+        assert!(variant.contains("area of 3 x 3 fields"));
+        assert!(variant.contains("The deck has the 32 cards from 7 to A of each suit."));
+        assert!(variant.contains("\n  A  on any A\n"));
+        assert!(variant.contains("played on top of another card\n  None\n"));
+        assert!(variant.contains("completes a line of 2 or more cards of its suit"));
+    }
+}
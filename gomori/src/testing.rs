@@ -0,0 +1,236 @@
+use quickcheck::{Arbitrary, Gen};
+use rand::rngs::StdRng;
+use rand::seq::{IteratorRandom, SliceRandom};
+use rand::{Rng, SeedableRng};
+
+use crate::{
+    card, execute_first_turn, execute_turn, Board, Card, CardToPlay, CardsSet, Color, Field, Hand,
+    PlayTurnResponse, PlayerState, PlayerView, Rank, Suit, TurnOutcome, TurnValidator,
+};
+
+/// A game in progress, from the perspective of the player whose turn it is.
+///
+/// Generated by [`random_game_in_progress()`], or through its [`Arbitrary`] implementation.
+#[derive(Clone, Debug)]
+pub struct GameInProgress {
+    pub board: Board,
+    /// The player who plays the next turn.
+    pub player: PlayerState,
+    pub opponent: PlayerState,
+}
+
+/// Simulates a game with random legal turns and stops it at a random point before its end.
+///
+/// Unlike boards made up of random fields, the result is a position that can really occur,
+/// e.g. the draw piles, hands, won cards and the cards on the board are disjoint and together
+/// make up both decks.
+pub fn random_game_in_progress(rng: &mut StdRng) -> GameInProgress {
+    let [color_1, color_2] = {
+        let mut arr = [Color::Red, Color::Black];
+        arr.shuffle(rng);
+        arr
+    };
+    let mut players = [
+        PlayerState::new(color_1, rng),
+        PlayerState::new(color_2, rng),
+    ];
+    let first_card = players[0].hand.iter().choose(rng).unwrap();
+    let first_turn = execute_first_turn(&players[0], first_card).unwrap();
+    players[0] = first_turn.state;
+    let mut board = first_turn.board;
+
+    // A game has at most 47 turns after the first one, since every turn that is not
+    // skipped uses up a card from the draw piles (21 each) or ends the game.
+    let num_turns = rng.gen_range(0..48);
+    let mut current_player_idx = 1;
+    let mut turn_skipped = false;
+    for _ in 0..num_turns {
+        let action = random_turn(rng, &board, players[current_player_idx].hand);
+        let result = execute_turn(&players[current_player_idx], &board, action)
+            .expect("Random turns are legal");
+        match result.outcome {
+            TurnOutcome::GameEnded => break,
+            TurnOutcome::Skipped if turn_skipped => break,
+            outcome => turn_skipped = outcome == TurnOutcome::Skipped,
+        }
+        players[current_player_idx] = result.state;
+        board = result.board;
+        current_player_idx = 1 - current_player_idx;
+    }
+
+    let [player_1, player_2] = players;
+    let (player, opponent) = if current_player_idx == 0 {
+        (player_1, player_2)
+    } else {
+        (player_2, player_1)
+    };
+    GameInProgress {
+        board,
+        player,
+        opponent,
+    }
+}
+
+/// A board from the middle of a random game, see [`random_game_in_progress()`].
+pub fn random_board(rng: &mut StdRng) -> Board {
+    random_game_in_progress(rng).board
+}
+
+/// The hand of a player in the middle of a random game, see [`random_game_in_progress()`].
+///
+/// This is always a full hand, since the game is stopped before the draw pile runs out.
+pub fn random_hand(rng: &mut StdRng) -> Hand {
+    random_game_in_progress(rng).player.hand
+}
+
+/// The state of a player in the middle of a random game, see [`random_game_in_progress()`].
+pub fn random_player_state(rng: &mut StdRng) -> PlayerState {
+    random_game_in_progress(rng).player
+}
+
+/// Chooses a random legal turn, which is skipped only if no card in the hand can be played.
+pub fn random_turn(rng: &mut StdRng, board: &Board, hand: Hand) -> PlayTurnResponse {
+    let mut validator = TurnValidator::new(board.clone(), hand.cards());
+    let mut cards_to_play = Vec::new();
+    while !validator.can_finish() {
+        let ctp = validator
+            .playable_cards()
+            .into_iter()
+            .flat_map(|card| validator.board().legal_plays(card))
+            .choose(rng)
+            .expect("A playable card has a legal play");
+        validator.play(ctp).expect("Legal plays can be played");
+        cards_to_play.push(ctp);
+    }
+    PlayTurnResponse::play_or_skip(board, hand.cards(), cards_to_play)
+}
+
+// Generates a seed for the random_*() functions, so that quickcheck's Gen can be used with them.
+/// A field with a single, face-up card.
+pub fn field(i: i8, j: i8, card: Card) -> Field {
+    Field {
+        i,
+        j,
+        top_card: Some(card),
+        hidden_cards: Default::default(),
+    }
+}
+
+/// Playing a card at `(i, j)`, without a target for a king's ability.
+pub fn ctp(card: Card, i: i8, j: i8) -> CardToPlay {
+    CardToPlay {
+        card,
+        i,
+        j,
+        target_field_for_king_ability: None,
+    }
+}
+
+/// The view of a player on the second turn of a game, in which both colors are on the
+/// board and in the hand, e.g. for checking that a bot is color-symmetric.
+pub fn sample_player_view() -> PlayerView {
+    PlayerView {
+        board: Board::new(&[
+            field(0, 0, card!("7♠")),
+            field(0, 1, card!("7♥")),
+            field(1, 1, card!("2♣")),
+            field(1, 2, card!("J♣")),
+        ]),
+        hand: Hand::try_from([
+            card!("3♦"),
+            card!("7♦"),
+            card!("9♥"),
+            card!("Q♦"),
+            card!("A♥"),
+        ])
+        .unwrap(),
+        cards_won: CardsSet::new(),
+        cards_won_by_opponents: CardsSet::new(),
+        new_cards_won_by_opponents: CardsSet::new(),
+        draw_pile_size: 20,
+        opponent_draw_pile_size: 20,
+        turn_number: 2,
+    }
+}
+
+fn rng_from_gen(g: &mut Gen) -> StdRng {
+    StdRng::seed_from_u64(u64::arbitrary(g))
+}
+
+impl Arbitrary for GameInProgress {
+    fn arbitrary(g: &mut Gen) -> Self {
+        random_game_in_progress(&mut rng_from_gen(g))
+    }
+}
+
+impl Arbitrary for Board {
+    fn arbitrary(g: &mut Gen) -> Self {
+        random_board(&mut rng_from_gen(g))
+    }
+}
+
+impl Arbitrary for Hand {
+    fn arbitrary(g: &mut Gen) -> Self {
+        random_hand(&mut rng_from_gen(g))
+    }
+}
+
+impl Arbitrary for PlayerState {
+    fn arbitrary(g: &mut Gen) -> Self {
+        random_player_state(&mut rng_from_gen(g))
+    }
+}
+
+impl Arbitrary for Suit {
+    fn arbitrary(g: &mut Gen) -> Self {
+        *g.choose(&[Suit::Diamond, Suit::Heart, Suit::Spade, Suit::Club])
+            .unwrap()
+    }
+}
+
+impl Arbitrary for Rank {
+    fn arbitrary(g: &mut Gen) -> Self {
+        *g.choose(&Rank::ALL).unwrap()
+    }
+}
+
+impl Arbitrary for Card {
+    fn arbitrary(g: &mut Gen) -> Self {
+        Self {
+            rank: Rank::arbitrary(g),
+            suit: Suit::arbitrary(g),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use quickcheck::quickcheck;
+
+    use super::*;
+    use crate::CardsSet;
+
+    quickcheck! {
+        fn games_in_progress_are_reachable(game: GameInProgress) -> bool {
+            let GameInProgress { board, player, opponent } = game;
+            let mut all_cards = CardsSet::new();
+            let mut num_cards = 0;
+            let mut add = |cards: &mut dyn Iterator<Item = Card>| {
+                for card in cards {
+                    all_cards = all_cards.insert(card);
+                    num_cards += 1;
+                }
+            };
+            for field in board.to_fields_vec() {
+                add(&mut field.top_card.into_iter().chain(field.hidden_cards));
+            }
+            for state in [&player, &opponent] {
+                add(&mut state.draw_pile.iter().copied());
+                add(&mut state.hand.iter());
+                add(&mut state.cards_won.into_iter());
+            }
+            // Every card of both decks is in exactly one place
+            num_cards == 52 && all_cards.len() == 52 && player.hand.is_full() && opponent.hand.is_full()
+        }
+    }
+}
@@ -1,113 +1,562 @@
-use std::collections::BTreeSet;
+use alloc::collections::BTreeSet;
 
+use crate::prelude::*;
 use crate::{
-    Board, CalculatedEffects, Card, CardsSet, Field, IllegalMove, PlayTurnResponse, PlayerState,
+    Board, CalculatedEffects, Card, CardToPlay, CardsSet, Field, Hand, IllegalMove,
+    PlayTurnResponse, PlayerState, Rules, TurnAction,
 };
 
-/// Summarizes the outcome of playing a turn (i.e. playing up to five cards).
+/// How a turn (i.e. playing up to five cards) ended, see [`TurnResult`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum TurnOutcome {
+    /// No card was played, because the player had no card that could be played.
     Skipped,
-    Normal { cards_won_this_turn: CardsSet },
+    /// The player drew cards until its hand was full again.
+    ///
+    /// This used to hold the cards won in the turn, which are now in
+    /// [`TurnResult::cards_won_this_turn`], for every outcome.
+    Normal,
+    /// The player's draw pile ran out, which ends the game.
     GameEnded,
 }
 
+/// The game after a turn, returned by [`execute_turn()`] and [`execute_first_turn()`].
+#[derive(Clone, Debug)]
+pub struct TurnResult {
+    /// The board after all cards were played.
+    pub board: Board,
+    /// The state of the player after drawing new cards. The cards won in the turn are
+    /// added to its won cards, except in the turn that ends the game, since they don't
+    /// count for the score.
+    pub state: PlayerState,
+    pub outcome: TurnOutcome,
+    /// The cards won over the whole turn, also in the turn that ends the game.
+    pub cards_won_this_turn: CardsSet,
+    /// What each card did, in the order in which they were played. Empty if the turn was
+    /// skipped.
+    pub cards_played: Vec<PlayedCard>,
+}
+
+/// The effects of one card of a turn, see [`TurnResult::cards_played`].
+#[derive(Clone, Debug)]
+pub struct PlayedCard {
+    pub card_to_play: CardToPlay,
+    /// Whether the card was a jack, queen or king that was played on top of another card,
+    /// and the rules enable its ability.
+    pub used_ability: bool,
+    /// The cards won by this card.
+    pub cards_won: CardsSet,
+    /// The board after this card was played.
+    pub board: Board,
+}
+
+/// Plays the first turn of the game, i.e. places the card at `(0, 0)`, and the player
+/// draws a card.
+///
+/// Like [`execute_turn()`], the state is not changed. The outcome is always
+/// [`TurnOutcome::Normal`], since the first card can't win cards.
 pub fn execute_first_turn(
-    state: &mut PlayerState,
+    state: &PlayerState,
+    card_to_play: Card,
+) -> Result<TurnResult, IllegalMove> {
+    execute_first_turn_with_rules(state, card_to_play, Rules::STANDARD)
+}
+
+/// Like [`execute_first_turn()`], but the board follows the given rules, see [`Board::new_with_rules()`].
+///
+/// Panics if the rules are invalid.
+pub fn execute_first_turn_with_rules(
+    state: &PlayerState,
+    card_to_play: Card,
+    rules: Rules,
+) -> Result<TurnResult, IllegalMove> {
+    execute_first_turn_at(state, card_to_play, (0, 0), rules)
+}
+
+/// Like [`execute_first_turn_with_rules()`], but the card is placed at the given location
+/// instead of `(0, 0)`, like the `i` and `j` of [`Request::PlayFirstTurn`](crate::Request::PlayFirstTurn).
+///
+/// Panics if the rules are invalid.
+pub fn execute_first_turn_at(
+    state: &PlayerState,
     card_to_play: Card,
-) -> Result<Board, IllegalMove> {
+    (i, j): (i8, i8),
+    rules: Rules,
+) -> Result<TurnResult, IllegalMove> {
     // Draw a new card, and validate that the card was in the hand of the player
-    let mut card_found = false;
-    for card in state.hand.iter_mut() {
-        if *card == card_to_play {
-            let next_card: Card = state.draw_pile.pop().unwrap(); // Can't fail, since it's the first turn
-            let _ = std::mem::replace(card, next_card);
-            card_found = true;
-        }
+    if !state.hand.contains(card_to_play) {
+        return Err(IllegalMove::PlayedCardNotInHand);
     }
-    if !card_found {
-        Err(IllegalMove::PlayedCardNotInHand)
-    } else {
-        Ok(Board::new(&[Field {
-            i: 0,
-            j: 0,
+    let mut state = state.clone();
+    let next_card: Card = state.draw_pile.draw().unwrap(); // Can't fail, since it's the first turn
+    state.hand = state
+        .hand
+        .remove(card_to_play)
+        .insert(next_card)
+        .expect("The draw pile and the hand are disjoint");
+    let board = Board::new_with_rules(
+        &[Field {
+            i,
+            j,
             top_card: Some(card_to_play),
             hidden_cards: BTreeSet::new(),
-        }]))
-    }
+        }],
+        rules,
+    );
+    let played_card = PlayedCard {
+        card_to_play: CardToPlay {
+            card: card_to_play,
+            i,
+            j,
+            target_field_for_king_ability: None,
+        },
+        used_ability: false,
+        cards_won: CardsSet::new(),
+        board: board.clone(),
+    };
+    Ok(TurnResult {
+        board,
+        state,
+        outcome: TurnOutcome::Normal,
+        cards_won_this_turn: CardsSet::new(),
+        cards_played: vec![played_card],
+    })
 }
 
+/// Plays a turn of the player with this state on the board.
+///
+/// The state and the board are not changed, so that it's easy to try out several turns,
+/// e.g. those that the opponent could play next.
 pub fn execute_turn(
-    state: &mut PlayerState,
-    board: &mut Board,
+    state: &PlayerState,
+    board: &Board,
     action: PlayTurnResponse,
-) -> Result<TurnOutcome, IllegalMove> {
-    let mut cards_to_play = action.0;
-    if cards_to_play.is_empty() {
-        // The player wants to skip their turn. This is only allowed if there is no possible move.
-        for &hand_card in &state.hand {
-            if board.possible_to_play_card(hand_card) {
-                return Err(IllegalMove::PlayedZeroCards);
+) -> Result<TurnResult, IllegalMove> {
+    let explicit_skip = action.0 == TurnAction::Skip;
+    let skipped = action.is_skip();
+    let cards_to_play = action.cards_to_play();
+    if cards_to_play.len() > 5 {
+        return Err(IllegalMove::PlayedMoreThanFiveCards);
+    }
+
+    let mut validator = TurnValidator::new(board.clone(), state.hand.cards());
+    let mut cards_played = Vec::with_capacity(cards_to_play.len());
+    for &ctp in cards_to_play {
+        let used_ability = validator.board().get(ctp.i, ctp.j).is_some()
+            && board.rules().has_ability(ctp.card.rank);
+        let cards_won = validator.play(ctp)?;
+        cards_played.push(PlayedCard {
+            card_to_play: ctp,
+            used_ability,
+            cards_won,
+            board: validator.board().clone(),
+        });
+    }
+    let FinishedTurn {
+        board,
+        remaining_hand,
+        cards_won: cards_won_this_turn,
+    } = validator.finish().map_err(|err| match err {
+        IllegalMove::PlayedZeroCards if explicit_skip => IllegalMove::SkippedDespitePlayableCard,
+        err => err,
+    })?;
+    let mut state = state.clone();
+    if skipped {
+        return Ok(TurnResult {
+            board,
+            state,
+            outcome: TurnOutcome::Skipped,
+            cards_won_this_turn,
+            cards_played,
+        });
+    }
+
+    // Draw cards until hand is full again
+    let mut hand = Hand::try_from(remaining_hand).expect("The remaining hand is part of the hand");
+    let mut outcome = TurnOutcome::Normal;
+    while !hand.is_full() {
+        match state.draw_pile.draw() {
+            Some(card) => {
+                hand = hand
+                    .insert(card)
+                    .expect("The draw pile and the hand are disjoint");
             }
+            None => {
+                outcome = TurnOutcome::GameEnded;
+                break;
+            }
+        };
+    }
+    state.hand = hand;
+    if outcome == TurnOutcome::Normal {
+        state.cards_won |= cards_won_this_turn;
+    }
+    Ok(TurnResult {
+        board,
+        state,
+        outcome,
+        cards_won_this_turn,
+        cards_played,
+    })
+}
+
+/// Where a [`TurnValidator`] is in the turn.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TurnState {
+    /// No card has been played yet.
+    NotStarted,
+    /// The last card started a combo, so another card must be played if possible.
+    InCombo,
+    /// The last card did not start a combo, so no more cards may be played.
+    Ended,
+}
+
+/// The result of a turn that was checked by a [`TurnValidator`].
+#[cfg_attr(feature = "python", pyo3::pyclass(get_all))]
+#[derive(Clone, Debug)]
+pub struct FinishedTurn {
+    /// The board after all cards were played.
+    pub board: Board,
+    /// The cards from the hand that were not played.
+    pub remaining_hand: CardsSet,
+    /// The cards won over the whole turn.
+    pub cards_won: CardsSet,
+}
+
+/// Checks the cards of a turn one by one, as they are chosen.
+///
+/// This applies the same rules as [`execute_turn()`], which is implemented with it,
+/// but also allows asking about a partial turn, e.g. whether it may end after the
+/// cards played so far, or which cards can still be played.
+///
+/// ```
+/// use gomori::{card, Board, CardToPlay, CardsSet, Field, TurnState, TurnValidator};
+/// let board = Board::new(&[Field {
+///     i: 0,
+///     j: 0,
+///     top_card: Some(card!("7♠")),
+///     hidden_cards: Default::default(),
+/// }]);
+/// let hand = CardsSet::from_iter([card!("2♦"), card!("3♦"), card!("4♦"), card!("5♦"), card!("6♦")]);
+/// let mut validator = TurnValidator::new(board, hand);
+/// // The turn can't be skipped, since there are playable cards
+/// assert!(!validator.can_finish());
+/// validator.play(CardToPlay { card: card!("2♦"), i: 0, j: 1, target_field_for_king_ability: None }).unwrap();
+/// assert_eq!(validator.state(), TurnState::Ended);
+/// assert!(validator.can_finish());
+/// ```
+#[derive(Clone, Debug)]
+pub struct TurnValidator {
+    board: Board,
+    hand: CardsSet,
+    cards_won: CardsSet,
+    num_cards_played: usize,
+    state: TurnState,
+}
+
+impl TurnValidator {
+    pub fn new(board: Board, hand: CardsSet) -> Self {
+        Self {
+            board,
+            hand,
+            cards_won: CardsSet::new(),
+            num_cards_played: 0,
+            state: TurnState::NotStarted,
         }
-        return Ok(TurnOutcome::Skipped);
     }
-    if cards_to_play.len() > 5 {
-        return Err(IllegalMove::PlayedMoreThanFiveCards);
+
+    pub fn state(&self) -> TurnState {
+        self.state
+    }
+
+    /// The board after the cards played so far.
+    pub fn board(&self) -> &Board {
+        &self.board
+    }
+
+    /// The cards from the hand that have not been played yet.
+    pub fn remaining_hand(&self) -> CardsSet {
+        self.hand
     }
 
-    let mut hand = BTreeSet::from(state.hand);
+    /// The cards won by the cards played so far.
+    pub fn cards_won(&self) -> CardsSet {
+        self.cards_won
+    }
+
+    pub fn num_cards_played(&self) -> usize {
+        self.num_cards_played
+    }
 
-    cards_to_play.reverse(); // So that pop() goes through them in order
+    /// The cards from the remaining hand that may be played next.
+    ///
+    /// Empty if the turn has ended.
+    pub fn playable_cards(&self) -> CardsSet {
+        if self.state == TurnState::Ended {
+            return CardsSet::new();
+        }
+        self.hand
+            .into_iter()
+            .filter(|&card| self.board.possible_to_play_card(card))
+            .collect()
+    }
 
-    let mut cards_won_this_turn = CardsSet::new();
+    /// Whether the turn may end after the cards played so far.
+    ///
+    /// If this is false, another card must be played.
+    pub fn can_finish(&self) -> bool {
+        self.state == TurnState::Ended || self.board.must_skip(self.hand)
+    }
 
-    let mut card_idx = 0;
-    while let Some(ctp) = cards_to_play.pop() {
-        let card_is_valid = hand.remove(&ctp.card);
-        if !card_is_valid {
+    /// Plays the next card of the turn, and returns the cards won by it.
+    ///
+    /// If the card is illegal, the validator is left unchanged.
+    pub fn play(&mut self, ctp: CardToPlay) -> Result<CardsSet, IllegalMove> {
+        let card_idx = self.num_cards_played;
+        if self.state == TurnState::Ended {
+            return Err(IllegalMove::PlayedCardAfterEndOfCombo {
+                card_idx: card_idx - 1,
+            });
+        }
+        if !self.hand.contains(ctp.card) {
             return Err(IllegalMove::PlayedCardNotInHand);
         }
         let calculation @ CalculatedEffects {
             cards_won, combo, ..
-        } = board
+        } = self
+            .board
             .calculate(ctp)
             .map_err(|err| IllegalMove::IllegalCardPlayed {
                 card_idx,
-                card: ctp.card,
+                card_to_play: ctp,
+                board: crate::visualize_top_cards(&self.board.to_fields_vec()),
                 err,
             })?;
-        if !combo && !cards_to_play.is_empty() {
-            return Err(IllegalMove::PlayedCardAfterEndOfCombo { card_idx });
-        }
-        *board = calculation.execute();
-        if combo && cards_to_play.is_empty() {
-            // Is there a possible move?
-            for &hand_card in hand.iter() {
-                if board.possible_to_play_card(hand_card) {
-                    return Err(IllegalMove::PrematurelyEndedCombo { card_idx });
-                }
-            }
+        self.board = calculation.execute();
+        self.hand = self.hand.remove(ctp.card);
+        self.cards_won |= cards_won;
+        self.num_cards_played += 1;
+        self.state = if combo {
+            TurnState::InCombo
+        } else {
+            TurnState::Ended
+        };
+        Ok(cards_won)
+    }
+
+    /// Ends the turn, if that is allowed after the cards played so far.
+    pub fn finish(self) -> Result<FinishedTurn, IllegalMove> {
+        if !self.can_finish() {
+            return Err(match self.state {
+                TurnState::NotStarted => IllegalMove::PlayedZeroCards,
+                _ => IllegalMove::PrematurelyEndedCombo {
+                    card_idx: self.num_cards_played - 1,
+                },
+            });
         }
-        cards_won_this_turn |= cards_won;
+        Ok(FinishedTurn {
+            board: self.board,
+            remaining_hand: self.hand,
+            cards_won: self.cards_won,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{card, ctp, field, DrawPile};
 
-        card_idx += 1;
+    #[test]
+    fn combo_must_be_continued() {
+        // Playing the 5♠ on top of the 5♥ is a combo, and the other cards can still be played afterwards
+        let board = Board::new(&[field(0, 0, card!("5♥")), field(0, 1, card!("A♣"))]);
+        let hand = CardsSet::from_iter([
+            card!("5♠"),
+            card!("4♦"),
+            card!("8♦"),
+            card!("9♦"),
+            card!("T♦"),
+        ]);
+        let mut validator = TurnValidator::new(board, hand);
+        assert_eq!(validator.state(), TurnState::NotStarted);
+        assert!(!validator.can_finish());
+        assert!(matches!(
+            validator.clone().finish(),
+            Err(IllegalMove::PlayedZeroCards)
+        ));
+
+        validator.play(ctp(card!("5♠"), 0, 0)).unwrap();
+        assert_eq!(validator.state(), TurnState::InCombo);
+        assert_eq!(validator.num_cards_played(), 1);
+        assert!(!validator.remaining_hand().contains(card!("5♠")));
+        assert!(!validator.can_finish());
+        assert!(matches!(
+            validator.clone().finish(),
+            Err(IllegalMove::PrematurelyEndedCombo { card_idx: 0 })
+        ));
+
+        validator.play(ctp(card!("4♦"), 1, 0)).unwrap();
+        assert_eq!(validator.state(), TurnState::Ended);
+        assert!(validator.playable_cards().is_empty());
+        assert!(matches!(
+            validator.clone().play(ctp(card!("8♦"), 1, 1)),
+            Err(IllegalMove::PlayedCardAfterEndOfCombo { card_idx: 1 })
+        ));
+        let finished = validator.finish().unwrap();
+        assert_eq!(finished.remaining_hand.len(), 3);
     }
 
-    // Draw cards until hand is full again
-    let mut hand: Vec<Card> = hand.into_iter().collect();
-    while hand.len() < 5 {
-        match state.draw_pile.pop() {
-            Some(card) => {
-                hand.push(card);
-            }
-            None => {
-                return Ok(TurnOutcome::GameEnded);
-            }
+    #[test]
+    fn last_turn_reports_cards_won() {
+        let board = Board::new(&[
+            field(0, 0, card!("7♥")),
+            field(0, 1, card!("8♥")),
+            field(0, 2, card!("9♥")),
+        ]);
+        let state = PlayerState {
+            draw_pile: DrawPile::default(),
+            hand: Hand::try_from(
+                [
+                    card!("T♥"),
+                    card!("2♠"),
+                    card!("3♠"),
+                    card!("4♠"),
+                    card!("5♠"),
+                ]
+                .as_slice(),
+            )
+            .unwrap(),
+            cards_won: CardsSet::new(),
         };
+        assert!(matches!(
+            execute_turn(&state, &board, PlayTurnResponse::skip()),
+            Err(IllegalMove::SkippedDespitePlayableCard)
+        ));
+        assert!(matches!(
+            execute_turn(&state, &board, PlayTurnResponse::play(vec![])),
+            Err(IllegalMove::PlayedZeroCards)
+        ));
+        let action = PlayTurnResponse::play(vec![ctp(card!("T♥"), 0, 3)]);
+        let result = execute_turn(&state, &board, action).unwrap();
+        assert_eq!(result.outcome, TurnOutcome::GameEnded);
+        assert_eq!(result.cards_won_this_turn.len(), 3);
+        // The cards won in the last turn don't count for the score
+        assert!(result.state.cards_won.is_empty());
+        assert_eq!(result.state.hand.len(), 4);
+        assert_eq!(
+            result.board.get(0, 3).unwrap().top_card(),
+            Some(card!("T♥"))
+        );
+        // The inputs are not changed
+        assert_eq!(state.hand.len(), 5);
+        assert_eq!(board.get(0, 3), None);
+    }
+
+    #[test]
+    fn effects_of_each_card_are_reported() {
+        let board = Board::new(&[field(0, 0, card!("5♥")), field(0, 1, card!("9♦"))]);
+        let state = PlayerState {
+            draw_pile: DrawPile::from_cards(vec![card!("6♠"), card!("7♠")]),
+            hand: Hand::try_from(
+                [
+                    card!("5♠"),
+                    card!("J♦"),
+                    card!("8♦"),
+                    card!("9♣"),
+                    card!("T♦"),
+                ]
+                .as_slice(),
+            )
+            .unwrap(),
+            cards_won: CardsSet::new(),
+        };
+        let action = PlayTurnResponse::play(vec![
+            ctp(card!("5♠"), 0, 0),
+            ctp(card!("J♦"), 0, 1),
+            ctp(card!("8♦"), 1, 0),
+        ]);
+        let result = execute_turn(&state, &board, action).unwrap();
+        assert_eq!(result.cards_played.len(), 3);
+        let [first, second, third] = &result.cards_played[..] else {
+            unreachable!()
+        };
+        assert_eq!(first.card_to_play, ctp(card!("5♠"), 0, 0));
+        assert!(!first.used_ability);
+        assert_eq!(first.board.get(0, 0).unwrap().top_card(), Some(card!("5♠")));
+        assert_eq!(first.board.get(0, 1).unwrap().top_card(), Some(card!("9♦")));
+        assert!(second.used_ability);
+        assert!(!third.used_ability);
+        assert_eq!(third.board, result.board);
+        assert_eq!(
+            first.cards_won | second.cards_won | third.cards_won,
+            result.cards_won_this_turn
+        );
+    }
+
+    #[test]
+    fn first_turn_leaves_the_state_unchanged() {
+        let state = PlayerState {
+            draw_pile: DrawPile::from_cards(vec![card!("6♠")]),
+            hand: Hand::try_from(
+                [
+                    card!("T♥"),
+                    card!("2♠"),
+                    card!("3♠"),
+                    card!("4♠"),
+                    card!("5♠"),
+                ]
+                .as_slice(),
+            )
+            .unwrap(),
+            cards_won: CardsSet::new(),
+        };
+        let result = execute_first_turn_at(&state, card!("T♥"), (1, 2), Rules::STANDARD).unwrap();
+        assert_eq!(result.outcome, TurnOutcome::Normal);
+        assert_eq!(
+            result.board.get(1, 2).unwrap().top_card(),
+            Some(card!("T♥"))
+        );
+        assert!(result.state.hand.contains(card!("6♠")));
+        assert!(!result.state.hand.contains(card!("T♥")));
+        assert!(result.state.draw_pile.is_empty());
+        assert_eq!(state.draw_pile.len(), 1);
+        assert!(matches!(
+            execute_first_turn(&state, card!("6♠")),
+            Err(IllegalMove::PlayedCardNotInHand)
+        ));
+    }
+
+    #[test]
+    fn illegal_card_leaves_validator_unchanged() {
+        let board = Board::new(&[field(0, 0, card!("5♥"))]);
+        let hand = CardsSet::from_iter([card!("2♠")]);
+        let mut validator = TurnValidator::new(board, hand);
+        assert!(matches!(
+            validator.play(ctp(card!("3♠"), 0, 1)),
+            Err(IllegalMove::PlayedCardNotInHand)
+        ));
+        let err = validator.play(ctp(card!("2♠"), 0, 0)).unwrap_err();
+        let IllegalMove::IllegalCardPlayed {
+            card_idx: 0,
+            card_to_play,
+            ref board,
+            ..
+        } = err
+        else {
+            panic!("Unexpected error {:?}", err);
+        };
+        assert_eq!(card_to_play, ctp(card!("2♠"), 0, 0));
+        assert!(board.contains(&card!("5♥").to_string()));
+        assert_eq!(
+            err.to_string(),
+            format!(
+                "Error playing the first card, which was {} at (0, 0)",
+                card!("2♠")
+            )
+        );
+        assert_eq!(validator.state(), TurnState::NotStarted);
+        assert_eq!(validator.remaining_hand(), hand);
+        assert_eq!(validator.playable_cards(), hand);
     }
-    state.hand = hand.try_into().unwrap();
-    state.cards_won |= cards_won_this_turn;
-    Ok(TurnOutcome::Normal {
-        cards_won_this_turn,
-    })
 }
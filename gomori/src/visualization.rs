@@ -1,3 +1,4 @@
+use crate::prelude::*;
 use crate::Field;
 
 pub fn visualize_top_cards(fields: &[Field]) -> String {
@@ -0,0 +1,107 @@
+//! JavaScript bindings, enabled by the `wasm` feature.
+//!
+//! Cards, fields and plays are passed as plain JavaScript objects with the same structure as in
+//! the JSON protocol, e.g. `{suit: "♥", rank: "7"}` for a card, or
+//! `{card: {suit: "♥", rank: "7"}, i: 0, j: 1}` for a [`CardToPlay`].
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use wasm_bindgen::prelude::*;
+
+use crate::{Board, Card, CardToPlay, CardsSet, Field};
+
+fn from_js<T: DeserializeOwned>(value: JsValue) -> Result<T, JsError> {
+    serde_wasm_bindgen::from_value(value).map_err(|err| JsError::new(&err.to_string()))
+}
+
+fn to_js<T: Serialize + ?Sized>(value: &T) -> Result<JsValue, JsError> {
+    serde_wasm_bindgen::to_value(value).map_err(|err| JsError::new(&err.to_string()))
+}
+
+/// The JavaScript version of [`CalculatedEffects`](crate::CalculatedEffects).
+#[derive(Serialize)]
+struct CalculatedEffects {
+    cards_won: Vec<Card>,
+    combo: bool,
+}
+
+/// A [`Board`], exported to JavaScript as `Board`.
+#[wasm_bindgen(js_name = Board)]
+pub struct JsBoard(Board);
+
+#[wasm_bindgen(js_class = Board)]
+impl JsBoard {
+    /// Creates a board from an array of fields, like [`Board::try_new()`].
+    #[wasm_bindgen(constructor)]
+    pub fn new(fields: JsValue) -> Result<JsBoard, JsError> {
+        let fields: Vec<Field> = from_js(fields)?;
+        Ok(Self(Board::try_new(&fields)?))
+    }
+
+    /// Returns an array of fields, sorted like in the protocol.
+    #[wasm_bindgen(js_name = toFields)]
+    pub fn to_fields(&self) -> Result<JsValue, JsError> {
+        to_js(&self.0.to_fields_vec())
+    }
+
+    /// Returns `{cards_won, combo}`, or throws if the card can't be played.
+    pub fn calculate(&self, card_to_play: JsValue) -> Result<JsValue, JsError> {
+        let effects = self.0.calculate(from_js(card_to_play)?)?;
+        to_js(&CalculatedEffects {
+            cards_won: Vec::from_iter(effects.cards_won),
+            combo: effects.combo,
+        })
+    }
+
+    #[wasm_bindgen(js_name = scoreIfPlayed)]
+    pub fn score_if_played(&self, card_to_play: JsValue) -> Result<u32, JsError> {
+        Ok(self.0.score_if_played(from_js(card_to_play)?)?)
+    }
+
+    /// Returns the board after playing the card, or throws if the card can't be played.
+    #[wasm_bindgen(js_name = playCard)]
+    pub fn play_card(&self, card_to_play: JsValue) -> Result<JsBoard, JsError> {
+        Ok(Self(self.0.play_card(from_js(card_to_play)?)?))
+    }
+
+    #[wasm_bindgen(js_name = possibleToPlayCard)]
+    pub fn possible_to_play_card(&self, card: JsValue) -> Result<bool, JsError> {
+        Ok(self.0.possible_to_play_card(from_js(card)?))
+    }
+
+    /// Whether a player with the given array of cards must skip their turn.
+    #[wasm_bindgen(js_name = mustSkip)]
+    pub fn must_skip(&self, hand: JsValue) -> Result<bool, JsError> {
+        let hand: Vec<Card> = from_js(hand)?;
+        Ok(self.0.must_skip(CardsSet::from_iter(hand)))
+    }
+
+    /// Returns an array of `[i, j]` coordinates.
+    #[wasm_bindgen(js_name = locationsForCard)]
+    pub fn locations_for_card(&self, card: JsValue) -> Result<JsValue, JsError> {
+        let locations: Vec<(i8, i8)> = self
+            .0
+            .locations_for_card(from_js(card)?)
+            .into_iter()
+            .collect();
+        to_js(&locations)
+    }
+
+    /// Returns an array of `[i, j]` coordinates in the playable area without a card on them.
+    #[wasm_bindgen(js_name = emptyPlayableLocations)]
+    pub fn empty_playable_locations(&self) -> Result<JsValue, JsError> {
+        let locations: Vec<(i8, i8)> = self.0.empty_playable_locations().into_iter().collect();
+        to_js(&locations)
+    }
+
+    /// Returns an array of all the ways to legally play any of the given array of cards.
+    #[wasm_bindgen(js_name = legalPlays)]
+    pub fn legal_plays(&self, cards: JsValue) -> Result<JsValue, JsError> {
+        let cards: Vec<Card> = from_js(cards)?;
+        let plays: Vec<CardToPlay> = cards
+            .into_iter()
+            .flat_map(|card| self.0.legal_plays(card))
+            .collect();
+        to_js(&plays)
+    }
+}
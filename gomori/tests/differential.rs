@@ -0,0 +1,131 @@
+//! Plays random games and checks every card played against the straightforward
+//! reimplementation of the rules in [`gomori::naive`], which doesn't use bitboards or any
+//! other tricks.
+//!
+//! The test needs the `testing` feature. The number of games can be raised with the
+//! `GOMORI_DIFFERENTIAL_GAMES` environment variable, e.g.
+//! `GOMORI_DIFFERENTIAL_GAMES=100000 cargo test --release --features testing --test differential`.
+
+use gomori::naive::{self, NaiveEffects};
+use gomori::{
+    execute_first_turn, execute_turn, Board, CardToPlay, CardsSet, Color, Field, PlayTurnResponse,
+    PlayerState, TurnOutcome, TurnValidator,
+};
+use rand::rngs::StdRng;
+use rand::seq::IteratorRandom;
+use rand::{Rng, SeedableRng};
+
+const DEFAULT_NUM_GAMES: u64 = 1000;
+// How many random, possibly illegal, cards are checked before every card that is played
+const PROBES_PER_CARD: usize = 3;
+
+fn sorted_fields(board: &Board) -> Vec<Field> {
+    let mut fields = board.to_fields_vec();
+    fields.sort_by_key(|f| (f.i, f.j));
+    fields
+}
+
+/// Checks a single card, which may be illegal, against the reference implementation.
+fn check_card(board: &Board, ctp: CardToPlay) {
+    let expected = naive::play(&board.to_fields_vec(), ctp);
+    let actual = board.calculate(ctp).ok().map(|effects| NaiveEffects {
+        cards_won: effects.cards_won,
+        combo: effects.combo,
+        flipped: effects.flipped_fields().into_iter().collect(),
+        won: effects.won_fields().into_iter().collect(),
+        fields_after: sorted_fields(&effects.execute()),
+    });
+    assert_eq!(
+        actual,
+        expected,
+        "Playing {:?} on {:?}",
+        ctp,
+        board.to_fields_vec()
+    );
+}
+
+// A card from the hand at a random position around the board, which is often illegal
+fn random_probe(rng: &mut StdRng, board: &Board, hand: CardsSet) -> Option<CardToPlay> {
+    let area = board.playable_area();
+    let random_coords = |rng: &mut StdRng| {
+        (
+            rng.gen_range(area.i_min - 1..=area.i_max + 1),
+            rng.gen_range(area.j_min - 1..=area.j_max + 1),
+        )
+    };
+    let (i, j) = random_coords(rng);
+    let target_field_for_king_ability = match rng.gen_range(0..3) {
+        0 => None,
+        1 => Some(random_coords(rng)),
+        _ => board.iter().map(|&(i, j, _)| (i, j)).choose(rng),
+    };
+    Some(CardToPlay {
+        card: hand.into_iter().choose(rng)?,
+        i,
+        j,
+        target_field_for_king_ability,
+    })
+}
+
+// Chooses a random legal turn and checks every card in it, and some random other cards
+fn play_random_turn(rng: &mut StdRng, board: &Board, state: &PlayerState) -> PlayTurnResponse {
+    let mut validator = TurnValidator::new(board.clone(), state.hand.cards());
+    let mut cards_to_play = Vec::new();
+    while !validator.can_finish() {
+        for _ in 0..PROBES_PER_CARD {
+            if let Some(probe) = random_probe(rng, validator.board(), validator.remaining_hand()) {
+                check_card(validator.board(), probe);
+            }
+        }
+        let ctp = validator
+            .playable_cards()
+            .into_iter()
+            .flat_map(|card| validator.board().legal_plays(card))
+            .choose(rng)
+            .expect("A playable card has a legal play");
+        check_card(validator.board(), ctp);
+        validator.play(ctp).unwrap();
+        cards_to_play.push(ctp);
+    }
+    PlayTurnResponse::play_or_skip(board, state.hand.cards(), cards_to_play)
+}
+
+fn play_random_game(seed: u64) {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut players = [
+        PlayerState::new(Color::Red, &mut rng),
+        PlayerState::new(Color::Black, &mut rng),
+    ];
+    let first_card = players[0].hand.iter().choose(&mut rng).unwrap();
+    let first_turn = execute_first_turn(&players[0], first_card).unwrap();
+    players[0] = first_turn.state;
+    let mut board = first_turn.board;
+    let mut current_player_idx = 1;
+    let mut turn_skipped = false;
+    loop {
+        let state = &mut players[current_player_idx];
+        let action = play_random_turn(&mut rng, &board, state);
+        let result = execute_turn(state, &board, action).unwrap();
+        *state = result.state;
+        board = result.board;
+        match result.outcome {
+            TurnOutcome::GameEnded => break,
+            TurnOutcome::Skipped if turn_skipped => break,
+            outcome => turn_skipped = outcome == TurnOutcome::Skipped,
+        }
+        current_player_idx = 1 - current_player_idx;
+    }
+}
+
+#[test]
+fn board_matches_reference_implementation() {
+    let num_games = std::env::var("GOMORI_DIFFERENTIAL_GAMES")
+        .map(|n| {
+            n.parse()
+                .expect("GOMORI_DIFFERENTIAL_GAMES must be a number")
+        })
+        .unwrap_or(DEFAULT_NUM_GAMES);
+    for seed in 0..num_games {
+        play_random_game(seed);
+    }
+}
@@ -0,0 +1,308 @@
+use std::cell::RefCell;
+
+use gomori::{
+    Card, CardsSet, Color, Field, Hand, MatchContext, Okay, PlayTurnResponse, PlayerView, Protocol,
+    Request,
+};
+use serde::Serialize;
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+use crate::{
+    assert_implements_play_turn, protocol_from_env, record_default_play_turn_view, view_from_turn,
+    GamePhase, GameSetup, GameSummary, GameTracker,
+};
+
+thread_local! {
+    // See BOTS_WITHOUT_PLAY_TURN_VIEW
+    static ASYNC_BOTS_WITHOUT_PLAY_TURN_VIEW: RefCell<Vec<&'static str>> =
+        const { RefCell::new(Vec::new()) };
+}
+
+/// Like [`Bot`](crate::Bot), but with async methods, for bots that wait on other things
+/// while choosing their move, e.g. on an inference server.
+///
+/// [`run()`](AsyncBot::run) must be awaited inside a tokio runtime. Requests are handled one
+/// after another, so the futures don't need to be `Send`. To limit the time spent on a move,
+/// wrap the work in [`tokio::time::timeout()`](https://docs.rs/tokio/latest/tokio/time/fn.timeout.html).
+#[allow(async_fn_in_trait)]
+pub trait AsyncBot {
+    async fn new_game(&mut self, color: Color, setup: GameSetup);
+    async fn play_first_turn(&mut self, cards: Hand) -> Card;
+    /// Like [`play_first_turn()`](AsyncBot::play_first_turn), but also with the location
+    /// `(i, j)` at which the card will be placed. By default, it calls `play_first_turn()`.
+    async fn play_first_turn_at(&mut self, cards: Hand, _i: i8, _j: i8) -> Card {
+        self.play_first_turn(cards).await
+    }
+    /// Bots implement either this method or [`play_turn_view()`](AsyncBot::play_turn_view),
+    /// see [`Bot::play_turn()`](crate::Bot::play_turn).
+    async fn play_turn(
+        &mut self,
+        cards: Hand,
+        fields: Vec<Field>,
+        cards_won_by_opponent: CardsSet,
+        phase: GamePhase,
+    ) -> PlayTurnResponse {
+        assert_implements_play_turn::<Self>(&ASYNC_BOTS_WITHOUT_PLAY_TURN_VIEW);
+        self.play_turn_view(view_from_turn(cards, &fields, cards_won_by_opponent, phase))
+            .await
+    }
+
+    /// Like [`play_turn()`](AsyncBot::play_turn), but with a [`PlayerView`], see
+    /// [`Bot::play_turn_view()`](crate::Bot::play_turn_view). By default, it calls
+    /// `play_turn()`.
+    async fn play_turn_view(&mut self, view: PlayerView) -> PlayTurnResponse {
+        record_default_play_turn_view::<Self>(&ASYNC_BOTS_WITHOUT_PLAY_TURN_VIEW);
+        let phase = GamePhase::from(&view);
+        self.play_turn(
+            view.hand,
+            view.board.to_fields_vec(),
+            view.new_cards_won_by_opponents,
+            phase,
+        )
+        .await
+    }
+
+    /// Called once when [`run()`](AsyncBot::run) starts, before the first request is handled.
+    async fn on_session_start(&mut self) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    /// Called before [`new_game()`](AsyncBot::new_game) with the score of the match so far,
+    /// if the judge sent it.
+    async fn on_match_context(&mut self, _context: MatchContext) {}
+
+    /// Called after each game, i.e. when the next game starts or the session ends.
+    async fn on_game_end(&mut self, _summary: GameSummary) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    async fn run(&mut self) -> anyhow::Result<()>
+    where
+        Self: Sized,
+    {
+        let connection = AsyncBotConnection::with_protocol(protocol_from_env()?);
+        serve(self, connection).await
+    }
+}
+
+// The main loop of AsyncBot::run(), see Bot::run() for the synchronous equivalent.
+async fn serve<B: AsyncBot>(bot: &mut B, mut connection: AsyncBotConnection) -> anyhow::Result<()> {
+    let mut tracker = GameTracker::default();
+    let mut final_context = None;
+
+    bot.on_session_start().await?;
+    while let Some(req) = connection.receive().await? {
+        match req {
+            Request::NewGame {
+                color,
+                num_players,
+                seat,
+                suit,
+                match_context,
+                rules,
+            } => {
+                if let Some(summary) = tracker.new_game(match_context, rules) {
+                    bot.on_game_end(summary).await?;
+                }
+                if let Some(context) = match_context {
+                    bot.on_match_context(context).await;
+                }
+                bot.new_game(color, GameSetup::new(color, num_players, seat, suit, rules))
+                    .await;
+                connection.send(&Okay()).await?;
+            }
+            Request::PlayFirstTurn { cards, i, j } => {
+                let card = bot.play_first_turn_at(cards, i, j).await;
+                connection.send(&card).await?;
+            }
+            Request::PlayTurn { .. } => {
+                let view = tracker.view(&req);
+                let response = bot.play_turn_view(view.clone()).await;
+                tracker.record_response(&view, &response);
+                connection.send(&response).await?;
+            }
+            Request::Bye { match_context } => {
+                final_context = match_context;
+                break;
+            }
+        }
+    }
+    if let Some(summary) = tracker.finish(final_context) {
+        bot.on_game_end(summary).await?;
+    }
+    Ok(())
+}
+
+/// The async equivalent of [`BotConnection`](crate::BotConnection), using tokio's stdin/stdout.
+pub struct AsyncBotConnection {
+    input: Box<dyn AsyncBufRead + Unpin>,
+    output: Box<dyn AsyncWrite + Unpin>,
+    protocol: Protocol,
+    // A re-usable buffer for reading requests
+    buf: String,
+    // Same, for binary protocols, and for writing responses
+    bytes: Vec<u8>,
+}
+
+impl AsyncBotConnection {
+    /// Uses the protocol that the judge asked for, see [`protocol_from_env()`].
+    ///
+    /// Panics if the judge asked for an unknown protocol.
+    pub fn new() -> Self {
+        Self::with_protocol(protocol_from_env().unwrap())
+    }
+
+    pub fn with_protocol(protocol: Protocol) -> Self {
+        Self::with_io(
+            Box::new(tokio::io::BufReader::new(tokio::io::stdin())),
+            Box::new(tokio::io::stdout()),
+            protocol,
+        )
+    }
+
+    /// Communicates through the given reader and writer instead of stdin/stdout.
+    pub fn with_io(
+        input: Box<dyn AsyncBufRead + Unpin>,
+        output: Box<dyn AsyncWrite + Unpin>,
+        protocol: Protocol,
+    ) -> Self {
+        Self {
+            input,
+            output,
+            protocol,
+            buf: String::new(),
+            bytes: Vec::new(),
+        }
+    }
+
+    /// Waits for the next request from the judge.
+    ///
+    /// Returns `None` if the judge has closed the connection.
+    pub async fn receive(&mut self) -> anyhow::Result<Option<Request>> {
+        match self.protocol {
+            Protocol::Json => {
+                self.buf.clear(); // because read_line() appends to the buffer
+                let num_bytes_read = self.input.read_line(&mut self.buf).await?;
+                if num_bytes_read == 0 {
+                    // 0 bytes read means EOF - the judge has exited.
+                    return Ok(None);
+                }
+                Ok(Some(serde_json::from_str::<Request>(self.buf.trim_end())?))
+            }
+            Protocol::MsgPack => {
+                let mut len = [0u8; 4];
+                match self.input.read_exact(&mut len).await {
+                    Ok(_) => {}
+                    Err(err) if err.kind() == std::io::ErrorKind::UnexpectedEof => return Ok(None),
+                    Err(err) => return Err(err.into()),
+                }
+                self.bytes.resize(u32::from_be_bytes(len) as usize, 0);
+                self.input.read_exact(&mut self.bytes).await?;
+                Ok(Some(rmp_serde::from_slice::<Request>(&self.bytes)?))
+            }
+        }
+    }
+
+    /// Sends the response to the last request.
+    pub async fn send<T: Serialize>(&mut self, response: &T) -> anyhow::Result<()> {
+        self.bytes.clear();
+        match self.protocol {
+            Protocol::Json => {
+                serde_json::to_writer(&mut self.bytes, response)?;
+                self.bytes.push(b'\n');
+            }
+            Protocol::MsgPack => {
+                self.bytes.extend_from_slice(&[0; 4]);
+                rmp_serde::encode::write_named(&mut self.bytes, response)?;
+                let len = (self.bytes.len() - 4) as u32;
+                self.bytes[..4].copy_from_slice(&len.to_be_bytes());
+            }
+        }
+        self.output.write_all(&self.bytes).await?;
+        self.output.flush().await?;
+        Ok(())
+    }
+}
+
+impl Default for AsyncBotConnection {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use gomori::{card, Rules};
+
+    use super::*;
+
+    struct FirstCardBot;
+
+    impl AsyncBot for FirstCardBot {
+        async fn new_game(&mut self, _color: Color, _setup: GameSetup) {}
+
+        async fn play_first_turn(&mut self, cards: Hand) -> Card {
+            cards.iter().next().unwrap()
+        }
+
+        async fn play_turn(
+            &mut self,
+            _cards: Hand,
+            _fields: Vec<Field>,
+            _cards_won_by_opponent: CardsSet,
+            _phase: GamePhase,
+        ) -> PlayTurnResponse {
+            PlayTurnResponse::skip()
+        }
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn serves_requests_until_bye() {
+        let cards = [
+            card!("2♦"),
+            card!("3♦"),
+            card!("4♦"),
+            card!("5♦"),
+            card!("6♦"),
+        ];
+        let requests = [
+            Request::NewGame {
+                color: Color::Red,
+                num_players: 2,
+                seat: 0,
+                suit: None,
+                match_context: None,
+                rules: Rules::STANDARD,
+            },
+            Request::PlayFirstTurn {
+                cards: Hand::try_from(cards).unwrap(),
+                i: 0,
+                j: 0,
+            },
+            Request::Bye {
+                match_context: None,
+            },
+        ];
+        let mut input = String::new();
+        for request in &requests {
+            input += &serde_json::to_string(request).unwrap();
+            input.push('\n');
+        }
+        let (output, mut judge_end) = tokio::io::duplex(1024);
+        let connection = AsyncBotConnection::with_io(
+            Box::new(std::io::Cursor::new(input.into_bytes())),
+            Box::new(output),
+            Protocol::Json,
+        );
+        serve(&mut FirstCardBot, connection).await.unwrap();
+
+        let mut responses = String::new();
+        judge_end.read_to_string(&mut responses).await.unwrap();
+        let expected = format!(
+            "{}\n{}\n",
+            serde_json::to_string(&Okay()).unwrap(),
+            serde_json::to_string(&cards[0]).unwrap()
+        );
+        assert_eq!(responses, expected);
+    }
+}
@@ -1,52 +1,9 @@
-use crate::Bot;
+pub use gomori::CardCounter;
 use gomori::{
-    Board, Card, CardsSet, Color, CompactField, Field, PlayTurnResponse, BLACK_CARDS_SET,
-    RED_CARDS_SET,
+    Board, Card, CardsSet, Color, Field, Hand, MatchContext, PlayTurnResponse, PlayerView,
 };
 
-/// Information about the cards in the game, derived from
-/// observing all played cards.
-///
-/// This can be automatically updated by implementing [`HasCardCounter`] for your bot
-/// and wrapping it in a `CardCountingWrapper`.
-#[derive(Clone, Copy, Debug)]
-pub struct CardCounter {
-    /// Cards in our draw pile.
-    pub draw_pile: CardsSet,
-    /// Cards in the opponent's draw pile + hand.
-    /// We don't have any information to distinguish the two.
-    pub available_cards_opponent: CardsSet,
-    /// Cards won by us.
-    pub cards_won_self: CardsSet,
-    /// Cards won by our opponent.
-    pub cards_won_opponent: CardsSet,
-}
-
-impl CardCounter {
-    fn new(color: Color) -> Self {
-        let (draw_pile, available_cards_opponent) = match color {
-            Color::Black => (BLACK_CARDS_SET, RED_CARDS_SET),
-            Color::Red => (RED_CARDS_SET, BLACK_CARDS_SET),
-        };
-        Self {
-            draw_pile,
-            available_cards_opponent,
-            cards_won_self: CardsSet::new(),
-            cards_won_opponent: CardsSet::new(),
-        }
-    }
-}
-
-impl Default for CardCounter {
-    fn default() -> Self {
-        CardCounter {
-            draw_pile: CardsSet::new(),
-            available_cards_opponent: CardsSet::new(),
-            cards_won_self: CardsSet::new(),
-            cards_won_opponent: CardsSet::new(),
-        }
-    }
-}
+use crate::{Bot, GamePhase, GameSetup, GameSummary};
 
 /// Implement this trait on your bot to allow it to be used with a [`CardCountingWrapper`].
 ///
@@ -70,43 +27,86 @@ where
     pub fn new(bot: T) -> Self {
         Self { bot }
     }
+
+    fn observe_request(&mut self, cards: Hand, board: &Board, cards_won_by_opponent: CardsSet) {
+        let counter = self.bot.get_counter();
+        counter.draw_pile = counter.draw_pile.difference(cards.cards());
+        counter.cards_won_opponent |= cards_won_by_opponent;
+        let on_board = board
+            .iter()
+            .fold(CardsSet::new(), |on_board, (_, _, field)| {
+                on_board | field.all_cards()
+            });
+        counter.available_cards_opponent = counter
+            .available_cards_opponent
+            .difference(cards_won_by_opponent | on_board);
+    }
+
+    fn observe_response(&mut self, mut board: Board, response: &PlayTurnResponse) {
+        for &card_to_play in response.cards_to_play() {
+            if let Ok(effects) = board.calculate(card_to_play) {
+                self.bot.get_counter().cards_won_self |= effects.cards_won;
+                board = effects.execute();
+            } else {
+                // Let the judge handle the illegal card play.
+                return;
+            }
+        }
+    }
 }
 
 impl<T: HasCardCounter + Bot> Bot for CardCountingWrapper<T> {
-    fn new_game(&mut self, color: Color) {
-        *self.bot.get_counter() = CardCounter::new(color);
-        self.bot.new_game(color);
+    fn new_game(&mut self, color: Color, setup: GameSetup) {
+        *self.bot.get_counter() =
+            CardCounter::for_deck_with_rules(setup.deck, setup.num_players, setup.rules);
+        self.bot.new_game(color, setup);
     }
 
-    fn play_first_turn(&mut self, cards: [Card; 5]) -> Card {
-        self.bot.get_counter().draw_pile &= !CardsSet::from_iter(cards);
+    fn play_first_turn(&mut self, cards: Hand) -> Card {
+        let counter = self.bot.get_counter();
+        counter.draw_pile = counter.draw_pile.difference(cards.cards());
         self.bot.play_first_turn(cards)
     }
 
+    fn play_first_turn_at(&mut self, cards: Hand, i: i8, j: i8) -> Card {
+        let counter = self.bot.get_counter();
+        counter.draw_pile = counter.draw_pile.difference(cards.cards());
+        self.bot.play_first_turn_at(cards, i, j)
+    }
+
     fn play_turn(
         &mut self,
-        cards: [Card; 5],
+        cards: Hand,
         fields: Vec<Field>,
         cards_won_by_opponent: CardsSet,
+        phase: GamePhase,
     ) -> PlayTurnResponse {
-        self.bot.get_counter().draw_pile &= !CardsSet::from_iter(cards);
-        self.bot.get_counter().cards_won_opponent |= cards_won_by_opponent;
-        self.bot.get_counter().available_cards_opponent &= !cards_won_by_opponent;
-        for field in &fields {
-            self.bot.get_counter().available_cards_opponent &=
-                !CompactField::from(field).all_cards();
-        }
-        let mut board = Board::new(&fields);
-        let response = self.bot.play_turn(cards, fields, cards_won_by_opponent);
-        for &card_to_play in &response.0 {
-            if let Ok(effects) = board.calculate(card_to_play) {
-                self.bot.get_counter().cards_won_self |= effects.cards_won;
-                board = effects.execute();
-            } else {
-                // Let the judge handle the illegal card play.
-                return response;
-            }
-        }
+        let board = Board::new_with_rules(&fields, phase.rules);
+        self.observe_request(cards, &board, cards_won_by_opponent);
+        let response = self
+            .bot
+            .play_turn(cards, fields, cards_won_by_opponent, phase);
+        self.observe_response(board, &response);
         response
     }
+
+    fn play_turn_view(&mut self, view: PlayerView) -> PlayTurnResponse {
+        let board = view.board.clone();
+        self.observe_request(view.hand, &board, view.new_cards_won_by_opponents);
+        let response = self.bot.play_turn_view(view);
+        self.observe_response(board, &response);
+        response
+    }
+
+    fn on_session_start(&mut self) -> anyhow::Result<()> {
+        self.bot.on_session_start()
+    }
+
+    fn on_match_context(&mut self, context: MatchContext) {
+        self.bot.on_match_context(context);
+    }
+
+    fn on_game_end(&mut self, summary: GameSummary) -> anyhow::Result<()> {
+        self.bot.on_game_end(summary)
+    }
 }
@@ -0,0 +1,112 @@
+use gomori::{visualize_top_cards, CardToPlay, PlayTurnResponse, PlayerView, SwapColors};
+
+use crate::{Bot, GameSetup};
+
+/// Checks that a deterministic bot plays the same turn with the colors swapped, i.e. that
+/// its evaluation has no accidental color bias.
+///
+/// The bot plays a turn in the position of `view`, and another one in the position with
+/// red and black swapped (see [`SwapColors`]). Each turn starts a new game, with the color
+/// of the hand, so this is meant for two-player games. Panics if the second turn is not
+/// the first one with its colors swapped, e.g. in a test.
+pub fn assert_color_symmetric<B: Bot>(bot: &mut B, view: &PlayerView) {
+    let swapped_view = view.swap_colors();
+    let response = play_in_new_game(bot, view.clone());
+    let swapped_response = play_in_new_game(bot, swapped_view.clone());
+
+    let expected: Vec<CardToPlay> = response
+        .cards_to_play()
+        .iter()
+        .map(CardToPlay::swap_colors)
+        .collect();
+    if swapped_response.is_skip() != response.is_skip()
+        || swapped_response.cards_to_play() != expected
+    {
+        panic!(
+            "The bot plays {:?} in this position with the hand {}:\n{}\n\
+            but {:?} with the colors swapped, instead of {:?}:\n{}",
+            response.0,
+            view.hand.cards(),
+            visualize_top_cards(&view.board.to_fields_vec()),
+            swapped_response.0,
+            expected,
+            visualize_top_cards(&swapped_view.board.to_fields_vec()),
+        );
+    }
+}
+
+fn play_in_new_game<B: Bot>(bot: &mut B, view: PlayerView) -> PlayTurnResponse {
+    let color = view
+        .hand
+        .into_iter()
+        .next()
+        .expect("A hand has at least one card")
+        .color();
+    let setup = GameSetup::new(color, 2, 0, None, view.board.rules());
+    bot.new_game(color, setup);
+    bot.play_turn_view(view)
+}
+
+#[cfg(test)]
+mod tests {
+    use gomori::{
+        sample_player_view, Board, Card, CardsSet, Color, Field, Hand, Suit, TurnValidator,
+    };
+
+    use super::*;
+    use crate::GamePhase;
+
+    /// Plays the first legal card in each step of the turn, optionally preferring a suit.
+    struct FirstCardBot {
+        preferred_suit: Option<Suit>,
+    }
+
+    impl Bot for FirstCardBot {
+        fn new_game(&mut self, _color: Color, _setup: GameSetup) {}
+
+        fn play_first_turn(&mut self, cards: Hand) -> Card {
+            cards.into_iter().next().unwrap()
+        }
+
+        fn play_turn(
+            &mut self,
+            cards: Hand,
+            fields: Vec<Field>,
+            _cards_won_by_opponent: CardsSet,
+            phase: GamePhase,
+        ) -> PlayTurnResponse {
+            let board = Board::new_with_rules(&fields, phase.rules);
+            let mut validator = TurnValidator::new(board.clone(), cards.cards());
+            let mut cards_to_play = Vec::new();
+            while !validator.can_finish() {
+                let mut playable: Vec<Card> = validator.playable_cards().into_iter().collect();
+                playable.sort_by_key(|card| Some(card.suit) != self.preferred_suit);
+                let ctp = validator.board().legal_plays(playable[0])[0];
+                validator.play(ctp).unwrap();
+                cards_to_play.push(ctp);
+            }
+            PlayTurnResponse::play_or_skip(&board, cards.cards(), cards_to_play)
+        }
+    }
+
+    #[test]
+    fn unbiased_bot_is_symmetric() {
+        assert_color_symmetric(
+            &mut FirstCardBot {
+                preferred_suit: None,
+            },
+            &sample_player_view(),
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "with the colors swapped")]
+    fn bot_preferring_hearts_is_not_symmetric() {
+        assert_color_symmetric(
+            &mut FirstCardBot {
+                preferred_suit: Some(Suit::Heart),
+            },
+            &sample_player_view(),
+        );
+    }
+}
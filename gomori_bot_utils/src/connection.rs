@@ -0,0 +1,112 @@
+use std::io::{BufRead, ErrorKind, Read, Stdin, Stdout, Write};
+
+use gomori::{Protocol, Request};
+use serde::Serialize;
+
+/// The bot's end of the communication with the judge.
+///
+/// Communication happens through stdin/stdout, with one JSON message per line,
+/// or with length-prefixed MessagePack messages (see [`Protocol`]).
+/// Stderr can be used for logging.
+///
+/// [`Bot::run()`](crate::Bot::run) takes care of this for you, so this is only needed
+/// for bots that implement the main loop themselves.
+///
+/// Stdin and stdout are only locked while receiving and sending, so requests can also be
+/// received by one connection on another thread, and responses sent by another one.
+pub struct BotConnection {
+    stdin: Stdin,
+    stdout: Stdout,
+    protocol: Protocol,
+    // A re-usable buffer for reading requests
+    buf: String,
+    // Same, for binary protocols
+    bytes: Vec<u8>,
+}
+
+impl BotConnection {
+    /// Uses the protocol that the judge asked for, see [`protocol_from_env()`].
+    ///
+    /// Panics if the judge asked for an unknown protocol.
+    pub fn new() -> Self {
+        Self::with_protocol(protocol_from_env().unwrap())
+    }
+
+    pub fn with_protocol(protocol: Protocol) -> Self {
+        Self {
+            stdin: std::io::stdin(),
+            stdout: std::io::stdout(),
+            protocol,
+            buf: String::new(),
+            bytes: Vec::new(),
+        }
+    }
+
+    /// Waits for the next request from the judge.
+    ///
+    /// Returns `None` if the judge has closed the connection.
+    pub fn receive(&mut self) -> anyhow::Result<Option<Request>> {
+        let mut stdin = self.stdin.lock();
+        match self.protocol {
+            Protocol::Json => {
+                self.buf.clear(); // because stdin.read_line() appends to the buffer
+                let num_bytes_read = stdin.read_line(&mut self.buf)?;
+                if num_bytes_read == 0 {
+                    // 0 bytes read means EOF - the judge has exited.
+                    return Ok(None);
+                }
+                Ok(Some(serde_json::from_str::<Request>(self.buf.trim_end())?))
+            }
+            Protocol::MsgPack => {
+                let mut len = [0u8; 4];
+                match stdin.read_exact(&mut len) {
+                    Ok(()) => {}
+                    Err(err) if err.kind() == ErrorKind::UnexpectedEof => return Ok(None),
+                    Err(err) => return Err(err.into()),
+                }
+                self.bytes.resize(u32::from_be_bytes(len) as usize, 0);
+                stdin.read_exact(&mut self.bytes)?;
+                Ok(Some(rmp_serde::from_slice::<Request>(&self.bytes)?))
+            }
+        }
+    }
+
+    /// Sends the response to the last request.
+    pub fn send<T: Serialize>(&mut self, response: &T) -> anyhow::Result<()> {
+        let mut stdout = self.stdout.lock();
+        match self.protocol {
+            Protocol::Json => {
+                serde_json::to_writer(&mut stdout, response)?;
+                writeln!(stdout)?;
+            }
+            Protocol::MsgPack => {
+                self.bytes.clear();
+                rmp_serde::encode::write_named(&mut self.bytes, response)?;
+                stdout.write_all(&(self.bytes.len() as u32).to_be_bytes())?;
+                stdout.write_all(&self.bytes)?;
+            }
+        }
+        stdout.flush()?;
+        Ok(())
+    }
+}
+
+impl Default for BotConnection {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Reads the protocol that the judge asked for from the [`Protocol::ENV_VAR`]
+/// environment variable, defaulting to JSON if it is not set.
+pub fn protocol_from_env() -> anyhow::Result<Protocol> {
+    let value = match std::env::var(Protocol::ENV_VAR) {
+        Ok(value) => value,
+        Err(std::env::VarError::NotPresent) => return Ok(Protocol::default()),
+        Err(err) => return Err(err.into()),
+    };
+    [Protocol::Json, Protocol::MsgPack]
+        .into_iter()
+        .find(|protocol| protocol.name() == value)
+        .ok_or_else(|| anyhow::anyhow!("Unknown protocol '{}' in {}", value, Protocol::ENV_VAR))
+}
@@ -0,0 +1,154 @@
+use gomori::{CardsSet, MatchContext, PlayTurnResponse, PlayerView, Request, Rules};
+
+/// Whether the bot won a game.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GameOutcome {
+    Won,
+    Lost,
+    /// Nobody won the game.
+    Tied,
+}
+
+impl GameOutcome {
+    /// The outcome of the game that was played between two match contexts, or `None` if
+    /// they are not of consecutive games of the same match.
+    pub fn between(before: &MatchContext, after: &MatchContext) -> Option<Self> {
+        let new_games = (
+            after.wins.checked_sub(before.wins)?,
+            after.losses.checked_sub(before.losses)?,
+            after.ties.checked_sub(before.ties)?,
+        );
+        match new_games {
+            (1, 0, 0) => Some(GameOutcome::Won),
+            (0, 1, 0) => Some(GameOutcome::Lost),
+            (0, 0, 1) => Some(GameOutcome::Tied),
+            _ => None,
+        }
+    }
+}
+
+/// What the bot knows about a game once it has ended, see
+/// [`Bot::on_game_end()`](crate::Bot::on_game_end).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct GameSummary {
+    /// The cards won by the bot's legal turns.
+    pub cards_won: CardsSet,
+    /// The cards won by the other players, up to the bot's last turn.
+    pub cards_won_by_opponents: CardsSet,
+    /// The score of the match before the game, if the judge sent it.
+    pub match_context: Option<MatchContext>,
+    /// How the game ended. This is only known if the judge sent a [`MatchContext`] for
+    /// this game and the next one, or the final score with [`Request::Bye`].
+    pub outcome: Option<GameOutcome>,
+}
+
+impl GameSummary {
+    // Fills in the outcome from the score after the game
+    fn ended(mut self, after: Option<MatchContext>) -> Self {
+        if let (Some(before), Some(after)) = (&self.match_context, &after) {
+            self.outcome = GameOutcome::between(before, after);
+        }
+        self
+    }
+}
+
+// Tracks the current game for the runners of the bot traits, to summarize it at the end
+#[derive(Debug, Default)]
+pub(crate) struct GameTracker {
+    // The summary so far, if a game is in progress
+    game: Option<GameSummary>,
+    // The rules of the current game
+    rules: Rules,
+}
+
+impl GameTracker {
+    /// Starts a new game, and returns the summary of the previous one, if there was one.
+    pub(crate) fn new_game(
+        &mut self,
+        match_context: Option<MatchContext>,
+        rules: Rules,
+    ) -> Option<GameSummary> {
+        self.rules = rules;
+        let previous = self.game.replace(GameSummary {
+            match_context,
+            ..Default::default()
+        })?;
+        Some(previous.ended(match_context))
+    }
+
+    /// The view of a `PlayTurn` request, which includes the cards won so far.
+    pub(crate) fn view(&mut self, request: &Request) -> PlayerView {
+        let game = self.game.get_or_insert_with(GameSummary::default);
+        let view = PlayerView::from_request(
+            request,
+            self.rules,
+            game.cards_won,
+            game.cards_won_by_opponents,
+        )
+        .expect("The request is a PlayTurn request");
+        game.cards_won_by_opponents = view.cards_won_by_opponents;
+        view
+    }
+
+    /// Adds the cards won by the response to a request with this view.
+    pub(crate) fn record_response(&mut self, view: &PlayerView, response: &PlayTurnResponse) {
+        // An illegal move ends the game anyway
+        if let (Some(game), Ok(cards_won)) = (
+            &mut self.game,
+            view.cards_won_with(response.cards_to_play()),
+        ) {
+            game.cards_won |= cards_won;
+        }
+    }
+
+    /// Ends the session, and returns the summary of the last game, if there was one. The
+    /// final score of the match is sent with [`Request::Bye`], if at all.
+    pub(crate) fn finish(&mut self, match_context: Option<MatchContext>) -> Option<GameSummary> {
+        Some(self.game.take()?.ended(match_context))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn context(wins: usize, losses: usize, ties: usize) -> Option<MatchContext> {
+        Some(MatchContext {
+            wins,
+            losses,
+            ties,
+            num_games: Some(10),
+        })
+    }
+
+    #[test]
+    fn outcomes_follow_from_the_match_contexts() {
+        let mut tracker = GameTracker::default();
+        assert_eq!(tracker.new_game(context(0, 0, 0), Rules::STANDARD), None);
+        let summary = tracker.new_game(context(0, 1, 0), Rules::STANDARD).unwrap();
+        assert_eq!(summary.match_context, context(0, 0, 0));
+        assert_eq!(summary.outcome, Some(GameOutcome::Lost));
+        let summary = tracker.new_game(context(1, 1, 0), Rules::STANDARD).unwrap();
+        assert_eq!(summary.outcome, Some(GameOutcome::Won));
+        // A new match, or a judge that only sometimes sends the context
+        assert_eq!(
+            tracker
+                .new_game(context(0, 0, 0), Rules::STANDARD)
+                .unwrap()
+                .outcome,
+            None
+        );
+        assert_eq!(
+            tracker.new_game(None, Rules::STANDARD).unwrap().outcome,
+            None
+        );
+        assert_eq!(tracker.finish(context(0, 0, 1)).unwrap().outcome, None);
+        assert_eq!(tracker.finish(None), None);
+        // The final score tells how the last game ended
+        tracker.new_game(context(2, 1, 0), Rules::STANDARD);
+        let summary = tracker.finish(context(2, 1, 1)).unwrap();
+        assert_eq!(summary.outcome, Some(GameOutcome::Tied));
+        tracker.new_game(context(2, 1, 0), Rules::STANDARD);
+        assert_eq!(tracker.finish(None).unwrap().outcome, None);
+    }
+}
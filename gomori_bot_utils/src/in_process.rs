@@ -0,0 +1,378 @@
+use gomori::{Card, Okay, PlayTurnResponse, Request};
+use serde::Serialize;
+
+use crate::{Bot, GameSetup, GameTracker};
+
+/// Runs a [`Bot`] in the same process as the judge, e.g. to play many games of self-play
+/// without the overhead of a bot process.
+///
+/// The bot gets the same calls as from [`Bot::run()`], including those of
+/// [`Bot::on_session_start()`] before the first request and [`Bot::on_game_end()`], and the
+/// responses are encoded like by a [`BotConnection`](crate::BotConnection).
+pub struct InProcessBot<B> {
+    bot: B,
+    tracker: GameTracker,
+    session_started: bool,
+}
+
+impl<B: Bot> InProcessBot<B> {
+    pub fn new(bot: B) -> Self {
+        Self {
+            bot,
+            tracker: GameTracker::default(),
+            session_started: false,
+        }
+    }
+
+    pub fn bot(&self) -> &B {
+        &self.bot
+    }
+
+    /// Handles a JSON request, and returns the JSON response, or an empty string for
+    /// [`Request::Bye`].
+    pub fn handle_json(&mut self, request: &str) -> anyhow::Result<String> {
+        let request = serde_json::from_str(request)?;
+        match self.handle(request)? {
+            Some(response) => Ok(serde_json::to_string(&response)?),
+            None => Ok(String::new()),
+        }
+    }
+
+    /// Like [`handle_json()`](Self::handle_json), with the MessagePack encoding of
+    /// [`Protocol::MsgPack`](gomori::Protocol::MsgPack), but without the length prefix.
+    pub fn handle_msgpack(&mut self, request: &[u8]) -> anyhow::Result<Vec<u8>> {
+        let request = rmp_serde::from_slice(request)?;
+        match self.handle(request)? {
+            Some(response) => Ok(rmp_serde::to_vec_named(&response)?),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    fn handle(&mut self, request: Request) -> anyhow::Result<Option<Response>> {
+        if !self.session_started {
+            self.session_started = true;
+            self.bot.on_session_start()?;
+        }
+        respond(&mut self.bot, &mut self.tracker, request)
+    }
+}
+
+// The response to any request
+#[derive(Serialize)]
+#[serde(untagged)]
+pub(crate) enum Response {
+    Okay(Okay),
+    Card(Card),
+    Turn(PlayTurnResponse),
+}
+
+// Lets the bot handle the request, and returns its response, or `None` at the end of the
+// session, after the summary of the last game has been passed to the bot.
+pub(crate) fn respond<B: Bot + ?Sized>(
+    bot: &mut B,
+    tracker: &mut GameTracker,
+    request: Request,
+) -> anyhow::Result<Option<Response>> {
+    let response = match request {
+        Request::NewGame {
+            color,
+            num_players,
+            seat,
+            suit,
+            match_context,
+            rules,
+        } => {
+            if let Some(summary) = tracker.new_game(match_context, rules) {
+                bot.on_game_end(summary)?;
+            }
+            if let Some(context) = match_context {
+                bot.on_match_context(context);
+            }
+            bot.new_game(color, GameSetup::new(color, num_players, seat, suit, rules));
+            Response::Okay(Okay())
+        }
+        Request::PlayFirstTurn { cards, i, j } => {
+            Response::Card(bot.play_first_turn_at(cards, i, j))
+        }
+        Request::PlayTurn { .. } => {
+            let view = tracker.view(&request);
+            let board_view = view.clone();
+            let response = bot.play_turn_view(view);
+            tracker.record_response(&board_view, &response);
+            Response::Turn(response)
+        }
+        Request::Bye { match_context } => {
+            if let Some(summary) = tracker.finish(match_context) {
+                bot.on_game_end(summary)?;
+            }
+            return Ok(None);
+        }
+    };
+    Ok(Some(response))
+}
+
+#[cfg(test)]
+mod tests {
+    use gomori::{
+        card, ctp, field, sample_player_view, CardsSet, Color, Deck, Hand, MatchContext,
+        PlayerView, Rules, Suit,
+    };
+
+    use super::*;
+    use crate::{GameOutcome, GamePhase, GameSummary};
+
+    #[derive(Default)]
+    struct CountingBot {
+        sessions: usize,
+        summaries: Vec<GameSummary>,
+        setup: Option<GameSetup>,
+        first_card_location: Option<(i8, i8)>,
+    }
+
+    impl Bot for CountingBot {
+        fn new_game(&mut self, _color: Color, setup: GameSetup) {
+            self.setup = Some(setup);
+        }
+
+        fn play_first_turn(&mut self, cards: Hand) -> Card {
+            cards.into_iter().next().unwrap()
+        }
+
+        fn play_first_turn_at(&mut self, cards: Hand, i: i8, j: i8) -> Card {
+            self.first_card_location = Some((i, j));
+            self.play_first_turn(cards)
+        }
+
+        // Plays the first card at the end of the row of the scripted game
+        fn play_turn(
+            &mut self,
+            cards: Hand,
+            _fields: Vec<gomori::Field>,
+            _cards_won_by_opponent: CardsSet,
+            _phase: GamePhase,
+        ) -> PlayTurnResponse {
+            let card = cards.into_iter().next().unwrap();
+            PlayTurnResponse::play(vec![ctp(card, 0, 3)])
+        }
+
+        fn on_session_start(&mut self) -> anyhow::Result<()> {
+            self.sessions += 1;
+            Ok(())
+        }
+
+        fn on_game_end(&mut self, summary: GameSummary) -> anyhow::Result<()> {
+            self.summaries.push(summary);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn handles_requests_like_run() {
+        let new_game = Request::NewGame {
+            color: Color::Black,
+            num_players: 2,
+            seat: 0,
+            suit: None,
+            match_context: None,
+            rules: Rules::STANDARD,
+        };
+        let hand = Hand::try_from([
+            card!("2♠"),
+            card!("3♠"),
+            card!("4♠"),
+            card!("5♠"),
+            card!("6♠"),
+        ])
+        .unwrap();
+        let first_turn = Request::PlayFirstTurn {
+            cards: hand,
+            i: 1,
+            j: -2,
+        };
+        let mut bot = InProcessBot::new(CountingBot::default());
+        let response = bot
+            .handle_json(&serde_json::to_string(&new_game).unwrap())
+            .unwrap();
+        assert_eq!(response, serde_json::to_string(&Okay()).unwrap());
+        let response = bot
+            .handle_msgpack(&rmp_serde::to_vec_named(&first_turn).unwrap())
+            .unwrap();
+        assert_eq!(
+            rmp_serde::from_slice::<Card>(&response).unwrap(),
+            card!("2♠")
+        );
+        assert_eq!(bot.bot().first_card_location, Some((1, -2)));
+        bot.handle_json(&serde_json::to_string(&new_game).unwrap())
+            .unwrap();
+        assert_eq!(bot.bot().summaries.len(), 1);
+        let bye = Request::Bye {
+            match_context: None,
+        };
+        let response = bot
+            .handle_json(&serde_json::to_string(&bye).unwrap())
+            .unwrap();
+        assert_eq!(response, "");
+        assert_eq!(bot.bot().sessions, 1);
+        assert_eq!(bot.bot().summaries.len(), 2);
+    }
+
+    #[test]
+    fn summarizes_the_last_game_with_the_final_score() {
+        let context = |wins| MatchContext {
+            wins,
+            losses: 1,
+            ties: 0,
+            num_games: Some(3),
+        };
+        let requests = [
+            Request::NewGame {
+                color: Color::Red,
+                num_players: 2,
+                seat: 1,
+                suit: None,
+                match_context: Some(context(1)),
+                rules: Rules::STANDARD,
+            },
+            Request::PlayTurn {
+                cards: Hand::try_from(&[card!("T♥")][..]).unwrap(),
+                fields: vec![
+                    field(0, 0, card!("7♥")),
+                    field(0, 1, card!("8♥")),
+                    field(0, 2, card!("9♥")),
+                ],
+                cards_won_by_opponent: [card!("A♣")].into(),
+                draw_pile_size: 10,
+                opponent_draw_pile_size: 11,
+                turn_number: 7,
+            },
+            Request::Bye {
+                match_context: Some(context(2)),
+            },
+        ];
+        let mut bot = InProcessBot::new(CountingBot::default());
+        for request in &requests {
+            bot.handle_json(&serde_json::to_string(request).unwrap())
+                .unwrap();
+        }
+        assert_eq!(
+            bot.bot().summaries,
+            [GameSummary {
+                cards_won: CardsSet::from_iter([card!("7♥"), card!("8♥"), card!("9♥")]),
+                cards_won_by_opponents: CardsSet::from_iter([card!("A♣")]),
+                match_context: Some(context(1)),
+                outcome: Some(GameOutcome::Won),
+            }]
+        );
+    }
+
+    fn variant() -> Rules {
+        Rules {
+            line_length: 3,
+            aces_are_wild: false,
+            ..Rules::STANDARD
+        }
+    }
+
+    #[test]
+    fn passes_the_players_to_the_bot() {
+        let new_game = Request::NewGame {
+            color: Color::Red,
+            num_players: 3,
+            seat: 2,
+            suit: Some(Suit::Heart),
+            match_context: None,
+            rules: variant(),
+        };
+        let mut bot = InProcessBot::new(CountingBot::default());
+        bot.handle_json(&serde_json::to_string(&new_game).unwrap())
+            .unwrap();
+        assert_eq!(
+            bot.bot().setup,
+            Some(GameSetup {
+                num_players: 3,
+                seat: 2,
+                deck: Deck::Suit(Suit::Heart),
+                rules: variant(),
+            })
+        );
+    }
+
+    // Bots that implement only one or none of the play_turn methods
+    #[derive(Default)]
+    struct ViewBot {
+        view: Option<PlayerView>,
+    }
+
+    struct NoTurnBot;
+
+    impl Bot for ViewBot {
+        fn new_game(&mut self, _color: Color, _setup: GameSetup) {}
+
+        fn play_first_turn(&mut self, cards: Hand) -> Card {
+            cards.into_iter().next().unwrap()
+        }
+
+        fn play_turn_view(&mut self, view: PlayerView) -> PlayTurnResponse {
+            self.view = Some(view);
+            PlayTurnResponse::skip()
+        }
+    }
+
+    impl Bot for NoTurnBot {
+        fn new_game(&mut self, _color: Color, _setup: GameSetup) {}
+
+        fn play_first_turn(&mut self, cards: Hand) -> Card {
+            cards.into_iter().next().unwrap()
+        }
+    }
+
+    #[test]
+    fn boards_follow_the_rules_of_the_game() {
+        let requests = [
+            Request::NewGame {
+                color: Color::Red,
+                num_players: 2,
+                seat: 1,
+                suit: None,
+                match_context: None,
+                rules: variant(),
+            },
+            Request::PlayTurn {
+                cards: Hand::try_from(&[card!("T♥")][..]).unwrap(),
+                fields: vec![field(0, 0, card!("7♥"))],
+                cards_won_by_opponent: Default::default(),
+                draw_pile_size: 10,
+                opponent_draw_pile_size: 11,
+                turn_number: 2,
+            },
+        ];
+        let mut bot = InProcessBot::new(ViewBot::default());
+        for request in &requests {
+            bot.handle_json(&serde_json::to_string(request).unwrap())
+                .unwrap();
+        }
+        let view = bot.bot().view.as_ref().unwrap();
+        assert_eq!(view.board.rules(), variant());
+        assert_eq!(GamePhase::from(view).rules, variant());
+    }
+
+    #[test]
+    fn play_turn_forwards_to_play_turn_view() {
+        let view = sample_player_view();
+        let mut bot = ViewBot::default();
+        let response = bot.play_turn(
+            view.hand,
+            view.board.to_fields_vec(),
+            view.new_cards_won_by_opponents,
+            GamePhase::from(&view),
+        );
+        assert_eq!(response, PlayTurnResponse::skip());
+        assert_eq!(bot.view, Some(view));
+    }
+
+    #[test]
+    #[should_panic(expected = "must implement play_turn() or play_turn_view()")]
+    fn bots_must_implement_a_play_turn_method() {
+        NoTurnBot.play_turn_view(sample_player_view());
+    }
+}
@@ -1,59 +1,221 @@
+#[cfg(feature = "async")]
+mod async_bot;
 mod card_counting;
+mod color_symmetry;
+mod connection;
+mod game_summary;
+mod in_process;
+mod opening_book;
+mod storage;
+#[cfg(feature = "async")]
+pub use async_bot::*;
 pub use card_counting::*;
+pub use color_symmetry::*;
+pub use connection::*;
+pub use game_summary::*;
+pub use in_process::InProcessBot;
+pub use opening_book::*;
+pub use storage::*;
 
-use gomori::{Card, CardsSet, Color, Field, Okay, PlayTurnResponse, Request};
+use std::cell::RefCell;
+
+use gomori::{
+    Board, Card, CardsSet, Color, Deck, Field, Hand, MatchContext, PlayTurnResponse, PlayerView,
+    Rules, Suit,
+};
+use in_process::respond;
+
+/// How far the game has progressed, e.g. for playing differently in the endgame.
+///
+/// See [`Request::PlayTurn`] for the meaning of the fields.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct GamePhase {
+    pub draw_pile_size: usize,
+    pub opponent_draw_pile_size: usize,
+    pub turn_number: usize,
+    /// The rules of the game, as sent in the [`Request::NewGame`](gomori::Request::NewGame).
+    ///
+    /// Bots should build the board from the fields with [`Board::new_with_rules()`].
+    pub rules: Rules,
+}
+
+impl From<&PlayerView> for GamePhase {
+    fn from(view: &PlayerView) -> Self {
+        Self {
+            draw_pile_size: view.draw_pile_size,
+            opponent_draw_pile_size: view.opponent_draw_pile_size,
+            turn_number: view.turn_number,
+            rules: view.board.rules(),
+        }
+    }
+}
+
+/// Who plays a game, and with which cards, e.g. for counting the cards of more than one
+/// opponent.
+///
+/// See [`Request::NewGame`](gomori::Request::NewGame) for the meaning of the fields.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct GameSetup {
+    pub num_players: usize,
+    pub seat: usize,
+    /// The cards that the player plays with, i.e. a color in a two-player game, and a
+    /// suit otherwise.
+    pub deck: Deck,
+    pub rules: Rules,
+}
+
+impl GameSetup {
+    /// The setup of a two-player game with the standard rules, in which the player has the
+    /// given color and the given seat.
+    pub fn two_players(color: Color, seat: usize) -> Self {
+        Self::new(color, 2, seat, None, Rules::STANDARD)
+    }
+
+    /// The setup as sent in a [`Request::NewGame`](gomori::Request::NewGame).
+    pub fn new(
+        color: Color,
+        num_players: usize,
+        seat: usize,
+        suit: Option<Suit>,
+        rules: Rules,
+    ) -> Self {
+        Self {
+            num_players,
+            seat,
+            deck: suit.map_or(Deck::Color(color), Deck::Suit),
+            rules,
+        }
+    }
+}
+
+/// The view of a turn for bots that are only given the contents of the
+/// [`Request::PlayTurn`](gomori::Request::PlayTurn).
+///
+/// The cards won in earlier turns are unknown, so `cards_won` is empty and
+/// `cards_won_by_opponents` only contains the cards won since the player's last turn.
+pub fn view_from_turn(
+    cards: Hand,
+    fields: &[Field],
+    cards_won_by_opponent: CardsSet,
+    phase: GamePhase,
+) -> PlayerView {
+    PlayerView {
+        board: Board::new_with_rules(fields, phase.rules),
+        hand: cards,
+        cards_won: CardsSet::new(),
+        cards_won_by_opponents: cards_won_by_opponent,
+        new_cards_won_by_opponents: cards_won_by_opponent,
+        draw_pile_size: phase.draw_pile_size,
+        opponent_draw_pile_size: phase.opponent_draw_pile_size,
+        turn_number: phase.turn_number,
+    }
+}
+
+// The default play_turn() and play_turn_view() call each other, so a bot that implements
+// neither would recurse until the stack overflows. To panic with a clear message instead,
+// the default play_turn_view() records the type of the bot, and the default play_turn()
+// checks that it isn't recorded. Since this only depends on the type, records are never
+// removed.
+pub(crate) type BotTypes = std::thread::LocalKey<RefCell<Vec<&'static str>>>;
+
+thread_local! {
+    static BOTS_WITHOUT_PLAY_TURN_VIEW: RefCell<Vec<&'static str>> =
+        const { RefCell::new(Vec::new()) };
+}
+
+pub(crate) fn record_default_play_turn_view<B: ?Sized>(types: &'static BotTypes) {
+    let name = std::any::type_name::<B>();
+    types.with_borrow_mut(|types| {
+        if !types.contains(&name) {
+            types.push(name);
+        }
+    });
+}
+
+pub(crate) fn assert_implements_play_turn<B: ?Sized>(types: &'static BotTypes) {
+    let name = std::any::type_name::<B>();
+    assert!(
+        !types.with_borrow(|types| types.contains(&name)),
+        "{} must implement play_turn() or play_turn_view()",
+        name
+    );
+}
 
 /// A trait to simplify writing bots.
 pub trait Bot {
-    fn new_game(&mut self, color: Color);
-    fn play_first_turn(&mut self, cards: [Card; 5]) -> Card;
+    fn new_game(&mut self, color: Color, setup: GameSetup);
+    fn play_first_turn(&mut self, cards: Hand) -> Card;
+    /// Like [`play_first_turn()`](Bot::play_first_turn), but also with the location
+    /// `(i, j)` at which the card will be placed.
+    ///
+    /// This is what [`run()`](Bot::run) calls. By default, it calls `play_first_turn()`.
+    fn play_first_turn_at(&mut self, cards: Hand, _i: i8, _j: i8) -> Card {
+        self.play_first_turn(cards)
+    }
+    /// Returns the cards to play, or [`PlayTurnResponse::skip()`] if none can be played.
+    ///
+    /// Bots implement either this method or [`play_turn_view()`](Bot::play_turn_view).
+    /// By default, it calls `play_turn_view()` with a view in which only the cards won by
+    /// the opponents since the player's last turn are known, see [`view_from_turn()`].
     fn play_turn(
         &mut self,
-        cards: [Card; 5],
+        cards: Hand,
         fields: Vec<Field>,
         cards_won_by_opponent: CardsSet,
-    ) -> PlayTurnResponse;
+        phase: GamePhase,
+    ) -> PlayTurnResponse {
+        assert_implements_play_turn::<Self>(&BOTS_WITHOUT_PLAY_TURN_VIEW);
+        self.play_turn_view(view_from_turn(cards, &fields, cards_won_by_opponent, phase))
+    }
+
+    /// Like [`play_turn()`](Bot::play_turn), but with everything the bot knows about the
+    /// game in a [`PlayerView`], including the cards won by all players so far.
+    ///
+    /// This is what [`run()`](Bot::run) calls. By default, it calls `play_turn()`.
+    fn play_turn_view(&mut self, view: PlayerView) -> PlayTurnResponse {
+        record_default_play_turn_view::<Self>(&BOTS_WITHOUT_PLAY_TURN_VIEW);
+        let phase = GamePhase::from(&view);
+        self.play_turn(
+            view.hand,
+            view.board.to_fields_vec(),
+            view.new_cards_won_by_opponents,
+            phase,
+        )
+    }
+
+    /// Called once when [`run()`](Bot::run) starts, before the first request is handled.
+    ///
+    /// This is a good place to load state from a [`BotStorage`].
+    fn on_session_start(&mut self) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    /// Called before [`new_game()`](Bot::new_game) with the score of the match so far, if
+    /// the judge sent it, e.g. for bots that play more riskily when they are behind.
+    fn on_match_context(&mut self, _context: MatchContext) {}
+
+    /// Called after each game, i.e. when the next game starts or the session ends.
+    ///
+    /// This is a good place to save state to a [`BotStorage`].
+    fn on_game_end(&mut self, _summary: GameSummary) -> anyhow::Result<()> {
+        Ok(())
+    }
 
     fn run(&mut self) -> anyhow::Result<()> {
-        // Communication happens through stdin/stdout.
-        // Stderr can be used for logging.
-        let mut stdin = std::io::stdin().lock();
-        let mut stdout = std::io::stdout().lock();
-        let mut buf = String::new();
-
-        loop {
-            // Read the next line into buf
-            buf.clear(); // because stdin.read_line() appends to the buffer
-            use std::io::BufRead;
-            let num_bytes_read = stdin.read_line(&mut buf)?;
-            if num_bytes_read == 0 {
-                // 0 bytes read means EOF - the judge has exited.
-                break Ok(());
-            }
+        let mut connection = BotConnection::with_protocol(protocol_from_env()?);
+        let mut tracker = GameTracker::default();
 
-            let req = serde_json::from_str::<Request>(buf.trim_end())?;
-
-            match req {
-                Request::NewGame { color } => {
-                    self.new_game(color);
-                    serde_json::to_writer(&mut stdout, &Okay())?;
-                }
-                Request::PlayFirstTurn { cards } => {
-                    serde_json::to_writer(&mut stdout, &self.play_first_turn(cards))?
-                }
-                Request::PlayTurn {
-                    cards,
-                    fields,
-                    cards_won_by_opponent,
-                } => serde_json::to_writer(
-                    &mut stdout,
-                    &self.play_turn(cards, fields, CardsSet::from_iter(cards_won_by_opponent)),
-                )?,
-                Request::Bye => break Ok(()),
+        self.on_session_start()?;
+        while let Some(req) = connection.receive()? {
+            match respond(self, &mut tracker, req)? {
+                Some(response) => connection.send(&response)?,
+                None => return Ok(()),
             }
-            use std::io::Write;
-            writeln!(stdout)?;
-            stdout.flush()?;
         }
+        // The judge closed the connection without saying bye
+        if let Some(summary) = tracker.finish(None) {
+            self.on_game_end(summary)?;
+        }
+        Ok(())
     }
 }
@@ -0,0 +1,251 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufReader, BufWriter};
+use std::path::Path;
+
+use anyhow::Context;
+use gomori::{
+    Board, CanonicalBoard, Card, CardToPlay, CardsSet, Color, Field, Hand, MatchContext,
+    PlayTurnResponse, PlayerView, TurnValidator,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{Bot, GamePhase, GameSetup, GameSummary};
+
+/// Recommended turns for early-game positions.
+///
+/// Positions are identified by the [`CanonicalBoard`] and the cards in the hand,
+/// so a recommendation also applies to any translated, rotated or reflected board.
+///
+/// Stored as a JSON list of entries, each with the `board` as a list of [`Field`]s,
+/// the `cards` in the hand and the `response`. The board doesn't need to be in
+/// canonical form when editing the file by hand.
+#[derive(Clone, Debug, Default)]
+pub struct OpeningBook {
+    entries: HashMap<(CanonicalBoard, CardsSet), Vec<CardToPlay>>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Entry {
+    board: CanonicalBoard,
+    cards: CardsSet,
+    response: Vec<CardToPlay>,
+}
+
+impl OpeningBook {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Adds a recommended turn for the given position, replacing any previous one.
+    pub fn insert(&mut self, board: &Board, cards: CardsSet, response: PlayTurnResponse) {
+        let (canonical, transform) = board.canonicalize();
+        let response = response
+            .cards_to_play()
+            .iter()
+            .map(|ctp| transform.apply(ctp))
+            .collect();
+        self.entries.insert((canonical, cards), response);
+    }
+
+    /// Looks up the recommended turn for the given position, in the coordinates of `board`.
+    ///
+    /// Recommendations that are not legal in this position are ignored.
+    pub fn lookup(&self, board: &Board, cards: CardsSet) -> Option<PlayTurnResponse> {
+        let (canonical, transform) = board.canonicalize();
+        let response = self.entries.get(&(canonical, cards))?;
+        let inverse = transform.inverse();
+        let mut validator = TurnValidator::new(board.clone(), cards);
+        let mut cards_to_play = Vec::with_capacity(response.len());
+        for &ctp in response {
+            let ctp = inverse.apply(&ctp);
+            validator.play(ctp).ok()?;
+            cards_to_play.push(ctp);
+        }
+        validator.finish().ok()?;
+        Some(PlayTurnResponse::play_or_skip(board, cards, cards_to_play))
+    }
+
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let inner = || -> anyhow::Result<Self> {
+            let f = File::open(path)?;
+            let entries: Vec<Entry> = serde_json::from_reader(BufReader::new(f))?;
+            Ok(Self {
+                entries: entries
+                    .into_iter()
+                    .map(|entry| ((entry.board, entry.cards), entry.response))
+                    .collect(),
+            })
+        };
+        inner().with_context(|| format!("Could not load opening book '{}'", path.display()))
+    }
+
+    /// Writes the book to a file, sorted by position so that the output is deterministic.
+    pub fn save(&self, path: &Path) -> anyhow::Result<()> {
+        let inner = || -> anyhow::Result<()> {
+            let mut entries: Vec<_> = self
+                .entries
+                .iter()
+                .map(|((board, cards), response)| Entry {
+                    board: board.clone(),
+                    cards: *cards,
+                    response: response.clone(),
+                })
+                .collect();
+            entries.sort_by_cached_key(|entry| (entry.board.clone(), Vec::from_iter(entry.cards)));
+            let writer = BufWriter::new(File::create(path)?);
+            serde_json::to_writer_pretty(writer, &entries)?;
+            Ok(())
+        };
+        inner().with_context(|| format!("Could not save opening book '{}'", path.display()))
+    }
+}
+
+/// Plays the turns recommended by an [`OpeningBook`] for your bot.
+///
+/// During the first `num_turns` turns of each game (not counting the first turn of the
+/// game, which has no board yet), the book is consulted first. Requests that are not in
+/// the book are passed on to the wrapped bot, as are all other requests.
+pub struct OpeningBookWrapper<T> {
+    bot: T,
+    book: OpeningBook,
+    num_turns: usize,
+    turns_played: usize,
+}
+
+impl<T> OpeningBookWrapper<T> {
+    pub fn new(bot: T, book: OpeningBook, num_turns: usize) -> Self {
+        Self {
+            bot,
+            book,
+            num_turns,
+            turns_played: 0,
+        }
+    }
+
+    // Counts the turn, and looks it up if it's early enough
+    fn book_response(&mut self, board: &Board, cards: Hand) -> Option<PlayTurnResponse> {
+        self.turns_played += 1;
+        if self.turns_played <= self.num_turns {
+            self.book.lookup(board, cards.cards())
+        } else {
+            None
+        }
+    }
+}
+
+impl<T: Bot> Bot for OpeningBookWrapper<T> {
+    fn new_game(&mut self, color: Color, setup: GameSetup) {
+        self.turns_played = 0;
+        self.bot.new_game(color, setup);
+    }
+
+    fn play_first_turn(&mut self, cards: Hand) -> Card {
+        self.bot.play_first_turn(cards)
+    }
+
+    fn play_first_turn_at(&mut self, cards: Hand, i: i8, j: i8) -> Card {
+        self.bot.play_first_turn_at(cards, i, j)
+    }
+
+    fn play_turn(
+        &mut self,
+        cards: Hand,
+        fields: Vec<Field>,
+        cards_won_by_opponent: CardsSet,
+        phase: GamePhase,
+    ) -> PlayTurnResponse {
+        if let Some(response) =
+            self.book_response(&Board::new_with_rules(&fields, phase.rules), cards)
+        {
+            return response;
+        }
+        self.bot
+            .play_turn(cards, fields, cards_won_by_opponent, phase)
+    }
+
+    fn play_turn_view(&mut self, view: PlayerView) -> PlayTurnResponse {
+        if let Some(response) = self.book_response(&view.board, view.hand) {
+            return response;
+        }
+        self.bot.play_turn_view(view)
+    }
+
+    fn on_session_start(&mut self) -> anyhow::Result<()> {
+        self.bot.on_session_start()
+    }
+
+    fn on_match_context(&mut self, context: MatchContext) {
+        self.bot.on_match_context(context);
+    }
+
+    fn on_game_end(&mut self, summary: GameSummary) -> anyhow::Result<()> {
+        self.bot.on_game_end(summary)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use gomori::card;
+
+    use super::*;
+
+    fn board(fields: &[(i8, i8, &str)]) -> Board {
+        let fields: Vec<_> = fields
+            .iter()
+            .map(|&(i, j, card)| Field {
+                i,
+                j,
+                top_card: Some(card.parse().unwrap()),
+                hidden_cards: BTreeSet::new(),
+            })
+            .collect();
+        Board::new(&fields)
+    }
+
+    #[test]
+    fn lookup_in_symmetric_position() {
+        let cards = CardsSet::from_iter([
+            card!("2♦"),
+            card!("3♦"),
+            card!("4♦"),
+            card!("5♦"),
+            card!("6♦"),
+        ]);
+        let mut book = OpeningBook::new();
+        let response = PlayTurnResponse::play(vec![CardToPlay {
+            card: card!("2♦"),
+            i: 0,
+            j: 2,
+            target_field_for_king_ability: None,
+        }]);
+        book.insert(&board(&[(0, 0, "7♠"), (0, 1, "8♠")]), cards, response);
+
+        // The same position, transposed and translated
+        let other_board = board(&[(3, 5, "7♠"), (4, 5, "8♠")]);
+        let response = book.lookup(&other_board, cards).unwrap();
+        let cards_to_play = response.cards_to_play();
+        assert_eq!((cards_to_play[0].i, cards_to_play[0].j), (5, 5));
+        // A different hand is not in the book
+        assert!(book
+            .lookup(&other_board, cards.remove(card!("6♦")))
+            .is_none());
+
+        let path = std::env::temp_dir().join(format!("gomori_book_{}.json", std::process::id()));
+        book.save(&path).unwrap();
+        let loaded = OpeningBook::load(&path).unwrap();
+        std::fs::remove_file(path).unwrap();
+        assert_eq!(loaded.len(), 1);
+        assert!(loaded.lookup(&other_board, cards).is_some());
+    }
+}
@@ -0,0 +1,146 @@
+use std::fs::File;
+use std::io::{BufReader, BufWriter};
+use std::path::{Path, PathBuf};
+
+use anyhow::Context;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+/// Persistent storage for bot state, e.g. weights that are adjusted between games.
+///
+/// Each value is stored under a key as a JSON file in the storage directory, which
+/// survives both `NewGame` boundaries and restarts of the bot process.
+///
+/// Values are written atomically, i.e. a bot that is killed while saving leaves
+/// the previous value intact. Every value is also tagged with a version number,
+/// and values written with a different version are ignored when loading. Bump the
+/// version whenever the format of the stored data changes.
+///
+/// ```no_run
+/// use gomori_bot_utils::BotStorage;
+/// # fn main() -> anyhow::Result<()> {
+/// let storage = BotStorage::new("bot_state", 1)?;
+/// let weights: Vec<f64> = storage.load("weights")?.unwrap_or_else(|| vec![1.0, 0.5]);
+/// storage.save("weights", &weights)?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct BotStorage {
+    directory: PathBuf,
+    version: u32,
+}
+
+#[derive(Serialize)]
+struct VersionedRef<'a, T> {
+    version: u32,
+    data: &'a T,
+}
+
+#[derive(Deserialize)]
+struct Versioned {
+    version: u32,
+    data: serde_json::Value,
+}
+
+impl BotStorage {
+    /// Opens the storage in the given directory, creating the directory if needed.
+    pub fn new(directory: impl Into<PathBuf>, version: u32) -> anyhow::Result<Self> {
+        let directory = directory.into();
+        std::fs::create_dir_all(&directory).with_context(|| {
+            format!(
+                "Could not create storage directory '{}'",
+                directory.display()
+            )
+        })?;
+        Ok(Self { directory, version })
+    }
+
+    /// The directory that the values are stored in.
+    pub fn directory(&self) -> &Path {
+        &self.directory
+    }
+
+    /// Loads the value stored under the given key.
+    ///
+    /// Returns `None` if there is no value, or if it was stored with a different version.
+    pub fn load<T: DeserializeOwned>(&self, key: &str) -> anyhow::Result<Option<T>> {
+        let path = self.path_for_key(key)?;
+        let inner = || -> anyhow::Result<Option<T>> {
+            let f = match File::open(&path) {
+                Ok(f) => f,
+                Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+                Err(err) => return Err(err.into()),
+            };
+            let versioned: Versioned = serde_json::from_reader(BufReader::new(f))?;
+            if versioned.version != self.version {
+                return Ok(None);
+            }
+            Ok(Some(serde_json::from_value(versioned.data)?))
+        };
+        inner().with_context(|| format!("Could not load '{}'", path.display()))
+    }
+
+    /// Stores a value under the given key, replacing any previous value.
+    pub fn save<T: Serialize>(&self, key: &str, value: &T) -> anyhow::Result<()> {
+        let path = self.path_for_key(key)?;
+        let tmp_path = path.with_extension("json.tmp");
+        let inner = || -> anyhow::Result<()> {
+            let mut writer = BufWriter::new(File::create(&tmp_path)?);
+            let versioned = VersionedRef {
+                version: self.version,
+                data: value,
+            };
+            serde_json::to_writer(&mut writer, &versioned)?;
+            let f = writer.into_inner().map_err(|err| err.into_error())?;
+            f.sync_all()?;
+            // Renaming is atomic, so readers either see the old or the new value.
+            std::fs::rename(&tmp_path, &path)?;
+            Ok(())
+        };
+        inner().with_context(|| format!("Could not save '{}'", path.display()))
+    }
+
+    /// Removes the value stored under the given key, if any.
+    pub fn remove(&self, key: &str) -> anyhow::Result<()> {
+        let path = self.path_for_key(key)?;
+        match std::fs::remove_file(&path) {
+            Err(err) if err.kind() != std::io::ErrorKind::NotFound => {
+                Err(err).with_context(|| format!("Could not remove '{}'", path.display()))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn path_for_key(&self, key: &str) -> anyhow::Result<PathBuf> {
+        let is_valid_char = |c: char| c.is_ascii_alphanumeric() || c == '_' || c == '-';
+        if key.is_empty() || !key.chars().all(is_valid_char) {
+            anyhow::bail!(
+                "Invalid storage key '{}', only ASCII letters, digits, '_' and '-' are allowed",
+                key
+            );
+        }
+        Ok(self.directory.join(format!("{}.json", key)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn save_and_load() {
+        let dir = std::env::temp_dir().join(format!("gomori_storage_{}", std::process::id()));
+        let storage = BotStorage::new(&dir, 1).unwrap();
+        assert_eq!(storage.load::<Vec<u8>>("weights").unwrap(), None);
+        storage.save("weights", &vec![1u8, 2, 3]).unwrap();
+        assert_eq!(storage.load("weights").unwrap(), Some(vec![1u8, 2, 3]));
+        // A different version doesn't see the old value
+        let storage_v2 = BotStorage::new(&dir, 2).unwrap();
+        assert_eq!(storage_v2.load::<Vec<u8>>("weights").unwrap(), None);
+        assert!(storage.save("../weights", &0).is_err());
+        storage.remove("weights").unwrap();
+        assert_eq!(storage.load::<Vec<u8>>("weights").unwrap(), None);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,38 @@
+use std::fmt::Debug;
+
+/// A state of a two-player game, as needed by the [`Searcher`](crate::Searcher).
+///
+/// A player may make several moves in a row, e.g. play the cards of a combo one by one.
+/// Scores are always from the perspective of the player whose move it is.
+pub trait GameStateTrait: Clone {
+    type Move: Copy + Debug + PartialEq;
+
+    /// The moves of the player whose move it is.
+    ///
+    /// Must not be empty unless the state is terminal.
+    fn legal_moves(&self) -> Vec<Self::Move>;
+
+    fn apply(&self, mv: Self::Move) -> Self;
+
+    fn is_terminal(&self) -> bool;
+
+    /// Identifies the player whose move it is, e.g. 0 or 1.
+    fn side_to_move(&self) -> usize;
+
+    /// How good the state is for the player whose move it is. Higher is better.
+    fn evaluate(&self) -> i32;
+
+    /// Sorts the moves so that the most promising ones come first, which lets alpha-beta
+    /// pruning cut off more of the tree.
+    ///
+    /// The move stored in the transposition table, if any, is tried before all of them.
+    fn order_moves(&self, _moves: &mut [Self::Move]) {}
+
+    /// A hash of the state for the transposition table, or `None` to not store this state.
+    ///
+    /// Two states with the same key are treated as the same state, so the key should cover
+    /// everything that affects the rest of the game.
+    fn key(&self) -> Option<u64> {
+        None
+    }
+}
@@ -0,0 +1,359 @@
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+use gomori::{
+    Board, CardToPlay, DeterminizedState, FinishedTurn, Hand, PlayTurnResponse, PlayerState,
+    TurnState, TurnValidator,
+};
+
+use crate::{GameStateTrait, Searcher};
+
+/// A move in a [`GomoriState`]: a turn is made up of the cards played one by one,
+/// followed by ending the turn.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum SearchMove {
+    Play(CardToPlay),
+    /// Ends the turn, or skips it if no card has been played.
+    EndTurn,
+}
+
+/// A state of a two-player game in which all cards are known, for searching with a [`Searcher`].
+///
+/// Follows the same rules as [`execute_turn()`](gomori::execute_turn), including
+/// the end of the game when a draw pile runs out or both players skip their turn.
+#[derive(Clone, Debug)]
+pub struct GomoriState {
+    /// The turn in progress, which also holds the current board.
+    turn: TurnValidator,
+    players: [PlayerState; 2],
+    side_to_move: usize,
+    turns_skipped_in_a_row: u8,
+    game_ended: bool,
+    evaluate: fn(&GomoriState) -> i32,
+}
+
+impl GomoriState {
+    /// Starts a turn of `state.me`, in which the given function evaluates the states.
+    ///
+    /// The player is 0 and the opponent is 1. Panics if the state has more than one
+    /// opponent.
+    pub fn new(state: DeterminizedState, evaluate: fn(&GomoriState) -> i32) -> Self {
+        let DeterminizedState {
+            board,
+            me,
+            opponents,
+        } = state;
+        let Ok([opponent]) = <[PlayerState; 1]>::try_from(opponents) else {
+            panic!("Only games with two players can be searched");
+        };
+        Self {
+            turn: TurnValidator::new(board, me.hand.cards()),
+            players: [me, opponent],
+            side_to_move: 0,
+            turns_skipped_in_a_row: 0,
+            game_ended: false,
+            evaluate,
+        }
+    }
+
+    /// The board after the cards played so far in this turn.
+    pub fn board(&self) -> &Board {
+        self.turn.board()
+    }
+
+    /// The state of a player as of the start of the current turn.
+    pub fn player(&self, idx: usize) -> &PlayerState {
+        &self.players[idx]
+    }
+
+    /// The turn in progress of the player whose move it is.
+    pub fn turn(&self) -> &TurnValidator {
+        &self.turn
+    }
+
+    pub fn game_ended(&self) -> bool {
+        self.game_ended
+    }
+
+    /// The number of cards won by the player whose move it is, including those won in
+    /// the current turn, minus the number of cards won by the other player.
+    ///
+    /// A simple evaluation function.
+    pub fn card_difference(&self) -> i32 {
+        let own = self.players[self.side_to_move].cards_won | self.turn.cards_won();
+        let other = self.players[1 - self.side_to_move].cards_won;
+        own.len() as i32 - other.len() as i32
+    }
+
+    // Mirrors what execute_turn() does after the cards are played
+    fn after_end_of_turn(&self) -> Self {
+        let skipped = self.turn.num_cards_played() == 0;
+        let FinishedTurn {
+            board,
+            remaining_hand,
+            cards_won,
+        } = self
+            .turn
+            .clone()
+            .finish()
+            .expect("Only legal moves are applied");
+        let mut players = self.players.clone();
+        let mut turns_skipped_in_a_row = self.turns_skipped_in_a_row;
+        let mut game_ended = false;
+        let player = &mut players[self.side_to_move];
+        if skipped {
+            turns_skipped_in_a_row += 1;
+            game_ended = turns_skipped_in_a_row == 2;
+        } else {
+            turns_skipped_in_a_row = 0;
+            let mut hand =
+                Hand::try_from(remaining_hand).expect("The remaining hand is part of the hand");
+            while !hand.is_full() && !game_ended {
+                match player.draw_pile.draw() {
+                    Some(card) => {
+                        hand = hand
+                            .insert(card)
+                            .expect("The draw pile and the hand are disjoint")
+                    }
+                    None => game_ended = true,
+                }
+            }
+            player.hand = hand;
+            // Like in execute_turn(), the cards won in the last turn don't count
+            if !game_ended {
+                player.cards_won |= cards_won;
+            }
+        }
+        let side_to_move = 1 - self.side_to_move;
+        Self {
+            turn: TurnValidator::new(board, players[side_to_move].hand.cards()),
+            players,
+            side_to_move,
+            turns_skipped_in_a_row,
+            game_ended,
+            evaluate: self.evaluate,
+        }
+    }
+}
+
+impl GameStateTrait for GomoriState {
+    type Move = SearchMove;
+
+    fn legal_moves(&self) -> Vec<SearchMove> {
+        if self.game_ended {
+            return Vec::new();
+        }
+        let mut moves: Vec<_> = self
+            .turn
+            .playable_cards()
+            .into_iter()
+            .flat_map(|card| self.turn.board().legal_plays(card))
+            .map(SearchMove::Play)
+            .collect();
+        if self.turn.can_finish() {
+            moves.push(SearchMove::EndTurn);
+        }
+        moves
+    }
+
+    fn apply(&self, mv: SearchMove) -> Self {
+        match mv {
+            SearchMove::Play(ctp) => {
+                let mut state = self.clone();
+                state.turn.play(ctp).expect("Only legal moves are applied");
+                state
+            }
+            SearchMove::EndTurn => self.after_end_of_turn(),
+        }
+    }
+
+    fn is_terminal(&self) -> bool {
+        self.game_ended
+    }
+
+    fn side_to_move(&self) -> usize {
+        self.side_to_move
+    }
+
+    fn evaluate(&self) -> i32 {
+        (self.evaluate)(self)
+    }
+
+    /// Plays that win cards first, then combos, then the others, and ending the turn last.
+    fn order_moves(&self, moves: &mut [SearchMove]) {
+        moves.sort_by_cached_key(|&mv| match mv {
+            SearchMove::Play(ctp) => match self.turn.board().calculate(ctp) {
+                Ok(effects) => -(effects.cards_won.len() as i32 * 2 + i32::from(effects.combo)),
+                Err(_) => 0,
+            },
+            SearchMove::EndTurn => 1,
+        });
+    }
+
+    fn key(&self) -> Option<u64> {
+        let mut hasher = DefaultHasher::new();
+        self.board().zobrist_key().hash(&mut hasher);
+        for player in &self.players {
+            player.hand.hash(&mut hasher);
+            player.cards_won.hash(&mut hasher);
+            // Determinizations of the same game differ in the draw piles, and the table is
+            // shared between them
+            player.draw_pile.hash(&mut hasher);
+        }
+        self.turn.remaining_hand().hash(&mut hasher);
+        self.turn.cards_won().hash(&mut hasher);
+        let turn_state = match self.turn.state() {
+            TurnState::NotStarted => 0u8,
+            TurnState::InCombo => 1,
+            TurnState::Ended => 2,
+        };
+        turn_state.hash(&mut hasher);
+        self.side_to_move.hash(&mut hasher);
+        self.turns_skipped_in_a_row.hash(&mut hasher);
+        self.game_ended.hash(&mut hasher);
+        Some(hasher.finish())
+    }
+}
+
+/// Searches the cards to play in a turn, one card at a time.
+///
+/// The states should be the start of the same player's turn, e.g. several determinizations
+/// of the same game. Since the player's own cards are known, its moves are the same in each.
+/// There must be at least one state.
+pub fn best_turn(
+    searcher: &mut Searcher<GomoriState>,
+    mut roots: Vec<GomoriState>,
+) -> PlayTurnResponse {
+    let board = roots[0].board().clone();
+    let hand = roots[0].turn().remaining_hand();
+    let mut cards_to_play = Vec::new();
+    while let Some(result) = searcher.search_determinized(&roots) {
+        match result.best_move {
+            SearchMove::Play(ctp) => {
+                cards_to_play.push(ctp);
+                roots = roots
+                    .iter()
+                    .map(|root| root.apply(result.best_move))
+                    .collect();
+            }
+            SearchMove::EndTurn => break,
+        }
+    }
+    PlayTurnResponse::play_or_skip(&board, hand, cards_to_play)
+}
+
+#[cfg(test)]
+mod tests {
+    use gomori::{execute_turn, random_game_in_progress, DrawPile, GameInProgress};
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    use super::*;
+    use crate::SearchConfig;
+
+    fn config() -> SearchConfig {
+        SearchConfig {
+            max_depth: 3,
+            time_limit: None,
+            tt_entries: 1 << 12,
+        }
+    }
+
+    fn root(game: &GameInProgress, evaluate: fn(&GomoriState) -> i32) -> GomoriState {
+        GomoriState::new(
+            DeterminizedState {
+                board: game.board.clone(),
+                me: game.player.clone(),
+                opponents: vec![game.opponent.clone()],
+            },
+            evaluate,
+        )
+    }
+
+    #[test]
+    fn best_turns_are_legal() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut searcher = Searcher::new(config());
+        for _ in 0..10 {
+            let game = random_game_in_progress(&mut rng);
+            let turn = best_turn(
+                &mut searcher,
+                vec![root(&game, GomoriState::card_difference)],
+            );
+            execute_turn(&game.player, &game.board, turn).unwrap();
+        }
+    }
+
+    // Only counts the cards won by player 0, so that the opponent's moves don't matter
+    fn cards_won_by_player_0(state: &GomoriState) -> i32 {
+        let mut cards_won = state.player(0).cards_won;
+        if state.side_to_move() == 0 {
+            cards_won |= state.turn().cards_won();
+            cards_won.len() as i32
+        } else {
+            -(cards_won.len() as i32)
+        }
+    }
+
+    /// States that only differ in the order of a draw pile, like two determinizations of the
+    /// same game, must not share entries in the transposition table.
+    #[test]
+    fn draw_piles_are_part_of_the_key() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let game = random_game_in_progress(&mut rng);
+        let state = root(&game, GomoriState::card_difference);
+        let mut other_game = game.clone();
+        let mut cards = other_game.opponent.draw_pile.cards().to_vec();
+        cards.reverse();
+        assert_ne!(cards, other_game.opponent.draw_pile.cards());
+        other_game.opponent.draw_pile = DrawPile::from_cards(cards);
+        let other_state = root(&other_game, GomoriState::card_difference);
+        assert_ne!(state.key(), other_state.key());
+        assert_eq!(state.key(), state.clone().key());
+    }
+
+    /// Searching deep enough to see the whole turn finds the turn that wins the most cards.
+    #[test]
+    fn finds_the_turn_that_wins_the_most_cards() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let mut searcher = Searcher::new(SearchConfig {
+            max_depth: 6,
+            ..config()
+        });
+        for _ in 0..10 {
+            let game = random_game_in_progress(&mut rng);
+            // If the game ends with this turn, the cards won in it don't count
+            if game.player.draw_pile.len() < Hand::MAX_SIZE {
+                continue;
+            }
+            let root = root(&game, cards_won_by_player_0);
+            let turn = best_turn(&mut searcher, vec![root.clone()]);
+            searcher.clear();
+
+            // Plays every legal sequence of cards, and remembers how many cards it wins
+            fn most_cards_won(validator: TurnValidator) -> u32 {
+                let mut most = if validator.can_finish() {
+                    validator.cards_won().len()
+                } else {
+                    0
+                };
+                for card in validator.playable_cards() {
+                    for ctp in validator.board().legal_plays(card) {
+                        let mut next = validator.clone();
+                        next.play(ctp).unwrap();
+                        most = most.max(most_cards_won(next));
+                    }
+                }
+                most
+            }
+            let mut validator = root.turn().clone();
+            for &ctp in turn.cards_to_play() {
+                validator.play(ctp).unwrap();
+            }
+            assert_eq!(
+                validator.cards_won().len(),
+                most_cards_won(root.turn().clone())
+            );
+        }
+    }
+}
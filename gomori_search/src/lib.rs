@@ -0,0 +1,48 @@
+//! A game tree search framework for bots.
+//!
+//! A game is described by implementing [`GameStateTrait`] for its states. A [`Searcher`]
+//! then finds the best move with iterative deepening negamax and alpha-beta pruning,
+//! using the trait's move ordering hook and a [`TranspositionTable`].
+//!
+//! Since the opponent's hand and the draw piles are hidden in gomori, the search runs on
+//! determinized states, i.e. states where the hidden cards have been made up, see
+//! [`gomori::determinize()`]. [`Searcher::search_determinized()`] averages the scores
+//! of the moves over several such states.
+//!
+//! [`GomoriState`] implements the trait for gomori, so a bot only needs to supply an
+//! evaluation function:
+//!
+//! ```
+//! use gomori_search::{best_turn, GomoriState, SearchConfig, Searcher};
+//! # use rand::SeedableRng;
+//! # let mut rng = rand::rngs::StdRng::seed_from_u64(0);
+//! # let me = gomori::PlayerState::new(gomori::Color::Red, &mut rng);
+//! # let opponent = gomori::PlayerState::new(gomori::Color::Black, &mut rng);
+//! # let first_card = me.hand.iter().next().unwrap();
+//! # let first_turn = gomori::execute_first_turn(&me, first_card).unwrap();
+//! # let (board, me) = (first_turn.board, first_turn.state);
+//! # let states = vec![gomori::DeterminizedState { board, me: opponent, opponents: vec![me] }];
+//!
+//! // Prefers winning cards, and then having many cards on the board
+//! fn evaluate(state: &GomoriState) -> i32 {
+//!     10 * GomoriState::card_difference(state) + state.board().len() as i32
+//! }
+//!
+//! let mut searcher = Searcher::new(SearchConfig::default());
+//! // The states are usually sampled with gomori::determinize()
+//! let roots: Vec<_> = states
+//!     .into_iter()
+//!     .map(|state| GomoriState::new(state, evaluate))
+//!     .collect();
+//! let turn = best_turn(&mut searcher, roots);
+//! ```
+
+pub use game_state::*;
+pub use gomori_state::*;
+pub use search::*;
+pub use transposition::*;
+
+mod game_state;
+mod gomori_state;
+mod search;
+mod transposition;
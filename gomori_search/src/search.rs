@@ -0,0 +1,441 @@
+use std::time::{Duration, Instant};
+
+use crate::{Bound, Entry, GameStateTrait, TranspositionTable};
+
+/// Larger than any score that [`GameStateTrait::evaluate()`] should return.
+pub const INFINITY: i32 = i32::MAX;
+
+// How many nodes are searched between checks of the time limit
+const NODES_PER_TIME_CHECK: u64 = 1024;
+
+/// Settings for a [`Searcher`].
+#[derive(Clone, Debug)]
+pub struct SearchConfig {
+    /// The search deepens one move at a time, up to this many moves.
+    pub max_depth: u32,
+    /// Stop deepening once this much time has passed, and use the deepest completed
+    /// iteration. The first iteration is always completed.
+    pub time_limit: Option<Duration>,
+    /// The number of states that the transposition table can hold.
+    pub tt_entries: usize,
+}
+
+impl Default for SearchConfig {
+    fn default() -> Self {
+        Self {
+            max_depth: 12,
+            time_limit: Some(Duration::from_millis(100)),
+            tt_entries: 1 << 16,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SearchResult<M> {
+    pub best_move: M,
+    /// The score of the best move, averaged over the root states.
+    pub score: i32,
+    /// The depth of the deepest completed iteration.
+    pub depth: u32,
+    /// The number of states visited, over all iterations.
+    pub nodes: u64,
+}
+
+/// Iterative deepening negamax search with alpha-beta pruning.
+///
+/// The transposition table is kept between searches, so that searching the states after
+/// the chosen move can reuse the results. Call [`clear()`](Searcher::clear) when the states
+/// of a new game would be mixed up with those of the last one.
+pub struct Searcher<S: GameStateTrait> {
+    config: SearchConfig,
+    tt: TranspositionTable<S::Move>,
+    deadline: Option<Instant>,
+    aborted: bool,
+    // Whether some line of the current search was cut off by the depth limit
+    depth_limited: bool,
+    nodes: u64,
+}
+
+impl<S: GameStateTrait> Searcher<S> {
+    pub fn new(config: SearchConfig) -> Self {
+        Self {
+            tt: TranspositionTable::new(config.tt_entries),
+            config,
+            deadline: None,
+            aborted: false,
+            depth_limited: false,
+            nodes: 0,
+        }
+    }
+
+    pub fn config(&self) -> &SearchConfig {
+        &self.config
+    }
+
+    pub fn transposition_table(&self) -> &TranspositionTable<S::Move> {
+        &self.tt
+    }
+
+    pub fn clear(&mut self) {
+        self.tt.clear();
+    }
+
+    /// Finds the best move in the given state.
+    ///
+    /// Returns `None` if the state is terminal or has no legal moves.
+    pub fn search(&mut self, root: &S) -> Option<SearchResult<S::Move>> {
+        self.search_determinized(std::slice::from_ref(root))
+    }
+
+    /// Finds the move with the best average score over several states, e.g. different
+    /// guesses of the opponent's hand.
+    ///
+    /// Moves that are legal in only some of the states are averaged over those states.
+    /// With more than one state, every move is searched with a full window, which is
+    /// slower than finding just the best move of a single state.
+    pub fn search_determinized(&mut self, roots: &[S]) -> Option<SearchResult<S::Move>> {
+        let deadline = self.config.time_limit.map(|limit| Instant::now() + limit);
+        self.aborted = false;
+        self.nodes = 0;
+        let exact_scores = roots.len() > 1;
+
+        let mut result = None;
+        for depth in 1..=self.config.max_depth {
+            // The first iteration is not aborted, so that there is always a result
+            self.deadline = if depth == 1 { None } else { deadline };
+            self.depth_limited = false;
+            // The total score of each move, and the number of states in which it is legal
+            let mut totals: Vec<(S::Move, i64, i64)> = Vec::new();
+            for root in roots {
+                for (mv, score) in self.score_root_moves(root, depth, exact_scores) {
+                    match totals.iter_mut().find(|(other, _, _)| *other == mv) {
+                        Some((_, total, count)) => {
+                            *total += i64::from(score);
+                            *count += 1;
+                        }
+                        None => totals.push((mv, i64::from(score), 1)),
+                    }
+                }
+                if self.aborted {
+                    break;
+                }
+            }
+            if self.aborted {
+                break;
+            }
+
+            let mut best: Option<(S::Move, f64)> = None;
+            for (mv, total, count) in totals {
+                let average = total as f64 / count as f64;
+                if best.is_none_or(|(_, best_average)| average > best_average) {
+                    best = Some((mv, average));
+                }
+            }
+            result = best.map(|(best_move, average)| SearchResult {
+                best_move,
+                score: average.round() as i32,
+                depth,
+                nodes: 0,
+            });
+            // Deeper iterations would give the same result
+            if !self.depth_limited {
+                break;
+            }
+        }
+        result.map(|result| SearchResult {
+            nodes: self.nodes,
+            ..result
+        })
+    }
+
+    // The scores of all moves in the root state. Unless exact scores are requested, only
+    // the score of the best move is exact, and the others may be too high.
+    fn score_root_moves(&mut self, root: &S, depth: u32, exact: bool) -> Vec<(S::Move, i32)> {
+        if root.is_terminal() {
+            return Vec::new();
+        }
+        self.nodes += 1;
+        let key = root.key();
+        let tt_move = key
+            .and_then(|key| self.tt.get(key))
+            .and_then(|entry| entry.best_move);
+        let mut alpha = -INFINITY;
+        let mut scores = Vec::new();
+        let mut best: Option<(S::Move, i32)> = None;
+        for mv in ordered_moves(root, tt_move) {
+            let child = root.apply(mv);
+            let window_start = if exact { -INFINITY } else { alpha };
+            let score = self.child_score(root, &child, depth - 1, window_start, INFINITY);
+            if self.aborted {
+                return Vec::new();
+            }
+            if best.is_none_or(|(_, best_score)| score > best_score) {
+                best = Some((mv, score));
+            }
+            alpha = alpha.max(score);
+            scores.push((mv, score));
+        }
+        if let (Some(key), Some((best_move, score))) = (key, best) {
+            self.tt.insert(Entry {
+                key,
+                depth,
+                score,
+                bound: Bound::Exact,
+                best_move: Some(best_move),
+            });
+        }
+        scores
+    }
+
+    // The score of the child state from the perspective of the player to move in the parent
+    fn child_score(&mut self, parent: &S, child: &S, depth: u32, alpha: i32, beta: i32) -> i32 {
+        if child.side_to_move() == parent.side_to_move() {
+            self.negamax(child, depth, alpha, beta)
+        } else {
+            -self.negamax(child, depth, -beta, -alpha)
+        }
+    }
+
+    fn negamax(&mut self, state: &S, depth: u32, mut alpha: i32, beta: i32) -> i32 {
+        self.nodes += 1;
+        if self.nodes.is_multiple_of(NODES_PER_TIME_CHECK) && self.out_of_time() {
+            self.aborted = true;
+        }
+        if self.aborted {
+            return 0;
+        }
+        if state.is_terminal() {
+            return state.evaluate();
+        }
+        if depth == 0 {
+            self.depth_limited = true;
+            return state.evaluate();
+        }
+
+        let key = state.key();
+        let mut tt_move = None;
+        if let Some(entry) = key.and_then(|key| self.tt.get(key)) {
+            tt_move = entry.best_move;
+            if entry.depth >= depth {
+                let usable = match entry.bound {
+                    Bound::Exact => true,
+                    Bound::Lower => entry.score >= beta,
+                    Bound::Upper => entry.score <= alpha,
+                };
+                if usable {
+                    self.depth_limited |= entry.depth != u32::MAX;
+                    return entry.score;
+                }
+            }
+        }
+
+        let alpha_orig = alpha;
+        let depth_limited_before = std::mem::take(&mut self.depth_limited);
+        let mut best_score = -INFINITY;
+        let mut best_move = None;
+        for mv in ordered_moves(state, tt_move) {
+            let child = state.apply(mv);
+            let score = self.child_score(state, &child, depth - 1, alpha, beta);
+            if self.aborted {
+                return 0;
+            }
+            if score > best_score {
+                best_score = score;
+                best_move = Some(mv);
+            }
+            alpha = alpha.max(score);
+            if alpha >= beta {
+                break;
+            }
+        }
+        if best_move.is_none() {
+            // No legal moves, although the state is not terminal
+            self.depth_limited = depth_limited_before;
+            return state.evaluate();
+        }
+        let exhaustive = !self.depth_limited;
+        self.depth_limited |= depth_limited_before;
+
+        if let Some(key) = key {
+            let bound = if best_score <= alpha_orig {
+                Bound::Upper
+            } else if best_score >= beta {
+                Bound::Lower
+            } else {
+                Bound::Exact
+            };
+            self.tt.insert(Entry {
+                key,
+                depth: if exhaustive { u32::MAX } else { depth },
+                score: best_score,
+                bound,
+                best_move,
+            });
+        }
+        best_score
+    }
+
+    fn out_of_time(&self) -> bool {
+        self.deadline
+            .is_some_and(|deadline| Instant::now() >= deadline)
+    }
+}
+
+// The legal moves, ordered by the state, but with the move from the transposition table first
+fn ordered_moves<S: GameStateTrait>(state: &S, tt_move: Option<S::Move>) -> Vec<S::Move> {
+    let mut moves = state.legal_moves();
+    state.order_moves(&mut moves);
+    if let Some(pos) = tt_move.and_then(|tt_move| moves.iter().position(|&mv| mv == tt_move)) {
+        moves[..=pos].rotate_right(1);
+    }
+    moves
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Players take turns taking one or two sticks, and whoever takes the last stick wins.
+    /// After taking two sticks, the same player may also take another stick, or pass.
+    #[derive(Clone, Debug)]
+    struct Nim {
+        sticks: u32,
+        player: usize,
+        may_take_another: bool,
+    }
+
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    enum NimMove {
+        Take(u32),
+        Pass,
+    }
+
+    impl GameStateTrait for Nim {
+        type Move = NimMove;
+
+        fn legal_moves(&self) -> Vec<NimMove> {
+            if self.may_take_another {
+                return vec![NimMove::Take(1), NimMove::Pass];
+            }
+            (1..=self.sticks.min(2)).map(NimMove::Take).collect()
+        }
+
+        fn apply(&self, mv: NimMove) -> Self {
+            match mv {
+                NimMove::Take(n) => {
+                    let sticks = self.sticks - n;
+                    // Taking another stick is only possible if it isn't the last one
+                    let may_take_another = n == 2 && !self.may_take_another && sticks > 1;
+                    Nim {
+                        sticks,
+                        player: if may_take_another || sticks == 0 {
+                            self.player
+                        } else {
+                            1 - self.player
+                        },
+                        may_take_another,
+                    }
+                }
+                NimMove::Pass => Nim {
+                    player: 1 - self.player,
+                    may_take_another: false,
+                    ..*self
+                },
+            }
+        }
+
+        fn is_terminal(&self) -> bool {
+            self.sticks == 0
+        }
+
+        fn side_to_move(&self) -> usize {
+            self.player
+        }
+
+        // The player to move at the end is the one who took the last stick
+        fn evaluate(&self) -> i32 {
+            if self.sticks == 0 {
+                1
+            } else {
+                0
+            }
+        }
+
+        fn key(&self) -> Option<u64> {
+            Some(u64::from(self.sticks) * 4 + self.player as u64 * 2 + self.may_take_another as u64)
+        }
+    }
+
+    // Plain minimax, for comparison
+    fn minimax(state: &Nim) -> i32 {
+        if state.is_terminal() {
+            return state.evaluate();
+        }
+        state
+            .legal_moves()
+            .into_iter()
+            .map(|mv| {
+                let child = state.apply(mv);
+                if child.side_to_move() == state.side_to_move() {
+                    minimax(&child)
+                } else {
+                    -minimax(&child)
+                }
+            })
+            .max()
+            .unwrap()
+    }
+
+    fn config() -> SearchConfig {
+        SearchConfig {
+            max_depth: 30,
+            time_limit: None,
+            tt_entries: 64,
+        }
+    }
+
+    #[test]
+    fn finds_the_same_scores_as_minimax() {
+        let mut searcher = Searcher::new(config());
+        for sticks in 1..12 {
+            let state = Nim {
+                sticks,
+                player: 0,
+                may_take_another: false,
+            };
+            let result = searcher.search(&state).unwrap();
+            assert_eq!(result.score, minimax(&state), "{} sticks", sticks);
+            let child = state.apply(result.best_move);
+            let child_score = if child.side_to_move() == state.side_to_move() {
+                minimax(&child)
+            } else {
+                -minimax(&child)
+            };
+            assert_eq!(child_score, result.score, "{} sticks", sticks);
+        }
+    }
+
+    #[test]
+    fn stops_at_max_depth_or_end_of_game() {
+        let state = Nim {
+            sticks: 11,
+            player: 0,
+            may_take_another: false,
+        };
+        let mut searcher = Searcher::new(SearchConfig {
+            max_depth: 2,
+            ..config()
+        });
+        assert_eq!(searcher.search(&state).unwrap().depth, 2);
+        searcher.clear();
+
+        let mut searcher = Searcher::new(config());
+        let result = searcher.search(&state).unwrap();
+        assert!(result.depth < config().max_depth);
+        // The second search reuses the transposition table
+        assert!(searcher.search(&state).unwrap().nodes < result.nodes);
+
+        let state = Nim { sticks: 0, ..state };
+        assert_eq!(searcher.search(&state), None);
+    }
+}
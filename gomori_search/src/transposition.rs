@@ -0,0 +1,57 @@
+/// How a stored score relates to the true score of a state.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Bound {
+    Exact,
+    /// The search was cut off, the true score is at least this high.
+    Lower,
+    /// No move reached alpha, the true score is at most this high.
+    Upper,
+}
+
+/// The result of searching a state, as stored in a [`TranspositionTable`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Entry<M> {
+    pub key: u64,
+    /// How many moves deep the state was searched.
+    pub depth: u32,
+    pub score: i32,
+    pub bound: Bound,
+    pub best_move: Option<M>,
+}
+
+/// A fixed-size cache of search results, indexed by [`GameStateTrait::key()`](crate::GameStateTrait::key).
+///
+/// When two states map to the same slot, the one searched more deeply is kept.
+#[derive(Clone, Debug)]
+pub struct TranspositionTable<M> {
+    entries: Vec<Option<Entry<M>>>,
+}
+
+impl<M: Copy> TranspositionTable<M> {
+    /// Creates a table with room for `num_entries` states, at least one.
+    pub fn new(num_entries: usize) -> Self {
+        Self {
+            entries: vec![None; num_entries.max(1)],
+        }
+    }
+
+    fn slot(&self, key: u64) -> usize {
+        (key % self.entries.len() as u64) as usize
+    }
+
+    pub fn get(&self, key: u64) -> Option<Entry<M>> {
+        self.entries[self.slot(key)].filter(|entry| entry.key == key)
+    }
+
+    pub fn insert(&mut self, entry: Entry<M>) {
+        let slot = self.slot(entry.key);
+        match self.entries[slot] {
+            Some(existing) if existing.key != entry.key && existing.depth > entry.depth => {}
+            _ => self.entries[slot] = Some(entry),
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.fill(None);
+    }
+}
@@ -1,5 +1,12 @@
+mod watch;
+
 use std::io::{self, stdout};
+use std::path::PathBuf;
+use std::time::Duration;
 
+use clap::Parser;
+use gomori::{card, Card};
+use judge::card_name;
 use ratatui::{
     crossterm::{
         event::{self, Event, KeyCode},
@@ -10,20 +17,65 @@ use ratatui::{
     widgets::*,
 };
 
-fn main() -> io::Result<()> {
+#[derive(Parser)]
+struct Args {
+    /// Let two bots play a game against each other, and watch it. These are the paths to the
+    /// config JSON files of the bots, as for the judge
+    #[arg(long, num_args = 2, value_names = ["PLAYER_1", "PLAYER_2"])]
+    watch: Option<Vec<PathBuf>>,
+
+    /// How long to show the board after each card that is played, in milliseconds
+    #[arg(long, default_value_t = 500)]
+    delay_ms: u64,
+
+    /// When watching, show the best turns for the player whose turn it is, as rated by
+    /// `gomori::advisor`, and highlight where the best one plays its cards
+    #[arg(long)]
+    hints: bool,
+
+    /// RNG seed for dealing the cards
+    #[arg(long)]
+    seed: Option<u64>,
+
+    /// The directory that the stderr output of the bots is written to, since it would
+    /// garble the screen
+    #[arg(long, default_value_os_t = std::env::temp_dir().join("gomori_tui_logs"))]
+    bot_logs_dir: PathBuf,
+}
+
+fn main() -> anyhow::Result<()> {
+    let args = Args::parse();
+    // Start the bots before taking over the terminal, so that errors are readable
+    let watcher = match &args.watch {
+        Some(config_paths) => Some(watch::GameWatcher::new(
+            config_paths,
+            &args.bot_logs_dir,
+            Duration::from_millis(args.delay_ms),
+            args.hints,
+            args.seed,
+        )?),
+        None => None,
+    };
+
     enable_raw_mode()?;
     stdout().execute(EnterAlternateScreen)?;
     let mut terminal = Terminal::new(CrosstermBackend::new(stdout()))?;
-    let player = HumanPlayer {};
+    let result = match watcher {
+        Some(watcher) => watcher.run(&mut terminal),
+        None => show_hand(&mut terminal).map_err(anyhow::Error::from),
+    };
+    disable_raw_mode()?;
+    stdout().execute(LeaveAlternateScreen)?;
+    result
+}
 
+fn show_hand(terminal: &mut Terminal<impl Backend>) -> io::Result<()> {
+    let player = HumanPlayer {};
     let mut should_quit = false;
     while !should_quit {
         terminal.draw(|frame| player.ui(frame))?;
         should_quit = handle_events()?;
     }
-
-    disable_raw_mode()?;
-    stdout().execute(LeaveAlternateScreen)?;
     Ok(())
 }
 
@@ -33,7 +85,9 @@ const CARD_WIDTH: u16 = 6;
 
 const HAND_CARDS_WIDGET_WIDTH: u16 = CARD_WIDTH * 5;
 
-struct HandCardsWidget {}
+struct HandCardsWidget {
+    cards: Vec<Card>,
+}
 
 impl Widget for HandCardsWidget {
     fn render(self, area: Rect, buf: &mut Buffer) {
@@ -52,7 +106,7 @@ impl Widget for HandCardsWidget {
             (area.x + 3 * CARD_WIDTH, area.y + 1),
             (area.x + 4 * CARD_WIDTH, area.y),
         ];
-        for (x, y) in coords {
+        for ((x, y), card) in coords.into_iter().zip(self.cards) {
             let block = Block::new()
                 .border_type(BorderType::Rounded)
                 .borders(Borders::all());
@@ -65,13 +119,14 @@ impl Widget for HandCardsWidget {
                 },
                 buf,
             );
-            buf.set_string(x + 2, y + 1, "J♥", Style::new());
+            buf.set_string(x + 2, y + 1, card_name(card), Style::new());
         }
     }
 }
 
-fn handle_events() -> io::Result<bool> {
-    if event::poll(std::time::Duration::from_millis(16))? {
+/// Whether the user pressed "q" within the timeout.
+fn quit_pressed(timeout: Duration) -> io::Result<bool> {
+    if event::poll(timeout)? {
         if let Event::Key(key) = event::read()? {
             if key.kind == event::KeyEventKind::Press && key.code == KeyCode::Char('q') {
                 return Ok(true);
@@ -81,6 +136,10 @@ fn handle_events() -> io::Result<bool> {
     Ok(false)
 }
 
+fn handle_events() -> io::Result<bool> {
+    quit_pressed(Duration::from_millis(16))
+}
+
 impl HumanPlayer {
     fn ui(&self, frame: &mut Frame) {
         let main_layout = Layout::new(
@@ -88,6 +147,7 @@ impl HumanPlayer {
             [Constraint::Min(0), Constraint::Length(6)],
         )
         .split(frame.size());
-        frame.render_widget(HandCardsWidget {}, main_layout[1]);
+        let cards = vec![card!("J♥"); 5];
+        frame.render_widget(HandCardsWidget { cards }, main_layout[1]);
     }
 }
@@ -0,0 +1,332 @@
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use gomori::advisor::{material, rank_turns};
+use gomori::{Board, Color, Hand, PlayTurnResponse};
+use judge::{
+    card_name, play_game, BotStderr, Deal, GameEvent, GameResult, MatchConfig, Player,
+    PlayerConfig, PlayerStats, PlayerType,
+};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use ratatui::{prelude::*, widgets::*};
+
+use crate::{quit_pressed, HandCardsWidget, CARD_WIDTH};
+
+/// The height of a field on the board, including its border.
+const FIELD_HEIGHT: u16 = 3;
+
+/// How many of the best turns are shown as hints.
+const NUM_HINTS: usize = 3;
+
+/// Runs a game between two bots, and shows every card played on the terminal.
+pub struct GameWatcher {
+    players: Vec<Player>,
+    delay: Duration,
+    hints: bool,
+    rng: StdRng,
+}
+
+impl GameWatcher {
+    /// Starts the bots with the given config files. Their stderr output is written to
+    /// `bot_logs_dir`. With `hints`, the best turns are shown for every turn.
+    pub fn new(
+        config_paths: &[PathBuf],
+        bot_logs_dir: &Path,
+        delay: Duration,
+        hints: bool,
+        seed: Option<u64>,
+    ) -> anyhow::Result<Self> {
+        let players = config_paths
+            .iter()
+            .map(|path| {
+                let mut config = PlayerConfig::load(path)?;
+                anyhow::ensure!(
+                    config.player_type == PlayerType::Bot,
+                    "'{}' is not a bot, but only bots can be watched",
+                    path.display()
+                );
+                config.stderr = BotStderr::Dir(bot_logs_dir.to_path_buf());
+                Player::from_config(config)
+            })
+            .collect::<anyhow::Result<_>>()?;
+        let rng = StdRng::seed_from_u64(seed.unwrap_or_else(rand::random));
+        Ok(Self {
+            players,
+            delay,
+            hints,
+            rng,
+        })
+    }
+
+    /// Plays the game, and then shows its result until "q" is pressed.
+    ///
+    /// If "q" is pressed during the game, it is played to the end without being shown.
+    pub fn run(mut self, terminal: &mut Terminal<impl Backend>) -> anyhow::Result<()> {
+        let num_players = self.players.len();
+        let deal = Deal::random(num_players, None, &mut self.rng);
+        let mut view = GameView {
+            names: self.players.iter().map(|p| p.name.clone()).collect(),
+            show_hints: self.hints,
+            ..Default::default()
+        };
+        let mut quit = false;
+        // The first error of the terminal, since events can't return one
+        let mut terminal_error: Option<io::Error> = None;
+        play_game(
+            &mut self.players,
+            &mut None,
+            &MatchConfig::default(),
+            &deal,
+            None,
+            &mut vec![PlayerStats::default(); num_players],
+            &mut |event| {
+                view.update(&event);
+                let wait = matches!(
+                    event,
+                    GameEvent::CardPlayed { .. } | GameEvent::TurnSkipped { .. }
+                );
+                if quit || terminal_error.is_some() || !wait {
+                    return;
+                }
+                let result = terminal
+                    .draw(|frame| view.ui(frame))
+                    .and_then(|_| wait_for_quit(self.delay));
+                match result {
+                    Ok(quit_pressed) => quit = quit_pressed,
+                    Err(err) => terminal_error = Some(err),
+                }
+            },
+        )?;
+        if let Some(err) = terminal_error {
+            return Err(err.into());
+        }
+        if !quit {
+            loop {
+                terminal.draw(|frame| view.ui(frame))?;
+                if quit_pressed(Duration::from_millis(100))? {
+                    break;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+// Waits for the delay, and returns early if "q" is pressed.
+fn wait_for_quit(delay: Duration) -> io::Result<bool> {
+    let start = Instant::now();
+    while let Some(remaining) = delay.checked_sub(start.elapsed()) {
+        if quit_pressed(remaining)? {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+/// What is known about the game so far, from its events.
+#[derive(Default)]
+struct GameView {
+    names: Vec<String>,
+    colors: Vec<Color>,
+    hands: Vec<Hand>,
+    cards_won: Vec<u32>,
+    board: Option<Board>,
+    /// The coordinates of the last card played.
+    last_location: Option<(i8, i8)>,
+    /// What happened last, e.g. the card that was played.
+    status: String,
+    show_hints: bool,
+    /// The best turns for the player whose turn it is, with their ratings.
+    hints: Vec<(PlayTurnResponse, f64)>,
+    /// The player that the hints are for.
+    hint_player_idx: usize,
+}
+
+impl GameView {
+    fn update(&mut self, event: &GameEvent) {
+        match *event {
+            GameEvent::GameStarted { deal } => {
+                let num_players = deal.decks.len();
+                self.colors = deal.decks.iter().map(|deck| deck.color()).collect();
+                self.hands = vec![Hand::default(); num_players];
+                self.cards_won = vec![0; num_players];
+                self.status = String::from("The game started");
+            }
+            GameEvent::TurnStarted {
+                player_idx,
+                hand,
+                board,
+            } => {
+                self.hands[player_idx] = hand;
+                if let (true, Some(board)) = (self.show_hints, board) {
+                    self.hints = rank_turns(board, hand.cards(), material);
+                    self.hints.truncate(NUM_HINTS);
+                    self.hint_player_idx = player_idx;
+                }
+            }
+            GameEvent::CardPlayed {
+                player_idx,
+                card_to_play,
+                board,
+            } => {
+                self.hands[player_idx] = self.hands[player_idx].remove(card_to_play.card);
+                self.board = Some(board.clone());
+                self.last_location = Some((card_to_play.i, card_to_play.j));
+                self.status = format!(
+                    "{} played {} at ({}, {})",
+                    self.names[player_idx],
+                    card_name(card_to_play.card),
+                    card_to_play.i,
+                    card_to_play.j
+                );
+            }
+            GameEvent::TurnAnswered { .. } => {}
+            GameEvent::CardsWon { player_idx, cards } => {
+                self.cards_won[player_idx] += cards.len();
+                self.status += &format!(" and won {} cards", cards.len());
+            }
+            GameEvent::TurnSkipped { player_idx } => {
+                self.status = format!("{} skipped their turn", self.names[player_idx]);
+            }
+            GameEvent::GameEnded { result } => {
+                self.status = match result {
+                    GameResult::WonByPlayer { player_idx } => {
+                        format!("{} won the game", self.names[*player_idx])
+                    }
+                    GameResult::Tie { .. } => String::from("The game is a tie"),
+                    GameResult::IllegalMoveByPlayer { player_idx, err } => {
+                        format!("Illegal move by {}: {}", self.names[*player_idx], err)
+                    }
+                    GameResult::ProtocolErrorByPlayer { player_idx, err } => {
+                        format!("Protocol error by {}: {}", self.names[*player_idx], err)
+                    }
+                    GameResult::PlayerCrashed { player_idx, err } => {
+                        format!("{} crashed: {:#}", self.names[*player_idx], err)
+                    }
+                };
+                self.status += ". Press q to quit.";
+            }
+        }
+    }
+
+    fn ui(&self, frame: &mut Frame) {
+        let num_hint_lines = if self.show_hints { NUM_HINTS + 1 } else { 0 };
+        let mut constraints = vec![
+            Constraint::Length(1),
+            Constraint::Length(num_hint_lines as u16),
+            Constraint::Min(0),
+        ];
+        constraints.extend(self.hands.iter().map(|_| Constraint::Length(7)));
+        let layout = Layout::new(Direction::Vertical, constraints).split(frame.size());
+        frame.render_widget(Paragraph::new(self.status.as_str()), layout[0]);
+        if !self.hints.is_empty() {
+            frame.render_widget(Paragraph::new(self.hint_lines()), layout[1]);
+        }
+        if let Some(board) = &self.board {
+            // Where the best turn plays its cards
+            let hinted = self.hints.first().map_or_else(Vec::new, |(turn, _)| {
+                turn.cards_to_play()
+                    .iter()
+                    .map(|ctp| (ctp.i, ctp.j))
+                    .collect()
+            });
+            let board_widget = BoardWidget {
+                board,
+                highlighted: self.last_location,
+                hinted,
+            };
+            frame.render_widget(board_widget, layout[2]);
+        }
+        for (idx, area) in layout[3..].iter().enumerate() {
+            let title = format!(
+                "{} ({:?}), {} cards won",
+                self.names[idx], self.colors[idx], self.cards_won[idx]
+            );
+            let block = Block::new().title(title).borders(Borders::TOP);
+            let cards = self.hands[idx].iter().collect();
+            frame.render_widget(HandCardsWidget { cards }, block.inner(*area));
+            frame.render_widget(block, *area);
+        }
+    }
+
+    // The hints as one line per turn, e.g. "1. 9♠ at (0, 3), 6♦ at (-2, 0): 3 cards won"
+    fn hint_lines(&self) -> Vec<Line<'_>> {
+        let mut lines = vec![Line::from(format!(
+            "Best turns for {}:",
+            self.names[self.hint_player_idx]
+        ))];
+        for (idx, (turn, rating)) in self.hints.iter().enumerate() {
+            let cards = if turn.is_skip() {
+                String::from("skip")
+            } else {
+                let cards: Vec<String> = turn
+                    .cards_to_play()
+                    .iter()
+                    .map(|ctp| format!("{} at ({}, {})", card_name(ctp.card), ctp.i, ctp.j))
+                    .collect();
+                cards.join(", ")
+            };
+            lines.push(Line::from(format!(
+                "{}. {}: {} cards won",
+                idx + 1,
+                cards,
+                rating
+            )));
+        }
+        lines
+    }
+}
+
+/// Draws the fields of a board, with the top card and the number of cards on each of them.
+struct BoardWidget<'a> {
+    board: &'a Board,
+    /// This field gets a highlighted border.
+    highlighted: Option<(i8, i8)>,
+    /// These fields get a green border, unless they are highlighted. Empty fields among
+    /// them are drawn too.
+    hinted: Vec<(i8, i8)>,
+}
+
+impl Widget for BoardWidget<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let mut bbox = self.board.bbox();
+        for &(i, j) in &self.hinted {
+            bbox.update(i, j);
+        }
+        let width = u16::from(bbox.width()) * CARD_WIDTH;
+        let height = u16::from(bbox.height()) * FIELD_HEIGHT;
+        // Centered, and cut off if the terminal is too small
+        let x0 = area.x + area.width.saturating_sub(width) / 2;
+        let y0 = area.y + area.height.saturating_sub(height) / 2;
+        for (i, j) in bbox.iter() {
+            let x = x0 + (j - bbox.j_min) as u16 * CARD_WIDTH;
+            let y = y0 + (i - bbox.i_min) as u16 * FIELD_HEIGHT;
+            let field_area = Rect::new(x, y, CARD_WIDTH, FIELD_HEIGHT).intersection(area);
+            let mut block = Block::new()
+                .border_type(BorderType::Rounded)
+                .borders(Borders::all());
+            let hinted = self.hinted.contains(&(i, j));
+            if self.highlighted == Some((i, j)) {
+                block = block.border_style(Style::new().yellow());
+            } else if hinted {
+                block = block.border_style(Style::new().green());
+            }
+            let Some(field) = self.board.get(i, j) else {
+                if hinted {
+                    block.render(field_area, buf);
+                }
+                continue;
+            };
+            let num_cards = field.all_cards().len();
+            if num_cards > 1 {
+                block = block.title(num_cards.to_string());
+            }
+            let top_card = field.top_card().map_or(String::from("▒▒"), card_name);
+            Paragraph::new(top_card)
+                .block(block)
+                .render(field_area, buf);
+        }
+    }
+}
@@ -0,0 +1,314 @@
+use std::time::Duration;
+
+use anyhow::Context;
+use gomori::{
+    Board, Card, CardToPlay, CardsSet, DrawPile, Hand, IllegalMove, Okay, PlayTurnResponse,
+    PlayerState, Request, Rules, TurnValidator,
+};
+
+use crate::human::card_name;
+use crate::player::{Player, PlayerWithGameState};
+use crate::recording::RecordedRequest;
+
+/// A turn of a recorded game, with what the analyzed bot would have played instead.
+#[derive(Debug)]
+pub struct AnalyzedTurn {
+    /// The player who played the turn in the recording.
+    pub player: String,
+    pub turn_number: usize,
+    pub recorded: Vec<CardToPlay>,
+    pub suggested: Vec<CardToPlay>,
+    /// The cards won by the recorded move, or why it's illegal.
+    pub recorded_cards_won: Result<CardsSet, IllegalMove>,
+    /// The cards won by the bot's move, or why it's illegal.
+    pub suggested_cards_won: Result<CardsSet, IllegalMove>,
+}
+
+impl AnalyzedTurn {
+    /// Whether the bot would have played the same cards, in the same order and places.
+    pub fn agrees(&self) -> bool {
+        self.recorded == self.suggested
+    }
+
+    /// How many more cards the bot's move wins than the recorded one, if both are legal.
+    pub fn cards_won_delta(&self) -> Option<i64> {
+        match (&self.recorded_cards_won, &self.suggested_cards_won) {
+            (Ok(recorded), Ok(suggested)) => {
+                Some(i64::from(suggested.len()) - i64::from(recorded.len()))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// The comparison of a bot with the moves of a recorded game, see [`analyze_recording()`].
+#[derive(Debug, Default)]
+pub struct Analysis {
+    pub turns: Vec<AnalyzedTurn>,
+}
+
+impl Analysis {
+    pub fn num_agreements(&self) -> usize {
+        self.turns.iter().filter(|turn| turn.agrees()).count()
+    }
+
+    /// The fraction of turns in which the bot would have played the recorded move.
+    pub fn agreement(&self) -> f64 {
+        if self.turns.is_empty() {
+            return 0.0;
+        }
+        self.num_agreements() as f64 / self.turns.len() as f64
+    }
+
+    /// The sum of [`AnalyzedTurn::cards_won_delta()`] over the turns where the bot would
+    /// have played differently.
+    pub fn total_cards_won_delta(&self) -> i64 {
+        self.turns
+            .iter()
+            .filter(|turn| !turn.agrees())
+            .filter_map(|turn| turn.cards_won_delta())
+            .sum()
+    }
+}
+
+/// Lists the divergences, followed by a summary.
+impl std::fmt::Display for Analysis {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for turn in self.turns.iter().filter(|turn| !turn.agrees()) {
+            writeln!(f, "Turn {} of {}:", turn.turn_number, turn.player)?;
+            writeln!(
+                f,
+                "  recorded: {} ({})",
+                move_name(&turn.recorded),
+                outcome_name(&turn.recorded_cards_won)
+            )?;
+            writeln!(
+                f,
+                "  bot:      {} ({})",
+                move_name(&turn.suggested),
+                outcome_name(&turn.suggested_cards_won)
+            )?;
+            if let Some(delta) = turn.cards_won_delta() {
+                writeln!(f, "  cards won delta: {:+}", delta)?;
+            }
+        }
+        write!(
+            f,
+            "Agreement: {} of {} turns ({:.1}%), cards won delta over the divergences: {:+}",
+            self.num_agreements(),
+            self.turns.len(),
+            self.agreement() * 100.0,
+            self.total_cards_won_delta()
+        )
+    }
+}
+
+/// Replays the requests of a recorded game to a bot, and compares its answers to the
+/// `PlayTurn` requests with the recorded moves.
+///
+/// The bot gets the requests of each recorded player in turn, or only of `only_player`,
+/// each starting with their `NewGame` request. The moves are judged with the rules in the
+/// `NewGame` request, which are the standard rules in recordings that don't include them.
+pub fn analyze_recording(
+    bot: &mut Player,
+    recording: &[RecordedRequest],
+    only_player: Option<&str>,
+) -> anyhow::Result<Analysis> {
+    let mut names: Vec<&str> = Vec::new();
+    for req in recording {
+        if !names.contains(&req.player.as_str()) {
+            names.push(&req.player);
+        }
+    }
+    if let Some(name) = only_player {
+        anyhow::ensure!(
+            names.contains(&name),
+            "There is no player '{}' in the recording",
+            name
+        );
+        names.retain(|&n| n == name);
+    }
+
+    // The bot's state in the game isn't needed, since the game isn't played
+    let mut bot = PlayerWithGameState {
+        player: bot,
+        state: PlayerState {
+            draw_pile: DrawPile::default(),
+            hand: Hand::default(),
+            cards_won: CardsSet::new(),
+        },
+        last_response_time: Duration::ZERO,
+    };
+    let mut analysis = Analysis::default();
+    let mut rules = Rules::STANDARD;
+    for name in names {
+        for recorded in recording.iter().filter(|req| req.player == name) {
+            match &recorded.request {
+                Request::NewGame {
+                    rules: game_rules, ..
+                } => {
+                    rules = *game_rules;
+                    let _: Okay = bot.perform_request(&mut None, &recorded.request)?;
+                }
+                Request::PlayFirstTurn { .. } => {
+                    let _: Card = bot.perform_request(&mut None, &recorded.request)?;
+                }
+                Request::PlayTurn {
+                    cards,
+                    fields,
+                    turn_number,
+                    ..
+                } => {
+                    let recorded_move: PlayTurnResponse =
+                        serde_json::from_value(recorded.response.clone()).with_context(|| {
+                            format!("Invalid recorded response in turn {}", turn_number)
+                        })?;
+                    let suggested: PlayTurnResponse =
+                        bot.perform_request(&mut None, &recorded.request)?;
+                    let board = Board::new_with_rules(fields, rules);
+                    analysis.turns.push(AnalyzedTurn {
+                        player: name.to_string(),
+                        turn_number: *turn_number,
+                        recorded_cards_won: cards_won(
+                            &board,
+                            *cards,
+                            recorded_move.cards_to_play(),
+                        ),
+                        suggested_cards_won: cards_won(&board, *cards, suggested.cards_to_play()),
+                        recorded: recorded_move.cards_to_play().to_vec(),
+                        suggested: suggested.cards_to_play().to_vec(),
+                    });
+                }
+                Request::Bye { .. } => {}
+            }
+        }
+    }
+    Ok(analysis)
+}
+
+// The cards won by playing these cards from the hand, or why that's illegal
+fn cards_won(
+    board: &Board,
+    hand: Hand,
+    cards_to_play: &[CardToPlay],
+) -> Result<CardsSet, IllegalMove> {
+    let mut validator = TurnValidator::new(board.clone(), hand.cards());
+    for &ctp in cards_to_play {
+        validator.play(ctp)?;
+    }
+    Ok(validator.finish()?.cards_won)
+}
+
+fn move_name(cards_to_play: &[CardToPlay]) -> String {
+    if cards_to_play.is_empty() {
+        return String::from("skip");
+    }
+    let plays: Vec<String> = cards_to_play
+        .iter()
+        .map(|ctp| {
+            let mut name = format!("{} at ({}, {})", card_name(ctp.card), ctp.i, ctp.j);
+            if let Some((tgt_i, tgt_j)) = ctp.target_field_for_king_ability {
+                name += &format!(" flipping ({}, {})", tgt_i, tgt_j);
+            }
+            name
+        })
+        .collect();
+    plays.join(", then ")
+}
+
+fn outcome_name(cards_won: &Result<CardsSet, IllegalMove>) -> String {
+    match cards_won {
+        Ok(cards) if cards.is_empty() => String::from("wins nothing"),
+        Ok(cards) => format!("wins {}", cards),
+        Err(err) => format!("illegal: {}", err),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use gomori::{card, ctp, field, Color};
+
+    use super::*;
+    use crate::player::Connection;
+
+    /// A bot that plays the given moves, in order.
+    struct ScriptedBot {
+        moves: Vec<Vec<CardToPlay>>,
+    }
+
+    impl Connection for ScriptedBot {
+        fn exchange(&mut self, request: &str) -> anyhow::Result<String> {
+            let response = match serde_json::from_str(request)? {
+                Request::PlayTurn { .. } => {
+                    serde_json::to_string(&PlayTurnResponse::play(self.moves.remove(0)))?
+                }
+                _ => serde_json::to_string(&Okay())?,
+            };
+            Ok(response)
+        }
+    }
+
+    fn play_turn(turn_number: usize, response: &[CardToPlay]) -> RecordedRequest {
+        let fields = vec![
+            field(0, 0, card!("7♥")),
+            field(0, 1, card!("8♥")),
+            field(0, 2, card!("9♥")),
+        ];
+        RecordedRequest {
+            player: String::from("Alice"),
+            request: Request::PlayTurn {
+                cards: Hand::try_from([card!("T♥"), card!("2♠")].as_slice()).unwrap(),
+                fields,
+                cards_won_by_opponent: BTreeSet::new(),
+                draw_pile_size: 10,
+                opponent_draw_pile_size: 10,
+                turn_number,
+            },
+            response: serde_json::to_value(PlayTurnResponse::play(response.to_vec())).unwrap(),
+            time_ms: None,
+            illegal_move: None,
+        }
+    }
+
+    #[test]
+    fn divergences_are_compared_by_cards_won() {
+        let new_game = RecordedRequest {
+            player: String::from("Alice"),
+            request: Request::NewGame {
+                color: Color::Red,
+                num_players: 2,
+                seat: 0,
+                suit: None,
+                match_context: None,
+                rules: Rules::STANDARD,
+            },
+            response: serde_json::to_value(Okay()).unwrap(),
+            time_ms: None,
+            illegal_move: None,
+        };
+        let recording = [
+            new_game,
+            play_turn(2, &[ctp(card!("2♠"), 1, 0)]),
+            play_turn(4, &[ctp(card!("T♥"), 0, 3)]),
+        ];
+        let mut bot = Player::with_connection(
+            String::from("Bot"),
+            Box::new(ScriptedBot {
+                moves: vec![vec![ctp(card!("T♥"), 0, 3)], vec![ctp(card!("T♥"), 0, 3)]],
+            }),
+        );
+        let analysis = analyze_recording(&mut bot, &recording, None).unwrap();
+        assert_eq!(analysis.turns.len(), 2);
+        assert_eq!(analysis.num_agreements(), 1);
+        assert_eq!(analysis.agreement(), 0.5);
+        assert_eq!(analysis.turns[0].cards_won_delta(), Some(3));
+        assert_eq!(analysis.total_cards_won_delta(), 3);
+        assert!(analysis.to_string().ends_with(
+            "Agreement: 1 of 2 turns (50.0%), cards won delta over the divergences: +3"
+        ));
+
+        assert!(analyze_recording(&mut bot, &recording, Some("Bob")).is_err());
+    }
+}
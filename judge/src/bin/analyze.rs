@@ -0,0 +1,34 @@
+use std::path::PathBuf;
+
+use clap::Parser;
+use judge::{analyze_recording, read_recording, Player};
+
+/// Replays a game recorded with the judge's `--record-games-to-directory`, asks a bot what
+/// it would have played in every turn, and compares its moves with the recorded ones.
+///
+/// For every turn in which the bot would have played differently, both moves are printed
+/// with the cards that they win. At the end, the fraction of turns in which the bot agrees
+/// with the recording is printed, e.g. to check a new version of a bot against the games of
+/// the old one.
+#[derive(Parser)]
+struct Args {
+    /// Path to the config JSON file of the bot
+    #[arg(long)]
+    bot: PathBuf,
+
+    /// The recorded game
+    recording: PathBuf,
+
+    /// Only compare the turns of the player with this nick, instead of those of all players
+    #[arg(long)]
+    player: Option<String>,
+}
+
+fn main() -> anyhow::Result<()> {
+    let args = Args::parse();
+    let recording = read_recording(&args.recording)?;
+    let mut bot = Player::new(&args.bot)?;
+    let analysis = analyze_recording(&mut bot, &recording, args.player.as_deref())?;
+    println!("{}", analysis);
+    Ok(())
+}
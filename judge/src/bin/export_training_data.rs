@@ -0,0 +1,35 @@
+use std::path::PathBuf;
+
+use clap::Parser;
+use judge::{training_samples, write_npy_files, ResultsDb};
+
+/// Exports the games in a results database, as written with the judge's `--results-db`, as
+/// training data for machine learning.
+///
+/// Every card played is a sample, with the board before it, the card and where it was
+/// played, and whether the player went on to win the game. The samples are written as
+/// NumPy arrays into `boards.npy`, `cards_to_play.npy` and `outcomes.npy`, with the
+/// encoding of `gomori::encode_board()` and `gomori::encode_card_to_play()`.
+#[derive(Parser)]
+struct Args {
+    /// The SQLite database with the games
+    results_db: PathBuf,
+
+    /// The directory for the NumPy files, which is created if it doesn't exist
+    #[arg(short, long, default_value = ".")]
+    output_dir: PathBuf,
+}
+
+fn main() -> anyhow::Result<()> {
+    let args = Args::parse();
+    let games = ResultsDb::open_read_only(&args.results_db)?.games()?;
+    let samples = training_samples(&games)?;
+    std::fs::create_dir_all(&args.output_dir)?;
+    write_npy_files(&samples, &args.output_dir)?;
+    eprintln!(
+        "Exported {} samples from {} games",
+        samples.len(),
+        games.len()
+    );
+    Ok(())
+}
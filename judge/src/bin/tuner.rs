@@ -0,0 +1,170 @@
+use std::path::PathBuf;
+
+use clap::{Parser, ValueEnum};
+use gomori::Protocol;
+use judge::{
+    describe_values, grid, play_matchup, BotStderr, CommandTemplate, MatchConfig, ParamSpec,
+    Player, PlayerConfig, PlayerType, ResourceLimits, ScoreEstimate, Spsa,
+};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use tracing::info;
+use tracing_subscriber::filter::{LevelFilter, Targets};
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+
+/// Tunes the parameters of a bot by letting it play with different parameters.
+///
+/// For example, `tuner "target/release/my_bot --depth {depth}" -p depth=1:4:1 --opponent bots/greedy_bot.json`
+/// lets the bot play against GreedyBot with each depth from 1 to 4.
+#[derive(Parser)]
+struct Args {
+    /// The command to start the bot, with a placeholder like {depth} for each parameter
+    template: String,
+
+    /// A parameter to tune, as name=min:max:step
+    #[arg(short, long = "param", required = true)]
+    params: Vec<ParamSpec>,
+
+    /// How to search for the best parameters
+    #[arg(short, long, value_enum, default_value_t = Mode::Grid)]
+    mode: Mode,
+
+    /// The opponent of the bot in grid mode. By default, the bot plays against itself with
+    /// the parameters in the middle of their ranges.
+    #[arg(long)]
+    opponent: Option<PathBuf>,
+
+    /// How many games each match has
+    #[arg(short, long, default_value_t = 100)]
+    num_games: usize,
+
+    /// How many matches SPSA plays, between the parameters shifted up and down
+    #[arg(long, default_value_t = 100)]
+    iterations: usize,
+
+    /// SPSA's initial step size, as a fraction of each parameter's range
+    #[arg(long, default_value_t = 0.2)]
+    spsa_a: f64,
+
+    /// SPSA's initial shift of the parameters, as a fraction of each parameter's range.
+    /// It should be larger than the step of the parameters.
+    #[arg(long, default_value_t = 0.2)]
+    spsa_c: f64,
+
+    /// Talk to the bot through the MessagePack protocol instead of JSON
+    #[arg(long, default_value_t = false)]
+    msgpack: bool,
+
+    /// RNG seed
+    #[arg(long)]
+    seed: Option<u64>,
+
+    /// A log level among "off", "error", "warn", "info", "debug", "trace"
+    #[arg(short, long, default_value = "warn")]
+    log_level: LevelFilter,
+}
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+enum Mode {
+    /// Try every combination of parameter values
+    Grid,
+    /// Simultaneous perturbation stochastic approximation, which adjusts all parameters
+    /// at once and is better suited to many parameters
+    Spsa,
+}
+
+fn main() -> anyhow::Result<()> {
+    let args = Args::parse();
+    initialize_logging(args.log_level);
+
+    let template = CommandTemplate::new(&args.template, &args.params)?;
+    let seed = args.seed.unwrap_or_else(rand::random);
+    info!(seed);
+    let mut rng = StdRng::seed_from_u64(seed);
+    let config = MatchConfig {
+        num_games: args.num_games,
+        alternate_start: true,
+        ..Default::default()
+    };
+    let midpoints: Vec<f64> = args.params.iter().map(ParamSpec::midpoint).collect();
+    let bot = |values: &[f64]| -> anyhow::Result<Player> {
+        Player::from_config(PlayerConfig {
+            nick: describe_values(&args.params, values),
+            cmd: template.instantiate(&args.params, values),
+            player_type: PlayerType::Bot,
+            protocol: if args.msgpack {
+                Protocol::MsgPack
+            } else {
+                Protocol::Json
+            },
+            stderr: BotStderr::Inherit,
+            limits: ResourceLimits::default(),
+        })
+    };
+    let opponent = || -> anyhow::Result<Player> {
+        match &args.opponent {
+            Some(path) => Player::new(path),
+            None => bot(&midpoints),
+        }
+    };
+    let mut play = |player: Player, opponent: Player| -> anyhow::Result<ScoreEstimate> {
+        let mut players = [player, opponent];
+        let score = play_matchup(&mut rng, &mut players, &config, &mut None, &mut |_| {})?;
+        Ok(ScoreEstimate::from_match_score(&score))
+    };
+
+    match args.mode {
+        Mode::Grid => {
+            let mut results = Vec::new();
+            for values in grid(&args.params) {
+                let estimate = play(bot(&values)?, opponent()?)?;
+                eprintln!("{}: {}", describe_values(&args.params, &values), estimate);
+                results.push((values, estimate));
+            }
+            results.sort_by(|(_, a), (_, b)| b.score.total_cmp(&a.score));
+            eprintln!("Best parameters:");
+            for (values, estimate) in results.iter().take(5) {
+                eprintln!("- {}: {}", describe_values(&args.params, values), estimate);
+            }
+        }
+        Mode::Spsa => {
+            let mut spsa = Spsa::new(args.params.clone(), args.spsa_a, args.spsa_c);
+            // Separate from the matches' RNG, which the closure borrows
+            let mut spsa_rng = StdRng::seed_from_u64(seed.wrapping_add(1));
+            for iteration in 0..args.iterations {
+                let (plus, minus, delta) = spsa.perturb(&mut spsa_rng);
+                let estimate = play(bot(&plus)?, bot(&minus)?)?;
+                spsa.update(&delta, estimate.score);
+                eprintln!(
+                    "Iteration {}: {}",
+                    iteration + 1,
+                    describe_values(&args.params, &spsa.values())
+                );
+            }
+            // Check the result against the starting point
+            let best = spsa.values();
+            let estimate = play(bot(&best)?, bot(&midpoints)?)?;
+            eprintln!(
+                "Best parameters: {}\nAgainst {}: {}",
+                describe_values(&args.params, &best),
+                describe_values(&args.params, &midpoints),
+                estimate
+            );
+        }
+    }
+    Ok(())
+}
+
+fn initialize_logging(level: LevelFilter) {
+    let format = tracing_subscriber::fmt::format()
+        .with_target(false)
+        .compact();
+
+    let filter = Targets::new().with_default(level);
+
+    tracing_subscriber::registry()
+        .with(tracing_subscriber::fmt::layer().event_format(format))
+        .with(filter)
+        .init();
+}
@@ -0,0 +1,37 @@
+use std::path::PathBuf;
+
+use clap::Parser;
+use judge::{read_full_recording, verify_recording};
+
+/// Recomputes games recorded with the judge's `--verifiable-recordings`, using only the rules
+/// engine and the recorded responses, and checks that every recorded request, e.g. the board
+/// and the hand in each turn, matches the recomputed game.
+///
+/// A divergence means that the judge or the rules engine behave differently than when the
+/// games were recorded, e.g. after a change to either of them, or because of nondeterminism.
+/// The exit code is nonzero if any game diverges.
+#[derive(Parser)]
+struct Args {
+    /// The recorded games
+    #[arg(required = true)]
+    recordings: Vec<PathBuf>,
+}
+
+fn main() -> anyhow::Result<()> {
+    let args = Args::parse();
+    let mut num_diverged = 0;
+    for path in &args.recordings {
+        let verification = verify_recording(&read_full_recording(path)?)?;
+        println!("{}: {}", path.display(), verification);
+        if !verification.is_ok() {
+            num_diverged += 1;
+        }
+    }
+    anyhow::ensure!(
+        num_diverged == 0,
+        "{} of {} games diverged",
+        num_diverged,
+        args.recordings.len()
+    );
+    Ok(())
+}
@@ -0,0 +1,153 @@
+use gomori::{Board, Card, CardsSet, Deck, PlayerState, Rules};
+
+/// Checks that no card gets lost or duplicated during a game.
+///
+/// Every card that was dealt must be in exactly one place: on the board, or in a
+/// player's hand, draw pile or pile of won cards. The rules engine should guarantee
+/// this, so a violation means a bug, or a game state that was not created by it.
+pub struct DeckTracker {
+    dealt: CardsSet,
+}
+
+/// Where a card is during a game.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CardLocation {
+    Board { i: i8, j: i8 },
+    Hand { player_idx: usize },
+    DrawPile { player_idx: usize },
+    Won { player_idx: usize },
+}
+
+/// The error for a game state in which the cards don't add up to the dealt cards.
+#[derive(Debug, PartialEq, Eq)]
+pub struct CardConservationError {
+    /// Cards that are in more than one place, with all of their places.
+    pub duplicated: Vec<(Card, Vec<CardLocation>)>,
+    /// Dealt cards that are nowhere.
+    pub missing: CardsSet,
+    /// Cards that are somewhere, but were not dealt.
+    pub unexpected: CardsSet,
+}
+
+impl std::error::Error for CardConservationError {}
+
+impl std::fmt::Display for CardConservationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "The cards in the game don't add up:")?;
+        for (card, locations) in &self.duplicated {
+            write!(f, " {} is in more than one place {:?};", card, locations)?;
+        }
+        if !self.missing.is_empty() {
+            write!(f, " missing {};", self.missing)?;
+        }
+        if !self.unexpected.is_empty() {
+            write!(f, " not dealt {};", self.unexpected)?;
+        }
+        Ok(())
+    }
+}
+
+impl DeckTracker {
+    /// Tracks the cards of the decks, as far as they are played with in the variant of the
+    /// game, see [`Deck::cards_with_rules()`].
+    pub fn new(decks: &[Deck], rules: Rules) -> Self {
+        Self {
+            dealt: decks
+                .iter()
+                .flat_map(|deck| deck.cards_with_rules(rules))
+                .collect(),
+        }
+    }
+
+    /// Checks where all cards are, given the board (if the first card has been played)
+    /// and the state of each player.
+    pub fn check<'a>(
+        &self,
+        board: Option<&Board>,
+        players: impl IntoIterator<Item = &'a PlayerState>,
+    ) -> Result<(), CardConservationError> {
+        let mut places: Vec<(Card, CardLocation)> = Vec::new();
+        for &(i, j, field) in board.into_iter().flat_map(|board| board.iter()) {
+            for card in field.all_cards() {
+                places.push((card, CardLocation::Board { i, j }));
+            }
+        }
+        for (player_idx, state) in players.into_iter().enumerate() {
+            for card in state.hand {
+                places.push((card, CardLocation::Hand { player_idx }));
+            }
+            for &card in &state.draw_pile {
+                places.push((card, CardLocation::DrawPile { player_idx }));
+            }
+            for card in state.cards_won {
+                places.push((card, CardLocation::Won { player_idx }));
+            }
+        }
+
+        let found: CardsSet = places.iter().map(|&(card, _)| card).collect();
+        let mut duplicated: Vec<(Card, Vec<CardLocation>)> = Vec::new();
+        if found.len() as usize != places.len() {
+            for card in found {
+                let locations: Vec<CardLocation> = places
+                    .iter()
+                    .filter(|&&(other, _)| other == card)
+                    .map(|&(_, location)| location)
+                    .collect();
+                if locations.len() > 1 {
+                    duplicated.push((card, locations));
+                }
+            }
+        }
+        let error = CardConservationError {
+            duplicated,
+            missing: self.dealt & !found,
+            unexpected: found & !self.dealt,
+        };
+        if error.duplicated.is_empty() && error.missing.is_empty() && error.unexpected.is_empty() {
+            Ok(())
+        } else {
+            Err(error)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use gomori::{card, execute_first_turn, Color};
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    use super::*;
+
+    #[test]
+    fn finds_duplicated_and_missing_cards() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let decks = [Deck::Color(Color::Red), Deck::Color(Color::Black)];
+        let tracker = DeckTracker::new(&decks, Rules::STANDARD);
+        let mut states = decks.map(|deck| PlayerState::with_deck(deck, &mut rng));
+        assert_eq!(tracker.check(None, &states), Ok(()));
+
+        let card = states[0].hand.iter().next().unwrap();
+        let first_turn = execute_first_turn(&states[0], card).unwrap();
+        states[0] = first_turn.state;
+        let board = first_turn.board;
+        assert_eq!(tracker.check(Some(&board), &states), Ok(()));
+
+        // A card is won by both players, and another is lost
+        let lost = states[1].draw_pile.draw().unwrap();
+        states[0].cards_won = CardsSet::from_iter([card!("2♦")]);
+        states[1].cards_won = CardsSet::from_iter([card!("2♦")]);
+        let err = tracker.check(Some(&board), &states).unwrap_err();
+        let mut locations = err.duplicated[0].1.clone();
+        locations.retain(|location| matches!(location, CardLocation::Won { .. }));
+        assert_eq!(
+            locations,
+            [
+                CardLocation::Won { player_idx: 0 },
+                CardLocation::Won { player_idx: 1 }
+            ]
+        );
+        assert_eq!(err.missing, CardsSet::from_iter([lost]));
+        assert!(err.unexpected.is_empty());
+    }
+}
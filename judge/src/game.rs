@@ -1,126 +1,849 @@
-use std::cmp::Ordering;
-use std::collections::BTreeSet;
+use std::collections::{BTreeSet, HashSet};
+use std::time::Duration;
 
 use gomori::{
-    execute_first_turn, execute_turn, Card, CardsSet, Color, IllegalMove, Okay, PlayTurnResponse,
-    Request, TurnOutcome,
+    execute_first_turn_at, execute_turn, Board, Card, CardToPlay, CardsSet, Deck, DrawPile, Hand,
+    IllegalMove, MatchContext, Okay, PlayTurnResponse, PlayerState, Request, Rules, TurnAction,
+    TurnOutcome,
 };
 use rand::rngs::StdRng;
-use rand::seq::SliceRandom;
 use rand::Rng;
+use tracing::{debug, info};
 
-use crate::player::{Player, PlayerWithGameState};
+use crate::deck_tracker::DeckTracker;
+use crate::matchup::MatchConfig;
+use crate::player::{ConnectionClosed, Player, PlayerWithGameState};
+use crate::protocol_errors::ProtocolError;
 use crate::recording::Recorder;
+use crate::stats::PlayerStats;
 
+#[derive(Debug)]
 pub enum GameResult {
-    WonByPlayer { player_idx: usize },
-    Tie,
-    IllegalMoveByPlayer { player_idx: usize, err: IllegalMove },
+    WonByPlayer {
+        player_idx: usize,
+    },
+    Tie {
+        reason: TieReason,
+    },
+    IllegalMoveByPlayer {
+        player_idx: usize,
+        err: IllegalMove,
+    },
+    /// The player's response didn't follow the protocol, e.g. because it wasn't valid JSON.
+    ProtocolErrorByPlayer {
+        player_idx: usize,
+        err: ProtocolError,
+    },
+    /// The player exited, or closed its end of the connection (see [`ConnectionClosed`]).
+    /// If that was because of its [`ResourceLimits`](crate::ResourceLimits), the error has a
+    /// [`ResourceLimitExceeded`](crate::ResourceLimitExceeded) as context.
+    PlayerCrashed {
+        player_idx: usize,
+        err: anyhow::Error,
+    },
 }
 
-/// Returns an error only on communication failure, not when an
-/// illegal move is played.
+/// Why a game ended in a tie.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TieReason {
+    /// Several players won the most cards.
+    SameNumberOfCards,
+    /// The game reached [`MatchConfig::max_turns`].
+    TurnLimit,
+}
+
+/// The cards that the players get in a game, and who starts it.
+///
+/// Indexed by the player, like the players of [`play_game()`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Deal {
+    pub decks: Vec<Deck>,
+    /// Each player's deck in the shuffled order. The last cards are drawn first, so the
+    /// first hand consists of the last [`Hand::MAX_SIZE`](gomori::Hand::MAX_SIZE) cards.
+    pub draw_piles: Vec<DrawPile>,
+    pub starting_player: usize,
+}
+
+impl Deal {
+    /// Deals the decks, see [`Deck::deal()`], and shuffles them.
+    ///
+    /// The player at index `starting_player` plays the first turn, or a random player if
+    /// it's `None`.
+    pub fn random(num_players: usize, starting_player: Option<usize>, rng: &mut StdRng) -> Self {
+        Self::random_with_rules(num_players, starting_player, Rules::STANDARD, rng)
+    }
+
+    /// Like [`random()`](Self::random), but the draw piles only have the cards of the
+    /// decks that are played with in the variant, see [`Deck::cards_with_rules()`].
+    pub fn random_with_rules(
+        num_players: usize,
+        starting_player: Option<usize>,
+        rules: Rules,
+        rng: &mut StdRng,
+    ) -> Self {
+        let decks = Deck::deal(num_players, rng);
+        let draw_piles = decks
+            .iter()
+            .map(|&deck| DrawPile::shuffled_with_rules(deck, rules, rng))
+            .collect();
+        let starting_player = starting_player.unwrap_or_else(|| rng.gen_range(0..num_players));
+        Self {
+            decks,
+            draw_piles,
+            starting_player,
+        }
+    }
+
+    /// The same deal, but every player gets the deck and draw pile of the previous player,
+    /// and the last one those of the first player. The player with the deck of the starting
+    /// player starts.
+    ///
+    /// With two players, this swaps their cards, so that playing both deals is fair even if
+    /// one of them is luckier.
+    pub fn mirrored(&self) -> Self {
+        let num_players = self.decks.len();
+        let mut decks = self.decks.clone();
+        let mut draw_piles = self.draw_piles.clone();
+        decks.rotate_right(1);
+        draw_piles.rotate_right(1);
+        Self {
+            decks,
+            draw_piles,
+            starting_player: (self.starting_player + 1) % num_players,
+        }
+    }
+}
+
+/// Something that happened in a game, see [`play_game()`].
+///
+/// The events of a game are emitted in the order in which they happen, and the
+/// last one is always [`GameEvent::GameEnded`].
+#[derive(Debug)]
+pub enum GameEvent<'a> {
+    /// The players got these cards, before they are told about the new game.
+    GameStarted {
+        deal: &'a Deal,
+    },
+    /// The player is asked to play a turn with this hand, on this board. Before the first
+    /// turn, there is no board yet.
+    TurnStarted {
+        player_idx: usize,
+        hand: Hand,
+        board: Option<&'a Board>,
+    },
+    /// The player answered the request to play its turn after this long, see
+    /// [`PlayerWithGameState::last_response_time`]. Not emitted if the player crashed.
+    TurnAnswered {
+        player_idx: usize,
+        response_time: Duration,
+    },
+    /// The player played a card in an accepted turn, which resulted in this board.
+    CardPlayed {
+        player_idx: usize,
+        card_to_play: CardToPlay,
+        board: &'a Board,
+    },
+    /// The player won cards with the card that was played last.
+    CardsWon {
+        player_idx: usize,
+        cards: CardsSet,
+    },
+    /// The player could not play any card.
+    TurnSkipped {
+        player_idx: usize,
+    },
+    GameEnded {
+        result: &'a GameResult,
+    },
+}
+
+/// Turns a crash of the player into a [`GameResult::PlayerCrashed`], and an invalid
+/// response into a [`GameResult::ProtocolErrorByPlayer`], and passes on other errors.
+fn crash_or_error(player_idx: usize, err: anyhow::Error) -> anyhow::Result<GameResult> {
+    if err.is::<ConnectionClosed>() {
+        Ok(GameResult::PlayerCrashed { player_idx, err })
+    } else if let Some(err) = err.downcast_ref::<ProtocolError>() {
+        Ok(GameResult::ProtocolErrorByPlayer {
+            player_idx,
+            err: err.clone(),
+        })
+    } else {
+        Err(err)
+    }
+}
+
+// Ends the game, and records the game up to the illegal move
+fn illegal_move(
+    recorder: &mut Option<Recorder>,
+    player_idx: usize,
+    err: IllegalMove,
+) -> anyhow::Result<GameResult> {
+    if let Some(rec) = recorder {
+        rec.store_illegal_move(&err)?;
+        rec.write_game_recording()?;
+    }
+    Ok(GameResult::IllegalMoveByPlayer { player_idx, err })
+}
+
+/// Returns an error only on communication failure, not when an illegal move is played, a
+/// response doesn't follow the protocol or a player crashes.
+///
+/// There can be two to four players, who get the cards of the `deal`, which must have one
+/// deck per player.
+/// The game is played with `config.rules`. If `config.explain` is true, the rules engine's
+/// reasoning for every card played in an accepted turn is logged, and added to the recording.
+/// The game is a tie if it lasts for more than `config.max_turns` turns, see [`TieReason`].
+///
+/// The starting player of the deal plays the first turn, and the others follow in the order
+/// of their indices.
+///
+/// The accepted turns of each player are added to the `stats`, which has one entry per player.
+/// If the game is part of a match, `match_contexts` has the score of the match so far from
+/// the perspective of each player, which they are told about in the request for a new game.
+///
+/// After every turn, a [`DeckTracker`] checks that no card was lost or duplicated, and a
+/// [`CardConservationError`](crate::CardConservationError) is returned otherwise. Likewise,
+/// an error is returned if a position occurs a second time, which the rules don't allow.
+///
+/// Everything that happens in the game is passed to `on_event`, e.g. for spectating it live.
+/// The cards of a turn are only reported once the whole turn has been accepted. If an
+/// error is returned, the game doesn't end with a [`GameEvent::GameEnded`].
 pub fn play_game(
-    rng: &mut StdRng,
-    player_1: &mut Player,
-    player_2: &mut Player,
+    players: &mut [Player],
     recorder: &mut Option<Recorder>,
+    config: &MatchConfig,
+    deal: &Deal,
+    match_contexts: Option<&[MatchContext]>,
+    stats: &mut [PlayerStats],
+    on_event: &mut dyn FnMut(GameEvent<'_>),
 ) -> anyhow::Result<GameResult> {
-    // Assign one bot the red cards and the other the black cards randomly
-    let [player_1_color, player_2_color] = {
-        let mut arr = [Color::Red, Color::Black];
-        arr.shuffle(rng);
-        arr
-    };
+    let result = play_game_until_end(
+        players,
+        recorder,
+        config,
+        deal,
+        match_contexts,
+        stats,
+        on_event,
+    )?;
+    on_event(GameEvent::GameEnded { result: &result });
+    Ok(result)
+}
+
+fn play_game_until_end(
+    players: &mut [Player],
+    recorder: &mut Option<Recorder>,
+    config: &MatchConfig,
+    deal: &Deal,
+    match_contexts: Option<&[MatchContext]>,
+    stats: &mut [PlayerStats],
+    on_event: &mut dyn FnMut(GameEvent<'_>),
+) -> anyhow::Result<GameResult> {
+    let num_players = players.len();
+    anyhow::ensure!(
+        deal.decks.len() == num_players && deal.draw_piles.len() == num_players,
+        "The deal is for {} players, not {}",
+        deal.decks.len(),
+        num_players
+    );
+    anyhow::ensure!(
+        match_contexts.is_none_or(|contexts| contexts.len() == num_players),
+        "There must be one match context per player"
+    );
+    if let Some(rec) = recorder {
+        rec.start_game(config, deal);
+    }
+    on_event(GameEvent::GameStarted { deal });
 
     // Bundle everything up in a PlayerWithGameState struct, which tracks the player's state during this game
-    let mut players = [
-        PlayerWithGameState::new(player_1, player_1_color, rng),
-        PlayerWithGameState::new(player_2, player_2_color, rng),
-    ];
+    let mut players: Vec<_> = players
+        .iter_mut()
+        .zip(&deal.draw_piles)
+        .map(|(player, draw_pile)| PlayerWithGameState::new(player, draw_pile.clone()))
+        .collect();
+    let deck_tracker = DeckTracker::new(&deal.decks, config.rules);
+    let mut repetition_detector = RepetitionDetector::default();
 
-    // Inform the players about the new game, so that they can reset their state
-    let _: Okay = players[0].perform_request(
-        recorder,
-        &Request::NewGame {
-            color: player_1_color,
-        },
-    )?;
-    let _: Okay = players[1].perform_request(
-        recorder,
-        &Request::NewGame {
-            color: player_2_color,
-        },
-    )?;
+    let mut current_player_idx = deal.starting_player;
 
-    // Randomly pick a starting player
-    let mut current_player_idx = if rng.gen::<bool>() { 1 } else { 0 };
+    // Inform the players about the new game, so that they can reset their state
+    for (player_idx, &deck) in deal.decks.iter().enumerate() {
+        let req = Request::NewGame {
+            color: deck.color(),
+            num_players,
+            seat: (player_idx + num_players - current_player_idx) % num_players,
+            suit: match deck {
+                Deck::Color(_) => None,
+                Deck::Suit(suit) => Some(suit),
+            },
+            match_context: match_contexts.map(|contexts| contexts[player_idx]),
+            rules: config.rules,
+        };
+        let res: anyhow::Result<Okay> = players[player_idx].perform_request(recorder, &req);
+        if let Err(err) = res {
+            return crash_or_error(player_idx, err);
+        }
+    }
 
     // Play the first turn. This one is special.
+    on_event(GameEvent::TurnStarted {
+        player_idx: current_player_idx,
+        hand: players[current_player_idx].state.hand,
+        board: None,
+    });
+    let (i, j) = config.first_card_location;
     let req = Request::PlayFirstTurn {
         cards: players[current_player_idx].state.hand,
+        i,
+        j,
     };
-    let card: Card = players[current_player_idx].perform_request(recorder, &req)?;
-    let mut board = match execute_first_turn(&mut players[current_player_idx].state, card) {
-        Ok(board) => board,
-        Err(err) => {
-            return Ok(GameResult::IllegalMoveByPlayer {
-                player_idx: current_player_idx,
-                err,
-            })
+    let card: Card = match players[current_player_idx].perform_request(recorder, &req) {
+        Ok(card) => card,
+        Err(err) => return crash_or_error(current_player_idx, err),
+    };
+    let response_time = players[current_player_idx].last_response_time;
+    stats[current_player_idx]
+        .response_times
+        .record(response_time);
+    on_event(GameEvent::TurnAnswered {
+        player_idx: current_player_idx,
+        response_time,
+    });
+    let first_turn = execute_first_turn_at(
+        &players[current_player_idx].state,
+        card,
+        (i, j),
+        config.rules,
+    );
+    let mut board = match first_turn {
+        Ok(result) => {
+            players[current_player_idx].state = result.state;
+            result.board
         }
+        Err(err) => return illegal_move(recorder, current_player_idx, err),
     };
+    on_event(GameEvent::CardPlayed {
+        player_idx: current_player_idx,
+        card_to_play: CardToPlay {
+            card,
+            i,
+            j,
+            target_field_for_king_ability: None,
+        },
+        board: &board,
+    });
+    deck_tracker.check(Some(&board), players.iter().map(|p| &p.state))?;
 
-    let mut turn_skipped = false;
-    let mut cards_won_by_opponent = CardsSet::new();
-    loop {
+    let mut turns_skipped_in_a_row = 0;
+    // For each player, the cards won by the others since the player's last turn
+    let mut cards_won_by_opponents = vec![CardsSet::new(); num_players];
+    let mut num_turns = 1;
+    let forced_tie = loop {
         // eprintln!("{}", board);
-        current_player_idx = 1 - current_player_idx;
+        if num_turns == config.max_turns {
+            break Some(TieReason::TurnLimit);
+        }
+        num_turns += 1;
+        current_player_idx = (current_player_idx + 1) % num_players;
+        on_event(GameEvent::TurnStarted {
+            player_idx: current_player_idx,
+            hand: players[current_player_idx].state.hand,
+            board: Some(&board),
+        });
+        let opponent_draw_pile_size = (0..num_players)
+            .filter(|&idx| idx != current_player_idx)
+            .map(|idx| players[idx].state.draw_pile.len())
+            .sum();
         let current_player = &mut players[current_player_idx];
         let req = Request::PlayTurn {
             cards: current_player.state.hand,
             fields: board.to_fields_vec(),
-            cards_won_by_opponent: BTreeSet::from_iter(cards_won_by_opponent),
+            cards_won_by_opponent: BTreeSet::from_iter(std::mem::take(
+                &mut cards_won_by_opponents[current_player_idx],
+            )),
+            draw_pile_size: current_player.state.draw_pile.len(),
+            opponent_draw_pile_size,
+            turn_number: num_turns,
         };
-        let action: PlayTurnResponse = current_player.perform_request(recorder, &req)?;
-        match execute_turn(&mut current_player.state, &mut board, action) {
-            Ok(TurnOutcome::Normal {
-                cards_won_this_turn,
-            }) => {
-                turn_skipped = false;
-                cards_won_by_opponent = cards_won_this_turn;
+        let action: PlayTurnResponse = match current_player.perform_request(recorder, &req) {
+            Ok(action) => action,
+            Err(err) => return crash_or_error(current_player_idx, err),
+        };
+        stats[current_player_idx]
+            .response_times
+            .record(current_player.last_response_time);
+        on_event(GameEvent::TurnAnswered {
+            player_idx: current_player_idx,
+            response_time: current_player.last_response_time,
+        });
+        let result = match execute_turn(&current_player.state, &board, action.clone()) {
+            Ok(result) => result,
+            Err(err) => return illegal_move(recorder, current_player_idx, err),
+        };
+        if action.0 == TurnAction::Play(Vec::new()) {
+            debug!(
+                player = current_player.player.name,
+                "Skipped the turn with an empty list of cards instead of \"skip\""
+            );
+        }
+        stats[current_player_idx].record_turn(&result);
+        let mut explanations = Vec::new();
+        let mut board_before_card = &board;
+        for played_card in &result.cards_played {
+            if config.explain {
+                // Explanations are not part of the turn result, since they are only needed here
+                let effects = board_before_card
+                    .calculate(played_card.card_to_play)
+                    .expect("Turn was accepted, so every card must be playable");
+                explanations.push(effects.explain());
+            }
+            on_event(GameEvent::CardPlayed {
+                player_idx: current_player_idx,
+                card_to_play: played_card.card_to_play,
+                board: &played_card.board,
+            });
+            if !played_card.cards_won.is_empty() {
+                on_event(GameEvent::CardsWon {
+                    player_idx: current_player_idx,
+                    cards: played_card.cards_won,
+                });
+            }
+            board_before_card = &played_card.board;
+        }
+        if config.explain {
+            for explanation in &explanations {
+                info!(player = current_player.player.name, "{}", explanation);
             }
-            Ok(TurnOutcome::GameEnded) => {
-                break;
+            if let Some(rec) = recorder {
+                rec.store_explanations(&explanations)?;
             }
-            Ok(TurnOutcome::Skipped) => {
-                cards_won_by_opponent = CardsSet::new();
-                if turn_skipped {
-                    break; // When both players couldn't play a card, the game ends
-                } else {
-                    turn_skipped = true;
+        }
+        current_player.state = result.state;
+        board = result.board;
+        match result.outcome {
+            TurnOutcome::Normal => {
+                turns_skipped_in_a_row = 0;
+                for (player_idx, cards_won) in cards_won_by_opponents.iter_mut().enumerate() {
+                    if player_idx != current_player_idx {
+                        *cards_won |= result.cards_won_this_turn;
+                    }
                 }
             }
-            Err(err) => {
-                return Ok(GameResult::IllegalMoveByPlayer {
+            TurnOutcome::GameEnded => {
+                // Not checked by the deck tracker, since the cards won in the last turn are
+                // not added to the player's won cards
+                break None;
+            }
+            TurnOutcome::Skipped => {
+                on_event(GameEvent::TurnSkipped {
                     player_idx: current_player_idx,
-                    err,
-                })
+                });
+                turns_skipped_in_a_row += 1;
+                if turns_skipped_in_a_row == num_players {
+                    break None; // When no player could play a card, the game ends
+                }
             }
         };
-    }
+        deck_tracker.check(Some(&board), players.iter().map(|p| &p.state))?;
+        repetition_detector.check(&board, players.iter().map(|p| &p.state), current_player_idx)?;
+    };
 
     if let Some(rec) = recorder {
         rec.write_game_recording()?;
     }
+    if let Some(reason) = forced_tie {
+        return Ok(GameResult::Tie { reason });
+    }
 
-    // Report who won
-    let num_cards_0 = players[0].state.cards_won.len();
-    let num_cards_1 = players[1].state.cards_won.len();
-    let game_result = match num_cards_0.cmp(&num_cards_1) {
-        Ordering::Less => GameResult::WonByPlayer { player_idx: 1 },
-        Ordering::Equal => GameResult::Tie,
-        Ordering::Greater => GameResult::WonByPlayer { player_idx: 0 },
+    // Report who won, i.e. who won the most cards, if there's a single such player
+    let num_cards: Vec<_> = players.iter().map(|p| p.state.cards_won.len()).collect();
+    let max_num_cards = *num_cards.iter().max().unwrap();
+    let mut best_players = (0..num_players).filter(|&idx| num_cards[idx] == max_num_cards);
+    let game_result = match (best_players.next(), best_players.next()) {
+        (Some(player_idx), None) => GameResult::WonByPlayer { player_idx },
+        _ => GameResult::Tie {
+            reason: TieReason::SameNumberOfCards,
+        },
     };
     Ok(game_result)
 }
+
+/// Checks that no position occurs twice in a game.
+///
+/// Since every card played is removed from a draw pile, a repeated position means that the
+/// rules engine has a bug, so it is an error rather than a tie, like for the [`DeckTracker`].
+#[derive(Default)]
+struct RepetitionDetector {
+    // The board, the player who just played, and the state of every player. Whole positions
+    // are stored, so that positions with the same hash are not mistaken for each other.
+    seen: HashSet<(Board, usize, Vec<PlayerState>)>,
+}
+
+impl RepetitionDetector {
+    /// Records the position after the turn of `last_player_idx`, and returns an error if it
+    /// occurred before.
+    fn check<'a>(
+        &mut self,
+        board: &Board,
+        players: impl IntoIterator<Item = &'a PlayerState>,
+        last_player_idx: usize,
+    ) -> anyhow::Result<()> {
+        let position = (
+            board.clone(),
+            last_player_idx,
+            players.into_iter().cloned().collect(),
+        );
+        anyhow::ensure!(
+            self.seen.insert(position),
+            "The position after this turn of player {} occurred a second time, which the rules \
+             don't allow:\n{}",
+            last_player_idx,
+            gomori::visualize_top_cards(&board.to_fields_vec())
+        );
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    use gomori::{card, field, Board, Rank, Rules};
+    use rand::SeedableRng;
+
+    use super::*;
+    use crate::player::Connection;
+    use crate::scenarios::first_legal_turn;
+
+    /// For every turn in order: the cards_won_by_opponent that the player received, and the cards it won.
+    type TurnLog = Rc<RefCell<Vec<(CardsSet, CardsSet)>>>;
+
+    /// For every turn in order: the turn number and the draw pile sizes that the player received.
+    type PhaseLog = Rc<RefCell<Vec<(usize, usize, usize)>>>;
+
+    /// A bot that plays the first legal card it finds under the rules it was told, and logs
+    /// its turns.
+    #[derive(Default)]
+    struct FirstLegalPlayBot {
+        log: TurnLog,
+        phases: PhaseLog,
+        rules: Rules,
+    }
+
+    impl Connection for FirstLegalPlayBot {
+        fn exchange(&mut self, request: &str) -> anyhow::Result<String> {
+            let response = match serde_json::from_str(request)? {
+                Request::NewGame { rules, .. } => {
+                    self.rules = rules;
+                    serde_json::to_string(&Okay())?
+                }
+                Request::PlayFirstTurn { cards, .. } => {
+                    self.log
+                        .borrow_mut()
+                        .push((CardsSet::new(), CardsSet::new()));
+                    serde_json::to_string(&cards.iter().next().unwrap())?
+                }
+                Request::PlayTurn {
+                    cards,
+                    fields,
+                    cards_won_by_opponent,
+                    draw_pile_size,
+                    opponent_draw_pile_size,
+                    turn_number,
+                } => {
+                    self.phases.borrow_mut().push((
+                        turn_number,
+                        draw_pile_size,
+                        opponent_draw_pile_size,
+                    ));
+                    let (response, cards_won) =
+                        first_legal_turn(cards, Board::new_with_rules(&fields, self.rules));
+                    self.log
+                        .borrow_mut()
+                        .push((CardsSet::from_iter(cards_won_by_opponent), cards_won));
+                    serde_json::to_string(&response)?
+                }
+                Request::Bye { .. } => String::new(),
+            };
+            Ok(response)
+        }
+    }
+
+    #[test]
+    fn cards_won_by_opponent_are_from_previous_turns() {
+        for num_players in 2..=4 {
+            let log = TurnLog::default();
+            let mut players: Vec<_> = (1..=num_players)
+                .map(|idx| {
+                    Player::with_connection(
+                        format!("Player {}", idx),
+                        Box::new(FirstLegalPlayBot {
+                            log: log.clone(),
+                            ..Default::default()
+                        }),
+                    )
+                })
+                .collect();
+            let mut stats = vec![PlayerStats::default(); num_players];
+            let mut rng = StdRng::seed_from_u64(0);
+            let mut any_cards_won = false;
+            for _ in 0..20 {
+                log.borrow_mut().clear();
+                let result = play_game(
+                    &mut players,
+                    &mut None,
+                    &MatchConfig::default(),
+                    &Deal::random(num_players, None, &mut rng),
+                    None,
+                    &mut stats,
+                    &mut |_| {},
+                )
+                .unwrap();
+                assert!(!matches!(result, GameResult::IllegalMoveByPlayer { .. }));
+                // Players take turns, so each turn's cards_won_by_opponent are the cards won
+                // in the turns of the other players since the player's own previous turn
+                for turns in log.borrow().windows(num_players) {
+                    let (cards_won_by_opponent, _) = turns[num_players - 1];
+                    let cards_won = turns[..num_players - 1]
+                        .iter()
+                        .fold(CardsSet::new(), |acc, &(_, cards_won)| acc | cards_won);
+                    assert_eq!(cards_won_by_opponent, cards_won);
+                    any_cards_won |= !cards_won.is_empty();
+                }
+            }
+            assert!(any_cards_won);
+        }
+    }
+
+    #[test]
+    fn events_describe_the_game() {
+        let mut players: Vec<_> = (1..=2)
+            .map(|idx| {
+                Player::with_connection(
+                    format!("Player {}", idx),
+                    Box::new(FirstLegalPlayBot::default()),
+                )
+            })
+            .collect();
+        let mut stats = vec![PlayerStats::default(); 2];
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut last_board: Option<Board> = None;
+        let mut turn_player = None;
+        let mut num_turns = 0;
+        let mut num_answers = 0;
+        let mut game_ended = false;
+        let config = MatchConfig {
+            first_card_location: (2, -1),
+            ..Default::default()
+        };
+        let result = play_game(
+            &mut players,
+            &mut None,
+            &config,
+            &Deal::random(2, None, &mut rng),
+            None,
+            &mut stats,
+            &mut |event| {
+                assert!(!game_ended, "No events after the end of the game");
+                match event {
+                    GameEvent::GameStarted { deal } => {
+                        assert_eq!(deal.draw_piles.len(), 2);
+                        assert!(last_board.is_none());
+                    }
+                    GameEvent::TurnStarted {
+                        player_idx,
+                        hand,
+                        board,
+                    } => {
+                        assert!(!hand.is_empty());
+                        // Each turn starts on the board that the last card resulted in
+                        assert_eq!(board, last_board.as_ref());
+                        turn_player = Some(player_idx);
+                        num_turns += 1;
+                    }
+                    GameEvent::TurnAnswered { player_idx, .. } => {
+                        assert_eq!(turn_player, Some(player_idx));
+                        num_answers += 1;
+                    }
+                    GameEvent::CardPlayed {
+                        player_idx,
+                        card_to_play,
+                        board,
+                    } => {
+                        assert_eq!(turn_player, Some(player_idx));
+                        let expected = match &last_board {
+                            Some(last_board) => last_board.play_card(card_to_play).unwrap(),
+                            None => Board::new(&[gomori::Field {
+                                i: 2,
+                                j: -1,
+                                top_card: Some(card_to_play.card),
+                                hidden_cards: BTreeSet::new(),
+                            }]),
+                        };
+                        assert_eq!(board, &expected);
+                        last_board = Some(board.clone());
+                    }
+                    GameEvent::CardsWon { player_idx, cards } => {
+                        assert_eq!(turn_player, Some(player_idx));
+                        assert!(!cards.is_empty());
+                    }
+                    GameEvent::TurnSkipped { player_idx } => {
+                        assert_eq!(turn_player, Some(player_idx));
+                    }
+                    GameEvent::GameEnded { result } => {
+                        assert!(!matches!(result, GameResult::IllegalMoveByPlayer { .. }));
+                        game_ended = true;
+                    }
+                }
+            },
+        )
+        .unwrap();
+        assert!(game_ended);
+        assert!(!matches!(result, GameResult::IllegalMoveByPlayer { .. }));
+        // The first turn is not in the stats
+        let turns_in_stats: usize = stats.iter().map(|s| s.turns_played + s.turns_skipped).sum();
+        assert_eq!(num_turns, turns_in_stats + 1);
+        // Unlike the other stats, the response times include the first turn
+        assert_eq!(num_answers, num_turns);
+        let num_response_times: usize = stats.iter().map(|s| s.response_times.len()).sum();
+        assert_eq!(num_response_times, num_turns);
+    }
+
+    #[test]
+    fn games_that_last_too_long_are_a_tie() {
+        let log = TurnLog::default();
+        let mut players: Vec<_> = (1..=2)
+            .map(|idx| {
+                Player::with_connection(
+                    format!("Player {}", idx),
+                    Box::new(FirstLegalPlayBot {
+                        log: log.clone(),
+                        ..Default::default()
+                    }),
+                )
+            })
+            .collect();
+        let config = MatchConfig {
+            max_turns: 5,
+            ..Default::default()
+        };
+        let result = play_game(
+            &mut players,
+            &mut None,
+            &config,
+            &Deal::random(2, None, &mut StdRng::seed_from_u64(0)),
+            None,
+            &mut vec![PlayerStats::default(); 2],
+            &mut |_| {},
+        )
+        .unwrap();
+        assert!(matches!(
+            result,
+            GameResult::Tie {
+                reason: TieReason::TurnLimit
+            }
+        ));
+        assert_eq!(log.borrow().len(), 5);
+    }
+
+    #[test]
+    fn repeated_positions_are_an_error() {
+        let deal = Deal::random(2, Some(0), &mut StdRng::seed_from_u64(0));
+        let players: Vec<_> = deal
+            .draw_piles
+            .iter()
+            .map(|draw_pile| PlayerState::with_draw_pile(draw_pile.clone()))
+            .collect();
+        let board = Board::new(&[field(0, 0, card!("7♥"))]);
+        let mut detector = RepetitionDetector::default();
+        detector.check(&board, &players, 0).unwrap();
+        // The same board and cards, but after the turn of the other player
+        detector.check(&board, &players, 1).unwrap();
+        let other_board = Board::new(&[field(0, 0, card!("8♥"))]);
+        detector.check(&other_board, &players, 0).unwrap();
+        assert!(detector.check(&board, &players, 0).is_err());
+    }
+
+    #[test]
+    fn game_phase_is_sent_to_bots() {
+        let phases = PhaseLog::default();
+        let mut players: Vec<_> = (1..=2)
+            .map(|idx| {
+                Player::with_connection(
+                    format!("Player {}", idx),
+                    Box::new(FirstLegalPlayBot {
+                        phases: phases.clone(),
+                        ..Default::default()
+                    }),
+                )
+            })
+            .collect();
+        play_game(
+            &mut players,
+            &mut None,
+            &MatchConfig::default(),
+            &Deal::random(2, Some(0), &mut StdRng::seed_from_u64(0)),
+            None,
+            &mut vec![PlayerStats::default(); 2],
+            &mut |_| {},
+        )
+        .unwrap();
+        let phases = phases.borrow();
+        // The starting player has drawn a card after the first turn
+        assert_eq!(phases[0], (2, 21, 20));
+        for (turn_idx, turns) in phases.windows(3).enumerate() {
+            assert_eq!(turns[0].0, turn_idx + 2);
+            // The draw piles only get smaller
+            assert!(turns[2].1 <= turns[0].1);
+            assert!(turns[1].2 <= turns[0].1);
+        }
+    }
+
+    #[test]
+    fn variants_are_sent_to_bots() {
+        let mut players: Vec<_> = (1..=2)
+            .map(|idx| {
+                Player::with_connection(
+                    format!("Player {}", idx),
+                    Box::new(FirstLegalPlayBot::default()),
+                )
+            })
+            .collect();
+        let small_board = Rules {
+            board_size: 3,
+            line_length: 3,
+            aces_are_wild: false,
+            jack_ability: false,
+            queen_ability: true,
+            king_ability: false,
+            lowest_rank: Rank::Two,
+        };
+        let large_board = Rules {
+            board_size: 5,
+            lowest_rank: Rank::Seven,
+            ..Rules::STANDARD
+        };
+        let mut rng = StdRng::seed_from_u64(0);
+        for rules in [small_board, large_board] {
+            let config = MatchConfig {
+                rules,
+                ..Default::default()
+            };
+            for _ in 0..20 {
+                // The deck tracker checks that only the cards of the variant's deck are played
+                let result = play_game(
+                    &mut players,
+                    &mut None,
+                    &config,
+                    &Deal::random_with_rules(2, None, rules, &mut rng),
+                    None,
+                    &mut vec![PlayerStats::default(); 2],
+                    &mut |_| {},
+                )
+                .unwrap();
+                assert!(!matches!(result, GameResult::IllegalMoveByPlayer { .. }));
+            }
+        }
+    }
+}
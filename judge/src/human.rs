@@ -0,0 +1,350 @@
+use std::io::{BufRead, Write};
+
+use anyhow::Context;
+use gomori::{
+    visualize_top_cards, Board, Card, CardToPlay, CardsSet, Hand, Okay, PlayTurnResponse, Request,
+    Rules, TurnState, TurnValidator,
+};
+
+use crate::player::Connection;
+
+/// A player that is a person at the terminal.
+///
+/// Instead of forwarding requests to a bot process, it renders them on the terminal,
+/// lets the person pick among the legal moves, and answers with the corresponding response.
+/// Moves can be picked by their number in the list, or typed out like `K♠ 2 1 king 0 0`,
+/// see [`parse_play()`].
+pub struct HumanPlayer {
+    input: Box<dyn BufRead>,
+    output: Box<dyn Write>,
+    // The rules of the current game
+    rules: Rules,
+}
+
+impl HumanPlayer {
+    /// Creates a player that reads from stdin and writes to stderr.
+    pub fn new() -> Self {
+        Self::with_io(
+            Box::new(std::io::BufReader::new(std::io::stdin())),
+            Box::new(std::io::stderr()),
+        )
+    }
+
+    pub fn with_io(input: Box<dyn BufRead>, output: Box<dyn Write>) -> Self {
+        Self {
+            input,
+            output,
+            rules: Rules::STANDARD,
+        }
+    }
+
+    fn play_first_turn(&mut self, hand: Hand, i: i8, j: i8) -> anyhow::Result<Card> {
+        let cards = Vec::from(hand);
+        writeln!(
+            self.output,
+            "\nIt's your first turn. The card will be placed at ({}, {}).",
+            i, j
+        )?;
+        for (idx, card) in cards.iter().enumerate() {
+            writeln!(self.output, "  {}: {}", idx + 1, card_name(*card))?;
+        }
+        let idx = self.read_choice(cards.len())?;
+        Ok(cards[idx])
+    }
+
+    fn play_turn(
+        &mut self,
+        cards: Hand,
+        board: Board,
+        cards_won_by_opponent: CardsSet,
+        draw_pile_size: usize,
+        opponent_draw_pile_size: usize,
+        turn_number: usize,
+    ) -> anyhow::Result<PlayTurnResponse> {
+        writeln!(self.output)?;
+        writeln!(
+            self.output,
+            "Turn {}: {} cards are left in your draw pile, and {} in your opponent's.",
+            turn_number, draw_pile_size, opponent_draw_pile_size
+        )?;
+        if !cards_won_by_opponent.is_empty() {
+            writeln!(
+                self.output,
+                "Your opponent won {} in their last turn.",
+                cards_won_by_opponent
+            )?;
+        }
+
+        let mut validator = TurnValidator::new(board.clone(), cards.cards());
+        let mut cards_to_play = Vec::new();
+        while !validator.playable_cards().is_empty() {
+            let board = validator.board();
+            writeln!(
+                self.output,
+                "{}",
+                visualize_top_cards(&board.to_fields_vec())
+            )?;
+            writeln!(self.output, "Your hand: {}", validator.remaining_hand())?;
+            let plays = legal_plays(board, validator.playable_cards());
+            if plays.is_empty() {
+                break;
+            }
+            for (idx, ctp) in plays.iter().enumerate() {
+                writeln!(self.output, "  {}: {}", idx + 1, describe_play(board, *ctp))?;
+            }
+            let ctp = self.read_play(&plays)?;
+            validator.play(ctp)?;
+            cards_to_play.push(ctp);
+            if validator.state() == TurnState::InCombo {
+                writeln!(self.output, "Combo! Play another card.")?;
+            }
+        }
+        match validator.state() {
+            TurnState::NotStarted => {
+                writeln!(self.output, "None of your cards can be played, skipping.")?
+            }
+            TurnState::InCombo => writeln!(
+                self.output,
+                "None of your cards can be played, ending turn."
+            )?,
+            TurnState::Ended => {}
+        }
+        Ok(PlayTurnResponse::play_or_skip(
+            &board,
+            cards.cards(),
+            cards_to_play,
+        ))
+    }
+
+    // Reads a number between 1 and num_choices, and returns it as a zero-based index.
+    fn read_choice(&mut self, num_choices: usize) -> anyhow::Result<usize> {
+        let prompt = format!("Your choice (1-{})", num_choices);
+        self.read_until(&prompt, |line| parse_choice(line, num_choices))
+    }
+
+    // Reads the number of one of the plays, or a play in text form that is among them.
+    fn read_play(&mut self, plays: &[CardToPlay]) -> anyhow::Result<CardToPlay> {
+        let prompt = format!(
+            "Your choice (1-{}, or a move like \"K♠ 2 1 king 0 0\")",
+            plays.len()
+        );
+        self.read_until(&prompt, |line| match parse_choice(line, plays.len()) {
+            Some(idx) => Some(plays[idx]),
+            None => parse_play(line).filter(|ctp| plays.contains(ctp)),
+        })
+    }
+
+    // Prompts for input until a line is accepted by `parse`.
+    fn read_until<T>(
+        &mut self,
+        prompt: &str,
+        parse: impl Fn(&str) -> Option<T>,
+    ) -> anyhow::Result<T> {
+        let mut line = String::new();
+        loop {
+            write!(self.output, "{}: ", prompt)?;
+            self.output.flush()?;
+            line.clear();
+            if self.input.read_line(&mut line)? == 0 {
+                anyhow::bail!("Reached end of input while waiting for a choice");
+            }
+            match parse(line.trim()) {
+                Some(value) => return Ok(value),
+                None => writeln!(self.output, "Invalid choice '{}'.", line.trim())?,
+            }
+        }
+    }
+}
+
+impl Default for HumanPlayer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Connection for HumanPlayer {
+    fn exchange(&mut self, request: &str) -> anyhow::Result<String> {
+        let request: Request = serde_json::from_str(request).context("Could not parse request")?;
+        let response = match request {
+            Request::NewGame { color, rules, .. } => {
+                self.rules = rules;
+                writeln!(self.output, "\nA new game starts, you play {:?}.", color)?;
+                if rules != Rules::STANDARD {
+                    writeln!(self.output, "The game is played with these rules:")?;
+                    write!(self.output, "{}", rules.describe())?;
+                }
+                serde_json::to_string(&Okay())?
+            }
+            Request::PlayFirstTurn { cards, i, j } => {
+                serde_json::to_string(&self.play_first_turn(cards, i, j)?)?
+            }
+            Request::PlayTurn {
+                cards,
+                fields,
+                cards_won_by_opponent,
+                draw_pile_size,
+                opponent_draw_pile_size,
+                turn_number,
+            } => {
+                let board = Board::try_new_with_rules(&fields, self.rules)
+                    .context("Invalid board in request")?;
+                let cards_won_by_opponent = CardsSet::from_iter(cards_won_by_opponent);
+                let response = self.play_turn(
+                    cards,
+                    board,
+                    cards_won_by_opponent,
+                    draw_pile_size,
+                    opponent_draw_pile_size,
+                    turn_number,
+                )?;
+                serde_json::to_string(&response)?
+            }
+            Request::Bye { .. } => String::new(),
+        };
+        Ok(response)
+    }
+}
+
+// Parses a number between 1 and num_choices as a zero-based index.
+fn parse_choice(text: &str, num_choices: usize) -> Option<usize> {
+    match text.parse::<usize>() {
+        Ok(choice) if (1..=num_choices).contains(&choice) => Some(choice - 1),
+        _ => None,
+    }
+}
+
+/// Parses a move like `K♠ 2 1 king 0 0`: the card, the coordinates to play it at, and
+/// for a king played on another card, the word `king` and the coordinates of the field to flip.
+///
+/// Since suit symbols are hard to type, the card may also be written like `Ks`, and the
+/// ten as `10`. Whether the move is legal is not checked.
+pub fn parse_play(text: &str) -> Option<CardToPlay> {
+    let mut words = text.split_whitespace();
+    let card = parse_card(words.next()?)?;
+    let coordinates = |words: &mut std::str::SplitWhitespace| -> Option<(i8, i8)> {
+        let i = words.next()?.parse().ok()?;
+        let j = words.next()?.parse().ok()?;
+        Some((i, j))
+    };
+    let (i, j) = coordinates(&mut words)?;
+    let target_field_for_king_ability = match words.next() {
+        Some(word) if word.eq_ignore_ascii_case("king") => Some(coordinates(&mut words)?),
+        Some(_) => return None,
+        None => None,
+    };
+    if words.next().is_some() {
+        return None;
+    }
+    Some(CardToPlay {
+        card,
+        i,
+        j,
+        target_field_for_king_ability,
+    })
+}
+
+// Like the `FromStr` impl of `Card`, but also accepts letters for the suits and 10 for the ten.
+fn parse_card(text: &str) -> Option<Card> {
+    let text = text.to_uppercase();
+    let text = text
+        .strip_prefix("10")
+        .map_or(text.clone(), |rest| format!("T{}", rest));
+    text.parse().ok()
+}
+
+/// All the ways to play a single one of the given cards.
+fn legal_plays(board: &Board, cards: CardsSet) -> Vec<CardToPlay> {
+    cards
+        .into_iter()
+        .flat_map(|card| board.legal_plays(card))
+        .collect()
+}
+
+fn describe_play(board: &Board, ctp: CardToPlay) -> String {
+    let mut description = format!("{} at ({}, {})", card_name(ctp.card), ctp.i, ctp.j);
+    if let Some((tgt_i, tgt_j)) = ctp.target_field_for_king_ability {
+        description += &format!(", flipping ({}, {})", tgt_i, tgt_j);
+    }
+    if let Ok(effects) = board.calculate(ctp) {
+        if !effects.cards_won.is_empty() {
+            description += &format!(", wins {}", effects.cards_won);
+        }
+        if effects.combo {
+            description += ", combo";
+        }
+    }
+    description
+}
+
+/// Renders a card as rank + suit, e.g. "10♥", which is more legible than its Unicode character.
+pub fn card_name(card: Card) -> String {
+    format!("{}{}", card.rank, card.suit)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use gomori::{card, Field};
+
+    use super::*;
+
+    #[test]
+    fn human_plays_chosen_moves() {
+        let input = "1\n";
+        let mut player = HumanPlayer::with_io(
+            Box::new(std::io::Cursor::new(input)),
+            Box::new(std::io::sink()),
+        );
+        let request = Request::PlayTurn {
+            cards: Hand::try_from([
+                card!("2♦"),
+                card!("3♦"),
+                card!("4♦"),
+                card!("5♦"),
+                card!("6♦"),
+            ])
+            .unwrap(),
+            fields: vec![Field {
+                i: 0,
+                j: 0,
+                top_card: Some(card!("A♠")),
+                hidden_cards: BTreeSet::new(),
+            }],
+            cards_won_by_opponent: BTreeSet::new(),
+            draw_pile_size: 20,
+            opponent_draw_pile_size: 21,
+            turn_number: 3,
+        };
+        let response = player
+            .exchange(&serde_json::to_string(&request).unwrap())
+            .unwrap();
+        let response: PlayTurnResponse = serde_json::from_str(&response).unwrap();
+        assert_eq!(response.cards_to_play().len(), 1);
+        assert_eq!(response.cards_to_play()[0].card, card!("2♦"));
+    }
+
+    #[test]
+    fn plays_can_be_typed_out() {
+        let king = CardToPlay {
+            card: card!("K♠"),
+            i: 2,
+            j: 1,
+            target_field_for_king_ability: Some((0, 0)),
+        };
+        assert_eq!(parse_play("K♠ 2 1 king 0 0"), Some(king));
+        assert_eq!(parse_play("ks 2 1 KING 0 0"), Some(king));
+        assert_eq!(
+            parse_play("10h -1 0"),
+            Some(CardToPlay {
+                card: card!("T♥"),
+                i: -1,
+                j: 0,
+                target_field_for_king_ability: None,
+            })
+        );
+        assert_eq!(parse_play("K♠ 2 1 king 0"), None);
+        assert_eq!(parse_play("K♠ 2 1 0 0"), None);
+        assert_eq!(parse_play("1♠ 2 1"), None);
+    }
+}
@@ -1,6 +1,36 @@
+mod analysis;
+mod deck_tracker;
 mod game;
+mod human;
+mod matchup;
 mod player;
+mod positions;
+mod protocol_errors;
 mod recording;
+mod resource_limits;
+mod results_db;
+#[cfg(test)]
+mod scenarios;
+mod spectator;
+mod sprt;
+mod stats;
+mod training_data;
+mod tuning;
+mod verification;
+pub use analysis::*;
+pub use deck_tracker::*;
 pub use game::*;
+pub use human::*;
+pub use matchup::*;
 pub use player::*;
+pub use positions::*;
+pub use protocol_errors::*;
 pub use recording::*;
+pub use resource_limits::*;
+pub use results_db::*;
+pub use spectator::*;
+pub use sprt::*;
+pub use stats::*;
+pub use training_data::*;
+pub use tuning::*;
+pub use verification::*;
@@ -1,26 +1,78 @@
+use std::fs::File;
+use std::io::{BufWriter, Write};
 use std::path::PathBuf;
 
+use anyhow::Context;
 use clap::Parser;
-use judge::{play_game, GameResult, Player, Recorder};
+use gomori::{Rank, Rules};
+use judge::{
+    play_gauntlet, play_matchup, BotStderr, Deal, GameEvent, MatchConfig, MatchScore, Player,
+    PlayerConfig, PositionStats, Recorder, ResultsDb, ScoreEstimate, Spectator, Sprt, SprtConfig,
+    SprtResult,
+};
 use rand::rngs::StdRng;
 use rand::SeedableRng;
-use tracing::{debug, info};
+use tracing::info;
 use tracing_subscriber::filter::{LevelFilter, Targets};
 use tracing_subscriber::layer::SubscriberExt;
 use tracing_subscriber::util::SubscriberInitExt;
 
+// How many of the most common positions are reported per player
+const NUM_COMMON_POSITIONS: usize = 5;
+
 #[derive(Parser)]
+#[command(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
 struct Args {
-    /// Path to the config JSON file for player 1
-    player_1_config: PathBuf,
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    /// Paths to the config JSON files of the players, two to four of them, or with --gauntlet,
+    /// the candidate and any number of opponents
+    #[arg(required = true, num_args = 2..)]
+    player_configs: Vec<PathBuf>,
 
-    /// Path to the config JSON file for player 2
-    player_2_config: PathBuf,
+    /// Instead of playing one match between all players, let the player with this nick play
+    /// a two-player match against each of the other players in turn
+    #[arg(
+        long,
+        visible_alias = "only-against",
+        conflicts_with_all = ["results_db", "stats_file", "common_positions"]
+    )]
+    gauntlet: Option<String>,
 
-    /// How many games to play
+    /// How many games to play, or with --series-length, how many series
     #[arg(short, long, default_value_t = 100)]
     num_games: usize,
 
+    /// Play "best of N" series of this many games, which end once a player has won the majority
+    #[arg(long)]
+    series_length: Option<usize>,
+
+    /// Stop as soon as a sequential probability ratio test decides whether player 1 is better
+    /// than player 2, e.g. "elo0=0,elo1=5,alpha=0.05,beta=0.05". --num-games is the maximum.
+    #[arg(long)]
+    sprt: Option<SprtConfig>,
+
+    /// Play this many games before the match, which are not counted, to let bots warm up,
+    /// e.g. those that are compiled just in time or load a model on their first requests
+    #[arg(long, default_value_t = 0)]
+    warmup: usize,
+
+    /// Let the players take turns starting a game, strictly in order, instead of choosing
+    /// randomly. With --series-length, the turns start over in each series
+    #[arg(
+        long,
+        visible_alias = "alternate-first-player",
+        default_value_t = false
+    )]
+    alternate_start: bool,
+
+    /// Play rounds of one game per player, in which the players take turns playing with the
+    /// same shuffled decks, to reduce the randomness of the results. With two players, the
+    /// outcomes of these pairs of games are reported too
+    #[arg(long, visible_alias = "duplicate", default_value_t = false)]
+    mirror_decks: bool,
+
     /// RNG seed
     #[arg(long)]
     seed: Option<u64>,
@@ -29,86 +81,573 @@ struct Args {
     #[arg(short, long, default_value_t = false)]
     stop_on_illegal_move: bool,
 
+    /// Log the rules engine's reasoning for every card played, and add it to recordings
+    #[arg(short, long, default_value_t = false)]
+    explain: bool,
+
+    /// How often a bot is restarted after crashing, before it forfeits the remaining games
+    #[arg(long, default_value_t = 0)]
+    max_restarts: usize,
+
+    /// Write the stderr output of each bot to a log file named after its nick in this
+    /// directory, instead of where its config says
+    #[arg(long)]
+    bot_logs_dir: Option<PathBuf>,
+
     /// Record the game's interactions as JSON files into this directory
     #[arg(short, long)]
     record_games_to_directory: Option<PathBuf>,
 
+    /// Also record the rules and the shuffled draw piles of each game, so that the `verify`
+    /// binary can recompute the recorded games
+    #[arg(long, default_value_t = false, requires = "record_games_to_directory")]
+    verifiable_recordings: bool,
+
+    /// Let spectators watch the games live, by sending each event of the games as a line of
+    /// JSON to everyone who connects to this port on localhost
+    #[arg(long)]
+    spectate_port: Option<u16>,
+
+    /// Also send the shuffled draw piles and the hands of the players to spectators. Only use
+    /// this if no bot can connect to the port, since it reveals the opponents' cards
+    #[arg(long, default_value_t = false, requires = "spectate_port")]
+    spectator_show_hidden: bool,
+
+    /// Add the games, their turns and the cards played to this SQLite database, which is
+    /// created if it doesn't exist
+    #[arg(long)]
+    results_db: Option<PathBuf>,
+
+    /// Write the shuffled draw piles of each game as a line of JSON to this file
+    #[arg(long)]
+    deals_file: Option<PathBuf>,
+
+    /// Write statistics about each player's turns as JSON to this file
+    #[arg(long)]
+    stats_file: Option<PathBuf>,
+
+    /// Report the boards on which each player played most often in the first this many turns
+    /// of the games, e.g. to build an opening book or spot bots that always play alike
+    #[arg(long)]
+    common_positions: Option<usize>,
+
+    #[command(flatten)]
+    rules: RulesArgs,
+
+    /// Where the first card of each game is placed, as "i,j". The starting player is told
+    /// about it in its request to play the first turn
+    #[arg(long, default_value = "0,0", value_parser = parse_location)]
+    first_card_location: (i8, i8),
+
+    /// End games that last longer than this many turns as a tie
+    #[arg(long, default_value_t = MatchConfig::default().max_turns)]
+    max_turns: usize,
+
     /// A log level among "off", "error", "warn", "info", "debug", "trace"
     #[arg(short, long, default_value = "info")]
     log_level: LevelFilter,
 }
 
+#[derive(clap::Subcommand)]
+enum Command {
+    /// Print the rules of the game, with the variant chosen by the options, and exit
+    Rules(RulesArgs),
+}
+
+#[derive(clap::Args)]
+struct RulesArgs {
+    /// Rules variant: the width and height of the area that the cards must fit into, at most 5
+    #[arg(long, default_value_t = gomori::BOARD_SIZE)]
+    board_size: i8,
+
+    /// Rules variant: how many cards of the same suit in a row complete a line
+    #[arg(long, default_value_t = gomori::BOARD_SIZE as u8)]
+    line_length: u8,
+
+    /// Rules variant: aces may only be played on other aces
+    #[arg(long, default_value_t = false)]
+    aces_not_wild: bool,
+
+    /// Rules variant: jacks have no ability
+    #[arg(long, default_value_t = false)]
+    no_jack_ability: bool,
+
+    /// Rules variant: queens have no ability
+    #[arg(long, default_value_t = false)]
+    no_queen_ability: bool,
+
+    /// Rules variant: kings have no ability
+    #[arg(long, default_value_t = false)]
+    no_king_ability: bool,
+
+    /// Rules variant: the deck only has the cards of this rank and higher, e.g. 7 for 32 cards
+    #[arg(long, default_value = "2", value_parser = parse_rank)]
+    lowest_rank: Rank,
+}
+
+impl RulesArgs {
+    fn rules(&self) -> Rules {
+        Rules {
+            board_size: self.board_size,
+            line_length: self.line_length,
+            aces_are_wild: !self.aces_not_wild,
+            jack_ability: !self.no_jack_ability,
+            queen_ability: !self.no_queen_ability,
+            king_ability: !self.no_king_ability,
+            lowest_rank: self.lowest_rank,
+        }
+    }
+}
+
 fn main() -> anyhow::Result<()> {
     let args = Args::parse();
 
     initialize_logging(args.log_level);
 
-    let mut player_1 = Player::new(&args.player_1_config)?;
-    let mut player_2 = Player::new(&args.player_2_config)?;
-
-    let player_names = [player_1.name.clone(), player_2.name.clone()];
+    if let Some(Command::Rules(rules_args)) = &args.command {
+        let rules = rules_args.rules();
+        rules.validate()?;
+        print!("{}", rules.describe());
+        return Ok(());
+    }
+    let rules = args.rules.rules();
+    anyhow::ensure!(
+        args.gauntlet.is_some() || args.player_configs.len() <= 4,
+        "A game has two to four players, but {} player configs were given",
+        args.player_configs.len()
+    );
 
-    let mut wins = [0, 0];
-    let mut illegal_moves = [0, 0];
-    let mut ties = 0;
+    let mut players = args
+        .player_configs
+        .iter()
+        .map(|path| {
+            let mut config = PlayerConfig::load(path)?;
+            if let Some(dir) = &args.bot_logs_dir {
+                config.stderr = BotStderr::Dir(dir.clone());
+            }
+            Player::from_config(config)
+        })
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    let gauntlet_candidate = match &args.gauntlet {
+        Some(nick) => Some(
+            players
+                .iter()
+                .position(|player| &player.name == nick)
+                .with_context(|| format!("There is no player with the nick '{}'", nick))?,
+        ),
+        None => None,
+    };
 
     let mut recorder = if let Some(dir_path) = args.record_games_to_directory {
-        Some(Recorder::new(dir_path)?)
+        let recorder = Recorder::new(dir_path)?;
+        Some(if args.verifiable_recordings {
+            recorder.with_setups()
+        } else {
+            recorder
+        })
     } else {
         None
     };
 
+    let mut spectator = match args.spectate_port {
+        Some(port) => {
+            let spectator = Spectator::bind(port)?;
+            Some(if args.spectator_show_hidden {
+                spectator.with_hidden_information()
+            } else {
+                spectator
+            })
+        }
+        None => None,
+    };
+
     // Get a random seed
     let seed = args.seed.unwrap_or_else(rand::random);
     info!(seed);
     let mut rng = StdRng::seed_from_u64(seed);
 
-    for game_idx in 0..args.num_games {
-        match play_game(&mut rng, &mut player_1, &mut player_2, &mut recorder)? {
-            GameResult::WonByPlayer { player_idx } => {
-                debug!(winner = player_names[player_idx], game_idx);
-                wins[player_idx] += 1;
-            }
-            GameResult::Tie => {
-                debug!(game_idx, "Tie");
-                ties += 1;
+    let mut results_db = match &args.results_db {
+        Some(path) => {
+            let names: Vec<String> = players.iter().map(|p| p.name.clone()).collect();
+            Some(ResultsDb::open(path, seed, &names)?)
+        }
+        None => None,
+    };
+
+    let mut deals_file = match &args.deals_file {
+        Some(path) => Some(BufWriter::new(File::create(path).with_context(|| {
+            format!("Could not create deals file '{}'", path.display())
+        })?)),
+        None => None,
+    };
+    let mut deals_error = None;
+
+    let mut position_stats = args
+        .common_positions
+        .map(|num_turns| PositionStats::new(players.len(), num_turns));
+
+    let config = MatchConfig {
+        num_games: args.num_games,
+        stop_on_illegal_move: args.stop_on_illegal_move,
+        explain: args.explain,
+        max_restarts: args.max_restarts,
+        series_length: args.series_length,
+        alternate_start: args.alternate_start,
+        mirror_decks: args.mirror_decks,
+        rules,
+        first_card_location: args.first_card_location,
+        max_turns: args.max_turns,
+        sprt: args.sprt,
+        warmup_games: args.warmup,
+    };
+    let mut on_event = |event: GameEvent<'_>| {
+        if let Some(spectator) = &mut spectator {
+            spectator.broadcast(&event);
+        }
+        if let Some(db) = &mut results_db {
+            db.record(&event);
+        }
+        if let Some(position_stats) = &mut position_stats {
+            position_stats.record(&event);
+        }
+        if let (Some(file), GameEvent::GameStarted { deal }) = (&mut deals_file, &event) {
+            if deals_error.is_none() {
+                deals_error = write_deal(file, deal).err();
             }
-            GameResult::IllegalMoveByPlayer { player_idx, err } => {
-                info!(
-                    player = player_names[player_idx],
-                    game_idx, "Illegal move by player"
-                );
-                let mut err_dyn = &err as &dyn std::error::Error;
-                while let Some(src_err) = err_dyn.source() {
-                    info!("{}", err_dyn);
-                    err_dyn = src_err;
-                }
-                info!("{}", err_dyn);
-                if args.stop_on_illegal_move {
-                    break;
-                } else {
-                    wins[1 - player_idx] += 1;
-                    illegal_moves[player_idx] += 1;
+        }
+    };
+
+    if let Some(candidate) = gauntlet_candidate {
+        let scores = play_gauntlet(
+            &mut rng,
+            &mut players,
+            candidate,
+            &config,
+            &mut recorder,
+            &mut on_event,
+        )?;
+        finish_deals_file(deals_file, deals_error)?;
+        print_gauntlet_summary(&players, candidate, &scores);
+        return Ok(());
+    }
+
+    let score = play_matchup(
+        &mut rng,
+        &mut players,
+        &config,
+        &mut recorder,
+        &mut on_event,
+    )?;
+    let starting_player_win_rate = score.starting_player_win_rate();
+    let MatchScore {
+        wins,
+        illegal_moves,
+        protocol_errors,
+        crashes,
+        ties,
+        starts,
+        wins_as_starting_player,
+        series_wins,
+        series_ties,
+        stats,
+        warmup_stats,
+        sprt,
+        pairs,
+    } = score;
+    finish_deals_file(deals_file, deals_error)?;
+    if let Some(db) = results_db {
+        db.finish()?;
+    }
+
+    eprintln!("End result:");
+    for (idx, player) in players.iter().enumerate() {
+        let note = if players.len() == 2 {
+            wins_by_default_note(
+                illegal_moves[1 - idx],
+                protocol_errors[1 - idx],
+                crashes[1 - idx],
+                2 - idx,
+            )
+        } else {
+            forfeits_note(illegal_moves[idx], protocol_errors[idx], crashes[idx])
+        };
+        eprintln!("- {} wins by {}{}", wins[idx], player.name, note);
+    }
+    eprintln!("- {} ties", ties);
+    if let Some(win_rate) = starting_player_win_rate {
+        eprintln!("By starting player:");
+        let num_games: usize = starts.iter().sum();
+        for (idx, player) in players.iter().enumerate() {
+            eprintln!(
+                "- {} won {} of the {} games it started, and {} of the {} others",
+                player.name,
+                wins_as_starting_player[idx],
+                starts[idx],
+                wins[idx] - wins_as_starting_player[idx],
+                num_games - starts[idx]
+            );
+        }
+        eprintln!(
+            "- the starting player won {} of {} games ({:.1}%)",
+            wins_as_starting_player.iter().sum::<usize>(),
+            num_games,
+            100.0 * win_rate
+        );
+    }
+    if args.series_length.is_some() {
+        eprintln!("Series:");
+        for (idx, player) in players.iter().enumerate() {
+            eprintln!("- {} won by {}", series_wins[idx], player.name);
+        }
+        eprintln!("- {} ties", series_ties);
+    }
+    if let Some(pairs) = pairs {
+        eprintln!("Pairs of games with the same deal:");
+        eprintln!(
+            "- {} better for {}",
+            pairs.first_player_better, players[0].name
+        );
+        eprintln!(
+            "- {} better for {}",
+            pairs.second_player_better, players[1].name
+        );
+        eprintln!("- {} even", pairs.even);
+    }
+    if let Some(sprt) = sprt {
+        print_sprt_summary(&sprt);
+    }
+    if args.warmup > 0 {
+        eprintln!("Statistics of the {} warm-up games:", args.warmup);
+        for (player, stats) in players.iter().zip(&warmup_stats) {
+            eprintln!("- {}: {}", player.name, stats);
+        }
+    }
+    eprintln!("Statistics:");
+    for (player, stats) in players.iter().zip(&stats) {
+        eprintln!("- {}: {}", player.name, stats);
+    }
+    if let (Some(position_stats), Some(num_turns)) = (position_stats, args.common_positions) {
+        print_common_positions(&players, &position_stats, num_turns);
+    }
+
+    if let Some(path) = args.stats_file {
+        let json: Vec<_> = players
+            .iter()
+            .zip(stats.iter().zip(&warmup_stats))
+            .map(|(player, (stats, warmup_stats))| {
+                let mut json = serde_json::json!({ "player": player.name, "stats": stats });
+                if args.warmup > 0 {
+                    json["warmup_stats"] = serde_json::json!(warmup_stats);
                 }
-            }
+                json
+            })
+            .collect();
+        let inner = || -> anyhow::Result<()> {
+            serde_json::to_writer_pretty(BufWriter::new(File::create(&path)?), &json)?;
+            Ok(())
+        };
+        inner().with_context(|| format!("Could not write stats to '{}'", path.display()))?;
+    }
+    Ok(())
+}
+
+/// Writes the deal as a line of JSON. As in [`Deal::draw_piles`], the last card of each
+/// draw pile is drawn first.
+fn write_deal(writer: &mut impl Write, deal: &Deal) -> anyhow::Result<()> {
+    let json = serde_json::json!({
+        "starting_player": deal.starting_player,
+        "draw_piles": deal.draw_piles,
+    });
+    serde_json::to_writer(&mut *writer, &json)?;
+    writeln!(writer)?;
+    Ok(())
+}
+
+fn finish_deals_file(
+    deals_file: Option<BufWriter<File>>,
+    deals_error: Option<anyhow::Error>,
+) -> anyhow::Result<()> {
+    if let Some(mut file) = deals_file {
+        let result = match deals_error {
+            Some(err) => Err(err),
+            None => file.flush().map_err(anyhow::Error::from),
+        };
+        result.context("Could not write deals file")?;
+    }
+    Ok(())
+}
+
+/// Prints the candidate's score against each opponent, and over all games.
+fn print_gauntlet_summary(players: &[Player], candidate: usize, scores: &[MatchScore]) {
+    let opponents = players
+        .iter()
+        .enumerate()
+        .filter(|&(idx, _)| idx != candidate);
+    let mut total = MatchScore {
+        wins: vec![0, 0],
+        ..Default::default()
+    };
+    eprintln!("Gauntlet of {}:", players[candidate].name);
+    for ((_, opponent), score) in opponents.zip(scores) {
+        eprintln!(
+            "- against {}: {} wins, {} losses, {} ties, score {}",
+            opponent.name,
+            score.wins[0],
+            score.wins[1],
+            score.ties,
+            ScoreEstimate::from_match_score(score)
+        );
+        if let Some(win_rate) = score.starting_player_win_rate() {
+            eprintln!(
+                "  won {} of the {} games it started, the starting player won {:.1}%",
+                score.wins_as_starting_player[0],
+                score.starts[0],
+                100.0 * win_rate
+            );
+        }
+        if let Some(pairs) = &score.pairs {
+            eprintln!(
+                "  pairs of games with the same deal: {} better, {} worse, {} even",
+                pairs.first_player_better, pairs.second_player_better, pairs.even
+            );
         }
+        if let Some(sprt) = &score.sprt {
+            print_sprt_summary(sprt);
+        }
+        total.wins[0] += score.wins[0];
+        total.wins[1] += score.wins[1];
+        total.ties += score.ties;
     }
+    eprintln!("Overall: {}", ScoreEstimate::from_match_score(&total));
+}
 
-    let paren_1 = if illegal_moves[1] > 0 {
-        format!(" ({} through illegal moves by player 2)", illegal_moves[1])
-    } else {
-        String::new()
+fn print_common_positions(players: &[Player], position_stats: &PositionStats, num_turns: usize) {
+    eprintln!("Most common positions in the first {} turns:", num_turns);
+    for (idx, player) in players.iter().enumerate() {
+        let (num_positions, num_distinct) = position_stats.num_positions(idx);
+        eprintln!(
+            "- {} played on {} different boards in {} turns",
+            player.name, num_distinct, num_positions
+        );
+        for position in position_stats.most_common(idx, NUM_COMMON_POSITIONS) {
+            eprintln!("  - {}", position);
+        }
+    }
+}
+
+fn print_sprt_summary(sprt: &Sprt) {
+    let (lower, upper) = sprt.bounds();
+    let conclusion = match sprt.result() {
+        SprtResult::Accept => format!(
+            "ACCEPT, player 1 is better by at least {} Elo",
+            sprt.config.elo1
+        ),
+        SprtResult::Reject => format!(
+            "REJECT, player 1 is at most {} Elo better",
+            sprt.config.elo0
+        ),
+        SprtResult::Continue => {
+            String::from("INCONCLUSIVE, the maximum number of games was reached")
+        }
     };
-    let paren_2 = if illegal_moves[0] > 0 {
-        format!(" ({} through illegal moves by player 1)", illegal_moves[0])
+    eprintln!(
+        "SPRT: {}\n- LLR {:.2} (bounds {:.2}, {:.2}) after {} games",
+        conclusion,
+        sprt.llr(),
+        lower,
+        upper,
+        sprt.llr_trajectory.len()
+    );
+    // At most 20 points of the trajectory, the last one being the final LLR
+    let num_games = sprt.llr_trajectory.len();
+    let interval = num_games.div_ceil(20).max(1);
+    let points: Vec<String> = (1..=num_games)
+        .filter(|game| game % interval == 0 || *game == num_games)
+        .map(|game| format!("{}: {:.2}", game, sprt.llr_trajectory[game - 1]))
+        .collect();
+    eprintln!("- LLR trajectory by game: {}", points.join(", "));
+}
+
+/// Describes how many of the wins are due to the other player's illegal moves, protocol
+/// errors or crashes.
+fn wins_by_default_note(
+    illegal_moves: usize,
+    protocol_errors: usize,
+    crashes: usize,
+    other_player: usize,
+) -> String {
+    let mut reasons = Vec::new();
+    if illegal_moves > 0 {
+        reasons.push(format!(
+            "{} through illegal moves by player {}",
+            illegal_moves, other_player
+        ));
+    }
+    if protocol_errors > 0 {
+        reasons.push(format!(
+            "{} through protocol errors by player {}",
+            protocol_errors, other_player
+        ));
+    }
+    if crashes > 0 {
+        reasons.push(format!(
+            "{} through crashes of player {}",
+            crashes, other_player
+        ));
+    }
+    if reasons.is_empty() {
+        String::new()
     } else {
+        format!(" ({})", reasons.join(", "))
+    }
+}
+
+/// Describes how many games the player lost through its own illegal moves, protocol errors
+/// or crashes, for games with more than two players, where nobody wins those games.
+fn forfeits_note(illegal_moves: usize, protocol_errors: usize, crashes: usize) -> String {
+    let mut reasons = Vec::new();
+    if illegal_moves > 0 {
+        reasons.push(format!("{} illegal moves", illegal_moves));
+    }
+    if protocol_errors > 0 {
+        reasons.push(format!("{} protocol errors", protocol_errors));
+    }
+    if crashes > 0 {
+        reasons.push(format!("{} crashes", crashes));
+    }
+    if reasons.is_empty() {
         String::new()
+    } else {
+        format!(
+            " (lost {} games through {})",
+            illegal_moves + protocol_errors + crashes,
+            reasons.join(" and ")
+        )
+    }
+}
+
+/// Parses "i,j". The coordinates are limited so that the board can grow in every
+/// direction without leaving the range of `i8`.
+fn parse_location(text: &str) -> Result<(i8, i8), String> {
+    let parse_coordinate = |text: &str| {
+        text.trim()
+            .parse::<i8>()
+            .ok()
+            .filter(|coordinate| (-100..=100).contains(coordinate))
+            .ok_or_else(|| format!("'{}' is not a number between -100 and 100", text))
     };
-    eprintln!(
-        "End result:\n- {} wins by {}{}\n- {} wins by {}{}\n- {} ties",
-        wins[0], &player_1.name, paren_1, wins[1], player_2.name, paren_2, ties
-    );
-    Ok(())
+    let (i, j) = text
+        .split_once(',')
+        .ok_or_else(|| String::from("Expected a location like \"0,0\""))?;
+    Ok((parse_coordinate(i)?, parse_coordinate(j)?))
+}
+
+/// Parses a rank as in the JSON protocol, e.g. "10" or "J".
+fn parse_rank(text: &str) -> Result<Rank, String> {
+    Rank::ALL
+        .into_iter()
+        .find(|rank| rank.to_string() == text)
+        .ok_or_else(|| format!("'{}' is not one of 2 to 10, J, Q, K or A", text))
 }
 
 fn initialize_logging(level: LevelFilter) {
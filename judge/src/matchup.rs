@@ -0,0 +1,475 @@
+use std::cmp::Ordering;
+
+use gomori::{IllegalMove, MatchContext, Rules};
+use rand::rngs::StdRng;
+use tracing::{debug, info, warn};
+
+use crate::game::{play_game, Deal, GameEvent, GameResult};
+use crate::player::Player;
+use crate::recording::Recorder;
+use crate::sprt::{Sprt, SprtConfig, SprtResult};
+use crate::stats::PlayerStats;
+
+/// Settings for a series of games between two to four players.
+#[derive(Clone, Debug)]
+pub struct MatchConfig {
+    /// How many games to play, or with `series_length`, how many series.
+    pub num_games: usize,
+    /// Stop the match as soon as one player makes an illegal move.
+    pub stop_on_illegal_move: bool,
+    /// Log the rules engine's reasoning for every card played, see [`gomori::Explanation`].
+    pub explain: bool,
+    /// How often each player is restarted after crashing. Once a player can't be
+    /// restarted anymore, it loses all remaining games.
+    pub max_restarts: usize,
+    /// Play series of this many games, i.e. "best of N". A series ends as soon as one
+    /// player has won the majority of its games.
+    pub series_length: Option<usize>,
+    /// Let the players take turns starting the games, strictly in order, beginning with the
+    /// first player. In series, which may end early, the turns start over in each series,
+    /// beginning with the next player. Otherwise, the starting player is chosen randomly in every game, which
+    /// makes the results noisier if starting is an advantage.
+    pub alternate_start: bool,
+    /// Play the games in rounds of one game per player, where every game after the first
+    /// one of a round uses the [mirrored](Deal::mirrored) deal of the previous game. Every
+    /// player then plays with each shuffled deck once, which makes the results less random.
+    pub mirror_decks: bool,
+    /// The rules of the game, for playing a variant. The players are told about them in
+    /// the request for a new game.
+    pub rules: Rules,
+    /// Where the first card of each game is placed. The starting player is told about it in
+    /// the request to play the first turn.
+    pub first_card_location: (i8, i8),
+    /// End a game as a tie once it has lasted this many turns, including the first turn and
+    /// skipped turns, e.g. in case the players keep skipping their turns in turn.
+    pub max_turns: usize,
+    /// Stop the match as soon as a sequential probability ratio test decides whether the
+    /// first player is better. Then, `num_games` is only the maximum number of games.
+    /// Only possible with two players.
+    pub sprt: Option<SprtConfig>,
+    /// Play this many games before the match, e.g. to let bots that compile their code or
+    /// load a model on their first requests get up to speed. Their results are not counted,
+    /// and their statistics are kept apart in [`MatchScore::warmup_stats`].
+    pub warmup_games: usize,
+}
+
+impl Default for MatchConfig {
+    fn default() -> Self {
+        Self {
+            num_games: 100,
+            stop_on_illegal_move: false,
+            explain: false,
+            max_restarts: 0,
+            series_length: None,
+            alternate_start: false,
+            mirror_decks: false,
+            rules: Rules::STANDARD,
+            first_card_location: (0, 0),
+            max_turns: 1000,
+            sprt: None,
+            warmup_games: 0,
+        }
+    }
+}
+
+/// The results of a match, from the perspective of all players.
+///
+/// The vectors are indexed by the player, i.e. index 0 is the first player.
+///
+/// This is not `Eq`, since the [`Sprt`] in `sprt` has its config and LLRs as floats.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct MatchScore {
+    /// Games won by each player. In a two-player match, this includes games won through an
+    /// illegal move, protocol error or crash of the other player.
+    pub wins: Vec<usize>,
+    /// Games lost by each player due to an illegal move.
+    pub illegal_moves: Vec<usize>,
+    /// Games lost by each player due to a response that didn't follow the protocol, see
+    /// [`GameResult::ProtocolErrorByPlayer`].
+    pub protocol_errors: Vec<usize>,
+    /// Games lost by each player due to a crash, see [`GameResult::PlayerCrashed`].
+    pub crashes: Vec<usize>,
+    pub ties: usize,
+    /// Games started by each player, i.e. in which it played the first turn.
+    pub starts: Vec<usize>,
+    /// Games won by each player that it started, which is part of `wins`.
+    pub wins_as_starting_player: Vec<usize>,
+    /// Series won by each player, if the match is played in series.
+    pub series_wins: Vec<usize>,
+    /// Series in which no single player won the most games.
+    pub series_ties: usize,
+    /// Statistics about the turns played by each player.
+    pub stats: Vec<PlayerStats>,
+    /// Statistics about the turns played by each player in the
+    /// [warm-up games](MatchConfig::warmup_games), which are not part of `stats`.
+    pub warmup_stats: Vec<PlayerStats>,
+    /// The state of the sequential probability ratio test, if the match used one.
+    pub sprt: Option<Sprt>,
+    /// The outcomes of the pairs of games with the same deal, if the match was between two
+    /// players with [`MatchConfig::mirror_decks`].
+    pub pairs: Option<PairedScore>,
+}
+
+impl MatchScore {
+    /// The fraction of the games that were won by the player who started them, or `None`
+    /// if no game was played. With two evenly matched players, a value above 0.5 is an
+    /// advantage of moving first.
+    pub fn starting_player_win_rate(&self) -> Option<f64> {
+        let games: usize = self.starts.iter().sum();
+        let wins: usize = self.wins_as_starting_player.iter().sum();
+        (games > 0).then(|| wins as f64 / games as f64)
+    }
+}
+
+/// The outcomes of pairs of games in which two players played the same deal, once with
+/// each deck.
+///
+/// Within a pair, a win counts as 1 point and a tie as half a point. Since both players had
+/// the same luck, the player with more points played better.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PairedScore {
+    /// Pairs in which the first player scored more points.
+    pub first_player_better: usize,
+    /// Pairs in which the second player scored more points.
+    pub second_player_better: usize,
+    /// Pairs in which both players scored the same, e.g. because each won with the same deck.
+    pub even: usize,
+}
+
+/// Plays a series of games between two to four players.
+///
+/// When a player makes an illegal move, sends a response that doesn't follow the protocol
+/// or crashes, the game ends. With two players, the
+/// other player wins it; with more players, nobody does.
+///
+/// The events of all games are passed to `on_event`, see [`play_game()`], except for
+/// those of the [warm-up games](MatchConfig::warmup_games).
+///
+/// After the last game, the players are told the final score with a `Bye` request, so
+/// they get no more requests after that.
+///
+/// Returns an error only on communication failure, not when an illegal move is played, a
+/// response doesn't follow the protocol or a player crashes.
+pub fn play_matchup(
+    rng: &mut StdRng,
+    players: &mut [Player],
+    config: &MatchConfig,
+    recorder: &mut Option<Recorder>,
+    on_event: &mut dyn FnMut(GameEvent<'_>),
+) -> anyhow::Result<MatchScore> {
+    let (score, match_contexts) = play_match(rng, players, config, recorder, on_event)?;
+    for (player, context) in players.iter_mut().zip(match_contexts) {
+        player.say_bye(Some(context));
+    }
+    Ok(score)
+}
+
+// Plays a match like play_matchup(), without saying bye, and also returns the final score
+// from the perspective of each player
+fn play_match(
+    rng: &mut StdRng,
+    players: &mut [Player],
+    config: &MatchConfig,
+    recorder: &mut Option<Recorder>,
+    on_event: &mut dyn FnMut(GameEvent<'_>),
+) -> anyhow::Result<(MatchScore, Vec<MatchContext>)> {
+    let num_players = players.len();
+    anyhow::ensure!(
+        (2..=4).contains(&num_players),
+        "A match needs two to four players, not {}",
+        num_players
+    );
+    anyhow::ensure!(
+        config.series_length != Some(0),
+        "A series must have at least one game"
+    );
+    anyhow::ensure!(
+        config.sprt.is_none() || num_players == 2,
+        "A sequential probability ratio test needs two players"
+    );
+    anyhow::ensure!(
+        config.max_turns > 0,
+        "A game must be allowed at least one turn"
+    );
+    config.rules.validate()?;
+    let player_names: Vec<_> = players.iter().map(|p| p.name.clone()).collect();
+    let mut score = MatchScore {
+        wins: vec![0; num_players],
+        illegal_moves: vec![0; num_players],
+        protocol_errors: vec![0; num_players],
+        crashes: vec![0; num_players],
+        ties: 0,
+        starts: vec![0; num_players],
+        wins_as_starting_player: vec![0; num_players],
+        series_wins: vec![0; num_players],
+        series_ties: 0,
+        stats: vec![PlayerStats::default(); num_players],
+        warmup_stats: vec![PlayerStats::default(); num_players],
+        sprt: config.sprt.map(Sprt::new),
+        pairs: (config.mirror_decks && num_players == 2).then(PairedScore::default),
+    };
+    let mut restarts = vec![0; num_players];
+    // The player who wins by default when the other one loses through its own fault
+    let other_player = |player_idx: usize| (num_players == 2).then_some(1 - player_idx);
+    // Games won by each player in the current series, and the number of games in it
+    let mut series_wins = vec![0; num_players];
+    let mut series_games = 0;
+    let mut num_series = 0;
+    let mut deal: Option<Deal> = None;
+    // The wins of the first player minus those of the second in the current pair of games
+    let mut pair_balance = 0;
+    // The score so far from the perspective of each player, which they are told about
+    let mut match_contexts = vec![
+        MatchContext {
+            num_games: config.series_length.is_none().then_some(config.num_games),
+            ..Default::default()
+        };
+        num_players
+    ];
+
+    play_warmup_games(rng, players, config, &mut score.warmup_stats)?;
+
+    for game_idx in 0.. {
+        let finished = match config.series_length {
+            Some(_) => num_series == config.num_games,
+            None => game_idx == config.num_games,
+        };
+        if finished {
+            break;
+        }
+        let new_deal = match &deal {
+            Some(deal) if config.mirror_decks && game_idx % num_players != 0 => deal.mirrored(),
+            _ => {
+                // In series, by the index of the game in its series, and each series starts
+                // with the next player
+                let turn_idx = match config.series_length {
+                    Some(_) => num_series + series_games,
+                    None => game_idx,
+                };
+                let starting_player = config.alternate_start.then_some(turn_idx % num_players);
+                Deal::random_with_rules(num_players, starting_player, config.rules, rng)
+            }
+        };
+        let deal = deal.insert(new_deal);
+        debug!(game_idx, draw_piles = ?deal.draw_piles, "Dealt");
+        let result = play_game(
+            players,
+            recorder,
+            config,
+            deal,
+            Some(&match_contexts),
+            &mut score.stats,
+            on_event,
+        )?;
+        let winner = match result {
+            GameResult::WonByPlayer { player_idx } => {
+                debug!(winner = player_names[player_idx], game_idx);
+                score.wins[player_idx] += 1;
+                Some(player_idx)
+            }
+            GameResult::Tie { reason } => {
+                debug!(game_idx, ?reason, "Tie");
+                score.ties += 1;
+                None
+            }
+            GameResult::IllegalMoveByPlayer { player_idx, err } => {
+                info!(
+                    player = player_names[player_idx],
+                    game_idx, "Illegal move by player"
+                );
+                let mut err_dyn = &err as &dyn std::error::Error;
+                while let Some(src_err) = err_dyn.source() {
+                    info!("{}", err_dyn);
+                    err_dyn = src_err;
+                }
+                info!("{}", err_dyn);
+                if let IllegalMove::IllegalCardPlayed { board, .. } = &err {
+                    info!("Board before that card:\n{}", board);
+                }
+                if config.stop_on_illegal_move {
+                    break;
+                }
+                score.illegal_moves[player_idx] += 1;
+                let winner = other_player(player_idx);
+                if let Some(winner_idx) = winner {
+                    score.wins[winner_idx] += 1;
+                }
+                winner
+            }
+            GameResult::ProtocolErrorByPlayer { player_idx, err } => {
+                info!(
+                    player = player_names[player_idx],
+                    game_idx, "Protocol error by player: {}", err
+                );
+                score.protocol_errors[player_idx] += 1;
+                let winner = other_player(player_idx);
+                if let Some(winner_idx) = winner {
+                    score.wins[winner_idx] += 1;
+                }
+                winner
+            }
+            GameResult::PlayerCrashed { player_idx, err } => {
+                warn!(
+                    player = player_names[player_idx],
+                    game_idx, "Player crashed: {:#}", err
+                );
+                score.crashes[player_idx] += 1;
+                // Without a restart, the player crashes again right away in the next game
+                if restarts[player_idx] < config.max_restarts {
+                    restarts[player_idx] += 1;
+                    players[player_idx].restart()?;
+                }
+                let winner = other_player(player_idx);
+                if let Some(winner_idx) = winner {
+                    score.wins[winner_idx] += 1;
+                }
+                winner
+            }
+        };
+        score.starts[deal.starting_player] += 1;
+        if winner == Some(deal.starting_player) {
+            score.wins_as_starting_player[deal.starting_player] += 1;
+        }
+        for (player_idx, context) in match_contexts.iter_mut().enumerate() {
+            context.record_game(player_idx, winner);
+        }
+
+        if let Some(pairs) = &mut score.pairs {
+            pair_balance += match winner {
+                Some(0) => 1,
+                Some(_) => -1,
+                None => 0,
+            };
+            if game_idx % 2 == 1 {
+                match pair_balance.cmp(&0) {
+                    Ordering::Greater => pairs.first_player_better += 1,
+                    Ordering::Less => pairs.second_player_better += 1,
+                    Ordering::Equal => pairs.even += 1,
+                }
+                pair_balance = 0;
+            }
+        }
+
+        if let Some(series_length) = config.series_length {
+            if let Some(player_idx) = winner {
+                series_wins[player_idx] += 1;
+            }
+            series_games += 1;
+            let decided = series_wins.iter().any(|&wins| wins > series_length / 2);
+            if decided || series_games == series_length {
+                let max_wins = *series_wins.iter().max().unwrap();
+                let mut best_players = (0..num_players).filter(|&idx| series_wins[idx] == max_wins);
+                match (best_players.next(), best_players.next()) {
+                    (Some(player_idx), None) => score.series_wins[player_idx] += 1,
+                    _ => score.series_ties += 1,
+                }
+                debug!(series_idx = num_series, ?series_wins, "Series ended");
+                series_wins = vec![0; num_players];
+                series_games = 0;
+                num_series += 1;
+            }
+        }
+
+        // Last, since it may end the match
+        if let Some(sprt) = &mut score.sprt {
+            let result = sprt.record_game(winner);
+            debug!(game_idx, llr = sprt.llr(), "SPRT");
+            if result != SprtResult::Continue {
+                info!("SPRT decided: {}", result);
+                break;
+            }
+        }
+    }
+    Ok((score, match_contexts))
+}
+
+/// Plays a two-player match of the candidate, `players[candidate]`, against each of the
+/// other players in turn, e.g. to compare a new version of a bot with a panel of reference
+/// bots.
+///
+/// Each match is played like in [`play_matchup()`] with the same config, with the
+/// candidate as the first player. Each opponent is told bye after its match, but the
+/// candidate only after the last one. Returns the scores of the matches in the order of the
+/// opponents in `players`.
+pub fn play_gauntlet(
+    rng: &mut StdRng,
+    players: &mut [Player],
+    candidate: usize,
+    config: &MatchConfig,
+    recorder: &mut Option<Recorder>,
+    on_event: &mut dyn FnMut(GameEvent<'_>),
+) -> anyhow::Result<Vec<MatchScore>> {
+    anyhow::ensure!(
+        players.len() >= 2 && candidate < players.len(),
+        "A gauntlet needs a candidate and at least one opponent"
+    );
+    // The candidate moves to the front, and each opponent in turn next to it
+    players.swap(0, candidate);
+    let mut scores = Vec::with_capacity(players.len() - 1);
+    let mut result = Ok(());
+    let mut candidate_context = None;
+    for opponent in 1..players.len() {
+        players.swap(1, opponent);
+        let score = play_match(rng, &mut players[..2], config, recorder, on_event);
+        if let Ok((_, match_contexts)) = &score {
+            players[1].say_bye(Some(match_contexts[1]));
+            candidate_context = Some(match_contexts[0]);
+        }
+        players.swap(1, opponent);
+        match score {
+            Ok((score, _)) => scores.push(score),
+            Err(err) => {
+                result = Err(err);
+                break;
+            }
+        }
+    }
+    if result.is_ok() {
+        players[0].say_bye(candidate_context);
+    }
+    players.swap(0, candidate);
+    result?;
+    // The first opponent was swapped to the candidate's position
+    if candidate > 0 {
+        let first = scores.remove(candidate - 1);
+        scores.insert(0, first);
+    }
+    Ok(scores)
+}
+
+// Plays the warm-up games of the match. Crashed players are restarted without counting
+// towards `max_restarts`, since nothing is at stake yet.
+fn play_warmup_games(
+    rng: &mut StdRng,
+    players: &mut [Player],
+    config: &MatchConfig,
+    stats: &mut [PlayerStats],
+) -> anyhow::Result<()> {
+    for game_idx in 0..config.warmup_games {
+        let deal = Deal::random_with_rules(players.len(), None, config.rules, rng);
+        debug!(game_idx, draw_piles = ?deal.draw_piles, "Dealt warm-up game");
+        match play_game(players, &mut None, config, &deal, None, stats, &mut |_| {})? {
+            GameResult::IllegalMoveByPlayer { player_idx, err } => {
+                info!(
+                    player = players[player_idx].name,
+                    game_idx, "Illegal move by player in warm-up game: {}", err
+                );
+            }
+            GameResult::ProtocolErrorByPlayer { player_idx, err } => {
+                info!(
+                    player = players[player_idx].name,
+                    game_idx, "Protocol error by player in warm-up game: {}", err
+                );
+            }
+            GameResult::PlayerCrashed { player_idx, err } => {
+                warn!(
+                    player = players[player_idx].name,
+                    game_idx, "Player crashed in warm-up game: {:#}", err
+                );
+                players[player_idx].restart()?;
+            }
+            GameResult::WonByPlayer { .. } | GameResult::Tie { .. } => {}
+        }
+    }
+    Ok(())
+}
@@ -1,29 +1,142 @@
 use std::fs::File;
-use std::io::{BufRead, BufReader, Write};
-use std::path::Path;
-use std::process::{ChildStdin, ChildStdout, Command, Stdio};
+use std::io::{BufRead, BufReader, ErrorKind, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Child, ChildStdin, ChildStdout, Command, Stdio};
+use std::time::{Duration, Instant};
 
 use anyhow::Context;
-use gomori::{Color, PlayerState, Request};
-use rand::rngs::StdRng;
+use gomori::{DrawPile, MatchContext, PlayerState, Protocol, Request};
 use serde::{Deserialize, Serialize};
-use tracing::{info, trace};
+use tracing::{debug, info, trace};
 
+use crate::human::HumanPlayer;
+use crate::protocol_errors::ProtocolError;
 use crate::recording::Recorder;
+use crate::resource_limits::{try_wait_with_cpu_time, ResourceLimits};
 
 pub struct Player {
     pub name: String,
+    connection: Box<dyn Connection>,
+}
+
+/// The channel through which a player receives requests and sends responses.
+///
+/// Requests and responses are single lines of JSON, without the trailing newline,
+/// unless the connection uses a binary [`Protocol`].
+pub trait Connection {
+    fn exchange(&mut self, request: &str) -> anyhow::Result<String>;
+
+    /// The encoding of requests and responses.
+    fn protocol(&self) -> Protocol {
+        Protocol::Json
+    }
+
+    /// Like [`exchange()`](Connection::exchange), but for the binary protocols.
+    ///
+    /// Only called if [`protocol()`](Connection::protocol) is not [`Protocol::Json`].
+    fn exchange_binary(&mut self, _request: &[u8]) -> anyhow::Result<Vec<u8>> {
+        anyhow::bail!("Binary protocols are not supported by this connection")
+    }
+
+    /// Starts the player anew after it crashed, i.e. after an exchange failed with
+    /// [`ConnectionClosed`].
+    fn restart(&mut self) -> anyhow::Result<()> {
+        anyhow::bail!("This player can't be restarted")
+    }
+
+    /// Sends [`Request::Bye`], which has no response, encoded like the other requests
+    /// for the connection's [`protocol()`](Connection::protocol).
+    ///
+    /// By default, nothing is sent.
+    fn send_bye(&mut self, _request: &[u8]) -> anyhow::Result<()> {
+        Ok(())
+    }
+}
+
+/// The error for a player that has exited, or closed its end of the connection.
+///
+/// Connections return it from [`Connection::exchange()`], so that the judge can tell
+/// a crashed player apart from other communication failures.
+#[derive(Debug)]
+pub struct ConnectionClosed;
+
+impl std::error::Error for ConnectionClosed {}
+
+impl std::fmt::Display for ConnectionClosed {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "The player closed the connection")
+    }
+}
+
+// Maps the IO errors that mean that the other side of the pipe is gone to ConnectionClosed.
+fn io_error_to_anyhow(err: std::io::Error) -> anyhow::Error {
+    match err.kind() {
+        ErrorKind::BrokenPipe | ErrorKind::UnexpectedEof => ConnectionClosed.into(),
+        _ => err.into(),
+    }
+}
+
+/// A bot running as a child process, which communicates via its stdin/stdout.
+struct ProcessConnection {
+    nick: String,
+    cmd: Vec<String>,
+    stderr: BotStderr,
+    child: Child,
     stdin: ChildStdin,
     stdout: BufReader<ChildStdout>,
+    protocol: Protocol,
+    limits: ResourceLimits,
+    // Whether the child has been reaped, after which it must not be killed or waited for
+    reaped: bool,
     // A re-usable buffer for IO.
-    // Should always be empty before and after perform_request().
+    // Should always be empty before and after exchange().
     buf: String,
 }
 
 #[derive(Serialize, Deserialize)]
 pub struct PlayerConfig {
     pub nick: String,
+    /// The command to start the bot. Only needed for bots.
+    #[serde(default)]
     pub cmd: Vec<String>,
+    #[serde(rename = "type", default)]
+    pub player_type: PlayerType,
+    /// The encoding of the messages sent to and from the bot. Only used for bots.
+    #[serde(default)]
+    pub protocol: Protocol,
+    /// Where the bot's stderr goes. Only used for bots.
+    #[serde(default)]
+    pub stderr: BotStderr,
+    /// Limits on the memory and CPU time of the bot. Only used for bots.
+    #[serde(flatten)]
+    pub limits: ResourceLimits,
+}
+
+/// Where the stderr output of a bot goes.
+///
+/// In the config file, this is `"inherit"`, `"prefix"` or `{"dir": "path/to/logs"}`.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BotStderr {
+    /// Into the judge's stderr, unchanged.
+    #[default]
+    Inherit,
+    /// Into the judge's stderr, with every line prefixed by the nick of the player, so
+    /// that the output of several bots can be told apart.
+    Prefix,
+    /// Appended to the file `<nick>.log` in this directory, which is created if needed.
+    Dir(PathBuf),
+}
+
+/// Whether a player is a bot or a person.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PlayerType {
+    /// A bot process, started with the configured command.
+    #[default]
+    Bot,
+    /// A person at the terminal that the judge is running in.
+    Human,
 }
 
 impl PlayerConfig {
@@ -32,7 +145,7 @@ impl PlayerConfig {
             let f = File::open(path)?;
             let config = serde_json::from_reader::<_, PlayerConfig>(BufReader::new(f))
                 .context("Could not parse file as PlayerConfig JSON")?;
-            if config.cmd.is_empty() {
+            if config.player_type == PlayerType::Bot && config.cmd.is_empty() {
                 anyhow::bail!("'cmd' field cannot be empty.");
             }
             Ok(config)
@@ -44,66 +157,484 @@ impl PlayerConfig {
 pub struct PlayerWithGameState<'a> {
     pub player: &'a mut Player,
     pub state: PlayerState,
+    /// How long the player took to answer the last request, not counting the time to
+    /// encode the request and decode the response.
+    pub last_response_time: Duration,
 }
 
 impl Player {
     pub fn new(path: &Path) -> anyhow::Result<Self> {
         let config = PlayerConfig::load(path)?;
-        let child_proc = Command::new(&config.cmd[0])
-            .args(&config.cmd[1..])
+        Self::from_config(config)
+    }
+
+    pub fn from_config(config: PlayerConfig) -> anyhow::Result<Self> {
+        let connection: Box<dyn Connection> = match config.player_type {
+            PlayerType::Bot => Box::new(ProcessConnection::spawn(
+                &config.nick,
+                &config.cmd,
+                config.protocol,
+                config.stderr,
+                config.limits,
+            )?),
+            PlayerType::Human => Box::new(HumanPlayer::new()),
+        };
+        Ok(Self::with_connection(config.nick, connection))
+    }
+
+    pub fn with_connection(name: String, connection: Box<dyn Connection>) -> Self {
+        Self { name, connection }
+    }
+
+    /// Starts the player anew after it crashed, see [`Connection::restart()`].
+    pub fn restart(&mut self) -> anyhow::Result<()> {
+        info!(player = self.name, "Restarting player");
+        self.connection
+            .restart()
+            .with_context(|| format!("Failed to restart '{}'", self.name))
+    }
+
+    /// Tells the player that it gets no more requests, and the final score of its match,
+    /// if it played one. The player may have exited already, so errors are only logged.
+    pub fn say_bye(&mut self, match_context: Option<MatchContext>) {
+        let req = Request::Bye { match_context };
+        trace!(name: "Sending request", player = &self.name, request = ?req);
+        let result = match self.connection.protocol() {
+            Protocol::Json => serde_json::to_vec(&req).map_err(anyhow::Error::from),
+            Protocol::MsgPack => rmp_serde::to_vec_named(&req).map_err(anyhow::Error::from),
+        }
+        .and_then(|req_bytes| self.connection.send_bye(&req_bytes));
+        if let Err(err) = result {
+            debug!(player = self.name, "Could not say bye: {:#}", err);
+        }
+    }
+}
+
+impl ProcessConnection {
+    fn spawn(
+        nick: &str,
+        cmd: &[String],
+        protocol: Protocol,
+        stderr: BotStderr,
+        limits: ResourceLimits,
+    ) -> anyhow::Result<Self> {
+        let stderr_stdio = match &stderr {
+            BotStderr::Inherit => Stdio::inherit(),
+            BotStderr::Prefix => Stdio::piped(),
+            BotStderr::Dir(dir) => {
+                let path = log_file_path(dir, nick);
+                std::fs::create_dir_all(dir)
+                    .and_then(|_| File::options().create(true).append(true).open(&path))
+                    .with_context(|| format!("Could not open log file '{}'", path.display()))?
+                    .into()
+            }
+        };
+        let mut command = Command::new(&cmd[0]);
+        command
+            .args(&cmd[1..])
+            .env(Protocol::ENV_VAR, protocol.name())
             .stdin(Stdio::piped())
             .stdout(Stdio::piped())
+            .stderr(stderr_stdio);
+        limits.apply(&mut command)?;
+        let mut child = command
             .spawn()
-            .with_context(|| format!("Failed to spawn child process {:?}", &config.cmd))?;
-        info!(cmd = ?config.cmd, "Spawned child process");
+            .with_context(|| format!("Failed to spawn child process {:?}", cmd))?;
+        info!(
+            ?cmd,
+            protocol = protocol.name(),
+            ?limits,
+            "Spawned child process"
+        );
+
+        if let Some(child_stderr) = child.stderr.take() {
+            // The thread ends when the bot exits and its end of the pipe is closed
+            let prefix = format!("[{}] ", nick);
+            std::thread::spawn(move || {
+                for line in BufReader::new(child_stderr).lines() {
+                    match line {
+                        Ok(line) => eprintln!("{}{}", prefix, line),
+                        Err(_) => break,
+                    }
+                }
+            });
+        }
 
         Ok(Self {
-            name: config.nick,
-            stdin: child_proc.stdin.expect("Could not access stdin"),
-            stdout: BufReader::new(child_proc.stdout.expect("Could not access stdout")),
+            nick: nick.to_owned(),
+            cmd: cmd.to_vec(),
+            stderr,
+            stdin: child.stdin.take().expect("Could not access stdin"),
+            stdout: BufReader::new(child.stdout.take().expect("Could not access stdout")),
+            child,
+            protocol,
+            limits,
+            reaped: false,
             buf: String::new(),
         })
     }
 }
 
+impl ProcessConnection {
+    // Writes a request followed by a newline, or preceded by its length for MessagePack
+    fn write_request(&mut self, request: &[u8]) -> anyhow::Result<()> {
+        match self.protocol {
+            Protocol::Json => self
+                .stdin
+                .write_all(request)
+                .and_then(|_| self.stdin.write_all(b"\n")),
+            Protocol::MsgPack => self
+                .stdin
+                .write_all(&(request.len() as u32).to_be_bytes())
+                .and_then(|_| self.stdin.write_all(request)),
+        }
+        .and_then(|_| self.stdin.flush())
+        .map_err(io_error_to_anyhow)
+        .context("Could not send request")
+    }
+
+    fn exchange_text(&mut self, request: &str) -> anyhow::Result<String> {
+        self.write_request(request.as_bytes())?;
+        self.buf.clear();
+        if self.stdout.read_line(&mut self.buf)? == 0 {
+            // 0 bytes read means EOF - the bot has exited.
+            return Err(ConnectionClosed.into());
+        }
+        let response = String::from(self.buf.trim_end());
+        self.buf.clear();
+        Ok(response)
+    }
+
+    /// Messages are preceded by their length, see [`Protocol::MsgPack`].
+    fn exchange_bytes(&mut self, request: &[u8]) -> anyhow::Result<Vec<u8>> {
+        self.write_request(request)?;
+        let mut len = [0u8; 4];
+        self.stdout
+            .read_exact(&mut len)
+            .map_err(io_error_to_anyhow)
+            .context("Could not read response length")?;
+        let mut response = vec![0u8; u32::from_be_bytes(len) as usize];
+        self.stdout
+            .read_exact(&mut response)
+            .map_err(io_error_to_anyhow)
+            .context("Could not read response")?;
+        Ok(response)
+    }
+
+    // If the bot exited because of a resource limit, adds that to the error
+    fn explain_closed(&mut self, err: anyhow::Error) -> anyhow::Error {
+        if self.limits.is_empty() || !err.is::<ConnectionClosed>() {
+            return err;
+        }
+        // The bot's end of the pipe may be closed shortly before it has exited
+        let mut exit = None;
+        for _ in 0..100 {
+            exit = try_wait_with_cpu_time(&self.child);
+            if exit.is_some() {
+                self.reaped = true;
+                break;
+            }
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        match exit.and_then(|(status, cpu_time)| self.limits.exceeded(status, cpu_time)) {
+            Some(exceeded) => err.context(exceeded),
+            None => err,
+        }
+    }
+}
+
+impl Connection for ProcessConnection {
+    fn exchange(&mut self, request: &str) -> anyhow::Result<String> {
+        self.exchange_text(request)
+            .map_err(|err| self.explain_closed(err))
+    }
+
+    fn protocol(&self) -> Protocol {
+        self.protocol
+    }
+
+    fn exchange_binary(&mut self, request: &[u8]) -> anyhow::Result<Vec<u8>> {
+        self.exchange_bytes(request)
+            .map_err(|err| self.explain_closed(err))
+    }
+
+    fn restart(&mut self) -> anyhow::Result<()> {
+        // The process has most likely exited already, so errors are expected here
+        if !self.reaped {
+            let _ = self.child.kill();
+            let _ = self.child.wait();
+        }
+        *self = Self::spawn(
+            &self.nick,
+            &self.cmd,
+            self.protocol,
+            self.stderr.clone(),
+            self.limits,
+        )?;
+        Ok(())
+    }
+
+    fn send_bye(&mut self, request: &[u8]) -> anyhow::Result<()> {
+        self.write_request(request)
+    }
+}
+
+// Nicks may contain characters that are not allowed in file names
+fn log_file_path(dir: &Path, nick: &str) -> PathBuf {
+    let file_stem: String = nick
+        .chars()
+        .map(|c| {
+            if c.is_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    dir.join(format!("{}.log", file_stem))
+}
+
 impl<'a> PlayerWithGameState<'a> {
-    pub fn new(player: &'a mut Player, color: Color, rng: &mut StdRng) -> Self {
+    /// The player starts with the given draw pile, see [`PlayerState::with_draw_pile()`].
+    pub fn new(player: &'a mut Player, draw_pile: DrawPile) -> Self {
         Self {
             player,
-            state: PlayerState::new(color, rng),
+            state: PlayerState::with_draw_pile(draw_pile),
+            last_response_time: Duration::ZERO,
         }
     }
 
-    pub fn perform_request<T: serde::de::DeserializeOwned>(
+    /// Sends the request, and parses the response. A response that can't be parsed, or that
+    /// has more than five cards to play, is a [`ProtocolError`].
+    pub fn perform_request<T: Serialize + serde::de::DeserializeOwned>(
         &mut self,
         recorder: &mut Option<Recorder>,
         req: &Request,
     ) -> anyhow::Result<T> {
+        if self.player.connection.protocol() == Protocol::MsgPack {
+            return self.perform_msgpack_request(recorder, req);
+        }
         let mut inner = || -> anyhow::Result<T> {
-            let mut req_json = serde_json::to_string(req)?;
+            let req_json = serde_json::to_string(req)?;
             trace!(name: "Sending request", player = &self.player.name, request = %req_json);
-            req_json.push('\n');
-            self.player
-                .stdin
-                .write_all(req_json.as_bytes())
-                .context("Could not send request")?;
-            self.player.stdin.flush()?;
-            self.player.buf.clear();
-            self.player.stdout.read_line(&mut self.player.buf)?;
-            let serialized_response = self.player.buf.trim_end();
-            let response = serde_json::from_str::<T>(serialized_response).with_context(|| {
-                format!("Could not parse response '{}' as JSON", serialized_response)
-            })?;
+            let start = Instant::now();
+            let serialized_response = self.player.connection.exchange(&req_json)?;
+            self.last_response_time = start.elapsed();
+            let response = serde_json::from_str::<T>(&serialized_response)
+                .map_err(|err| ProtocolError::from_json(req, &serialized_response, err))?;
+            ProtocolError::check(req, &serde_json::to_value(&response)?)?;
             trace!(name: "Recieved response", player = &self.player.name, response = %serialized_response);
             if let Some(recorder) = recorder {
                 recorder.store_request(
                     &self.player.name,
                     req_json,
-                    String::from(serialized_response),
+                    serialized_response,
+                    self.last_response_time,
                 );
             }
             Ok(response)
         };
         inner().with_context(|| format!("Failed to make a request to '{}'", self.player.name))
     }
+
+    fn perform_msgpack_request<T: Serialize + serde::de::DeserializeOwned>(
+        &mut self,
+        recorder: &mut Option<Recorder>,
+        req: &Request,
+    ) -> anyhow::Result<T> {
+        let mut inner = || -> anyhow::Result<T> {
+            let req_bytes = rmp_serde::to_vec_named(req)?;
+            trace!(name: "Sending request", player = &self.player.name, request = ?req);
+            let start = Instant::now();
+            let response_bytes = self.player.connection.exchange_binary(&req_bytes)?;
+            self.last_response_time = start.elapsed();
+            let response = rmp_serde::from_slice::<T>(&response_bytes)
+                .map_err(|err| ProtocolError::from_msgpack(req, &response_bytes, err))?;
+            ProtocolError::check(req, &serde_json::to_value(&response)?)?;
+            trace!(name: "Recieved response", player = &self.player.name, num_bytes = response_bytes.len());
+            if let Some(recorder) = recorder {
+                // Recordings are JSON regardless of the protocol
+                recorder.store_request(
+                    &self.player.name,
+                    serde_json::to_string(req)?,
+                    serde_json::to_string(&response)?,
+                    self.last_response_time,
+                );
+            }
+            Ok(response)
+        };
+        inner().with_context(|| format!("Failed to make a request to '{}'", self.player.name))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use gomori::{card, Color, Deck, Field, Hand, Okay, Rules};
+
+    use super::*;
+    use crate::ResourceLimitExceeded;
+
+    /// `cat` sends each request back as the response, so this checks that
+    /// requests survive the framing and encoding in both directions.
+    #[test]
+    fn msgpack_requests_round_trip() {
+        let config = PlayerConfig {
+            nick: String::from("Echo"),
+            cmd: vec![String::from("cat")],
+            player_type: PlayerType::Bot,
+            protocol: Protocol::MsgPack,
+            stderr: BotStderr::Inherit,
+            limits: ResourceLimits::default(),
+        };
+        let mut player = Player::from_config(config).unwrap();
+        let mut player = PlayerWithGameState::new(
+            &mut player,
+            DrawPile::from_cards(Deck::Color(Color::Red).cards()),
+        );
+        let req = Request::PlayTurn {
+            cards: Hand::try_from([
+                card!("2♦"),
+                card!("3♦"),
+                card!("4♥"),
+                card!("J♥"),
+                card!("K♦"),
+            ])
+            .unwrap(),
+            fields: vec![Field {
+                i: 0,
+                j: 0,
+                top_card: None,
+                hidden_cards: BTreeSet::from([card!("A♠")]),
+            }],
+            cards_won_by_opponent: BTreeSet::from([card!("7♣")]),
+            draw_pile_size: 25,
+            opponent_draw_pile_size: 24,
+            turn_number: 4,
+        };
+        let echoed: Request = player.perform_request(&mut None, &req).unwrap();
+        assert_eq!(
+            serde_json::to_string(&echoed).unwrap(),
+            serde_json::to_string(&req).unwrap()
+        );
+    }
+
+    #[test]
+    fn stderr_is_written_to_log_file() {
+        let dir = std::env::temp_dir().join(format!("judge-bot-logs-{}", std::process::id()));
+        let config = PlayerConfig {
+            nick: String::from("Chatty/Echo"),
+            cmd: ["sh", "-c", "echo starting >&2; exec cat"]
+                .map(String::from)
+                .to_vec(),
+            player_type: PlayerType::Bot,
+            protocol: Protocol::Json,
+            stderr: BotStderr::Dir(dir.clone()),
+            limits: ResourceLimits::default(),
+        };
+        let mut player = Player::from_config(config).unwrap();
+        // Once the bot responds, it has written to stderr
+        assert_eq!(player.connection.exchange("ping").unwrap(), "ping");
+        let log = std::fs::read_to_string(dir.join("Chatty_Echo.log")).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(log, "starting\n");
+    }
+
+    /// A process that exits right away has crashed, and can be restarted.
+    #[test]
+    fn exited_process_is_a_crash() {
+        let config = PlayerConfig {
+            nick: String::from("Quitter"),
+            cmd: vec![String::from("true")],
+            player_type: PlayerType::Bot,
+            protocol: Protocol::Json,
+            stderr: BotStderr::Inherit,
+            limits: ResourceLimits::default(),
+        };
+        let mut player = Player::from_config(config).unwrap();
+        for _ in 0..2 {
+            let mut player = PlayerWithGameState::new(
+                &mut player,
+                DrawPile::from_cards(Deck::Color(Color::Red).cards()),
+            );
+            let req = Request::NewGame {
+                color: Color::Red,
+                num_players: 2,
+                seat: 0,
+                suit: None,
+                match_context: None,
+                rules: Rules::STANDARD,
+            };
+            let err = player.perform_request::<Okay>(&mut None, &req).unwrap_err();
+            assert!(err.is::<ConnectionClosed>());
+            player.player.restart().unwrap();
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn memory_limit_is_applied() {
+        let config: PlayerConfig = serde_json::from_value(serde_json::json!({
+            "nick": "Limited",
+            "cmd": ["sh", "-c", "ulimit -v; exec cat"],
+            "max_memory_mb": 256,
+        }))
+        .unwrap();
+        assert_eq!(config.limits.max_memory_mb, Some(256));
+        let mut player = Player::from_config(config).unwrap();
+        // In kilobytes
+        assert_eq!(player.connection.exchange("ping").unwrap(), "262144");
+    }
+
+    /// A bot that uses up its CPU time is killed, which is a crash with a clear reason.
+    #[cfg(unix)]
+    #[test]
+    fn cpu_limit_is_reported() {
+        let config = PlayerConfig {
+            nick: String::from("Busy"),
+            cmd: ["sh", "-c", "while :; do :; done"]
+                .map(String::from)
+                .to_vec(),
+            player_type: PlayerType::Bot,
+            protocol: Protocol::Json,
+            stderr: BotStderr::Inherit,
+            limits: ResourceLimits {
+                max_memory_mb: None,
+                cpu_seconds: Some(1),
+            },
+        };
+        let mut player = Player::from_config(config).unwrap();
+        let err = player.connection.exchange("ping").unwrap_err();
+        assert!(err.is::<ConnectionClosed>());
+        assert_eq!(
+            err.downcast_ref::<ResourceLimitExceeded>(),
+            Some(&ResourceLimitExceeded::CpuTime { seconds: 1 })
+        );
+    }
+
+    /// Other crashes are not blamed on the limits, even if the bot is killed by SIGKILL.
+    #[cfg(unix)]
+    #[test]
+    fn other_crashes_are_not_attributed_to_limits() {
+        for script in ["kill -9 $$", "exit 1"] {
+            let config = PlayerConfig {
+                nick: String::from("Crashing"),
+                cmd: ["sh", "-c", script].map(String::from).to_vec(),
+                player_type: PlayerType::Bot,
+                protocol: Protocol::Json,
+                stderr: BotStderr::Inherit,
+                limits: ResourceLimits {
+                    max_memory_mb: Some(256),
+                    cpu_seconds: Some(1),
+                },
+            };
+            let mut player = Player::from_config(config).unwrap();
+            let err = player.connection.exchange("ping").unwrap_err();
+            assert!(err.is::<ConnectionClosed>());
+            assert_eq!(err.downcast_ref::<ResourceLimitExceeded>(), None);
+            // The reaped bot can still be restarted
+            player.restart().unwrap();
+        }
+    }
 }
@@ -0,0 +1,170 @@
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+
+use gomori::{Board, CompactField};
+
+use crate::game::GameEvent;
+
+/// Counts how often the players get to play on the same board across many games, e.g. to
+/// find positions that are worth adding to an opening book, or bots that play the same
+/// moves against each other over and over.
+///
+/// The games are passed to [`record()`](Self::record) event by event, like to a
+/// [`ResultsDb`](crate::ResultsDb). Only the early game is counted: the boards on which the
+/// players play turns 2 to `max_turn_number` of each game. The first turn is left out, since
+/// there is no board yet. Boards are the same if they have the same cards in the same
+/// places, whatever the order of their fields, and the players' hands are not part of the
+/// position.
+pub struct PositionStats {
+    max_turn_number: usize,
+    // For each player and board, how often it was the one to play on
+    counts: Vec<HashMap<Board, usize>>,
+    // The turn number of the current game, counting from 1
+    turn_number: usize,
+}
+
+/// A board that a player had to play on, and how often.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PositionCount {
+    pub board: Board,
+    pub count: usize,
+}
+
+impl Display for PositionCount {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}× ", self.count)?;
+        for (idx, (i, j, field)) in sorted_fields(&self.board).into_iter().enumerate() {
+            if idx > 0 {
+                write!(f, ", ")?;
+            }
+            match field.top_card() {
+                Some(card) => write!(f, "{} at ({}, {})", card, i, j)?,
+                None => write!(f, "face-down card at ({}, {})", i, j)?,
+            }
+            let num_hidden = field.hidden_cards().len();
+            if num_hidden > 0 {
+                write!(f, " on {} more", num_hidden)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl PositionStats {
+    pub fn new(num_players: usize, max_turn_number: usize) -> Self {
+        Self {
+            max_turn_number,
+            counts: vec![HashMap::new(); num_players],
+            turn_number: 0,
+        }
+    }
+
+    /// Adds an event of the current game. Events of players beyond the number that the
+    /// stats were created for are ignored.
+    pub fn record(&mut self, event: &GameEvent) {
+        if let GameEvent::TurnStarted {
+            player_idx, board, ..
+        } = *event
+        {
+            match board {
+                None => self.turn_number = 1,
+                Some(board) => {
+                    self.turn_number += 1;
+                    if self.turn_number <= self.max_turn_number {
+                        if let Some(counts) = self.counts.get_mut(player_idx) {
+                            *counts.entry(board.clone()).or_default() += 1;
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// How many early-game turns the player played, and on how many different boards.
+    pub fn num_positions(&self, player_idx: usize) -> (usize, usize) {
+        let counts = &self.counts[player_idx];
+        let total = counts.values().sum();
+        (total, counts.len())
+    }
+
+    /// The boards on which the player played most often, at most `n` of them, most common
+    /// first. Boards that occurred only once are left out.
+    pub fn most_common(&self, player_idx: usize, n: usize) -> Vec<PositionCount> {
+        let mut repeated: Vec<_> = self.counts[player_idx]
+            .iter()
+            .filter(|&(_, &count)| count > 1)
+            .map(|(board, &count)| (Reverse(count), sorted_fields(board), board))
+            .collect();
+        // Ties are broken by the fields, to not depend on the order of the hash map
+        repeated.sort_unstable_by(|a, b| (&a.0, &a.1).cmp(&(&b.0, &b.1)));
+        repeated
+            .into_iter()
+            .take(n)
+            .map(|(Reverse(count), _, board)| PositionCount {
+                board: board.clone(),
+                count,
+            })
+            .collect()
+    }
+}
+
+fn sorted_fields(board: &Board) -> Vec<(i8, i8, CompactField)> {
+    let mut fields = board.to_vec();
+    fields.sort_unstable();
+    fields
+}
+
+#[cfg(test)]
+mod tests {
+    use gomori::{card, field, Hand};
+
+    use super::*;
+
+    fn turn(stats: &mut PositionStats, player_idx: usize, board: Option<&Board>) {
+        stats.record(&GameEvent::TurnStarted {
+            player_idx,
+            hand: Hand::try_from(&[card!("2♠")][..]).unwrap(),
+            board,
+        });
+    }
+
+    #[test]
+    fn repeated_early_boards_are_counted_per_player() {
+        let one_card = Board::new(&[field(0, 0, card!("7♥"))]);
+        let two_cards = Board::new(&[field(0, 0, card!("7♥")), field(0, 1, card!("8♥"))]);
+        let same_two_cards = Board::new(&[field(0, 1, card!("8♥")), field(0, 0, card!("7♥"))]);
+        let other = Board::new(&[field(0, 0, card!("7♥")), field(1, 0, card!("8♥"))]);
+        let late = Board::new(&[
+            field(0, 0, card!("7♥")),
+            field(0, 1, card!("8♥")),
+            field(0, 2, card!("9♥")),
+        ]);
+        let mut stats = PositionStats::new(2, 3);
+        for game_board in [&two_cards, &same_two_cards, &other] {
+            turn(&mut stats, 0, None);
+            turn(&mut stats, 1, Some(&one_card));
+            turn(&mut stats, 0, Some(game_board));
+            // Past the early game
+            turn(&mut stats, 1, Some(&late));
+            turn(&mut stats, 0, Some(&late));
+        }
+        assert_eq!(stats.num_positions(0), (3, 2));
+        assert_eq!(stats.num_positions(1), (3, 1));
+
+        let most_common = stats.most_common(0, 5);
+        assert_eq!(
+            most_common,
+            [PositionCount {
+                board: two_cards,
+                count: 2
+            }]
+        );
+        assert_eq!(
+            most_common[0].to_string(),
+            format!("2× {} at (0, 0), {} at (0, 1)", card!("7♥"), card!("8♥"))
+        );
+        assert_eq!(stats.most_common(1, 5)[0].count, 3);
+        assert!(stats.most_common(1, 0).is_empty());
+    }
+}
@@ -0,0 +1,364 @@
+use std::fmt::{Display, Formatter};
+
+use gomori::{Card, CardToPlay, Hand, Okay, PlayTurnResponse, Rank, Request, Suit};
+use serde::de::DeserializeOwned;
+use serde_json::{Map, Value};
+
+// Longer parts of a response are shortened to this many characters in diagnostics
+const MAX_SNIPPET_LEN: usize = 60;
+
+const ROOT: &str = "response";
+const OKAY: &str = "an acknowledgement, i.e. []";
+const CARD: &str = r#"a card, like {"suit": "♥", "rank": "7"}"#;
+const SUIT: &str = r#"one of "♦", "♥", "♠" and "♣""#;
+const RANK: &str = r#"one of "2" to "10", "J", "Q", "K" and "A""#;
+const CARD_TO_PLAY: &str =
+    r#"a card to play, like {"card": {"suit": "♥", "rank": "7"}, "i": 0, "j": 1}"#;
+const COORDINATE: &str = "a coordinate from -128 to 127";
+const TARGET_FIELD: &str = "null or the coordinates of a field, like [0, 1]";
+const TURN: &str = r#""skip" or a list of cards to play"#;
+const AT_MOST_FIVE: &str = "at most 5 cards to play";
+
+/// A response that doesn't follow the protocol, with a diagnosis of what is wrong with it.
+///
+/// Unlike an [`IllegalMove`](gomori::IllegalMove), the response could not even be parsed,
+/// but it loses the game just the same, see [`GameResult::ProtocolErrorByPlayer`](crate::GameResult::ProtocolErrorByPlayer).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ProtocolError {
+    /// The response is not valid JSON, or not valid MessagePack with the binary protocol.
+    Malformed {
+        /// The error of the parser.
+        message: String,
+        /// The part of the response where parsing failed.
+        snippet: String,
+    },
+    /// The response is valid JSON or MessagePack, but not what the request asked for.
+    Invalid {
+        /// Where in the response the error is, e.g. `response[1].card.suit`.
+        path: String,
+        /// What the protocol expects there.
+        expected: &'static str,
+        /// The offending part of the response, as JSON.
+        snippet: String,
+        /// The error of the parser.
+        message: String,
+    },
+}
+
+impl std::error::Error for ProtocolError {}
+
+impl Display for ProtocolError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ProtocolError::Malformed { message, snippet } => {
+                write!(
+                    f,
+                    "Could not parse the response: {}, at `{}`",
+                    message, snippet
+                )
+            }
+            ProtocolError::Invalid {
+                path,
+                expected,
+                snippet,
+                message,
+            } => write!(
+                f,
+                "Invalid {}: expected {}, but got `{}` ({})",
+                path, expected, snippet, message
+            ),
+        }
+    }
+}
+
+impl ProtocolError {
+    /// Diagnoses why the JSON response to the request could not be parsed, given the error
+    /// of the parser.
+    pub fn from_json(request: &Request, response: &str, err: serde_json::Error) -> Self {
+        match serde_json::from_str::<Value>(response) {
+            Ok(value) => Self::diagnose(request, &value, err.to_string()),
+            Err(_) => ProtocolError::Malformed {
+                message: err.to_string(),
+                snippet: snippet_around(response, err.line(), err.column()),
+            },
+        }
+    }
+
+    /// Like [`from_json()`](Self::from_json), for a response in the MessagePack protocol.
+    pub fn from_msgpack(request: &Request, response: &[u8], err: rmp_serde::decode::Error) -> Self {
+        match rmp_serde::from_slice::<Value>(response) {
+            Ok(value) => Self::diagnose(request, &value, err.to_string()),
+            Err(_) => ProtocolError::Malformed {
+                message: err.to_string(),
+                snippet: shorten(&format!("{:02x?}", response)),
+            },
+        }
+    }
+
+    /// Checks a response that could be parsed for what the parser doesn't check, which is
+    /// that a turn has at most [`Hand::MAX_SIZE`] cards to play.
+    pub fn check(request: &Request, value: &Value) -> Result<(), Self> {
+        match request {
+            Request::PlayTurn { .. } => check_num_cards(value),
+            _ => Ok(()),
+        }
+    }
+
+    // Finds the part of the value that isn't what the request asked for
+    fn diagnose(request: &Request, value: &Value, message: String) -> Self {
+        let result = match request {
+            Request::NewGame { .. } => check::<Okay>(value, ROOT, OKAY),
+            Request::PlayFirstTurn { .. } => check_card(value, ROOT),
+            Request::PlayTurn { .. } => check_turn(value),
+            Request::Bye { .. } => Ok(()),
+        };
+        result.err().unwrap_or_else(|| ProtocolError::Invalid {
+            path: String::from(ROOT),
+            expected: "a response to the request",
+            snippet: shorten(&value.to_string()),
+            message,
+        })
+    }
+}
+
+fn check<T: DeserializeOwned>(
+    value: &Value,
+    path: &str,
+    expected: &'static str,
+) -> Result<(), ProtocolError> {
+    match T::deserialize(value) {
+        Ok(_) => Ok(()),
+        Err(err) => Err(invalid(path, expected, value, err.to_string())),
+    }
+}
+
+// Checks a field of the object, which is required unless it's an `Option`
+fn check_field<T: DeserializeOwned>(
+    object: &Map<String, Value>,
+    path: &str,
+    name: &str,
+    expected: &'static str,
+    check_value: impl FnOnce(&Value, &str) -> Result<(), ProtocolError>,
+) -> Result<(), ProtocolError> {
+    let field_path = format!("{}.{}", path, name);
+    match object.get(name) {
+        Some(value) => check_value(value, &field_path),
+        None if T::deserialize(&Value::Null).is_ok() => Ok(()),
+        None => Err(invalid(
+            &field_path,
+            expected,
+            &Value::Object(object.clone()),
+            format!("missing field `{}`", name),
+        )),
+    }
+}
+
+fn check_card(value: &Value, path: &str) -> Result<(), ProtocolError> {
+    if let Some(object) = value.as_object() {
+        check_field::<Suit>(object, path, "suit", SUIT, |value, path| {
+            check::<Suit>(value, path, SUIT)
+        })?;
+        check_field::<Rank>(object, path, "rank", RANK, |value, path| {
+            check::<Rank>(value, path, RANK)
+        })?;
+    }
+    check::<Card>(value, path, CARD)
+}
+
+fn check_card_to_play(value: &Value, path: &str) -> Result<(), ProtocolError> {
+    if let Some(object) = value.as_object() {
+        check_field::<Card>(object, path, "card", CARD, check_card)?;
+        for name in ["i", "j"] {
+            check_field::<i8>(object, path, name, COORDINATE, |value, path| {
+                check::<i8>(value, path, COORDINATE)
+            })?;
+        }
+        check_field::<Option<(i8, i8)>>(
+            object,
+            path,
+            "target_field_for_king_ability",
+            TARGET_FIELD,
+            |value, path| check::<Option<(i8, i8)>>(value, path, TARGET_FIELD),
+        )?;
+    }
+    check::<CardToPlay>(value, path, CARD_TO_PLAY)
+}
+
+fn check_turn(value: &Value) -> Result<(), ProtocolError> {
+    if let Some(items) = value.as_array() {
+        for (idx, item) in items.iter().enumerate() {
+            check_card_to_play(item, &format!("{}[{}]", ROOT, idx))?;
+        }
+    }
+    check::<PlayTurnResponse>(value, ROOT, TURN)?;
+    check_num_cards(value)
+}
+
+fn check_num_cards(value: &Value) -> Result<(), ProtocolError> {
+    match value.as_array() {
+        Some(items) if items.len() > Hand::MAX_SIZE => {
+            let message = format!("{} cards to play", items.len());
+            Err(invalid(ROOT, AT_MOST_FIVE, value, message))
+        }
+        _ => Ok(()),
+    }
+}
+
+fn invalid(path: &str, expected: &'static str, value: &Value, message: String) -> ProtocolError {
+    ProtocolError::Invalid {
+        path: String::from(path),
+        expected,
+        snippet: shorten(&value.to_string()),
+        message,
+    }
+}
+
+fn shorten(text: &str) -> String {
+    if text.chars().count() <= MAX_SNIPPET_LEN {
+        return String::from(text);
+    }
+    let mut shortened: String = text.chars().take(MAX_SNIPPET_LEN - 1).collect();
+    shortened.push('…');
+    shortened
+}
+
+// The part of the text before and after the position of a parser error, which counts lines
+// and columns from 1
+fn snippet_around(text: &str, line: usize, column: usize) -> String {
+    let Some(line) = text.lines().nth(line.saturating_sub(1)) else {
+        return shorten(text);
+    };
+    let chars: Vec<char> = line.chars().collect();
+    let start = column.saturating_sub(MAX_SNIPPET_LEN / 2).min(chars.len());
+    let end = (start + MAX_SNIPPET_LEN).min(chars.len());
+    let mut snippet: String = chars[start..end].iter().collect();
+    if start > 0 {
+        snippet.insert(0, '…');
+    }
+    if end < chars.len() {
+        snippet.push('…');
+    }
+    snippet
+}
+
+#[cfg(test)]
+mod tests {
+    use gomori::{card, Color, Hand, Rules};
+
+    use super::*;
+
+    fn play_turn() -> Request {
+        Request::PlayTurn {
+            cards: Hand::try_from([
+                card!("2♥"),
+                card!("3♥"),
+                card!("4♥"),
+                card!("5♥"),
+                card!("6♥"),
+            ])
+            .unwrap(),
+            fields: Vec::new(),
+            cards_won_by_opponent: Default::default(),
+            draw_pile_size: 0,
+            opponent_draw_pile_size: 0,
+            turn_number: 2,
+        }
+    }
+
+    fn diagnose_json<T: DeserializeOwned + std::fmt::Debug>(
+        request: &Request,
+        response: &str,
+    ) -> ProtocolError {
+        let err = serde_json::from_str::<T>(response).unwrap_err();
+        ProtocolError::from_json(request, response, err)
+    }
+
+    #[test]
+    fn invalid_parts_are_located() {
+        let response = r#"[{"card": {"suit": "♥", "rank": "7"}, "i": 0, "j": 1},
+            {"card": {"suit": "H", "rank": "8"}, "i": 0, "j": 2}]"#;
+        let err = diagnose_json::<PlayTurnResponse>(&play_turn(), response);
+        let ProtocolError::Invalid {
+            path,
+            expected,
+            snippet,
+            message,
+        } = &err
+        else {
+            panic!("Expected an invalid response, got {:?}", err);
+        };
+        assert_eq!(path, "response[1].card.suit");
+        assert_eq!(*expected, SUIT);
+        assert_eq!(snippet, "\"H\"");
+        assert!(message.contains("unknown variant `H`"));
+        assert!(err
+            .to_string()
+            .starts_with("Invalid response[1].card.suit: expected one of"));
+
+        let response = r#"[{"card": {"suit": "♥", "rank": "7"}, "j": 1}]"#;
+        let err = diagnose_json::<PlayTurnResponse>(&play_turn(), response);
+        assert!(matches!(err, ProtocolError::Invalid { ref path, .. } if path == "response[0].i"));
+
+        let response = r#"[{"card": {"suit": "♥", "rank": "7"}, "i": 300, "j": 1}]"#;
+        let err = diagnose_json::<PlayTurnResponse>(&play_turn(), response);
+        assert!(matches!(err, ProtocolError::Invalid { ref path, .. } if path == "response[0].i"));
+
+        let err = diagnose_json::<PlayTurnResponse>(&play_turn(), r#"{"suit": "♥", "rank": "7"}"#);
+        assert!(
+            matches!(err, ProtocolError::Invalid { ref path, expected, .. } if path == "response" && expected == TURN)
+        );
+
+        let new_game = Request::NewGame {
+            color: Color::Red,
+            num_players: 2,
+            seat: 0,
+            suit: None,
+            match_context: None,
+            rules: Rules::STANDARD,
+        };
+        let err = diagnose_json::<Okay>(&new_game, r#""ok""#);
+        assert!(matches!(err, ProtocolError::Invalid { expected, .. } if expected == OKAY));
+    }
+
+    #[test]
+    fn turns_have_at_most_five_cards() {
+        let ctp = serde_json::json!({"card": {"suit": "♥", "rank": "7"}, "i": 0, "j": 1});
+        let response = Value::Array(vec![ctp.clone(); 6]);
+        // The parser accepts any number of cards
+        assert!(serde_json::from_value::<PlayTurnResponse>(response.clone()).is_ok());
+        let err = ProtocolError::check(&play_turn(), &response).unwrap_err();
+        assert!(matches!(
+            err,
+            ProtocolError::Invalid { ref path, expected, ref message, .. }
+                if path == "response" && expected == AT_MOST_FIVE && message == "6 cards to play"
+        ));
+        assert_eq!(
+            ProtocolError::check(&play_turn(), &Value::Array(vec![ctp; 5])),
+            Ok(())
+        );
+        assert_eq!(
+            ProtocolError::check(&play_turn(), &serde_json::json!("skip")),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn malformed_responses_are_shown_where_parsing_failed() {
+        let response = format!(
+            r#"[{{"card": {{"suit": "♥", "rank": "7"}}, "i": 0, "j": 1}}{}"#,
+            " ".repeat(100)
+        );
+        let err = diagnose_json::<PlayTurnResponse>(&play_turn(), &response);
+        let ProtocolError::Malformed { message, snippet } = &err else {
+            panic!("Expected a malformed response, got {:?}", err);
+        };
+        assert!(message.contains("EOF"));
+        assert!(snippet.starts_with('…'));
+        assert!(snippet.chars().count() <= MAX_SNIPPET_LEN + 2);
+
+        let bytes = [0xc1];
+        let err = rmp_serde::from_slice::<Card>(&bytes).unwrap_err();
+        let err = ProtocolError::from_msgpack(&play_turn(), &bytes, err);
+        assert!(matches!(err, ProtocolError::Malformed { ref snippet, .. } if snippet == "[c1]"));
+    }
+}
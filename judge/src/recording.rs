@@ -1,13 +1,34 @@
 use std::fs::File;
-use std::io::{BufWriter, Write};
-use std::path::PathBuf;
+use std::io::{BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 
+use anyhow::Context;
+use gomori::{CardToPlay, DrawPile, Explanation, IllegalMove, Request, Rules};
 use serde::{Deserialize, Serialize};
 
+use crate::game::Deal;
+use crate::matchup::MatchConfig;
+
 pub struct Recorder {
     num: usize,
     directory: PathBuf,
     requests: Vec<RequestToPlayer>,
+    record_setups: bool,
+    // The setup of the current game, if setups are recorded
+    setup: Option<GameSetup>,
+}
+
+/// What is needed besides the requests and responses of a game to recompute it, see
+/// [`Recorder::with_setups()`] and [`verify_recording()`](crate::verify_recording).
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GameSetup {
+    pub rules: Rules,
+    pub first_card_location: (i8, i8),
+    pub max_turns: usize,
+    pub starting_player: usize,
+    /// As in [`Deal::draw_piles`], the last card of each draw pile is drawn first.
+    pub draw_piles: Vec<DrawPile>,
 }
 
 impl Recorder {
@@ -19,23 +40,82 @@ impl Recorder {
             num: 1,
             directory,
             requests: Vec::new(),
+            record_setups: false,
+            setup: None,
         })
     }
 
-    pub fn store_request(&mut self, player: &str, request: String, response: String) {
+    /// Also records the [`GameSetup`] of each game. The recordings are then JSON objects
+    /// with the setup in `setup` and the requests in `requests`, instead of lists of requests.
+    pub fn with_setups(mut self) -> Self {
+        self.record_setups = true;
+        self
+    }
+
+    /// Starts recording a new game. The requests of a game that was not written, because a
+    /// player crashed, are discarded.
+    pub fn start_game(&mut self, config: &MatchConfig, deal: &Deal) {
+        self.requests.clear();
+        self.setup = self.record_setups.then(|| GameSetup {
+            rules: config.rules,
+            first_card_location: config.first_card_location,
+            max_turns: config.max_turns,
+            starting_player: deal.starting_player,
+            draw_piles: deal.draw_piles.clone(),
+        });
+    }
+
+    /// Stores a request and the response to it, which took the player `response_time`.
+    pub fn store_request(
+        &mut self,
+        player: &str,
+        request: String,
+        response: String,
+        response_time: Duration,
+    ) {
         self.requests.push(RequestToPlayer {
             player: String::from(player),
             request,
             response,
+            time_ms: Some(response_time.as_secs_f64() * 1000.0),
+            explanations: None,
+            illegal_move: None,
         });
     }
 
+    /// Attaches the illegal move that ends the game to the most recently stored request.
+    pub fn store_illegal_move(&mut self, err: &IllegalMove) -> anyhow::Result<()> {
+        let req = self
+            .requests
+            .last_mut()
+            .ok_or_else(|| anyhow::anyhow!("No request to attach the illegal move to"))?;
+        req.illegal_move = Some(serde_json::to_string(&RecordedIllegalMove::from(err))?);
+        Ok(())
+    }
+
+    /// Attaches explanations of the cards played to the most recently stored request.
+    pub fn store_explanations(&mut self, explanations: &[Explanation]) -> anyhow::Result<()> {
+        let req = self
+            .requests
+            .last_mut()
+            .ok_or_else(|| anyhow::anyhow!("No request to attach explanations to"))?;
+        req.explanations = Some(serde_json::to_string(explanations)?);
+        Ok(())
+    }
+
     // TODO: Refactor - this is super ugly
     // I don't use serde here but write JSON manually because the request/response
     // are already JSON strings and serde escapes them.
     pub fn write_game_recording(&mut self) -> anyhow::Result<()> {
         let filepath = self.directory.join(format!("game_{:0>6}.json", self.num));
         let mut writer = BufWriter::new(File::create(filepath)?);
+        if let Some(setup) = self.setup.take() {
+            write!(
+                writer,
+                "{{\n\"setup\": {},\n\"requests\": ",
+                serde_json::to_string(&setup)?
+            )?;
+        }
         write!(writer, "[")?;
         let mut first = true;
         for req in std::mem::take(&mut self.requests).into_iter() {
@@ -46,11 +126,24 @@ impl Recorder {
             }
             write!(
                 writer,
-                "\n  {{\n    \"player\": \"{}\",\n    \"request\": {},\n    \"response\": {}\n  }}",
+                "\n  {{\n    \"player\": \"{}\",\n    \"request\": {},\n    \"response\": {}",
                 req.player, req.request, req.response
             )?;
+            if let Some(time_ms) = req.time_ms {
+                write!(writer, ",\n    \"time_ms\": {:.3}", time_ms)?;
+            }
+            if let Some(explanations) = req.explanations {
+                write!(writer, ",\n    \"explanations\": {}", explanations)?;
+            }
+            if let Some(illegal_move) = req.illegal_move {
+                write!(writer, ",\n    \"illegal_move\": {}", illegal_move)?;
+            }
+            write!(writer, "\n  }}")?;
         }
         write!(writer, "\n]")?;
+        if self.record_setups {
+            write!(writer, "\n}}")?;
+        }
         self.num += 1;
         Ok(())
     }
@@ -66,6 +159,102 @@ pub struct RequestToPlayer {
     player: String,
     request: String,
     response: String,
+    /// How long the player took to respond, in milliseconds. Missing in older recordings.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    time_ms: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    explanations: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    illegal_move: Option<String>,
+}
+
+/// The illegal move in response to a recorded request, which ended the game.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecordedIllegalMove {
+    /// Why the move is illegal.
+    pub error: String,
+    /// The card that could not be played, if that's why the move is illegal.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub card_to_play: Option<CardToPlay>,
+    /// The top cards of the board before that card, as drawn by
+    /// [`visualize_top_cards()`](gomori::visualize_top_cards).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub board: Option<String>,
+}
+
+impl From<&IllegalMove> for RecordedIllegalMove {
+    fn from(err: &IllegalMove) -> Self {
+        let mut error = err.to_string();
+        if let Some(source) = std::error::Error::source(err) {
+            error += &format!(": {}", source);
+        }
+        let (card_to_play, board) = match err {
+            IllegalMove::IllegalCardPlayed {
+                card_to_play,
+                board,
+                ..
+            } => (Some(*card_to_play), Some(board.clone())),
+            _ => (None, None),
+        };
+        Self {
+            error,
+            card_to_play,
+            board,
+        }
+    }
+}
+
+/// A request and its response from a file written by the [`Recorder`], see
+/// [`read_recording()`].
+#[derive(Clone, Debug, Deserialize)]
+pub struct RecordedRequest {
+    pub player: String,
+    pub request: Request,
+    /// The response as JSON, since its type depends on the request.
+    pub response: serde_json::Value,
+    #[serde(default)]
+    pub time_ms: Option<f64>,
+    /// The illegal move that the response is, if it ended the game.
+    #[serde(default)]
+    pub illegal_move: Option<RecordedIllegalMove>,
+}
+
+/// A game from a file written by the [`Recorder`], see [`read_full_recording()`].
+#[derive(Clone, Debug, Deserialize)]
+pub struct Recording {
+    /// Only recorded with [`Recorder::with_setups()`].
+    #[serde(default)]
+    pub setup: Option<GameSetup>,
+    pub requests: Vec<RecordedRequest>,
+}
+
+// The formats of recordings without and with the game setup
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum RecordingFormat {
+    Requests(Vec<RecordedRequest>),
+    Full(Recording),
+}
+
+/// Reads a game recorded with `--record-games-to-directory`.
+pub fn read_full_recording(path: &Path) -> anyhow::Result<Recording> {
+    let inner = || -> anyhow::Result<Recording> {
+        let reader = BufReader::new(File::open(path)?);
+        Ok(match serde_json::from_reader(reader)? {
+            RecordingFormat::Requests(requests) => Recording {
+                setup: None,
+                requests,
+            },
+            RecordingFormat::Full(recording) => recording,
+        })
+    };
+    inner().with_context(|| format!("Could not read recording '{}'", path.display()))
+}
+
+/// Reads the requests of a game recorded with `--record-games-to-directory`, in the order
+/// in which they were made.
+pub fn read_recording(path: &Path) -> anyhow::Result<Vec<RecordedRequest>> {
+    Ok(read_full_recording(path)?.requests)
 }
 
 // #[derive(Serialize, Deserialize)]
@@ -0,0 +1,164 @@
+use std::process::{Child, Command, ExitStatus};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+/// Limits on the resources that a bot process may use, set with `setrlimit()` before the
+/// bot starts. Only supported on Unix.
+///
+/// In the config file, these are the optional `max_memory_mb` and `cpu_seconds` fields.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ResourceLimits {
+    /// The maximum size of the bot's virtual memory in megabytes (`RLIMIT_AS`).
+    /// Allocations beyond it fail, which makes most bots exit with an error. Since that can't
+    /// be told apart from other errors, such a crash is not attributed to the limit.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_memory_mb: Option<u64>,
+    /// The CPU time in seconds that the bot may use over all games (`RLIMIT_CPU`),
+    /// after which it is killed. A restart after a crash starts with a fresh budget.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cpu_seconds: Option<u64>,
+}
+
+/// The context of the [`ConnectionClosed`](crate::ConnectionClosed) error of a bot that
+/// exited because of its [`ResourceLimits`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ResourceLimitExceeded {
+    /// The bot was killed for using up its CPU time.
+    CpuTime { seconds: u64 },
+}
+
+impl std::error::Error for ResourceLimitExceeded {}
+
+impl std::fmt::Display for ResourceLimitExceeded {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ResourceLimitExceeded::CpuTime { seconds } => {
+                write!(f, "The player exceeded its CPU time limit of {} s", seconds)
+            }
+        }
+    }
+}
+
+impl ResourceLimits {
+    pub fn is_empty(&self) -> bool {
+        self.max_memory_mb.is_none() && self.cpu_seconds.is_none()
+    }
+
+    /// Makes the command set the limits in the child process, before it runs the bot.
+    #[cfg(unix)]
+    pub fn apply(&self, cmd: &mut Command) -> anyhow::Result<()> {
+        use std::os::unix::process::CommandExt;
+
+        if self.is_empty() {
+            return Ok(());
+        }
+        let limits = *self;
+        // SAFETY: Between fork and exec, only setrlimit() is called, which is async-signal-safe
+        // and doesn't allocate.
+        unsafe {
+            cmd.pre_exec(move || {
+                if let Some(megabytes) = limits.max_memory_mb {
+                    let bytes = megabytes.saturating_mul(1024 * 1024);
+                    check(libc::setrlimit(libc::RLIMIT_AS, &rlimit(bytes, bytes)))?;
+                }
+                if let Some(seconds) = limits.cpu_seconds {
+                    // The soft limit sends SIGXCPU, and the hard limit SIGKILL in case the bot
+                    // handles SIGXCPU
+                    let limit = rlimit(seconds, seconds.saturating_add(1));
+                    check(libc::setrlimit(libc::RLIMIT_CPU, &limit))?;
+                }
+                Ok(())
+            });
+        }
+        Ok(())
+    }
+
+    #[cfg(not(unix))]
+    pub fn apply(&self, _cmd: &mut Command) -> anyhow::Result<()> {
+        anyhow::ensure!(
+            self.is_empty(),
+            "Resource limits for bots are only supported on Unix"
+        );
+        Ok(())
+    }
+
+    /// Which limit the bot exceeded, judging by how it exited and the CPU time it used.
+    ///
+    /// SIGXCPU is only sent for the CPU limit, but SIGKILL could also come from e.g. the OOM
+    /// killer, so it only counts if the bot used up its CPU time.
+    #[cfg(unix)]
+    pub fn exceeded(
+        &self,
+        status: ExitStatus,
+        cpu_time: Duration,
+    ) -> Option<ResourceLimitExceeded> {
+        use std::os::unix::process::ExitStatusExt;
+
+        let seconds = self.cpu_seconds?;
+        let killed_for_cpu_time = match status.signal() {
+            Some(libc::SIGXCPU) => true,
+            Some(libc::SIGKILL) => cpu_time >= Duration::from_secs(seconds),
+            _ => false,
+        };
+        killed_for_cpu_time.then_some(ResourceLimitExceeded::CpuTime { seconds })
+    }
+
+    #[cfg(not(unix))]
+    pub fn exceeded(
+        &self,
+        _status: ExitStatus,
+        _cpu_time: Duration,
+    ) -> Option<ResourceLimitExceeded> {
+        None
+    }
+}
+
+/// Reaps the child if it has exited, and returns its exit status and the CPU time it used.
+///
+/// Afterwards, the [`Child`] must not be waited for or killed anymore, since its process ID
+/// may already belong to another process.
+#[cfg(unix)]
+pub(crate) fn try_wait_with_cpu_time(child: &Child) -> Option<(ExitStatus, Duration)> {
+    use std::os::unix::process::ExitStatusExt;
+
+    let mut status = 0;
+    // SAFETY: An all-zero rusage is valid, and wait4() only writes to the given pointers
+    let mut usage: libc::rusage = unsafe { std::mem::zeroed() };
+    let pid = child.id() as libc::pid_t;
+    // SAFETY: The pointers are valid for the duration of the call
+    let ret = unsafe { libc::wait4(pid, &mut status, libc::WNOHANG, &mut usage) };
+    if ret != pid {
+        return None;
+    }
+    let cpu_time = timeval_to_duration(usage.ru_utime) + timeval_to_duration(usage.ru_stime);
+    Some((ExitStatus::from_raw(status), cpu_time))
+}
+
+#[cfg(not(unix))]
+pub(crate) fn try_wait_with_cpu_time(_child: &Child) -> Option<(ExitStatus, Duration)> {
+    None
+}
+
+#[cfg(unix)]
+fn timeval_to_duration(time: libc::timeval) -> Duration {
+    Duration::from_secs(time.tv_sec as u64) + Duration::from_micros(time.tv_usec as u64)
+}
+
+#[cfg(unix)]
+fn rlimit(soft: u64, hard: u64) -> libc::rlimit {
+    libc::rlimit {
+        rlim_cur: soft as libc::rlim_t,
+        rlim_max: hard as libc::rlim_t,
+    }
+}
+
+// Turns the return value of a libc function into a Result
+#[cfg(unix)]
+fn check(ret: libc::c_int) -> std::io::Result<()> {
+    if ret == 0 {
+        Ok(())
+    } else {
+        Err(std::io::Error::last_os_error())
+    }
+}
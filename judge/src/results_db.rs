@@ -0,0 +1,512 @@
+use std::path::Path;
+
+use anyhow::Context;
+use gomori::{Card, CardToPlay, Rank, Suit};
+use rusqlite::{params, Connection, OpenFlags, OptionalExtension};
+
+use crate::game::{GameEvent, GameResult, TieReason};
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS runs (
+    id INTEGER PRIMARY KEY,
+    seed INTEGER NOT NULL,
+    created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+CREATE TABLE IF NOT EXISTS players (
+    run_id INTEGER NOT NULL REFERENCES runs(id),
+    player_idx INTEGER NOT NULL,
+    name TEXT NOT NULL,
+    PRIMARY KEY (run_id, player_idx)
+);
+CREATE TABLE IF NOT EXISTS games (
+    id INTEGER PRIMARY KEY,
+    run_id INTEGER NOT NULL REFERENCES runs(id),
+    game_idx INTEGER NOT NULL,
+    starting_player INTEGER NOT NULL,
+    -- 'won', 'tie', 'illegal_move', 'protocol_error' or 'crashed'
+    outcome TEXT,
+    -- The winner, or the player who made the illegal move or protocol error, or crashed
+    player_idx INTEGER,
+    -- For ties, 'same_number_of_cards' or 'turn_limit'
+    tie_reason TEXT
+);
+CREATE TABLE IF NOT EXISTS turns (
+    id INTEGER PRIMARY KEY,
+    game_id INTEGER NOT NULL REFERENCES games(id),
+    turn_idx INTEGER NOT NULL,
+    player_idx INTEGER NOT NULL,
+    skipped INTEGER NOT NULL DEFAULT 0,
+    -- How long the player took to answer, NULL if it crashed
+    response_time_ms REAL
+);
+CREATE TABLE IF NOT EXISTS moves (
+    id INTEGER PRIMARY KEY,
+    turn_id INTEGER NOT NULL REFERENCES turns(id),
+    move_idx INTEGER NOT NULL,
+    rank TEXT NOT NULL,
+    suit TEXT NOT NULL,
+    i INTEGER NOT NULL,
+    j INTEGER NOT NULL,
+    target_i INTEGER,
+    target_j INTEGER,
+    num_cards_won INTEGER NOT NULL DEFAULT 0
+);
+";
+
+/// A finished game, as read from a [`ResultsDb`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct RecordedGame {
+    /// `"won"`, `"tie"`, `"illegal_move"`, `"protocol_error"` or `"crashed"`.
+    pub outcome: String,
+    /// The winner, or the player who made the illegal move or protocol error, or crashed.
+    pub player_idx: Option<usize>,
+    /// For ties, `"same_number_of_cards"` or `"turn_limit"`.
+    pub tie_reason: Option<String>,
+    /// Each turn's player and the cards played in it, the first turn included.
+    pub turns: Vec<(usize, Vec<CardToPlay>)>,
+}
+
+/// The outcome of a game as in [`RecordedGame::outcome`], and the player it refers to.
+pub(crate) fn outcome_and_player(result: &GameResult) -> (&'static str, Option<usize>) {
+    match *result {
+        GameResult::WonByPlayer { player_idx } => ("won", Some(player_idx)),
+        GameResult::Tie { .. } => ("tie", None),
+        GameResult::IllegalMoveByPlayer { player_idx, .. } => ("illegal_move", Some(player_idx)),
+        GameResult::ProtocolErrorByPlayer { player_idx, .. } => {
+            ("protocol_error", Some(player_idx))
+        }
+        GameResult::PlayerCrashed { player_idx, .. } => ("crashed", Some(player_idx)),
+    }
+}
+
+/// Why a game ended in a tie, as in [`RecordedGame::tie_reason`].
+pub(crate) fn tie_reason(result: &GameResult) -> Option<&'static str> {
+    match result {
+        GameResult::Tie { reason } => Some(match reason {
+            TieReason::SameNumberOfCards => "same_number_of_cards",
+            TieReason::TurnLimit => "turn_limit",
+        }),
+        _ => None,
+    }
+}
+
+/// Writes the games of the judge into a SQLite database, for analyzing many games.
+///
+/// Every run of the judge adds a row to the `runs` table, with its players in `players`.
+/// Each game is in `games`, each turn in `turns` and each card played in `moves`. Cards are
+/// stored with their rank and suit as in the JSON protocol, e.g. `'7'` and `'♥'`.
+/// The tables are created if the database doesn't have them yet.
+///
+/// A game is written once it has ended. Games that are interrupted by an error are not
+/// written at all.
+pub struct ResultsDb {
+    conn: Connection,
+    run_id: i64,
+    game_idx: usize,
+    game_id: Option<i64>,
+    turn_id: i64,
+    turn_idx: usize,
+    move_idx: usize,
+    move_id: i64,
+    /// The first error while writing. Nothing is written after it.
+    error: Option<anyhow::Error>,
+}
+
+impl ResultsDb {
+    /// Opens the database, or creates it if it doesn't exist, and starts a new run with
+    /// these players.
+    pub fn open(path: &Path, seed: u64, player_names: &[String]) -> anyhow::Result<Self> {
+        let inner = || -> anyhow::Result<ResultsDb> {
+            let conn = Connection::open(path)?;
+            conn.execute_batch(SCHEMA)?;
+            // SQLite has no unsigned integers, so the seed is stored with the same bits
+            conn.execute("INSERT INTO runs (seed) VALUES (?1)", [seed as i64])?;
+            let run_id = conn.last_insert_rowid();
+            for (player_idx, name) in player_names.iter().enumerate() {
+                conn.execute(
+                    "INSERT INTO players (run_id, player_idx, name) VALUES (?1, ?2, ?3)",
+                    params![run_id, player_idx, name],
+                )?;
+            }
+            Ok(ResultsDb {
+                conn,
+                run_id,
+                game_idx: 0,
+                game_id: None,
+                turn_id: 0,
+                turn_idx: 0,
+                move_idx: 0,
+                move_id: 0,
+                error: None,
+            })
+        };
+        inner().with_context(|| format!("Could not open results database '{}'", path.display()))
+    }
+
+    /// Opens an existing database only for reading, e.g. with [`games()`](Self::games).
+    pub fn open_read_only(path: &Path) -> anyhow::Result<Self> {
+        let conn = Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY)
+            .with_context(|| format!("Could not open results database '{}'", path.display()))?;
+        Ok(ResultsDb {
+            conn,
+            run_id: 0,
+            game_idx: 0,
+            game_id: None,
+            turn_id: 0,
+            turn_idx: 0,
+            move_idx: 0,
+            move_id: 0,
+            error: None,
+        })
+    }
+
+    /// Adds an event of the current game. Errors are kept until [`finish()`](Self::finish).
+    pub fn record(&mut self, event: &GameEvent) {
+        if self.error.is_none() {
+            if let Err(err) = self.try_record(event) {
+                self.error = Some(err);
+            }
+        }
+    }
+
+    /// Returns the first error that occurred while writing, if any.
+    pub fn finish(self) -> anyhow::Result<()> {
+        match self.error {
+            Some(err) => Err(err.context("Could not write to results database")),
+            None => Ok(()),
+        }
+    }
+
+    fn try_record(&mut self, event: &GameEvent) -> anyhow::Result<()> {
+        match *event {
+            // The cards are not stored
+            GameEvent::GameStarted { .. } => {}
+            GameEvent::TurnStarted {
+                player_idx, board, ..
+            } => {
+                if board.is_none() {
+                    self.start_game(player_idx)?;
+                }
+                let game_id = self.game_id.context("Turn outside of a game")?;
+                self.conn.execute(
+                    "INSERT INTO turns (game_id, turn_idx, player_idx) VALUES (?1, ?2, ?3)",
+                    params![game_id, self.turn_idx, player_idx],
+                )?;
+                self.turn_id = self.conn.last_insert_rowid();
+                self.turn_idx += 1;
+                self.move_idx = 0;
+            }
+            GameEvent::TurnAnswered { response_time, .. } => {
+                self.conn.execute(
+                    "UPDATE turns SET response_time_ms = ?1 WHERE id = ?2",
+                    params![response_time.as_secs_f64() * 1000.0, self.turn_id],
+                )?;
+            }
+            GameEvent::CardPlayed { card_to_play, .. } => {
+                let CardToPlay {
+                    card: Card { rank, suit },
+                    i,
+                    j,
+                    target_field_for_king_ability: target,
+                } = card_to_play;
+                self.conn.execute(
+                    "INSERT INTO moves (turn_id, move_idx, rank, suit, i, j, target_i, target_j)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+                    params![
+                        self.turn_id,
+                        self.move_idx,
+                        to_text(rank)?,
+                        to_text(suit)?,
+                        i,
+                        j,
+                        target.map(|(i, _)| i),
+                        target.map(|(_, j)| j),
+                    ],
+                )?;
+                self.move_id = self.conn.last_insert_rowid();
+                self.move_idx += 1;
+            }
+            GameEvent::CardsWon { cards, .. } => {
+                self.conn.execute(
+                    "UPDATE moves SET num_cards_won = ?1 WHERE id = ?2",
+                    params![cards.len(), self.move_id],
+                )?;
+            }
+            GameEvent::TurnSkipped { .. } => {
+                self.conn
+                    .execute("UPDATE turns SET skipped = 1 WHERE id = ?1", [self.turn_id])?;
+            }
+            GameEvent::GameEnded { result } => {
+                let game_id = self.game_id.take().context("End outside of a game")?;
+                let (outcome, player_idx) = outcome_and_player(result);
+                self.conn.execute(
+                    "UPDATE games SET outcome = ?1, player_idx = ?2, tie_reason = ?3 WHERE id = ?4",
+                    params![outcome, player_idx, tie_reason(result), game_id],
+                )?;
+                self.conn.execute_batch("COMMIT")?;
+                self.game_idx += 1;
+            }
+        }
+        Ok(())
+    }
+
+    fn start_game(&mut self, starting_player: usize) -> anyhow::Result<()> {
+        // A game that was interrupted by an error is discarded
+        if !self.conn.is_autocommit() {
+            self.conn.execute_batch("ROLLBACK")?;
+        }
+        // Writing the whole game at once is much faster
+        self.conn.execute_batch("BEGIN")?;
+        self.conn.execute(
+            "INSERT INTO games (run_id, game_idx, starting_player) VALUES (?1, ?2, ?3)",
+            params![self.run_id, self.game_idx, starting_player],
+        )?;
+        self.game_id = Some(self.conn.last_insert_rowid());
+        self.turn_idx = 0;
+        Ok(())
+    }
+
+    /// How many games each player has won, over all runs, by player name.
+    pub fn wins_by_player(&self) -> anyhow::Result<Vec<(String, u64)>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT players.name, COUNT(games.id) FROM players
+             LEFT JOIN games ON games.run_id = players.run_id
+                 AND games.player_idx = players.player_idx AND games.outcome = 'won'
+             GROUP BY players.name ORDER BY players.name",
+        )?;
+        let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+        Ok(rows.collect::<Result<_, _>>()?)
+    }
+
+    /// How many cards a player has won on average per card played, over all runs.
+    ///
+    /// Returns `None` if the player hasn't played any card.
+    pub fn cards_won_per_move(&self, player_name: &str) -> anyhow::Result<Option<f64>> {
+        let average = self
+            .conn
+            .query_row(
+                "SELECT AVG(moves.num_cards_won) FROM moves
+                 JOIN turns ON moves.turn_id = turns.id
+                 JOIN games ON turns.game_id = games.id
+                 JOIN players ON players.run_id = games.run_id
+                     AND players.player_idx = turns.player_idx
+                 WHERE players.name = ?1",
+                [player_name],
+                |row| row.get(0),
+            )
+            .optional()?;
+        Ok(average.flatten())
+    }
+
+    /// All finished games of all runs, in the order in which they were played.
+    pub fn games(&self) -> anyhow::Result<Vec<RecordedGame>> {
+        let mut games: Vec<(i64, RecordedGame)> = Vec::new();
+        let mut stmt = self.conn.prepare(
+            "SELECT id, outcome, player_idx, tie_reason FROM games
+             WHERE outcome IS NOT NULL ORDER BY id",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?))
+        })?;
+        for row in rows {
+            let (id, outcome, player_idx, tie_reason) = row?;
+            games.push((
+                id,
+                RecordedGame {
+                    outcome,
+                    player_idx,
+                    tie_reason,
+                    turns: Vec::new(),
+                },
+            ));
+        }
+
+        let mut stmt = self.conn.prepare(
+            "SELECT turns.game_id, turns.id, turns.player_idx,
+                 moves.rank, moves.suit, moves.i, moves.j, moves.target_i, moves.target_j
+             FROM turns LEFT JOIN moves ON moves.turn_id = turns.id
+             ORDER BY turns.game_id, turns.turn_idx, moves.move_idx",
+        )?;
+        let mut rows = stmt.query([])?;
+        let mut game_pos = 0;
+        let mut last_turn_id = None;
+        while let Some(row) = rows.next()? {
+            let game_id: i64 = row.get(0)?;
+            // Skip the turns of unfinished games
+            while game_pos < games.len() && games[game_pos].0 < game_id {
+                game_pos += 1;
+            }
+            let Some((id, game)) = games.get_mut(game_pos) else {
+                break;
+            };
+            if *id != game_id {
+                continue;
+            }
+            let turn_id: i64 = row.get(1)?;
+            if last_turn_id != Some(turn_id) {
+                game.turns.push((row.get(2)?, Vec::new()));
+                last_turn_id = Some(turn_id);
+            }
+            // A skipped turn has no moves
+            let rank: Option<String> = row.get(3)?;
+            let Some(rank) = rank else {
+                continue;
+            };
+            let suit: String = row.get(4)?;
+            let target_i: Option<i8> = row.get(7)?;
+            let target_j: Option<i8> = row.get(8)?;
+            let ctp = CardToPlay {
+                card: Card {
+                    rank: from_text::<Rank>(rank)?,
+                    suit: from_text::<Suit>(suit)?,
+                },
+                i: row.get(5)?,
+                j: row.get(6)?,
+                target_field_for_king_ability: target_i.zip(target_j),
+            };
+            game.turns.last_mut().unwrap().1.push(ctp);
+        }
+        Ok(games.into_iter().map(|(_, game)| game).collect())
+    }
+}
+
+// The text of a rank or suit in the JSON protocol
+fn to_text(value: impl serde::Serialize) -> anyhow::Result<String> {
+    match serde_json::to_value(value)? {
+        serde_json::Value::String(text) => Ok(text),
+        other => anyhow::bail!("Expected a string, not {}", other),
+    }
+}
+
+// The inverse of to_text()
+fn from_text<T: serde::de::DeserializeOwned>(text: String) -> anyhow::Result<T> {
+    Ok(serde_json::from_value(serde_json::Value::String(text))?)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use gomori::{card, ctp, field, Board, CardsSet, Hand};
+
+    use super::*;
+
+    #[test]
+    fn records_games() {
+        let path = std::env::temp_dir().join(format!("judge-results-{}.db", std::process::id()));
+        let names = [String::from("A"), String::from("B")];
+        let mut db = ResultsDb::open(&path, 0, &names).unwrap();
+        let board = Board::new(&[field(0, 0, card!("7♥"))]);
+        for winner in [1, 1, 0] {
+            db.record(&GameEvent::TurnStarted {
+                player_idx: 0,
+                hand: Hand::default(),
+                board: None,
+            });
+            db.record(&GameEvent::CardPlayed {
+                player_idx: 0,
+                card_to_play: ctp(card!("7♥"), 0, 0),
+                board: &board,
+            });
+            db.record(&GameEvent::TurnStarted {
+                player_idx: 1,
+                hand: Hand::default(),
+                board: Some(&board),
+            });
+            db.record(&GameEvent::TurnAnswered {
+                player_idx: 1,
+                response_time: Duration::from_micros(1500),
+            });
+            db.record(&GameEvent::CardPlayed {
+                player_idx: 1,
+                card_to_play: ctp(card!("7♠"), 0, 0),
+                board: &board,
+            });
+            db.record(&GameEvent::CardsWon {
+                player_idx: 1,
+                cards: CardsSet::from_iter([card!("7♥"), card!("8♥")]),
+            });
+            db.record(&GameEvent::GameEnded {
+                result: &GameResult::WonByPlayer { player_idx: winner },
+            });
+        }
+        // An interrupted game is discarded
+        db.record(&GameEvent::TurnStarted {
+            player_idx: 0,
+            hand: Hand::default(),
+            board: None,
+        });
+        assert_eq!(
+            db.wins_by_player().unwrap(),
+            [(String::from("A"), 1), (String::from("B"), 2)]
+        );
+        assert_eq!(db.cards_won_per_move("A").unwrap(), Some(0.0));
+        assert_eq!(db.cards_won_per_move("B").unwrap(), Some(2.0));
+        assert_eq!(db.cards_won_per_move("C").unwrap(), None);
+        let rank: String = db
+            .conn
+            .query_row("SELECT rank FROM moves WHERE turn_id = 1", [], |row| {
+                row.get(0)
+            })
+            .unwrap();
+        assert_eq!(rank, "7");
+        let response_times: Vec<Option<f64>> = db
+            .conn
+            .prepare("SELECT response_time_ms FROM turns WHERE game_id = 1 ORDER BY turn_idx")
+            .unwrap()
+            .query_map([], |row| row.get(0))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(response_times, [None, Some(1.5)]);
+        db.finish().unwrap();
+
+        let games = ResultsDb::open_read_only(&path).unwrap().games().unwrap();
+        assert_eq!(games.len(), 3);
+        assert_eq!(games[2].outcome, "won");
+        assert_eq!(games[2].player_idx, Some(0));
+        assert_eq!(games[2].tie_reason, None);
+        assert_eq!(
+            games[2].turns,
+            [
+                (0, vec![ctp(card!("7♥"), 0, 0)]),
+                (1, vec![ctp(card!("7♠"), 0, 0)])
+            ]
+        );
+
+        // Another run adds to the same database
+        let db = ResultsDb::open(&path, 1, &names).unwrap();
+        assert_eq!(db.run_id, 2);
+        let num_games: u64 = db
+            .conn
+            .query_row("SELECT COUNT(*) FROM games", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(num_games, 3);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn records_tie_reasons() {
+        let path = std::env::temp_dir().join(format!("judge-ties-{}.db", std::process::id()));
+        let names = [String::from("A"), String::from("B")];
+        let mut db = ResultsDb::open(&path, 0, &names).unwrap();
+        db.record(&GameEvent::TurnStarted {
+            player_idx: 0,
+            hand: Hand::default(),
+            board: None,
+        });
+        db.record(&GameEvent::GameEnded {
+            result: &GameResult::Tie {
+                reason: TieReason::TurnLimit,
+            },
+        });
+        db.finish().unwrap();
+
+        let games = ResultsDb::open_read_only(&path).unwrap().games().unwrap();
+        assert_eq!(games.len(), 1);
+        assert_eq!(games[0].outcome, "tie");
+        assert_eq!(games[0].player_idx, None);
+        assert_eq!(games[0].tie_reason.as_deref(), Some("turn_limit"));
+        std::fs::remove_file(&path).unwrap();
+    }
+}
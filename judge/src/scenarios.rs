@@ -0,0 +1,876 @@
+//! Scripted players that misbehave in specific ways, and tests for how the judge handles them.
+//!
+//! The tests serve as a specification of the judge's error handling: which misbehavior is
+//! scored as an illegal move or a crash, and which aborts the match as a communication failure.
+
+use std::collections::VecDeque;
+use std::time::Duration;
+
+use gomori::{Board, CardsSet, Hand, Okay, PlayTurnResponse, Request, Rules, TurnValidator};
+
+use crate::player::{Connection, ConnectionClosed};
+
+/// Plays the first legal card in every step of the turn.
+///
+/// Returns the response, and the cards won with it.
+pub(crate) fn first_legal_turn(cards: Hand, board: Board) -> (PlayTurnResponse, CardsSet) {
+    let mut validator = TurnValidator::new(board.clone(), cards.cards());
+    let mut cards_to_play = Vec::new();
+    while !validator.can_finish() {
+        let ctp = validator
+            .playable_cards()
+            .into_iter()
+            .flat_map(|card| validator.board().legal_plays(card))
+            .next()
+            .expect("No legal play found");
+        validator.play(ctp).unwrap();
+        cards_to_play.push(ctp);
+    }
+    (
+        PlayTurnResponse::play_or_skip(&board, cards.cards(), cards_to_play),
+        validator.cards_won(),
+    )
+}
+
+/// How a [`ScriptedPlayer`] deviates from a well-behaved bot.
+#[derive(Clone, Copy, Debug)]
+pub(crate) enum Misbehavior {
+    /// Plays the first legal cards, see [`first_legal_turn()`].
+    None,
+    /// Writes its response to the first `PlayTurn` request twice.
+    TwoLines,
+    /// Responds to the first `PlayTurn` request with a card instead of a list of cards.
+    WrongResponseType,
+    /// Exits, i.e. closes its stdout, when it receives the first `PlayTurn` request.
+    ClosesStdout,
+    /// Waits this long before every response.
+    Slow(Duration),
+    /// Plays six cards in response to the first `PlayTurn` request, which the parser accepts.
+    SixCards,
+    /// Plays a card far away from the others in response to the first `PlayTurn` request.
+    DistantCard,
+    /// Responds to the first `PlayTurn` request with a card whose suit is spelled out.
+    UnknownSuit,
+    /// Responds to the first `PlayTurn` request with JSON that is cut off.
+    TruncatedJson,
+}
+
+/// A bot with scripted behavior, which emulates the stdout of a bot process: it may write
+/// any number of lines in response to a request, and the judge reads one line per request.
+pub(crate) struct ScriptedPlayer {
+    misbehavior: Misbehavior,
+    // The rules of the current game
+    rules: Rules,
+    played_first_turn: bool,
+    stdout_closed: bool,
+    // Written by the bot, but not read by the judge yet
+    unread_lines: VecDeque<String>,
+}
+
+impl ScriptedPlayer {
+    pub(crate) fn new(misbehavior: Misbehavior) -> Self {
+        Self {
+            misbehavior,
+            rules: Rules::STANDARD,
+            played_first_turn: false,
+            stdout_closed: false,
+            unread_lines: VecDeque::new(),
+        }
+    }
+
+    // Writes the lines that the bot outputs in response to the request.
+    fn respond(&mut self, request: Request) -> anyhow::Result<()> {
+        let response = match request {
+            Request::NewGame { rules, .. } => {
+                self.rules = rules;
+                self.played_first_turn = false;
+                serde_json::to_string(&Okay())?
+            }
+            Request::PlayFirstTurn { cards, .. } => {
+                serde_json::to_string(&cards.iter().next().unwrap())?
+            }
+            Request::PlayTurn { cards, fields, .. } => {
+                let first_turn = !std::mem::replace(&mut self.played_first_turn, true);
+                let (mut response, _) =
+                    first_legal_turn(cards, Board::new_with_rules(&fields, self.rules));
+                match self.misbehavior {
+                    Misbehavior::TwoLines if first_turn => {
+                        self.unread_lines
+                            .push_back(serde_json::to_string(&response)?);
+                    }
+                    Misbehavior::WrongResponseType if first_turn => {
+                        return self.write(serde_json::to_string(&cards.iter().next().unwrap())?);
+                    }
+                    Misbehavior::ClosesStdout if first_turn => {
+                        self.stdout_closed = true;
+                        return Ok(());
+                    }
+                    Misbehavior::SixCards if first_turn => {
+                        let ctp = response.cards_to_play()[0];
+                        response = PlayTurnResponse::play(vec![ctp; 6]);
+                    }
+                    Misbehavior::DistantCard if first_turn => {
+                        let mut ctp = response.cards_to_play()[0];
+                        ctp.i = fields[0].i + 10;
+                        ctp.target_field_for_king_ability = None;
+                        response = PlayTurnResponse::play(vec![ctp]);
+                    }
+                    Misbehavior::UnknownSuit if first_turn => {
+                        let mut json = serde_json::to_value(&response)?;
+                        json[0]["card"]["suit"] = serde_json::json!("Hearts");
+                        return self.write(json.to_string());
+                    }
+                    Misbehavior::TruncatedJson if first_turn => {
+                        let json = serde_json::to_string(&response)?;
+                        return self.write(json[..json.len() - 2].to_string());
+                    }
+                    _ => {}
+                }
+                serde_json::to_string(&response)?
+            }
+            Request::Bye { .. } => return Ok(()),
+        };
+        self.write(response)
+    }
+
+    fn write(&mut self, line: String) -> anyhow::Result<()> {
+        if let Misbehavior::Slow(duration) = self.misbehavior {
+            std::thread::sleep(duration);
+        }
+        self.unread_lines.push_back(line);
+        Ok(())
+    }
+}
+
+impl Connection for ScriptedPlayer {
+    fn exchange(&mut self, request: &str) -> anyhow::Result<String> {
+        if !self.stdout_closed {
+            self.respond(serde_json::from_str(request)?)?;
+        }
+        match self.unread_lines.pop_front() {
+            Some(line) => Ok(line),
+            // Like a process that has exited
+            None if self.stdout_closed => Err(ConnectionClosed.into()),
+            None => Ok(String::new()),
+        }
+    }
+
+    fn restart(&mut self) -> anyhow::Result<()> {
+        *self = Self::new(self.misbehavior);
+        Ok(())
+    }
+}
+
+mod tests {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    use gomori::{IllegalMove, MatchContext};
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    use super::*;
+    use crate::{
+        play_game, play_gauntlet, play_matchup, read_full_recording, read_recording,
+        verify_recording, Deal, GameEvent, GameResult, MatchConfig, MatchScore, Player,
+        PlayerStats, PositionStats, ProtocolError, Recorder, SprtConfig, SprtResult,
+        TrainingDataWriter,
+    };
+
+    fn players(misbehavior: Misbehavior) -> [Player; 2] {
+        [
+            Player::with_connection(
+                String::from("Scripted"),
+                Box::new(ScriptedPlayer::new(misbehavior)),
+            ),
+            Player::with_connection(
+                String::from("Well-behaved"),
+                Box::new(ScriptedPlayer::new(Misbehavior::None)),
+            ),
+        ]
+    }
+
+    fn play_one_game(misbehavior: Misbehavior) -> anyhow::Result<GameResult> {
+        let mut players = players(misbehavior);
+        let mut rng = StdRng::seed_from_u64(0);
+        play_game(
+            &mut players,
+            &mut None,
+            &MatchConfig::default(),
+            &Deal::random(2, None, &mut rng),
+            None,
+            &mut [PlayerStats::default(), PlayerStats::default()],
+            &mut |_| {},
+        )
+    }
+
+    fn play_matchup_with(
+        misbehavior: Misbehavior,
+        config: MatchConfig,
+    ) -> anyhow::Result<MatchScore> {
+        let mut players = players(misbehavior);
+        let mut rng = StdRng::seed_from_u64(0);
+        play_matchup(&mut rng, &mut players, &config, &mut None, &mut |_| {})
+    }
+
+    #[test]
+    fn well_behaved_players_finish_the_match() {
+        let config = MatchConfig {
+            num_games: 10,
+            ..Default::default()
+        };
+        let score = play_matchup_with(Misbehavior::None, config).unwrap();
+        assert_eq!(score.wins[0] + score.wins[1] + score.ties, 10);
+        assert_eq!(score.illegal_moves, [0, 0]);
+        for stats in &score.stats {
+            assert!(stats.turns_played > 0);
+            assert!(stats.cards_played >= stats.turns_played);
+        }
+    }
+
+    #[test]
+    fn warmup_games_are_not_counted() {
+        let config = MatchConfig {
+            num_games: 3,
+            warmup_games: 2,
+            ..Default::default()
+        };
+        let mut players = players(Misbehavior::None);
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut games_started = 0;
+        let score = play_matchup(&mut rng, &mut players, &config, &mut None, &mut |event| {
+            if let GameEvent::GameStarted { .. } = event {
+                games_started += 1;
+            }
+        })
+        .unwrap();
+        assert_eq!(games_started, 3);
+        assert_eq!(score.wins[0] + score.wins[1] + score.ties, 3);
+        for (stats, warmup_stats) in score.stats.iter().zip(&score.warmup_stats) {
+            assert!(stats.turns_played > 0);
+            assert!(warmup_stats.turns_played > 0);
+        }
+    }
+
+    /// Remembers the match contexts that it is sent, including the final one with `Bye`, and
+    /// otherwise behaves well.
+    struct ContextRecorder {
+        player: ScriptedPlayer,
+        contexts: Rc<RefCell<Vec<Option<MatchContext>>>>,
+    }
+
+    impl Connection for ContextRecorder {
+        fn exchange(&mut self, request: &str) -> anyhow::Result<String> {
+            if let Request::NewGame { match_context, .. } = serde_json::from_str(request)? {
+                self.contexts.borrow_mut().push(match_context);
+            }
+            self.player.exchange(request)
+        }
+
+        fn send_bye(&mut self, request: &[u8]) -> anyhow::Result<()> {
+            if let Request::Bye { match_context } = serde_json::from_slice(request)? {
+                self.contexts.borrow_mut().push(match_context);
+            }
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn players_are_told_the_score_before_each_game_and_at_the_end() {
+        let config = MatchConfig {
+            num_games: 3,
+            warmup_games: 1,
+            ..Default::default()
+        };
+        let contexts = Rc::new(RefCell::new(Vec::new()));
+        let [_, well_behaved] = players(Misbehavior::None);
+        let recorder = ContextRecorder {
+            player: ScriptedPlayer::new(Misbehavior::None),
+            contexts: contexts.clone(),
+        };
+        let mut players = [
+            Player::with_connection(String::from("Recorder"), Box::new(recorder)),
+            well_behaved,
+        ];
+        let mut rng = StdRng::seed_from_u64(0);
+        let score = play_matchup(&mut rng, &mut players, &config, &mut None, &mut |_| {}).unwrap();
+
+        let contexts = contexts.borrow();
+        // The warm-up game is not part of the match
+        assert_eq!(contexts.len(), 5);
+        assert_eq!(contexts[0], None);
+        let contexts: Vec<_> = contexts[1..].iter().map(|c| c.unwrap()).collect();
+        assert_eq!(
+            contexts[0],
+            MatchContext {
+                num_games: Some(3),
+                ..Default::default()
+            }
+        );
+        assert_eq!(contexts[2].games_played(), 2);
+        assert_eq!(
+            contexts[3],
+            MatchContext {
+                wins: score.wins[0],
+                losses: score.wins[1],
+                ties: score.ties,
+                num_games: Some(3),
+            }
+        );
+    }
+
+    #[test]
+    fn four_players_finish_the_match() {
+        let config = MatchConfig {
+            num_games: 10,
+            alternate_start: true,
+            ..Default::default()
+        };
+        let mut players: Vec<_> = (1..=4)
+            .map(|idx| {
+                Player::with_connection(
+                    format!("Well-behaved {}", idx),
+                    Box::new(ScriptedPlayer::new(Misbehavior::None)),
+                )
+            })
+            .collect();
+        let mut rng = StdRng::seed_from_u64(0);
+        let score = play_matchup(&mut rng, &mut players, &config, &mut None, &mut |_| {}).unwrap();
+        assert_eq!(score.wins.iter().sum::<usize>() + score.ties, 10);
+        assert_eq!(score.illegal_moves, [0; 4]);
+        for stats in &score.stats {
+            assert!(stats.turns_played > 0);
+        }
+    }
+
+    #[test]
+    fn gauntlet_plays_the_candidate_against_each_opponent() {
+        let config = MatchConfig {
+            num_games: 4,
+            ..Default::default()
+        };
+        // The candidate in every seat, with the opponents in the same order around it
+        for candidate in 0..3 {
+            let mut names = vec!["Well-behaved 1", "Distant card"];
+            let mut misbehaviors = vec![Misbehavior::None, Misbehavior::DistantCard];
+            names.insert(candidate, "Candidate");
+            misbehaviors.insert(candidate, Misbehavior::None);
+            let mut players = misbehaviors
+                .into_iter()
+                .zip(&names)
+                .map(|(misbehavior, name)| {
+                    Player::with_connection(
+                        name.to_string(),
+                        Box::new(ScriptedPlayer::new(misbehavior)),
+                    )
+                })
+                .collect::<Vec<_>>();
+            let mut rng = StdRng::seed_from_u64(0);
+            let scores = play_gauntlet(
+                &mut rng,
+                &mut players,
+                candidate,
+                &config,
+                &mut None,
+                &mut |_| {},
+            )
+            .unwrap();
+            assert_eq!(scores.len(), 2);
+            for score in &scores {
+                assert_eq!(score.wins[0] + score.wins[1] + score.ties, 4);
+                assert_eq!(score.illegal_moves[0], 0);
+            }
+            assert_eq!(scores[0].illegal_moves[1], 0, "candidate {}", candidate);
+            assert_eq!(scores[1].illegal_moves[1], 4, "candidate {}", candidate);
+            let order: Vec<_> = players.iter().map(|player| player.name.as_str()).collect();
+            assert_eq!(order, names);
+        }
+    }
+
+    #[test]
+    fn recorded_games_are_recomputed_by_verification() {
+        let dir = std::env::temp_dir().join(format!("judge-verify-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let mut recorder = Some(Recorder::new(dir.clone()).unwrap().with_setups());
+        let config = MatchConfig {
+            num_games: 3,
+            ..Default::default()
+        };
+        let mut players = players(Misbehavior::None);
+        let mut rng = StdRng::seed_from_u64(0);
+        play_matchup(&mut rng, &mut players, &config, &mut recorder, &mut |_| {}).unwrap();
+        let paths: Vec<_> = (1..=3)
+            .map(|num| dir.join(format!("game_{:0>6}.json", num)))
+            .collect();
+        for path in &paths {
+            let verification = verify_recording(&read_full_recording(path).unwrap()).unwrap();
+            assert!(verification.is_ok(), "{}", verification);
+            assert!(verification.num_turns > 2);
+        }
+        // Other tools still read the requests
+        assert!(!read_recording(&paths[0]).unwrap().is_empty());
+
+        // With the draw piles swapped, the hands differ from the first turn on
+        let mut recording = read_full_recording(&paths[0]).unwrap();
+        recording.setup.as_mut().unwrap().draw_piles.swap(0, 1);
+        let verification = verify_recording(&recording).unwrap();
+        let divergence = verification.divergence.unwrap();
+        assert_eq!(divergence.turn_number, 1);
+        assert!(divergence.description.starts_with("The hand is"));
+
+        // Without the last request, the game doesn't end
+        let mut recording = read_full_recording(&paths[1]).unwrap();
+        recording.requests.pop();
+        let verification = verify_recording(&recording).unwrap();
+        assert_eq!(
+            verification.divergence.unwrap().description,
+            "The recording ends before the recomputed game"
+        );
+
+        recording.setup = None;
+        assert!(verify_recording(&recording).is_err());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn mirrored_games_swap_the_decks() {
+        let config = MatchConfig {
+            num_games: 4,
+            mirror_decks: true,
+            ..Default::default()
+        };
+        let mut players = players(Misbehavior::None);
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut deals = Vec::new();
+        let score = play_matchup(&mut rng, &mut players, &config, &mut None, &mut |event| {
+            if let GameEvent::GameStarted { deal } = event {
+                deals.push(deal.clone());
+            }
+        })
+        .unwrap();
+        assert_eq!(deals.len(), 4);
+        let pairs = score.pairs.unwrap();
+        assert_eq!(
+            pairs.first_player_better + pairs.second_player_better + pairs.even,
+            2
+        );
+        for pair in deals.chunks(2) {
+            assert_eq!(pair[1], pair[0].mirrored());
+            assert_eq!(pair[1].draw_piles[0], pair[0].draw_piles[1]);
+            assert_ne!(pair[1].starting_player, pair[0].starting_player);
+        }
+        assert_ne!(deals[0].draw_piles, deals[2].draw_piles);
+    }
+
+    /// In a game with more than two players, an illegal move loses the game, but nobody wins it.
+    #[test]
+    fn illegal_moves_with_three_players_are_not_wins() {
+        let config = MatchConfig {
+            num_games: 10,
+            ..Default::default()
+        };
+        let [scripted, well_behaved] = players(Misbehavior::DistantCard);
+        let mut players = vec![
+            scripted,
+            well_behaved,
+            Player::with_connection(
+                String::from("Well-behaved 2"),
+                Box::new(ScriptedPlayer::new(Misbehavior::None)),
+            ),
+        ];
+        let mut rng = StdRng::seed_from_u64(0);
+        let score = play_matchup(&mut rng, &mut players, &config, &mut None, &mut |_| {}).unwrap();
+        assert_eq!(score.illegal_moves, [10, 0, 0]);
+        assert_eq!(score.wins.iter().sum::<usize>() + score.ties, 0);
+    }
+
+    /// The second line is taken as the response to the next request, so the player
+    /// repeats a turn with cards that are no longer in its hand.
+    #[test]
+    fn two_lines_are_a_stale_turn() {
+        let result = play_one_game(Misbehavior::TwoLines).unwrap();
+        assert!(matches!(
+            result,
+            GameResult::IllegalMoveByPlayer {
+                player_idx: 0,
+                err: IllegalMove::PlayedCardNotInHand
+            }
+        ));
+    }
+
+    /// A response that can't be parsed is a protocol error, which loses the game, and the
+    /// match continues.
+    #[test]
+    fn wrong_response_type_is_a_protocol_error() {
+        let config = MatchConfig {
+            num_games: 3,
+            ..Default::default()
+        };
+        let score = play_matchup_with(Misbehavior::WrongResponseType, config).unwrap();
+        assert_eq!(score.protocol_errors, [3, 0]);
+        assert_eq!(score.illegal_moves, [0, 0]);
+        assert_eq!(score.wins, [0, 3]);
+
+        let result = play_one_game(Misbehavior::WrongResponseType).unwrap();
+        let GameResult::ProtocolErrorByPlayer { player_idx, err } = result else {
+            panic!("Expected a protocol error");
+        };
+        assert_eq!(player_idx, 0);
+        let ProtocolError::Invalid { path, snippet, .. } = &err else {
+            panic!("Expected an invalid response, got {:?}", err);
+        };
+        assert_eq!(path, "response");
+        assert!(snippet.contains("\"suit\":"));
+    }
+
+    /// Protocol errors point to the part of the response that is wrong.
+    #[test]
+    fn protocol_errors_are_diagnosed() {
+        let result = play_one_game(Misbehavior::UnknownSuit).unwrap();
+        let GameResult::ProtocolErrorByPlayer { err, .. } = result else {
+            panic!("Expected a protocol error");
+        };
+        assert!(matches!(
+            &err,
+            ProtocolError::Invalid { path, snippet, .. }
+                if path == "response[0].card.suit" && snippet == "\"Hearts\""
+        ));
+
+        let result = play_one_game(Misbehavior::TruncatedJson).unwrap();
+        assert!(matches!(
+            result,
+            GameResult::ProtocolErrorByPlayer {
+                player_idx: 0,
+                err: ProtocolError::Malformed { .. }
+            }
+        ));
+    }
+
+    /// A player that exits has crashed, which loses the game.
+    #[test]
+    fn closed_stdout_is_a_crash() {
+        let result = play_one_game(Misbehavior::ClosesStdout).unwrap();
+        let GameResult::PlayerCrashed { player_idx, err } = result else {
+            panic!("Expected a crash");
+        };
+        assert_eq!(player_idx, 0);
+        assert!(format!("{:#}", err).contains("Failed to make a request to 'Scripted'"));
+    }
+
+    /// The match continues after a crash, whether the player is restarted or not.
+    /// A player that is not restarted crashes again in every following game.
+    #[test]
+    fn crashes_are_scored_as_losses() {
+        for max_restarts in [0, 3, 10] {
+            let config = MatchConfig {
+                num_games: 10,
+                max_restarts,
+                ..Default::default()
+            };
+            let MatchScore {
+                wins,
+                illegal_moves,
+                crashes,
+                ties,
+                stats,
+                ..
+            } = play_matchup_with(Misbehavior::ClosesStdout, config).unwrap();
+            assert_eq!(
+                (wins, illegal_moves, crashes, ties),
+                (vec![0, 10], vec![0, 0], vec![10, 0], 0)
+            );
+            // The player crashes in its first turn after the first turn of the game, so only
+            // the first turns, if any, have a response time
+            assert_eq!(
+                stats[0],
+                PlayerStats {
+                    response_times: stats[0].response_times.clone(),
+                    ..Default::default()
+                }
+            );
+            assert!(stats[0].response_times.len() <= 10);
+        }
+    }
+
+    /// There is no timeout, the judge waits for slow players.
+    #[test]
+    fn slow_players_are_waited_for() {
+        let result = play_one_game(Misbehavior::Slow(Duration::from_millis(1))).unwrap();
+        assert!(!matches!(result, GameResult::IllegalMoveByPlayer { .. }));
+    }
+
+    #[test]
+    fn six_cards_are_a_protocol_error() {
+        let result = play_one_game(Misbehavior::SixCards).unwrap();
+        assert!(matches!(
+            result,
+            GameResult::ProtocolErrorByPlayer {
+                player_idx: 0,
+                err: ProtocolError::Invalid { ref path, .. }
+            } if path == "response"
+        ));
+    }
+
+    #[test]
+    fn illegal_cards_are_recorded_with_the_board() {
+        let result = play_one_game(Misbehavior::DistantCard).unwrap();
+        let GameResult::IllegalMoveByPlayer {
+            player_idx: 0,
+            err:
+                err @ IllegalMove::IllegalCardPlayed {
+                    card_idx: 0,
+                    card_to_play,
+                    board,
+                    ..
+                },
+        } = &result
+        else {
+            panic!("Expected an illegal card, got {:?}", result);
+        };
+        let location = format!("at ({}, {})", card_to_play.i, card_to_play.j);
+        assert!(err.to_string().contains(&location), "{}", err);
+        assert!(!board.is_empty());
+
+        let dir = std::env::temp_dir().join(format!("judge-illegal-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let mut recorder = Some(Recorder::new(dir.clone()).unwrap().with_setups());
+        let config = MatchConfig {
+            num_games: 1,
+            ..Default::default()
+        };
+        let mut players = players(Misbehavior::DistantCard);
+        let mut rng = StdRng::seed_from_u64(0);
+        play_matchup(&mut rng, &mut players, &config, &mut recorder, &mut |_| {}).unwrap();
+        let mut recording = read_full_recording(&dir.join("game_000001.json")).unwrap();
+        let illegal_move = recording
+            .requests
+            .last()
+            .unwrap()
+            .illegal_move
+            .clone()
+            .unwrap();
+        assert!(illegal_move.card_to_play.is_some());
+        assert!(illegal_move.board.is_some());
+        assert!(verify_recording(&recording).unwrap().is_ok());
+
+        // If the move is legal in the recomputed game, the games diverge
+        recording.requests.last_mut().unwrap().illegal_move = None;
+        let description = verify_recording(&recording)
+            .unwrap()
+            .divergence
+            .unwrap()
+            .description;
+        assert!(description.starts_with("The recorded move is illegal"));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    /// An illegal move loses the game, and the match continues.
+    #[test]
+    fn illegal_moves_are_scored_as_losses() {
+        let config = MatchConfig {
+            num_games: 10,
+            ..Default::default()
+        };
+        let MatchScore {
+            wins,
+            illegal_moves,
+            crashes,
+            ties,
+            stats,
+            ..
+        } = play_matchup_with(Misbehavior::DistantCard, config).unwrap();
+        assert_eq!(
+            (wins, illegal_moves, crashes, ties),
+            (vec![0, 10], vec![10, 0], vec![0, 0], 0)
+        );
+        // Illegal moves are not counted as turns, but their response times are
+        assert_eq!(
+            stats[0],
+            PlayerStats {
+                response_times: stats[0].response_times.clone(),
+                ..Default::default()
+            }
+        );
+        assert!(stats[0].response_times.len() >= 10);
+    }
+
+    /// A series ends once a player has won the majority of its games.
+    #[test]
+    fn series_end_once_decided() {
+        let config = MatchConfig {
+            num_games: 4,
+            series_length: Some(5),
+            alternate_start: true,
+            ..Default::default()
+        };
+        let score = play_matchup_with(Misbehavior::DistantCard, config).unwrap();
+        assert_eq!(score.wins, [0, 12]);
+        assert_eq!(score.series_wins, [0, 4]);
+        assert_eq!(score.series_ties, 0);
+
+        // Without a majority, the series is a tie
+        let config = MatchConfig {
+            num_games: 3,
+            series_length: Some(2),
+            ..Default::default()
+        };
+        let score = play_matchup_with(Misbehavior::None, config).unwrap();
+        assert_eq!(
+            score.series_wins[0] + score.series_wins[1] + score.series_ties,
+            3
+        );
+        assert_eq!(score.wins[0] + score.wins[1] + score.ties, 6);
+    }
+
+    /// In series, the players take turns starting by the index of the game in the series,
+    /// even if a series ends early.
+    #[test]
+    fn starting_players_alternate_within_series() {
+        let config = MatchConfig {
+            num_games: 3,
+            series_length: Some(3),
+            alternate_start: true,
+            ..Default::default()
+        };
+        let mut players = players(Misbehavior::DistantCard);
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut starting_players = Vec::new();
+        play_matchup(&mut rng, &mut players, &config, &mut None, &mut |event| {
+            if let GameEvent::GameStarted { deal } = event {
+                starting_players.push(deal.starting_player);
+            }
+        })
+        .unwrap();
+        // Every series is decided after two games
+        assert_eq!(starting_players, [0, 1, 1, 0, 0, 1]);
+    }
+
+    /// Every card played after the first one of each game is written as a sample, as the
+    /// games end.
+    #[test]
+    fn games_are_written_as_training_data() {
+        let config = MatchConfig {
+            num_games: 5,
+            ..Default::default()
+        };
+        let dir = std::env::temp_dir().join(format!("judge-selfplay-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let mut writer = TrainingDataWriter::create(&dir).unwrap();
+        let mut players = players(Misbehavior::None);
+        let mut rng = StdRng::seed_from_u64(0);
+        let score = play_matchup(&mut rng, &mut players, &config, &mut None, &mut |event| {
+            writer.record(&event)
+        })
+        .unwrap();
+        let num_samples = writer.num_samples();
+        writer.finish().unwrap();
+        let cards_played: usize = score.stats.iter().map(|stats| stats.cards_played).sum();
+        assert_eq!(num_samples, cards_played);
+        let outcomes = std::fs::read(dir.join("outcomes.npy")).unwrap();
+        let header = String::from_utf8_lossy(&outcomes[10..128]);
+        assert!(header.contains(&format!("'shape': ({},)", num_samples)));
+        assert_eq!(outcomes.len(), 128 + num_samples);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    /// Deterministic players play the same moves whenever they get the same cards, so every
+    /// early position of a game repeats when its deal is played again.
+    #[test]
+    fn replayed_deals_repeat_their_positions() {
+        let mut players = players(Misbehavior::None);
+        let mut rng = StdRng::seed_from_u64(0);
+        let deal = Deal::random(2, Some(0), &mut rng);
+        let mut position_stats = PositionStats::new(2, 6);
+        for _ in 0..2 {
+            play_game(
+                &mut players,
+                &mut None,
+                &MatchConfig::default(),
+                &deal,
+                None,
+                &mut [PlayerStats::default(), PlayerStats::default()],
+                &mut |event| position_stats.record(&event),
+            )
+            .unwrap();
+        }
+        // The second player plays turns 2, 4 and 6, and the first one turns 3 and 5
+        assert_eq!(position_stats.num_positions(0), (4, 2));
+        assert_eq!(position_stats.num_positions(1), (6, 3));
+        let most_common = position_stats.most_common(1, 10);
+        assert_eq!(most_common.len(), 3);
+        assert!(most_common.iter().all(|position| position.count == 2));
+    }
+
+    /// With alternate_start, each player starts every other game, and the wins are broken
+    /// down by who started.
+    #[test]
+    fn starting_players_alternate() {
+        let config = MatchConfig {
+            num_games: 9,
+            alternate_start: true,
+            ..Default::default()
+        };
+        let score = play_matchup_with(Misbehavior::DistantCard, config).unwrap();
+        assert_eq!(score.starts, [5, 4]);
+        assert_eq!(score.wins_as_starting_player, [0, 4]);
+        assert_eq!(score.starting_player_win_rate(), Some(4.0 / 9.0));
+
+        let config = MatchConfig {
+            num_games: 10,
+            alternate_start: true,
+            mirror_decks: true,
+            ..Default::default()
+        };
+        let score = play_matchup_with(Misbehavior::DistantCard, config).unwrap();
+        assert_eq!(score.starts, [5, 5]);
+        assert_eq!(score.wins, [0, 10]);
+        assert_eq!(score.wins_as_starting_player, [0, 5]);
+        assert_eq!(score.starting_player_win_rate(), Some(0.5));
+    }
+
+    /// With stop_on_illegal_move, the match ends without scoring the game with the illegal move.
+    #[test]
+    fn stop_on_illegal_move_ends_the_match() {
+        let config = MatchConfig {
+            num_games: 10,
+            stop_on_illegal_move: true,
+            ..Default::default()
+        };
+        let MatchScore {
+            wins,
+            illegal_moves,
+            crashes,
+            ties,
+            ..
+        } = play_matchup_with(Misbehavior::DistantCard, config).unwrap();
+        assert_eq!(
+            (wins, illegal_moves, crashes, ties),
+            (vec![0, 0], vec![0, 0], vec![0, 0], 0)
+        );
+    }
+
+    /// With SPRT, a player that loses every game is rejected long before the maximum number of games.
+    #[test]
+    fn sprt_stops_early() {
+        let config = MatchConfig {
+            num_games: 1000,
+            sprt: Some(SprtConfig::default()),
+            ..Default::default()
+        };
+        let score = play_matchup_with(Misbehavior::DistantCard, config).unwrap();
+        let sprt = score.sprt.unwrap();
+        assert_eq!(sprt.result(), SprtResult::Reject);
+        assert_eq!(sprt.llr_trajectory.len(), score.wins[1]);
+        assert!(score.wins[1] < 1000);
+
+        // The game that decides the test still counts for its series
+        let config = MatchConfig {
+            num_games: 1000,
+            series_length: Some(1),
+            sprt: Some(SprtConfig::default()),
+            ..Default::default()
+        };
+        let score = play_matchup_with(Misbehavior::DistantCard, config).unwrap();
+        assert_eq!(score.series_wins, [0, score.wins[1]]);
+    }
+}
@@ -0,0 +1,269 @@
+use std::io::{ErrorKind, Write};
+use std::net::{Ipv4Addr, SocketAddr, TcpListener, TcpStream};
+use std::time::Duration;
+
+use anyhow::Context;
+use serde_json::json;
+use tracing::{debug, info};
+
+use crate::game::GameEvent;
+use crate::results_db::{outcome_and_player, tie_reason};
+
+/// Broadcasts the events of the games to spectators that connect to a local TCP port.
+///
+/// Each event is sent as one line of JSON, e.g.
+/// `{"game":0,"event":"cards_won","player_idx":1,"cards":["7♥","7♦"]}`.
+/// The events of cards played also contain the `fields` of the resulting board, in
+/// the format of the bot protocol, so spectators that connect during a game can start
+/// showing it with the next card.
+///
+/// Since any process on the machine can connect, e.g. a bot that is playing, the events
+/// leave out what the players can't see: the draw piles dealt at the start of a game and
+/// the hand of the player to move are replaced by their sizes, unless the spectator was
+/// created [`with_hidden_information()`](Self::with_hidden_information).
+///
+/// Spectators only receive events, they can't send anything. A spectator that doesn't
+/// keep up with reading the events is disconnected, so that it can't slow down the games.
+pub struct Spectator {
+    listener: TcpListener,
+    clients: Vec<TcpStream>,
+    game_idx: usize,
+    show_hidden: bool,
+}
+
+// How long the judge waits for a spectator to accept an event
+const WRITE_TIMEOUT: Duration = Duration::from_secs(1);
+
+impl Spectator {
+    /// Listens on this port of localhost. With port 0, a free port is chosen, see
+    /// [`local_addr()`](Self::local_addr).
+    pub fn bind(port: u16) -> anyhow::Result<Self> {
+        let inner = || -> anyhow::Result<Spectator> {
+            let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, port))?;
+            // New spectators are accepted whenever there is an event to send
+            listener.set_nonblocking(true)?;
+            Ok(Spectator {
+                listener,
+                clients: Vec::new(),
+                game_idx: 0,
+                show_hidden: false,
+            })
+        };
+        let spectator = inner().with_context(|| format!("Could not listen on port {}", port))?;
+        info!(addr = %spectator.local_addr()?, "Waiting for spectators");
+        Ok(spectator)
+    }
+
+    /// Also sends the draw piles and the hands of the players.
+    pub fn with_hidden_information(self) -> Self {
+        Self {
+            show_hidden: true,
+            ..self
+        }
+    }
+
+    pub fn local_addr(&self) -> anyhow::Result<SocketAddr> {
+        Ok(self.listener.local_addr()?)
+    }
+
+    /// Sends the event to all spectators. Never fails, since spectators are optional:
+    /// a spectator that can't be reached is just disconnected.
+    pub fn broadcast(&mut self, event: &GameEvent) {
+        self.accept_new_clients();
+        if !self.clients.is_empty() {
+            let mut line = event_to_json(self.game_idx, event, self.show_hidden).to_string();
+            line.push('\n');
+            self.clients.retain_mut(|client| {
+                let result = client.write_all(line.as_bytes());
+                if let Err(err) = &result {
+                    debug!("Disconnecting spectator: {}", err);
+                }
+                result.is_ok()
+            });
+        }
+        if let GameEvent::GameEnded { .. } = event {
+            self.game_idx += 1;
+        }
+    }
+
+    fn accept_new_clients(&mut self) {
+        loop {
+            match self.listener.accept() {
+                Ok((stream, addr)) => {
+                    // The stream may inherit the listener's non-blocking mode
+                    let setup = stream
+                        .set_nonblocking(false)
+                        .and_then(|()| stream.set_write_timeout(Some(WRITE_TIMEOUT)));
+                    match setup {
+                        Ok(()) => {
+                            debug!(%addr, "New spectator");
+                            self.clients.push(stream);
+                        }
+                        Err(err) => debug!(%addr, "Could not set up spectator: {}", err),
+                    }
+                }
+                Err(err) if err.kind() == ErrorKind::WouldBlock => break,
+                Err(err) => {
+                    debug!("Could not accept spectator: {}", err);
+                    break;
+                }
+            }
+        }
+    }
+}
+
+fn event_to_json(game_idx: usize, event: &GameEvent, show_hidden: bool) -> serde_json::Value {
+    match event {
+        GameEvent::GameStarted { deal } => {
+            let mut json = json!({
+                "game": game_idx,
+                "event": "game_started",
+                "starting_player": deal.starting_player,
+                "draw_pile_sizes": deal.draw_piles.iter().map(|pile| pile.len()).collect::<Vec<_>>(),
+            });
+            if show_hidden {
+                json["draw_piles"] = json!(deal.draw_piles);
+            }
+            json
+        }
+        GameEvent::TurnStarted {
+            player_idx,
+            hand,
+            board,
+        } => {
+            let mut json = json!({
+                "game": game_idx,
+                "event": "turn_started",
+                "player_idx": player_idx,
+                "hand_size": hand.len(),
+                "fields": board.map(|board| board.to_fields_vec()).unwrap_or_default(),
+            });
+            if show_hidden {
+                json["hand"] = json!(hand);
+            }
+            json
+        }
+        GameEvent::TurnAnswered {
+            player_idx,
+            response_time,
+        } => json!({
+            "game": game_idx,
+            "event": "turn_answered",
+            "player_idx": player_idx,
+            "response_time_ms": response_time.as_secs_f64() * 1000.0,
+        }),
+        GameEvent::CardPlayed {
+            player_idx,
+            card_to_play,
+            board,
+        } => json!({
+            "game": game_idx,
+            "event": "card_played",
+            "player_idx": player_idx,
+            "card_to_play": card_to_play,
+            "fields": board.to_fields_vec(),
+        }),
+        GameEvent::CardsWon { player_idx, cards } => json!({
+            "game": game_idx,
+            "event": "cards_won",
+            "player_idx": player_idx,
+            "cards": cards,
+        }),
+        GameEvent::TurnSkipped { player_idx } => json!({
+            "game": game_idx,
+            "event": "turn_skipped",
+            "player_idx": player_idx,
+        }),
+        GameEvent::GameEnded { result } => {
+            let (outcome, player_idx) = outcome_and_player(result);
+            json!({
+                "game": game_idx,
+                "event": "game_ended",
+                "outcome": outcome,
+                "player_idx": player_idx,
+                "tie_reason": tie_reason(result),
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{BufRead, BufReader};
+
+    use gomori::{card, CardsSet, Hand};
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    use super::*;
+    use crate::game::{GameResult, TieReason};
+    use crate::Deal;
+
+    fn game_start_events(spectator: &mut Spectator) -> Vec<serde_json::Value> {
+        let client = TcpStream::connect(spectator.local_addr().unwrap()).unwrap();
+        let mut lines = BufReader::new(client).lines();
+        let mut rng = StdRng::seed_from_u64(0);
+        let deal = Deal::random(2, Some(0), &mut rng);
+        spectator.broadcast(&GameEvent::GameStarted { deal: &deal });
+        let hand = Hand::try_from(&deal.draw_piles[0].cards()[..Hand::MAX_SIZE]).unwrap();
+        spectator.broadcast(&GameEvent::TurnStarted {
+            player_idx: 0,
+            hand,
+            board: None,
+        });
+        (0..2)
+            .map(|_| serde_json::from_str(&lines.next().unwrap().unwrap()).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn hidden_information_is_only_sent_when_enabled() {
+        let events = game_start_events(&mut Spectator::bind(0).unwrap());
+        assert_eq!(events[0]["draw_pile_sizes"], json!([26, 26]));
+        assert!(events[0].get("draw_piles").is_none());
+        assert_eq!(events[1]["hand_size"], 5);
+        assert!(events[1].get("hand").is_none());
+
+        let mut spectator = Spectator::bind(0).unwrap().with_hidden_information();
+        let events = game_start_events(&mut spectator);
+        assert_eq!(events[0]["draw_piles"].as_array().unwrap().len(), 2);
+        assert_eq!(events[1]["hand"].as_array().unwrap().len(), 5);
+    }
+
+    #[test]
+    fn spectators_receive_events() {
+        let mut spectator = Spectator::bind(0).unwrap();
+        let client = TcpStream::connect(spectator.local_addr().unwrap()).unwrap();
+        let mut lines = BufReader::new(client).lines();
+
+        let cards = CardsSet::from_iter([card!("7♥"), card!("7♦")]);
+        spectator.broadcast(&GameEvent::CardsWon {
+            player_idx: 1,
+            cards,
+        });
+        spectator.broadcast(&GameEvent::GameEnded {
+            result: &GameResult::Tie {
+                reason: TieReason::TurnLimit,
+            },
+        });
+        spectator.broadcast(&GameEvent::TurnSkipped { player_idx: 0 });
+
+        let events: Vec<serde_json::Value> = (0..3)
+            .map(|_| serde_json::from_str(&lines.next().unwrap().unwrap()).unwrap())
+            .collect();
+        assert_eq!(
+            events[0],
+            json!({"game": 0, "event": "cards_won", "player_idx": 1, "cards": cards})
+        );
+        assert_eq!(events[1]["outcome"], "tie");
+        assert_eq!(events[1]["tie_reason"], "turn_limit");
+        assert_eq!(events[2]["game"], 1);
+
+        // Spectators that leave are dropped
+        drop(lines);
+        for _ in 0..10 {
+            spectator.broadcast(&GameEvent::TurnSkipped { player_idx: 0 });
+        }
+        assert!(spectator.clients.is_empty());
+    }
+}
@@ -0,0 +1,217 @@
+use std::fmt::Display;
+use std::str::FromStr;
+
+use anyhow::Context;
+
+/// The hypotheses and error rates of a sequential probability ratio test.
+///
+/// Parsed from e.g. `elo0=0,elo1=5,alpha=0.05,beta=0.05`, where omitted values keep
+/// their defaults, which are those of this example.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SprtConfig {
+    /// The Elo difference of the null hypothesis, H0, that the first player is not better.
+    pub elo0: f64,
+    /// The Elo difference of the alternative hypothesis, H1, that the first player is better.
+    pub elo1: f64,
+    /// The probability of accepting H1 although H0 is true.
+    pub alpha: f64,
+    /// The probability of accepting H0 although H1 is true.
+    pub beta: f64,
+}
+
+impl Default for SprtConfig {
+    fn default() -> Self {
+        Self {
+            elo0: 0.0,
+            elo1: 5.0,
+            alpha: 0.05,
+            beta: 0.05,
+        }
+    }
+}
+
+impl FromStr for SprtConfig {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let inner = || -> anyhow::Result<SprtConfig> {
+            let mut config = SprtConfig::default();
+            for pair in s.split(',').filter(|pair| !pair.trim().is_empty()) {
+                let (key, value) = pair.split_once('=').context("Missing '='")?;
+                let value: f64 = value.trim().parse()?;
+                match key.trim() {
+                    "elo0" => config.elo0 = value,
+                    "elo1" => config.elo1 = value,
+                    "alpha" => config.alpha = value,
+                    "beta" => config.beta = value,
+                    other => anyhow::bail!("Unknown key '{}'", other),
+                }
+            }
+            anyhow::ensure!(config.elo0 < config.elo1, "elo0 must be less than elo1");
+            for error_rate in [config.alpha, config.beta] {
+                anyhow::ensure!(
+                    error_rate > 0.0 && error_rate < 0.5,
+                    "alpha and beta must be between 0 and 0.5"
+                );
+            }
+            Ok(config)
+        };
+        inner().with_context(|| format!("Invalid SPRT parameters '{}'", s))
+    }
+}
+
+/// The decision of a sequential probability ratio test.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SprtResult {
+    /// Not enough games yet.
+    Continue,
+    /// H1 is accepted, i.e. the first player is better by at least `elo1`.
+    Accept,
+    /// H0 is accepted, i.e. the first player is not better than by `elo0`.
+    Reject,
+}
+
+impl Display for SprtResult {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SprtResult::Continue => write!(f, "CONTINUE"),
+            SprtResult::Accept => write!(f, "ACCEPT"),
+            SprtResult::Reject => write!(f, "REJECT"),
+        }
+    }
+}
+
+/// A sequential probability ratio test of whether the first of two players is better.
+///
+/// The log-likelihood ratio (LLR) uses the normal approximation for the scores of
+/// wins, ties and losses, as in chess engine testing. Games won or lost through an
+/// illegal move or crash count like other games.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Sprt {
+    pub config: SprtConfig,
+    pub wins: usize,
+    pub ties: usize,
+    pub losses: usize,
+    /// The LLR after each game.
+    pub llr_trajectory: Vec<f64>,
+}
+
+impl Sprt {
+    pub fn new(config: SprtConfig) -> Self {
+        Self {
+            config,
+            wins: 0,
+            ties: 0,
+            losses: 0,
+            llr_trajectory: Vec::new(),
+        }
+    }
+
+    /// Adds a game, given the index of its winner or `None` for a tie, and returns the
+    /// decision so far.
+    pub fn record_game(&mut self, winner: Option<usize>) -> SprtResult {
+        match winner {
+            Some(0) => self.wins += 1,
+            Some(_) => self.losses += 1,
+            None => self.ties += 1,
+        }
+        self.llr_trajectory.push(self.llr());
+        self.result()
+    }
+
+    /// The log-likelihood ratio of H1 over H0.
+    ///
+    /// Each of wins, ties and losses starts out with half a game, so that the variance of
+    /// the score isn't zero when all games so far ended the same way.
+    pub fn llr(&self) -> f64 {
+        if self.wins + self.ties + self.losses == 0 {
+            return 0.0;
+        }
+        let [wins, ties, losses] = [self.wins, self.ties, self.losses].map(|n| n as f64 + 0.5);
+        let n = wins + ties + losses;
+        let (w, d) = (wins / n, ties / n);
+        let score = w + d / 2.0;
+        let variance = w + d / 4.0 - score * score;
+        let variance_of_mean = variance / n;
+        let s0 = expected_score(self.config.elo0);
+        let s1 = expected_score(self.config.elo1);
+        (s1 - s0) * (2.0 * score - s0 - s1) / (2.0 * variance_of_mean)
+    }
+
+    /// The LLR below which H0 is accepted, and the one above which H1 is accepted.
+    pub fn bounds(&self) -> (f64, f64) {
+        let SprtConfig { alpha, beta, .. } = self.config;
+        ((beta / (1.0 - alpha)).ln(), ((1.0 - beta) / alpha).ln())
+    }
+
+    pub fn result(&self) -> SprtResult {
+        let llr = self.llr();
+        let (lower, upper) = self.bounds();
+        if llr >= upper {
+            SprtResult::Accept
+        } else if llr <= lower {
+            SprtResult::Reject
+        } else {
+            SprtResult::Continue
+        }
+    }
+}
+
+// The expected score of a player who is better by this many Elo points
+fn expected_score(elo: f64) -> f64 {
+    1.0 / (1.0 + 10f64.powf(-elo / 400.0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_config() {
+        let config: SprtConfig = "elo0=-2, elo1=3,alpha=0.1".parse().unwrap();
+        assert_eq!(
+            config,
+            SprtConfig {
+                elo0: -2.0,
+                elo1: 3.0,
+                alpha: 0.1,
+                beta: 0.05,
+            }
+        );
+        assert!("elo0=5,elo1=0".parse::<SprtConfig>().is_err());
+        assert!("elo2=5".parse::<SprtConfig>().is_err());
+        assert!("alpha=0.7".parse::<SprtConfig>().is_err());
+    }
+
+    #[test]
+    fn decides_once_the_bounds_are_crossed() {
+        let config = SprtConfig {
+            elo0: 0.0,
+            elo1: 50.0,
+            ..Default::default()
+        };
+        // A player that scores 75% is clearly better
+        let mut sprt = Sprt::new(config);
+        let mut result = SprtResult::Continue;
+        for game_idx in 0..1000 {
+            let winner = [Some(0), Some(0), None, Some(1)][game_idx % 4];
+            result = sprt.record_game(winner);
+            if result != SprtResult::Continue {
+                break;
+            }
+        }
+        assert_eq!(result, SprtResult::Accept);
+        assert!(sprt.llr_trajectory.len() < 100);
+        assert!(sprt.llr() >= sprt.bounds().1);
+
+        // Equally strong players are not better by 50 Elo
+        let mut sprt = Sprt::new(config);
+        for game_idx in 0..1000 {
+            result = sprt.record_game([Some(0), None, Some(1)][game_idx % 3]);
+            if result != SprtResult::Continue {
+                break;
+            }
+        }
+        assert_eq!(result, SprtResult::Reject);
+    }
+}
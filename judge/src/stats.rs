@@ -0,0 +1,268 @@
+use std::fmt::Display;
+use std::time::Duration;
+
+use gomori::{Rank, TurnOutcome, TurnResult};
+use serde::ser::SerializeStruct;
+use serde::{Serialize, Serializer};
+
+/// Statistics about the turns of one player, accumulated over the games of a match.
+///
+/// Only accepted turns are counted, so the illegal move or crash that ends a game is not.
+/// The first turn of a game is not counted either, since it is always a single card.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct PlayerStats {
+    /// Turns in which at least one card was played.
+    pub turns_played: usize,
+    /// Turns in which the player had no card that could be played.
+    pub turns_skipped: usize,
+    /// The total length of all combos, i.e. the number of cards played in all turns.
+    pub cards_played: usize,
+    /// Cards won in all turns. Cards won in the last turn of a game are not counted, as
+    /// for the score.
+    pub cards_won: usize,
+    /// How often a jack was played on another card, flipping its neighbors.
+    pub jack_abilities: usize,
+    /// How often a queen was played on another card, flipping its diagonal neighbors.
+    pub queen_abilities: usize,
+    /// How often a king was played on another card, flipping a card of the player's choice.
+    pub king_abilities: usize,
+    /// How long the player took to answer each request to play a turn. Unlike the other
+    /// fields, this includes the first turn and turns with an illegal move.
+    pub response_times: ResponseTimes,
+}
+
+impl PlayerStats {
+    /// The average number of cards played per turn, not counting skipped turns.
+    pub fn average_combo_length(&self) -> f64 {
+        ratio(self.cards_played, self.turns_played)
+    }
+
+    /// The average number of cards won per turn, not counting skipped turns.
+    pub fn cards_won_per_turn(&self) -> f64 {
+        ratio(self.cards_won, self.turns_played)
+    }
+
+    /// Adds a turn that was accepted by [`gomori::execute_turn()`].
+    pub fn record_turn(&mut self, result: &TurnResult) {
+        match result.outcome {
+            TurnOutcome::Skipped => {
+                self.turns_skipped += 1;
+                return;
+            }
+            TurnOutcome::Normal => self.cards_won += result.cards_won_this_turn.len() as usize,
+            TurnOutcome::GameEnded => {}
+        }
+        self.turns_played += 1;
+        self.cards_played += result.cards_played.len();
+        for played_card in result.cards_played.iter().filter(|c| c.used_ability) {
+            match played_card.card_to_play.card.rank {
+                Rank::Jack => self.jack_abilities += 1,
+                Rank::Queen => self.queen_abilities += 1,
+                Rank::King => self.king_abilities += 1,
+                _ => {}
+            }
+        }
+    }
+}
+
+/// The times that a player took to answer requests, for calculating percentiles.
+///
+/// Serialized as the number of responses and the percentiles in milliseconds, not as the
+/// individual times.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ResponseTimes {
+    times: Vec<Duration>,
+}
+
+impl ResponseTimes {
+    pub fn record(&mut self, time: Duration) {
+        self.times.push(time);
+    }
+
+    pub fn len(&self) -> usize {
+        self.times.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.times.is_empty()
+    }
+
+    /// The shortest time within which at least `percent` percent of the responses arrived,
+    /// or `None` if there were no responses.
+    pub fn percentile(&self, percent: f64) -> Option<Duration> {
+        let mut times = self.times.clone();
+        times.sort_unstable();
+        // The nearest-rank method, so that e.g. the 100th percentile is the maximum
+        let rank = (percent / 100.0 * times.len() as f64).ceil() as usize;
+        times.get(rank.clamp(1, times.len().max(1)) - 1).copied()
+    }
+
+    pub fn max(&self) -> Option<Duration> {
+        self.times.iter().max().copied()
+    }
+}
+
+fn millis(time: Option<Duration>) -> Option<f64> {
+    time.map(|time| time.as_secs_f64() * 1000.0)
+}
+
+impl Serialize for ResponseTimes {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut s = serializer.serialize_struct("ResponseTimes", 5)?;
+        s.serialize_field("count", &self.len())?;
+        s.serialize_field("p50_ms", &millis(self.percentile(50.0)))?;
+        s.serialize_field("p90_ms", &millis(self.percentile(90.0)))?;
+        s.serialize_field("p99_ms", &millis(self.percentile(99.0)))?;
+        s.serialize_field("max_ms", &millis(self.max()))?;
+        s.end()
+    }
+}
+
+impl Display for ResponseTimes {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let ms = |time: Option<Duration>| millis(time).unwrap_or_default();
+        write!(
+            f,
+            "p50 {:.2} ms, p90 {:.2} ms, p99 {:.2} ms, max {:.2} ms",
+            ms(self.percentile(50.0)),
+            ms(self.percentile(90.0)),
+            ms(self.percentile(99.0)),
+            ms(self.max())
+        )
+    }
+}
+
+fn ratio(numerator: usize, denominator: usize) -> f64 {
+    if denominator == 0 {
+        0.0
+    } else {
+        numerator as f64 / denominator as f64
+    }
+}
+
+impl Display for PlayerStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} turns played, {} skipped, {:.2} cards played and {:.2} cards won per turn, \
+             abilities used: {} jacks, {} queens, {} kings",
+            self.turns_played,
+            self.turns_skipped,
+            self.average_combo_length(),
+            self.cards_won_per_turn(),
+            self.jack_abilities,
+            self.queen_abilities,
+            self.king_abilities
+        )?;
+        if !self.response_times.is_empty() {
+            write!(f, ", response times: {}", self.response_times)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use gomori::{
+        card, execute_turn, Board, CardToPlay, CardsSet, DrawPile, Field, Hand, PlayTurnResponse,
+        PlayerState, Rules,
+    };
+
+    use super::*;
+
+    #[test]
+    fn abilities_are_counted_for_combos_only() {
+        let fields = [Field {
+            i: 0,
+            j: 0,
+            top_card: Some(card!("7♥")),
+            hidden_cards: BTreeSet::new(),
+        }];
+        let board = Board::new(&fields);
+        let action = PlayTurnResponse::play(vec![
+            CardToPlay {
+                card: card!("J♥"),
+                i: 0,
+                j: 0,
+                target_field_for_king_ability: None,
+            },
+            CardToPlay {
+                card: card!("Q♠"),
+                i: 0,
+                j: 1,
+                target_field_for_king_ability: None,
+            },
+        ]);
+        let state = PlayerState {
+            draw_pile: DrawPile::default(),
+            hand: Hand::try_from(
+                [
+                    card!("J♥"),
+                    card!("Q♠"),
+                    card!("2♣"),
+                    card!("3♣"),
+                    card!("4♣"),
+                ]
+                .as_slice(),
+            )
+            .unwrap(),
+            cards_won: CardsSet::new(),
+        };
+        let mut stats = PlayerStats::default();
+        let result = execute_turn(&state, &board, action.clone()).unwrap();
+        stats.record_turn(&result);
+        stats.record_turn(&TurnResult {
+            outcome: TurnOutcome::Skipped,
+            cards_played: Vec::new(),
+            ..result
+        });
+        assert_eq!(
+            stats,
+            PlayerStats {
+                turns_played: 1,
+                turns_skipped: 1,
+                cards_played: 2,
+                jack_abilities: 1,
+                ..Default::default()
+            }
+        );
+        assert_eq!(stats.average_combo_length(), 2.0);
+
+        // Without the jack's ability, playing it on another card is just a combo
+        let variant = Board::new_with_rules(
+            &fields,
+            Rules {
+                jack_ability: false,
+                ..Rules::STANDARD
+            },
+        );
+        stats.record_turn(&execute_turn(&state, &variant, action).unwrap());
+        assert_eq!(stats.turns_played, 2);
+        assert_eq!(stats.jack_abilities, 1);
+    }
+
+    #[test]
+    fn response_time_percentiles() {
+        let mut times = ResponseTimes::default();
+        assert_eq!(times.percentile(50.0), None);
+        for ms in (1..=100).rev() {
+            times.record(Duration::from_millis(ms));
+        }
+        assert_eq!(times.percentile(50.0), Some(Duration::from_millis(50)));
+        assert_eq!(times.percentile(99.0), Some(Duration::from_millis(99)));
+        assert_eq!(times.percentile(0.0), Some(Duration::from_millis(1)));
+        assert_eq!(times.max(), Some(Duration::from_millis(100)));
+        assert_eq!(
+            serde_json::to_value(&times).unwrap(),
+            serde_json::json!({
+                "count": 100,
+                "p50_ms": 50.0,
+                "p90_ms": 90.0,
+                "p99_ms": 99.0,
+                "max_ms": 100.0,
+            })
+        );
+    }
+}
@@ -0,0 +1,322 @@
+use std::fs::File;
+use std::io::{BufWriter, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+use anyhow::Context;
+use gomori::{
+    encode_board, encode_card_to_play, Board, Field, BOARD_SIZE, ENCODED_BOARD_LEN, NUM_CHANNELS,
+};
+
+use crate::game::GameEvent;
+use crate::results_db::{outcome_and_player, tie_reason, RecordedGame};
+
+/// A position from a game, the card that was played in it, and how the game ended for the
+/// player who played it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TrainingSample {
+    /// See [`gomori::encode_board()`].
+    pub board: [f32; ENCODED_BOARD_LEN],
+    /// See [`gomori::encode_card_to_play()`].
+    pub card_to_play: [i8; 5],
+    /// 1 if the player won the game, 0 for a tie and -1 if another player won.
+    pub outcome: i8,
+}
+
+/// Turns every card played in the games into a sample, except for the first card of each
+/// game, which is played without a board.
+///
+/// Games that ended with an illegal move or crash are skipped, since their outcome says
+/// nothing about the cards played. The games must have been played with the standard rules.
+pub fn training_samples(games: &[RecordedGame]) -> anyhow::Result<Vec<TrainingSample>> {
+    let mut samples = Vec::new();
+    for (game_idx, game) in games.iter().enumerate() {
+        if game.outcome != "won" && game.outcome != "tie" {
+            continue;
+        }
+        let mut inner = || -> anyhow::Result<()> {
+            let mut board: Option<Board> = None;
+            for &(player_idx, ref cards_to_play) in &game.turns {
+                let outcome = match game.player_idx {
+                    Some(winner) if winner == player_idx => 1,
+                    Some(_) => -1,
+                    None => 0,
+                };
+                for &ctp in cards_to_play {
+                    board = Some(match board {
+                        Some(board) => {
+                            samples.push(TrainingSample {
+                                board: encode_board(&board),
+                                card_to_play: encode_card_to_play(&board, ctp),
+                                outcome,
+                            });
+                            board.play_card(ctp)?
+                        }
+                        None => Board::new(&[Field {
+                            i: ctp.i,
+                            j: ctp.j,
+                            top_card: Some(ctp.card),
+                            hidden_cards: Default::default(),
+                        }]),
+                    });
+                }
+            }
+            Ok(())
+        };
+        inner().with_context(|| format!("Could not replay game {}", game_idx))?;
+    }
+    Ok(samples)
+}
+
+/// Writes the samples as `boards.npy`, `cards_to_play.npy` and `outcomes.npy` into the
+/// directory, which can be loaded with NumPy's `np.load()`.
+///
+/// Their shapes are `(n, NUM_CHANNELS, BOARD_SIZE, BOARD_SIZE)` with `float32` values,
+/// `(n, 5)` with `int8` values and `(n,)` with `int8` values, respectively.
+pub fn write_npy_files(samples: &[TrainingSample], directory: &Path) -> anyhow::Result<()> {
+    let mut writer = TrainingDataWriter::create(directory)?;
+    for sample in samples {
+        writer.write_sample(sample)?;
+    }
+    writer.finish()
+}
+
+/// Writes samples into the same files as [`write_npy_files()`] as they come, e.g. those of
+/// each game of a long self-play run, without keeping them in memory.
+///
+/// The games can be passed to [`record()`](Self::record) event by event, like to a
+/// [`ResultsDb`](crate::ResultsDb), and their samples are written once they have ended.
+/// The files are only complete after [`finish()`](Self::finish).
+pub struct TrainingDataWriter {
+    boards: NpyWriter,
+    cards_to_play: NpyWriter,
+    outcomes: NpyWriter,
+    // The current game, if one has started
+    game: Option<RecordedGame>,
+    /// The first error while recording. Nothing is written after it.
+    error: Option<anyhow::Error>,
+}
+
+impl TrainingDataWriter {
+    /// Creates the files in the directory, which must exist.
+    pub fn create(directory: &Path) -> anyhow::Result<Self> {
+        let size = BOARD_SIZE as usize;
+        Ok(Self {
+            boards: NpyWriter::create(
+                &directory.join("boards.npy"),
+                "<f4",
+                &[NUM_CHANNELS, size, size],
+            )?,
+            cards_to_play: NpyWriter::create(&directory.join("cards_to_play.npy"), "|i1", &[5])?,
+            outcomes: NpyWriter::create(&directory.join("outcomes.npy"), "|i1", &[])?,
+            game: None,
+            error: None,
+        })
+    }
+
+    /// The number of samples written so far.
+    pub fn num_samples(&self) -> usize {
+        self.outcomes.len
+    }
+
+    pub fn write_sample(&mut self, sample: &TrainingSample) -> anyhow::Result<()> {
+        self.boards
+            .write_row(sample.board.iter().flat_map(|value| value.to_le_bytes()))?;
+        self.cards_to_play
+            .write_row(sample.card_to_play.iter().map(|&value| value as u8))?;
+        self.outcomes.write_row([sample.outcome as u8].into_iter())
+    }
+
+    /// Adds an event of the current game, and writes its samples, see
+    /// [`training_samples()`], once it has ended. Errors are kept until
+    /// [`finish()`](Self::finish).
+    pub fn record(&mut self, event: &GameEvent) {
+        if self.error.is_none() {
+            if let Err(err) = self.try_record(event) {
+                self.error = Some(err);
+            }
+        }
+    }
+
+    fn try_record(&mut self, event: &GameEvent) -> anyhow::Result<()> {
+        match *event {
+            GameEvent::TurnStarted {
+                player_idx, board, ..
+            } => {
+                // A game that was interrupted by an error is discarded
+                if board.is_none() {
+                    self.game = Some(RecordedGame {
+                        outcome: String::new(),
+                        player_idx: None,
+                        tie_reason: None,
+                        turns: Vec::new(),
+                    });
+                }
+                let game = self.game.as_mut().context("Turn outside of a game")?;
+                game.turns.push((player_idx, Vec::new()));
+            }
+            GameEvent::CardPlayed { card_to_play, .. } => {
+                let game = self.game.as_mut().context("Card outside of a game")?;
+                let (_, cards_to_play) = game.turns.last_mut().context("Card outside of a turn")?;
+                cards_to_play.push(card_to_play);
+            }
+            GameEvent::GameEnded { result } => {
+                let mut game = self.game.take().context("End outside of a game")?;
+                let (outcome, player_idx) = outcome_and_player(result);
+                game.outcome = String::from(outcome);
+                game.player_idx = player_idx;
+                game.tie_reason = tie_reason(result).map(String::from);
+                for sample in training_samples(&[game])? {
+                    self.write_sample(&sample)?;
+                }
+            }
+            GameEvent::GameStarted { .. }
+            | GameEvent::TurnAnswered { .. }
+            | GameEvent::CardsWon { .. }
+            | GameEvent::TurnSkipped { .. } => {}
+        }
+        Ok(())
+    }
+
+    /// Completes the files, and returns the first error that occurred while recording, if
+    /// any.
+    pub fn finish(self) -> anyhow::Result<()> {
+        if let Some(err) = self.error {
+            return Err(err.context("Could not write training data"));
+        }
+        self.boards.finish()?;
+        self.cards_to_play.finish()?;
+        self.outcomes.finish()
+    }
+}
+
+// The magic string, version, header length and header of an NPY file take this many bytes.
+// The data must start at a multiple of 64 bytes, and this leaves room for the header to be
+// rewritten with any number of rows.
+const NPY_HEADER_LEN: usize = 128;
+
+// An array in the NPY format, version 1.0, which is written row by row. The number of rows
+// in the header is only updated by `finish()`.
+struct NpyWriter {
+    path: PathBuf,
+    writer: BufWriter<File>,
+    dtype: &'static str,
+    row_shape: Vec<usize>,
+    len: usize,
+}
+
+impl NpyWriter {
+    fn create(path: &Path, dtype: &'static str, row_shape: &[usize]) -> anyhow::Result<Self> {
+        let mut npy = Self {
+            path: path.to_path_buf(),
+            writer: BufWriter::new(
+                File::create(path)
+                    .with_context(|| format!("Could not create '{}'", path.display()))?,
+            ),
+            dtype,
+            row_shape: row_shape.to_vec(),
+            len: 0,
+        };
+        npy.write_header()?;
+        Ok(npy)
+    }
+
+    fn write_row(&mut self, data: impl Iterator<Item = u8>) -> anyhow::Result<()> {
+        let inner = || -> anyhow::Result<()> {
+            for byte in data {
+                self.writer.write_all(&[byte])?;
+            }
+            Ok(())
+        };
+        inner().with_context(|| format!("Could not write '{}'", self.path.display()))?;
+        self.len += 1;
+        Ok(())
+    }
+
+    fn finish(mut self) -> anyhow::Result<()> {
+        self.writer
+            .seek(SeekFrom::Start(0))
+            .with_context(|| format!("Could not write '{}'", self.path.display()))?;
+        self.write_header()?;
+        self.writer
+            .flush()
+            .with_context(|| format!("Could not write '{}'", self.path.display()))
+    }
+
+    fn write_header(&mut self) -> anyhow::Result<()> {
+        let shape: Vec<String> = std::iter::once(self.len)
+            .chain(self.row_shape.iter().copied())
+            .map(|dim| format!("{},", dim))
+            .collect();
+        let mut header = format!(
+            "{{'descr': '{}', 'fortran_order': False, 'shape': ({}), }}",
+            self.dtype,
+            shape.join(" ")
+        );
+        // The header ends with a newline
+        let padding = NPY_HEADER_LEN - 10 - header.len() - 1;
+        header.extend(std::iter::repeat_n(' ', padding));
+        header.push('\n');
+        let mut inner = || -> anyhow::Result<()> {
+            self.writer.write_all(b"\x93NUMPY\x01\x00")?;
+            self.writer
+                .write_all(&(header.len() as u16).to_le_bytes())?;
+            self.writer.write_all(header.as_bytes())?;
+            Ok(())
+        };
+        inner().with_context(|| format!("Could not write '{}'", self.path.display()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use gomori::{card, ctp};
+
+    use super::*;
+
+    #[test]
+    fn samples_from_recorded_games() {
+        let game = |outcome: &str, player_idx| RecordedGame {
+            outcome: String::from(outcome),
+            player_idx,
+            tie_reason: None,
+            turns: vec![
+                (1, vec![ctp(card!("7♥"), 0, 0)]),
+                (0, vec![ctp(card!("7♠"), 0, 0), ctp(card!("8♠"), 0, 1)]),
+                (1, vec![]),
+            ],
+        };
+        let games = [
+            game("won", Some(1)),
+            game("crashed", Some(0)),
+            game("tie", None),
+        ];
+        let samples = training_samples(&games).unwrap();
+        let outcomes: Vec<i8> = samples.iter().map(|sample| sample.outcome).collect();
+        assert_eq!(outcomes, [-1, -1, 0, 0]);
+        assert_eq!(samples[0].card_to_play, [5 * 4 + 2, 0, 0, -128, -128]);
+        assert_eq!(samples[1].card_to_play, [6 * 4 + 2, 0, 1, -128, -128]);
+
+        let dir = std::env::temp_dir().join(format!("judge-training-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        write_npy_files(&samples, &dir).unwrap();
+        // The data starts after the header, at a multiple of 64 bytes
+        let read_npy = |name: &str| {
+            let bytes = std::fs::read(dir.join(name)).unwrap();
+            assert_eq!(&bytes[..8], b"\x93NUMPY\x01\x00");
+            let data_start = 10 + u16::from_le_bytes([bytes[8], bytes[9]]) as usize;
+            assert_eq!(data_start % 64, 0);
+            let header = String::from_utf8(bytes[10..data_start].to_vec()).unwrap();
+            (header, bytes[data_start..].to_vec())
+        };
+        let (header, data) = read_npy("outcomes.npy");
+        assert_eq!(
+            header.trim_end(),
+            "{'descr': '|i1', 'fortran_order': False, 'shape': (4,), }"
+        );
+        assert_eq!(data, [255, 255, 0, 0]);
+        let (header, data) = read_npy("boards.npy");
+        assert!(header.contains("'shape': (4, 19, 4, 4,)"));
+        assert_eq!(data.len(), 4 * 4 * ENCODED_BOARD_LEN);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
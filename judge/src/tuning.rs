@@ -0,0 +1,374 @@
+use std::fmt::Display;
+use std::str::FromStr;
+
+use anyhow::Context;
+use rand::Rng;
+
+use crate::MatchScore;
+
+/// A parameter of a bot, and the values it is tuned over.
+///
+/// Parsed from `name=min:max:step`, e.g. `depth=1:4:1`. The values are the multiples of
+/// `step` above `min`, up to `max`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ParamSpec {
+    pub name: String,
+    pub min: f64,
+    pub max: f64,
+    pub step: f64,
+}
+
+impl FromStr for ParamSpec {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let inner = || -> anyhow::Result<ParamSpec> {
+            let (name, range) = s.split_once('=').context("Missing '='")?;
+            let bounds: Vec<f64> = range
+                .split(':')
+                .map(|bound| bound.trim().parse())
+                .collect::<Result<_, _>>()?;
+            let [min, max, step] = bounds[..] else {
+                anyhow::bail!("Expected three numbers separated by ':'");
+            };
+            anyhow::ensure!(!name.is_empty(), "The name is empty");
+            anyhow::ensure!(step > 0.0, "The step must be positive");
+            anyhow::ensure!(min <= max, "The minimum is larger than the maximum");
+            Ok(ParamSpec {
+                name: name.to_string(),
+                min,
+                max,
+                step,
+            })
+        };
+        inner().with_context(|| format!("Invalid parameter '{}', expected name=min:max:step", s))
+    }
+}
+
+impl ParamSpec {
+    /// All values on the grid, from `min` to `max`.
+    pub fn values(&self) -> Vec<f64> {
+        let num_steps = ((self.max - self.min) / self.step + 1e-9).floor() as usize;
+        (0..=num_steps)
+            .map(|idx| self.min + idx as f64 * self.step)
+            .collect()
+    }
+
+    /// The grid value closest to `value`.
+    pub fn snap(&self, value: f64) -> f64 {
+        let values = self.values();
+        let idx = ((value - self.min) / self.step).round().max(0.0) as usize;
+        values[idx.min(values.len() - 1)]
+    }
+
+    /// The grid value in the middle of the range, where tuning starts.
+    pub fn midpoint(&self) -> f64 {
+        self.snap((self.min + self.max) / 2.0)
+    }
+
+    /// Formats a value for the command line. Integers are written without a decimal point
+    /// if the whole range consists of integers.
+    pub fn format(&self, value: f64) -> String {
+        let integral = [self.min, self.step].iter().all(|x| x.fract() == 0.0);
+        if integral {
+            format!("{}", value.round() as i64)
+        } else {
+            // Avoid printing rounding errors like 0.30000000000000004
+            let decimals = format!("{}", self.step)
+                .split_once('.')
+                .map_or(0, |(_, fract)| fract.len());
+            format!("{:.*}", decimals, value)
+        }
+    }
+}
+
+/// The command to start a bot, with `{name}` placeholders for its parameters,
+/// e.g. `target/release/my_bot --depth {depth}`.
+///
+/// The arguments are separated by whitespace, quoting is not supported.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CommandTemplate {
+    args: Vec<String>,
+}
+
+impl CommandTemplate {
+    /// Fails if the template is empty or doesn't use all parameters.
+    pub fn new(template: &str, params: &[ParamSpec]) -> anyhow::Result<Self> {
+        let args: Vec<String> = template.split_whitespace().map(String::from).collect();
+        anyhow::ensure!(!args.is_empty(), "The command template is empty");
+        for param in params {
+            let placeholder = format!("{{{}}}", param.name);
+            anyhow::ensure!(
+                args.iter().any(|arg| arg.contains(&placeholder)),
+                "The command template does not contain '{}'",
+                placeholder
+            );
+        }
+        Ok(Self { args })
+    }
+
+    /// The command with the placeholders replaced by the values, in the order of `params`.
+    pub fn instantiate(&self, params: &[ParamSpec], values: &[f64]) -> Vec<String> {
+        self.args
+            .iter()
+            .map(|arg| {
+                params
+                    .iter()
+                    .zip(values)
+                    .fold(arg.clone(), |arg, (param, &value)| {
+                        arg.replace(&format!("{{{}}}", param.name), &param.format(value))
+                    })
+            })
+            .collect()
+    }
+}
+
+/// Describes parameter values like `depth=2 aggression=0.5`.
+pub fn describe_values(params: &[ParamSpec], values: &[f64]) -> String {
+    params
+        .iter()
+        .zip(values)
+        .map(|(param, &value)| format!("{}={}", param.name, param.format(value)))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// All combinations of the values of the parameters.
+pub fn grid(params: &[ParamSpec]) -> Vec<Vec<f64>> {
+    params.iter().fold(vec![vec![]], |combinations, param| {
+        combinations
+            .iter()
+            .flat_map(|combination| {
+                param.values().into_iter().map(move |value| {
+                    let mut combination = combination.clone();
+                    combination.push(value);
+                    combination
+                })
+            })
+            .collect()
+    })
+}
+
+/// The score of the first player of a two-player match, with a 95% confidence interval.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ScoreEstimate {
+    /// The fraction of points won, counting a tie as half a point.
+    pub score: f64,
+    /// Half the width of the 95% confidence interval of the score.
+    pub margin: f64,
+    pub games: usize,
+}
+
+impl ScoreEstimate {
+    /// Games lost through an illegal move or crash count as losses, as in [`MatchScore::wins`].
+    pub fn from_match_score(score: &MatchScore) -> Self {
+        let games = score.wins.iter().sum::<usize>() + score.ties;
+        if games == 0 {
+            return Self {
+                score: 0.5,
+                margin: 0.5,
+                games,
+            };
+        }
+        let n = games as f64;
+        let p = (score.wins[0] as f64 + 0.5 * score.ties as f64) / n;
+        // The sample variance of the points per game, which are 0, 0.5 or 1
+        let sum_of_squares = score.wins[0] as f64 + 0.25 * score.ties as f64;
+        let variance = (sum_of_squares / n - p * p).max(0.0);
+        Self {
+            score: p,
+            margin: 1.96 * (variance / n).sqrt(),
+            games,
+        }
+    }
+
+    /// The rating difference that corresponds to the score, in Elo points.
+    pub fn elo_difference(&self) -> f64 {
+        let p = self.score.clamp(1e-3, 1.0 - 1e-3);
+        -400.0 * (1.0 / p - 1.0).log10()
+    }
+}
+
+impl Display for ScoreEstimate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{:.1}% ± {:.1}% ({:+.0} Elo) over {} games",
+            self.score * 100.0,
+            self.margin * 100.0,
+            // Adding zero turns -0 into 0
+            self.elo_difference().round() + 0.0,
+            self.games
+        )
+    }
+}
+
+/// Simultaneous perturbation stochastic approximation, which tunes all parameters at once.
+///
+/// In every iteration, all parameters are shifted randomly up or down by the same amount,
+/// and the two resulting bots play a match against each other. The parameters then move
+/// towards the winner. The parameters are handled in units of their range, so that
+/// `a` and `c` apply to all of them alike.
+#[derive(Clone, Debug)]
+pub struct Spsa {
+    params: Vec<ParamSpec>,
+    // The current parameters, scaled to 0..1
+    theta: Vec<f64>,
+    /// The step size at the start, as a fraction of each range.
+    pub a: f64,
+    /// The size of the perturbations at the start, as a fraction of each range.
+    pub c: f64,
+    iteration: usize,
+}
+
+impl Spsa {
+    /// Starts at the midpoints of the parameters.
+    pub fn new(params: Vec<ParamSpec>, a: f64, c: f64) -> Self {
+        let theta = params
+            .iter()
+            .map(|param| normalize(param, param.midpoint()))
+            .collect();
+        Self {
+            params,
+            theta,
+            a,
+            c,
+            iteration: 0,
+        }
+    }
+
+    /// The current parameters, snapped to the grid.
+    pub fn values(&self) -> Vec<f64> {
+        self.denormalize(&self.theta)
+    }
+
+    /// The two parameter sets to compare in the next iteration, and the perturbation
+    /// to pass to [`update()`](Spsa::update).
+    pub fn perturb(&self, rng: &mut impl Rng) -> (Vec<f64>, Vec<f64>, Vec<f64>) {
+        let c_k = self.c / (self.iteration as f64 + 1.0).powf(0.101);
+        let delta: Vec<f64> = self
+            .params
+            .iter()
+            .map(|_| if rng.gen::<bool>() { 1.0 } else { -1.0 })
+            .collect();
+        let shifted = |sign: f64| -> Vec<f64> {
+            let theta: Vec<f64> = self
+                .theta
+                .iter()
+                .zip(&delta)
+                .map(|(theta, delta)| theta + sign * c_k * delta)
+                .collect();
+            self.denormalize(&theta)
+        };
+        (shifted(1.0), shifted(-1.0), delta)
+    }
+
+    /// Moves the parameters towards the winner, given the score of the parameters shifted
+    /// up by `delta` against those shifted down.
+    pub fn update(&mut self, delta: &[f64], score_plus: f64) {
+        let k = self.iteration as f64 + 1.0;
+        let a_k = self.a / k.powf(0.602);
+        let c_k = self.c / k.powf(0.101);
+        // The estimated gradient is (y+ - y-) / (2 c_k delta), with y- = 1 - y+
+        let difference = 2.0 * score_plus - 1.0;
+        for (theta, delta) in self.theta.iter_mut().zip(delta) {
+            *theta = (*theta + a_k * difference / (2.0 * c_k) * delta).clamp(0.0, 1.0);
+        }
+        self.iteration += 1;
+    }
+
+    fn denormalize(&self, theta: &[f64]) -> Vec<f64> {
+        self.params
+            .iter()
+            .zip(theta)
+            .map(|(param, &x)| param.snap(param.min + x.clamp(0.0, 1.0) * (param.max - param.min)))
+            .collect()
+    }
+}
+
+fn normalize(param: &ParamSpec, value: f64) -> f64 {
+    if param.max == param.min {
+        0.0
+    } else {
+        (value - param.min) / (param.max - param.min)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    use super::*;
+
+    fn param(s: &str) -> ParamSpec {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn param_specs() {
+        let depth = param("depth=1:4:1");
+        assert_eq!(depth.values(), [1.0, 2.0, 3.0, 4.0]);
+        assert_eq!(depth.format(3.0), "3");
+        assert_eq!(depth.midpoint(), 3.0);
+        let aggression = param("aggression=0.1:0.5:0.2");
+        assert_eq!(aggression.values().len(), 3);
+        assert_eq!(aggression.format(aggression.snap(0.33)), "0.3");
+        assert!("depth=1:4".parse::<ParamSpec>().is_err());
+        assert!("depth=4:1:1".parse::<ParamSpec>().is_err());
+
+        let params = [depth, aggression];
+        assert_eq!(grid(&params).len(), 12);
+        let template =
+            CommandTemplate::new("bot --depth {depth} --a={aggression}", &params).unwrap();
+        assert_eq!(
+            template.instantiate(&params, &[2.0, 0.5]),
+            ["bot", "--depth", "2", "--a=0.5"]
+        );
+        assert!(CommandTemplate::new("bot --depth {depth}", &params).is_err());
+    }
+
+    #[test]
+    fn score_estimates() {
+        let score = MatchScore {
+            wins: vec![60, 20],
+            ties: 20,
+            ..Default::default()
+        };
+        let estimate = ScoreEstimate::from_match_score(&score);
+        assert_eq!(estimate.games, 100);
+        assert!((estimate.score - 0.7).abs() < 1e-9);
+        assert!(estimate.margin > 0.05 && estimate.margin < 0.1);
+        assert!(estimate.elo_difference() > 100.0);
+    }
+
+    /// With a noisy match result that prefers a certain parameter value, SPSA gets close to it.
+    #[test]
+    fn spsa_finds_the_optimum() {
+        let params = vec![param("x=0:100:1"), param("y=0:10:0.5")];
+        let optimum = [80.0, 2.0];
+        let mut spsa = Spsa::new(params.clone(), 0.1, 0.1);
+        let mut rng = StdRng::seed_from_u64(0);
+        // The bot closer to the optimum is more likely to win
+        let distance = |values: &[f64]| {
+            values
+                .iter()
+                .zip(&optimum)
+                .zip(&params)
+                .map(|((value, optimum), param)| {
+                    ((value - optimum) / (param.max - param.min)).abs()
+                })
+                .sum::<f64>()
+        };
+        for _ in 0..500 {
+            let (plus, minus, delta) = spsa.perturb(&mut rng);
+            let p_plus = 0.5 + (distance(&minus) - distance(&plus)).clamp(-0.5, 0.5);
+            let games = 20;
+            let points = (0..games).filter(|_| rng.gen_bool(p_plus)).count();
+            spsa.update(&delta, points as f64 / games as f64);
+        }
+        let values = spsa.values();
+        assert!((values[0] - optimum[0]).abs() <= 10.0, "{:?}", values);
+        assert!((values[1] - optimum[1]).abs() <= 1.0, "{:?}", values);
+    }
+}
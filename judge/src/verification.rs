@@ -0,0 +1,307 @@
+use std::collections::BTreeSet;
+use std::fmt::Debug;
+
+use anyhow::Context;
+use gomori::{
+    execute_first_turn_at, execute_turn, Card, CardsSet, IllegalMove, PlayTurnResponse,
+    PlayerState, Request, TurnOutcome,
+};
+
+use crate::recording::{GameSetup, RecordedIllegalMove, RecordedRequest, Recording};
+
+/// The first difference between a recorded game and the game recomputed from its setup and
+/// responses, see [`verify_recording()`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Divergence {
+    /// The turn in which the games diverged, or 0 for the requests for a new game.
+    pub turn_number: usize,
+    /// The index of the first request that differs, in the recording.
+    pub request_idx: usize,
+    pub description: String,
+}
+
+/// The result of [`verify_recording()`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Verification {
+    /// The number of turns that were recomputed, up to the divergence, if there is one.
+    pub num_turns: usize,
+    /// The number of cards that each player won in the recomputed game, without those of
+    /// the turn that ended it.
+    pub cards_won: Vec<u32>,
+    /// After a divergence, the games can't be compared anymore.
+    pub divergence: Option<Divergence>,
+}
+
+impl Verification {
+    pub fn is_ok(&self) -> bool {
+        self.divergence.is_none()
+    }
+}
+
+impl std::fmt::Display for Verification {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.divergence {
+            None => {
+                let cards_won: Vec<String> = self.cards_won.iter().map(|n| n.to_string()).collect();
+                write!(
+                    f,
+                    "OK, {} turns, cards won: {}",
+                    self.num_turns,
+                    cards_won.join(", ")
+                )
+            }
+            Some(divergence) => write!(
+                f,
+                "DIVERGED in turn {} (request {}): {}",
+                divergence.turn_number, divergence.request_idx, divergence.description
+            ),
+        }
+    }
+}
+
+/// Recomputes a game recorded with [`Recorder::with_setups()`](crate::Recorder::with_setups)
+/// from its setup and the recorded responses, using only the rules engine, and compares
+/// every recorded request with the one that the recomputed game leads to.
+///
+/// A divergence means that the judge or the rules engine behave differently than when the
+/// game was recorded, e.g. because a new version changed them or because of nondeterminism.
+/// A game that ended with an illegal move must end with the same illegal move in the
+/// recomputed game.
+///
+/// Returns an error if the recording has no setup.
+pub fn verify_recording(recording: &Recording) -> anyhow::Result<Verification> {
+    let setup = recording
+        .setup
+        .as_ref()
+        .context("The recording has no game setup, it must be made with --verifiable-recordings")?;
+    let mut replay = Replay::new(setup);
+    let divergence = replay.run(&recording.requests).err();
+    Ok(Verification {
+        num_turns: replay.turn_number,
+        cards_won: replay
+            .states
+            .iter()
+            .map(|state| state.cards_won.len())
+            .collect(),
+        divergence,
+    })
+}
+
+// The recomputed game, which is advanced by the recorded requests
+struct Replay<'a> {
+    setup: &'a GameSetup,
+    states: Vec<PlayerState>,
+    // The nicks of the players, in the order of the requests for a new game
+    names: Vec<&'a str>,
+    turn_number: usize,
+    request_idx: usize,
+}
+
+impl<'a> Replay<'a> {
+    fn new(setup: &'a GameSetup) -> Self {
+        Self {
+            setup,
+            states: setup
+                .draw_piles
+                .iter()
+                .cloned()
+                .map(PlayerState::with_draw_pile)
+                .collect(),
+            names: Vec::new(),
+            turn_number: 0,
+            request_idx: 0,
+        }
+    }
+
+    fn diverge(&self, description: String) -> Divergence {
+        Divergence {
+            turn_number: self.turn_number,
+            request_idx: self.request_idx,
+            description,
+        }
+    }
+
+    fn check<T: PartialEq + Debug>(
+        &self,
+        what: &str,
+        recorded: T,
+        recomputed: T,
+    ) -> Result<(), Divergence> {
+        if recorded == recomputed {
+            return Ok(());
+        }
+        Err(self.diverge(format!(
+            "The {} is {:?} in the recording, but {:?} in the recomputed game",
+            what, recorded, recomputed
+        )))
+    }
+
+    // The next request, which must be to the given player
+    fn next_request(
+        &mut self,
+        requests: &mut impl Iterator<Item = (usize, &'a RecordedRequest)>,
+        player_idx: usize,
+    ) -> Result<&'a RecordedRequest, Divergence> {
+        let Some((request_idx, request)) = requests.next() else {
+            return Err(self.diverge(String::from(
+                "The recording ends before the recomputed game",
+            )));
+        };
+        self.request_idx = request_idx;
+        if let Some(&name) = self.names.get(player_idx) {
+            self.check("player", request.player.as_str(), name)?;
+        }
+        Ok(request)
+    }
+
+    fn response<T: serde::de::DeserializeOwned>(
+        &self,
+        request: &RecordedRequest,
+    ) -> Result<T, Divergence> {
+        serde_json::from_value(request.response.clone())
+            .map_err(|err| self.diverge(format!("The recorded response is invalid: {}", err)))
+    }
+
+    // The result of executing a recorded move, or `None` if it is illegal and was recorded as
+    // such, which ends the game
+    fn outcome<T>(
+        &self,
+        request: &RecordedRequest,
+        result: Result<T, IllegalMove>,
+    ) -> Result<Option<T>, Divergence> {
+        match (result, &request.illegal_move) {
+            (Ok(result), None) => Ok(Some(result)),
+            (Ok(_), Some(recorded)) => Err(self.diverge(format!(
+                "The recorded move is legal in the recomputed game, but was illegal: {}",
+                recorded.error
+            ))),
+            (Err(err), None) => Err(self.diverge(format!("The recorded move is illegal: {}", err))),
+            (Err(err), Some(recorded)) => {
+                self.check("illegal move", recorded, &RecordedIllegalMove::from(&err))?;
+                Ok(None)
+            }
+        }
+    }
+
+    fn run(&mut self, requests: &'a [RecordedRequest]) -> Result<(), Divergence> {
+        let setup = self.setup;
+        let num_players = setup.draw_piles.len();
+        let mut requests = requests
+            .iter()
+            .enumerate()
+            .filter(|(_, req)| !matches!(req.request, Request::Bye { .. }));
+
+        for player_idx in 0..num_players {
+            let request = self.next_request(&mut requests, player_idx)?;
+            let Request::NewGame {
+                num_players: recorded_num_players,
+                seat,
+                rules,
+                ..
+            } = request.request
+            else {
+                return Err(self.diverge(String::from("Expected a request for a new game")));
+            };
+            self.names.push(&request.player);
+            self.check("number of players", recorded_num_players, num_players)?;
+            let expected_seat = (player_idx + num_players - setup.starting_player) % num_players;
+            self.check("seat", seat, expected_seat)?;
+            self.check("rules", rules, setup.rules)?;
+        }
+
+        self.turn_number = 1;
+        let mut current_player_idx = setup.starting_player;
+        let request = self.next_request(&mut requests, current_player_idx)?;
+        let Request::PlayFirstTurn { cards, i, j } = request.request else {
+            return Err(self.diverge(String::from("Expected a request to play the first turn")));
+        };
+        self.check("hand", cards, self.states[current_player_idx].hand)?;
+        self.check("first card location", (i, j), setup.first_card_location)?;
+        let card: Card = self.response(request)?;
+        let result =
+            execute_first_turn_at(&self.states[current_player_idx], card, (i, j), setup.rules);
+        let Some(result) = self.outcome(request, result)? else {
+            return self.expect_end(requests);
+        };
+        self.states[current_player_idx] = result.state;
+        let mut board = result.board;
+
+        let mut turns_skipped_in_a_row = 0;
+        let mut cards_won_by_opponents = vec![CardsSet::new(); num_players];
+        while self.turn_number < setup.max_turns {
+            self.turn_number += 1;
+            current_player_idx = (current_player_idx + 1) % num_players;
+            let request = self.next_request(&mut requests, current_player_idx)?;
+            let Request::PlayTurn {
+                cards,
+                ref fields,
+                ref cards_won_by_opponent,
+                draw_pile_size,
+                opponent_draw_pile_size,
+                turn_number,
+            } = request.request
+            else {
+                return Err(self.diverge(String::from("Expected a request to play a turn")));
+            };
+            let state = &self.states[current_player_idx];
+            self.check("hand", cards, state.hand)?;
+            self.check("board", fields, &board.to_fields_vec())?;
+            let won_by_opponents = std::mem::take(&mut cards_won_by_opponents[current_player_idx]);
+            self.check(
+                "set of cards won by opponents",
+                cards_won_by_opponent,
+                &BTreeSet::from_iter(won_by_opponents),
+            )?;
+            self.check("draw pile size", draw_pile_size, state.draw_pile.len())?;
+            let recomputed_opponent_draw_pile_size = (0..num_players)
+                .filter(|&idx| idx != current_player_idx)
+                .map(|idx| self.states[idx].draw_pile.len())
+                .sum();
+            self.check(
+                "opponents' draw pile size",
+                opponent_draw_pile_size,
+                recomputed_opponent_draw_pile_size,
+            )?;
+            self.check("turn number", turn_number, self.turn_number)?;
+
+            let action: PlayTurnResponse = self.response(request)?;
+            let Some(result) = self.outcome(request, execute_turn(state, &board, action))? else {
+                break;
+            };
+            self.states[current_player_idx] = result.state;
+            board = result.board;
+            match result.outcome {
+                TurnOutcome::Normal => {
+                    turns_skipped_in_a_row = 0;
+                    for (player_idx, cards_won) in cards_won_by_opponents.iter_mut().enumerate() {
+                        if player_idx != current_player_idx {
+                            *cards_won |= result.cards_won_this_turn;
+                        }
+                    }
+                }
+                TurnOutcome::GameEnded => break,
+                TurnOutcome::Skipped => {
+                    turns_skipped_in_a_row += 1;
+                    if turns_skipped_in_a_row == num_players {
+                        break;
+                    }
+                }
+            }
+        }
+
+        self.expect_end(requests)
+    }
+
+    fn expect_end(
+        &mut self,
+        mut requests: impl Iterator<Item = (usize, &'a RecordedRequest)>,
+    ) -> Result<(), Divergence> {
+        if let Some((request_idx, _)) = requests.next() {
+            self.request_idx = request_idx;
+            return Err(self.diverge(String::from(
+                "The recording continues after the recomputed game ended",
+            )));
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,153 @@
+use std::path::PathBuf;
+use std::time::Instant;
+
+use clap::{Parser, Subcommand};
+use gomori::Protocol;
+use gomori_bot_utils::{Bot, InProcessBot};
+use judge::{play_matchup, Connection, MatchConfig, Player, TrainingDataWriter};
+use mcts_bot::{IsmctsConfig, MctsBot};
+use nn_bot::{NnBot, QModel};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+/// Plays games of a bot against itself, in this process, and writes every card played as
+/// training data for reinforcement learning.
+///
+/// The samples are written as the games end, in the same format as by the judge's
+/// `export_training_data` tool: NumPy arrays in `boards.npy`, `cards_to_play.npy` and
+/// `outcomes.npy`. Both players are instances of the same bot with the same options, and
+/// take turns starting the games.
+#[derive(Parser)]
+struct Args {
+    #[command(subcommand)]
+    bot: BotArgs,
+
+    /// How many games to play
+    #[arg(short, long, default_value_t = 1000)]
+    num_games: usize,
+
+    /// The directory for the NumPy files, which is created if it doesn't exist
+    #[arg(short, long, default_value = ".")]
+    output_dir: PathBuf,
+
+    /// RNG seed, for the deals and the bots
+    #[arg(long)]
+    seed: Option<u64>,
+}
+
+#[derive(Subcommand)]
+enum BotArgs {
+    /// The bot of the `nn_bot` crate
+    Nn {
+        /// The ONNX model that rates the cards, see the docs of `QModel`
+        #[arg(short, long)]
+        model: PathBuf,
+
+        /// Zero to always play the best card, higher values sample more of the other cards
+        #[arg(short, long, default_value_t = 0.0)]
+        temperature: f32,
+    },
+    /// The bot of the `mcts_bot` crate
+    Mcts {
+        /// How many games to simulate per turn
+        #[arg(short, long, default_value_t = IsmctsConfig::default().iterations)]
+        iterations: usize,
+
+        /// The exploration constant, higher values try out more moves
+        #[arg(short, long, default_value_t = IsmctsConfig::default().exploration)]
+        exploration: f64,
+    },
+}
+
+/// Lets the judge talk to a bot in this process, instead of to a bot process.
+struct InProcessConnection<B> {
+    bot: InProcessBot<B>,
+}
+
+impl<B: Bot> Connection for InProcessConnection<B> {
+    fn exchange(&mut self, request: &str) -> anyhow::Result<String> {
+        self.bot.handle_json(request)
+    }
+
+    // Cheaper to encode and decode than JSON
+    fn protocol(&self) -> Protocol {
+        Protocol::MsgPack
+    }
+
+    fn exchange_binary(&mut self, request: &[u8]) -> anyhow::Result<Vec<u8>> {
+        self.bot.handle_msgpack(request)
+    }
+}
+
+fn player<B: Bot + 'static>(name: String, bot: B) -> Player {
+    Player::with_connection(
+        name,
+        Box::new(InProcessConnection {
+            bot: InProcessBot::new(bot),
+        }),
+    )
+}
+
+fn players(bot_args: &BotArgs, rng: &mut StdRng) -> anyhow::Result<Vec<Player>> {
+    (1..=2)
+        .map(|idx| {
+            let seed = rng.gen();
+            Ok(match bot_args {
+                BotArgs::Nn { model, temperature } => player(
+                    format!("nn {}", idx),
+                    NnBot::new(QModel::load(model)?, *temperature, seed),
+                ),
+                BotArgs::Mcts {
+                    iterations,
+                    exploration,
+                } => {
+                    let config = IsmctsConfig {
+                        iterations: *iterations,
+                        exploration: *exploration,
+                    };
+                    player(format!("mcts {}", idx), MctsBot::new(config, seed))
+                }
+            })
+        })
+        .collect()
+}
+
+fn main() -> anyhow::Result<()> {
+    let args = Args::parse();
+    let seed = args.seed.unwrap_or_else(rand::random);
+    eprintln!("Seed: {}", seed);
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut players = players(&args.bot, &mut rng)?;
+
+    std::fs::create_dir_all(&args.output_dir)?;
+    let mut writer = TrainingDataWriter::create(&args.output_dir)?;
+    let config = MatchConfig {
+        num_games: args.num_games,
+        alternate_start: true,
+        ..Default::default()
+    };
+    let start = Instant::now();
+    let score = play_matchup(&mut rng, &mut players, &config, &mut None, &mut |event| {
+        writer.record(&event)
+    })?;
+    let elapsed = start.elapsed();
+    let num_samples = writer.num_samples();
+    writer.finish()?;
+
+    eprintln!(
+        "Played {} games in {:.1} s ({:.1} games/s): {} and {} wins, {} ties, {} illegal moves",
+        args.num_games,
+        elapsed.as_secs_f64(),
+        args.num_games as f64 / elapsed.as_secs_f64(),
+        score.wins[0],
+        score.wins[1],
+        score.ties,
+        score.illegal_moves.iter().sum::<usize>()
+    );
+    eprintln!(
+        "Wrote {} samples to '{}'",
+        num_samples,
+        args.output_dir.display()
+    );
+    Ok(())
+}